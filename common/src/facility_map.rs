@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::types::CameraExtrinsics;
+use crate::world_model::SemanticCell;
+
+// Canonical description of a facility's floor, shared by the simulator, the
+// perception node's zone tagging and the operator platform. Everything is in
+// facility meters on the floor plane, origin at a corner of the floor.
+
+pub const FACILITY_MAP_VERSION: u32 = 1;
+
+// Cells of this type are off limits to people and robots
+pub const FORBIDDEN_CELL_TYPE: &str = "forbidden_zone";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FacilityMap {
+    pub version: u32,
+    pub facility_id: String,
+    pub width: f64, // along x
+    pub height: f64, // along y
+    pub cells: Vec<SemanticCell>,
+    pub zones: Vec<MapZone>,
+    pub cameras: Vec<CameraPlacement>,
+}
+
+// A named group of cells, e.g. a loading dock made of several cells
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MapZone {
+    pub zone_id: String,
+    pub risk_level: u8,
+    pub cell_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CameraPlacement {
+    pub camera_id: String, // the camera's device id
+    pub extrinsics: CameraExtrinsics,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FacilityMapError {
+    Parse(String),
+    UnsupportedVersion(u32),
+    InvalidBounds,
+    InvalidCell(String),
+    CellOutOfBounds(String),
+    OverlappingCells(String, String),
+    DuplicateId(String),
+    UnknownCell { zone_id: String, cell_id: String },
+}
+
+impl fmt::Display for FacilityMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FacilityMapError::Parse(e) => write!(f, "Facility map is not valid JSON: {}", e),
+            FacilityMapError::UnsupportedVersion(v) => write!(f, "Facility map version {} is not supported", v),
+            FacilityMapError::InvalidBounds => write!(f, "Facility map width and height must be positive"),
+            FacilityMapError::InvalidCell(id) => write!(f, "Cell {} must have a positive, finite size", id),
+            FacilityMapError::CellOutOfBounds(id) => write!(f, "Cell {} extends outside the facility", id),
+            FacilityMapError::OverlappingCells(a, b) => write!(f, "Cells {} and {} overlap", a, b),
+            FacilityMapError::DuplicateId(id) => write!(f, "{} is used by more than one cell, zone or camera", id),
+            FacilityMapError::UnknownCell { zone_id, cell_id } => write!(f, "Zone {} refers to unknown cell {}", zone_id, cell_id),
+        }
+    }
+}
+
+impl std::error::Error for FacilityMapError {}
+
+impl FacilityMap {
+    // Parses and validates
+    pub fn from_json(json: &str) -> Result<Self, FacilityMapError> {
+        let map: FacilityMap = serde_json::from_str(json).map_err(|e| FacilityMapError::Parse(e.to_string()))?;
+        map.validate()?;
+        Ok(map)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("facility map serializes")
+    }
+
+    // Cells may share edges but not overlap, so every point of the floor is
+    // in at most one cell; zones are how areas are grouped instead
+    pub fn validate(&self) -> Result<(), FacilityMapError> {
+        if self.version != FACILITY_MAP_VERSION {
+            return Err(FacilityMapError::UnsupportedVersion(self.version));
+        }
+        if !(self.width > 0.0 && self.width.is_finite() && self.height > 0.0 && self.height.is_finite()) {
+            return Err(FacilityMapError::InvalidBounds);
+        }
+
+        let mut cell_ids = HashSet::new();
+        for (i, cell) in self.cells.iter().enumerate() {
+            if !cell_ids.insert(cell.cell_id.as_str()) {
+                return Err(FacilityMapError::DuplicateId(cell.cell_id.clone()));
+            }
+            let (x, y, width, height) = (cell.position.x, cell.position.y, cell.size.width, cell.size.height);
+            if !(width > 0.0 && height > 0.0 && [x, y, width, height].iter().all(|v| v.is_finite())) {
+                return Err(FacilityMapError::InvalidCell(cell.cell_id.clone()));
+            }
+            if x < 0.0 || y < 0.0 || x + width > self.width || y + height > self.height {
+                return Err(FacilityMapError::CellOutOfBounds(cell.cell_id.clone()));
+            }
+            if let Some(other) = self.cells[..i].iter().find(|other| overlaps(cell, other)) {
+                return Err(FacilityMapError::OverlappingCells(other.cell_id.clone(), cell.cell_id.clone()));
+            }
+        }
+
+        let mut zone_ids = HashSet::new();
+        for zone in &self.zones {
+            if !zone_ids.insert(zone.zone_id.as_str()) {
+                return Err(FacilityMapError::DuplicateId(zone.zone_id.clone()));
+            }
+            if let Some(cell_id) = zone.cell_ids.iter().find(|id| !cell_ids.contains(id.as_str())) {
+                return Err(FacilityMapError::UnknownCell { zone_id: zone.zone_id.clone(), cell_id: cell_id.clone() });
+            }
+        }
+
+        let mut camera_ids = HashSet::new();
+        if let Some(camera) = self.cameras.iter().find(|camera| !camera_ids.insert(camera.camera_id.as_str())) {
+            return Err(FacilityMapError::DuplicateId(camera.camera_id.clone()));
+        }
+
+        Ok(())
+    }
+
+    pub fn forbidden_cells(&self) -> impl Iterator<Item = &SemanticCell> {
+        self.cells.iter().filter(|cell| cell.r#type == FORBIDDEN_CELL_TYPE)
+    }
+
+    pub fn camera(&self, camera_id: &str) -> Option<&CameraPlacement> {
+        self.cameras.iter().find(|camera| camera.camera_id == camera_id)
+    }
+}
+
+// Touching edges don't count
+fn overlaps(a: &SemanticCell, b: &SemanticCell) -> bool {
+    a.position.x < b.position.x + b.size.width
+        && b.position.x < a.position.x + a.size.width
+        && a.position.y < b.position.y + b.size.height
+        && b.position.y < a.position.y + a.size.height
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world_model::{Position, Size};
+
+    fn cell(cell_id: &str, x: f64, y: f64, size: f64, r#type: &str) -> SemanticCell {
+        SemanticCell {
+            cell_id: cell_id.to_string(),
+            position: Position { x, y, z: 0.0 },
+            size: Size { width: size, height: size },
+            r#type: r#type.to_string(),
+            risk_level: if r#type == FORBIDDEN_CELL_TYPE { 3 } else { 1 },
+        }
+    }
+
+    fn map() -> FacilityMap {
+        FacilityMap {
+            version: FACILITY_MAP_VERSION,
+            facility_id: "MAIN_WAREHOUSE".to_string(),
+            width: 20.0,
+            height: 10.0,
+            cells: vec![
+                cell("CELL-0-0", 0.0, 0.0, 5.0, "pathway"),
+                cell("CELL-1-0", 5.0, 0.0, 5.0, "workstation"),
+                cell("CELL-2-0", 10.0, 0.0, 5.0, FORBIDDEN_CELL_TYPE),
+            ],
+            zones: vec![MapZone { zone_id: "dock".to_string(), risk_level: 2, cell_ids: vec!["CELL-0-0".to_string(), "CELL-1-0".to_string()] }],
+            cameras: vec![CameraPlacement {
+                camera_id: "CAM-00-00".to_string(),
+                extrinsics: CameraExtrinsics { rotation: [0.0, -0.5, 0.0], translation: [10.0, 10.0, 5.0] },
+            }],
+        }
+    }
+
+    #[test]
+    fn test_round_trips_through_json() {
+        let map = map();
+
+        let imported = FacilityMap::from_json(&map.to_json()).unwrap();
+
+        assert_eq!(imported, map);
+        assert_eq!(imported.forbidden_cells().map(|c| c.cell_id.as_str()).collect::<Vec<_>>(), vec!["CELL-2-0"]);
+        assert_eq!(imported.camera("CAM-00-00").unwrap().extrinsics.translation, [10.0, 10.0, 5.0]);
+    }
+
+    #[test]
+    fn test_rejects_overlapping_and_invalid_cells() {
+        assert_eq!(map().validate(), Ok(()));
+
+        let mut overlapping = map();
+        overlapping.cells.push(cell("CELL-X", 7.5, 2.5, 5.0, "pathway"));
+        assert_eq!(
+            overlapping.validate(),
+            Err(FacilityMapError::OverlappingCells("CELL-1-0".to_string(), "CELL-X".to_string()))
+        );
+
+        let mut empty = map();
+        empty.cells[1].size.width = 0.0;
+        assert_eq!(empty.validate(), Err(FacilityMapError::InvalidCell("CELL-1-0".to_string())));
+
+        let mut outside = map();
+        outside.cells.push(cell("CELL-3-1", 15.0, 7.5, 5.0, "pathway"));
+        assert_eq!(outside.validate(), Err(FacilityMapError::CellOutOfBounds("CELL-3-1".to_string())));
+
+        let mut duplicate = map();
+        duplicate.cells.push(cell("CELL-0-0", 15.0, 0.0, 5.0, "pathway"));
+        assert_eq!(duplicate.validate(), Err(FacilityMapError::DuplicateId("CELL-0-0".to_string())));
+
+        let mut dangling = map();
+        dangling.zones[0].cell_ids.push("CELL-9-9".to_string());
+        assert!(matches!(dangling.validate(), Err(FacilityMapError::UnknownCell { .. })));
+
+        assert!(matches!(FacilityMap::from_json("{\"version\": 1}"), Err(FacilityMapError::Parse(_))));
+    }
+}
@@ -1,2 +1,6 @@
+pub mod facility_map;
+pub mod replay;
+pub mod secrets;
 pub mod types;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod world_model;
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+
+use crate::types::PerceptionFrame;
+
+// Bumped whenever the header or frame layout changes
+pub const REPLAY_SCHEMA_VERSION: u32 = 2;
+const MAGIC: &[u8; 4] = b"AFRP";
+// Guards against reading a corrupt length as a huge allocation
+const MAX_RECORD_BYTES: u32 = 64 * 1024 * 1024;
+
+// Replay files are the magic bytes, then length-delimited bincode records:
+// one header, then one record per PerceptionFrame. Each length is a
+// little-endian u32.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReplayHeader {
+    pub schema_version: u32,
+    pub camera_id: String,
+    pub from_ms: u64,
+    pub to_ms: u64,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_record<W: Write, T: Serialize>(writer: &mut W, value: &T) -> io::Result<()> {
+    let bytes = bincode::serialize(value).map_err(|e| invalid(e.to_string()))?;
+    let len = u32::try_from(bytes.len()).map_err(|_| invalid("record too large".to_string()))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(&bytes)
+}
+
+// Encodes the header as the first bytes of a replay file
+pub fn encode_header(header: &ReplayHeader) -> io::Result<Vec<u8>> {
+    let mut bytes = MAGIC.to_vec();
+    write_record(&mut bytes, header)?;
+    Ok(bytes)
+}
+
+// Encodes one frame record, for streaming frames after the header
+pub fn encode_frame(frame: &PerceptionFrame) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    write_record(&mut bytes, frame)?;
+    Ok(bytes)
+}
+
+pub struct ReplayReader<R> {
+    reader: R,
+    header: ReplayHeader,
+}
+
+impl<R: Read> ReplayReader<R> {
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a replay file".to_string()));
+        }
+
+        let header: ReplayHeader = read_record(&mut reader)?.ok_or_else(|| invalid("missing replay header".to_string()))?;
+        if header.schema_version != REPLAY_SCHEMA_VERSION {
+            return Err(invalid(format!(
+                "replay schema version {} is not supported (expected {})",
+                header.schema_version, REPLAY_SCHEMA_VERSION
+            )));
+        }
+
+        Ok(Self { reader, header })
+    }
+
+    pub fn header(&self) -> &ReplayHeader {
+        &self.header
+    }
+}
+
+impl<R: Read> Iterator for ReplayReader<R> {
+    type Item = io::Result<PerceptionFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        read_record(&mut self.reader).transpose()
+    }
+}
+
+// None at a clean end of input
+fn read_record<R: Read, T: for<'de> Deserialize<'de>>(reader: &mut R) -> io::Result<Option<T>> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let len = u32::from_le_bytes(len);
+    if len > MAX_RECORD_BYTES {
+        return Err(invalid(format!("record of {} bytes exceeds the limit", len)));
+    }
+    let mut bytes = vec![0u8; len as usize];
+    reader.read_exact(&mut bytes)?;
+    bincode::deserialize(&bytes).map(Some).map_err(|e| invalid(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BBox, CameraIntrinsics, CoordinateSpace, Detection};
+
+    fn frame(frame_id: u64) -> PerceptionFrame {
+        PerceptionFrame {
+            frame_id,
+            timestamp: 1_700_000_000_000 + frame_id * 33,
+            source_camera_id: "cam-1".to_string(),
+            image_width: 640,
+            image_height: 480,
+            model_version: "yolov5s-1.0".to_string(),
+            inference_time_ms: 12.5,
+            detections: (0..frame_id % 3)
+                .map(|i| Detection {
+                    bbox: BBox::new(10.0 * i as f32, 20.0, 50.0 + i as f32, 90.0),
+                    confidence: 0.5 + 0.1 * i as f32,
+                    class_id: i as u32,
+                    class_label: format!("class_{}", i),
+                    tracker_id: Some(frame_id * 10 + i),
+                    oriented: None,
+                })
+                .collect(),
+            camera_intrinsics: Some(CameraIntrinsics { fx: 600.0, fy: 600.0, cx: 320.0, cy: 240.0, distortion: [0.0; 5] }),
+            camera_extrinsics: None,
+            coordinate_space: CoordinateSpace::Pixels,
+        }
+    }
+
+    #[test]
+    fn test_exported_frames_read_back_identical() {
+        let header = ReplayHeader {
+            schema_version: REPLAY_SCHEMA_VERSION,
+            camera_id: "cam-1".to_string(),
+            from_ms: 1_700_000_000_000,
+            to_ms: 1_700_000_001_000,
+        };
+        let frames: Vec<PerceptionFrame> = (0..5).map(frame).collect();
+
+        let mut file = encode_header(&header).unwrap();
+        for frame in &frames {
+            file.extend(encode_frame(frame).unwrap());
+        }
+
+        let reader = ReplayReader::new(file.as_slice()).unwrap();
+        assert_eq!(reader.header(), &header);
+        let read: Vec<PerceptionFrame> = reader.collect::<io::Result<_>>().unwrap();
+        assert_eq!(read, frames);
+    }
+
+    #[test]
+    fn test_rejects_other_schema_versions() {
+        let header = ReplayHeader {
+            schema_version: REPLAY_SCHEMA_VERSION + 1,
+            camera_id: "cam-1".to_string(),
+            from_ms: 0,
+            to_ms: 0,
+        };
+        let file = encode_header(&header).unwrap();
+
+        let error = ReplayReader::new(file.as_slice()).err().unwrap();
+        assert!(error.to_string().contains("schema version"), "{}", error);
+    }
+}
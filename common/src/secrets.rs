@@ -0,0 +1,71 @@
+use std::path::{Component, Path};
+
+// Directory Vault Agent renders secrets into, one file per secret
+pub const DEFAULT_VAULT_DIR: &str = "/vault/secrets";
+pub const VAULT_DIR_ENV: &str = "AETHERFORGE_VAULT_DIR";
+
+/// Resolves a config value that may reference a secret held elsewhere:
+///
+/// - `env:NAME` reads environment variable `NAME`
+/// - `file:/path` reads the file, trimming a trailing newline
+/// - `vault:name` reads `name` from the Vault Agent secrets directory
+///   (`$AETHERFORGE_VAULT_DIR`, default `/vault/secrets`)
+///
+/// Anything else is returned as-is, so plaintext values keep working in dev.
+pub fn resolve_secret(value: &str) -> Result<String, String> {
+    if let Some(name) = value.strip_prefix("env:") {
+        std::env::var(name).map_err(|_| format!("secret env:{} is not set", name))
+    } else if let Some(path) = value.strip_prefix("file:") {
+        read_secret_file(Path::new(path)).map_err(|e| format!("secret file:{} could not be read: {}", path, e))
+    } else if let Some(name) = value.strip_prefix("vault:") {
+        // A plain relative name, so joining it can't leave the directory
+        let valid = !name.is_empty() && Path::new(name).components().all(|c| matches!(c, Component::Normal(_)));
+        if !valid {
+            return Err(format!("secret vault:{} is not a valid secret name", name));
+        }
+        let dir = std::env::var(VAULT_DIR_ENV).unwrap_or_else(|_| DEFAULT_VAULT_DIR.to_string());
+        read_secret_file(&Path::new(&dir).join(name)).map_err(|e| format!("secret vault:{} could not be read: {}", name, e))
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// Replaces a secret reference with the secret it points to
+pub fn resolve_secret_in_place(value: &mut String) -> Result<(), String> {
+    *value = resolve_secret(value)?;
+    Ok(())
+}
+
+fn read_secret_file(path: &Path) -> std::io::Result<String> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(contents.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolves_env_and_file_references() {
+        std::env::set_var("AETHERFORGE_TEST_SECRET", "s3cret");
+        assert_eq!(resolve_secret("env:AETHERFORGE_TEST_SECRET").unwrap(), "s3cret");
+
+        let path = std::env::temp_dir().join(format!("aetherforge-secret-{}", std::process::id()));
+        std::fs::write(&path, "from-file\n").unwrap();
+        assert_eq!(resolve_secret(&format!("file:{}", path.display())).unwrap(), "from-file");
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(resolve_secret("plain-dev-password").unwrap(), "plain-dev-password");
+    }
+
+    #[test]
+    fn test_missing_secret_is_an_error() {
+        let err = resolve_secret("env:AETHERFORGE_TEST_SECRET_MISSING").unwrap_err();
+        assert!(err.contains("AETHERFORGE_TEST_SECRET_MISSING"));
+
+        assert!(resolve_secret("file:/nonexistent/aetherforge/secret").is_err());
+        assert!(resolve_secret("vault:../etc/passwd").is_err());
+        assert!(resolve_secret("vault:/etc/shadow").unwrap_err().contains("not a valid secret name"));
+        assert!(resolve_secret("vault:./db").is_err());
+    }
+}
\ No newline at end of file
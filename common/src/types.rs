@@ -134,7 +134,7 @@ use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct BBox {
     pub xmin: f32,
     pub ymin: f32,
@@ -158,18 +158,177 @@ impl BBox {
     pub fn area(&self) -> f32 {
         self.width() * self.height()
     }
+    
+    pub fn intersection_over_union(&self, other: &BBox) -> f32 {
+        let ix = (self.xmax.min(other.xmax) - self.xmin.max(other.xmin)).max(0.0);
+        let iy = (self.ymax.min(other.ymax) - self.ymin.max(other.ymin)).max(0.0);
+        let intersection = ix * iy;
+        let union = self.area() + other.area() - intersection;
+        
+        if union <= 0.0 {
+            0.0
+        } else {
+            intersection / union
+        }
+    }
+    
+    // Limited to [0, width]×[0, height]. A box entirely outside ends up
+    // with zero width or height.
+    pub fn clamp(&self, width: f32, height: f32) -> BBox {
+        BBox::new(
+            self.xmin.clamp(0.0, width),
+            self.ymin.clamp(0.0, height),
+            self.xmax.clamp(0.0, width),
+            self.ymax.clamp(0.0, height),
+        )
+    }
+    
+    // Pixel coordinates to 0..1 fractions of the image size
+    pub fn to_normalized(&self, image_width: u32, image_height: u32) -> BBox {
+        let w = image_width.max(1) as f32;
+        let h = image_height.max(1) as f32;
+        BBox::new(self.xmin / w, self.ymin / h, self.xmax / w, self.ymax / h)
+    }
+    
+    // 0..1 fractions of the image size to pixel coordinates
+    pub fn to_pixels(&self, image_width: u32, image_height: u32) -> BBox {
+        let w = image_width as f32;
+        let h = image_height as f32;
+        BBox::new(self.xmin * w, self.ymin * h, self.xmax * w, self.ymax * h)
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// A box rotated `angle` radians about its center. Positive angles turn the
+// width axis from +x towards +y, which is clockwise on screen. Oriented
+// boxes are always in pixels: a rotated rectangle doesn't stay a rectangle
+// under the per-axis scaling of `CoordinateSpace::Normalized`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct OrientedBBox {
+    pub cx: f32,
+    pub cy: f32,
+    pub width: f32,
+    pub height: f32,
+    pub angle: f32,
+}
+
+impl OrientedBBox {
+    pub fn new(cx: f32, cy: f32, width: f32, height: f32, angle: f32) -> Self {
+        Self { cx, cy, width, height, angle }
+    }
+    
+    pub fn area(&self) -> f32 {
+        self.width.max(0.0) * self.height.max(0.0)
+    }
+    
+    // Counter-clockwise in x/y terms, starting from the (-w/2, -h/2) corner
+    pub fn corners(&self) -> [(f32, f32); 4] {
+        let (sin, cos) = self.angle.sin_cos();
+        let (hw, hh) = (self.width / 2.0, self.height / 2.0);
+        [(-hw, -hh), (hw, -hh), (hw, hh), (-hw, hh)]
+            .map(|(x, y)| (self.cx + x * cos - y * sin, self.cy + x * sin + y * cos))
+    }
+    
+    // Smallest axis-aligned box containing this one
+    pub fn envelope(&self) -> BBox {
+        let corners = self.corners();
+        let fold = |f: fn(f32, f32) -> f32, pick: fn(&(f32, f32)) -> f32, init: f32| {
+            corners.iter().map(pick).fold(init, f)
+        };
+        BBox::new(
+            fold(f32::min, |c| c.0, f32::INFINITY),
+            fold(f32::min, |c| c.1, f32::INFINITY),
+            fold(f32::max, |c| c.0, f32::NEG_INFINITY),
+            fold(f32::max, |c| c.1, f32::NEG_INFINITY),
+        )
+    }
+    
+    pub fn intersection_over_union(&self, other: &OrientedBBox) -> f32 {
+        if self.area() <= 0.0 || other.area() <= 0.0 {
+            return 0.0;
+        }
+        
+        let intersection = polygon_area(&clip_convex(&self.corners(), &other.corners()));
+        let union = self.area() + other.area() - intersection;
+        
+        if union <= 0.0 {
+            0.0
+        } else {
+            intersection / union
+        }
+    }
+}
+
+// Sutherland-Hodgman: the part of `subject` inside the convex,
+// counter-clockwise polygon `clip`
+fn clip_convex(subject: &[(f32, f32)], clip: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let cross = |a: (f32, f32), b: (f32, f32), p: (f32, f32)| (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+    let mut output = subject.to_vec();
+    
+    for i in 0..clip.len() {
+        let (a, b) = (clip[i], clip[(i + 1) % clip.len()]);
+        let input = std::mem::take(&mut output);
+        
+        for j in 0..input.len() {
+            let (p, q) = (input[j], input[(j + 1) % input.len()]);
+            let (dp, dq) = (cross(a, b, p), cross(a, b, q));
+            if dp >= 0.0 {
+                output.push(p);
+            }
+            if (dp >= 0.0) != (dq >= 0.0) {
+                let t = dp / (dp - dq);
+                output.push((p.0 + t * (q.0 - p.0), p.1 + t * (q.1 - p.1)));
+            }
+        }
+        
+        if output.is_empty() {
+            break;
+        }
+    }
+    
+    output
+}
+
+// Shoelace formula
+fn polygon_area(points: &[(f32, f32)]) -> f32 {
+    let twice: f32 = (0..points.len())
+        .map(|i| {
+            let (p, q) = (points[i], points[(i + 1) % points.len()]);
+            p.0 * q.1 - q.0 * p.1
+        })
+        .sum();
+    twice.abs() / 2.0
+}
+
+// Units of every `BBox` in a `PerceptionFrame`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoordinateSpace {
+    #[default]
+    Pixels,
+    Normalized,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Detection {
-    pub bbox: BBox,
+    pub bbox: BBox, // for oriented detections, the oriented box's envelope
     pub confidence: f32,
     pub class_id: u32,
     pub class_label: String,
     pub tracker_id: Option<u64>,
+    #[serde(default)]
+    pub oriented: Option<OrientedBBox>, // from models with an angle output, in pixels
+}
+
+impl Detection {
+    // Oriented IoU when both boxes have one, otherwise axis-aligned
+    pub fn intersection_over_union(&self, other: &Detection) -> f32 {
+        match (&self.oriented, &other.oriented) {
+            (Some(a), Some(b)) => a.intersection_over_union(b),
+            _ => self.bbox.intersection_over_union(&other.bbox),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct PerceptionFrame {
     pub frame_id: u64,
     pub timestamp: u64,
@@ -181,9 +340,90 @@ pub struct PerceptionFrame {
     pub detections: Vec<Detection>,
     pub camera_intrinsics: Option<CameraIntrinsics>,
     pub camera_extrinsics: Option<CameraExtrinsics>,
+    // Pixels when JSON omits it. Bincode has no field names, so binary
+    // peers must be built with this field on both sides.
+    #[serde(default)]
+    pub coordinate_space: CoordinateSpace,
+}
+
+impl PerceptionFrame {
+    // Rescales every detection into `space`; no-op if already there
+    pub fn convert_coordinates(&mut self, space: CoordinateSpace) {
+        if self.coordinate_space == space {
+            return;
+        }
+        
+        for detection in &mut self.detections {
+            detection.bbox = match space {
+                CoordinateSpace::Normalized => detection.bbox.to_normalized(self.image_width, self.image_height),
+                CoordinateSpace::Pixels => detection.bbox.to_pixels(self.image_width, self.image_height),
+            };
+        }
+        self.coordinate_space = space;
+    }
+}
+
+// Floor-plane position in facility coordinates, meters
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct WorldPosition {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl WorldPosition {
+    pub fn distance(&self, other: &WorldPosition) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
+// One camera's tracker id contributing to a fused object
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TrackSource {
+    pub camera_id: String,
+    pub tracker_id: u64,
+}
+
+// A global track picked up by a camera that had not been following it.
+// `overlap_confidence` is 1.0 when the new camera's position matches the
+// track exactly, falling to 0.0 at the association radius.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TrackHandoff {
+    pub global_track_id: u64,
+    pub from_camera: String,
+    pub to_camera: String,
+    pub timestamp: u64,
+    pub overlap_confidence: f32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FusedObject {
+    pub global_track_id: u64,
+    pub class_label: String, // smoothed over the track's recent frames
+    #[serde(default)]
+    pub raw_class_label: Option<String>, // this frame's label; none while coasting
+    pub position: WorldPosition,
+    pub confidence: f32, // smoothed over the track's lifetime
+    #[serde(default)]
+    pub raw_confidence: f32, // this frame's fused confidence
+    pub sources: Vec<TrackSource>,
+    #[serde(default)]
+    pub zone_id: Option<String>, // semantic cell the object is in, when the node has a facility map
+    #[serde(default)]
+    pub coasting: bool, // unseen this frame; position is predicted from the track's last motion
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FusionResult {
+    pub timestamp: u64,
+    pub objects: Vec<FusedObject>,
+    // Smoothed scene confidence, for publishing and alerting
+    #[serde(default)]
+    pub fusion_confidence: f32,
+    #[serde(default)]
+    pub raw_fusion_confidence: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct CameraIntrinsics {
     pub fx: f32,
     pub fy: f32,
@@ -192,7 +432,7 @@ pub struct CameraIntrinsics {
     pub distortion: [f32; 5],
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct CameraExtrinsics {
     pub rotation: [f32; 3],
     pub translation: [f32; 3],
@@ -200,6 +440,7 @@ pub struct CameraExtrinsics {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CameraFrame {
+    pub camera_id: String,
     pub data: Vec<u8>,
     pub width: u32,
     pub height: u32,
@@ -264,4 +505,109 @@ pub struct MessagingConfig {
     pub high_water_mark: u32,
     pub send_timeout_ms: i32,
     pub reconnect_interval_ms: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_with(bbox: BBox) -> PerceptionFrame {
+        PerceptionFrame {
+            frame_id: 1,
+            timestamp: 1_700_000_000_000,
+            source_camera_id: "cam-1".to_string(),
+            image_width: 1920,
+            image_height: 1080,
+            model_version: "1.0".to_string(),
+            inference_time_ms: 0.0,
+            detections: vec![Detection {
+                bbox,
+                confidence: 0.9,
+                class_id: 0,
+                class_label: "person".to_string(),
+                tracker_id: None,
+                oriented: None,
+            }],
+            camera_intrinsics: None,
+            camera_extrinsics: None,
+            coordinate_space: CoordinateSpace::Pixels,
+        }
+    }
+
+    fn assert_bbox_eq(a: BBox, b: BBox) {
+        for (x, y) in [(a.xmin, b.xmin), (a.ymin, b.ymin), (a.xmax, b.xmax), (a.ymax, b.ymax)] {
+            assert!((x - y).abs() < 1e-3, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_bbox_pixel_normalized_round_trip() {
+        let pixels = BBox::new(480.0, 270.0, 960.0, 810.0);
+
+        let normalized = pixels.to_normalized(1920, 1080);
+        assert_bbox_eq(normalized, BBox::new(0.25, 0.25, 0.5, 0.75));
+        assert_bbox_eq(normalized.to_pixels(1920, 1080), pixels);
+    }
+
+    #[test]
+    fn test_frame_records_coordinate_space() {
+        let mut frame = frame_with(BBox::new(480.0, 270.0, 960.0, 810.0));
+        assert_eq!(frame.coordinate_space, CoordinateSpace::Pixels);
+
+        frame.convert_coordinates(CoordinateSpace::Normalized);
+        assert_eq!(frame.coordinate_space, CoordinateSpace::Normalized);
+        assert_bbox_eq(frame.detections[0].bbox, BBox::new(0.25, 0.25, 0.5, 0.75));
+
+        // Converting to the current space must not rescale twice
+        frame.convert_coordinates(CoordinateSpace::Normalized);
+        assert_bbox_eq(frame.detections[0].bbox, BBox::new(0.25, 0.25, 0.5, 0.75));
+
+        frame.convert_coordinates(CoordinateSpace::Pixels);
+        assert_eq!(frame.coordinate_space, CoordinateSpace::Pixels);
+        assert_bbox_eq(frame.detections[0].bbox, BBox::new(480.0, 270.0, 960.0, 810.0));
+    }
+
+    #[test]
+    fn test_oriented_iou() {
+        let square = OrientedBBox::new(0.0, 0.0, 2.0, 2.0, 0.0);
+        let turned = OrientedBBox::new(0.0, 0.0, 2.0, 2.0, std::f32::consts::FRAC_PI_4);
+        let quarter_turn = OrientedBBox::new(0.0, 0.0, 2.0, 2.0, std::f32::consts::FRAC_PI_2);
+
+        assert!((square.intersection_over_union(&square) - 1.0).abs() < 1e-5);
+        assert!((square.intersection_over_union(&quarter_turn) - 1.0).abs() < 1e-5);
+        // The overlap of a square and itself turned 45 degrees is a regular octagon
+        assert!((square.intersection_over_union(&turned) - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-4);
+        assert_eq!(square.intersection_over_union(&OrientedBBox::new(5.0, 0.0, 2.0, 2.0, 0.3)), 0.0);
+    }
+
+    #[test]
+    fn test_parallel_diagonal_boxes_do_not_overlap() {
+        // Two pallets side by side, seen at 45 degrees
+        let angle = std::f32::consts::FRAC_PI_4;
+        let a = OrientedBBox::new(100.0, 100.0, 200.0, 20.0, angle);
+        let b = OrientedBBox::new(120.0, 80.0, 200.0, 20.0, angle);
+
+        assert_eq!(a.intersection_over_union(&b), 0.0);
+        // Their axis-aligned envelopes overlap heavily, which is what breaks NMS
+        assert!(a.envelope().intersection_over_union(&b.envelope()) > 0.5);
+
+        let detection = |bbox: OrientedBBox| Detection {
+            bbox: bbox.envelope(),
+            confidence: 0.9,
+            class_id: 0,
+            class_label: "pallet".to_string(),
+            tracker_id: None,
+            oriented: Some(bbox),
+        };
+        assert_eq!(detection(a).intersection_over_union(&detection(b)), 0.0);
+    }
+
+    #[test]
+    fn test_frame_without_coordinate_space_defaults_to_pixels() {
+        let mut json = serde_json::to_value(frame_with(BBox::new(0.0, 0.0, 10.0, 10.0))).unwrap();
+        json.as_object_mut().unwrap().remove("coordinate_space");
+
+        let frame: PerceptionFrame = serde_json::from_value(json).unwrap();
+        assert_eq!(frame.coordinate_space, CoordinateSpace::Pixels);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,179 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::BTreeSet;
+
+use crate::types::{FusedObject, FusionResult};
+
+// World-space schema shared with the simulator's output, so downstream
+// consumers can take real and simulated world models interchangeably.
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Size {
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SemanticCell {
+    pub cell_id: String,
+    pub position: Position,
+    pub size: Size,
+    pub r#type: String,
+    pub risk_level: u8,
+}
+
+impl SemanticCell {
+    // Cells span `size` from their corner `position`; an edge belongs to the
+    // cell on its far side, so neighbouring cells never both contain a point
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.position.x
+            && x < self.position.x + self.size.width
+            && y >= self.position.y
+            && y < self.position.y + self.size.height
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorldDetection {
+    pub id: String,
+    pub r#type: String,
+    pub subtype: Option<String>,
+    pub position: Position,
+    pub confidence: f64,
+    pub source_cameras: Vec<String>,
+    pub is_static: bool,
+    pub lifespan: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorldModel {
+    pub timestamp: DateTime<Utc>,
+    pub zone_id: String,
+    pub detections: Vec<WorldDetection>,
+    pub semantic_map: Vec<SemanticCell>,
+    pub active_cameras: Vec<String>,
+    pub fusion_confidence: f64,
+}
+
+// Converts fused perception output into the simulator's world model schema
+#[derive(Debug, Clone)]
+pub struct WorldModelAdapter {
+    pub zone_id: String,
+    pub static_classes: Vec<String>, // classes that never move, e.g. racks
+}
+
+impl WorldModelAdapter {
+    pub fn new(zone_id: impl Into<String>) -> Self {
+        Self {
+            zone_id: zone_id.into(),
+            static_classes: vec!["pallet".to_string(), "obstacle".to_string(), "rack".to_string()],
+        }
+    }
+
+    pub fn convert(&self, result: &FusionResult) -> WorldModel {
+        let detections: Vec<WorldDetection> = result.objects.iter().map(|o| self.convert_object(o)).collect();
+
+        let active_cameras: BTreeSet<&str> = result
+            .objects
+            .iter()
+            .flat_map(|o| o.sources.iter().map(|s| s.camera_id.as_str()))
+            .collect();
+
+        WorldModel {
+            timestamp: Utc.timestamp_millis_opt(result.timestamp as i64).single().unwrap_or_default(),
+            zone_id: self.zone_id.clone(),
+            detections,
+            semantic_map: Vec::new(),
+            active_cameras: active_cameras.into_iter().map(String::from).collect(),
+            fusion_confidence: result.fusion_confidence as f64,
+        }
+    }
+
+    fn convert_object(&self, object: &FusedObject) -> WorldDetection {
+        let source_cameras: BTreeSet<&str> = object.sources.iter().map(|s| s.camera_id.as_str()).collect();
+
+        WorldDetection {
+            id: format!("TRK-{}", object.global_track_id),
+            r#type: object.class_label.clone(),
+            subtype: None,
+            // Fused positions are already on the floor plane in facility meters
+            position: Position {
+                x: object.position.x as f64,
+                y: object.position.y as f64,
+                z: 0.0,
+            },
+            confidence: object.confidence as f64,
+            source_cameras: source_cameras.into_iter().map(String::from).collect(),
+            is_static: self.static_classes.contains(&object.class_label),
+            lifespan: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{TrackSource, WorldPosition};
+
+    #[test]
+    fn test_fusion_result_converts_to_simulator_schema() {
+        let result = FusionResult {
+            timestamp: 1_700_000_000_250,
+            objects: vec![
+                FusedObject {
+                    global_track_id: 7,
+                    class_label: "person".to_string(),
+                    raw_class_label: Some("person".to_string()),
+                    position: WorldPosition { x: 12.5, y: 4.0 },
+                    confidence: 0.9,
+                    raw_confidence: 0.85,
+                    sources: vec![
+                        TrackSource { camera_id: "cam-b".to_string(), tracker_id: 3 },
+                        TrackSource { camera_id: "cam-a".to_string(), tracker_id: 11 },
+                    ],
+                    zone_id: None,
+                    coasting: false,
+                },
+                FusedObject {
+                    global_track_id: 8,
+                    class_label: "pallet".to_string(),
+                    raw_class_label: Some("pallet".to_string()),
+                    position: WorldPosition { x: 2.0, y: 30.0 },
+                    confidence: 0.7,
+                    raw_confidence: 0.75,
+                    sources: vec![TrackSource { camera_id: "cam-a".to_string(), tracker_id: 4 }],
+                    zone_id: None,
+                    coasting: false,
+                },
+            ],
+            fusion_confidence: 0.8,
+            raw_fusion_confidence: 0.8,
+        };
+
+        let world_model = WorldModelAdapter::new("MAIN_WAREHOUSE").convert(&result);
+        let json = serde_json::to_value(&world_model).unwrap();
+
+        assert_eq!(json["zone_id"], "MAIN_WAREHOUSE");
+        assert_eq!(json["timestamp"], "2023-11-14T22:13:20.250Z");
+        assert_eq!(json["active_cameras"], serde_json::json!(["cam-a", "cam-b"]));
+        assert!((world_model.fusion_confidence - 0.8).abs() < 1e-6);
+        assert!(json["semantic_map"].as_array().unwrap().is_empty());
+
+        let person = &json["detections"][0];
+        assert_eq!(person["id"], "TRK-7");
+        assert_eq!(person["type"], "person");
+        assert_eq!(person["position"], serde_json::json!({ "x": 12.5, "y": 4.0, "z": 0.0 }));
+        assert_eq!(person["source_cameras"], serde_json::json!(["cam-a", "cam-b"]));
+        assert_eq!(person["is_static"], false);
+        assert!(person["subtype"].is_null() && person["lifespan"].is_null());
+
+        assert_eq!(json["detections"][1]["is_static"], true);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,113 @@
+use actix_web::{web, HttpResponse, get, post};
+use serde_json::json;
+
+use crate::{
+    api::ApiError,
+    models::{CoverageQuery, HandoffQuery, OccupancyQuery, RecordDetectionsRequest, RecordHandoffsRequest},
+    services::{analytics_service::AnalyticsService, handoff_time, CoverageService},
+    AppState,
+};
+
+// Default occupancy bucket width: 15 minutes
+const DEFAULT_BUCKET_SECS: i64 = 900;
+
+#[get("/analytics/occupancy")]
+async fn get_zone_occupancy(
+    state: web::Data<AppState>,
+    query: web::Query<OccupancyQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let analytics_service = AnalyticsService::new(state.db_pool.clone());
+    let query = query.into_inner();
+    
+    let bucket_secs = query.bucket.unwrap_or(DEFAULT_BUCKET_SECS);
+    if bucket_secs <= 0 || query.to <= query.from {
+        return Err(ApiError::BadRequest("Invalid occupancy range or bucket".to_string()));
+    }
+    
+    let buckets = analytics_service.get_zone_occupancy(&query.zone, query.from, query.to, bucket_secs)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(buckets))
+}
+
+#[post("/analytics/handoffs")]
+async fn record_handoffs(
+    state: web::Data<AppState>,
+    request: web::Json<RecordHandoffsRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let analytics_service = AnalyticsService::new(state.db_pool.clone());
+    let request = request.into_inner();
+    
+    if let Some(handoff) = request.handoffs.iter().find(|h| handoff_time(h.timestamp).is_none()) {
+        return Err(ApiError::BadRequest(format!("Handoff of track {} has an invalid timestamp", handoff.global_track_id)));
+    }
+    
+    let recorded = analytics_service.record_handoffs(&request.node_id, &request.handoffs)
+        .await?;
+    
+    Ok(HttpResponse::Created().json(json!({ "recorded": recorded })))
+}
+
+#[post("/analytics/detections")]
+async fn record_detections(
+    state: web::Data<AppState>,
+    request: web::Json<RecordDetectionsRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let request = request.into_inner();
+    
+    if let Some(frame) = request.frames.iter().find(|f| handoff_time(f.timestamp).is_none()) {
+        return Err(ApiError::BadRequest(format!("Frame {} has an invalid timestamp", frame.frame_id)));
+    }
+    
+    let recorded = state.detection_persistence.record_frames(&request.node_id, request.frames)
+        .await?;
+    
+    Ok(HttpResponse::Created().json(json!({ "recorded": recorded })))
+}
+
+#[get("/analytics/handoffs")]
+async fn get_handoffs(
+    state: web::Data<AppState>,
+    query: web::Query<HandoffQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let analytics_service = AnalyticsService::new(state.db_pool.clone());
+    let query = query.into_inner();
+    
+    if query.to <= query.from {
+        return Err(ApiError::BadRequest("Invalid handoff range".to_string()));
+    }
+    
+    let handoffs = analytics_service.get_handoffs(&query)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(handoffs))
+}
+
+#[get("/analytics/coverage")]
+async fn get_camera_coverage(
+    state: web::Data<AppState>,
+    query: web::Query<CoverageQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let coverage_service = CoverageService::new(state.db_pool.clone());
+    let query = query.into_inner();
+    
+    if query.max_x <= query.min_x || query.max_y <= query.min_y {
+        return Err(ApiError::BadRequest("Invalid coverage bounds".to_string()));
+    }
+    if query.cell_size.is_some_and(|size| !size.is_finite() || size <= 0.0) {
+        return Err(ApiError::BadRequest("Cell size must be positive".to_string()));
+    }
+    
+    let coverage = coverage_service.analyze_coverage(&query)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(coverage))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_zone_occupancy)
+        .service(record_handoffs)
+        .service(record_detections)
+        .service(get_handoffs)
+        .service(get_camera_coverage);
+}
\ No newline at end of file
@@ -1,10 +1,15 @@
 use actix_web::{web, HttpResponse, get, post, put, delete};
 use uuid::Uuid;
+use serde::Deserialize;
 use serde_json::json;
+use futures::StreamExt;
 
 use crate::{
-    models::{CreateAnnotationRequest, UpdateAnnotationRequest},
+    api::ApiError,
+    models::{BulkReviewRequest, CreateAnnotationRequest, PredictQuery, UpdateAnnotationRequest},
     services::annotation_service::AnnotationService,
+    services::{AnnotationExportFormat, AnnotationExportService, PredictionService, ScoringClient},
+    storage::FileStorage,
     AppState,
 };
 
@@ -12,13 +17,12 @@ use crate::{
 async fn get_annotation(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let annotation_service = AnnotationService::new(state.db_pool.clone());
     let annotation_id = path.into_inner();
     
     let annotation = annotation_service.get_annotation(annotation_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(annotation))
 }
@@ -28,7 +32,7 @@ async fn get_camera_annotations(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     query: web::Query<HashMap<String, i64>>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let annotation_service = AnnotationService::new(state.db_pool.clone());
     let camera_id = path.into_inner();
     
@@ -36,8 +40,7 @@ async fn get_camera_annotations(
     let offset = query.get("offset").cloned();
     
     let annotations = annotation_service.get_annotations_by_camera(camera_id, limit, offset)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(annotations))
 }
@@ -47,29 +50,46 @@ async fn create_annotation(
     state: web::Data<AppState>,
     user_id: web::ReqData<Uuid>, // Assuming we have authentication middleware
     annotation_data: web::Json<CreateAnnotationRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let annotation_service = AnnotationService::new(state.db_pool.clone());
     
     let annotation = annotation_service.create_annotation(*user_id, annotation_data.into_inner())
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Created().json(annotation))
 }
 
+#[put("/annotations/bulk")]
+async fn bulk_review_annotations(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    review: web::Json<BulkReviewRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let annotation_service = AnnotationService::new(state.db_pool.clone());
+    let review = review.into_inner();
+    
+    let annotations = annotation_service.bulk_update_status(&review.ids, review.status, *user_id)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    Ok(HttpResponse::Ok().json(json!({
+        "updated": annotations.len(),
+        "annotations": annotations,
+    })))
+}
+
 #[put("/annotations/{id}")]
 async fn update_annotation(
     state: web::Data<AppState>,
     user_id: web::ReqData<Uuid>,
     path: web::Path<Uuid>,
     annotation_data: web::Json<UpdateAnnotationRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let annotation_service = AnnotationService::new(state.db_pool.clone());
     let annotation_id = path.into_inner();
     
     let annotation = annotation_service.update_annotation(annotation_id, *user_id, annotation_data.into_inner())
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(annotation))
 }
@@ -78,54 +98,80 @@ async fn update_annotation(
 async fn delete_annotation(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let annotation_service = AnnotationService::new(state.db_pool.clone());
     let annotation_id = path.into_inner();
     
     annotation_service.delete_annotation(annotation_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::NoContent().finish())
 }
 
+// Re-runs a chosen model on the annotation's image for a side-by-side
+// comparison with the human labels
+#[post("/annotations/{id}/predict")]
+async fn predict_annotation(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    query: web::Query<PredictQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let scoring_url = state.config.ml.scoring_url.as_deref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("No scoring node configured (ml.scoring_url)".to_string()))?;
+    let prediction_service = PredictionService::new(state.db_pool.clone(), ScoringClient::new(scoring_url, state.config.ml.scoring_token.clone())?);
+    let storage = FileStorage::new(state.config.storage.annotations_dir.clone());
+    
+    let prediction = prediction_service.predict(path.into_inner(), query.model_id, &storage)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(prediction))
+}
+
 #[get("/annotations/stats")]
 async fn get_annotation_stats(
     state: web::Data<AppState>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let annotation_service = AnnotationService::new(state.db_pool.clone());
     
     let stats = annotation_service.get_annotation_stats()
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(stats))
 }
 
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    format: Option<String>,
+}
+
+// Streamed as chunked csv or ndjson, so memory stays flat however many
+// annotations there are
 #[get("/annotations/export")]
 async fn export_annotations(
     state: web::Data<AppState>,
-    query: web::Query<HashMap<String, String>>,
-) -> Result<HttpResponse, actix_web::Error> {
-    let annotation_service = AnnotationService::new(state.db_pool.clone());
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let export_service = AnnotationExportService::new(state.db_pool.clone());
     
-    let format = query.get("format").map(|s| s.as_str()).unwrap_or("csv");
-    let data = annotation_service.export_annotations(format)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+    let format = AnnotationExportFormat::parse(query.format.as_deref().unwrap_or("csv"))
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let body = export_service.export(format)
+        .map(|chunk| chunk.map(web::Bytes::from).map_err(ApiError::Internal));
     
     Ok(HttpResponse::Ok()
-        .content_type("text/csv")
-        .header("Content-Disposition", "attachment; filename=annotations.csv")
-        .body(data))
+        .content_type(format.content_type())
+        .insert_header(("Content-Disposition", format!("attachment; filename=annotations.{}", format.extension())))
+        .streaming(body))
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(get_annotation)
         .service(get_camera_annotations)
         .service(create_annotation)
+        .service(bulk_review_annotations)
         .service(update_annotation)
         .service(delete_annotation)
         .service(get_annotation_stats)
+        .service(predict_annotation)
         .service(export_annotations);
 }
\ No newline at end of file
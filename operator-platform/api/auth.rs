@@ -1,91 +1,179 @@
-use actix_web::{web, HttpResponse, post};
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::header,
+    middleware::Next,
+    web, HttpMessage, HttpResponse, post,
+};
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{encode, EncodingKey, Header};
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
-    models::{LoginRequest, AuthResponse, CreateUserRequest, User, UserRole},
-    services::user_service::UserService,
+    api::ApiError,
+    models::{LoginRequest, AuthResponse, CreateUserRequest, RotateSecretRequest, User, UserRole},
+    services::{user_service::UserService, Claims, SigningKeys, UserPreferencesService},
     AppState,
 };
 
+// Validates the bearer token when one is sent and hands its user id and
+// claims to handlers as request data. A bad or expired token is rejected
+// here; requests without one pass through for handlers to refuse.
+pub async fn authenticate(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let token = req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    
+    if let Some(token) = token {
+        let state = req.app_data::<web::Data<AppState>>()
+            .ok_or_else(|| ApiError::internal(anyhow::anyhow!("App state missing")))?;
+        let claims = state.signing_keys.validate(&token)
+            .map_err(|e| ApiError::Unauthorized(format!("Invalid token: {}", e)))?;
+        
+        req.extensions_mut().insert(claims.sub);
+        req.extensions_mut().insert(claims);
+    }
+    
+    next.call(req).await
+}
+
 #[post("/auth/register")]
 async fn register(
     state: web::Data<AppState>,
+    claims: Option<web::ReqData<Claims>>,
     user_data: web::Json<CreateUserRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
+    let role = registered_role(claims.as_deref(), user_data.role.clone())?;
     let user_service = UserService::new(state.db_pool.clone());
     
     // Check if user already exists
     if user_service.get_user_by_email(&user_data.email).await.is_ok() {
-        return Ok(HttpResponse::Conflict().json(json!({
-            "error": "User with this email already exists"
-        })));
+        return Err(ApiError::Conflict("User with this email already exists".to_string()));
     }
     
     // Hash password
     let password_hash = hash(&user_data.password, state.config.auth.password_hash_cost)
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .map_err(ApiError::internal)?;
     
     // Create user
     let user = user_service.create_user(
         &user_data.username,
         &user_data.email,
         &password_hash,
-        user_data.role.clone(),
-    ).await.map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        role,
+    ).await?;
     
     // Generate token
-    let token = generate_token(&user, &state.config.auth.secret_key)?;
+    let token = generate_token(&user, &state.signing_keys)?;
     
-    Ok(HttpResponse::Ok().json(AuthResponse { token, user }))
+    Ok(HttpResponse::Ok().json(AuthResponse { token, user, preferences: json!({}) }))
 }
 
 #[post("/auth/login")]
 async fn login(
     state: web::Data<AppState>,
     login_data: web::Json<LoginRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let user_service = UserService::new(state.db_pool.clone());
     
     // Get user by email
     let user = user_service.get_user_by_email(&login_data.email)
         .await
-        .map_err(|_| actix_web::error::ErrorUnauthorized("Invalid credentials"))?;
+        .map_err(|_| ApiError::Unauthorized("Invalid credentials".to_string()))?;
     
     // Verify password
     let valid = verify(&login_data.password, &user.password_hash)
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .map_err(ApiError::internal)?;
     
     if !valid {
-        return Err(actix_web::error::ErrorUnauthorized("Invalid credentials"));
+        return Err(ApiError::Unauthorized("Invalid credentials".to_string()));
     }
     
     // Generate token
-    let token = generate_token(&user, &state.config.auth.secret_key)?;
+    let token = generate_token(&user, &state.signing_keys)?;
+    
+    // So the dashboard opens with the user's saved filters
+    let preferences = UserPreferencesService::new(state.db_pool.clone())
+        .get(user.id)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(AuthResponse { token, user, preferences }))
+}
+
+// Switches token signing to a new secret. Tokens signed with the old one
+// keep working for the grace window. Rotation is in memory only: set the new
+// secret as auth.secret_key, and the old as auth.previous_secret_key, before
+// the next restart.
+#[post("/auth/rotate-secret")]
+async fn rotate_secret(
+    state: web::Data<AppState>,
+    claims: Option<web::ReqData<Claims>>,
+    request: web::Json<RotateSecretRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let claims = claims.ok_or_else(|| ApiError::Unauthorized("Authentication required".to_string()))?;
+    if claims.role != UserRole::Admin {
+        return Err(ApiError::Forbidden("Only admins can rotate the signing secret".to_string()));
+    }
+    
+    let request = request.into_inner();
+    let grace = request.grace_min.map(|minutes| Duration::minutes(minutes as i64));
+    let previous_valid_until = state.signing_keys.rotate(request.secret_key, grace)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
     
-    Ok(HttpResponse::Ok().json(AuthResponse { token, user }))
+    tracing::info!("Signing secret rotated by {}; previous secret valid until {}", claims.sub, previous_valid_until);
+    
+    Ok(HttpResponse::Ok().json(json!({ "previous_valid_until": previous_valid_until })))
+}
+
+// Anyone can register as a viewer; any other role takes an admin's token
+fn registered_role(claims: Option<&Claims>, requested: Option<UserRole>) -> Result<UserRole, ApiError> {
+    match (requested, claims) {
+        (None | Some(UserRole::Viewer), _) => Ok(UserRole::Viewer),
+        (Some(role), Some(claims)) if claims.role == UserRole::Admin => Ok(role),
+        (Some(_), _) => Err(ApiError::Forbidden("Only admins can register users with a role other than viewer".to_string())),
+    }
 }
 
-fn generate_token(user: &User, secret_key: &str) -> Result<String, actix_web::Error> {
+fn generate_token(user: &User, signing_keys: &SigningKeys) -> Result<String, ApiError> {
     let expiration = Utc::now() + Duration::hours(24);
     
-    let claims = json!({
-        "sub": user.id.to_string(),
-        "email": user.email,
-        "role": user.role,
-        "exp": expiration.timestamp(),
-    });
-    
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret_key.as_ref()),
-    ).map_err(|e| actix_web::error::ErrorInternalServerError(e))
+    let claims = Claims {
+        sub: user.id,
+        email: user.email.clone(),
+        role: user.role.clone(),
+        exp: expiration.timestamp(),
+    };
+    
+    signing_keys.sign(&claims).map_err(ApiError::internal)
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
-    cfg.service(register).service(login);
-}
\ No newline at end of file
+    cfg.service(register).service(login).service(rotate_secret);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(role: UserRole) -> Claims {
+        Claims { sub: Uuid::new_v4(), email: "user@example.com".to_string(), role, exp: 0 }
+    }
+
+    #[test]
+    fn test_only_admins_register_privileged_roles() {
+        assert_eq!(registered_role(None, None).unwrap(), UserRole::Viewer);
+        assert_eq!(registered_role(None, Some(UserRole::Viewer)).unwrap(), UserRole::Viewer);
+        assert!(matches!(registered_role(None, Some(UserRole::Admin)), Err(ApiError::Forbidden(_))));
+        assert!(matches!(registered_role(Some(&claims(UserRole::Operator)), Some(UserRole::Operator)), Err(ApiError::Forbidden(_))));
+
+        let admin = claims(UserRole::Admin);
+        assert_eq!(registered_role(Some(&admin), Some(UserRole::Operator)).unwrap(), UserRole::Operator);
+        assert_eq!(registered_role(Some(&admin), None).unwrap(), UserRole::Viewer);
+    }
+}
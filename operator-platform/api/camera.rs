@@ -1,23 +1,29 @@
-use actix_web::{web, HttpResponse, get, post, put, delete};
+use actix_web::{http::header, web, HttpRequest, HttpResponse, get, post, put, delete};
 use uuid::Uuid;
 use serde_json::json;
 use std::collections::HashMap;
+use validator::Validate;
+use futures::StreamExt;
+use aetherforge_common::replay;
 
 use crate::{
-    models::{CreateCameraRequest, UpdateCameraRequest, CalibrationRequest},
+    api::ApiError,
+    models::{Camera, CreateCameraRequest, UpdateCameraRequest, CalibrationRequest, CalibrationResultRequest, DetectionExportQuery, PtzRequest, ExposureRequest, UserRole},
     services::camera_service::CameraService,
+    services::Claims,
+    services::{CalibrationExportFormat, DetectionExportService},
+    services::live_stream::MJPEG_BOUNDARY,
     AppState,
 };
 
 #[get("/cameras")]
 async fn get_cameras(
     state: web::Data<AppState>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     
     let cameras = camera_service.get_all_cameras()
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(cameras))
 }
@@ -26,13 +32,12 @@ async fn get_cameras(
 async fn get_camera(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     let camera_id = path.into_inner();
     
     let camera = camera_service.get_camera_by_id(camera_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(camera))
 }
@@ -41,13 +46,12 @@ async fn get_camera(
 async fn get_cameras_by_zone(
     state: web::Data<AppState>,
     path: web::Path<String>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     let zone = path.into_inner();
     
     let cameras = camera_service.get_cameras_by_zone(&zone)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(cameras))
 }
@@ -56,7 +60,7 @@ async fn get_cameras_by_zone(
 async fn get_cameras_by_status(
     state: web::Data<AppState>,
     path: web::Path<String>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     let status_str = path.into_inner();
     
@@ -66,12 +70,11 @@ async fn get_cameras_by_status(
         "calibrating" => crate::models::CameraStatus::Calibrating,
         "maintenance" => crate::models::CameraStatus::Maintenance,
         "error" => crate::models::CameraStatus::Error,
-        _ => return Err(actix_web::error::ErrorBadRequest("Invalid status")),
+        _ => return Err(ApiError::BadRequest("Invalid status".to_string())),
     };
     
     let cameras = camera_service.get_cameras_by_status(status)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(cameras))
 }
@@ -80,12 +83,13 @@ async fn get_cameras_by_status(
 async fn create_camera(
     state: web::Data<AppState>,
     camera_data: web::Json<CreateCameraRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     
+    camera_data.validate()?;
+    
     let camera = camera_service.create_camera(camera_data.into_inner())
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Created().json(camera))
 }
@@ -95,13 +99,14 @@ async fn update_camera(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     camera_data: web::Json<UpdateCameraRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     let camera_id = path.into_inner();
     
+    camera_data.validate()?;
+    
     let camera = camera_service.update_camera(camera_id, camera_data.into_inner())
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(camera))
 }
@@ -110,13 +115,12 @@ async fn update_camera(
 async fn delete_camera(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     let camera_id = path.into_inner();
     
     camera_service.delete_camera(camera_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::NoContent().finish())
 }
@@ -125,47 +129,175 @@ async fn delete_camera(
 async fn get_calibration_history(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     let camera_id = path.into_inner();
     
     let history = camera_service.get_calibration_history(camera_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(history))
 }
 
+#[derive(Debug, serde::Deserialize)]
+struct CalibrationExportQuery {
+    format: Option<String>, // opencv (default) or ros
+}
+
+// OpenCV calibration.yaml or ROS camera_info YAML
+#[get("/cameras/{id}/calibration/export")]
+async fn export_calibration(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    query: web::Query<CalibrationExportQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
+    let camera_id = path.into_inner();
+    
+    let format = CalibrationExportFormat::parse(query.format.as_deref().unwrap_or("opencv"))
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let file = camera_service.export_calibration(camera_id, format)
+        .await?;
+    
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-yaml")
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", file.file_name)))
+        .body(file.yaml))
+}
+
+// Pattern images for the next calibration run, as one or more multipart
+// `image` parts (JPEG or PNG)
+#[post("/cameras/{id}/calibration/images")]
+async fn upload_calibration_images(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    request: HttpRequest,
+    mut payload: web::Payload,
+) -> Result<HttpResponse, ApiError> {
+    let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
+    let camera_id = path.into_inner();
+    
+    let boundary = request.headers().get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|content_type| multer::parse_boundary(content_type).ok())
+        .ok_or_else(|| ApiError::BadRequest("Expected a multipart/form-data body".to_string()))?;
+    
+    // Buffered first: the multipart parser needs a Send stream, which
+    // actix's payload isn't
+    let max_bytes = state.config.storage.max_upload_size;
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        if body.len() + chunk.len() > max_bytes {
+            return Err(ApiError::BadRequest(format!("Upload is larger than {} bytes", max_bytes)));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let body = body.freeze();
+    let mut multipart = multer::Multipart::new(futures::stream::once(async move { Ok::<_, std::io::Error>(body) }), boundary);
+    
+    let mut images = Vec::new();
+    while let Some(field) = multipart.next_field().await.map_err(|e| ApiError::BadRequest(e.to_string()))? {
+        if field.name() != Some("image") {
+            continue;
+        }
+        let bytes = field.bytes().await.map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        images.push(bytes.to_vec());
+    }
+    if images.is_empty() {
+        return Err(ApiError::BadRequest("Missing image part".to_string()));
+    }
+    
+    camera_service.get_camera_by_id(camera_id).await?;
+    let saved = camera_service.upload_calibration_images(camera_id, &images)
+        .await?;
+    
+    Ok(HttpResponse::Created().json(json!({ "images": saved })))
+}
+
+#[get("/cameras/{id}/calibration/images")]
+async fn get_calibration_images(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
+    let camera_id = path.into_inner();
+    
+    let images = camera_service.list_calibration_images(camera_id)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(images))
+}
+
+#[delete("/cameras/{id}/calibration/images/{filename}")]
+async fn delete_calibration_image(
+    state: web::Data<AppState>,
+    path: web::Path<(Uuid, String)>,
+) -> Result<HttpResponse, ApiError> {
+    let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
+    let (camera_id, filename) = path.into_inner();
+    
+    camera_service.delete_calibration_image(camera_id, &filename)
+        .await?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
 #[post("/cameras/{id}/calibration/start")]
 async fn start_calibration(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     calibration_data: web::Json<CalibrationRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     let camera_id = path.into_inner();
+    calibration_data.validate()?;
     
     camera_service.start_calibration(camera_id, calibration_data.into_inner())
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Accepted().json(json!({"message": "Calibration started"})))
 }
 
+#[post("/cameras/{id}/calibration/result")]
+async fn save_calibration_result(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    result: web::Json<CalibrationResultRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
+    let camera_id = path.into_inner();
+    let result = result.into_inner();
+    result.validate()?;
+    
+    let camera = camera_service.save_calibration_data(
+        camera_id,
+        result.intrinsics,
+        result.extrinsics,
+        &result.calibration_method,
+        result.calibration_accuracy,
+        user_id.into_inner(),
+        result.calibration_images,
+    )
+    .await?;
+    
+    Ok(HttpResponse::Ok().json(camera))
+}
+
 #[get("/cameras/{id}/health/metrics")]
 async fn get_health_metrics(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     query: web::Query<HashMap<String, i32>>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     let camera_id = path.into_inner();
     
     let hours = query.get("hours").cloned().unwrap_or(24);
     
     let metrics = camera_service.get_health_metrics(camera_id, hours)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(metrics))
 }
@@ -175,15 +307,14 @@ async fn get_status_history(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     query: web::Query<HashMap<String, i64>>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     let camera_id = path.into_inner();
     
     let limit = query.get("limit").cloned();
     
     let history = camera_service.get_status_history(camera_id, limit)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(history))
 }
@@ -191,12 +322,11 @@ async fn get_status_history(
 #[get("/cameras/zones")]
 async fn get_camera_zones(
     state: web::Data<AppState>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     
     let zones = camera_service.get_camera_zones()
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(zones))
 }
@@ -204,31 +334,190 @@ async fn get_camera_zones(
 #[get("/cameras/stats")]
 async fn get_camera_stats(
     state: web::Data<AppState>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     
     let stats = camera_service.get_camera_stats()
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(stats))
 }
 
+// Which cameras are calibrated, per zone and overall. Cameras calibrated
+// longer ago than `monitoring.calibration_stale_days` are listed and
+// marked as needing recalibration.
+#[get("/cameras/calibration/summary")]
+async fn get_calibration_summary(
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
+    let stale_after = chrono::Duration::days(i64::from(state.config.monitoring.calibration_stale_days));
+    
+    let summary = camera_service.get_calibration_summary(stale_after)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(summary))
+}
+
 #[get("/cameras/{id}/test-connection")]
 async fn test_camera_connection(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
     let camera_id = path.into_inner();
     
     let is_connected = camera_service.test_camera_connection(camera_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(json!({"connected": is_connected})))
 }
 
+// Steering and exposure changes affect what everyone sees, so viewers can't
+// make them
+fn require_camera_operator(claims: Option<web::ReqData<Claims>>) -> Result<(), ApiError> {
+    let claims = claims.ok_or_else(|| ApiError::Unauthorized("Authentication required".to_string()))?;
+    match claims.role {
+        UserRole::Admin | UserRole::Operator => Ok(()),
+        UserRole::Viewer => Err(ApiError::Forbidden("Only operators can control cameras".to_string())),
+    }
+}
+
+// Loads a camera for live viewing, enforcing the user's zone grants
+pub(super) async fn authorize_camera_view(
+    state: &AppState,
+    user_id: Uuid,
+    camera_id: Uuid,
+) -> Result<Camera, ApiError> {
+    let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
+    
+    let camera = camera_service.get_camera_by_id(camera_id)
+        .await
+        .map_err(|e| ApiError::NotFound(e.to_string()))?;
+    
+    if let Some(zone) = &camera.zone {
+        let allowed = camera_service.user_can_view_zone(user_id, zone)
+            .await?;
+        
+        if !allowed {
+            return Err(ApiError::Forbidden("No access to this camera's zone".to_string()));
+        }
+    }
+    
+    Ok(camera)
+}
+
+#[get("/cameras/{id}/live.mjpeg")]
+async fn get_live_mjpeg(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let camera_id = path.into_inner();
+    let camera = authorize_camera_view(&state, *user_id, camera_id).await?;
+    
+    let rtsp_url = camera.rtsp_url
+        .ok_or_else(|| ApiError::BadRequest("Camera has no RTSP stream".to_string()))?;
+    
+    let mut viewer = state.live_streams.subscribe_mjpeg(camera_id, &rtsp_url)
+        .map_err(|e| ApiError::ServiceUnavailable(e.to_string()))?;
+    
+    // The viewer is moved into the stream so its guard is released when the
+    // browser disconnects and actix drops the body
+    let body = async_stream::stream! {
+        loop {
+            match viewer.rx.recv().await {
+                Ok(chunk) => yield Ok::<_, actix_web::Error>(chunk),
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+    
+    Ok(HttpResponse::Ok()
+        .content_type(format!("multipart/x-mixed-replace;boundary={}", MJPEG_BOUNDARY))
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(body))
+}
+
+// Moves a PTZ camera. Operators and admins who can view the camera can steer
+// it; 409 if it has no ONVIF PTZ service, 429 if commands come faster than
+// the camera's minimum interval (stops are never throttled).
+#[post("/cameras/{id}/ptz")]
+async fn move_camera(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    claims: Option<web::ReqData<Claims>>,
+    path: web::Path<Uuid>,
+    request: web::Json<PtzRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_camera_operator(claims)?;
+    request.validate()?;
+    let camera = authorize_camera_view(&state, *user_id, path.into_inner()).await?;
+    
+    state.camera_control.ptz(&camera, &request).await?;
+    
+    Ok(HttpResponse::Accepted().finish())
+}
+
+#[post("/cameras/{id}/exposure")]
+async fn set_camera_exposure(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    claims: Option<web::ReqData<Claims>>,
+    path: web::Path<Uuid>,
+    request: web::Json<ExposureRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_camera_operator(claims)?;
+    request.validate()?;
+    let camera = authorize_camera_view(&state, *user_id, path.into_inner()).await?;
+    
+    state.camera_control.set_exposure(&camera, &request).await?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Persisted detections as a replay file (see aetherforge_common::replay)
+// that the perception node can score offline with --replay
+#[get("/cameras/{id}/detections/export")]
+async fn export_detections(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    query: web::Query<DetectionExportQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let camera_service = CameraService::new(state.db_pool.clone(), state.file_storage.clone());
+    let export_service = DetectionExportService::new(state.db_pool.clone());
+    let camera_id = path.into_inner();
+    let query = query.into_inner();
+    
+    let camera = camera_service.get_camera_by_id(camera_id).await?;
+    let frame_size = (
+        camera.resolution_width.unwrap_or(0) as u32,
+        camera.resolution_height.unwrap_or(0) as u32,
+    );
+    
+    let header = replay::encode_header(&replay::ReplayHeader {
+        schema_version: replay::REPLAY_SCHEMA_VERSION,
+        camera_id: camera.device_id.clone(),
+        from_ms: query.from.timestamp_millis() as u64,
+        to_ms: query.to.timestamp_millis() as u64,
+    }).map_err(ApiError::internal)?;
+    
+    let frames = export_service.export_frames(camera_id, camera.device_id.clone(), frame_size, query.from, query.to)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    let body = futures::stream::once(async move { Ok::<_, ApiError>(web::Bytes::from(header)) })
+        .chain(frames.map(|frame| {
+            let frame = frame.map_err(ApiError::Internal)?;
+            replay::encode_frame(&frame).map(web::Bytes::from).map_err(ApiError::internal)
+        }));
+    
+    Ok(HttpResponse::Ok()
+        .content_type("application/octet-stream")
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}.afreplay\"", camera.device_id)))
+        .streaming(body))
+}
+
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(get_cameras)
         .service(get_camera)
@@ -238,10 +527,67 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .service(update_camera)
         .service(delete_camera)
         .service(get_calibration_history)
+        .service(export_calibration)
+        .service(upload_calibration_images)
+        .service(get_calibration_images)
+        .service(delete_calibration_image)
         .service(start_calibration)
+        .service(save_calibration_result)
         .service(get_health_metrics)
         .service(get_status_history)
         .service(get_camera_zones)
         .service(get_camera_stats)
-        .service(test_camera_connection);
-}
\ No newline at end of file
+        .service(get_calibration_summary)
+        .service(test_camera_connection)
+        .service(get_live_mjpeg)
+        .service(move_camera)
+        .service(set_camera_exposure)
+        .service(export_detections);
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, ResponseError};
+    use crate::storage::{test_pool, FileStorage};
+    
+    async fn test_service() -> CameraService {
+        CameraService::new(test_pool().await, FileStorage::new(std::env::temp_dir()))
+    }
+    
+    fn camera_request(device_id: &str) -> CreateCameraRequest {
+        CreateCameraRequest {
+            name: "dock".to_string(),
+            description: None,
+            device_id: device_id.to_string(),
+            location: "dock 1".to_string(),
+            zone: None,
+            stream_url: "rtsp://dock/stream".to_string(),
+            rtsp_url: None,
+            onvif_url: None,
+            fps: None,
+            resolution_width: None,
+            resolution_height: None,
+        }
+    }
+    
+    #[actix_rt::test]
+    #[ignore]
+    async fn test_missing_camera_is_404() {
+        let service = test_service().await;
+        
+        let error = ApiError::from(service.get_camera_by_id(Uuid::new_v4()).await.unwrap_err());
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    }
+    
+    #[actix_rt::test]
+    #[ignore]
+    async fn test_duplicate_camera_is_409() {
+        let service = test_service().await;
+        let device_id = format!("dup-{}", Uuid::new_v4());
+        
+        service.create_camera(camera_request(&device_id)).await.unwrap();
+        let error = ApiError::from(service.create_camera(camera_request(&device_id)).await.unwrap_err());
+        assert_eq!(error.status_code(), StatusCode::CONFLICT);
+        assert_eq!(error.code(), "conflict");
+    }
+}
@@ -0,0 +1,206 @@
+use actix_web::{web, HttpResponse, get, post};
+use futures::StreamExt;
+use uuid::Uuid;
+use serde_json::json;
+
+use crate::{
+    api::ApiError,
+    models::{AddDatasetAnnotationsRequest, CreateDatasetRequest, GenerateSplitsRequest, SplitRatios},
+    services::dataset_service::{verify_bundle, DatasetService},
+    storage::FileStorage,
+    AppState,
+};
+
+// Bundles are checked in memory, so uploads for verification are capped
+const MAX_BUNDLE_BYTES: usize = 512 * 1024 * 1024;
+
+#[post("/datasets")]
+async fn create_dataset(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    dataset_data: web::Json<CreateDatasetRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let dataset_service = DatasetService::new(state.db_pool.clone());
+    
+    let dataset = dataset_service.create_dataset(*user_id, dataset_data.into_inner())
+        .await?;
+    
+    Ok(HttpResponse::Created().json(dataset))
+}
+
+#[get("/datasets/{id}")]
+async fn get_dataset(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let dataset_service = DatasetService::new(state.db_pool.clone());
+    
+    let dataset = dataset_service.get_dataset(path.into_inner())
+        .await
+        .map_err(|e| ApiError::NotFound(e.to_string()))?;
+    
+    Ok(HttpResponse::Ok().json(dataset))
+}
+
+#[post("/datasets/{id}/annotations")]
+async fn add_dataset_annotations(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    request: web::Json<AddDatasetAnnotationsRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let dataset_service = DatasetService::new(state.db_pool.clone());
+    
+    let added = dataset_service.add_annotations(path.into_inner(), &request.annotation_ids)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    Ok(HttpResponse::Ok().json(json!({ "added": added })))
+}
+
+// Freezes the dataset's current annotations as a new immutable version
+#[post("/datasets/{id}/versions")]
+async fn snapshot_dataset(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let dataset_service = DatasetService::new(state.db_pool.clone());
+    
+    let version = dataset_service.snapshot(path.into_inner(), *user_id)
+        .await?;
+    
+    Ok(HttpResponse::Created().json(version))
+}
+
+#[get("/datasets/{id}/versions")]
+async fn get_dataset_versions(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let dataset_service = DatasetService::new(state.db_pool.clone());
+    
+    let versions = dataset_service.get_versions(path.into_inner())
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(versions))
+}
+
+#[get("/dataset-versions/{id}")]
+async fn get_dataset_version(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let dataset_service = DatasetService::new(state.db_pool.clone());
+    
+    let version = dataset_service.get_version(path.into_inner())
+        .await
+        .map_err(|e| ApiError::NotFound(e.to_string()))?;
+    
+    Ok(HttpResponse::Ok().json(version))
+}
+
+#[post("/datasets/{id}/splits")]
+async fn generate_dataset_splits(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    request: web::Json<GenerateSplitsRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let dataset_service = DatasetService::new(state.db_pool.clone());
+    let request = request.into_inner();
+    let ratios = request.ratios
+        .unwrap_or_else(|| SplitRatios::from_validation_split(state.config.ml.validation_split));
+    
+    let summary = dataset_service.generate_splits(path.into_inner(), ratios, request.seed)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+// Per-class counts with suggested loss weights and oversampling factors
+#[get("/datasets/{id}/class-distribution")]
+async fn get_class_distribution(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let dataset_service = DatasetService::new(state.db_pool.clone());
+    
+    let distribution = dataset_service.compute_class_distribution(path.into_inner())
+        .await
+        .map_err(|e| ApiError::NotFound(e.to_string()))?;
+    
+    Ok(HttpResponse::Ok().json(distribution))
+}
+
+#[get("/datasets/{id}/splits")]
+async fn get_dataset_splits(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let dataset_service = DatasetService::new(state.db_pool.clone());
+    
+    let splits = dataset_service.get_splits(path.into_inner())
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(splits))
+}
+
+// The version as a reproducible .tar.gz of images, COCO annotations and a
+// manifest, for handing to an external training service
+#[get("/dataset-versions/{id}/bundle")]
+async fn export_dataset_bundle(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let dataset_service = DatasetService::new(state.db_pool.clone());
+    let storage = FileStorage::new(state.config.storage.annotations_dir.clone());
+    let version_id = path.into_inner();
+    
+    let version = dataset_service.get_version(version_id)
+        .await?
+        .version;
+    let bundle = dataset_service.export_bundle(version_id, &storage)
+        .await?;
+    
+    Ok(HttpResponse::Ok()
+        .content_type("application/gzip")
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"dataset-{}-v{}.tar.gz\"", version.dataset_id, version.version)))
+        .body(bundle))
+}
+
+// Checks a bundle against its manifest, e.g. before re-importing it
+#[post("/dataset-bundles/verify")]
+async fn verify_dataset_bundle(
+    mut payload: web::Payload,
+) -> Result<HttpResponse, ApiError> {
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        if body.len() + chunk.len() > MAX_BUNDLE_BYTES {
+            return Err(ApiError::BadRequest(format!("Bundle is larger than {} bytes", MAX_BUNDLE_BYTES)));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    
+    // Inflating and hashing a large bundle would stall the worker
+    let body = body.freeze();
+    let manifest = web::block(move || verify_bundle(&body))
+        .await
+        .map_err(ApiError::internal)??;
+    
+    Ok(HttpResponse::Ok().json(manifest))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_dataset)
+        .service(get_dataset)
+        .service(add_dataset_annotations)
+        .service(snapshot_dataset)
+        .service(get_dataset_versions)
+        .service(get_dataset_version)
+        .service(generate_dataset_splits)
+        .service(get_dataset_splits)
+        .service(get_class_distribution)
+        .service(export_dataset_bundle)
+        .service(verify_dataset_bundle);
+}
\ No newline at end of file
@@ -0,0 +1,209 @@
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use serde::Serialize;
+use tracing::error;
+
+use aetherforge_common::facility_map::FacilityMapError;
+
+use crate::services::{BundleRejected, CalibrationImageError, CalibrationRejected, CalibrationNotExportable, CameraControlError, CoverageRejected, OccupancyRejected, PreferencesRejected};
+
+// Postgres SQLSTATEs that are the client's fault
+const UNIQUE_VIOLATION: &str = "23505";
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+const NOT_NULL_VIOLATION: &str = "23502";
+const CHECK_VIOLATION: &str = "23514";
+const INVALID_TEXT_REPRESENTATION: &str = "22P02";
+// Cancelled, e.g. by statement_timeout
+const QUERY_CANCELED: &str = "57014";
+
+// Error returned by every API handler. Serialized as `{ code, message }`.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    TooManyRequests(String),
+    #[error("{0}")]
+    ServiceUnavailable(String),
+    #[error(transparent)]
+    Internal(anyhow::Error),
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody<'a> {
+    code: &'static str,
+    message: &'a str,
+}
+
+impl ApiError {
+    pub fn internal(error: impl Into<anyhow::Error>) -> Self {
+        ApiError::Internal(error.into())
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::TooManyRequests(_) => "too_many_requests",
+            ApiError::ServiceUnavailable(_) => "service_unavailable",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    fn from_sqlx(error: &sqlx::Error) -> Option<Self> {
+        match error {
+            sqlx::Error::RowNotFound => Some(ApiError::NotFound("Resource not found".to_string())),
+            sqlx::Error::PoolTimedOut => Some(ApiError::ServiceUnavailable("Database is busy, try again".to_string())),
+            sqlx::Error::Database(db) => {
+                let message = db.message().to_string();
+                match db.code().as_deref() {
+                    Some(UNIQUE_VIOLATION) => Some(ApiError::Conflict(message)),
+                    Some(FOREIGN_KEY_VIOLATION | NOT_NULL_VIOLATION | CHECK_VIOLATION | INVALID_TEXT_REPRESENTATION) => {
+                        Some(ApiError::BadRequest(message))
+                    }
+                    Some(QUERY_CANCELED) => Some(ApiError::ServiceUnavailable("Database query timed out".to_string())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+// Services return anyhow; look through it for the errors we can classify
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        if let Some(api_error) = error.downcast_ref::<sqlx::Error>().and_then(ApiError::from_sqlx) {
+            return api_error;
+        }
+        if let Some(rejected) = error.downcast_ref::<CalibrationRejected>() {
+            return ApiError::BadRequest(rejected.to_string());
+        }
+        if let Some(images) = error.downcast_ref::<CalibrationImageError>() {
+            return match images {
+                CalibrationImageError::NotFound(_) => ApiError::NotFound(images.to_string()),
+                _ => ApiError::BadRequest(images.to_string()),
+            };
+        }
+        if let Some(rejected) = error.downcast_ref::<BundleRejected>() {
+            return ApiError::BadRequest(rejected.to_string());
+        }
+        if let Some(control) = error.downcast_ref::<CameraControlError>() {
+            return match control {
+                CameraControlError::Unsupported(_) => ApiError::Conflict(control.to_string()),
+                CameraControlError::RateLimited { .. } => ApiError::TooManyRequests(control.to_string()),
+                CameraControlError::Device(_) => ApiError::ServiceUnavailable(control.to_string()),
+            };
+        }
+        if let Some(map) = error.downcast_ref::<FacilityMapError>() {
+            return ApiError::BadRequest(map.to_string());
+        }
+        if let Some(uncalibrated) = error.downcast_ref::<CalibrationNotExportable>() {
+            return ApiError::Conflict(uncalibrated.to_string());
+        }
+        if let Some(rejected) = error.downcast_ref::<PreferencesRejected>() {
+            return ApiError::BadRequest(rejected.to_string());
+        }
+        if let Some(rejected) = error.downcast_ref::<CoverageRejected>() {
+            return ApiError::BadRequest(rejected.to_string());
+        }
+        if let Some(rejected) = error.downcast_ref::<OccupancyRejected>() {
+            return ApiError::BadRequest(rejected.to_string());
+        }
+        if let Some(validation) = error.downcast_ref::<validator::ValidationErrors>() {
+            return ApiError::BadRequest(validation.to_string());
+        }
+        ApiError::Internal(error)
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(error: sqlx::Error) -> Self {
+        ApiError::from_sqlx(&error).unwrap_or_else(|| ApiError::Internal(error.into()))
+    }
+}
+
+impl From<validator::ValidationErrors> for ApiError {
+    fn from(error: validator::ValidationErrors) -> Self {
+        ApiError::BadRequest(error.to_string())
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    // Internal details are logged, not sent to the client
+    fn error_response(&self) -> HttpResponse {
+        let message = match self {
+            ApiError::Internal(e) => {
+                error!("Internal error: {:#}", e);
+                "Internal server error".to_string()
+            }
+            other => other.to_string(),
+        };
+
+        HttpResponse::build(self.status_code()).json(ErrorBody {
+            code: self.code(),
+            message: &message,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+
+    async fn body_json(error: ApiError) -> (StatusCode, serde_json::Value) {
+        let response = error.error_response();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[actix_rt::test]
+    async fn test_row_not_found_is_404() {
+        let (status, body) = body_json(anyhow::Error::from(sqlx::Error::RowNotFound).into()).await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "not_found");
+    }
+
+    #[actix_rt::test]
+    async fn test_pool_timeout_is_503() {
+        let (status, body) = body_json(anyhow::Error::from(sqlx::Error::PoolTimedOut).into()).await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["code"], "service_unavailable");
+    }
+
+    #[actix_rt::test]
+    async fn test_internal_errors_hide_details() {
+        let (status, body) = body_json(anyhow::anyhow!("connection string leaked").into()).await;
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["code"], "internal_error");
+        assert_eq!(body["message"], "Internal server error");
+    }
+}
@@ -6,12 +6,23 @@ mod models;
 mod training;
 mod system;
 mod datasets;
+mod analytics;
+mod webrtc;
+mod nodes;
+mod world_model;
+mod users;
+mod error;
+pub mod server_timing;
 
-use actix_web::web;
+pub use error::ApiError;
+
+use actix_web::{middleware::from_fn, web};
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.configure(server_timing::configure);
     cfg.service(
         web::scope("/api/v1")
+            .wrap(from_fn(auth::authenticate))
             .configure(auth::configure)
             .configure(cameras::configure)
             .configure(calibration::configure)
@@ -20,5 +31,10 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .configure(training::configure)
             .configure(system::configure)
             .configure(datasets::configure)
+            .configure(analytics::configure)
+            .configure(webrtc::configure)
+            .configure(nodes::configure)
+            .configure(world_model::configure)
+            .configure(users::configure)
     );
 }
\ No newline at end of file
@@ -3,6 +3,7 @@ use uuid::Uuid;
 use serde_json::json;
 
 use crate::{
+    api::ApiError,
     models::{CreateModelRequest, UpdateModelRequest, DeploymentStatus},
     services::model_service::ModelService,
     AppState,
@@ -11,12 +12,11 @@ use crate::{
 #[get("/models")]
 async fn get_models(
     state: web::Data<AppState>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let model_service = ModelService::new(state.db_pool.clone());
     
     let models = model_service.get_all_models()
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(models))
 }
@@ -25,28 +25,40 @@ async fn get_models(
 async fn get_model(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let model_service = ModelService::new(state.db_pool.clone());
     let model_id = path.into_inner();
     
     let model = model_service.get_model(model_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(model))
 }
 
+#[get("/models/{id}/signature")]
+async fn get_model_signature(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let model_service = ModelService::new(state.db_pool.clone());
+    let model_id = path.into_inner();
+    
+    let signature = model_service.get_model_signature(model_id)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(signature))
+}
+
 #[get("/models/{name}/versions")]
 async fn get_model_versions(
     state: web::Data<AppState>,
     path: web::Path<String>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let model_service = ModelService::new(state.db_pool.clone());
     let model_name = path.into_inner();
     
     let versions = model_service.get_model_versions(&model_name)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(versions))
 }
@@ -56,12 +68,11 @@ async fn create_model(
     state: web::Data<AppState>,
     user_id: web::ReqData<Uuid>,
     model_data: web::Json<CreateModelRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let model_service = ModelService::new(state.db_pool.clone());
     
     let model = model_service.create_model(*user_id, model_data.into_inner())
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Created().json(model))
 }
@@ -71,13 +82,12 @@ async fn update_model(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     model_data: web::Json<UpdateModelRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let model_service = ModelService::new(state.db_pool.clone());
     let model_id = path.into_inner();
     
     let model = model_service.update_model(model_id, model_data.into_inner())
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(model))
 }
@@ -86,13 +96,12 @@ async fn update_model(
 async fn delete_model(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let model_service = ModelService::new(state.db_pool.clone());
     let model_id = path.into_inner();
     
     model_service.delete_model(model_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::NoContent().finish())
 }
@@ -103,15 +112,14 @@ async fn deploy_model(
     user_id: web::ReqData<Uuid>,
     path: web::Path<Uuid>,
     query: web::Query<HashMap<String, String>>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let model_service = ModelService::new(state.db_pool.clone());
     let model_id = path.into_inner();
     
     let deployed_to = query.get("deployed_to").map(|s| s.as_str()).unwrap_or("production");
     
     let deployment = model_service.deploy_model(model_id, deployed_to, *user_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(deployment))
 }
@@ -120,13 +128,12 @@ async fn deploy_model(
 async fn get_model_deployments(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let model_service = ModelService::new(state.db_pool.clone());
     let model_id = path.into_inner();
     
     let deployments = model_service.get_model_deployments(model_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(deployments))
 }
@@ -136,7 +143,7 @@ async fn update_deployment_status(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     query: web::Query<HashMap<String, String>>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let model_service = ModelService::new(state.db_pool.clone());
     let deployment_id = path.into_inner();
     
@@ -148,12 +155,11 @@ async fn update_deployment_status(
         "failed" => DeploymentStatus::Failed,
         "retiring" => DeploymentStatus::Retiring,
         "retired" => DeploymentStatus::Retired,
-        _ => return Err(actix_web::error::ErrorBadRequest("Invalid status")),
+        _ => return Err(ApiError::BadRequest("Invalid status".to_string())),
     };
     
     let deployment = model_service.update_deployment_status(deployment_id, status)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(deployment))
 }
@@ -161,6 +167,7 @@ async fn update_deployment_status(
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(get_models)
         .service(get_model)
+        .service(get_model_signature)
         .service(get_model_versions)
         .service(create_model)
         .service(update_model)
@@ -0,0 +1,247 @@
+use actix_web::{http::header, web, HttpRequest, HttpResponse, get, post, delete};
+use futures::StreamExt;
+use uuid::Uuid;
+
+use crate::{
+    api::ApiError,
+    models::{AssignCameraRequest, NodeConfigReport, NodeConfigStatus, NodeHeartbeat, NodeStatus, PushNodeConfigRequest, SampledFrameMetadata, UserRole},
+    services::{validate_node_config, AnnotationService, Claims, NodeService},
+    storage::FileStorage,
+    AppState,
+};
+
+// Largest sampled frame accepted, image and metadata together
+const MAX_SAMPLE_BYTES: usize = 16 * 1024 * 1024;
+
+#[get("/nodes")]
+async fn get_nodes(
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let node_service = NodeService::new(state.db_pool.clone());
+    
+    let nodes = node_service.list_nodes()
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(nodes))
+}
+
+#[get("/nodes/{id}")]
+async fn get_node(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let node_service = NodeService::new(state.db_pool.clone());
+    
+    let node = node_service.get_node(&path.into_inner())
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(node))
+}
+
+// Nodes post their health snapshot here periodically; the first one
+// registers the node
+#[post("/nodes/{id}/heartbeat")]
+async fn node_heartbeat(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    health: web::Json<serde_json::Value>,
+) -> Result<HttpResponse, ApiError> {
+    let node_service = NodeService::new(state.db_pool.clone());
+    let health = health.into_inner();
+    
+    let heartbeat: NodeHeartbeat = serde_json::from_value(health.clone())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid heartbeat: {}", e)))?;
+    if matches!(heartbeat.status, NodeStatus::Stale | NodeStatus::Unknown) {
+        return Err(ApiError::BadRequest("A node must report itself healthy, degraded or unhealthy".to_string()));
+    }
+    
+    let node = node_service.record_heartbeat(&path.into_inner(), heartbeat.status, health)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(node))
+}
+
+// Operators set the config a node should run; the node picks it up on its
+// next poll and reports back through /config/status
+#[post("/nodes/{id}/config")]
+async fn push_node_config(
+    state: web::Data<AppState>,
+    claims: Option<web::ReqData<Claims>>,
+    path: web::Path<String>,
+    request: web::Json<PushNodeConfigRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_node_operator(claims)?;
+    let node_service = NodeService::new(state.db_pool.clone());
+    let node_id = path.into_inner();
+    let config = request.into_inner().config;
+    
+    validate_node_config(&node_id, &config)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    let node = node_service.push_config(&node_id, config)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(node))
+}
+
+#[get("/nodes/{id}/config")]
+async fn get_node_config(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let node_service = NodeService::new(state.db_pool.clone());
+    let node_id = path.into_inner();
+    
+    let assignment = node_service.get_assignment(&node_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("No config assigned to node '{}'", node_id)))?;
+    
+    Ok(HttpResponse::Ok().json(assignment))
+}
+
+#[post("/nodes/{id}/config/status")]
+async fn report_node_config_status(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    report: web::Json<NodeConfigReport>,
+) -> Result<HttpResponse, ApiError> {
+    let node_service = NodeService::new(state.db_pool.clone());
+    let node_id = path.into_inner();
+    let report = report.into_inner();
+    
+    if report.status == NodeConfigStatus::Pending {
+        return Err(ApiError::BadRequest("A node can't report its config as pending".to_string()));
+    }
+    
+    let node = node_service.report_status(&node_id, &report)
+        .await?
+        .ok_or_else(|| ApiError::Conflict(format!("Config version {} is no longer current", report.version)))?;
+    
+    Ok(HttpResponse::Ok().json(node))
+}
+
+// What a node runs at startup instead of a camera list in its own YAML
+#[get("/nodes/{id}/cameras")]
+async fn get_node_cameras(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let node_service = NodeService::new(state.db_pool.clone());
+    
+    let cameras = node_service.get_assigned_cameras(&path.into_inner())
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(cameras))
+}
+
+#[post("/nodes/{id}/cameras")]
+async fn assign_camera(
+    state: web::Data<AppState>,
+    claims: Option<web::ReqData<Claims>>,
+    path: web::Path<String>,
+    request: web::Json<AssignCameraRequest>,
+) -> Result<HttpResponse, ApiError> {
+    require_node_operator(claims)?;
+    let node_service = NodeService::new(state.db_pool.clone());
+    let request = request.into_inner();
+    
+    let assignment = node_service.assign_camera(&path.into_inner(), request.camera_id, request.reassign)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Camera is assigned to another node; set reassign to move it".to_string()))?;
+    
+    Ok(HttpResponse::Ok().json(assignment))
+}
+
+#[delete("/nodes/{id}/cameras/{camera_id}")]
+async fn unassign_camera(
+    state: web::Data<AppState>,
+    claims: Option<web::ReqData<Claims>>,
+    path: web::Path<(String, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    require_node_operator(claims)?;
+    let node_service = NodeService::new(state.db_pool.clone());
+    let (node_id, camera_id) = path.into_inner();
+    
+    if !node_service.unassign_camera(&node_id, camera_id).await? {
+        return Err(ApiError::NotFound(format!("Camera {} is not assigned to node '{}'", camera_id, node_id)));
+    }
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Frames a node sampled for labeling, as multipart `metadata` (JSON) and
+// `image` (JPEG) parts. Each becomes a pending annotation pre-labelled
+// with the node's detections.
+#[post("/nodes/{id}/samples")]
+async fn ingest_sample(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+    request: HttpRequest,
+    mut payload: web::Payload,
+) -> Result<HttpResponse, ApiError> {
+    let boundary = request.headers().get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|content_type| multer::parse_boundary(content_type).ok())
+        .ok_or_else(|| ApiError::BadRequest("Expected a multipart/form-data body".to_string()))?;
+    
+    // Buffered first: the multipart parser needs a Send stream, which
+    // actix's payload isn't
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        if body.len() + chunk.len() > MAX_SAMPLE_BYTES {
+            return Err(ApiError::BadRequest(format!("Sample is larger than {} bytes", MAX_SAMPLE_BYTES)));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let body = body.freeze();
+    let mut multipart = multer::Multipart::new(futures::stream::once(async move { Ok::<_, std::io::Error>(body) }), boundary);
+    
+    let (mut metadata, mut image) = (None, None);
+    while let Some(field) = multipart.next_field().await.map_err(|e| ApiError::BadRequest(e.to_string()))? {
+        let name = field.name().map(str::to_string);
+        let bytes = field.bytes().await.map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        match name.as_deref() {
+            Some("metadata") => metadata = Some(bytes),
+            Some("image") => image = Some(bytes),
+            _ => {}
+        }
+    }
+    
+    let metadata: SampledFrameMetadata = metadata
+        .ok_or_else(|| ApiError::BadRequest("Missing metadata part".to_string()))
+        .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| ApiError::BadRequest(format!("Invalid metadata: {}", e))))?;
+    let image = image.ok_or_else(|| ApiError::BadRequest("Missing image part".to_string()))?;
+    if !image.starts_with(&[0xFF, 0xD8]) {
+        return Err(ApiError::BadRequest("Image part is not a JPEG".to_string()));
+    }
+    
+    let annotation_service = AnnotationService::new(state.db_pool.clone());
+    let storage = FileStorage::new(state.config.storage.annotations_dir.clone());
+    
+    let annotation = annotation_service.ingest_sample(&path.into_inner(), metadata, &image, &storage)
+        .await?;
+    
+    Ok(HttpResponse::Created().json(annotation))
+}
+
+fn require_node_operator(claims: Option<web::ReqData<Claims>>) -> Result<(), ApiError> {
+    let claims = claims.ok_or_else(|| ApiError::Unauthorized("Authentication required".to_string()))?;
+    match claims.role {
+        UserRole::Admin | UserRole::Operator => Ok(()),
+        UserRole::Viewer => Err(ApiError::Forbidden("Only operators can manage nodes".to_string())),
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_nodes)
+        .service(get_node)
+        .service(node_heartbeat)
+        .service(push_node_config)
+        .service(get_node_config)
+        .service(report_node_config_status)
+        .service(get_node_cameras)
+        .service(assign_camera)
+        .service(unassign_camera)
+        .service(ingest_sample);
+}
@@ -0,0 +1,114 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    get,
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    web, HttpResponse,
+};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::services::{HttpMetrics, RequestTiming};
+
+// Records each request's latency by endpoint and tells the client where the
+// time went: `db` is time spent in queries run on the DbPool, `handler` the
+// rest.
+pub async fn record(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let metrics = req.app_data::<web::Data<HttpMetrics>>().cloned();
+    let timing = Arc::new(RequestTiming::default());
+    let start = Instant::now();
+    
+    let mut res = timing.clone().scope(next.call(req)).await?;
+    
+    let total = start.elapsed();
+    let db = timing.db().min(total);
+    let handler = total - db;
+    
+    if let Some(metrics) = metrics {
+        let route = res.request().match_pattern().unwrap_or_else(|| "unmatched".to_string());
+        metrics.observe(res.request().method().as_str(), &route, res.status().as_u16(), total, db);
+    }
+    
+    let header = format!(
+        "db;dur={:.1}, handler;dur={:.1}, total;dur={:.1}",
+        db.as_secs_f64() * 1000.0,
+        handler.as_secs_f64() * 1000.0,
+        total.as_secs_f64() * 1000.0,
+    );
+    if let Ok(value) = HeaderValue::from_str(&header) {
+        res.headers_mut().insert(HeaderName::from_static("server-timing"), value);
+    }
+    
+    Ok(res)
+}
+
+// Prometheus scrape endpoint, outside the API prefix
+#[get("/metrics")]
+async fn get_metrics(
+    http_metrics: web::Data<HttpMetrics>,
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(http_metrics.encode())
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_metrics);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{middleware::from_fn, test, App};
+    use std::time::Duration;
+    
+    use crate::services::DbTimer;
+    
+    // Stands in for a handler making one 30ms query
+    async fn list_cameras() -> HttpResponse {
+        let _query = DbTimer::start();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        HttpResponse::Ok().finish()
+    }
+    
+    fn duration_of(header: &str, name: &str) -> f64 {
+        header
+            .split(", ")
+            .find_map(|entry| entry.strip_prefix(&format!("{};dur=", name)))
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+    
+    #[actix_rt::test]
+    async fn test_server_timing_header_and_latency_histogram() {
+        let metrics = web::Data::new(HttpMetrics::new());
+        let app = test::init_service(
+            App::new()
+                .app_data(metrics.clone())
+                .wrap(from_fn(record))
+                .route("/cameras/{id}", web::get().to(list_cameras))
+                .configure(configure),
+        )
+        .await;
+        
+        let res = test::call_service(&app, test::TestRequest::get().uri("/cameras/7").to_request()).await;
+        assert!(res.status().is_success());
+        
+        let header = res.headers().get("server-timing").unwrap().to_str().unwrap();
+        let db = duration_of(header, "db");
+        let total = duration_of(header, "total");
+        assert!(db >= 30.0, "db time {}ms in {}", db, header);
+        assert!(total >= db && (duration_of(header, "handler") - (total - db)).abs() < 0.2);
+        
+        // Recorded once, under the route pattern rather than the path
+        let scrape = test::call_and_read_body(&app, test::TestRequest::get().uri("/metrics").to_request()).await;
+        let scrape = String::from_utf8(scrape.to_vec()).unwrap();
+        assert!(scrape.contains(r#"aetherforge_http_request_duration_seconds_count{method="GET",route="/cameras/{id}",status="200"} 1"#), "{}", scrape);
+        assert!(scrape.contains(r#"aetherforge_http_request_db_seconds_count{method="GET",route="/cameras/{id}"} 1"#));
+    }
+}
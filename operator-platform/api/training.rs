@@ -3,6 +3,7 @@ use uuid::Uuid;
 use serde_json::json;
 
 use crate::{
+    api::ApiError,
     models::{CreateTrainingJobRequest, UpdateTrainingJobRequest},
     services::training_service::TrainingService,
     AppState,
@@ -11,12 +12,11 @@ use crate::{
 #[get("/training/jobs")]
 async fn get_training_jobs(
     state: web::Data<AppState>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let training_service = TrainingService::new(state.db_pool.clone());
     
     let jobs = training_service.get_all_training_jobs()
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(jobs))
 }
@@ -25,13 +25,12 @@ async fn get_training_jobs(
 async fn get_training_job(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let training_service = TrainingService::new(state.db_pool.clone());
     let job_id = path.into_inner();
     
     let job = training_service.get_training_job(job_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(job))
 }
@@ -41,12 +40,11 @@ async fn create_training_job(
     state: web::Data<AppState>,
     user_id: web::ReqData<Uuid>,
     job_data: web::Json<CreateTrainingJobRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let training_service = TrainingService::new(state.db_pool.clone());
     
     let job = training_service.create_training_job(*user_id, job_data.into_inner())
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Created().json(job))
 }
@@ -56,13 +54,12 @@ async fn update_training_job(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     job_data: web::Json<UpdateTrainingJobRequest>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let training_service = TrainingService::new(state.db_pool.clone());
     let job_id = path.into_inner();
     
     let job = training_service.update_training_job(job_id, job_data.into_inner())
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(job))
 }
@@ -71,13 +68,12 @@ async fn update_training_job(
 async fn delete_training_job(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let training_service = TrainingService::new(state.db_pool.clone());
     let job_id = path.into_inner();
     
     training_service.delete_training_job(job_id)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::NoContent().finish())
 }
@@ -85,12 +81,11 @@ async fn delete_training_job(
 #[get("/training/stats")]
 async fn get_training_stats(
     state: web::Data<AppState>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let training_service = TrainingService::new(state.db_pool.clone());
     
     let stats = training_service.get_training_job_stats()
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(stats))
 }
@@ -99,13 +94,12 @@ async fn get_training_stats(
 async fn get_training_summaries(
     state: web::Data<AppState>,
     query: web::Query<HashMap<String, i64>>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let training_service = TrainingService::new(state.db_pool.clone());
     
     let limit = query.get("limit").cloned();
     let summaries = training_service.get_training_job_summaries(limit)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(summaries))
 }
@@ -115,15 +109,14 @@ async fn add_training_log(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     log_data: web::Json<HashMap<String, String>>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let training_service = TrainingService::new(state.db_pool.clone());
     let job_id = path.into_inner();
     
     let log = log_data.get("log").map(|s| s.as_str()).unwrap_or("");
     
     let job = training_service.add_training_log(job_id, log)
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(job))
 }
@@ -131,12 +124,11 @@ async fn add_training_log(
 #[get("/training/active")]
 async fn get_active_training_jobs(
     state: web::Data<AppState>,
-) -> Result<HttpResponse, actix_web::Error> {
+) -> Result<HttpResponse, ApiError> {
     let training_service = TrainingService::new(state.db_pool.clone());
     
     let jobs = training_service.get_active_training_jobs()
-        .await
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .await?;
     
     Ok(HttpResponse::Ok().json(jobs))
 }
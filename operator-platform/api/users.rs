@@ -0,0 +1,42 @@
+use actix_web::{web, HttpResponse, get, put};
+use uuid::Uuid;
+
+use crate::{
+    api::ApiError,
+    services::UserPreferencesService,
+    AppState,
+};
+
+#[get("/users/me/preferences")]
+async fn get_preferences(
+    state: web::Data<AppState>,
+    user_id: Option<web::ReqData<Uuid>>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = user_id.ok_or_else(|| ApiError::Unauthorized("Authentication required".to_string()))?;
+    let preferences_service = UserPreferencesService::new(state.db_pool.clone());
+
+    let preferences = preferences_service.get(*user_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(preferences))
+}
+
+// Replaces the caller's preferences with the body, a JSON object
+#[put("/users/me/preferences")]
+async fn put_preferences(
+    state: web::Data<AppState>,
+    user_id: Option<web::ReqData<Uuid>>,
+    preferences: web::Json<serde_json::Value>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = user_id.ok_or_else(|| ApiError::Unauthorized("Authentication required".to_string()))?;
+    let preferences_service = UserPreferencesService::new(state.db_pool.clone());
+
+    let preferences = preferences_service.put(*user_id, &preferences)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(preferences))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_preferences).service(put_preferences);
+}
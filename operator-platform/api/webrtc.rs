@@ -0,0 +1,96 @@
+use actix_web::{web, HttpResponse, post, delete};
+use uuid::Uuid;
+use serde_json::json;
+
+use crate::{
+    api::ApiError,
+    api::camera::authorize_camera_view,
+    models::{WebRtcOfferRequest, WebRtcAnswer, IceCandidateRequest},
+    AppState,
+};
+
+#[post("/cameras/{id}/webrtc/offer")]
+async fn create_webrtc_session(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    offer: web::Json<WebRtcOfferRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let camera_id = path.into_inner();
+    let camera = authorize_camera_view(&state, *user_id, camera_id).await?;
+    
+    let rtsp_url = camera.rtsp_url
+        .ok_or_else(|| ApiError::BadRequest("Camera has no RTSP stream".to_string()))?;
+    
+    let (session_id, sdp) = state.webrtc_sessions
+        .create_session(camera_id, offer.into_inner().sdp, Some(&rtsp_url))
+        .await
+        .map_err(|e| ApiError::ServiceUnavailable(e.to_string()))?;
+    
+    Ok(HttpResponse::Created().json(WebRtcAnswer { session_id, sdp }))
+}
+
+#[post("/webrtc/sessions/{id}/renegotiate")]
+async fn renegotiate_webrtc_session(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    offer: web::Json<WebRtcOfferRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let session_id = path.into_inner();
+    authorize_session(&state, *user_id, session_id).await?;
+    
+    let sdp = state.webrtc_sessions.renegotiate(session_id, offer.into_inner().sdp)
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    Ok(HttpResponse::Ok().json(WebRtcAnswer { session_id, sdp }))
+}
+
+#[post("/webrtc/sessions/{id}/candidates")]
+async fn add_ice_candidate(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+    candidate: web::Json<IceCandidateRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let session_id = path.into_inner();
+    authorize_session(&state, *user_id, session_id).await?;
+    
+    state.webrtc_sessions.add_ice_candidate(session_id, candidate.into_inner())
+        .await
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    Ok(HttpResponse::Accepted().json(json!({"message": "Candidate added"})))
+}
+
+#[delete("/webrtc/sessions/{id}")]
+async fn close_webrtc_session(
+    state: web::Data<AppState>,
+    user_id: web::ReqData<Uuid>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let session_id = path.into_inner();
+    authorize_session(&state, *user_id, session_id).await?;
+    
+    state.webrtc_sessions.close_session(session_id)
+        .await?;
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Session operations are authorized against the session's camera zone
+async fn authorize_session(state: &AppState, user_id: Uuid, session_id: Uuid) -> Result<(), ApiError> {
+    let camera_id = state.webrtc_sessions.session_camera(session_id)
+        .ok_or_else(|| ApiError::NotFound("WebRTC session not found".to_string()))?;
+    
+    authorize_camera_view(state, user_id, camera_id).await?;
+    Ok(())
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_webrtc_session)
+        .service(renegotiate_webrtc_session)
+        .service(add_ice_candidate)
+        .service(close_webrtc_session);
+}
\ No newline at end of file
@@ -0,0 +1,110 @@
+use actix_web::{web, HttpResponse, get, post, put};
+use futures::StreamExt;
+
+use aetherforge_common::world_model::WorldModel;
+
+use crate::{
+    api::ApiError,
+    models::{FacilityMapQuery, WorldModelQuery, WorldModelRangeQuery},
+    services::{Claims, FacilityMapService, WorldModelService},
+    AppState,
+};
+
+const MAX_FACILITY_MAP_BYTES: usize = 16 * 1024 * 1024;
+
+// The facility aggregator posts a world model here every persist interval
+#[post("/worldmodel")]
+async fn save_world_model(
+    state: web::Data<AppState>,
+    world_model: web::Json<WorldModel>,
+) -> Result<HttpResponse, ApiError> {
+    let world_model_service = WorldModelService::new(state.db_pool.clone(), state.config.monitoring.world_model_retention_hours);
+    
+    let snapshot = world_model_service.save_snapshot(&world_model)
+        .await?;
+    
+    Ok(HttpResponse::Created().json(snapshot))
+}
+
+#[get("/worldmodel")]
+async fn get_world_model(
+    state: web::Data<AppState>,
+    query: web::Query<WorldModelQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let world_model_service = WorldModelService::new(state.db_pool.clone(), state.config.monitoring.world_model_retention_hours);
+    
+    let snapshot = world_model_service.nearest_snapshot(query.zone.as_deref(), query.at)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("No world model snapshots recorded".to_string()))?;
+    
+    Ok(HttpResponse::Ok().json(snapshot))
+}
+
+#[get("/worldmodel/range")]
+async fn get_world_model_range(
+    state: web::Data<AppState>,
+    query: web::Query<WorldModelRangeQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let world_model_service = WorldModelService::new(state.db_pool.clone(), state.config.monitoring.world_model_retention_hours);
+    
+    if query.to <= query.from {
+        return Err(ApiError::BadRequest("Invalid world model range".to_string()));
+    }
+    
+    let snapshots = world_model_service.snapshots_between(&query)
+        .await?;
+    
+    Ok(HttpResponse::Ok().json(snapshots))
+}
+
+// Takes the facility map JSON as the body; invalid maps are rejected with
+// the first problem found
+#[put("/facility/map")]
+async fn upload_facility_map(
+    state: web::Data<AppState>,
+    claims: Option<web::ReqData<Claims>>,
+    mut payload: web::Payload,
+) -> Result<HttpResponse, ApiError> {
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        if body.len() + chunk.len() > MAX_FACILITY_MAP_BYTES {
+            return Err(ApiError::BadRequest(format!("Facility map is larger than {} bytes", MAX_FACILITY_MAP_BYTES)));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let json = std::str::from_utf8(&body)
+        .map_err(|_| ApiError::BadRequest("Facility map is not UTF-8".to_string()))?;
+    
+    let facility_map_service = FacilityMapService::new(state.db_pool.clone());
+    
+    let record = facility_map_service.upload(json, claims.map(|claims| claims.sub))
+        .await?;
+    
+    Ok(HttpResponse::Created().json(record))
+}
+
+#[get("/facility/map")]
+async fn download_facility_map(
+    state: web::Data<AppState>,
+    query: web::Query<FacilityMapQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let facility_map_service = FacilityMapService::new(state.db_pool.clone());
+    
+    let map = facility_map_service.current(query.facility.as_deref())
+        .await?
+        .ok_or_else(|| ApiError::NotFound("No facility map uploaded".to_string()))?;
+    
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}.json\"", map.facility_id)))
+        .body(map.to_json()))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(save_world_model)
+        .service(get_world_model)
+        .service(get_world_model_range)
+        .service(upload_facility_map)
+        .service(download_facility_map);
+}
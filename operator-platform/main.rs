@@ -1,9 +1,8 @@
 use actix_cors::Cors;
-use actix_web::{web, App, HttpServer};
+use actix_web::{middleware::from_fn, web, App, HttpServer};
 use anyhow::Result;
-use sqlx::postgres::PgPool;
 use std::sync::Arc;
-use tokio::time::Duration;
+use std::time::Duration;
 
 mod api;
 mod config;
@@ -12,13 +11,31 @@ mod services;
 mod storage;
 
 use config::OperatorConfig;
-use storage::{create_db_pool, FileStorage};
-use services::camera_monitor::CameraMonitor;
+use storage::{create_db_pool, verify_enum_types, DbPool, FileStorage};
+use services::alert_debounce::AlertDebounceService;
+use services::camera_monitor::{CameraMonitor, SweepTracker};
+use services::NodeMonitor;
+use services::EventRetention;
+use services::RetrainingMonitor;
+use services::live_stream::LiveStreamManager;
+use services::webrtc_session::WebRtcSessionManager;
+use services::SigningKeys;
+use services::HttpMetrics;
+use services::CameraControl;
+use services::DetectionPersistenceService;
+use services::{serve_until_shutdown, termination_signal, Shutdown};
 
 pub struct AppState {
-    db_pool: PgPool,
+    db_pool: DbPool,
     file_storage: FileStorage,
     config: OperatorConfig,
+    live_streams: Arc<LiveStreamManager>,
+    webrtc_sessions: Arc<WebRtcSessionManager>,
+    alert_debounce: Arc<AlertDebounceService>,
+    signing_keys: Arc<SigningKeys>,
+    camera_control: Arc<CameraControl>,
+    detection_persistence: Arc<DetectionPersistenceService>,
+    camera_sweeps: Arc<SweepTracker>,
 }
 
 #[actix_web::main]
@@ -27,46 +44,123 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
     
     // Load configuration
-    let config = OperatorConfig::default();
+    let mut config = OperatorConfig::default();
+    config.resolve_secrets()?;
     
     // Initialize database
-    let db_pool = create_db_pool(&config.database.url, config.database.max_connections).await?;
+    let db_pool = create_db_pool(&config.database).await?;
+    verify_enum_types(&db_pool).await?;
     
     // Initialize file storage
     let file_storage = FileStorage::new(config.storage.data_dir.clone());
     
+    // Background tasks stop when this is triggered on shutdown
+    let shutdown = Shutdown::new();
+    let mut background = Vec::new();
+    
     // Start camera monitor
-    let camera_monitor = CameraMonitor::new(
-        db_pool.clone(),
-        Duration::from_secs(config.monitoring.health_check_interval_sec),
-    );
+    let camera_monitor = CameraMonitor::new(db_pool.clone(), &config.monitoring, &config.streaming);
+    let camera_sweeps = camera_monitor.sweeps();
     
-    tokio::spawn(async move {
-        if let Err(e) = camera_monitor.start().await {
+    let stop = shutdown.subscribe();
+    background.push(tokio::spawn(async move {
+        if let Err(e) = camera_monitor.start(stop).await {
             tracing::error!("Camera monitor failed: {}", e);
         }
-    });
+    }));
+    
+    // Mark perception nodes stale when their heartbeats stop
+    let node_monitor = NodeMonitor::new(db_pool.clone(), &config.monitoring);
+    
+    let stop = shutdown.subscribe();
+    background.push(tokio::spawn(async move {
+        if let Err(e) = node_monitor.start(stop).await {
+            tracing::error!("Node monitor failed: {}", e);
+        }
+    }));
+    
+    // Purge acknowledged system events past alert retention
+    let event_retention = EventRetention::new(db_pool.clone(), &config.monitoring);
+    
+    let stop = shutdown.subscribe();
+    background.push(tokio::spawn(async move {
+        if let Err(e) = event_retention.start(stop).await {
+            tracing::error!("Event retention failed: {}", e);
+        }
+    }));
+    
+    // Retrain deployed models whose confidence degrades, if enabled
+    let retraining_monitor = RetrainingMonitor::new(db_pool.clone(), &config.ml);
+    
+    let stop = shutdown.subscribe();
+    background.push(tokio::spawn(async move {
+        if let Err(e) = retraining_monitor.start(stop).await {
+            tracing::error!("Retraining monitor failed: {}", e);
+        }
+    }));
+    
+    // Debounce perception alerts before they reach system_events
+    let alert_debounce = Arc::new(AlertDebounceService::new(db_pool.clone(), &config.monitoring));
+    
+    let alert_expiry = alert_debounce.clone();
+    let stop = shutdown.subscribe();
+    background.push(tokio::spawn(async move {
+        if let Err(e) = alert_expiry.start(stop).await {
+            tracing::error!("Alert debouncer failed: {}", e);
+        }
+    }));
+    
+    // Live camera previews share one upstream pull per camera
+    let live_streams = LiveStreamManager::new(config.streaming.clone());
+    let webrtc_sessions = WebRtcSessionManager::new(config.streaming.clone())?;
+    
+    // JWT secrets, rotatable at runtime
+    let signing_keys = Arc::new(SigningKeys::new(&config.auth));
+    
+    // PTZ and exposure passthrough, throttled per camera
+    let camera_control = Arc::new(CameraControl::new(&config.camera_control));
+    
+    // Reported detections, kept in full around significant alerts
+    let detection_persistence = Arc::new(DetectionPersistenceService::new(db_pool.clone(), &config.detections));
     
     // Create app state
     let app_state = web::Data::new(AppState {
         db_pool,
         file_storage,
         config,
+        live_streams,
+        webrtc_sessions,
+        alert_debounce,
+        signing_keys,
+        camera_control,
+        detection_persistence,
+        camera_sweeps,
     });
     
-    // Start HTTP server
+    // Per-endpoint latency, scraped from /metrics
+    let http_metrics = web::Data::new(HttpMetrics::new());
+    
+    // Start HTTP server. Signals are handled below rather than by actix, so
+    // background tasks and the pool are shut down along with it.
+    let shutdown_timeout = Duration::from_secs(app_state.config.server.shutdown_timeout_sec);
+    let db_pool = app_state.db_pool.clone();
+    let server_state = app_state.clone();
     let server = HttpServer::new(move || {
         let cors = Cors::default()
-            .allowed_origins(&app_state.config.server.cors_origins)
+            .allowed_origins(&server_state.config.server.cors_origins)
             .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
             .allowed_headers(vec!["Content-Type", "Authorization"])
             .max_age(3600);
         
         App::new()
-            .app_data(app_state.clone())
+            .app_data(server_state.clone())
+            .app_data(http_metrics.clone())
             .wrap(cors)
+            .wrap(from_fn(api::server_timing::record))
             .configure(api::configure)
     })
+    .disable_signals()
+    .shutdown_timeout(shutdown_timeout.as_secs())
     .bind((app_state.config.server.host.clone(), app_state.config.server.port))?
     .run();
     
@@ -76,7 +170,5 @@ async fn main() -> Result<()> {
         app_state.config.server.port
     );
     
-    server.await?;
-    
-    Ok(())
+    serve_until_shutdown(server, termination_signal(), &shutdown, background, &db_pool, shutdown_timeout).await
 }
\ No newline at end of file
@@ -9,7 +9,7 @@ pub struct Annotation {
     pub id: Uuid,
     pub image_path: String,
     pub camera_id: Uuid,
-    pub created_by: Uuid,
+    pub created_by: Option<Uuid>, // None for frames sampled by a perception node
     pub annotations: serde_json::Value,
     pub status: AnnotationStatus,
     pub reviewed: bool,
@@ -17,6 +17,7 @@ pub struct Annotation {
     pub reviewed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub source_node_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, sqlx::Type)]
@@ -41,6 +42,41 @@ pub struct UpdateAnnotationRequest {
     pub reviewed: Option<bool>,
 }
 
+// The `metadata` part of a frame a perception node sampled for labeling;
+// the JPEG arrives alongside as the `image` part
+#[derive(Debug, Deserialize)]
+pub struct SampledFrameMetadata {
+    pub camera_id: String, // the camera's device_id, as the node knows it
+    pub timestamp: u64,
+    pub width: u32,
+    pub height: u32,
+    pub detections: Vec<aetherforge_common::types::Detection>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PredictQuery {
+    pub model_id: Uuid,
+}
+
+// A model's detections on an annotation's image beside the stored labels,
+// both as [{label, bbox: [xmin, ymin, xmax, ymax], ...}] in image pixels
+#[derive(Debug, Serialize)]
+pub struct AnnotationPrediction {
+    pub annotation_id: Uuid,
+    pub model_id: Uuid,
+    pub model_version: String,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub predictions: serde_json::Value,
+    pub annotations: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkReviewRequest {
+    pub ids: Vec<Uuid>,
+    pub status: AnnotationStatus,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AnnotationTask {
     pub id: Uuid,
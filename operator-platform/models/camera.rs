@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
@@ -14,6 +15,7 @@ pub struct Camera {
     pub zone: Option<String>,
     pub stream_url: String,
     pub rtsp_url: Option<String>,
+    pub onvif_url: Option<String>, // ONVIF device service, for PTZ and exposure control
     pub status: CameraStatus,
     pub health_status: CameraHealthStatus,
     pub last_ping: Option<DateTime<Utc>>,
@@ -24,6 +26,7 @@ pub struct Camera {
     pub extrinsics: Option<serde_json::Value>,
     pub calibration_status: CalibrationStatus,
     pub last_calibration: Option<DateTime<Utc>>,
+    pub calibration_target_accuracy: Option<f32>, // of the calibration last started, see CalibrationRequest
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -78,6 +81,9 @@ pub struct CreateCameraRequest {
     #[validate(url)]
     pub rtsp_url: Option<String>,
     
+    #[validate(url)]
+    pub onvif_url: Option<String>,
+    
     pub fps: Option<f32>,
     
     pub resolution_width: Option<i32>,
@@ -106,6 +112,9 @@ pub struct UpdateCameraRequest {
     #[validate(url)]
     pub rtsp_url: Option<String>,
     
+    #[validate(url)]
+    pub onvif_url: Option<String>,
+    
     pub status: Option<CameraStatus>,
     
     pub health_status: Option<CameraHealthStatus>,
@@ -117,6 +126,51 @@ pub struct UpdateCameraRequest {
     pub resolution_height: Option<i32>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PtzMode {
+    Continuous, // pan/tilt/zoom are velocities
+    Relative, // ... offsets from the current position
+    Absolute, // ... a position
+    Stop,
+}
+
+// Body of POST /cameras/{id}/ptz, in ONVIF's normalized generic spaces
+#[derive(Debug, Deserialize, Validate)]
+pub struct PtzRequest {
+    pub mode: PtzMode,
+    #[serde(default)]
+    #[validate(range(min = -1.0, max = 1.0))]
+    pub pan: f32,
+    #[serde(default)]
+    #[validate(range(min = -1.0, max = 1.0))]
+    pub tilt: f32,
+    #[serde(default)]
+    #[validate(range(min = -1.0, max = 1.0))]
+    pub zoom: f32,
+    #[validate(range(min = 1, max = 60000))]
+    pub timeout_ms: Option<u64>, // continuous moves stop on their own after this
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExposureMode {
+    Auto,
+    Manual,
+}
+
+// Body of POST /cameras/{id}/exposure; unset values are left as they are
+#[derive(Debug, Deserialize, Validate)]
+pub struct ExposureRequest {
+    pub mode: ExposureMode,
+    #[validate(range(min = 0.0))]
+    pub exposure_time_us: Option<f32>,
+    #[validate(range(min = 0.0))]
+    pub gain: Option<f32>,
+    #[validate(range(min = 0.0))]
+    pub iris: Option<f32>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CameraCalibrationData {
     pub camera_id: Uuid,
@@ -145,6 +199,24 @@ pub struct CalibrationRequest {
     pub square_size: f32,
 }
 
+// Result of a finished calibration run. `calibration_accuracy` is the
+// reprojection error; it must not exceed the `target_accuracy` the run was
+// started with, when one was set.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CalibrationResultRequest {
+    pub intrinsics: serde_json::Value,
+    
+    pub extrinsics: serde_json::Value,
+    
+    pub calibration_method: String,
+    
+    #[validate(range(min = 0))]
+    pub calibration_accuracy: f32,
+    
+    #[serde(default)]
+    pub calibration_images: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "calibration_pattern", rename_all = "lowercase")]
 pub enum CalibrationPattern {
@@ -153,7 +225,7 @@ pub enum CalibrationPattern {
     AsymmetricCircles,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub struct CameraHealthMetrics {
     pub camera_id: Uuid,
     pub timestamp: DateTime<Utc>,
@@ -167,6 +239,14 @@ pub struct CameraHealthMetrics {
     pub memory_usage: f32,
 }
 
+// Outcome of writing one sweep's health metrics
+#[derive(Debug, Default)]
+pub struct HealthMetricsWrite {
+    pub inserted: u64,
+    pub statements: usize, // 1 unless the batch failed and rows were retried singly
+    pub failed: Vec<Uuid>, // cameras whose row could not be written
+}
+
 #[derive(Debug, Serialize)]
 pub struct CameraStatusHistory {
     pub camera_id: Uuid,
@@ -176,6 +256,34 @@ pub struct CameraStatusHistory {
     pub message: Option<String>,
 }
 
+// Cameras by where their calibration stands. One still using an earlier
+// calibration while a new run is in progress or after one failed counts by
+// that earlier calibration.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct CalibrationCoverage {
+    pub total: usize,
+    pub calibrated: usize,
+    pub needs_recalibration: usize, // flagged, or calibrated longer ago than the staleness window
+    pub not_calibrated: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaleCalibration {
+    pub camera_id: Uuid,
+    pub name: String,
+    pub zone: Option<String>,
+    pub last_calibration: DateTime<Utc>,
+}
+
+// Response of GET /cameras/calibration/summary. Cameras without a zone are
+// only counted in `overall`.
+#[derive(Debug, Serialize)]
+pub struct CalibrationSummary {
+    pub overall: CalibrationCoverage,
+    pub zones: BTreeMap<String, CalibrationCoverage>,
+    pub stale: Vec<StaleCalibration>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CameraZone {
     pub id: Uuid,
@@ -185,4 +293,41 @@ pub struct CameraZone {
     pub camera_count: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
-}
\ No newline at end of file
+}
+
+// Query of GET /analytics/coverage. Bounds are floor coordinates in
+// meters, in the same world frame as the camera extrinsics.
+#[derive(Debug, Deserialize)]
+pub struct CoverageQuery {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+    pub cell_size: Option<f64>, // meters, 1.0 if unset
+    pub max_range: Option<f64>, // ignore floor further than this from a camera
+    pub zone: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct GridCell {
+    pub column: usize,
+    pub row: usize,
+    pub x: f64, // cell center
+    pub y: f64,
+}
+
+// Floor coverage rasterized over a grid. `coverage[row][column]` counts the
+// cameras that see the center of that cell.
+#[derive(Debug, Serialize)]
+pub struct CoverageMap {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+    pub coverage: Vec<Vec<u32>>,
+    pub covered_fraction: f64,
+    pub uncovered: Vec<GridCell>,
+    pub cameras: Vec<Uuid>,
+    pub uncalibrated: Vec<Uuid>, // skipped for lack of usable intrinsics or extrinsics
+}
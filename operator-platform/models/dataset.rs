@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct Dataset {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateDatasetRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddDatasetAnnotationsRequest {
+    pub annotation_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DatasetVersion {
+    pub id: Uuid,
+    pub dataset_id: Uuid,
+    pub version: i32,
+    pub content_hash: String,
+    pub annotation_count: i32,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DatasetVersionAnnotation {
+    pub annotation_id: Uuid,
+    pub image_path: String,
+    pub annotations: serde_json::Value,
+    pub content_hash: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatasetVersionDetail {
+    #[serde(flatten)]
+    pub version: DatasetVersion,
+    pub annotations: Vec<DatasetVersionAnnotation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "dataset_split", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetSplit {
+    Train,
+    Val,
+    Test,
+}
+
+impl DatasetSplit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DatasetSplit::Train => "train",
+            DatasetSplit::Val => "val",
+            DatasetSplit::Test => "test",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SplitRatios {
+    pub train: f64,
+    pub val: f64,
+    #[serde(default)]
+    pub test: f64,
+}
+
+impl SplitRatios {
+    // Train/val only, as described by `MLPipelineConfig::validation_split`
+    pub fn from_validation_split(validation_split: f32) -> Self {
+        let val = validation_split as f64;
+        Self { train: 1.0 - val, val, test: 0.0 }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateSplitsRequest {
+    pub ratios: Option<SplitRatios>, // defaults to the configured validation split
+    #[serde(default)]
+    pub seed: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DatasetSplitAssignment {
+    pub image_path: String,
+    pub split: DatasetSplit,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatasetSplitSummary {
+    pub dataset_id: Uuid,
+    pub train: usize,
+    pub val: usize,
+    pub test: usize,
+    pub newly_assigned: u64,
+}
+
+// How often each label occurs in a dataset, with balancing hints for training
+#[derive(Debug, Serialize)]
+pub struct ClassDistribution {
+    pub dataset_id: Uuid,
+    pub images: usize,
+    pub instances: usize,
+    pub classes: Vec<ClassBalance>, // most frequent first
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClassBalance {
+    pub label: String,
+    pub instances: usize,
+    pub images: usize,           // images with at least one instance
+    pub frequency: f64,          // share of all instances
+    pub weight: f64,             // loss weight; 1.0 when every class is equally common
+    pub oversample_factor: f64,  // how often to repeat its images to match the most common class
+}
+
+impl ClassDistribution {
+    // The hyperparameters a training job reads balanced sampling from
+    pub fn sampling_hints(&self) -> serde_json::Value {
+        let weights: serde_json::Map<String, serde_json::Value> =
+            self.classes.iter().map(|c| (c.label.clone(), c.weight.into())).collect();
+        let oversampling: serde_json::Map<String, serde_json::Value> =
+            self.classes.iter().map(|c| (c.label.clone(), c.oversample_factor.into())).collect();
+        serde_json::json!({ "class_weights": weights, "oversampling": oversampling })
+    }
+}
+
+// manifest.json at the root of an exported dataset bundle. Every other file
+// in the bundle is listed with its SHA-256, so a copy can be checked before
+// it's trained on.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct DatasetBundleManifest {
+    pub format_version: u32,
+    pub dataset_id: Uuid,
+    pub dataset_name: String,
+    pub version_id: Uuid,
+    pub version: i32,
+    pub content_hash: String, // the version's, over its annotations
+    pub created_at: DateTime<Utc>,
+    pub classes: Vec<DatasetBundleClass>, // COCO category ids
+    pub splits: BTreeMap<String, DatasetSplit>, // by image file; images never assigned a split are left out
+    pub files: Vec<DatasetBundleFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DatasetBundleClass {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DatasetBundleFile {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
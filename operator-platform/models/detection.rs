@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use aetherforge_common::types::{PerceptionFrame, TrackHandoff};
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DetectionRecord {
+    pub id: Uuid,
+    pub camera_id: Uuid,
+    pub frame_id: i64,
+    pub tracker_id: Option<i64>,
+    pub class_id: i32,
+    pub class_label: String,
+    pub confidence: f64, // FLOAT columns are double precision
+    pub xmin: f64,
+    pub ymin: f64,
+    pub xmax: f64,
+    pub ymax: f64,
+    pub model_version: String,
+    pub detected_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OccupancyQuery {
+    pub zone: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub bucket: Option<i64>, // bucket width in seconds
+}
+
+#[derive(Debug)]
+pub struct OccupancySample {
+    pub detected_at: DateTime<Utc>,
+    pub tracker_id: i64,
+    pub class_label: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct OccupancyBucket {
+    pub bucket_start: DateTime<Utc>,
+    pub humans: i64,
+    pub robots: i64,
+}
+#[derive(Debug, Deserialize)]
+pub struct DetectionExportQuery {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct TrackHandoffRecord {
+    pub id: Uuid,
+    pub node_id: String,
+    pub global_track_id: i64,
+    pub from_camera: String,
+    pub to_camera: String,
+    pub overlap_confidence: f64,
+    pub handed_off_at: DateTime<Utc>,
+}
+
+// Body of POST /analytics/handoffs, sent by a node's fusion engine
+#[derive(Debug, Deserialize)]
+pub struct RecordHandoffsRequest {
+    pub node_id: String,
+    pub handoffs: Vec<TrackHandoff>,
+}
+
+// Body of POST /analytics/detections; only a sample of the frames is stored,
+// see `PersistenceSampler`
+#[derive(Debug, Deserialize)]
+pub struct RecordDetectionsRequest {
+    pub node_id: String,
+    pub frames: Vec<PerceptionFrame>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HandoffQuery {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub camera: Option<String>, // handoffs into or out of this camera
+    pub limit: Option<i64>,
+}
@@ -4,10 +4,20 @@ mod calibration;
 mod annotation;
 mod model;
 mod training_job;
+mod detection;
+mod streaming;
+mod dataset;
+mod node;
+mod world_model;
 
 pub use user::*;
 pub use camera::*;
 pub use calibration::*;
 pub use annotation::*;
 pub use model::*;
-pub use training_job::*;
\ No newline at end of file
+pub use training_job::*;
+pub use detection::*;
+pub use streaming::*;
+pub use dataset::*;
+pub use node::*;
+pub use world_model::*;
\ No newline at end of file
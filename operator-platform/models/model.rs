@@ -105,4 +105,21 @@ pub enum DeploymentStatus {
     Failed,
     Retiring,
     Retired,
+}
+
+// Tensor as reported by the ONNX runtime; dynamic dimensions are -1
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct TensorSignature {
+    pub name: String,
+    pub shape: Vec<i64>,
+    pub dtype: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelSignature {
+    pub model_id: Uuid,
+    pub inputs: Vec<TensorSignature>,
+    pub outputs: Vec<TensorSignature>,
+    // Differences from the declared input_shape/output_shape
+    pub mismatches: Vec<String>,
 }
\ No newline at end of file
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// A perception node and the config operators want it to run
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PerceptionNode {
+    pub node_id: String,
+    pub desired_config: Option<serde_json::Value>, // the node's PerceptionConfig, as JSON
+    pub config_version: i64,
+    pub applied_version: Option<i64>,
+    pub config_status: NodeConfigStatus,
+    pub status_message: Option<String>,
+    pub restart_required: Vec<String>, // changed fields the node can't apply without a restart
+    pub status: NodeStatus,
+    pub health: Option<serde_json::Value>, // body of the last heartbeat
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Nodes send the first three in heartbeats (capitalized, as the node's
+// NodeStatus serializes); the monitor sets Stale when heartbeats stop
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "node_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum NodeStatus {
+    #[serde(alias = "Healthy")]
+    Healthy,
+    #[serde(alias = "Degraded")]
+    Degraded,
+    #[serde(alias = "Unhealthy")]
+    Unhealthy,
+    Stale,
+    Unknown, // registered by a config push, no heartbeat yet
+}
+
+// Only the status is interpreted; the whole body is kept as the node's health
+#[derive(Debug, Deserialize)]
+pub struct NodeHeartbeat {
+    pub status: NodeStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "node_config_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum NodeConfigStatus {
+    Pending,  // pushed, not yet picked up
+    Applied,
+    Rejected, // valid, but changes fields that need a restart
+    Invalid,  // failed to parse or validate on the node
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PushNodeConfigRequest {
+    pub config: serde_json::Value,
+}
+
+// What a node fetches when it polls for its config
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeConfigAssignment {
+    pub version: i64,
+    pub config: serde_json::Value,
+}
+
+// What a node reports back after trying to apply an assignment
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeConfigReport {
+    pub version: i64,
+    pub status: NodeConfigStatus,
+    pub message: Option<String>,
+    #[serde(default)]
+    pub restart_required: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct CameraAssignment {
+    pub camera_id: Uuid,
+    pub node_id: String,
+    pub assigned_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignCameraRequest {
+    pub camera_id: Uuid,
+    #[serde(default)]
+    pub reassign: bool, // move the camera if another node has it
+}
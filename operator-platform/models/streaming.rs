@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct WebRtcOfferRequest {
+    pub sdp: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebRtcAnswer {
+    pub session_id: Uuid,
+    pub sdp: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IceCandidateRequest {
+    pub candidate: String,
+    pub sdp_mid: Option<String>,
+    pub sdp_mline_index: Option<u16>,
+    pub username_fragment: Option<String>,
+}
\ No newline at end of file
@@ -2,8 +2,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use validator::Validate;
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct SystemEvent {
     pub id: Uuid,
     pub event_type: SystemEventType,
@@ -17,7 +18,16 @@ pub struct SystemEvent {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type)]
+// Query of DELETE /system/events. `before` defaults to the alert
+// retention cutoff; unacknowledged events are only purged when
+// `acknowledged` is false, and unacknowledged critical ones never are.
+#[derive(Debug, Deserialize)]
+pub struct PurgeEventsQuery {
+    pub before: Option<DateTime<Utc>>,
+    pub acknowledged: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "system_event_type", rename_all = "snake_case")]
 pub enum SystemEventType {
     CameraOffline,
@@ -33,7 +43,64 @@ pub enum SystemEventType {
     Other,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type)]
+impl SystemEventType {
+    // The database name, which is also how alert routes refer to it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SystemEventType::CameraOffline => "camera_offline",
+            SystemEventType::CameraError => "camera_error",
+            SystemEventType::InferenceError => "inference_error",
+            SystemEventType::TrainingError => "training_error",
+            SystemEventType::StorageLow => "storage_low",
+            SystemEventType::MemoryHigh => "memory_high",
+            SystemEventType::CpuHigh => "cpu_high",
+            SystemEventType::ServiceDown => "service_down",
+            SystemEventType::ModelPerformanceDegraded => "model_performance_degraded",
+            SystemEventType::SecurityAlert => "security_alert",
+            SystemEventType::Other => "other",
+        }
+    }
+}
+
+// Where raised alerts from a zone are delivered. A route without a zone is
+// the fallback for zones that have no route of their own. `alert_types`
+// holds event types or perception `alert_type`s; empty matches every alert.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AlertRoute {
+    pub id: Uuid,
+    pub zone: Option<String>,
+    pub alert_types: Vec<String>,
+    pub endpoint_url: String,
+    pub recipients: Vec<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Body of POST and PUT /system/alert-routes; PUT replaces the whole route
+#[derive(Debug, Deserialize, Validate)]
+pub struct AlertRouteRequest {
+    #[validate(length(min = 1))]
+    pub zone: Option<String>,
+    
+    #[serde(default)]
+    pub alert_types: Vec<String>,
+    
+    #[validate(url)]
+    pub endpoint_url: String,
+    
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "event_severity", rename_all = "lowercase")]
 pub enum EventSeverity {
     Critical,
@@ -57,6 +124,13 @@ pub struct ComponentHealth {
     pub details: Option<serde_json::Value>,
 }
 
+#[derive(Debug)]
+pub struct ZoneCameraCounts {
+    pub zone: String,
+    pub total: i64,
+    pub online: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "system_status", rename_all = "lowercase")]
 pub enum SystemStatus {
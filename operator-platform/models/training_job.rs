@@ -22,6 +22,7 @@ pub struct TrainingJob {
     pub completed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub dataset_version_id: Option<Uuid>, // immutable snapshot the job trains on
 }
 
 #[derive(Debug, Serialize, Deserialize, sqlx::Type)]
@@ -47,6 +48,9 @@ pub struct CreateTrainingJobRequest {
     
     pub dataset_id: Uuid,
     
+    // Defaults to a fresh snapshot of `dataset_id`
+    pub dataset_version_id: Option<Uuid>,
+    
     pub hyperparameters: serde_json::Value,
 }
 
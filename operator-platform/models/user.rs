@@ -15,7 +15,7 @@ pub struct User {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "user_role", rename_all = "lowercase")]
 pub enum UserRole {
     Admin,
@@ -34,7 +34,8 @@ pub struct CreateUserRequest {
     #[validate(length(min = 8))]
     pub password: String,
     
-    pub role: UserRole,
+    // Only admins may set this; self-registered users are viewers
+    pub role: Option<UserRole>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -61,4 +62,11 @@ pub struct LoginRequest {
 pub struct AuthResponse {
     pub token: String,
     pub user: User,
-}
\ No newline at end of file
+    pub preferences: serde_json::Value, // saved dashboard preferences; {} until the user saves some
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RotateSecretRequest {
+    pub secret_key: String,
+    pub grace_min: Option<u32>, // defaults to `auth.rotation_grace_min`
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// A persisted facility world model, for scrubbing back through the fused
+// scene. `snapshot` is the WorldModel as the aggregator posted it.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct WorldModelSnapshot {
+    pub id: Uuid,
+    pub zone_id: String,
+    pub captured_at: DateTime<Utc>,
+    pub object_count: i32,
+    pub fusion_confidence: f64,
+    pub snapshot: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorldModelQuery {
+    pub at: DateTime<Utc>,
+    pub zone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WorldModelRangeQuery {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub zone: Option<String>,
+    pub limit: Option<i64>,
+}
+
+// A facility map upload; `map` is the validated FacilityMap JSON
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct FacilityMapRecord {
+    pub id: Uuid,
+    pub facility_id: String,
+    pub map: serde_json::Value,
+    pub uploaded_by: Option<Uuid>,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FacilityMapQuery {
+    pub facility: Option<String>, // the most recently uploaded facility when unset
+}
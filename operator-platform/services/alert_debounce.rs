@@ -0,0 +1,275 @@
+use anyhow::Result;
+use crate::storage::DbPool;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::time::{self, Duration, Instant};
+use tracing::{info, warn};
+
+use crate::{
+    config::MonitoringConfig,
+    models::{EventSeverity, SystemEvent, SystemEventType},
+    services::system_service::SystemService,
+    services::{AlertRoutingService, ShutdownSignal},
+};
+
+// An alert condition reported by a perception node
+#[derive(Debug, Clone)]
+pub struct DebouncedAlert {
+    pub event_type: SystemEventType,
+    pub severity: EventSeverity,
+    pub message: String,
+    pub source: Option<String>,
+    pub details: Option<serde_json::Value>,
+}
+
+impl DebouncedAlert {
+    // Reports of the same type from the same source are one condition.
+    // Perception nodes tag the specific check in `details.alert_type`.
+    fn key(&self) -> String {
+        let alert_type = self
+            .details
+            .as_ref()
+            .and_then(|d| d.get("alert_type"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("");
+        format!("{:?}/{}/{}", self.event_type, self.source.as_deref().unwrap_or(""), alert_type)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AlertTransition {
+    Raise(DebouncedAlert),
+    Resolve { alert: DebouncedAlert, occurrences: u32, duration: Duration },
+}
+
+struct Condition {
+    alert: DebouncedAlert,
+    first_seen: Instant,
+    last_seen: Instant,
+    occurrences: u32,
+    raised: bool,
+}
+
+// Turns a stream of alert reports into raise/resolve transitions. A condition
+// is raised once it has persisted for `min_duration` or been reported
+// `min_occurrences` times, and resolves after `clear_after` without reports.
+// Conditions that clear before being raised leave no trace.
+pub struct AlertDebouncer {
+    min_duration: Duration,
+    min_occurrences: u32,
+    clear_after: Duration,
+    conditions: HashMap<String, Condition>,
+}
+
+impl AlertDebouncer {
+    pub fn new(min_duration: Duration, min_occurrences: u32, clear_after: Duration) -> Self {
+        Self {
+            min_duration,
+            min_occurrences: min_occurrences.max(1),
+            clear_after,
+            conditions: HashMap::new(),
+        }
+    }
+
+    pub fn observe(&mut self, alert: DebouncedAlert, now: Instant) -> Option<AlertTransition> {
+        let condition = self.conditions.entry(alert.key()).or_insert_with(|| Condition {
+            alert: alert.clone(),
+            first_seen: now,
+            last_seen: now,
+            occurrences: 0,
+            raised: false,
+        });
+
+        condition.alert = alert;
+        condition.last_seen = now;
+        condition.occurrences += 1;
+
+        let persisted = now.duration_since(condition.first_seen) >= self.min_duration;
+        if condition.raised || !(persisted || condition.occurrences >= self.min_occurrences) {
+            return None;
+        }
+
+        condition.raised = true;
+        Some(AlertTransition::Raise(condition.alert.clone()))
+    }
+
+    pub fn expire(&mut self, now: Instant) -> Vec<AlertTransition> {
+        let cleared: Vec<String> = self
+            .conditions
+            .iter()
+            .filter(|(_, c)| now.duration_since(c.last_seen) >= self.clear_after)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        cleared
+            .into_iter()
+            .filter_map(|key| self.conditions.remove(&key))
+            .filter(|c| c.raised)
+            .map(|c| AlertTransition::Resolve {
+                occurrences: c.occurrences,
+                duration: c.last_seen.duration_since(c.first_seen),
+                alert: c.alert,
+            })
+            .collect()
+    }
+}
+
+// Debounced path from perception alerts to `system_events`; raised alerts
+// are also sent to their zone's alert routes
+pub struct AlertDebounceService {
+    db_pool: DbPool,
+    clear_after: Duration,
+    debouncer: Mutex<AlertDebouncer>,
+    routing: Arc<AlertRoutingService>,
+}
+
+impl AlertDebounceService {
+    pub fn new(db_pool: DbPool, config: &MonitoringConfig) -> Self {
+        let clear_after = Duration::from_secs(config.alert_clear_after_sec);
+        Self {
+            routing: Arc::new(AlertRoutingService::new(db_pool.clone(), config)),
+            db_pool,
+            clear_after,
+            debouncer: Mutex::new(AlertDebouncer::new(
+                Duration::from_secs(config.alert_debounce_sec),
+                config.alert_debounce_occurrences,
+                clear_after,
+            )),
+        }
+    }
+
+    // Returns the event created if this report raised the condition
+    pub async fn report(&self, alert: DebouncedAlert) -> Result<Option<SystemEvent>> {
+        let transition = self.debouncer.lock().unwrap().observe(alert, Instant::now());
+        match transition {
+            Some(transition) => self.apply(transition).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Resolves cleared conditions; checks a few times per clear window
+    pub async fn start(&self, mut shutdown: ShutdownSignal) -> Result<()> {
+        let mut interval = time::interval((self.clear_after / 4).max(Duration::from_secs(1)));
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.recv() => {
+                    info!("Alert debouncer stopped");
+                    return Ok(());
+                }
+            }
+
+            let transitions = self.debouncer.lock().unwrap().expire(Instant::now());
+            for transition in transitions {
+                if let Err(e) = self.apply(transition).await {
+                    warn!("Failed to record alert resolution: {}", e);
+                }
+            }
+        }
+    }
+
+    async fn apply(&self, transition: AlertTransition) -> Result<SystemEvent> {
+        let system_service = SystemService::new(self.db_pool.clone());
+
+        match transition {
+            AlertTransition::Raise(alert) => {
+                info!("Alert raised: {}", alert.message);
+                let event = system_service
+                    .log_event(alert.event_type, alert.severity, &alert.message, alert.source.as_deref(), alert.details)
+                    .await?;
+                self.routing.spawn_dispatch(event.clone());
+                Ok(event)
+            }
+            AlertTransition::Resolve { alert, occurrences, duration } => {
+                info!("Alert resolved: {}", alert.message);
+                let details = serde_json::json!({
+                    "resolved": true,
+                    "alert": alert.details,
+                    "occurrences": occurrences,
+                    "duration_sec": duration.as_secs(),
+                });
+                system_service
+                    .log_event(
+                        alert.event_type,
+                        EventSeverity::Info,
+                        &format!("Resolved: {}", alert.message),
+                        alert.source.as_deref(),
+                        Some(details),
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(source: &str) -> DebouncedAlert {
+        DebouncedAlert {
+            event_type: SystemEventType::CameraError,
+            severity: EventSeverity::High,
+            message: format!("Camera {} frames degraded", source),
+            source: Some(source.to_string()),
+            details: Some(serde_json::json!({ "alert_type": "camera_quality_degraded" })),
+        }
+    }
+
+    fn debouncer() -> AlertDebouncer {
+        AlertDebouncer::new(Duration::from_secs(5), 3, Duration::from_secs(30))
+    }
+
+    #[test]
+    fn test_single_frame_blip_creates_no_event() {
+        let mut debouncer = debouncer();
+        let start = Instant::now();
+
+        assert!(debouncer.observe(alert("cam-1"), start).is_none());
+        assert!(debouncer.expire(start + Duration::from_secs(29)).is_empty());
+        assert!(debouncer.expire(start + Duration::from_secs(30)).is_empty());
+
+        // Cleared without trace: the next report starts a fresh condition
+        assert!(debouncer.observe(alert("cam-1"), start + Duration::from_secs(31)).is_none());
+    }
+
+    #[test]
+    fn test_sustained_condition_raises_once_and_resolves() {
+        let mut debouncer = debouncer();
+        let start = Instant::now();
+
+        // Reported every 2s: the third report raises, later ones are absorbed
+        let transitions: Vec<_> = (0..6)
+            .filter_map(|i| debouncer.observe(alert("cam-1"), start + Duration::from_secs(2 * i)))
+            .collect();
+        assert_eq!(transitions.len(), 1);
+        assert!(matches!(&transitions[0], AlertTransition::Raise(a) if a.source.as_deref() == Some("cam-1")));
+
+        // Another camera's condition is independent
+        assert!(debouncer.observe(alert("cam-2"), start + Duration::from_secs(10)).is_none());
+
+        let last_report = start + Duration::from_secs(10);
+        assert!(debouncer.expire(last_report + Duration::from_secs(29)).is_empty());
+
+        let resolved = debouncer.expire(last_report + Duration::from_secs(30));
+        assert_eq!(resolved.len(), 1);
+        match &resolved[0] {
+            AlertTransition::Resolve { alert, occurrences, duration } => {
+                assert_eq!(alert.source.as_deref(), Some("cam-1"));
+                assert_eq!(*occurrences, 6);
+                assert_eq!(*duration, Duration::from_secs(10));
+            }
+            other => panic!("expected a resolve, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_persistence_alone_raises() {
+        let mut debouncer = AlertDebouncer::new(Duration::from_secs(5), 100, Duration::from_secs(30));
+        let start = Instant::now();
+
+        assert!(debouncer.observe(alert("cam-1"), start).is_none());
+        assert!(debouncer.observe(alert("cam-1"), start + Duration::from_secs(5)).is_some());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,199 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt, TryStreamExt};
+use serde::Serialize;
+use crate::storage::DbPool;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// Rows are encoded into chunks of about this size, so an export holds one
+// chunk in memory however many rows it has
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Row-at-a-time formats only. COCO needs the full document (images and
+// categories are indexed across every annotation), so it can't be streamed
+// and isn't offered here.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnnotationExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl AnnotationExportFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            "coco" => bail!("COCO exports need the whole document and can't be streamed; use csv or ndjson"),
+            other => bail!("Unsupported export format '{}'; use csv or ndjson", other),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct AnnotationExportRow {
+    pub image_path: String,
+    pub camera_id: Uuid,
+    pub annotations: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct AnnotationExportService {
+    db_pool: DbPool,
+}
+
+impl AnnotationExportService {
+    pub fn new(db_pool: DbPool) -> Self {
+        Self { db_pool }
+    }
+
+    // Completed annotations, encoded as they come off the cursor
+    pub fn export(&self, format: AnnotationExportFormat) -> impl Stream<Item = Result<Vec<u8>>> {
+        let db_pool = self.db_pool.clone();
+        let rows = async_stream::try_stream! {
+            let mut rows = sqlx::query_as::<_, AnnotationExportRow>(
+                r#"
+                SELECT image_path, camera_id, annotations, created_at
+                FROM annotations
+                WHERE status = 'completed'
+                ORDER BY created_at, id
+                "#,
+            )
+            .fetch(&db_pool);
+
+            while let Some(row) = rows.try_next().await? {
+                yield row;
+            }
+        };
+
+        encode(rows, format)
+    }
+}
+
+// Turns a row stream into chunks of at most about CHUNK_SIZE bytes
+pub fn encode<S>(rows: S, format: AnnotationExportFormat) -> impl Stream<Item = Result<Vec<u8>>>
+where
+    S: Stream<Item = Result<AnnotationExportRow>>,
+{
+    async_stream::try_stream! {
+        let mut rows = Box::pin(rows);
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+        if format == AnnotationExportFormat::Csv {
+            chunk.extend_from_slice(b"image_path,camera_id,annotations,created_at\n");
+        }
+
+        while let Some(row) = rows.next().await {
+            encode_row(&row?, format, &mut chunk)?;
+            if chunk.len() >= CHUNK_SIZE {
+                yield std::mem::replace(&mut chunk, Vec::with_capacity(CHUNK_SIZE));
+            }
+        }
+
+        if !chunk.is_empty() {
+            yield chunk;
+        }
+    }
+}
+
+fn encode_row(row: &AnnotationExportRow, format: AnnotationExportFormat, out: &mut Vec<u8>) -> Result<()> {
+    match format {
+        AnnotationExportFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(out);
+            writer.write_record([
+                row.image_path.as_str(),
+                &row.camera_id.to_string(),
+                &row.annotations.to_string(),
+                &row.created_at.to_rfc3339(),
+            ])?;
+            writer.flush()?;
+        }
+        AnnotationExportFormat::Ndjson => {
+            serde_json::to_writer(&mut *out, row)?;
+            out.push(b'\n');
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn row(i: usize) -> AnnotationExportRow {
+        AnnotationExportRow {
+            image_path: format!("frames/{}.jpg", i),
+            camera_id: Uuid::nil(),
+            annotations: json!([{ "label": "person", "bbox": [1, 2, 3, 4] }]),
+            created_at: DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_large_export_streams_in_bounded_chunks() {
+        const ROWS: usize = 200_000;
+        let produced = Arc::new(AtomicUsize::new(0));
+        let counter = produced.clone();
+        let rows = futures::stream::iter(0..ROWS).map(move |i| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Ok(row(i))
+        });
+
+        let mut chunks = Box::pin(encode(rows, AnnotationExportFormat::Csv));
+        let first = chunks.next().await.unwrap().unwrap();
+        // The first chunk goes out long before the source is exhausted
+        assert!(produced.load(Ordering::Relaxed) < ROWS / 10);
+        assert!(first.starts_with(b"image_path,camera_id,annotations,created_at\n"));
+
+        let (mut lines, mut largest) = (first.iter().filter(|b| **b == b'\n').count(), first.len());
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.unwrap();
+            largest = largest.max(chunk.len());
+            lines += chunk.iter().filter(|b| **b == b'\n').count();
+        }
+
+        assert_eq!(lines, ROWS + 1);
+        assert!(largest < CHUNK_SIZE + 1024, "chunk of {} bytes", largest);
+    }
+
+    #[tokio::test]
+    async fn test_ndjson_rows_are_one_object_per_line() {
+        let rows = futures::stream::iter((0..3).map(|i| Ok(row(i))));
+        let body: Vec<u8> = encode(rows, AnnotationExportFormat::Ndjson)
+            .try_concat()
+            .await
+            .unwrap();
+
+        let lines: Vec<serde_json::Value> = body
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2]["image_path"], "frames/2.jpg");
+        assert_eq!(lines[0]["annotations"][0]["label"], "person");
+    }
+
+    #[test]
+    fn test_coco_is_not_streamable() {
+        assert_eq!(AnnotationExportFormat::parse("NDJSON").unwrap(), AnnotationExportFormat::Ndjson);
+        assert!(AnnotationExportFormat::parse("coco").unwrap_err().to_string().contains("can't be streamed"));
+    }
+}
@@ -0,0 +1,255 @@
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+use crate::storage::DbPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+use aetherforge_common::types::Detection;
+
+use crate::{
+    models::{AnnotationPrediction, Model},
+    services::{prelabels, AnnotationService, ModelService},
+    storage::FileStorage,
+};
+
+// Loading a model the node hasn't scored with yet can take a while
+const SCORING_TIMEOUT: Duration = Duration::from_secs(120);
+
+// One image as scored by a perception node's POST /score
+#[derive(Debug, Deserialize)]
+pub struct ScoredImage {
+    pub width: u32,
+    pub height: u32,
+    pub detections: Vec<Detection>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScoreReport {
+    model_version: String,
+    images: Vec<ScoredImage>,
+}
+
+// Runs models on images through a perception node's scoring API, so the
+// platform never loads models into its own process
+#[derive(Clone)]
+pub struct ScoringClient {
+    client: reqwest::Client,
+    base_url: String,
+    token: Option<String>, // sent as a bearer token; nodes refuse /score without it
+}
+
+impl ScoringClient {
+    pub fn new(base_url: &str, token: Option<String>) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(SCORING_TIMEOUT).build()?;
+        Ok(Self { client, base_url: base_url.trim_end_matches('/').to_string(), token })
+    }
+
+    // Returns the model version the node reports alongside the detections
+    pub async fn score(&self, image: Vec<u8>, model: &Model) -> Result<(String, ScoredImage)> {
+        let mut query = vec![("model_path", model.model_path.clone()), ("model_version", model.version.clone())];
+        if let Some(classes) = class_list(&model.classes) {
+            query.push(("classes", classes));
+        }
+
+        let mut request = self.client
+            .post(format!("{}/score", self.base_url))
+            .query(&query)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(image);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            bail!("Scoring node returned {}: {}", status, response.text().await.unwrap_or_default());
+        }
+
+        let mut report: ScoreReport = response.json().await?;
+        let scored = report.images.pop().ok_or_else(|| anyhow!("Scoring node returned no image"))?;
+        Ok((report.model_version, scored))
+    }
+}
+
+// Models register their classes as a JSON array of names
+fn class_list(classes: &serde_json::Value) -> Option<String> {
+    let names: Vec<&str> = classes.as_array()?.iter().filter_map(|c| c.as_str()).collect();
+    (!names.is_empty()).then(|| names.join(","))
+}
+
+#[derive(Clone)]
+pub struct PredictionService {
+    db_pool: DbPool,
+    scoring: ScoringClient,
+}
+
+impl PredictionService {
+    pub fn new(db_pool: DbPool, scoring: ScoringClient) -> Self {
+        Self { db_pool, scoring }
+    }
+
+    // What `model_id` makes of an annotation's image, next to the stored labels
+    pub async fn predict(&self, annotation_id: Uuid, model_id: Uuid, storage: &FileStorage) -> Result<AnnotationPrediction> {
+        let annotation = AnnotationService::new(self.db_pool.clone()).get_annotation(annotation_id).await?;
+        let model = ModelService::new(self.db_pool.clone()).get_model(model_id).await?;
+
+        // Sampled frames are stored by absolute path, uploads relative to the annotations dir
+        let image = storage.read_file("", &annotation.image_path).await?;
+        let (image_width, image_height) = image_dimensions(&image)
+            .ok_or_else(|| anyhow!("{} is not a JPEG or PNG image", annotation.image_path))?;
+
+        let (model_version, scored) = self.scoring.score(image, &model).await?;
+        let detections = align_to_image(scored, image_width, image_height);
+
+        Ok(AnnotationPrediction {
+            annotation_id,
+            model_id,
+            model_version,
+            image_width,
+            image_height,
+            predictions: prelabels(&detections),
+            annotations: annotation.annotations,
+        })
+    }
+}
+
+// Rescales detections if the scorer saw the image at another size, so they
+// overlay the stored image the human labels were drawn on
+pub fn align_to_image(scored: ScoredImage, width: u32, height: u32) -> Vec<Detection> {
+    if (scored.width, scored.height) == (width, height) || scored.width == 0 || scored.height == 0 {
+        return scored.detections;
+    }
+
+    let (sx, sy) = (width as f32 / scored.width as f32, height as f32 / scored.height as f32);
+    scored
+        .detections
+        .into_iter()
+        .map(|mut detection| {
+            let b = detection.bbox;
+            detection.bbox = aetherforge_common::types::BBox::new(b.xmin * sx, b.ymin * sy, b.xmax * sx, b.ymax * sy);
+            detection.oriented = None; // not a rectangle after unequal scaling
+            detection
+        })
+        .collect()
+}
+
+// Width and height from a JPEG SOF or PNG IHDR header, without decoding
+pub fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let be = |at: usize| Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+        return Some((be(16)?, be(20)?));
+    }
+    if !bytes.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+
+    let mut at = 2;
+    while at + 4 <= bytes.len() {
+        if bytes[at] != 0xFF {
+            return None;
+        }
+        let marker = bytes[at + 1];
+        let length = u16::from_be_bytes([bytes[at + 2], bytes[at + 3]]) as usize;
+        // Start-of-frame markers, excluding DHT (C4), JPG (C8) and DAC (CC)
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let segment = bytes.get(at + 4..at + 9)?;
+            let height = u16::from_be_bytes([segment[1], segment[2]]) as u32;
+            let width = u16::from_be_bytes([segment[3], segment[4]]) as u32;
+            return Some((width, height));
+        }
+        at += 2 + length;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::header, web, App, HttpRequest, HttpResponse, HttpServer};
+    use serde_json::json;
+
+    // SOI, an APP0 segment, then a baseline SOF0 for a 640x480 image
+    fn jpeg_header() -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00];
+        bytes.extend([0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0xE0, 0x02, 0x80]);
+        bytes.extend([0u8; 9]);
+        bytes
+    }
+
+    #[test]
+    fn test_image_dimensions() {
+        assert_eq!(image_dimensions(&jpeg_header()), Some((640, 480)));
+
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend(1920u32.to_be_bytes());
+        png.extend(1080u32.to_be_bytes());
+        assert_eq!(image_dimensions(&png), Some((1920, 1080)));
+
+        assert_eq!(image_dimensions(b"GIF89a"), None);
+    }
+
+    // A stand-in node that scores every image at half size, as a node
+    // downscaling large uploads would
+    #[actix_rt::test]
+    async fn test_predictions_come_back_aligned_with_stored_image() {
+        let server = HttpServer::new(|| {
+            App::new().route("/score", web::post().to(|request: HttpRequest, body: web::Bytes, query: web::Query<std::collections::HashMap<String, String>>| async move {
+                let authorization = request.headers().get(header::AUTHORIZATION).and_then(|value| value.to_str().ok());
+                assert_eq!(authorization, Some("Bearer node-secret"));
+                assert_eq!(image_dimensions(&body), Some((640, 480)));
+                assert_eq!(query.get("classes").map(String::as_str), Some("person,forklift"));
+                HttpResponse::Ok().json(json!({
+                    "model_version": query.get("model_version"),
+                    "images_scored": 1,
+                    "elapsed_sec": 0.01,
+                    "images_per_sec": 100.0,
+                    "images": [{
+                        "file": "upload",
+                        "width": 320,
+                        "height": 240,
+                        "detections": [{
+                            "bbox": { "xmin": 10.0, "ymin": 20.0, "xmax": 60.0, "ymax": 120.0 },
+                            "confidence": 0.5,
+                            "class_id": 1,
+                            "class_label": "forklift",
+                            "tracker_id": null
+                        }]
+                    }]
+                }))
+            }))
+        })
+        .bind("127.0.0.1:0")
+        .unwrap();
+        let address = server.addrs()[0];
+        actix_rt::spawn(server.run());
+
+        let model: Model = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "name": "detector",
+            "description": null,
+            "version": "2.1.0",
+            "model_path": "/var/lib/aetherforge/models/detector-2.1.0.onnx",
+            "model_type": "ObjectDetection",
+            "input_shape": [1, 3, 640, 640],
+            "output_shape": [1, 6, 8400],
+            "classes": ["person", "forklift"],
+            "performance_metrics": {},
+            "training_job_id": null,
+            "status": "Validated",
+            "created_by": Uuid::new_v4(),
+            "created_at": chrono::Utc::now(),
+            "updated_at": chrono::Utc::now()
+        }))
+        .unwrap();
+
+        let client = ScoringClient::new(&format!("http://{}/", address), Some("node-secret".to_string())).unwrap();
+        let (version, scored) = client.score(jpeg_header(), &model).await.unwrap();
+        assert_eq!(version, "2.1.0");
+
+        let detections = align_to_image(scored, 640, 480);
+        assert_eq!(
+            prelabels(&detections),
+            json!([{ "label": "forklift", "bbox": [20.0, 40.0, 120.0, 240.0], "confidence": 0.5, "source": "model" }])
+        );
+    }
+}
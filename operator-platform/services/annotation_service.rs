@@ -1,17 +1,22 @@
-use anyhow::Result;
-use sqlx::postgres::PgPool;
+use anyhow::{bail, Result};
 use uuid::Uuid;
 use chrono::Utc;
 
-use crate::models::{Annotation, AnnotationStatus, CreateAnnotationRequest, UpdateAnnotationRequest, AnnotationStats, AnnotationTask};
+use crate::models::{Annotation, AnnotationStatus, CreateAnnotationRequest, UpdateAnnotationRequest, AnnotationStats, AnnotationTask, SampledFrameMetadata};
+use crate::services::DbTimer;
+use crate::storage::{DbPool, FileStorage};
+use aetherforge_common::types::Detection;
+
+// Upper bound on ids accepted by a single bulk review
+const MAX_BULK_REVIEW: usize = 1000;
 
 #[derive(Clone)]
 pub struct AnnotationService {
-    db_pool: PgPool,
+    db_pool: DbPool,
 }
 
 impl AnnotationService {
-    pub fn new(db_pool: PgPool) -> Self {
+    pub fn new(db_pool: DbPool) -> Self {
         Self { db_pool }
     }
     
@@ -68,6 +73,36 @@ impl AnnotationService {
         Ok(annotation)
     }
     
+    // Stores a frame a perception node sampled and queues it for labeling,
+    // pre-labelled with the model's detections
+    pub async fn ingest_sample(&self, node_id: &str, metadata: SampledFrameMetadata, jpeg: &[u8], storage: &FileStorage) -> Result<Annotation> {
+        let camera_id: Uuid = sqlx::query_scalar("SELECT id FROM cameras WHERE device_id = $1")
+            .bind(&metadata.camera_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+        
+        let image_path = storage
+            .save_file(jpeg, &format!("samples/{}", camera_id), &format!("{}-{}.jpg", metadata.timestamp, Uuid::new_v4().simple()))
+            .await?;
+        
+        let annotation = sqlx::query_as::<_, Annotation>(
+            r#"
+            INSERT INTO annotations (image_path, camera_id, created_by, annotations, status, source_node_id)
+            VALUES ($1, $2, NULL, $3, $4, $5)
+            RETURNING *
+            "#,
+        )
+        .bind(image_path.to_string_lossy().to_string())
+        .bind(camera_id)
+        .bind(prelabels(&metadata.detections))
+        .bind(AnnotationStatus::Pending)
+        .bind(node_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        
+        Ok(annotation)
+    }
+    
     pub async fn update_annotation(&self, id: Uuid, user_id: Uuid, data: UpdateAnnotationRequest) -> Result<Annotation> {
         let annotation = sqlx::query_as!(
             Annotation,
@@ -96,6 +131,58 @@ impl AnnotationService {
         Ok(annotation)
     }
     
+    // Approves or rejects many annotations at once. Either every id is
+    // updated or, if any is missing, none are.
+    pub async fn bulk_update_status(&self, ids: &[Uuid], status: AnnotationStatus, reviewer: Uuid) -> Result<Vec<Annotation>> {
+        if matches!(status, AnnotationStatus::Pending) {
+            bail!("Bulk review must approve or reject");
+        }
+        
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+        
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        if ids.len() > MAX_BULK_REVIEW {
+            bail!("At most {} annotations can be reviewed at once", MAX_BULK_REVIEW);
+        }
+        
+        let _db = DbTimer::start();
+        let mut tx = self.db_pool.begin().await?;
+        
+        let annotations = sqlx::query_as!(
+            Annotation,
+            r#"
+            UPDATE annotations 
+            SET 
+                status = $1,
+                reviewed = true,
+                reviewed_by = $2,
+                reviewed_at = $3,
+                updated_at = $3
+            WHERE id = ANY($4)
+            RETURNING *
+            "#,
+            status as AnnotationStatus,
+            reviewer,
+            Utc::now(),
+            &ids
+        )
+        .fetch_all(&mut tx)
+        .await?;
+        
+        if annotations.len() != ids.len() {
+            // Dropping the transaction rolls the partial update back
+            bail!("{} of {} annotations not found", ids.len() - annotations.len(), ids.len());
+        }
+        
+        tx.commit().await?;
+        
+        Ok(annotations)
+    }
+    
     pub async fn delete_annotation(&self, id: Uuid) -> Result<()> {
         sqlx::query!(
             "DELETE FROM annotations WHERE id = $1",
@@ -148,40 +235,125 @@ impl AnnotationService {
         
         Ok(tasks)
     }
+}
+
+// Detections in the annotation layout the editor and exports use, marked
+// as model output so reviewers know to check them
+pub fn prelabels(detections: &[Detection]) -> serde_json::Value {
+    detections
+        .iter()
+        .map(|d| {
+            serde_json::json!({
+                "label": d.class_label,
+                "bbox": [d.bbox.xmin, d.bbox.ymin, d.bbox.xmax, d.bbox.ymax],
+                "confidence": d.confidence,
+                "source": "model",
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::test_pool;
+    use serde_json::json;
     
-    pub async fn export_annotations(&self, format: &str) -> Result<Vec<u8>> {
-        // This would export annotations in the specified format (COCO, YOLO, etc.)
-        // For now, we'll just return a simple CSV format
-        let annotations = sqlx::query!(
-            r#"
-            SELECT 
-                image_path,
-                camera_id,
-                annotations,
-                created_at
-            FROM annotations
-            WHERE status = 'completed'
-            "#
+    async fn seed(pool: &DbPool, count: usize) -> (Uuid, Vec<Uuid>) {
+        let suffix = Uuid::new_v4();
+        
+        let user_id = sqlx::query!(
+            "INSERT INTO users (username, email, password_hash) VALUES ($1, $2, 'x') RETURNING id",
+            format!("reviewer-{}", suffix),
+            format!("reviewer-{}@example.com", suffix)
         )
-        .fetch_all(&self.db_pool)
-        .await?;
+        .fetch_one(pool)
+        .await
+        .unwrap()
+        .id;
+        
+        let camera_id = sqlx::query!(
+            "INSERT INTO cameras (name, device_id, location, stream_url) VALUES ('bulk', $1, 'test', 'rtsp://test') RETURNING id",
+            format!("bulk-{}", suffix)
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+        .id;
         
-        let mut csv_data = Vec::new();
-        let mut wtr = csv::Writer::from_writer(&mut csv_data);
+        let service = AnnotationService::new(pool.clone());
+        let mut ids = Vec::new();
+        for i in 0..count {
+            let annotation = service
+                .create_annotation(user_id, CreateAnnotationRequest {
+                    image_path: format!("frames/{}.jpg", i),
+                    camera_id,
+                    annotations: json!([]),
+                })
+                .await
+                .unwrap();
+            ids.push(annotation.id);
+        }
         
-        wtr.write_record(&["image_path", "camera_id", "annotations", "created_at"])?;
+        (user_id, ids)
+    }
+    
+    #[test]
+    fn test_sampled_detections_become_model_prelabels() {
+        let metadata: SampledFrameMetadata = serde_json::from_value(json!({
+            "camera_id": "dock-cam",
+            "timestamp": 1_700_000_000_000u64,
+            "width": 640,
+            "height": 480,
+            "detections": [{
+                "bbox": { "xmin": 10.0, "ymin": 20.0, "xmax": 50.0, "ymax": 90.0 },
+                "confidence": 0.5,
+                "class_id": 2,
+                "class_label": "forklift",
+                "tracker_id": null
+            }]
+        }))
+        .unwrap();
         
-        for ann in annotations {
-            wtr.write_record(&[
-                ann.image_path,
-                ann.camera_id.to_string(),
-                ann.annotations.to_string(),
-                ann.created_at.to_string(),
-            ])?;
+        assert_eq!(
+            prelabels(&metadata.detections),
+            json!([{ "label": "forklift", "bbox": [10.0, 20.0, 50.0, 90.0], "confidence": 0.5, "source": "model" }])
+        );
+    }
+    
+    #[tokio::test]
+    #[ignore]
+    async fn test_bulk_approve_stamps_reviewer() {
+        let pool = test_pool().await;
+        let (reviewer, ids) = seed(&pool, 5).await;
+        let service = AnnotationService::new(pool);
+        
+        let updated = service.bulk_update_status(&ids, AnnotationStatus::Completed, reviewer).await.unwrap();
+        assert_eq!(updated.len(), ids.len());
+        
+        for id in ids {
+            let annotation = service.get_annotation(id).await.unwrap();
+            assert!(matches!(annotation.status, AnnotationStatus::Completed));
+            assert!(annotation.reviewed);
+            assert_eq!(annotation.reviewed_by, Some(reviewer));
+            assert!(annotation.reviewed_at.is_some());
         }
+    }
+    
+    #[tokio::test]
+    #[ignore]
+    async fn test_bulk_review_with_unknown_id_changes_nothing() {
+        let pool = test_pool().await;
+        let (reviewer, mut ids) = seed(&pool, 3).await;
+        let service = AnnotationService::new(pool);
         
-        wtr.flush()?;
+        ids.push(Uuid::new_v4());
+        assert!(service.bulk_update_status(&ids, AnnotationStatus::Rejected, reviewer).await.is_err());
         
-        Ok(csv_data)
+        for id in &ids[..3] {
+            let annotation = service.get_annotation(*id).await.unwrap();
+            assert!(matches!(annotation.status, AnnotationStatus::Pending));
+            assert_eq!(annotation.reviewed_by, None);
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,218 @@
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt::Write;
+
+use super::coverage_service::{matrix_from_rodrigues, StoredExtrinsics, StoredIntrinsics};
+use crate::models::Camera;
+
+// Returned when a camera has no calibration that can be exported
+#[derive(Debug, thiserror::Error)]
+#[error("Calibration not exportable: {0}")]
+pub struct CalibrationNotExportable(pub String);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalibrationExportFormat {
+    OpenCv,        // cv::FileStorage YAML, as written by the OpenCV calibration samples
+    RosCameraInfo, // sensor_msgs/CameraInfo as YAML
+}
+
+impl CalibrationExportFormat {
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "opencv" => Ok(Self::OpenCv),
+            "ros" | "camera_info" => Ok(Self::RosCameraInfo),
+            other => bail!("Unsupported calibration format '{}'; use opencv or ros", other),
+        }
+    }
+
+    fn file_name(&self, device_id: &str) -> String {
+        match self {
+            Self::OpenCv => format!("{}_calibration.yaml", device_id),
+            Self::RosCameraInfo => format!("{}_camera_info.yaml", device_id),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CalibrationFile {
+    pub file_name: String,
+    pub yaml: String,
+}
+
+// Renders the camera's stored calibration. Missing distortion exports as
+// zeros; without a stored resolution the principal point is taken as the
+// image center. Extrinsics are optional and only part of the OpenCV export;
+// CameraInfo has no place for them.
+pub fn render_calibration(camera: &Camera, format: CalibrationExportFormat) -> Result<CalibrationFile> {
+    let intrinsics = camera
+        .intrinsics
+        .as_ref()
+        .ok_or_else(|| CalibrationNotExportable(format!("camera {} has no intrinsics", camera.device_id)))?;
+    let intrinsics = StoredIntrinsics::deserialize(intrinsics)
+        .map_err(|e| CalibrationNotExportable(format!("camera {} intrinsics: {}", camera.device_id, e)))?;
+    let extrinsics = camera.extrinsics.as_ref().and_then(|extrinsics| StoredExtrinsics::deserialize(extrinsics).ok());
+
+    let (width, height) = match (camera.resolution_width, camera.resolution_height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => (width as u32, height as u32),
+        _ => ((intrinsics.cx * 2.0).round() as u32, (intrinsics.cy * 2.0).round() as u32),
+    };
+    let distortion = intrinsics.distortion.as_ref().map_or([0.0; 5], |d| [d.k1, d.k2, d.p1, d.p2, d.k3]);
+    let camera_matrix = [intrinsics.fx, 0.0, intrinsics.cx, 0.0, intrinsics.fy, intrinsics.cy, 0.0, 0.0, 1.0];
+
+    let yaml = match format {
+        CalibrationExportFormat::OpenCv => opencv_yaml(width, height, &camera_matrix, &distortion, extrinsics.as_ref()),
+        CalibrationExportFormat::RosCameraInfo => {
+            ros_camera_info(&camera.device_id, width, height, &camera_matrix, &distortion)
+        }
+    };
+    Ok(CalibrationFile { file_name: format.file_name(&camera.device_id), yaml })
+}
+
+fn opencv_yaml(width: u32, height: u32, camera_matrix: &[f64], distortion: &[f64], extrinsics: Option<&StoredExtrinsics>) -> String {
+    let mut yaml = format!("%YAML:1.0\n---\nimage_width: {}\nimage_height: {}\n", width, height);
+    opencv_matrix(&mut yaml, "camera_matrix", 3, 3, camera_matrix);
+    opencv_matrix(&mut yaml, "distortion_coefficients", 1, 5, distortion);
+    if let Some(extrinsics) = extrinsics {
+        let rotation: Vec<f64> = matrix_from_rodrigues(extrinsics.rotation).concat();
+        opencv_matrix(&mut yaml, "rotation_matrix", 3, 3, &rotation);
+        opencv_matrix(&mut yaml, "translation_vector", 3, 1, &extrinsics.translation);
+    }
+    yaml
+}
+
+fn opencv_matrix(yaml: &mut String, name: &str, rows: usize, cols: usize, data: &[f64]) {
+    let _ = write!(
+        yaml,
+        "{}: !!opencv-matrix\n   rows: {}\n   cols: {}\n   dt: d\n   data: {}\n",
+        name,
+        rows,
+        cols,
+        yaml_list(data)
+    );
+}
+
+// Monocular: no rectification, and P is K with a zero fourth column
+fn ros_camera_info(frame_id: &str, width: u32, height: u32, k: &[f64], d: &[f64]) -> String {
+    let r = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+    let p = [k[0], k[1], k[2], 0.0, k[3], k[4], k[5], 0.0, k[6], k[7], k[8], 0.0];
+    format!(
+        "header:\n  frame_id: {}\nheight: {}\nwidth: {}\ndistortion_model: plumb_bob\nD: {}\nK: {}\nR: {}\nP: {}\n\
+         binning_x: 0\nbinning_y: 0\nroi:\n  x_offset: 0\n  y_offset: 0\n  height: 0\n  width: 0\n  do_rectify: false\n",
+        Value::String(frame_id.to_string()),
+        height,
+        width,
+        yaml_list(d),
+        yaml_list(k),
+        yaml_list(&r),
+        yaml_list(&p)
+    )
+}
+
+// Debug formatting keeps a decimal point and round-trips exactly
+fn yaml_list(values: &[f64]) -> String {
+    format!("[ {} ]", values.iter().map(|v| format!("{:?}", v)).collect::<Vec<_>>().join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CalibrationStatus, CameraHealthStatus, CameraStatus};
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn calibrated_camera() -> Camera {
+        Camera {
+            id: Uuid::new_v4(),
+            name: "Dock 1".to_string(),
+            description: None,
+            device_id: "dock-1".to_string(),
+            location: "North wall".to_string(),
+            zone: None,
+            stream_url: "rtsp://dock-1/stream".to_string(),
+            rtsp_url: None,
+            onvif_url: None,
+            status: CameraStatus::Online,
+            health_status: CameraHealthStatus::Healthy,
+            last_ping: None,
+            fps: None,
+            resolution_width: Some(1920),
+            resolution_height: Some(1080),
+            intrinsics: Some(json!({
+                "fx": 1402.5, "fy": 1398.25, "cx": 961.3, "cy": 539.7,
+                "distortion": { "k1": -0.31, "k2": 0.12, "p1": 0.0004, "p2": -0.0002, "k3": -0.021 },
+            })),
+            extrinsics: Some(json!({ "rotation": [0.0, 0.0, 0.0], "translation": [1.0, 2.0, 3.0] })),
+            calibration_status: CalibrationStatus::Calibrated,
+            last_calibration: None,
+            calibration_target_accuracy: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct OpenCvMatrix {
+        rows: usize,
+        cols: usize,
+        dt: String,
+        data: Vec<f64>,
+    }
+
+    #[derive(Deserialize)]
+    struct OpenCvCalibration {
+        image_width: u32,
+        image_height: u32,
+        camera_matrix: OpenCvMatrix,
+        distortion_coefficients: OpenCvMatrix,
+        translation_vector: OpenCvMatrix,
+    }
+
+    #[test]
+    fn test_opencv_export_parses_back_to_the_stored_matrix() {
+        let yaml = render_calibration(&calibrated_camera(), CalibrationExportFormat::OpenCv).unwrap().yaml;
+        assert!(yaml.starts_with("%YAML:1.0\n---\n"));
+
+        // Generic YAML parsers don't take OpenCV's directive line
+        let parsed: OpenCvCalibration = serde_yaml::from_str(yaml.trim_start_matches("%YAML:1.0\n")).unwrap();
+        assert_eq!((parsed.image_width, parsed.image_height), (1920, 1080));
+        let k = &parsed.camera_matrix;
+        assert_eq!((k.rows, k.cols, k.dt.as_str()), (3, 3, "d"));
+        assert_eq!(k.data, vec![1402.5, 0.0, 961.3, 0.0, 1398.25, 539.7, 0.0, 0.0, 1.0]);
+        assert_eq!(parsed.distortion_coefficients.data, vec![-0.31, 0.12, 0.0004, -0.0002, -0.021]);
+        assert_eq!(parsed.translation_vector.data, vec![1.0, 2.0, 3.0]);
+    }
+
+    #[derive(Deserialize)]
+    #[allow(non_snake_case)]
+    struct CameraInfo {
+        width: u32,
+        height: u32,
+        distortion_model: String,
+        D: Vec<f64>,
+        K: Vec<f64>,
+        R: Vec<f64>,
+        P: Vec<f64>,
+    }
+
+    #[test]
+    fn test_ros_camera_info_has_d_k_r_p() {
+        let mut camera = calibrated_camera();
+        camera.resolution_width = None;
+        let yaml = render_calibration(&camera, CalibrationExportFormat::RosCameraInfo).unwrap().yaml;
+
+        let info: CameraInfo = serde_yaml::from_str(&yaml).unwrap();
+        // Falls back to twice the principal point
+        assert_eq!((info.width, info.height), (1923, 1079));
+        assert_eq!(info.distortion_model, "plumb_bob");
+        assert_eq!(info.D, vec![-0.31, 0.12, 0.0004, -0.0002, -0.021]);
+        assert_eq!(info.K, vec![1402.5, 0.0, 961.3, 0.0, 1398.25, 539.7, 0.0, 0.0, 1.0]);
+        assert_eq!(info.R, vec![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(info.P, vec![1402.5, 0.0, 961.3, 0.0, 0.0, 1398.25, 539.7, 0.0, 0.0, 0.0, 1.0, 0.0]);
+
+        camera.intrinsics = None;
+        let error = render_calibration(&camera, CalibrationExportFormat::RosCameraInfo).unwrap_err();
+        assert!(error.downcast_ref::<CalibrationNotExportable>().is_some());
+    }
+}
@@ -0,0 +1,465 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{SecondsFormat, Utc};
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::{
+    config::CameraControlConfig,
+    models::{Camera, ExposureMode, ExposureRequest, PtzMode, PtzRequest},
+};
+
+const SOAP_ENVELOPE_NAMESPACES: &str = concat!(
+    r#"xmlns:s="http://www.w3.org/2003/05/soap-envelope" "#,
+    r#"xmlns:tds="http://www.onvif.org/ver10/device/wsdl" "#,
+    r#"xmlns:trt="http://www.onvif.org/ver10/media/wsdl" "#,
+    r#"xmlns:tptz="http://www.onvif.org/ver20/ptz/wsdl" "#,
+    r#"xmlns:timg="http://www.onvif.org/ver20/imaging/wsdl" "#,
+    r#"xmlns:tt="http://www.onvif.org/ver10/schema""#,
+);
+
+// Fault subcodes meaning the device can't do what was asked, as opposed to
+// failing to do it
+const UNSUPPORTED_FAULTS: [&str; 3] = ["ActionNotSupported", "NoPTZProfile", "NoImagingForSource"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum CameraControlError {
+    #[error("{0}")]
+    Unsupported(String),
+    #[error("Camera {camera_id} was sent a command too recently, retry in {retry_after_ms}ms")]
+    RateLimited { camera_id: Uuid, retry_after_ms: u64 },
+    #[error("Camera rejected the command: {0}")]
+    Device(String),
+}
+
+// Where a device's ONVIF services live, from GetCapabilities; a missing
+// service is a capability the camera doesn't have
+#[derive(Debug, Default)]
+pub struct OnvifServices {
+    pub media: Option<String>,
+    pub ptz: Option<String>,
+    pub imaging: Option<String>,
+}
+
+struct MediaProfile {
+    token: String,
+    video_source: Option<String>,
+}
+
+// Talks SOAP to one camera's ONVIF services, authenticating with a
+// WS-Security UsernameToken digest when credentials are configured
+pub struct OnvifClient {
+    client: reqwest::Client,
+    device_url: String,
+    credentials: Option<(String, String)>,
+}
+
+impl OnvifClient {
+    pub fn new(device_url: &str, config: &CameraControlConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.command_timeout_sec.max(1)))
+            .build()?;
+        let credentials = config.onvif_username.clone().map(|username| (username, config.onvif_password.clone().unwrap_or_default()));
+        Ok(Self { client, device_url: device_url.to_string(), credentials })
+    }
+
+    pub async fn services(&self) -> Result<OnvifServices> {
+        let response = self.call(&self.device_url, "<tds:GetCapabilities><tds:Category>All</tds:Category></tds:GetCapabilities>").await?;
+        let xaddr = |service: &str| {
+            element(&response, service)
+                .and_then(|capability| element(capability, "XAddr"))
+                .map(|xaddr| xaddr.trim().to_string())
+                .filter(|xaddr| !xaddr.is_empty())
+        };
+        Ok(OnvifServices {
+            media: xaddr("Media"),
+            ptz: xaddr("PTZ"),
+            imaging: xaddr("Imaging"),
+        })
+    }
+
+    pub async fn ptz(&self, request: &PtzRequest) -> Result<()> {
+        let services = self.services().await?;
+        let ptz_url = services.ptz.clone().ok_or_else(|| CameraControlError::Unsupported("Camera does not support PTZ".to_string()))?;
+        let profile = self.media_profile(&services).await?;
+        let token = escape(&profile.token);
+
+        let vector = |element: &str| {
+            format!(
+                r#"<tptz:{element}><tt:PanTilt x="{}" y="{}"/><tt:Zoom x="{}"/></tptz:{element}>"#,
+                request.pan, request.tilt, request.zoom
+            )
+        };
+        let body = match request.mode {
+            PtzMode::Continuous => {
+                let timeout = request.timeout_ms.map(|ms| format!("<tptz:Timeout>PT{}S</tptz:Timeout>", ms as f64 / 1000.0)).unwrap_or_default();
+                format!("<tptz:ContinuousMove><tptz:ProfileToken>{}</tptz:ProfileToken>{}{}</tptz:ContinuousMove>", token, vector("Velocity"), timeout)
+            }
+            PtzMode::Relative => {
+                format!("<tptz:RelativeMove><tptz:ProfileToken>{}</tptz:ProfileToken>{}</tptz:RelativeMove>", token, vector("Translation"))
+            }
+            PtzMode::Absolute => {
+                format!("<tptz:AbsoluteMove><tptz:ProfileToken>{}</tptz:ProfileToken>{}</tptz:AbsoluteMove>", token, vector("Position"))
+            }
+            PtzMode::Stop => format!(
+                "<tptz:Stop><tptz:ProfileToken>{}</tptz:ProfileToken><tptz:PanTilt>true</tptz:PanTilt><tptz:Zoom>true</tptz:Zoom></tptz:Stop>",
+                token
+            ),
+        };
+
+        self.call(&ptz_url, &body).await?;
+        Ok(())
+    }
+
+    pub async fn set_exposure(&self, request: &ExposureRequest) -> Result<()> {
+        let services = self.services().await?;
+        let imaging_url = services
+            .imaging
+            .clone()
+            .ok_or_else(|| CameraControlError::Unsupported("Camera does not support exposure control".to_string()))?;
+        let video_source = self
+            .media_profile(&services)
+            .await?
+            .video_source
+            .ok_or_else(|| CameraControlError::Unsupported("Camera profile has no video source to adjust".to_string()))?;
+
+        // Element order follows the ONVIF Exposure schema
+        let mode = match request.mode {
+            ExposureMode::Auto => "AUTO",
+            ExposureMode::Manual => "MANUAL",
+        };
+        let mut exposure = format!("<tt:Mode>{}</tt:Mode>", mode);
+        if let Some(exposure_time_us) = request.exposure_time_us {
+            exposure.push_str(&format!("<tt:ExposureTime>{}</tt:ExposureTime>", exposure_time_us));
+        }
+        if let Some(gain) = request.gain {
+            exposure.push_str(&format!("<tt:Gain>{}</tt:Gain>", gain));
+        }
+        if let Some(iris) = request.iris {
+            exposure.push_str(&format!("<tt:Iris>{}</tt:Iris>", iris));
+        }
+
+        let body = format!(
+            "<timg:SetImagingSettings><timg:VideoSourceToken>{}</timg:VideoSourceToken>\
+             <timg:ImagingSettings><tt:Exposure>{}</tt:Exposure></timg:ImagingSettings>\
+             <timg:ForcePersistence>false</timg:ForcePersistence></timg:SetImagingSettings>",
+            escape(&video_source),
+            exposure
+        );
+        self.call(&imaging_url, &body).await?;
+        Ok(())
+    }
+
+    // The first media profile, which is the main stream on every camera
+    // we've seen
+    async fn media_profile(&self, services: &OnvifServices) -> Result<MediaProfile> {
+        let media_url = services
+            .media
+            .as_deref()
+            .ok_or_else(|| CameraControlError::Unsupported("Camera has no ONVIF media service".to_string()))?;
+        let response = self.call(media_url, "<trt:GetProfiles/>").await?;
+
+        let token = attribute(&response, "Profiles", "token")
+            .ok_or_else(|| CameraControlError::Unsupported("Camera has no media profiles".to_string()))?;
+        let video_source = element(&response, "Profiles")
+            .and_then(|profile| element(profile, "SourceToken"))
+            .map(|source| source.trim().to_string());
+        Ok(MediaProfile { token: token.to_string(), video_source })
+    }
+
+    async fn call(&self, url: &str, body: &str) -> Result<String> {
+        let envelope = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><s:Envelope {}>{}<s:Body>{}</s:Body></s:Envelope>"#,
+            SOAP_ENVELOPE_NAMESPACES,
+            self.security_header(),
+            body
+        );
+        let response = self.client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/soap+xml; charset=utf-8")
+            .body(envelope)
+            .send()
+            .await
+            .map_err(|e| CameraControlError::Device(e.to_string()))?;
+        let status = response.status();
+        let text = response.text().await.map_err(|e| CameraControlError::Device(e.to_string()))?;
+
+        if let Some(fault) = element(&text, "Fault") {
+            let reason = element(fault, "Reason").and_then(|reason| element(reason, "Text")).unwrap_or("SOAP fault").trim().to_string();
+            let subcode = element(fault, "Subcode").unwrap_or_default();
+            if UNSUPPORTED_FAULTS.iter().any(|code| subcode.contains(code)) {
+                return Err(CameraControlError::Unsupported(reason).into());
+            }
+            return Err(CameraControlError::Device(reason).into());
+        }
+        if !status.is_success() {
+            return Err(CameraControlError::Device(format!("ONVIF service returned {}", status)).into());
+        }
+        Ok(text)
+    }
+
+    fn security_header(&self) -> String {
+        let Some((username, password)) = &self.credentials else {
+            return String::new();
+        };
+
+        // PasswordDigest = Base64(SHA1(nonce + created + password))
+        let nonce = Uuid::new_v4();
+        let created = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let mut digest = Sha1::new();
+        digest.update(nonce.as_bytes());
+        digest.update(created.as_bytes());
+        digest.update(password.as_bytes());
+
+        format!(
+            concat!(
+                r#"<s:Header><wsse:Security s:mustUnderstand="1" "#,
+                r#"xmlns:wsse="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-secext-1.0.xsd" "#,
+                r#"xmlns:wsu="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-wssecurity-utility-1.0.xsd">"#,
+                r#"<wsse:UsernameToken><wsse:Username>{}</wsse:Username>"#,
+                r#"<wsse:Password Type="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-username-token-profile-1.0#PasswordDigest">{}</wsse:Password>"#,
+                r#"<wsse:Nonce EncodingType="http://docs.oasis-open.org/wss/2004/01/oasis-200401-wss-soap-message-security-1.0#Base64Binary">{}</wsse:Nonce>"#,
+                r#"<wsu:Created>{}</wsu:Created></wsse:UsernameToken></wsse:Security></s:Header>"#,
+            ),
+            escape(username),
+            BASE64.encode(digest.finalize()),
+            BASE64.encode(nonce.as_bytes()),
+            created
+        )
+    }
+}
+
+// Passes dashboard PTZ and exposure commands through to cameras over ONVIF.
+// Commands to one camera are spaced at least `min_command_interval_ms`
+// apart so a held joystick can't flood a camera's controller. A stop always
+// goes through, so a moving camera can be halted even right after a command.
+pub struct CameraControl {
+    config: CameraControlConfig,
+    last_command: Mutex<HashMap<Uuid, Instant>>,
+}
+
+impl CameraControl {
+    pub fn new(config: &CameraControlConfig) -> Self {
+        Self {
+            config: config.clone(),
+            last_command: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn ptz(&self, camera: &Camera, request: &PtzRequest) -> Result<()> {
+        let client = self.client(camera)?;
+        if request.mode == PtzMode::Stop {
+            self.last_command.lock().unwrap().insert(camera.id, Instant::now());
+        } else {
+            self.admit(camera.id, Instant::now())?;
+        }
+        client.ptz(request).await
+    }
+
+    pub async fn set_exposure(&self, camera: &Camera, request: &ExposureRequest) -> Result<()> {
+        let client = self.client(camera)?;
+        self.admit(camera.id, Instant::now())?;
+        client.set_exposure(request).await
+    }
+
+    fn client(&self, camera: &Camera) -> Result<OnvifClient> {
+        let onvif_url = camera
+            .onvif_url
+            .as_deref()
+            .ok_or_else(|| CameraControlError::Unsupported(format!("Camera {} has no ONVIF endpoint configured", camera.name)))?;
+        OnvifClient::new(onvif_url, &self.config).map_err(|e| anyhow!("ONVIF client for camera {}: {}", camera.id, e))
+    }
+
+    // Records the command as sent if the camera's interval has passed
+    pub fn admit(&self, camera_id: Uuid, now: Instant) -> std::result::Result<(), CameraControlError> {
+        let interval = Duration::from_millis(self.config.min_command_interval_ms);
+        let mut last_command = self.last_command.lock().unwrap();
+
+        if let Some(last) = last_command.get(&camera_id) {
+            let elapsed = now.saturating_duration_since(*last);
+            if elapsed < interval {
+                return Err(CameraControlError::RateLimited {
+                    camera_id,
+                    retry_after_ms: (interval - elapsed).as_millis() as u64,
+                });
+            }
+        }
+        last_command.insert(camera_id, now);
+        Ok(())
+    }
+}
+
+// The content of the first element named `local_name`, in any namespace
+fn element<'a>(xml: &'a str, local_name: &str) -> Option<&'a str> {
+    let (name, attributes_end) = start_tag(xml, local_name)?;
+    if xml[..attributes_end].ends_with('/') {
+        return Some("");
+    }
+    let content_start = attributes_end + 1;
+    let content_end = content_start + xml[content_start..].find(&format!("</{}>", name))?;
+    Some(&xml[content_start..content_end])
+}
+
+fn attribute<'a>(xml: &'a str, local_name: &str, attribute: &str) -> Option<&'a str> {
+    let (name, attributes_end) = start_tag(xml, local_name)?;
+    let tag_start = xml[..attributes_end].rfind(&format!("<{}", name))?;
+    let tag = &xml[tag_start..attributes_end];
+    let value_start = tag.find(&format!(" {}=\"", attribute))? + attribute.len() + 3;
+    let value_end = value_start + tag[value_start..].find('"')?;
+    Some(&tag[value_start..value_end])
+}
+
+// The qualified name of the first start tag with this local name, and the
+// offset of its closing '>'
+fn start_tag<'a>(xml: &'a str, local_name: &str) -> Option<(&'a str, usize)> {
+    let mut offset = 0;
+    while let Some(open) = xml[offset..].find('<') {
+        let name_start = offset + open + 1;
+        let tag = &xml[name_start..];
+        let name = &tag[..tag.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(tag.len())];
+        offset = name_start;
+
+        if !name.is_empty() && name.rsplit(':').next() == Some(local_name) {
+            return Some((name, name_start + tag.find('>')?));
+        }
+    }
+    None
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ApiError;
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, ResponseError};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn soap(body: &str) -> HttpResponse {
+        HttpResponse::Ok()
+            .content_type("application/soap+xml; charset=utf-8")
+            .body(format!(r#"<s:Envelope {}><s:Body>{}</s:Body></s:Envelope>"#, SOAP_ENVELOPE_NAMESPACES, body))
+    }
+
+    // GetCapabilities for a device at this server; a fixed camera has no PTZ service
+    fn capabilities(request: &HttpRequest, with_ptz: bool) -> HttpResponse {
+        let base = format!("http://{}", request.connection_info().host());
+        let ptz = if with_ptz { format!("<tt:PTZ><tt:XAddr>{}/onvif/ptz_service</tt:XAddr></tt:PTZ>", base) } else { String::new() };
+        soap(&format!(
+            "<tds:GetCapabilitiesResponse><tds:Capabilities>\
+             <tt:Device><tt:XAddr>{base}/onvif/device_service</tt:XAddr></tt:Device>\
+             <tt:Media><tt:XAddr>{base}/onvif/media_service</tt:XAddr></tt:Media>\
+             {ptz}\
+             </tds:Capabilities></tds:GetCapabilitiesResponse>"
+        ))
+    }
+
+    fn camera(onvif_url: Option<String>) -> Camera {
+        serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "name": "dock-ptz",
+            "description": null,
+            "device_id": "dock-ptz",
+            "location": "dock 1",
+            "zone": null,
+            "stream_url": "rtsp://dock/stream",
+            "rtsp_url": null,
+            "onvif_url": onvif_url,
+            "status": "Online",
+            "health_status": "Healthy",
+            "last_ping": null,
+            "fps": null,
+            "resolution_width": null,
+            "resolution_height": null,
+            "intrinsics": null,
+            "extrinsics": null,
+            "calibration_status": "NotCalibrated",
+            "last_calibration": null,
+            "created_at": Utc::now(),
+            "updated_at": Utc::now()
+        }))
+        .unwrap()
+    }
+
+    fn pan_right() -> PtzRequest {
+        PtzRequest { mode: PtzMode::Continuous, pan: 0.5, tilt: 0.0, zoom: 0.0, timeout_ms: Some(1500) }
+    }
+
+    fn status(error: anyhow::Error) -> u16 {
+        ApiError::from(error).status_code().as_u16()
+    }
+
+    #[actix_rt::test]
+    async fn test_ptz_move_is_sent_and_unsupported_camera_is_409() {
+        let ptz_commands = Arc::new(Mutex::new(Vec::<String>::new()));
+        let recorded = ptz_commands.clone();
+        let server = HttpServer::new(move || {
+            let recorded = recorded.clone();
+            App::new()
+                .route("/onvif/device_service", web::post().to(|request: HttpRequest| async move { capabilities(&request, true) }))
+                .route("/fixed/device_service", web::post().to(|request: HttpRequest| async move { capabilities(&request, false) }))
+                .route("/onvif/media_service", web::post().to(|| async {
+                    soap(r#"<trt:GetProfilesResponse><trt:Profiles token="Profile_1" fixed="true"><tt:Name>main</tt:Name>
+                        <tt:VideoSourceConfiguration token="VSC_1"><tt:SourceToken>VideoSource_1</tt:SourceToken></tt:VideoSourceConfiguration>
+                        </trt:Profiles></trt:GetProfilesResponse>"#)
+                }))
+                .route("/onvif/ptz_service", web::post().to(move |body: String| {
+                    let recorded = recorded.clone();
+                    async move {
+                        recorded.lock().unwrap().push(body);
+                        soap("<tptz:ContinuousMoveResponse/>")
+                    }
+                }))
+        })
+        .bind("127.0.0.1:0")
+        .unwrap();
+        let address = server.addrs()[0];
+        actix_rt::spawn(server.run());
+
+        let config = CameraControlConfig {
+            onvif_username: Some("operator".to_string()),
+            onvif_password: Some("secret".to_string()),
+            command_timeout_sec: 5,
+            min_command_interval_ms: 200,
+        };
+        let control = CameraControl::new(&config);
+
+        let ptz_camera = camera(Some(format!("http://{}/onvif/device_service", address)));
+        control.ptz(&ptz_camera, &pan_right()).await.unwrap();
+
+        let commands = ptz_commands.lock().unwrap().clone();
+        assert_eq!(commands.len(), 1);
+        let command = element(&commands[0], "ContinuousMove").unwrap();
+        assert_eq!(element(command, "ProfileToken"), Some("Profile_1"));
+        assert_eq!(attribute(command, "PanTilt", "x"), Some("0.5"));
+        assert_eq!(element(command, "Timeout"), Some("PT1.5S"));
+        assert_eq!(element(&commands[0], "Username"), Some("operator"));
+
+        // A second command straight away is throttled, but a stop is not
+        assert_eq!(status(control.ptz(&ptz_camera, &pan_right()).await.unwrap_err()), 429);
+        assert_eq!(ptz_commands.lock().unwrap().len(), 1);
+        let stop = PtzRequest { mode: PtzMode::Stop, pan: 0.0, tilt: 0.0, zoom: 0.0, timeout_ms: None };
+        control.ptz(&ptz_camera, &stop).await.unwrap();
+        let commands = ptz_commands.lock().unwrap().clone();
+        assert_eq!(commands.len(), 2);
+        assert!(element(&commands[1], "Stop").is_some());
+
+        // No PTZ service, or no ONVIF at all, is a conflict with the camera's capabilities
+        let fixed_camera = camera(Some(format!("http://{}/fixed/device_service", address)));
+        let error = control.ptz(&fixed_camera, &pan_right()).await.unwrap_err();
+        assert!(matches!(error.downcast_ref::<CameraControlError>(), Some(CameraControlError::Unsupported(_))));
+        assert_eq!(status(error), 409);
+        assert_eq!(status(control.ptz(&camera(None), &pan_right()).await.unwrap_err()), 409);
+        assert_eq!(ptz_commands.lock().unwrap().len(), 2);
+    }
+}
@@ -1,115 +1,540 @@
 use anyhow::Result;
-use sqlx::postgres::PgPool;
+use crate::storage::DbPool;
 use uuid::Uuid;
-use chrono::Utc;
-use tokio::time::{self, Duration};
-use tracing::{info, warn, error};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use futures::{stream, Future, StreamExt};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::time::{self, Duration, Instant};
+use tracing::{debug, info, warn, error};
 
 use crate::{
-    models::{Camera, CameraStatus, CameraHealthStatus, CameraHealthMetrics},
+    config::{MonitoringConfig, StreamingConfig},
+    models::{Camera, CameraStatus, CameraHealthStatus, CameraHealthMetrics, EventSeverity, SystemEventType},
     services::camera_service::CameraService,
+    services::SystemService,
+    services::{AlertRoutingService, ShutdownSignal},
+    services::stream_probe::{self, StreamStats},
 };
 
+// Per-camera probe breaker. After `failure_threshold` consecutive failures
+// the camera is reported offline without probing until a half-open retry
+// is due; each failed retry doubles the wait, up to `max_backoff`.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    base_backoff: Duration,
+    max_backoff: Duration,
+    consecutive_failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, base_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            base_backoff,
+            max_backoff,
+            consecutive_failures: 0,
+            retry_at: None,
+        }
+    }
+    
+    // Closed, or open with the half-open retry due
+    pub fn should_probe(&self, now: Instant) -> bool {
+        match self.retry_at {
+            Some(retry_at) => now >= retry_at,
+            None => true,
+        }
+    }
+    
+    pub fn is_open(&self) -> bool {
+        self.retry_at.is_some()
+    }
+    
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.retry_at = None;
+    }
+    
+    pub fn record_failure(&mut self, now: Instant) {
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= self.failure_threshold {
+            self.retry_at = Some(now + self.backoff());
+        }
+    }
+    
+    fn backoff(&self) -> Duration {
+        let doublings = (self.consecutive_failures - self.failure_threshold + 1).min(16);
+        self.base_backoff.saturating_mul(1 << doublings).min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reachability {
+    Online,
+    // Failing but not yet offline, or answering but not yet back online
+    Warning,
+    Offline,
+}
+
+// Per-camera status hysteresis, so one dropped probe doesn't flip a camera
+// offline and one lucky probe doesn't bring it back
+#[derive(Debug, Clone)]
+pub struct ReachabilityTracker {
+    offline_after: u32,
+    online_after: u32,
+    consecutive_failures: u32,
+    consecutive_successes: u32,
+    offline: bool,
+}
+
+impl ReachabilityTracker {
+    pub fn new(offline_after: u32, online_after: u32) -> Self {
+        Self {
+            offline_after: offline_after.max(1),
+            online_after: online_after.max(1),
+            consecutive_failures: 0,
+            consecutive_successes: 0,
+            offline: false,
+        }
+    }
+    
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+    
+    pub fn record(&mut self, connected: bool) -> Reachability {
+        if connected {
+            self.consecutive_failures = 0;
+            self.consecutive_successes += 1;
+            if self.offline && self.consecutive_successes < self.online_after {
+                return Reachability::Warning;
+            }
+            self.offline = false;
+            Reachability::Online
+        } else {
+            self.consecutive_successes = 0;
+            self.consecutive_failures += 1;
+            if !self.offline && self.consecutive_failures < self.offline_after {
+                return Reachability::Warning;
+            }
+            self.offline = true;
+            Reachability::Offline
+        }
+    }
+}
+
+// How the camera monitor's last sweep went, for the health detail endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepReport {
+    pub finished_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub cameras: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct SweepTracker {
+    last: Mutex<Option<SweepReport>>,
+}
+
+impl SweepTracker {
+    pub fn record(&self, report: SweepReport) {
+        *self.last.lock().unwrap() = Some(report);
+    }
+    
+    pub fn last(&self) -> Option<SweepReport> {
+        self.last.lock().unwrap().clone()
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProbeState {
+    breaker: Option<CircuitBreaker>,
+    reachability: Option<ReachabilityTracker>,
+}
+
 pub struct CameraMonitor {
-    db_pool: PgPool,
+    db_pool: DbPool,
     check_interval: Duration,
+    failure_threshold: u32,
+    max_backoff: Duration,
+    offline_after_failures: u32,
+    online_after_successes: u32,
+    check_concurrency: usize,
+    health_sample: Duration,
+    ffmpeg_path: PathBuf,
+    probes: Mutex<HashMap<Uuid, ProbeState>>,
+    routing: Arc<AlertRoutingService>,
+    sweeps: Arc<SweepTracker>,
 }
 
 impl CameraMonitor {
-    pub fn new(db_pool: PgPool, check_interval: Duration) -> Self {
-        Self { db_pool, check_interval }
+    pub fn new(db_pool: DbPool, config: &MonitoringConfig, streaming: &StreamingConfig) -> Self {
+        Self {
+            routing: Arc::new(AlertRoutingService::new(db_pool.clone(), config)),
+            db_pool,
+            check_interval: Duration::from_secs(config.health_check_interval_sec),
+            failure_threshold: config.camera_probe_failure_threshold,
+            max_backoff: Duration::from_secs(config.camera_probe_max_backoff_sec),
+            offline_after_failures: config.camera_offline_after_failures,
+            online_after_successes: config.camera_online_after_successes,
+            check_concurrency: config.camera_check_concurrency.max(1),
+            health_sample: Duration::from_secs(config.camera_health_sample_sec.max(1)),
+            ffmpeg_path: streaming.ffmpeg_path.clone(),
+            probes: Mutex::new(HashMap::new()),
+            sweeps: Arc::new(SweepTracker::default()),
+        }
+    }
+    
+    pub fn sweeps(&self) -> Arc<SweepTracker> {
+        self.sweeps.clone()
     }
     
-    pub async fn start(&self) -> Result<()> {
+    pub async fn start(&self, mut shutdown: ShutdownSignal) -> Result<()> {
         let mut interval = time::interval(self.check_interval);
         
         info!("Starting camera monitor with interval: {:?}", self.check_interval);
         
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.recv() => {
+                    info!("Camera monitor stopped");
+                    return Ok(());
+                }
+            }
             
-            if let Err(e) = self.check_cameras().await {
+            let started = Instant::now();
+            let result = self.check_cameras().await;
+            if let Err(e) = &result {
                 error!("Error checking cameras: {}", e);
             }
+            self.sweeps.record(SweepReport {
+                finished_at: Utc::now(),
+                duration_ms: started.elapsed().as_millis() as u64,
+                cameras: *result.as_ref().unwrap_or(&0),
+                error: result.err().map(|e| e.to_string()),
+            });
         }
     }
     
-    async fn check_cameras(&self) -> Result<()> {
+    // Returns the number of cameras checked
+    async fn check_cameras(&self) -> Result<usize> {
         let camera_service = CameraService::new(self.db_pool.clone());
         
         let cameras = camera_service.get_all_cameras().await?;
+        let checked = cameras.len();
         
-        for camera in cameras {
-            if let Err(e) = self.check_camera(&camera).await {
-                warn!("Error checking camera {}: {}", camera.id, e);
+        // Probes run in parallel, so a sweep takes about one probe timeout
+        // no matter how many cameras are dead
+        let measured = Mutex::new(Vec::new());
+        let measured_ref = &measured;
+        sweep(cameras, self.check_concurrency, move |camera| async move {
+            match self.check_camera(&camera).await {
+                Ok(Some(metrics)) => measured_ref.lock().unwrap().push(metrics),
+                Ok(None) => {}
+                Err(e) => warn!("Error checking camera {}: {}", camera.id, e),
             }
-        }
+        })
+        .await;
         
-        Ok(())
+        // One insert for the whole sweep, in measurement order
+        let mut measured = measured.into_inner().unwrap();
+        measured.sort_by_key(|metrics| metrics.timestamp);
+        let write = camera_service.save_health_metrics_batch(&measured).await?;
+        debug!("Saved health metrics for {} cameras in {} statements", write.inserted, write.statements);
+        
+        Ok(checked)
     }
     
-    async fn check_camera(&self, camera: &Camera) -> Result<()> {
+    // The camera's health metrics when it was reachable, for the sweep to save
+    async fn check_camera(&self, camera: &Camera) -> Result<Option<CameraHealthMetrics>> {
         let camera_service = CameraService::new(self.db_pool.clone());
         
-        // Test camera connection
-        let is_connected = camera_service.test_camera_connection(camera.id).await?;
+        // Open breaker: keep the camera's current status without waiting
+        // on a dead endpoint
+        if !self.breaker_allows_probe(camera.id) {
+            debug!("Skipping probe of camera {} while its circuit is open", camera.id);
+            let (status, health_status) = if self.is_offline(camera.id) {
+                (CameraStatus::Offline, CameraHealthStatus::Critical)
+            } else {
+                (CameraStatus::Online, CameraHealthStatus::Warning)
+            };
+            camera_service.update_camera_status(camera.id, status, health_status).await?;
+            return Ok(None);
+        }
         
-        let (status, health_status) = if is_connected {
+        // Test camera connection; a probe error counts as a failure
+        let is_connected = match camera_service.test_camera_connection(camera.id).await {
+            Ok(connected) => connected,
+            Err(e) => {
+                warn!("Probe of camera {} failed: {}", camera.id, e);
+                false
+            }
+        };
+        let (reachability, went_offline) = self.record_probe(camera.id, is_connected);
+        
+        let health_metrics = if is_connected {
             // If connected, check health metrics
-            let health_metrics = self.measure_camera_health(camera).await?;
-            
-            // Save health metrics
-            camera_service.save_health_metrics(health_metrics).await?;
-            
-            (CameraStatus::Online, self.determine_health_status(&health_metrics))
+            Some(self.measure_camera_health(camera).await?)
         } else {
-            // If not connected, mark as offline
-            (CameraStatus::Offline, CameraHealthStatus::Critical)
+            None
+        };
+        
+        let (status, health_status) = match (reachability, &health_metrics) {
+            (Reachability::Online, Some(metrics)) => (CameraStatus::Online, determine_health_status(camera, metrics)),
+            // Recovering cameras stay offline until they've proven stable
+            (Reachability::Warning, Some(_)) => (CameraStatus::Offline, CameraHealthStatus::Warning),
+            (Reachability::Warning, None) => (CameraStatus::Online, CameraHealthStatus::Warning),
+            _ => (CameraStatus::Offline, CameraHealthStatus::Critical),
         };
         
         // Update camera status
         camera_service.update_camera_status(camera.id, status, health_status).await?;
         
-        Ok(())
+        if went_offline {
+            let message = format!("Camera {} offline after {} failed probes", camera.name, self.offline_after_failures);
+            warn!("{}", message);
+            let event = SystemService::new(self.db_pool.clone())
+                .log_event(
+                    SystemEventType::CameraOffline,
+                    EventSeverity::High,
+                    &message,
+                    Some("camera_monitor"),
+                    Some(serde_json::json!({ "camera_id": camera.id, "zone": camera.zone })),
+                )
+                .await?;
+            self.routing.spawn_dispatch(event);
+        }
+        
+        Ok(health_metrics)
     }
     
-    async fn measure_camera_health(&self, camera: &Camera) -> Result<CameraHealthMetrics> {
-        // In a real implementation, this would measure actual camera metrics
-        // For now, we'll simulate some metrics
-        
-        let fps = 30.0; // Simulated FPS
-        let latency_ms = 100.0; // Simulated latency
-        let packet_loss = 0.01; // Simulated packet loss
-        let resolution_width = camera.resolution_width.unwrap_or(1920);
-        let resolution_height = camera.resolution_height.unwrap_or(1080);
-        let bitrate_kbps = 4000.0; // Simulated bitrate
-        let cpu_usage = 25.0; // Simulated CPU usage
-        let memory_usage = 45.0; // Simulated memory usage
-        
-        Ok(CameraHealthMetrics {
-            camera_id: camera.id,
-            timestamp: Utc::now(),
-            fps,
-            latency_ms,
-            packet_loss,
-            resolution_width,
-            resolution_height,
-            bitrate_kbps,
-            cpu_usage,
-            memory_usage,
-        })
+    fn breaker_allows_probe(&self, camera_id: Uuid) -> bool {
+        let probes = self.probes.lock().unwrap();
+        probes
+            .get(&camera_id)
+            .and_then(|state| state.breaker.as_ref())
+            .map(|breaker| breaker.should_probe(Instant::now()))
+            .unwrap_or(true)
+    }
+    
+    fn is_offline(&self, camera_id: Uuid) -> bool {
+        let probes = self.probes.lock().unwrap();
+        probes
+            .get(&camera_id)
+            .and_then(|state| state.reachability.as_ref())
+            .map(|reachability| reachability.is_offline())
+            .unwrap_or(false)
     }
     
-    fn determine_health_status(&self, metrics: &CameraHealthMetrics) -> CameraHealthStatus {
-        // Simple health determination logic
-        if metrics.packet_loss > 0.1 || metrics.latency_ms > 500.0 {
-            CameraHealthStatus::Critical
-        } else if metrics.packet_loss > 0.05 || metrics.latency_ms > 200.0 {
-            CameraHealthStatus::Warning
-        } else if metrics.fps < 15.0 {
-            CameraHealthStatus::Warning
+    // The camera's reachability after this probe, and whether it just went offline
+    fn record_probe(&self, camera_id: Uuid, connected: bool) -> (Reachability, bool) {
+        let mut probes = self.probes.lock().unwrap();
+        let state = probes.entry(camera_id).or_default();
+        let breaker = state
+            .breaker
+            .get_or_insert_with(|| CircuitBreaker::new(self.failure_threshold, self.check_interval, self.max_backoff));
+        
+        if connected {
+            if breaker.is_open() {
+                info!("Camera {} is reachable again, resuming normal probing", camera_id);
+            }
+            breaker.record_success();
         } else {
-            CameraHealthStatus::Healthy
+            breaker.record_failure(Instant::now());
         }
+        
+        let tracker = state
+            .reachability
+            .get_or_insert_with(|| ReachabilityTracker::new(self.offline_after_failures, self.online_after_successes));
+        let was_offline = tracker.is_offline();
+        let reachability = tracker.record(connected);
+        (reachability, !was_offline && tracker.is_offline())
+    }
+    
+    // Samples the live stream rather than trusting the configured values
+    async fn measure_camera_health(&self, camera: &Camera) -> Result<CameraHealthMetrics> {
+        let url = camera.rtsp_url.as_deref().unwrap_or(&camera.stream_url);
+        let stats = stream_probe::probe_stream(&self.ffmpeg_path, url, self.health_sample).await?;
+        
+        if stats.resolution_changes > 0 {
+            info!(
+                "Camera {} switched resolution {} times while sampled, now {}x{}",
+                camera.id, stats.resolution_changes, stats.width, stats.height
+            );
+        }
+        
+        Ok(health_metrics(camera.id, &stats))
+    }
+}
+
+fn health_metrics(camera_id: Uuid, stats: &StreamStats) -> CameraHealthMetrics {
+    CameraHealthMetrics {
+        camera_id,
+        timestamp: Utc::now(),
+        fps: stats.fps,
+        latency_ms: stats.latency_ms,
+        packet_loss: stats.packet_loss,
+        resolution_width: stats.width,
+        resolution_height: stats.height,
+        bitrate_kbps: stats.bitrate_kbps,
+        // Not visible from the stream; cameras don't report these to us
+        cpu_usage: 0.0,
+        memory_usage: 0.0,
+    }
+}
+
+// An adaptive stream stepping down in resolution or falling well short of
+// the camera's configured frame rate is degraded, not broken
+fn determine_health_status(camera: &Camera, metrics: &CameraHealthMetrics) -> CameraHealthStatus {
+    let below_resolution = match (camera.resolution_width, camera.resolution_height) {
+        (Some(width), Some(height)) => metrics.resolution_width < width || metrics.resolution_height < height,
+        _ => false,
+    };
+    let below_fps = camera.fps.map(|fps| metrics.fps < fps * 0.8).unwrap_or(false);
+    
+    if metrics.packet_loss > 0.1 || metrics.latency_ms > 500.0 || metrics.fps < 1.0 {
+        CameraHealthStatus::Critical
+    } else if metrics.packet_loss > 0.05 || metrics.latency_ms > 200.0 {
+        CameraHealthStatus::Warning
+    } else if metrics.fps < 15.0 || below_fps || below_resolution {
+        CameraHealthStatus::Warning
+    } else {
+        CameraHealthStatus::Healthy
+    }
+}
+
+// Runs `check` on every item with at most `concurrency` in flight
+async fn sweep<T, F, Fut>(items: Vec<T>, concurrency: usize, check: F)
+where
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    stream::iter(items)
+        .for_each_concurrent(concurrency, check)
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_failing_camera_is_probed_with_increasing_backoff() {
+        let interval = Duration::from_secs(10);
+        let mut breaker = CircuitBreaker::new(3, interval, Duration::from_secs(600));
+        let start = Instant::now();
+
+        // Monitor sweeps every 10s; the camera never answers
+        let mut probed_sweeps = Vec::new();
+        for sweep in 0..64u32 {
+            let now = start + interval * sweep;
+            if breaker.should_probe(now) {
+                probed_sweeps.push(sweep);
+                breaker.record_failure(now);
+            }
+        }
+
+        let gaps: Vec<u32> = probed_sweeps.windows(2).map(|w| w[1] - w[0]).collect();
+        assert_eq!(gaps, vec![1, 1, 2, 4, 8, 16]);
+        assert!(breaker.is_open());
+    }
+
+    #[test]
+    fn test_success_closes_breaker_and_backoff_is_capped() {
+        let interval = Duration::from_secs(10);
+        let mut breaker = CircuitBreaker::new(2, interval, Duration::from_secs(60));
+        let start = Instant::now();
+
+        for _ in 0..10 {
+            breaker.record_failure(start);
+        }
+        assert!(!breaker.should_probe(start + Duration::from_secs(59)));
+        assert!(breaker.should_probe(start + Duration::from_secs(60)));
+
+        breaker.record_success();
+        assert!(!breaker.is_open());
+        assert!(breaker.should_probe(start));
+
+        // A single failure after recovery doesn't reopen it
+        breaker.record_failure(start);
+        assert!(breaker.should_probe(start));
+    }
+
+    #[test]
+    fn test_single_blip_does_not_mark_camera_offline() {
+        let mut tracker = ReachabilityTracker::new(3, 2);
+
+        // One dropped probe between good ones only warns
+        assert_eq!(tracker.record(true), Reachability::Online);
+        assert_eq!(tracker.record(false), Reachability::Warning);
+        assert_eq!(tracker.record(true), Reachability::Online);
+        assert_eq!(tracker.record(false), Reachability::Warning);
+        assert_eq!(tracker.record(false), Reachability::Warning);
+        assert_eq!(tracker.record(true), Reachability::Online);
+        assert!(!tracker.is_offline());
+
+        // Sustained failures do
+        let states: Vec<Reachability> = (0..4).map(|_| tracker.record(false)).collect();
+        assert_eq!(states, vec![Reachability::Warning, Reachability::Warning, Reachability::Offline, Reachability::Offline]);
+
+        // Coming back takes two good probes in a row
+        assert_eq!(tracker.record(true), Reachability::Warning);
+        assert_eq!(tracker.record(false), Reachability::Offline);
+        assert_eq!(tracker.record(true), Reachability::Warning);
+        assert_eq!(tracker.record(true), Reachability::Online);
+        assert!(!tracker.is_offline());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_of_slow_cameras_takes_one_timeout() {
+        let probe_time = Duration::from_millis(300);
+        let completed = AtomicUsize::new(0);
+        let started = Instant::now();
+
+        // Ten cameras that each hang for the full probe time
+        let counter = &completed;
+        sweep((0..10).collect(), 16, move |_: usize| async move {
+            time::sleep(probe_time).await;
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .await;
+
+        assert_eq!(completed.load(Ordering::SeqCst), 10);
+        assert!(started.elapsed() < probe_time * 2, "sweep took {:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_respects_concurrency_cap_and_isolates_failures() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+
+        let (in_flight_ref, peak_ref, completed_ref) = (&in_flight, &peak, &completed);
+        sweep((0..9).collect(), 3, move |i: usize| {
+            let (in_flight, peak, completed) = (in_flight_ref, peak_ref, completed_ref);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                time::sleep(Duration::from_millis(20)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                // A failing camera returns early without affecting the rest
+                if i % 4 == 0 {
+                    return;
+                }
+                completed.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(completed.load(Ordering::SeqCst), 6);
     }
 }
\ No newline at end of file
@@ -0,0 +1,88 @@
+use aetherforge_common::types::{BBox, CoordinateSpace, Detection, PerceptionFrame};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use futures::{Stream, TryStreamExt};
+use crate::storage::DbPool;
+use uuid::Uuid;
+
+use crate::models::DetectionRecord;
+
+// Rebuilds persisted detections into the PerceptionFrames they came from,
+// for replaying history through the offline scoring path
+#[derive(Clone)]
+pub struct DetectionExportService {
+    db_pool: DbPool,
+}
+
+impl DetectionExportService {
+    pub fn new(db_pool: DbPool) -> Self {
+        Self { db_pool }
+    }
+
+    // Frames in [from, to) in capture order. Detections are only stored for
+    // frames that had some, so empty frames are not reproduced.
+    pub fn export_frames(
+        &self,
+        camera_id: Uuid,
+        source_camera_id: String,
+        frame_size: (u32, u32),
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<impl Stream<Item = Result<PerceptionFrame>>> {
+        if to <= from {
+            bail!("'to' must be after 'from'");
+        }
+        let db_pool = self.db_pool.clone();
+
+        Ok(async_stream::try_stream! {
+            let mut rows = sqlx::query_as::<_, DetectionRecord>(
+                r#"
+                SELECT id, camera_id, frame_id, tracker_id, class_id, class_label,
+                       confidence, xmin, ymin, xmax, ymax, model_version, detected_at, created_at
+                FROM detections
+                WHERE camera_id = $1 AND detected_at >= $2 AND detected_at < $3
+                ORDER BY detected_at, frame_id
+                "#,
+            )
+            .bind(camera_id)
+            .bind(from)
+            .bind(to)
+            .fetch(&db_pool);
+
+            let mut current: Option<PerceptionFrame> = None;
+            while let Some(record) = rows.try_next().await? {
+                if current.as_ref().is_some_and(|frame| frame.frame_id != record.frame_id as u64) {
+                    if let Some(frame) = current.take() {
+                        yield frame;
+                    }
+                }
+
+                let frame = current.get_or_insert_with(|| PerceptionFrame {
+                    frame_id: record.frame_id as u64,
+                    timestamp: record.detected_at.timestamp_millis() as u64,
+                    source_camera_id: source_camera_id.clone(),
+                    image_width: frame_size.0,
+                    image_height: frame_size.1,
+                    model_version: record.model_version.clone(),
+                    inference_time_ms: 0.0,
+                    detections: Vec::new(),
+                    camera_intrinsics: None,
+                    camera_extrinsics: None,
+                    coordinate_space: CoordinateSpace::Pixels,
+                });
+                frame.detections.push(Detection {
+                    bbox: BBox::new(record.xmin as f32, record.ymin as f32, record.xmax as f32, record.ymax as f32),
+                    confidence: record.confidence as f32,
+                    class_id: record.class_id as u32,
+                    class_label: record.class_label,
+                    tracker_id: record.tracker_id.map(|id| id as u64),
+                    oriented: None,
+                });
+            }
+
+            if let Some(frame) = current {
+                yield frame;
+            }
+        })
+    }
+}
@@ -0,0 +1,251 @@
+use anyhow::{bail, Result};
+use crate::storage::DbPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tracing::warn;
+use uuid::Uuid;
+
+use aetherforge_common::types::{CoordinateSpace, PerceptionFrame};
+
+use crate::{
+    config::DetectionPersistenceConfig,
+    services::{handoff_time, DebouncedAlert},
+};
+
+// Decides which reported frames have their detections stored. While a
+// significant alert (a collision risk, an intrusion) is active for a camera,
+// or for the zone it's in, every frame of it is kept; otherwise one frame in
+// `background_every` is. An alert is active from its first report until
+// `window_ms` after its last, so the frames leading into an incident are
+// kept even before the alert is debounced into an event. Windows are in the
+// nodes' clock: alert and frame timestamps, in ms since the epoch, so frames
+// that arrive late are judged by when they were captured.
+pub struct PersistenceSampler {
+    significant: HashSet<String>,
+    window_ms: u64,
+    background_every: u64,
+    cameras: HashMap<String, u64>, // alert active until
+    zones: HashMap<String, u64>,
+    background: HashMap<String, u64>, // frames seen per camera outside alerts
+}
+
+impl PersistenceSampler {
+    pub fn new(config: &DetectionPersistenceConfig) -> Self {
+        Self {
+            significant: config.significant_alert_types.iter().cloned().collect(),
+            window_ms: config.alert_window_sec.saturating_mul(1000),
+            background_every: u64::from(config.background_sample_every.max(1)),
+            cameras: HashMap::new(),
+            zones: HashMap::new(),
+            background: HashMap::new(),
+        }
+    }
+
+    // Opens or extends the alert window of the camera and zone the alert is
+    // about. Returns whether the alert was significant.
+    pub fn observe_alert(&mut self, alert: &DebouncedAlert, timestamp_ms: u64) -> bool {
+        let details = alert.details.as_ref();
+        let alert_type = details
+            .and_then(|d| d.get("alert_type"))
+            .and_then(|t| t.as_str())
+            .unwrap_or_else(|| alert.event_type.as_str());
+        if !self.significant.contains(alert_type) {
+            return false;
+        }
+
+        let until = timestamp_ms.saturating_add(self.window_ms);
+        let camera = details
+            .and_then(|d| d.get("camera_id"))
+            .and_then(|c| c.as_str())
+            .or(alert.source.as_deref());
+        // An alert reported out of order mustn't cut a window short
+        if let Some(camera) = camera {
+            let active_until = self.cameras.entry(camera.to_string()).or_insert(until);
+            *active_until = (*active_until).max(until);
+        }
+        if let Some(zone) = details.and_then(|d| d.get("zone")).and_then(|z| z.as_str()) {
+            let active_until = self.zones.entry(zone.to_string()).or_insert(until);
+            *active_until = (*active_until).max(until);
+        }
+
+        true
+    }
+
+    pub fn should_persist(&mut self, camera: &str, zone: Option<&str>, timestamp_ms: u64) -> bool {
+        let active = |until: Option<&u64>| until.is_some_and(|until| timestamp_ms < *until);
+        if active(self.cameras.get(camera)) || active(zone.and_then(|zone| self.zones.get(zone))) {
+            return true;
+        }
+
+        let seen = self.background.entry(camera.to_string()).or_insert(0);
+        let persist = seen.is_multiple_of(self.background_every);
+        *seen += 1;
+        persist
+    }
+}
+
+// Stores the detections of the perception frames nodes report, sampled by
+// `PersistenceSampler`
+pub struct DetectionPersistenceService {
+    db_pool: DbPool,
+    sampler: Mutex<PersistenceSampler>,
+}
+
+impl DetectionPersistenceService {
+    pub fn new(db_pool: DbPool, config: &DetectionPersistenceConfig) -> Self {
+        Self {
+            db_pool,
+            sampler: Mutex::new(PersistenceSampler::new(config)),
+        }
+    }
+
+    pub fn observe_alert(&self, alert: &DebouncedAlert, timestamp_ms: u64) {
+        self.sampler.lock().unwrap().observe_alert(alert, timestamp_ms);
+    }
+
+    // Returns how many detections were stored. Frames from cameras that
+    // aren't registered are dropped.
+    pub async fn record_frames(&self, node_id: &str, mut frames: Vec<PerceptionFrame>) -> Result<u64> {
+        let device_ids: Vec<String> = frames
+            .iter()
+            .map(|f| f.source_camera_id.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let cameras: HashMap<String, (Uuid, Option<String>)> =
+            sqlx::query_as::<_, (String, Uuid, Option<String>)>("SELECT device_id, id, zone FROM cameras WHERE device_id = ANY($1)")
+                .bind(&device_ids)
+                .fetch_all(&self.db_pool)
+                .await?
+                .into_iter()
+                .map(|(device_id, id, zone)| (device_id, (id, zone)))
+                .collect();
+
+        let mut camera_ids = Vec::new();
+        let mut frame_ids = Vec::new();
+        let mut tracker_ids = Vec::new();
+        let mut class_ids = Vec::new();
+        let mut class_labels = Vec::new();
+        let mut confidences = Vec::new();
+        let mut xmins = Vec::new();
+        let mut ymins = Vec::new();
+        let mut xmaxs = Vec::new();
+        let mut ymaxs = Vec::new();
+        let mut model_versions = Vec::new();
+        let mut times = Vec::new();
+
+        {
+            let mut sampler = self.sampler.lock().unwrap();
+            for frame in &mut frames {
+                let Some((camera_id, zone)) = cameras.get(&frame.source_camera_id) else {
+                    warn!("Node {} reported frames from unknown camera {}", node_id, frame.source_camera_id);
+                    continue;
+                };
+                let Some(detected_at) = handoff_time(frame.timestamp) else {
+                    bail!("Frame {} of camera {} has an invalid timestamp", frame.frame_id, frame.source_camera_id);
+                };
+                if !sampler.should_persist(&frame.source_camera_id, zone.as_deref(), frame.timestamp) {
+                    continue;
+                }
+
+                // Stored boxes are in pixels, as the export rebuilds them
+                frame.convert_coordinates(CoordinateSpace::Pixels);
+                for detection in &frame.detections {
+                    camera_ids.push(*camera_id);
+                    frame_ids.push(frame.frame_id as i64);
+                    tracker_ids.push(detection.tracker_id.map(|id| id as i64));
+                    class_ids.push(detection.class_id as i32);
+                    class_labels.push(detection.class_label.clone());
+                    confidences.push(detection.confidence as f64);
+                    xmins.push(detection.bbox.xmin as f64);
+                    ymins.push(detection.bbox.ymin as f64);
+                    xmaxs.push(detection.bbox.xmax as f64);
+                    ymaxs.push(detection.bbox.ymax as f64);
+                    model_versions.push(frame.model_version.clone());
+                    times.push(detected_at);
+                }
+            }
+        }
+
+        if camera_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO detections (camera_id, frame_id, tracker_id, class_id, class_label, confidence,
+                                    xmin, ymin, xmax, ymax, model_version, detected_at)
+            SELECT * FROM UNNEST($1::UUID[], $2::BIGINT[], $3::BIGINT[], $4::INTEGER[], $5::TEXT[], $6::FLOAT[],
+                                 $7::FLOAT[], $8::FLOAT[], $9::FLOAT[], $10::FLOAT[], $11::TEXT[], $12::TIMESTAMPTZ[])
+            "#,
+        )
+        .bind(&camera_ids)
+        .bind(&frame_ids)
+        .bind(&tracker_ids)
+        .bind(&class_ids)
+        .bind(&class_labels)
+        .bind(&confidences)
+        .bind(&xmins)
+        .bind(&ymins)
+        .bind(&xmaxs)
+        .bind(&ymaxs)
+        .bind(&model_versions)
+        .bind(&times)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EventSeverity, SystemEventType};
+
+    fn alert(alert_type: &str, camera: &str) -> DebouncedAlert {
+        DebouncedAlert {
+            event_type: SystemEventType::Other,
+            severity: EventSeverity::Critical,
+            message: format!("{} on {}", alert_type, camera),
+            source: Some(camera.to_string()),
+            details: Some(serde_json::json!({ "alert_type": alert_type })),
+        }
+    }
+
+    #[test]
+    fn test_alert_window_persisted_at_full_rate_background_downsampled() {
+        let config = DetectionPersistenceConfig {
+            significant_alert_types: vec!["collision_risk".to_string()],
+            alert_window_sec: 10,
+            background_sample_every: 5,
+        };
+        let mut sampler = PersistenceSampler::new(&config);
+        let start: u64 = 1_700_000_000_000;
+
+        // 10fps for 2s on two cameras in the same zone, nothing going on
+        let kept = |sampler: &mut PersistenceSampler, camera: &str, from_ms: u64| {
+            (0..20)
+                .filter(|i| sampler.should_persist(camera, Some("dock"), start + from_ms + i * 100))
+                .count()
+        };
+        assert_eq!(kept(&mut sampler, "dock-1", 0), 4);
+        assert_eq!(kept(&mut sampler, "dock-2", 0), 4);
+
+        // A degraded camera isn't worth keeping every frame for
+        assert!(!sampler.observe_alert(&alert("camera_quality_degraded", "dock-1"), start + 2000));
+        assert_eq!(kept(&mut sampler, "dock-1", 2000), 4);
+
+        // A collision risk on dock-1 keeps all of its frames; dock-2 stays sampled
+        assert!(sampler.observe_alert(&alert("collision_risk", "dock-1"), start + 4000));
+        assert_eq!(kept(&mut sampler, "dock-1", 4000), 20);
+        assert_eq!(kept(&mut sampler, "dock-2", 4000), 4);
+
+        // An older report arriving late doesn't shorten the window
+        assert!(sampler.observe_alert(&alert("collision_risk", "dock-1"), start + 1000));
+        assert_eq!(kept(&mut sampler, "dock-1", 12000), 20);
+
+        // Once the window passes without another report, back to sampling
+        assert_eq!(kept(&mut sampler, "dock-1", 14000), 4);
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use crate::storage::DbPool;
+use tokio::time::{self, Duration};
+use tracing::{error, info};
+
+use crate::{config::MonitoringConfig, services::{ShutdownSignal, SystemService}};
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// Enforces `alert_retention_days` by purging acknowledged system events
+// older than it, hourly. A retention of 0 keeps events forever.
+pub struct EventRetention {
+    db_pool: DbPool,
+    retention_days: u32,
+}
+
+impl EventRetention {
+    pub fn new(db_pool: DbPool, config: &MonitoringConfig) -> Self {
+        Self {
+            db_pool,
+            retention_days: config.alert_retention_days,
+        }
+    }
+    
+    pub async fn start(&self, mut shutdown: ShutdownSignal) -> Result<()> {
+        if self.retention_days == 0 {
+            info!("Alert retention disabled, keeping system events");
+            return Ok(());
+        }
+        
+        let mut interval = time::interval(CLEANUP_INTERVAL);
+        
+        info!("Starting event retention, keeping acknowledged events for {} days", self.retention_days);
+        
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.recv() => {
+                    info!("Event retention stopped");
+                    return Ok(());
+                }
+            }
+            
+            if let Err(e) = self.purge().await {
+                error!("Error purging system events: {}", e);
+            }
+        }
+    }
+    
+    async fn purge(&self) -> Result<()> {
+        let system_service = SystemService::new(self.db_pool.clone());
+        
+        let purged = system_service.purge_events(retention_cutoff(self.retention_days), true).await?;
+        if purged > 0 {
+            info!("Purged {} acknowledged system events older than {} days", purged, self.retention_days);
+        }
+        
+        Ok(())
+    }
+}
+
+pub fn retention_cutoff(retention_days: u32) -> DateTime<Utc> {
+    Utc::now() - chrono::Duration::days(retention_days as i64)
+}
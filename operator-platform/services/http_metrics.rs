@@ -0,0 +1,100 @@
+use prometheus::{Encoder, HistogramOpts, HistogramVec, Registry, TextEncoder};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static REQUEST_TIMING: Arc<RequestTiming>;
+}
+
+// Time spent in the database while serving one request
+#[derive(Debug, Default)]
+pub struct RequestTiming {
+    db_micros: AtomicU64,
+}
+
+impl RequestTiming {
+    pub fn db(&self) -> Duration {
+        Duration::from_micros(self.db_micros.load(Ordering::Relaxed))
+    }
+
+    // Runs `request` with this timing current, so database time inside it
+    // adds to it
+    pub async fn scope<F: Future>(self: Arc<Self>, request: F) -> F::Output {
+        REQUEST_TIMING.scope(self, request).await
+    }
+}
+
+// Counts the time from start until drop towards the Server-Timing of the
+// request it was started in. Started outside a request it records nothing.
+pub struct DbTimer {
+    timing: Option<Arc<RequestTiming>>,
+    start: Instant,
+}
+
+impl DbTimer {
+    pub fn start() -> Self {
+        Self { timing: REQUEST_TIMING.try_with(Arc::clone).ok(), start: Instant::now() }
+    }
+}
+
+impl Drop for DbTimer {
+    fn drop(&mut self) {
+        if let Some(timing) = &self.timing {
+            let elapsed = self.start.elapsed().as_micros() as u64;
+            timing.db_micros.fetch_add(elapsed, Ordering::Relaxed);
+        }
+    }
+}
+
+// Per-endpoint latency histograms in a private registry, scraped from
+// GET /metrics. Endpoints are labelled by route pattern, e.g.
+// /api/v1/cameras/{id}, so ids don't explode the label set.
+pub struct HttpMetrics {
+    registry: Registry,
+    request_duration: HistogramVec,
+    db_duration: HistogramVec,
+}
+
+impl HttpMetrics {
+    pub fn new() -> Self {
+        let request_duration = HistogramVec::new(
+            HistogramOpts::new("aetherforge_http_request_duration_seconds", "Time to serve an API request"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let db_duration = HistogramVec::new(
+            HistogramOpts::new("aetherforge_http_request_db_seconds", "Database time within an API request"),
+            &["method", "route"],
+        )
+        .unwrap();
+
+        let registry = Registry::new();
+        registry.register(Box::new(request_duration.clone())).unwrap();
+        registry.register(Box::new(db_duration.clone())).unwrap();
+
+        Self { registry, request_duration, db_duration }
+    }
+
+    pub fn observe(&self, method: &str, route: &str, status: u16, total: Duration, db: Duration) {
+        self.request_duration
+            .with_label_values(&[method, route, &status.to_string()])
+            .observe(total.as_secs_f64());
+        self.db_duration.with_label_values(&[method, route]).observe(db.as_secs_f64());
+    }
+
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding into a Vec cannot fail");
+        String::from_utf8(buffer).expect("text encoding is always UTF-8")
+    }
+}
+
+impl Default for HttpMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
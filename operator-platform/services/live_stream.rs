@@ -0,0 +1,185 @@
+use actix_web::web::Bytes;
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::config::StreamingConfig;
+
+// Boundary ffmpeg's mpjpeg muxer writes between frames
+pub const MJPEG_BOUNDARY: &str = "ffmpeg";
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+const BROADCAST_CAPACITY: usize = 64;
+
+// Shares one upstream RTSP pull per camera between all browser viewers.
+// The transcoder is started by the first viewer and killed when the last
+// viewer disconnects.
+pub struct LiveStreamManager {
+    config: StreamingConfig,
+    streams: Mutex<HashMap<Uuid, LiveStream>>,
+}
+
+struct LiveStream {
+    tx: broadcast::Sender<Bytes>,
+    viewers: usize,
+    upstream: JoinHandle<()>,
+}
+
+pub struct LiveViewer {
+    pub rx: broadcast::Receiver<Bytes>,
+    _guard: ViewerGuard,
+}
+
+struct ViewerGuard {
+    manager: Arc<LiveStreamManager>,
+    camera_id: Uuid,
+}
+
+impl LiveStreamManager {
+    pub fn new(config: StreamingConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            streams: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn subscribe_mjpeg(self: &Arc<Self>, camera_id: Uuid, rtsp_url: &str) -> Result<LiveViewer> {
+        let mut streams = self.streams.lock().unwrap();
+
+        // Drop a stream whose transcoder exited so the next viewer restarts it
+        if streams.get(&camera_id).map(|s| s.upstream.is_finished()).unwrap_or(false) {
+            streams.remove(&camera_id);
+        }
+
+        let stream = match streams.get_mut(&camera_id) {
+            Some(stream) => {
+                if stream.viewers >= self.config.max_viewers_per_camera {
+                    bail!("Camera {} already has {} viewers", camera_id, stream.viewers);
+                }
+                stream
+            }
+            None => {
+                let (tx, _) = broadcast::channel(BROADCAST_CAPACITY);
+                let upstream = self.spawn_mjpeg_transcoder(camera_id, rtsp_url, tx.clone())?;
+                streams.entry(camera_id).or_insert(LiveStream { tx, viewers: 0, upstream })
+            }
+        };
+
+        stream.viewers += 1;
+
+        Ok(LiveViewer {
+            rx: stream.tx.subscribe(),
+            _guard: ViewerGuard {
+                manager: self.clone(),
+                camera_id,
+            },
+        })
+    }
+
+    pub fn viewer_count(&self, camera_id: Uuid) -> usize {
+        self.streams.lock().unwrap().get(&camera_id).map(|s| s.viewers).unwrap_or(0)
+    }
+
+    fn release(&self, camera_id: Uuid) {
+        let mut streams = self.streams.lock().unwrap();
+
+        if let Some(stream) = streams.get_mut(&camera_id) {
+            stream.viewers = stream.viewers.saturating_sub(1);
+
+            if stream.viewers == 0 {
+                if let Some(stream) = streams.remove(&camera_id) {
+                    // Aborting drops the child handle, which kills ffmpeg
+                    stream.upstream.abort();
+                    info!("Stopped live stream for camera {}: no viewers remain", camera_id);
+                }
+            }
+        }
+    }
+
+    fn spawn_mjpeg_transcoder(&self, camera_id: Uuid, rtsp_url: &str, tx: broadcast::Sender<Bytes>) -> Result<JoinHandle<()>> {
+        let mut child = Command::new(&self.config.ffmpeg_path)
+            .args(["-loglevel", "error", "-rtsp_transport", "tcp", "-i", rtsp_url, "-an"])
+            .args(["-r", &self.config.mjpeg_frame_rate.to_string()])
+            .args(["-q:v", &self.config.mjpeg_quality.to_string()])
+            .args(["-f", "mpjpeg", "pipe:1"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let mut stdout = match child.stdout.take() {
+            Some(stdout) => stdout,
+            None => bail!("Transcoder for camera {} has no stdout", camera_id),
+        };
+
+        info!("Started live MJPEG stream for camera {}", camera_id);
+
+        Ok(tokio::spawn(async move {
+            // Keep the child owned by the task so aborting it kills the process
+            let _child = child;
+            let mut buf = vec![0u8; READ_CHUNK_SIZE];
+
+            loop {
+                match stdout.read(&mut buf).await {
+                    Ok(0) => {
+                        warn!("Live stream transcoder for camera {} exited", camera_id);
+                        break;
+                    }
+                    Ok(n) => {
+                        // A send error only means every viewer has gone away
+                        let _ = tx.send(Bytes::copy_from_slice(&buf[..n]));
+                    }
+                    Err(e) => {
+                        warn!("Live stream read failed for camera {}: {}", camera_id, e);
+                        break;
+                    }
+                }
+            }
+        }))
+    }
+}
+
+impl Drop for ViewerGuard {
+    fn drop(&mut self) {
+        self.manager.release(self.camera_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // Needs ffmpeg and an RTSP server publishing `videotestsrc`, e.g.
+    //   gst-rtsp-launch "( videotestsrc ! x264enc ! rtph264pay name=pay0 )"
+    // with AETHERFORGE_TEST_RTSP_URL=rtsp://127.0.0.1:8554/test
+    #[tokio::test]
+    #[ignore]
+    async fn test_mjpeg_stream_starts_from_videotestsrc() {
+        let rtsp_url = std::env::var("AETHERFORGE_TEST_RTSP_URL")
+            .unwrap_or_else(|_| "rtsp://127.0.0.1:8554/test".to_string());
+
+        let manager = LiveStreamManager::new(crate::config::OperatorConfig::default().streaming);
+        let camera_id = Uuid::new_v4();
+
+        let mut viewer = manager.subscribe_mjpeg(camera_id, &rtsp_url).unwrap();
+        assert_eq!(manager.viewer_count(camera_id), 1);
+
+        let chunk = tokio::time::timeout(Duration::from_secs(10), viewer.rx.recv())
+            .await
+            .expect("no MJPEG data within timeout")
+            .unwrap();
+        assert!(chunk.starts_with(format!("--{}", MJPEG_BOUNDARY).as_bytes()));
+
+        drop(viewer);
+        assert_eq!(manager.viewer_count(camera_id), 0);
+    }
+}
\ No newline at end of file
@@ -1,13 +1,65 @@
 mod user_service;
 mod camera_service;
+mod camera_monitor;
 mod calibration_service;
 mod annotation_service;
+mod annotation_export;
+mod annotation_prediction;
 mod model_service;
 mod training_service;
+mod system_service;
+mod dataset_service;
+mod analytics_service;
+mod coverage_service;
+mod world_model_service;
+mod live_stream;
+mod webrtc_session;
+mod alert_debounce;
+mod detection_export;
+mod node_service;
+mod node_monitor;
+mod event_retention;
+mod retraining_monitor;
+mod stream_probe;
+mod signing_keys;
+mod http_metrics;
+mod camera_control;
+mod shutdown;
+mod alert_routing;
+mod detection_persistence;
+mod facility_map_service;
+mod calibration_export;
+mod user_preferences_service;
 
 pub use user_service::*;
 pub use camera_service::*;
+pub use camera_monitor::*;
 pub use calibration_service::*;
 pub use annotation_service::*;
+pub use annotation_export::*;
+pub use annotation_prediction::*;
 pub use model_service::*;
-pub use training_service::*;
\ No newline at end of file
+pub use training_service::*;
+pub use system_service::*;
+pub use dataset_service::*;
+pub use analytics_service::*;
+pub use coverage_service::*;
+pub use world_model_service::*;
+pub use live_stream::*;
+pub use webrtc_session::*;
+pub use alert_debounce::*;
+pub use detection_export::*;
+pub use node_service::*;
+pub use node_monitor::*;
+pub use event_retention::*;
+pub use retraining_monitor::*;
+pub use stream_probe::*;
+pub use signing_keys::*;
+pub use http_metrics::*;
+pub use camera_control::*;
+pub use shutdown::*;
+pub use alert_routing::*;
+pub use detection_persistence::*;
+pub use facility_map_service::*;
+pub use calibration_export::*;
+pub use user_preferences_service::*;
\ No newline at end of file
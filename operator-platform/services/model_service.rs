@@ -1,17 +1,19 @@
-use anyhow::Result;
-use sqlx::postgres::PgPool;
+use anyhow::{anyhow, Result};
+use ort::{Session, ValueType};
+use crate::storage::DbPool;
+use std::path::Path;
 use uuid::Uuid;
 use chrono::Utc;
 
-use crate::models::{Model, ModelType, ModelStatus, CreateModelRequest, UpdateModelRequest, ModelVersion, ModelDeployment, DeploymentStatus};
+use crate::models::{Model, ModelType, ModelStatus, CreateModelRequest, UpdateModelRequest, ModelVersion, ModelDeployment, DeploymentStatus, ModelSignature, TensorSignature};
 
 #[derive(Clone)]
 pub struct ModelService {
-    db_pool: PgPool,
+    db_pool: DbPool,
 }
 
 impl ModelService {
-    pub fn new(db_pool: PgPool) -> Self {
+    pub fn new(db_pool: DbPool) -> Self {
         Self { db_pool }
     }
     
@@ -43,6 +45,25 @@ impl ModelService {
         Ok(model)
     }
     
+    // Reads the real tensor signature from the model artifact and compares it
+    // with the shapes declared when the model was registered
+    pub async fn get_model_signature(&self, id: Uuid) -> Result<ModelSignature> {
+        let model = self.get_model(id).await?;
+        
+        let model_path = model.model_path.clone();
+        let (inputs, outputs) = tokio::task::spawn_blocking(move || read_onnx_signature(Path::new(&model_path))).await??;
+        
+        let mut mismatches = find_signature_mismatches("input", &model.input_shape, &inputs);
+        mismatches.extend(find_signature_mismatches("output", &model.output_shape, &outputs));
+        
+        Ok(ModelSignature {
+            model_id: model.id,
+            inputs,
+            outputs,
+            mismatches,
+        })
+    }
+    
     pub async fn get_model_versions(&self, name: &str) -> Result<Vec<ModelVersion>> {
         let versions = sqlx::query_as!(
             ModelVersion,
@@ -190,4 +211,146 @@ impl ModelService {
         
         Ok(deployment)
     }
+}
+
+pub fn read_onnx_signature(path: &Path) -> Result<(Vec<TensorSignature>, Vec<TensorSignature>)> {
+    let session = Session::builder()?
+        .commit_from_file(path)
+        .map_err(|e| anyhow!("Failed to load model {}: {}", path.display(), e))?;
+    
+    let inputs = session
+        .inputs
+        .iter()
+        .map(|input| tensor_signature(&input.name, &input.input_type))
+        .collect();
+    let outputs = session
+        .outputs
+        .iter()
+        .map(|output| tensor_signature(&output.name, &output.output_type))
+        .collect();
+    
+    Ok((inputs, outputs))
+}
+
+fn tensor_signature(name: &str, value_type: &ValueType) -> TensorSignature {
+    match value_type {
+        ValueType::Tensor { ty, dimensions, .. } => TensorSignature {
+            name: name.to_string(),
+            shape: dimensions.clone(),
+            dtype: format!("{:?}", ty).to_lowercase(),
+        },
+        other => TensorSignature {
+            name: name.to_string(),
+            shape: Vec::new(),
+            dtype: format!("{:?}", other).to_lowercase(),
+        },
+    }
+}
+
+/// Compares a declared shape with the real tensors. The declaration may be a
+/// single shape (`[1, 3, 640, 640]`, checked against the first tensor), a list
+/// of shapes by position, or an object keyed by tensor name. `null`, `-1` and
+/// symbolic names declare a dynamic dimension.
+pub fn find_signature_mismatches(kind: &str, declared: &serde_json::Value, actual: &[TensorSignature]) -> Vec<String> {
+    use serde_json::Value;
+    
+    let mut mismatches = Vec::new();
+    
+    match declared {
+        Value::Array(dims) if dims.iter().all(|d| !d.is_array()) => {
+            match actual.first() {
+                Some(tensor) => compare_shape(kind, tensor, dims, &mut mismatches),
+                None => mismatches.push(format!("Model has no {} tensors", kind)),
+            }
+        }
+        Value::Array(shapes) => {
+            if shapes.len() != actual.len() {
+                mismatches.push(format!(
+                    "Declared {} {} tensors but model has {}",
+                    shapes.len(), kind, actual.len()
+                ));
+            }
+            for (shape, tensor) in shapes.iter().zip(actual) {
+                if let Value::Array(dims) = shape {
+                    compare_shape(kind, tensor, dims, &mut mismatches);
+                }
+            }
+        }
+        Value::Object(shapes) => {
+            for (name, shape) in shapes {
+                match (actual.iter().find(|t| &t.name == name), shape) {
+                    (Some(tensor), Value::Array(dims)) => compare_shape(kind, tensor, dims, &mut mismatches),
+                    (Some(_), _) => mismatches.push(format!("Declared {} '{}' shape is not a list", kind, name)),
+                    (None, _) => mismatches.push(format!("Declared {} '{}' does not exist in the model", kind, name)),
+                }
+            }
+        }
+        _ => mismatches.push(format!("Declared {} shape is not a list or object", kind)),
+    }
+    
+    mismatches
+}
+
+fn compare_shape(kind: &str, tensor: &TensorSignature, declared: &[serde_json::Value], mismatches: &mut Vec<String>) {
+    if declared.len() != tensor.shape.len() {
+        mismatches.push(format!(
+            "{} '{}' has rank {} but {} was declared",
+            kind, tensor.name, tensor.shape.len(), declared.len()
+        ));
+        return;
+    }
+    
+    for (axis, (declared_dim, actual_dim)) in declared.iter().zip(&tensor.shape).enumerate() {
+        let declared_dim = match declared_dim.as_i64() {
+            Some(dim) if dim >= 0 => dim,
+            _ => continue, // dynamic in the declaration
+        };
+        
+        if *actual_dim >= 0 && *actual_dim != declared_dim {
+            mismatches.push(format!(
+                "{} '{}' axis {} is {} but {} was declared",
+                kind, tensor.name, axis, actual_dim, declared_dim
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    
+    // Identity model: images[N,3,4,4] float32 -> output[N,3,4,4] float32
+    fn fixture_path() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/identity_nx3x4x4.onnx")
+    }
+    
+    #[test]
+    fn test_read_onnx_signature_matches_fixture() {
+        let (inputs, outputs) = read_onnx_signature(&fixture_path()).unwrap();
+        
+        assert_eq!(inputs, vec![TensorSignature {
+            name: "images".to_string(),
+            shape: vec![-1, 3, 4, 4],
+            dtype: "float32".to_string(),
+        }]);
+        assert_eq!(outputs[0].name, "output");
+        assert_eq!(outputs[0].shape, vec![-1, 3, 4, 4]);
+        
+        // A matching declaration, with the batch axis pinned, is clean
+        assert!(find_signature_mismatches("input", &json!([1, 3, 4, 4]), &inputs).is_empty());
+        assert!(find_signature_mismatches("output", &json!({ "output": [null, 3, 4, 4] }), &outputs).is_empty());
+    }
+    
+    #[test]
+    fn test_stale_declared_shape_is_flagged() {
+        let (inputs, outputs) = read_onnx_signature(&fixture_path()).unwrap();
+        
+        let mismatches = find_signature_mismatches("input", &json!([1, 3, 640, 640]), &inputs);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].contains("axis 2 is 4 but 640 was declared"));
+        
+        let mismatches = find_signature_mismatches("output", &json!({ "detections": [1, 25200, 85] }), &outputs);
+        assert_eq!(mismatches, vec!["Declared output 'detections' does not exist in the model".to_string()]);
+    }
 }
\ No newline at end of file
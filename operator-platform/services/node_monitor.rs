@@ -0,0 +1,53 @@
+use anyhow::Result;
+use crate::storage::DbPool;
+use tokio::time::{self, Duration};
+use tracing::{error, info, warn};
+
+use crate::{config::MonitoringConfig, services::{NodeService, ShutdownSignal}};
+
+// Marks perception nodes stale once their heartbeats stop
+pub struct NodeMonitor {
+    db_pool: DbPool,
+    check_interval: Duration,
+    heartbeat_timeout_sec: u64,
+}
+
+impl NodeMonitor {
+    pub fn new(db_pool: DbPool, config: &MonitoringConfig) -> Self {
+        Self {
+            db_pool,
+            check_interval: Duration::from_secs(config.health_check_interval_sec),
+            heartbeat_timeout_sec: config.node_heartbeat_timeout_sec,
+        }
+    }
+    
+    pub async fn start(&self, mut shutdown: ShutdownSignal) -> Result<()> {
+        let mut interval = time::interval(self.check_interval);
+        
+        info!("Starting node monitor, heartbeat timeout: {}s", self.heartbeat_timeout_sec);
+        
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.recv() => {
+                    info!("Node monitor stopped");
+                    return Ok(());
+                }
+            }
+            
+            if let Err(e) = self.check_nodes().await {
+                error!("Error checking nodes: {}", e);
+            }
+        }
+    }
+    
+    async fn check_nodes(&self) -> Result<()> {
+        let node_service = NodeService::new(self.db_pool.clone());
+        
+        for node_id in node_service.mark_stale(self.heartbeat_timeout_sec).await? {
+            warn!("Perception node {} missed heartbeats for {}s, marked stale", node_id, self.heartbeat_timeout_sec);
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use crate::storage::DbPool;
+use std::collections::HashMap;
+use tokio::time;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    config::{AutoRetrainConfig, MLPipelineConfig},
+    models::{CreateTrainingJobRequest, EventSeverity, SystemEventType},
+    services::{ShutdownSignal, SystemService, TrainingService},
+};
+
+// A deployed model's recent performance, from the confidences of the
+// detections made with its version
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DeployedModelPerformance {
+    pub model_id: Uuid,
+    pub name: String,
+    pub version: String,
+    pub created_by: Uuid,
+    pub detections: i64,
+    pub mean_confidence: Option<f64>,
+    pub version_shared: bool, // another deployed model has the same version
+}
+
+struct Degradation {
+    since: DateTime<Utc>,
+    triggered: bool,
+}
+
+// Decides when a model has been degraded for long enough to retrain. A model
+// triggers once per episode: it must recover above the threshold before it
+// can trigger again.
+pub struct DegradationTracker {
+    min_mean_confidence: f64,
+    min_detections: i64,
+    sustained: Duration,
+    models: HashMap<Uuid, Degradation>,
+}
+
+impl DegradationTracker {
+    pub fn new(config: &AutoRetrainConfig) -> Self {
+        Self {
+            min_mean_confidence: config.min_mean_confidence,
+            min_detections: config.min_detections,
+            sustained: Duration::minutes(config.sustained_min as i64),
+            models: HashMap::new(),
+        }
+    }
+
+    // True when this observation should start a retraining job
+    pub fn observe(&mut self, performance: &DeployedModelPerformance, now: DateTime<Utc>) -> bool {
+        // Too little traffic to judge says nothing either way
+        let Some(mean) = performance.mean_confidence.filter(|_| performance.detections >= self.min_detections) else {
+            return false;
+        };
+
+        if mean >= self.min_mean_confidence {
+            self.models.remove(&performance.model_id);
+            return false;
+        }
+
+        let degradation = self.models.entry(performance.model_id).or_insert(Degradation { since: now, triggered: false });
+        if degradation.triggered || now - degradation.since < self.sustained {
+            return false;
+        }
+        degradation.triggered = true;
+        true
+    }
+
+    // Lets a model whose retraining couldn't be queued trigger again
+    pub fn retry(&mut self, model_id: Uuid) {
+        if let Some(degradation) = self.models.get_mut(&model_id) {
+            degradation.triggered = false;
+        }
+    }
+}
+
+// Opt-in: watches deployed models and queues a retraining job when one
+// degrades, see `AutoRetrainConfig`
+pub struct RetrainingMonitor {
+    db_pool: DbPool,
+    config: AutoRetrainConfig,
+    default_hyperparameters: serde_json::Value,
+}
+
+impl RetrainingMonitor {
+    pub fn new(db_pool: DbPool, config: &MLPipelineConfig) -> Self {
+        Self {
+            db_pool,
+            config: config.auto_retrain.clone(),
+            default_hyperparameters: config.default_hyperparameters.clone(),
+        }
+    }
+
+    pub async fn start(&self, mut shutdown: ShutdownSignal) -> Result<()> {
+        if !self.config.enabled {
+            info!("Automatic retraining disabled");
+            return Ok(());
+        }
+
+        let mut interval = time::interval(std::time::Duration::from_secs(self.config.check_interval_sec.max(1)));
+        let mut tracker = DegradationTracker::new(&self.config);
+
+        info!(
+            "Starting retraining monitor: retrain below {:.2} mean confidence for {} min",
+            self.config.min_mean_confidence, self.config.sustained_min
+        );
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.recv() => {
+                    info!("Retraining monitor stopped");
+                    return Ok(());
+                }
+            }
+
+            if let Err(e) = self.check_models(&mut tracker).await {
+                error!("Error checking model performance: {}", e);
+            }
+        }
+    }
+
+    async fn check_models(&self, tracker: &mut DegradationTracker) -> Result<()> {
+        let now = Utc::now();
+
+        for performance in self.deployed_model_performance(now).await? {
+            if performance.version_shared {
+                warn!(
+                    "Not monitoring model {} v{}: another deployed model has version {}",
+                    performance.name, performance.version, performance.version
+                );
+                continue;
+            }
+            if tracker.observe(&performance, now) {
+                if let Err(e) = self.retrain(&performance).await {
+                    tracker.retry(performance.model_id);
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Detections record only the version they were made with, which names a
+    // single model only while no other deployed model shares it. Models
+    // that share one are reported without detections rather than credited
+    // with each other's.
+    async fn deployed_model_performance(&self, now: DateTime<Utc>) -> Result<Vec<DeployedModelPerformance>> {
+        let performance = sqlx::query_as::<_, DeployedModelPerformance>(
+            r#"
+            WITH deployed AS (
+                SELECT m.*, COUNT(*) OVER (PARTITION BY m.version) > 1 AS version_shared
+                FROM models m
+                WHERE m.status = 'deployed'
+            )
+            SELECT
+                m.id AS model_id,
+                m.name,
+                m.version,
+                m.created_by,
+                COUNT(d.id) AS detections,
+                AVG(d.confidence)::FLOAT8 AS mean_confidence,
+                m.version_shared
+            FROM deployed m
+            LEFT JOIN detections d ON d.model_version = m.version AND d.detected_at >= $1 AND NOT m.version_shared
+            GROUP BY m.id, m.name, m.version, m.created_by, m.version_shared
+            "#,
+        )
+        .bind(now - Duration::minutes(self.config.window_min as i64))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(performance)
+    }
+
+    // Queues a job on a fresh snapshot of the dataset the model was last
+    // trained on, with the same hyperparameters
+    async fn retrain(&self, performance: &DeployedModelPerformance) -> Result<()> {
+        let mean_confidence = performance.mean_confidence.unwrap_or_default();
+        let message = format!(
+            "Model {} v{} mean confidence {:.2} below {:.2} for {} min",
+            performance.name, performance.version, mean_confidence, self.config.min_mean_confidence, self.config.sustained_min
+        );
+
+        let previous: Option<(Uuid, serde_json::Value, i64)> = sqlx::query_as(
+            r#"
+            SELECT dataset_id, hyperparameters,
+                (SELECT COUNT(*) FROM training_jobs WHERE model_id = $1 AND status IN ('pending', 'preparing', 'training', 'validating'))
+            FROM training_jobs
+            WHERE model_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(performance.model_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let job_id = match previous {
+            Some((_, _, active)) if active > 0 => {
+                info!("{}; a training job is already running", message);
+                None
+            }
+            Some((dataset_id, hyperparameters, _)) => {
+                let request = CreateTrainingJobRequest {
+                    name: format!("Auto-retrain {} v{}", performance.name, performance.version).chars().take(100).collect(),
+                    description: Some(message.clone()),
+                    model_id: performance.model_id,
+                    dataset_id,
+                    dataset_version_id: None,
+                    hyperparameters: if hyperparameters.is_null() { self.default_hyperparameters.clone() } else { hyperparameters },
+                };
+                let job = TrainingService::new(self.db_pool.clone())
+                    .create_training_job(performance.created_by, request)
+                    .await?;
+                info!("{}; queued training job {}", message, job.id);
+                Some(job.id)
+            }
+            None => {
+                warn!("{}; no previous training job to take a dataset from", message);
+                None
+            }
+        };
+
+        let details = json!({
+            "model_id": performance.model_id,
+            "model_version": performance.version,
+            "mean_confidence": mean_confidence,
+            "detections": performance.detections,
+            "threshold": self.config.min_mean_confidence,
+            "training_job_id": job_id,
+        });
+        SystemService::new(self.db_pool.clone())
+            .log_event(SystemEventType::ModelPerformanceDegraded, EventSeverity::High, &message, Some("retraining_monitor"), Some(details))
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AutoRetrainConfig {
+        AutoRetrainConfig {
+            enabled: true,
+            check_interval_sec: 300,
+            window_min: 15,
+            min_detections: 100,
+            min_mean_confidence: 0.5,
+            sustained_min: 60,
+        }
+    }
+
+    fn performance(model_id: Uuid, mean_confidence: f64, detections: i64) -> DeployedModelPerformance {
+        DeployedModelPerformance {
+            model_id,
+            name: "warehouse-yolo".to_string(),
+            version: "2.1.0".to_string(),
+            created_by: Uuid::new_v4(),
+            detections,
+            mean_confidence: Some(mean_confidence),
+            version_shared: false,
+        }
+    }
+
+    #[test]
+    fn test_sustained_degradation_retrains_once() {
+        let mut tracker = DegradationTracker::new(&config());
+        let model = Uuid::new_v4();
+        let start = Utc::now();
+        let at = |minutes: i64| start + Duration::minutes(minutes);
+
+        // Healthy, then a brief dip that recovers before the hour is up
+        assert!(!tracker.observe(&performance(model, 0.8, 500), at(0)));
+        assert!(!tracker.observe(&performance(model, 0.4, 500), at(5)));
+        assert!(!tracker.observe(&performance(model, 0.7, 500), at(10)));
+
+        // Degraded from minute 15: retrained once, at minute 75, and never
+        // again while the degradation lasts
+        let triggers: Vec<i64> = (15..=240)
+            .step_by(5)
+            .filter(|minute| tracker.observe(&performance(model, 0.35, 500), at(*minute)))
+            .collect();
+        assert_eq!(triggers, vec![75]);
+
+        // Quiet periods don't count as recovery
+        assert!(!tracker.observe(&performance(model, 0.1, 3), at(245)));
+        assert!(!tracker.observe(&performance(model, 0.35, 500), at(250)));
+
+        // Recovering re-arms it
+        assert!(!tracker.observe(&performance(model, 0.9, 500), at(255)));
+        assert!(!tracker.observe(&performance(model, 0.3, 500), at(260)));
+        assert!(tracker.observe(&performance(model, 0.3, 500), at(320)));
+    }
+}
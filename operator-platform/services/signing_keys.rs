@@ -0,0 +1,156 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use uuid::Uuid;
+
+use crate::{config::AuthConfig, models::UserRole};
+
+// Shorter secrets are refused on rotation
+pub const MIN_SECRET_LEN: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: Uuid,
+    pub email: String,
+    pub role: UserRole,
+    pub exp: i64,
+}
+
+struct Secrets {
+    current: String,
+    previous: Option<(String, DateTime<Utc>)>, // with the end of its grace window
+}
+
+// The JWT signing secret, plus the one it replaced for a grace window so
+// rotating doesn't log everyone out. Tokens are always signed with the
+// current secret.
+pub struct SigningKeys {
+    secrets: RwLock<Secrets>,
+    grace: Duration,
+}
+
+impl SigningKeys {
+    pub fn new(config: &AuthConfig) -> Self {
+        let grace = Duration::minutes(config.rotation_grace_min as i64);
+        let previous = config.previous_secret_key.clone().map(|secret| (secret, Utc::now() + grace));
+        Self {
+            secrets: RwLock::new(Secrets { current: config.secret_key.clone(), previous }),
+            grace,
+        }
+    }
+
+    pub fn sign(&self, claims: &Claims) -> Result<String> {
+        let secrets = self.secrets.read().unwrap();
+        Ok(encode(&Header::default(), claims, &EncodingKey::from_secret(secrets.current.as_bytes()))?)
+    }
+
+    pub fn validate(&self, token: &str) -> Result<Claims> {
+        self.validate_at(token, Utc::now())
+    }
+
+    // Tries the current secret, then the previous one while its grace
+    // window is open
+    pub fn validate_at(&self, token: &str, now: DateTime<Utc>) -> Result<Claims> {
+        let secrets = self.secrets.read().unwrap();
+        let validation = Validation::default();
+
+        match decode::<Claims>(token, &DecodingKey::from_secret(secrets.current.as_bytes()), &validation) {
+            Ok(data) => return Ok(data.claims),
+            Err(e) if !matches!(e.kind(), jsonwebtoken::errors::ErrorKind::InvalidSignature) => return Err(e.into()),
+            Err(_) => {}
+        }
+
+        match &secrets.previous {
+            Some((previous, until)) if now < *until => {
+                Ok(decode::<Claims>(token, &DecodingKey::from_secret(previous.as_bytes()), &validation)?.claims)
+            }
+            _ => bail!("Token signature is not valid for the current signing secret"),
+        }
+    }
+
+    // Makes `secret` current. The outgoing secret is accepted for `grace`
+    // (the configured window if None) and returns when that ends.
+    pub fn rotate(&self, secret: String, grace: Option<Duration>) -> Result<DateTime<Utc>> {
+        self.rotate_at(secret, grace, Utc::now())
+    }
+
+    pub fn rotate_at(&self, secret: String, grace: Option<Duration>, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        if secret.len() < MIN_SECRET_LEN {
+            bail!("Signing secret must be at least {} characters", MIN_SECRET_LEN);
+        }
+
+        let mut secrets = self.secrets.write().unwrap();
+        if secret == secrets.current {
+            bail!("New signing secret is the current one");
+        }
+
+        let until = now + grace.unwrap_or(self.grace);
+        let previous = std::mem::replace(&mut secrets.current, secret);
+        secrets.previous = Some((previous, until));
+        Ok(until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> SigningKeys {
+        let config = AuthConfig {
+            secret_key: "a".repeat(MIN_SECRET_LEN),
+            previous_secret_key: None,
+            rotation_grace_min: 60,
+            token_expiration: 24,
+            password_hash_cost: 4,
+            session_timeout_min: 30,
+        };
+        SigningKeys::new(&config)
+    }
+
+    fn claims() -> Claims {
+        Claims {
+            sub: Uuid::new_v4(),
+            email: "operator@example.com".to_string(),
+            role: UserRole::Operator,
+            exp: (Utc::now() + Duration::hours(24)).timestamp(),
+        }
+    }
+
+    #[test]
+    fn test_previous_secret_validates_only_during_grace_window() {
+        let keys = keys();
+        let old_token = keys.sign(&claims()).unwrap();
+
+        let rotated_at = Utc::now();
+        let until = keys.rotate_at("b".repeat(MIN_SECRET_LEN), None, rotated_at).unwrap();
+        assert_eq!(until, rotated_at + Duration::minutes(60));
+
+        // New tokens use the new secret; old ones still work inside the window
+        let new_token = keys.sign(&claims()).unwrap();
+        assert_ne!(new_token, old_token);
+        assert!(keys.validate_at(&new_token, rotated_at).is_ok());
+        assert!(keys.validate_at(&old_token, rotated_at + Duration::minutes(59)).is_ok());
+
+        // and are rejected once it lapses
+        assert!(keys.validate_at(&old_token, rotated_at + Duration::minutes(61)).is_err());
+        assert!(keys.validate_at(&new_token, rotated_at + Duration::minutes(61)).is_ok());
+    }
+
+    #[test]
+    fn test_second_rotation_drops_the_oldest_secret() {
+        let keys = keys();
+        let first = keys.sign(&claims()).unwrap();
+
+        keys.rotate("b".repeat(MIN_SECRET_LEN), None).unwrap();
+        let second = keys.sign(&claims()).unwrap();
+        keys.rotate("c".repeat(MIN_SECRET_LEN), None).unwrap();
+
+        assert!(keys.validate(&first).is_err());
+        assert!(keys.validate(&second).is_ok());
+
+        assert!(keys.rotate("too-short".to_string(), None).is_err());
+        assert!(keys.rotate("c".repeat(MIN_SECRET_LEN), None).is_err());
+    }
+}
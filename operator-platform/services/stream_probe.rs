@@ -0,0 +1,243 @@
+use anyhow::{bail, Result};
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Command;
+use tokio::time::{self, Duration, Instant};
+
+// Extra time allowed on top of the sample window for connecting and
+// waiting for the first keyframe
+const CONNECT_GRACE: Duration = Duration::from_secs(10);
+
+// A frame gap this many nominal intervals wide counts as dropped frames
+const GAP_FACTOR: f64 = 1.5;
+
+// One decoded frame as reported by ffmpeg's showinfo filter
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameSample {
+    pub arrival: Duration, // since the sample started
+    pub pts: f64,          // stream timestamp, seconds
+    pub width: i32,
+    pub height: i32,
+}
+
+// What a short sample of a live stream looked like
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamStats {
+    pub fps: f32,
+    pub bitrate_kbps: f32,
+    pub latency_ms: f32,
+    pub packet_loss: f32,
+    pub width: i32,
+    pub height: i32,
+    // Adaptive streams switch resolution mid-sample; width/height are the last seen
+    pub resolution_changes: u32,
+}
+
+// Samples `url` for `window` with ffmpeg. The video is copied to stdout to
+// count the bytes actually received, and decoded through showinfo on stderr
+// to timestamp each frame as it arrives.
+pub async fn probe_stream(ffmpeg_path: &Path, url: &str, window: Duration) -> Result<StreamStats> {
+    let mut command = Command::new(ffmpeg_path);
+    command.args(["-hide_banner", "-nostats", "-loglevel", "info"]);
+    if url.starts_with("rtsp://") {
+        command.args(["-rtsp_transport", "tcp"]);
+    }
+    // As an input option, -t bounds both outputs
+    let mut child = command
+        .args(["-t", &format!("{:.3}", window.as_secs_f64()), "-i", url])
+        .args(["-map", "0:v:0", "-c", "copy", "-f", "mpegts", "pipe:1"])
+        .args(["-map", "0:v:0", "-vf", "showinfo", "-f", "null", "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let (mut stdout, stderr) = match (child.stdout.take(), child.stderr.take()) {
+        (Some(stdout), Some(stderr)) => (stdout, stderr),
+        _ => bail!("Stream probe for {} has no output pipes", url),
+    };
+
+    let started = Instant::now();
+
+    let count_bytes = async {
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut total = 0u64;
+        let mut first_at = None;
+        let mut last_at = started;
+        while let Ok(n) = stdout.read(&mut buf).await {
+            if n == 0 {
+                break;
+            }
+            first_at.get_or_insert_with(Instant::now);
+            last_at = Instant::now();
+            total += n as u64;
+        }
+        let span = first_at.map(|first| last_at.duration_since(first)).unwrap_or_default();
+        (total, span)
+    };
+
+    let collect_frames = async {
+        let mut lines = BufReader::new(stderr).lines();
+        let mut samples = Vec::new();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Some((pts, width, height)) = parse_showinfo(&line) {
+                samples.push(FrameSample { arrival: started.elapsed(), pts, width, height });
+            }
+        }
+        samples
+    };
+
+    let ((bytes, byte_span), samples) = match time::timeout(window + CONNECT_GRACE, async {
+        tokio::join!(count_bytes, collect_frames)
+    })
+    .await
+    {
+        Ok(result) => result,
+        Err(_) => bail!("Stream probe for {} timed out", url),
+    };
+    let _ = child.wait().await;
+
+    match summarize(&samples, bytes, byte_span) {
+        Some(stats) => Ok(stats),
+        None => bail!("Stream probe for {} received {} frames, too few to measure", url, samples.len()),
+    }
+}
+
+// Pulls `pts_time` and `s:WxH` out of a showinfo frame line
+pub fn parse_showinfo(line: &str) -> Option<(f64, i32, i32)> {
+    if !line.contains("Parsed_showinfo") || !line.contains(" n:") {
+        return None;
+    }
+
+    let field = |name: &str| {
+        line.split_whitespace()
+            .find_map(|token| token.strip_prefix(name))
+    };
+
+    let pts = field("pts_time:")?.parse().ok()?;
+    let (width, height) = field("s:")?.split_once('x')?;
+    Some((pts, width.parse().ok()?, height.parse().ok()?))
+}
+
+// Needs at least two frames. FPS comes from arrival times, not the stream's
+// advertised rate. Latency is how far behind its own timestamps the latest
+// frames arrived (the 95th percentile of arrival lag relative to the first
+// frame), which grows when the camera or network stalls; absolute glass-to-
+// glass latency would need a clock shared with the camera.
+pub fn summarize(samples: &[FrameSample], bytes: u64, byte_span: Duration) -> Option<StreamStats> {
+    let (first, last) = (samples.first()?, samples.last()?);
+    let arrival_span = last.arrival.saturating_sub(first.arrival).as_secs_f64();
+    if samples.len() < 2 || arrival_span <= 0.0 {
+        return None;
+    }
+
+    let fps = (samples.len() - 1) as f64 / arrival_span;
+
+    let bitrate_kbps = if byte_span.is_zero() {
+        0.0
+    } else {
+        bytes as f64 * 8.0 / 1000.0 / byte_span.as_secs_f64()
+    };
+
+    let mut lags: Vec<f64> = samples
+        .iter()
+        .map(|s| {
+            let arrived = s.arrival.saturating_sub(first.arrival).as_secs_f64();
+            (arrived - (s.pts - first.pts)).max(0.0)
+        })
+        .collect();
+    lags.sort_by(|a, b| a.total_cmp(b));
+    let latency = lags[((lags.len() - 1) as f64 * 0.95).round() as usize];
+
+    // Dropped frames show up as gaps in the timestamps
+    let mut intervals: Vec<f64> = samples
+        .windows(2)
+        .map(|w| w[1].pts - w[0].pts)
+        .filter(|d| *d > 0.0)
+        .collect();
+    let packet_loss = if intervals.is_empty() {
+        0.0
+    } else {
+        intervals.sort_by(|a, b| a.total_cmp(b));
+        let nominal = intervals[intervals.len() / 2];
+        let missing: f64 = intervals
+            .iter()
+            .filter(|d| **d > nominal * GAP_FACTOR)
+            .map(|d| (d / nominal).round() - 1.0)
+            .sum();
+        missing / (samples.len() as f64 + missing)
+    };
+
+    let resolution_changes = samples
+        .windows(2)
+        .filter(|w| (w[0].width, w[0].height) != (w[1].width, w[1].height))
+        .count() as u32;
+
+    Some(StreamStats {
+        fps: fps as f32,
+        bitrate_kbps: bitrate_kbps as f32,
+        latency_ms: (latency * 1000.0) as f32,
+        packet_loss: packet_loss as f32,
+        width: last.width,
+        height: last.height,
+        resolution_changes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(fps: f64, count: usize, skip: &[usize]) -> Vec<FrameSample> {
+        (0..count)
+            .filter(|i| !skip.contains(i))
+            .map(|i| {
+                let t = i as f64 / fps;
+                let (width, height) = if i < count / 2 { (1920, 1080) } else { (1280, 720) };
+                FrameSample { arrival: Duration::from_secs_f64(0.5 + t), pts: t, width, height }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_showinfo() {
+        let line = "[Parsed_showinfo_0 @ 0x55d0] n:  12 pts:  12288 pts_time:0.48    duration:1024 pos:  4812 fmt:yuv420p sar:1/1 s:640x480 i:P iskey:0 type:P";
+        assert_eq!(parse_showinfo(line), Some((0.48, 640, 480)));
+        assert_eq!(parse_showinfo("[Parsed_showinfo_0 @ 0x55d0] config in time_base: 1/25, frame_rate: 25/1"), None);
+        assert_eq!(parse_showinfo("Input #0, rtsp, from 'rtsp://camera/stream':"), None);
+    }
+
+    #[test]
+    fn test_summarize_measures_rate_gaps_and_resolution_changes() {
+        let samples = frames(25.0, 100, &[40, 41]);
+        let stats = summarize(&samples, 500_000, Duration::from_secs(4)).unwrap();
+
+        assert!((stats.fps - 24.5).abs() < 0.5, "fps {}", stats.fps);
+        assert!((stats.bitrate_kbps - 1000.0).abs() < 1.0);
+        assert!(stats.latency_ms < 1.0);
+        assert!((stats.packet_loss - 0.02).abs() < 0.001, "loss {}", stats.packet_loss);
+        assert_eq!((stats.width, stats.height), (1280, 720));
+        assert_eq!(stats.resolution_changes, 1);
+        assert!(summarize(&samples[..1], 0, Duration::ZERO).is_none());
+    }
+
+    // Needs ffmpeg and an RTSP server publishing a 25 fps, 1 Mbit/s `videotestsrc`, e.g.
+    //   gst-rtsp-launch "( videotestsrc is-live=true ! video/x-raw,framerate=25/1 ! x264enc bitrate=1000 tune=zerolatency ! rtph264pay name=pay0 )"
+    // with AETHERFORGE_TEST_RTSP_URL=rtsp://127.0.0.1:8554/test
+    #[tokio::test]
+    #[ignore]
+    async fn test_probe_measures_videotestsrc() {
+        let rtsp_url = std::env::var("AETHERFORGE_TEST_RTSP_URL")
+            .unwrap_or_else(|_| "rtsp://127.0.0.1:8554/test".to_string());
+        let ffmpeg = crate::config::OperatorConfig::default().streaming.ffmpeg_path;
+
+        let stats = probe_stream(&ffmpeg, &rtsp_url, Duration::from_secs(5)).await.unwrap();
+
+        assert!((stats.fps - 25.0).abs() < 2.5, "fps {}", stats.fps);
+        assert!((stats.bitrate_kbps - 1000.0).abs() < 300.0, "bitrate {}", stats.bitrate_kbps);
+        assert!(stats.packet_loss < 0.05, "loss {}", stats.packet_loss);
+        assert_eq!(stats.resolution_changes, 0);
+    }
+}
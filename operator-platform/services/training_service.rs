@@ -1,17 +1,18 @@
-use anyhow::Result;
-use sqlx::postgres::PgPool;
+use anyhow::{bail, Result};
+use crate::storage::DbPool;
 use uuid::Uuid;
 use chrono::Utc;
 
+use crate::services::dataset_service::{class_distribution, DatasetService};
 use crate::models::{TrainingJob, TrainingStatus, CreateTrainingJobRequest, UpdateTrainingJobRequest, TrainingJobStats, TrainingJobSummary};
 
 #[derive(Clone)]
 pub struct TrainingService {
-    db_pool: PgPool,
+    db_pool: DbPool,
 }
 
 impl TrainingService {
-    pub fn new(db_pool: PgPool) -> Self {
+    pub fn new(db_pool: DbPool) -> Self {
         Self { db_pool }
     }
     
@@ -44,20 +45,47 @@ impl TrainingService {
     }
     
     pub async fn create_training_job(&self, user_id: Uuid, data: CreateTrainingJobRequest) -> Result<TrainingJob> {
+        // Jobs always train on a frozen version, never the live dataset
+        let dataset_version_id = match data.dataset_version_id {
+            Some(version_id) => {
+                let version = DatasetService::new(self.db_pool.clone()).get_version(version_id).await?.version;
+                if version.dataset_id != data.dataset_id {
+                    bail!("Dataset version {} does not belong to dataset {}", version_id, data.dataset_id);
+                }
+                version_id
+            }
+            None => DatasetService::new(self.db_pool.clone()).snapshot(data.dataset_id, user_id).await?.id,
+        };
+        
+        // `"balance_classes": true` asks for weights and oversampling from
+        // the frozen version; explicitly given ones are left alone
+        let mut hyperparameters = data.hyperparameters;
+        if hyperparameters["balance_classes"] == true {
+            let version = DatasetService::new(self.db_pool.clone()).get_version(dataset_version_id).await?;
+            let members = version.annotations.iter().map(|a| (a.image_path.as_str(), &a.annotations));
+            let hints = class_distribution(data.dataset_id, members).sampling_hints();
+            if let (Some(params), Some(hints)) = (hyperparameters.as_object_mut(), hints.as_object()) {
+                for (key, value) in hints {
+                    params.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+            }
+        }
+        
         let job = sqlx::query_as!(
             TrainingJob,
             r#"
-            INSERT INTO training_jobs (name, description, model_id, dataset_id, hyperparameters, status, created_by)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO training_jobs (name, description, model_id, dataset_id, hyperparameters, status, created_by, dataset_version_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             RETURNING *
             "#,
             data.name,
             data.description,
             data.model_id,
             data.dataset_id,
-            data.hyperparameters,
+            hyperparameters,
             TrainingStatus::Pending as TrainingStatus,
-            user_id
+            user_id,
+            dataset_version_id
         )
         .fetch_one(&self.db_pool)
         .await?;
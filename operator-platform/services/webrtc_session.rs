@@ -0,0 +1,386 @@
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::net::UdpSocket;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::{MediaEngine, MIME_TYPE_H264};
+use webrtc::api::{APIBuilder, API};
+use webrtc::ice_transport::ice_candidate::RTCIceCandidateInit;
+use webrtc::ice_transport::ice_server::RTCIceServer;
+use webrtc::interceptor::registry::Registry;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::peer_connection_state::RTCPeerConnectionState;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use webrtc::track::track_local::track_local_static_rtp::TrackLocalStaticRTP;
+use webrtc::track::track_local::{TrackLocal, TrackLocalWriter};
+
+use crate::{config::StreamingConfig, models::IceCandidateRequest};
+
+// Keeps RTP packets under a typical path MTU once SRTP overhead is added
+const RTP_PACKET_SIZE: usize = 1200;
+const H264_PAYLOAD_TYPE: u8 = 96;
+
+// Negotiates browser peer connections and forwards a camera's H.264 RTSP
+// stream into them without transcoding. ffmpeg only repackages the stream
+// as RTP on a loopback port, which keeps latency well under a second.
+pub struct WebRtcSessionManager {
+    config: StreamingConfig,
+    api: API,
+    sessions: Mutex<HashMap<Uuid, WebRtcSession>>,
+}
+
+struct WebRtcSession {
+    camera_id: Uuid,
+    peer_connection: Arc<RTCPeerConnection>,
+    media: Option<JoinHandle<()>>,
+}
+
+impl WebRtcSessionManager {
+    pub fn new(config: StreamingConfig) -> Result<Arc<Self>> {
+        let mut media_engine = MediaEngine::default();
+        media_engine.register_default_codecs()?;
+
+        let registry = register_default_interceptors(Registry::new(), &mut media_engine)?;
+
+        let api = APIBuilder::new()
+            .with_media_engine(media_engine)
+            .with_interceptor_registry(registry)
+            .build();
+
+        Ok(Arc::new(Self {
+            config,
+            api,
+            sessions: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    // Answers a browser offer. `rtsp_url` is None when only signaling is
+    // wanted, e.g. in tests; the video track then simply carries no packets.
+    pub async fn create_session(
+        self: &Arc<Self>,
+        camera_id: Uuid,
+        offer_sdp: String,
+        rtsp_url: Option<&str>,
+    ) -> Result<(Uuid, String)> {
+        let peer_connection = Arc::new(self.api.new_peer_connection(self.rtc_configuration()).await?);
+
+        // Checked and reserved under one lock, so concurrent offers can't
+        // all pass the check before any of them is counted
+        let session_id = Uuid::new_v4();
+        let reserved = {
+            let mut sessions = self.sessions.lock().unwrap();
+            let reserved = sessions.len() < self.config.max_webrtc_sessions;
+            if reserved {
+                sessions.insert(session_id, WebRtcSession {
+                    camera_id,
+                    peer_connection: peer_connection.clone(),
+                    media: None,
+                });
+            }
+            reserved
+        };
+        if !reserved {
+            if let Err(e) = peer_connection.close().await {
+                warn!("Failed to close WebRTC peer connection {}: {}", session_id, e);
+            }
+            bail!("WebRTC session limit of {} reached", self.config.max_webrtc_sessions);
+        }
+        // Registered once the session exists, so a failure during
+        // negotiation finds it to tear down
+        self.watch_connection_state(session_id, &peer_connection);
+
+        let track = Arc::new(TrackLocalStaticRTP::new(
+            RTCRtpCodecCapability {
+                mime_type: MIME_TYPE_H264.to_owned(),
+                ..Default::default()
+            },
+            "video".to_owned(),
+            format!("camera-{}", camera_id),
+        ));
+
+        let negotiated: Result<(String, Option<JoinHandle<()>>)> = async {
+            peer_connection
+                .add_track(Arc::clone(&track) as Arc<dyn TrackLocal + Send + Sync>)
+                .await?;
+            let answer_sdp = Self::answer(&peer_connection, offer_sdp).await?;
+            let media = match rtsp_url {
+                Some(url) => Some(self.spawn_rtp_forwarder(camera_id, url, track).await?),
+                None => None,
+            };
+            Ok((answer_sdp, media))
+        }
+        .await;
+        let (answer_sdp, media) = match negotiated {
+            Ok(negotiated) => negotiated,
+            Err(e) => {
+                if let Err(close_error) = self.close_session(session_id).await {
+                    warn!("Failed to close WebRTC peer connection {}: {}", session_id, close_error);
+                }
+                return Err(e);
+            }
+        };
+
+        // The connection may have failed, and the session closed, while
+        // negotiating; its forwarder then has nothing to feed
+        match self.sessions.lock().unwrap().get_mut(&session_id) {
+            Some(session) => session.media = media,
+            None => {
+                if let Some(media) = media {
+                    media.abort();
+                }
+                bail!("WebRTC session {} closed while negotiating", session_id);
+            }
+        }
+
+        info!("WebRTC session {} opened for camera {}", session_id, camera_id);
+        Ok((session_id, answer_sdp))
+    }
+
+    // Handles a new offer on an established session, e.g. after an ICE restart
+    pub async fn renegotiate(&self, session_id: Uuid, offer_sdp: String) -> Result<String> {
+        let peer_connection = self.peer_connection(session_id)?;
+        Self::answer(&peer_connection, offer_sdp).await
+    }
+
+    pub async fn add_ice_candidate(&self, session_id: Uuid, candidate: IceCandidateRequest) -> Result<()> {
+        let peer_connection = self.peer_connection(session_id)?;
+
+        peer_connection
+            .add_ice_candidate(RTCIceCandidateInit {
+                candidate: candidate.candidate,
+                sdp_mid: candidate.sdp_mid,
+                sdp_mline_index: candidate.sdp_mline_index,
+                username_fragment: candidate.username_fragment,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn close_session(&self, session_id: Uuid) -> Result<()> {
+        let session = self.sessions.lock().unwrap().remove(&session_id);
+
+        if let Some(session) = session {
+            if let Some(media) = session.media {
+                media.abort();
+            }
+            session.peer_connection.close().await?;
+            info!("WebRTC session {} closed for camera {}", session_id, session.camera_id);
+        }
+
+        Ok(())
+    }
+
+    pub fn session_camera(&self, session_id: Uuid) -> Option<Uuid> {
+        self.sessions.lock().unwrap().get(&session_id).map(|s| s.camera_id)
+    }
+
+    pub fn session_count(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    fn peer_connection(&self, session_id: Uuid) -> Result<Arc<RTCPeerConnection>> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(&session_id)
+            .map(|s| s.peer_connection.clone())
+            .ok_or_else(|| anyhow!("WebRTC session {} not found", session_id))
+    }
+
+    fn rtc_configuration(&self) -> RTCConfiguration {
+        RTCConfiguration {
+            ice_servers: vec![RTCIceServer {
+                urls: self.config.webrtc_ice_servers.clone(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    async fn answer(peer_connection: &RTCPeerConnection, offer_sdp: String) -> Result<String> {
+        peer_connection
+            .set_remote_description(RTCSessionDescription::offer(offer_sdp)?)
+            .await?;
+
+        let answer = peer_connection.create_answer(None).await?;
+
+        // Gather host/srflx candidates up front so clients that don't trickle
+        // still get a usable answer
+        let mut gathering_complete = peer_connection.gathering_complete_promise().await;
+        peer_connection.set_local_description(answer).await?;
+        let _ = gathering_complete.recv().await;
+
+        peer_connection
+            .local_description()
+            .await
+            .map(|description| description.sdp)
+            .ok_or_else(|| anyhow!("Peer connection has no local description"))
+    }
+
+    fn watch_connection_state(self: &Arc<Self>, session_id: Uuid, peer_connection: &RTCPeerConnection) {
+        // Weak so the peer connection's callback doesn't keep the manager alive
+        let manager: Weak<Self> = Arc::downgrade(self);
+
+        peer_connection.on_peer_connection_state_change(Box::new(move |state: RTCPeerConnectionState| {
+            let manager = manager.clone();
+
+            Box::pin(async move {
+                // Disconnected is often a network blip that ICE recovers
+                // from; if it doesn't, the connection goes on to Failed
+                if matches!(state, RTCPeerConnectionState::Failed | RTCPeerConnectionState::Closed) {
+                    if let Some(manager) = manager.upgrade() {
+                        if let Err(e) = manager.close_session(session_id).await {
+                            warn!("Failed to tear down WebRTC session {}: {}", session_id, e);
+                        }
+                    }
+                }
+            })
+        }));
+    }
+
+    async fn spawn_rtp_forwarder(
+        &self,
+        camera_id: Uuid,
+        rtsp_url: &str,
+        track: Arc<TrackLocalStaticRTP>,
+    ) -> Result<JoinHandle<()>> {
+        let socket = UdpSocket::bind("127.0.0.1:0").await?;
+        let rtp_target = format!("rtp://{}?pkt_size={}", socket.local_addr()?, RTP_PACKET_SIZE);
+
+        let child = Command::new(&self.config.ffmpeg_path)
+            .args(["-loglevel", "error", "-rtsp_transport", "tcp", "-i", rtsp_url, "-an"])
+            .args(["-c:v", "copy", "-bsf:v", "h264_mp4toannexb"])
+            .args(["-f", "rtp", "-payload_type", &H264_PAYLOAD_TYPE.to_string(), &rtp_target])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        Ok(tokio::spawn(async move {
+            // Owned by the task so aborting the session kills ffmpeg
+            let _child = child;
+            let mut buf = vec![0u8; 1600];
+
+            loop {
+                match socket.recv(&mut buf).await {
+                    Ok(n) => {
+                        if let Err(e) = track.write(&buf[..n]).await {
+                            warn!("WebRTC track write failed for camera {}: {}", camera_id, e);
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        warn!("RTP forwarder for camera {} stopped: {}", camera_id, e);
+                        break;
+                    }
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use webrtc::rtp_transceiver::rtp_codec::RTPCodecType;
+    use webrtc::rtp_transceiver::rtp_transceiver_direction::RTCRtpTransceiverDirection;
+    use webrtc::rtp_transceiver::RTCRtpTransceiverInit;
+
+    #[tokio::test]
+    async fn test_signaling_offer_answer_handshake() {
+        let mut config = crate::config::OperatorConfig::default().streaming;
+        config.webrtc_ice_servers = Vec::new();
+
+        let manager = WebRtcSessionManager::new(config.clone()).unwrap();
+
+        // Browser side: receive-only video, as a viewer page would request
+        let client_api = WebRtcSessionManager::new(config).unwrap();
+        let client = client_api.api.new_peer_connection(RTCConfiguration::default()).await.unwrap();
+        client
+            .add_transceiver_from_kind(
+                RTPCodecType::Video,
+                Some(RTCRtpTransceiverInit {
+                    direction: RTCRtpTransceiverDirection::Recvonly,
+                    send_encodings: Vec::new(),
+                }),
+            )
+            .await
+            .unwrap();
+
+        let offer = client.create_offer(None).await.unwrap();
+        client.set_local_description(offer.clone()).await.unwrap();
+
+        let camera_id = Uuid::new_v4();
+        let (session_id, answer_sdp) = manager.create_session(camera_id, offer.sdp, None).await.unwrap();
+
+        assert!(answer_sdp.contains("m=video"));
+        assert!(answer_sdp.to_uppercase().contains("H264"));
+        assert_eq!(manager.session_camera(session_id), Some(camera_id));
+
+        client
+            .set_remote_description(RTCSessionDescription::answer(answer_sdp).unwrap())
+            .await
+            .unwrap();
+
+        manager.close_session(session_id).await.unwrap();
+        assert_eq!(manager.session_count(), 0);
+
+        // An offer that can't be answered leaves no session behind
+        assert!(manager.create_session(camera_id, "v=0".to_string(), None).await.is_err());
+        assert_eq!(manager.session_count(), 0);
+
+        client.close().await.unwrap();
+    }
+
+    async fn viewer_offer(api: &WebRtcSessionManager) -> (RTCPeerConnection, String) {
+        let client = api.api.new_peer_connection(RTCConfiguration::default()).await.unwrap();
+        client
+            .add_transceiver_from_kind(
+                RTPCodecType::Video,
+                Some(RTCRtpTransceiverInit {
+                    direction: RTCRtpTransceiverDirection::Recvonly,
+                    send_encodings: Vec::new(),
+                }),
+            )
+            .await
+            .unwrap();
+        let offer = client.create_offer(None).await.unwrap();
+        client.set_local_description(offer.clone()).await.unwrap();
+        (client, offer.sdp)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_offers_respect_the_session_limit() {
+        let mut config = crate::config::OperatorConfig::default().streaming;
+        config.webrtc_ice_servers = Vec::new();
+        config.max_webrtc_sessions = 1;
+
+        let manager = WebRtcSessionManager::new(config.clone()).unwrap();
+        let client_api = WebRtcSessionManager::new(config).unwrap();
+        let (first_client, first_offer) = viewer_offer(&client_api).await;
+        let (second_client, second_offer) = viewer_offer(&client_api).await;
+
+        let camera_id = Uuid::new_v4();
+        let (first, second) = tokio::join!(
+            manager.create_session(camera_id, first_offer, None),
+            manager.create_session(camera_id, second_offer, None),
+        );
+
+        assert_eq!([first.is_ok(), second.is_ok()].iter().filter(|ok| **ok).count(), 1);
+        let error = first.err().or(second.err()).unwrap().to_string();
+        assert!(error.contains("session limit of 1"), "{}", error);
+        assert_eq!(manager.session_count(), 1);
+
+        first_client.close().await.unwrap();
+        second_client.close().await.unwrap();
+    }
+}
\ No newline at end of file
@@ -1,5 +1,7 @@
 mod database;
 mod file_storage;
+mod schema_check;
 
 pub use database::*;
-pub use file_storage::*;
\ No newline at end of file
+pub use file_storage::*;
+pub use schema_check::*;
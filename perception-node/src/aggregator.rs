@@ -0,0 +1,360 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::{
+    config::{AggregatorConfig, MessagingConfig},
+    error::{PerceptionError, Result},
+    messaging::{
+        subscriber::{MessageSubscriber, ZmqSubscriber},
+        MessageSigner,
+    },
+};
+use aetherforge_common::{
+    utils::current_timestamp_ms,
+    world_model::{WorldModel, WorldModelAdapter},
+    FusedObject, FusionResult, TrackSource, WorldPosition,
+};
+
+// Same topic and JSON framing as the simulator's stream
+pub const WORLD_MODEL_TOPIC: &str = "world_model";
+
+struct NodeSnapshot {
+    result: FusionResult,
+    received_ms: u64,
+}
+
+// Merges each node's latest FusionResult into one facility-wide picture.
+// A node's track keeps its facility id for as long as the track lives; a new
+// track joins an object another node already sees if it has the same class
+// and is within `association_radius_m`, otherwise it gets a fresh id.
+pub struct FacilityAggregator {
+    association_radius_m: f32,
+    node_timeout_ms: u64,
+    adapter: WorldModelAdapter,
+    nodes: BTreeMap<String, NodeSnapshot>,
+    identities: HashMap<(String, u64), u64>, // (node, node track id) -> facility id
+    next_id: u64,
+}
+
+impl FacilityAggregator {
+    pub fn new(config: &AggregatorConfig) -> Self {
+        Self {
+            association_radius_m: config.association_radius_m,
+            node_timeout_ms: config.node_timeout_ms,
+            adapter: WorldModelAdapter::new(config.zone_id.clone()),
+            nodes: BTreeMap::new(),
+            identities: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    pub fn ingest(&mut self, node_id: &str, result: FusionResult, now_ms: u64) {
+        // Tracks the node no longer reports have ended
+        let live: HashSet<u64> = result.objects.iter().map(|o| o.global_track_id).collect();
+        self.identities.retain(|(node, track), _| node != node_id || live.contains(track));
+
+        let others = self.fuse(Some(node_id));
+        let mut claimed: HashSet<u64> = result
+            .objects
+            .iter()
+            .filter_map(|o| self.identities.get(&(node_id.to_string(), o.global_track_id)).copied())
+            .collect();
+
+        for object in &result.objects {
+            let key = (node_id.to_string(), object.global_track_id);
+            if self.identities.contains_key(&key) {
+                continue;
+            }
+
+            let matched = others
+                .iter()
+                .filter(|o| o.class_label == object.class_label && !claimed.contains(&o.global_track_id))
+                .map(|o| (o.global_track_id, o.position.distance(&object.position)))
+                .filter(|(_, distance)| *distance <= self.association_radius_m)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(id, _)| id);
+
+            let id = matched.unwrap_or_else(|| {
+                let id = self.next_id;
+                self.next_id += 1;
+                id
+            });
+            claimed.insert(id);
+            self.identities.insert(key, id);
+        }
+
+        self.nodes.insert(node_id.to_string(), NodeSnapshot { result, received_ms: now_ms });
+    }
+
+    // Drops nodes that haven't reported within the timeout; returns their ids
+    pub fn expire(&mut self, now_ms: u64) -> Vec<String> {
+        let silent: Vec<String> = self
+            .nodes
+            .iter()
+            .filter(|(_, snapshot)| now_ms.saturating_sub(snapshot.received_ms) > self.node_timeout_ms)
+            .map(|(node_id, _)| node_id.clone())
+            .collect();
+
+        for node_id in &silent {
+            self.nodes.remove(node_id);
+            self.identities.retain(|(node, _), _| node != node_id);
+        }
+        silent
+    }
+
+    pub fn fused(&self, now_ms: u64) -> FusionResult {
+        let confidences: Vec<f32> = self.nodes.values().map(|s| s.result.fusion_confidence).collect();
+        let raw_confidences: Vec<f32> = self.nodes.values().map(|s| s.result.raw_fusion_confidence).collect();
+
+        FusionResult {
+            timestamp: now_ms,
+            objects: self.fuse(None),
+            fusion_confidence: mean(&confidences),
+            raw_fusion_confidence: mean(&raw_confidences),
+        }
+    }
+
+    pub fn world_model(&self, now_ms: u64) -> WorldModel {
+        self.adapter.convert(&self.fused(now_ms))
+    }
+
+    // Facility objects from every node's contributions, optionally leaving one node out
+    fn fuse(&self, exclude: Option<&str>) -> Vec<FusedObject> {
+        let mut members: BTreeMap<u64, Vec<&FusedObject>> = BTreeMap::new();
+        for (node_id, snapshot) in self.nodes.iter().filter(|(node_id, _)| Some(node_id.as_str()) != exclude) {
+            for object in &snapshot.result.objects {
+                if let Some(id) = self.identities.get(&(node_id.clone(), object.global_track_id)) {
+                    members.entry(*id).or_default().push(object);
+                }
+            }
+        }
+
+        members
+            .into_iter()
+            .map(|(id, objects)| {
+                // Confidence-weighted position; the most confident view names the class
+                let weight: f32 = objects.iter().map(|o| o.confidence.max(f32::EPSILON)).sum();
+                let position = WorldPosition {
+                    x: objects.iter().map(|o| o.position.x * o.confidence.max(f32::EPSILON)).sum::<f32>() / weight,
+                    y: objects.iter().map(|o| o.position.y * o.confidence.max(f32::EPSILON)).sum::<f32>() / weight,
+                };
+                let best = objects.iter().max_by(|a, b| a.confidence.total_cmp(&b.confidence)).unwrap();
+
+                let mut sources: Vec<TrackSource> = Vec::new();
+                for source in objects.iter().flat_map(|o| o.sources.iter()) {
+                    if !sources.contains(source) {
+                        sources.push(source.clone());
+                    }
+                }
+
+                FusedObject {
+                    global_track_id: id,
+                    class_label: best.class_label.clone(),
+                    raw_class_label: best.raw_class_label.clone(),
+                    position,
+                    confidence: best.confidence,
+                    raw_confidence: objects.iter().map(|o| o.raw_confidence).fold(0.0, f32::max),
+                    sources,
+                    // Node contributions are within the association radius,
+                    // so the best one's cell stands for the object
+                    zone_id: best.zone_id.clone(),
+                    // Only coasting while no node still sees it
+                    coasting: objects.iter().all(|o| o.coasting),
+                }
+            })
+            .collect()
+    }
+}
+
+fn mean(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f32>() / values.len() as f32
+    }
+}
+
+// Subscribes to every configured node and republishes the facility world
+// model every `publish_interval_ms` until cancelled. Node messages are
+// checked against the messaging signing key when one is set. Read from
+// Kafka, what was ingested is committed every tick, so a restarted
+// aggregator picks up where it left off.
+pub async fn run(config: &AggregatorConfig, messaging: &MessagingConfig) -> Result<()> {
+    if config.nodes.is_empty() {
+        return Err(PerceptionError::ConfigError("aggregator.nodes is empty".to_string()));
+    }
+
+    let context = zmq::Context::new();
+    let verifier = MessageSigner::from_config(&messaging.security)?;
+    let chunk_timeout = Duration::from_millis(messaging.chunk_timeout_ms);
+    let max_chunks = messaging.max_chunks_per_message;
+    let mut subscribers = config
+        .nodes
+        .iter()
+        .map(|node| {
+            let subscriber: Box<dyn MessageSubscriber> = match &config.kafka {
+                #[cfg(feature = "kafka")]
+                Some(kafka) => Box::new(crate::messaging::kafka_subscriber::KafkaSubscriber::connect(
+                    kafka,
+                    &node.endpoint,
+                    Duration::ZERO,
+                    verifier.clone(),
+                    chunk_timeout,
+                    max_chunks,
+                )?),
+                #[cfg(not(feature = "kafka"))]
+                Some(_) => {
+                    return Err(PerceptionError::ConfigError(
+                        "aggregator.kafka is set but this build has no kafka feature".to_string(),
+                    ))
+                }
+                None => Box::new(ZmqSubscriber::connect(&context, &node.endpoint, 0, verifier.clone(), chunk_timeout, max_chunks)?),
+            };
+            Ok((node.node_id.clone(), subscriber))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let publisher = context.socket(zmq::PUB)?;
+    publisher.bind(&config.publish_endpoint)?;
+    info!("Aggregating {} nodes, publishing on {}", subscribers.len(), config.publish_endpoint);
+
+    let mut aggregator = FacilityAggregator::new(config);
+    let mut ticker = tokio::time::interval(Duration::from_millis(config.publish_interval_ms.max(1)));
+
+    let persist = match &config.persist_url {
+        Some(url) => {
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .map_err(|e| PerceptionError::ConfigError(e.to_string()))?;
+            info!("Persisting the world model to {} every {}ms", url, config.persist_interval_ms);
+            Some((client, format!("{}/worldmodel", url.trim_end_matches('/'))))
+        }
+        None => None,
+    };
+    let mut last_persisted_ms = 0;
+
+    loop {
+        ticker.tick().await;
+        let now = current_timestamp_ms();
+
+        for (node_id, subscriber) in &mut subscribers {
+            loop {
+                match subscriber.recv_fusion_result() {
+                    Ok(Some(result)) => aggregator.ingest(node_id, result, now),
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("Dropping message from {} ({}): {}", node_id, subscriber.endpoint(), e);
+                        break;
+                    }
+                }
+            }
+            if let Err(e) = subscriber.commit() {
+                warn!("Committing {} ({}) failed: {}", node_id, subscriber.endpoint(), e);
+            }
+        }
+
+        for node_id in aggregator.expire(now) {
+            warn!("Node {} went silent, dropping its objects", node_id);
+        }
+
+        let world_model = aggregator.world_model(now);
+        publisher.send(WORLD_MODEL_TOPIC, zmq::SNDMORE)?;
+        publisher.send(serde_json::to_vec(&world_model)?, 0)?;
+
+        // Posted off the loop so a slow platform never delays publishing; a
+        // missed snapshot is just a gap in playback
+        if let Some((client, url)) = &persist {
+            if now.saturating_sub(last_persisted_ms) >= config.persist_interval_ms {
+                last_persisted_ms = now;
+                let request = client.post(url.as_str()).json(&world_model);
+                let url = url.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = request.send().await.and_then(|r| r.error_for_status()) {
+                        warn!("Persisting world model to {} failed: {}", url, e);
+                    }
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(track: u64, class: &str, x: f32, y: f32, camera: &str) -> FusedObject {
+        FusedObject {
+            global_track_id: track,
+            class_label: class.to_string(),
+            raw_class_label: Some(class.to_string()),
+            position: WorldPosition { x, y },
+            confidence: 0.8,
+            raw_confidence: 0.8,
+            sources: vec![TrackSource { camera_id: camera.to_string(), tracker_id: track }],
+            zone_id: None,
+            coasting: false,
+        }
+    }
+
+    fn result(objects: Vec<FusedObject>) -> FusionResult {
+        FusionResult {
+            timestamp: 0,
+            objects,
+            fusion_confidence: 0.8,
+            raw_fusion_confidence: 0.8,
+        }
+    }
+
+    #[test]
+    fn test_overlapping_nodes_merge_and_silent_node_ages_out() {
+        let config = AggregatorConfig {
+            node_timeout_ms: 1000,
+            ..AggregatorConfig::default()
+        };
+        let mut aggregator = FacilityAggregator::new(&config);
+
+        // Both nodes see the same person near (10, 5); only node B sees the forklift
+        for t in (0..1000).step_by(100) {
+            aggregator.ingest("node-a", result(vec![object(1, "person", 10.0, 5.0, "a-cam")]), t);
+            aggregator.ingest(
+                "node-b",
+                result(vec![object(1, "person", 10.4, 5.2, "b-cam"), object(2, "forklift", 30.0, 8.0, "b-cam")]),
+                t,
+            );
+        }
+
+        let fused = aggregator.fused(1000);
+        assert_eq!(fused.objects.len(), 2);
+        let person = fused.objects.iter().find(|o| o.class_label == "person").unwrap();
+        assert!((person.position.x - 10.2).abs() < 1e-4 && (person.position.y - 5.1).abs() < 1e-4);
+        let cameras: Vec<&str> = person.sources.iter().map(|s| s.camera_id.as_str()).collect();
+        assert_eq!(cameras, vec!["a-cam", "b-cam"]);
+
+        // Node B drops off the bus; node A keeps reporting
+        for t in (1000..2500).step_by(100) {
+            aggregator.ingest("node-a", result(vec![object(1, "person", 10.0, 5.0, "a-cam")]), t);
+            let expired = aggregator.expire(t);
+            let expected: Vec<String> = if t == 2000 { vec!["node-b".to_string()] } else { Vec::new() };
+            assert_eq!(expired, expected, "at {}ms", t);
+        }
+
+        let world_model = aggregator.world_model(2500);
+        assert_eq!(world_model.zone_id, "FACILITY");
+        assert_eq!(world_model.detections.len(), 1);
+        assert_eq!(world_model.detections[0].id, format!("TRK-{}", person.global_track_id));
+        assert_eq!(world_model.active_cameras, vec!["a-cam".to_string()]);
+    }
+
+    #[test]
+    fn test_distant_objects_of_one_class_stay_separate() {
+        let mut aggregator = FacilityAggregator::new(&AggregatorConfig::default());
+
+        aggregator.ingest("node-a", result(vec![object(1, "person", 0.0, 0.0, "a-cam")]), 0);
+        aggregator.ingest("node-b", result(vec![object(1, "person", 5.0, 0.0, "b-cam"), object(2, "robot", 0.2, 0.0, "b-cam")]), 0);
+
+        let ids: HashSet<u64> = aggregator.fused(0).objects.iter().map(|o| o.global_track_id).collect();
+        assert_eq!(ids.len(), 3);
+    }
+}
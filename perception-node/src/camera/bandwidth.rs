@@ -0,0 +1,399 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::{
+    config::{BandwidthBudgetConfig, CameraConfig},
+    messaging::{AlertSeverity, MessagePublisher, SystemAlert},
+};
+
+pub const BANDWIDTH_EXCEEDED: &str = "bandwidth_exceeded";
+
+const SETTLE_CHECKS: u32 = 3; // checks a step gets to show up in the measured rate
+const MIN_EFFECT: f32 = 0.9; // a step took effect once the camera is below this share of its rate before it
+
+// Format a camera's pipeline is renegotiated down to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureLimit {
+    pub framerate: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// What the budget needs from the camera manager
+#[async_trait]
+pub trait CameraStreams: Send + Sync {
+    // Bytes each running camera has received from its source since it was created
+    async fn bytes_received(&self) -> Vec<(String, u64)>;
+    // Limits what the camera's source delivers, so its byte count drops;
+    // fails if the camera can't be limited that way. None lifts the limit.
+    async fn limit_capture(&self, camera_id: &str, limit: Option<CaptureLimit>) -> Result<()>;
+}
+
+struct CameraIngest {
+    priority: u8,
+    full: CaptureLimit,
+    level: u32, // throttle steps applied; 0 is the configured format
+    throttleable: bool, // cleared once a step fails or has no effect
+    bytes: Option<u64>,
+    kbps: f32,
+}
+
+// The last step down, until its camera's rate shows it took effect
+struct PendingStep {
+    camera_id: String,
+    kbps_before: f32,
+    checks: u32,
+}
+
+// Keeps the node's total camera ingest within `max_total_kbps`. Each check
+// that finds the total over budget steps the lowest-priority camera down
+// once more: framerate first, then resolution. Once the total is back
+// under `restore_ratio` of the budget, the highest-priority throttled camera
+// is stepped back up. No further step is taken until the last one shows up
+// in its camera's measured rate; a step that doesn't within a few checks is
+// undone and that camera is left alone from then on. Going over budget
+// alerts once, and re-arms when the total fits again.
+pub struct BandwidthBudget {
+    config: BandwidthBudgetConfig,
+    cameras: HashMap<String, CameraIngest>,
+    last_check_ms: Option<u64>,
+    pending: Option<PendingStep>,
+    exceeded: bool,
+}
+
+impl BandwidthBudget {
+    pub fn new(cameras: &[CameraConfig], config: &BandwidthBudgetConfig) -> Self {
+        let cameras = cameras
+            .iter()
+            .filter(|camera| camera.enabled)
+            .map(|camera| {
+                let ingest = CameraIngest {
+                    priority: camera.priority,
+                    full: CaptureLimit { framerate: camera.framerate, width: camera.width, height: camera.height },
+                    level: 0,
+                    throttleable: true,
+                    bytes: None,
+                    kbps: 0.0,
+                };
+                (camera.id.clone(), ingest)
+            })
+            .collect();
+
+        Self {
+            config: config.clone(),
+            cameras,
+            last_check_ms: None,
+            pending: None,
+            exceeded: false,
+        }
+    }
+
+    pub async fn check<S: CameraStreams + ?Sized>(&mut self, streams: &S, now_ms: u64) -> Vec<SystemAlert> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        let elapsed_ms = self.last_check_ms.map_or(0, |last| now_ms.saturating_sub(last));
+        self.last_check_ms = Some(now_ms);
+        for (camera_id, bytes) in streams.bytes_received().await {
+            let Some(camera) = self.cameras.get_mut(&camera_id) else {
+                continue;
+            };
+            // A restarted pipeline counts from zero again
+            if let Some(previous) = camera.bytes.filter(|previous| elapsed_ms > 0 && bytes >= *previous) {
+                // Bits per millisecond are kilobits per second
+                camera.kbps = (bytes - previous) as f32 * 8.0 / elapsed_ms as f32;
+            }
+            camera.bytes = Some(bytes);
+        }
+        if elapsed_ms == 0 {
+            return Vec::new();
+        }
+
+        let total_kbps: f32 = self.cameras.values().map(|camera| camera.kbps).sum();
+        let budget_kbps = self.config.max_total_kbps as f32;
+        let mut alerts = Vec::new();
+
+        if total_kbps > budget_kbps {
+            let throttled = match self.last_step_settled(streams).await {
+                true => self.step_down(streams).await,
+                false => None,
+            };
+            if !self.exceeded {
+                self.exceeded = true;
+                alerts.push(SystemAlert {
+                    severity: AlertSeverity::Warning,
+                    source: "bandwidth_budget".to_string(),
+                    message: format!(
+                        "Camera ingest at {:.0}kbps exceeds the {:.0}kbps budget{}",
+                        total_kbps,
+                        budget_kbps,
+                        throttled.as_ref().map_or(String::new(), |id| format!(", throttling camera {}", id))
+                    ),
+                    timestamp: now_ms,
+                    details: Some(json!({
+                        "alert_type": BANDWIDTH_EXCEEDED,
+                        "total_kbps": total_kbps,
+                        "budget_kbps": budget_kbps,
+                        "throttled_camera": throttled,
+                    })),
+                });
+            }
+        } else {
+            self.exceeded = false;
+            self.pending = None;
+            if total_kbps < budget_kbps * self.config.restore_ratio {
+                self.step_up(streams).await;
+            }
+        }
+
+        alerts
+    }
+
+    // Whether the last step down has shown up in its camera's rate. One
+    // that hasn't after SETTLE_CHECKS is undone, since the camera's ingest
+    // evidently isn't governed by its source format.
+    async fn last_step_settled<S: CameraStreams + ?Sized>(&mut self, streams: &S) -> bool {
+        let Some(pending) = self.pending.as_mut() else {
+            return true;
+        };
+        let kbps = self.cameras.get(&pending.camera_id).map_or(0.0, |camera| camera.kbps);
+        if kbps < pending.kbps_before * MIN_EFFECT {
+            self.pending = None;
+            return true;
+        }
+        pending.checks += 1;
+        if pending.checks < SETTLE_CHECKS {
+            return false;
+        }
+
+        let camera_id = self.pending.take().unwrap().camera_id;
+        warn!("Limiting camera {} had no measurable effect on its ingest, no longer throttling it", camera_id);
+        let camera = self.cameras.get_mut(&camera_id).unwrap();
+        camera.level -= 1;
+        camera.throttleable = false;
+        let camera = &self.cameras[&camera_id];
+        let limit = (camera.level > 0).then(|| self.limit(camera, camera.level)).flatten();
+        if let Err(e) = streams.limit_capture(&camera_id, limit).await {
+            error!("Failed to raise camera {}: {}", camera_id, e);
+        }
+        true
+    }
+
+    // Lowest priority first; between equals, the heaviest stream. A camera
+    // that refuses the limit is skipped from then on.
+    async fn step_down<S: CameraStreams + ?Sized>(&mut self, streams: &S) -> Option<String> {
+        loop {
+            let (camera_id, limit) = self
+                .cameras
+                .iter()
+                .filter(|(_, camera)| camera.throttleable)
+                .filter_map(|(id, camera)| self.limit(camera, camera.level + 1).map(|limit| (id, camera, limit)))
+                .min_by(|(_, a, _), (_, b, _)| a.priority.cmp(&b.priority).then(b.kbps.total_cmp(&a.kbps)))
+                .map(|(id, _, limit)| (id.clone(), limit))?;
+
+            info!("Over bandwidth budget, limiting camera {} to {}x{}@{}fps", camera_id, limit.width, limit.height, limit.framerate);
+            let camera = self.cameras.get_mut(&camera_id).unwrap();
+            match streams.limit_capture(&camera_id, Some(limit)).await {
+                Ok(()) => {
+                    camera.level += 1;
+                    self.pending = Some(PendingStep { camera_id: camera_id.clone(), kbps_before: camera.kbps, checks: 0 });
+                    return Some(camera_id);
+                }
+                Err(e) => {
+                    error!("Failed to limit camera {}, no longer throttling it: {}", camera_id, e);
+                    camera.throttleable = false;
+                }
+            }
+        }
+    }
+
+    async fn step_up<S: CameraStreams + ?Sized>(&mut self, streams: &S) {
+        let Some((camera_id, level)) = self
+            .cameras
+            .iter()
+            .filter(|(_, camera)| camera.level > 0)
+            .max_by_key(|(_, camera)| camera.priority)
+            .map(|(id, camera)| (id.clone(), camera.level - 1))
+        else {
+            return;
+        };
+
+        let camera = &self.cameras[&camera_id];
+        let limit = (level > 0).then(|| self.limit(camera, level)).flatten();
+        info!("Back under bandwidth budget, raising camera {} to throttle level {}", camera_id, level);
+        match streams.limit_capture(&camera_id, limit).await {
+            Ok(()) => self.cameras.get_mut(&camera_id).unwrap().level = level,
+            Err(e) => error!("Failed to raise camera {}: {}", camera_id, e),
+        }
+    }
+
+    // The format `level` steps down from the configured one, or None once
+    // that would go below the minimum framerate and width
+    fn limit(&self, camera: &CameraIngest, level: u32) -> Option<CaptureLimit> {
+        let scale = |value: u32| ((value as f32 * self.config.step_factor).round() as u32).max(1);
+        let mut limit = camera.full;
+        for _ in 0..level {
+            if limit.framerate > self.config.min_framerate {
+                limit.framerate = scale(limit.framerate).max(self.config.min_framerate);
+            } else {
+                // Even sizes, which most raw formats need
+                limit.width = scale(limit.width) & !1;
+                limit.height = scale(limit.height) & !1;
+                if limit.width < self.config.min_width {
+                    return None;
+                }
+            }
+        }
+        Some(limit)
+    }
+}
+
+pub async fn run<S, P>(mut budget: BandwidthBudget, streams: Arc<S>, publisher: Arc<P>, interval: Duration)
+where
+    S: CameraStreams + ?Sized,
+    P: MessagePublisher + ?Sized,
+{
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let now_ms = aetherforge_common::utils::current_timestamp_ms();
+        for alert in budget.check(streams.as_ref(), now_ms).await {
+            if let Err(e) = publisher.publish_alert(&alert).await {
+                warn!("Failed to publish bandwidth alert: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct FakeCamera {
+        full: CaptureLimit,
+        kbps: f32, // at the full format
+        current: CaptureLimit,
+        at_source: bool, // whether a limit reaches the source; an encoded stream accepts it but keeps its bitrate
+        bytes: u64,
+    }
+
+    // A limited source's bitrate scales with framerate and pixel count
+    struct FakeStreams {
+        cameras: Mutex<HashMap<String, FakeCamera>>,
+        limits: Mutex<Vec<(String, Option<CaptureLimit>)>>,
+    }
+
+    impl FakeStreams {
+        fn new(cameras: &[(&str, f32, bool)]) -> Self {
+            let full = CaptureLimit { framerate: 30, width: 1920, height: 1080 };
+            let cameras = cameras
+                .iter()
+                .map(|&(id, kbps, at_source)| (id.to_string(), FakeCamera { full, kbps, current: full, at_source, bytes: 0 }))
+                .collect();
+            Self { cameras: Mutex::new(cameras), limits: Mutex::new(Vec::new()) }
+        }
+
+        fn advance(&self, ms: u64) {
+            for camera in self.cameras.lock().unwrap().values_mut() {
+                let (full, current) = (camera.full, camera.current);
+                let share = match camera.at_source {
+                    true => (current.framerate * current.width * current.height) as f32 / (full.framerate * full.width * full.height) as f32,
+                    false => 1.0,
+                };
+                camera.bytes += (camera.kbps * share * ms as f32 / 8.0) as u64;
+            }
+        }
+
+        fn set_kbps(&self, camera_id: &str, kbps: f32) {
+            self.cameras.lock().unwrap().get_mut(camera_id).unwrap().kbps = kbps;
+        }
+
+        fn limits(&self) -> Vec<(String, Option<CaptureLimit>)> {
+            self.limits.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl CameraStreams for FakeStreams {
+        async fn bytes_received(&self) -> Vec<(String, u64)> {
+            self.cameras.lock().unwrap().iter().map(|(id, camera)| (id.clone(), camera.bytes)).collect()
+        }
+
+        async fn limit_capture(&self, camera_id: &str, limit: Option<CaptureLimit>) -> Result<()> {
+            let mut cameras = self.cameras.lock().unwrap();
+            let camera = cameras.get_mut(camera_id).unwrap();
+            camera.current = limit.unwrap_or(camera.full);
+            self.limits.lock().unwrap().push((camera_id.to_string(), limit));
+            Ok(())
+        }
+    }
+
+    fn camera(id: &str, priority: u8) -> CameraConfig {
+        CameraConfig { id: id.to_string(), priority, width: 1920, height: 1080, framerate: 30, ..CameraConfig::default() }
+    }
+
+    fn at_fps(framerate: u32) -> Option<CaptureLimit> {
+        Some(CaptureLimit { framerate, width: 1920, height: 1080 })
+    }
+
+    async fn run_checks(budget: &mut BandwidthBudget, streams: &FakeStreams, seconds: std::ops::Range<u64>) -> Vec<SystemAlert> {
+        let mut alerts = Vec::new();
+        for second in seconds {
+            alerts.extend(budget.check(streams, second * 1000).await);
+            streams.advance(1000);
+        }
+        alerts
+    }
+
+    #[tokio::test]
+    async fn test_over_budget_throttles_lowest_priority_camera_and_alerts() {
+        let streams = FakeStreams::new(&[("dock-1", 4000.0, true), ("yard-2", 4000.0, true)]);
+        let config = BandwidthBudgetConfig { enabled: true, max_total_kbps: 10_000, ..BandwidthBudgetConfig::default() };
+        let mut budget = BandwidthBudget::new(&[camera("dock-1", 5), camera("yard-2", 1)], &config);
+
+        // 8Mbps of 10Mbps: nothing to do
+        let mut alerts = run_checks(&mut budget, &streams, 0..3).await;
+        assert!(alerts.is_empty() && streams.limits().is_empty());
+
+        // The dock camera's scene gets busy and its bitrate doubles
+        streams.set_kbps("dock-1", 8000.0);
+        alerts.extend(run_checks(&mut budget, &streams, 3..8).await);
+
+        // Only the low-priority yard camera is stepped down, 30 -> 15fps,
+        // which brings the total to 10Mbps; one alert for the episode
+        assert_eq!(streams.limits(), vec![("yard-2".to_string(), at_fps(15))]);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].details.as_ref().unwrap()["alert_type"], BANDWIDTH_EXCEEDED);
+        assert_eq!(alerts[0].details.as_ref().unwrap()["throttled_camera"], "yard-2");
+
+        // The scene calms down; the yard camera gets its framerate back
+        streams.set_kbps("dock-1", 2000.0);
+        alerts.extend(run_checks(&mut budget, &streams, 8..11).await);
+        assert_eq!(streams.limits().last().unwrap(), &("yard-2".to_string(), None));
+        assert_eq!(alerts.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_step_without_effect_is_undone_instead_of_stacked() {
+        // The yard camera's encoded stream keeps its bitrate whatever the limit
+        let streams = FakeStreams::new(&[("dock-1", 6000.0, true), ("yard-2", 6000.0, false)]);
+        let config = BandwidthBudgetConfig { enabled: true, max_total_kbps: 10_000, ..BandwidthBudgetConfig::default() };
+        let mut budget = BandwidthBudget::new(&[camera("dock-1", 5), camera("yard-2", 1)], &config);
+
+        run_checks(&mut budget, &streams, 0..12).await;
+
+        // One step on the yard camera, waited out and undone, then the dock
+        // camera is stepped down once, which is enough
+        assert_eq!(
+            streams.limits(),
+            vec![("yard-2".to_string(), at_fps(15)), ("yard-2".to_string(), None), ("dock-1".to_string(), at_fps(15))]
+        );
+    }
+}
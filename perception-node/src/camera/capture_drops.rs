@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use crate::utils::metrics::Metrics;
+
+// Buffers without an offset carry GStreamer's BUFFER_OFFSET_NONE
+const OFFSET_NONE: u64 = u64::MAX;
+
+// Counts frames one camera lost before they reached the node. The appsink
+// drops the oldest buffer once `max_buffers` are queued; sources number
+// their buffers, so those drops show up as gaps in the offsets. A frame
+// the channel into the processing queue had no room for is lost as well.
+pub struct CaptureDrops {
+    camera_id: String,
+    last_offset: Option<u64>,
+    metrics: Arc<Metrics>,
+}
+
+impl CaptureDrops {
+    pub fn new(camera_id: &str, metrics: Arc<Metrics>) -> Self {
+        Self {
+            camera_id: camera_id.to_string(),
+            last_offset: None,
+            metrics,
+        }
+    }
+
+    // Offset of each buffer the appsink hands over. Sources that don't
+    // number their buffers are not counted; an offset going backwards is a
+    // restarted stream, not a loss.
+    pub fn observe_offset(&mut self, offset: u64) {
+        if offset == OFFSET_NONE {
+            return;
+        }
+        if let Some(last) = self.last_offset.filter(|last| offset > *last + 1) {
+            self.metrics.increment_capture_dropped_frames(&self.camera_id, offset - last - 1);
+        }
+        self.last_offset = Some(offset);
+    }
+
+    pub fn channel_full(&self) {
+        self.metrics.increment_capture_dropped_frames(&self.camera_id, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_gaps_and_full_channel_are_counted() {
+        let metrics = Arc::new(Metrics::new());
+        let mut drops = CaptureDrops::new("dock", metrics.clone());
+
+        // Appsink discarded 3 and 4, then 7; the stream restarted at 0
+        for offset in [0, 1, 2, 5, 6, 8, 0, 1, OFFSET_NONE, 2] {
+            drops.observe_offset(offset);
+        }
+        assert!(metrics.encode().contains("aetherforge_capture_dropped_frames_total{camera_id=\"dock\"} 3"));
+
+        drops.channel_full();
+        assert!(metrics.encode().contains("aetherforge_capture_dropped_frames_total{camera_id=\"dock\"} 4"));
+    }
+}
@@ -1,13 +1,21 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use gstreamer::prelude::*;
 use gstreamer_app::AppSink;
 use gstreamer_video::{VideoInfo, VideoFormat};
-use std::sync::{Arc, Mutex};
-use tokio::sync::mpsc;
+use std::sync::{atomic::{AtomicU64, Ordering}, Arc, Mutex};
+use tokio::sync::mpsc::{self, error::TrySendError};
 use tracing::{error, info, warn};
 
-use super::{Camera, CameraFrame};
-use crate::config::CameraConfig;
+use super::{bandwidth::CaptureLimit, capture_drops::CaptureDrops, timestamp::FrameClock, Camera, CameraFrame};
+use crate::{
+    config::CameraConfig,
+    utils::{affinity, metrics::Metrics},
+};
+
+// The capsfilter right after the source; the bandwidth budget limits a
+// camera by narrowing it, so the source itself delivers less. Custom
+// pipelines opt in by naming theirs the same.
+const SOURCE_CAPS: &str = "source_caps";
 
 pub struct GStreamerCamera {
     config: CameraConfig,
@@ -17,12 +25,19 @@ pub struct GStreamerCamera {
     frame_rx: Option<mpsc::Receiver<CameraFrame>>,
     is_running: bool,
     sequence_num: Arc<Mutex<u64>>,
+    frame_clock: Arc<Mutex<FrameClock>>,
+    capture_drops: Arc<Mutex<CaptureDrops>>,
+    bytes_received: Arc<AtomicU64>,
+    capture_limit: Option<CaptureLimit>, // set by the bandwidth budget; kept across restarts
 }
 
 impl GStreamerCamera {
-    pub fn new(config: CameraConfig) -> Self {
+    pub fn new(config: CameraConfig, metrics: Arc<Metrics>) -> Self {
         let (frame_tx, frame_rx) = mpsc::channel(10);
         
+        let frame_clock = Arc::new(Mutex::new(FrameClock::new(config.timestamp_source)));
+        let capture_drops = Arc::new(Mutex::new(CaptureDrops::new(&config.id, metrics)));
+        
         Self {
             config,
             pipeline: None,
@@ -31,6 +46,10 @@ impl GStreamerCamera {
             frame_rx: Some(frame_rx),
             is_running: false,
             sequence_num: Arc::new(Mutex::new(0)),
+            frame_clock,
+            capture_drops,
+            bytes_received: Arc::new(AtomicU64::new(0)),
+            capture_limit: None,
         }
     }
     
@@ -38,9 +57,9 @@ impl GStreamerCamera {
         let pipeline_desc = if self.config.pipeline.is_empty() {
             // Default pipeline for USB camera
             format!(
-                "v4l2src device={} ! video/x-raw,width={},height={},framerate={}/1 ! \
+                "v4l2src device={} ! capsfilter name={} caps=\"video/x-raw,width={},height={},framerate={}/1\" ! \
                  videoconvert ! video/x-raw,format=RGB ! appsink name=sink sync=false",
-                self.config.device, self.config.width, self.config.height, self.config.framerate
+                self.config.device, SOURCE_CAPS, self.config.width, self.config.height, self.config.framerate
             )
         } else {
             self.config.pipeline.clone()
@@ -50,6 +69,11 @@ impl GStreamerCamera {
         let pipeline = gstreamer::parse_launch(&pipeline_desc)?
             .downcast::<gstreamer::Pipeline>()
             .map_err(|_| anyhow!("Failed to downcast to pipeline"))?;
+        
+        // A restarted pipeline comes up already limited
+        if let (Some(limit), Some(filter)) = (self.capture_limit, pipeline.by_name(SOURCE_CAPS)) {
+            filter.set_property("caps", &Self::source_caps(limit));
+        }
             
         Ok(pipeline)
     }
@@ -62,14 +86,7 @@ impl GStreamerCamera {
             .map_err(|_| anyhow!("Failed to downcast to AppSink"))?;
             
         // Configure appsink
-        appsink.set_caps(Some(&gstreamer::Caps::new_simple(
-            "video/x-raw",
-            &[
-                ("format", &gstreamer_video::VideoFormat::Rgb.to_string()),
-                ("width", &(self.config.width as i32)),
-                ("height", &(self.config.height as i32)),
-            ],
-        )));
+        appsink.set_caps(Some(&self.appsink_caps(self.capture_limit)));
         
         appsink.set_drop(true);
         appsink.set_max_buffers(5);
@@ -78,11 +95,54 @@ impl GStreamerCamera {
         Ok(appsink)
     }
     
+    // Configured size unless the bandwidth budget limited the source; the
+    // framerate is whatever the source delivers
+    fn appsink_caps(&self, limit: Option<CaptureLimit>) -> gstreamer::Caps {
+        let (width, height) = limit.map_or((self.config.width, self.config.height), |limit| (limit.width, limit.height));
+        gstreamer::Caps::builder("video/x-raw")
+            .field("format", VideoFormat::Rgb.to_str())
+            .field("width", width as i32)
+            .field("height", height as i32)
+            .build()
+    }
+    
+    fn source_caps(limit: CaptureLimit) -> gstreamer::Caps {
+        gstreamer::Caps::builder("video/x-raw")
+            .field("width", limit.width as i32)
+            .field("height", limit.height as i32)
+            .field("framerate", gstreamer::Fraction::new(limit.framerate as i32, 1))
+            .build()
+    }
+    
+    // Counts what each source element pushes downstream, i.e. the stream as
+    // it arrives, encoded or not. This is what a limit on the source caps
+    // reduces. Network sources add their pads once the stream is up.
+    fn count_source_bytes(pipeline: &gstreamer::Pipeline, bytes: Arc<AtomicU64>) {
+        fn probe(pad: &gstreamer::Pad, bytes: Arc<AtomicU64>) {
+            pad.add_probe(gstreamer::PadProbeType::BUFFER, move |_, info| {
+                if let Some(buffer) = info.buffer() {
+                    bytes.fetch_add(buffer.size() as u64, Ordering::Relaxed);
+                }
+                gstreamer::PadProbeReturn::Ok
+            });
+        }
+        
+        for source in pipeline.iterate_sources().into_iter().flatten() {
+            for pad in source.src_pads() {
+                probe(&pad, bytes.clone());
+            }
+            let bytes = bytes.clone();
+            source.connect_pad_added(move |_, pad| probe(pad, bytes.clone()));
+        }
+    }
+    
     fn on_new_sample(
-        &self,
         appsink: &AppSink,
+        camera_id: &str,
         frame_tx: mpsc::Sender<CameraFrame>,
         sequence_num: Arc<Mutex<u64>>,
+        frame_clock: Arc<Mutex<FrameClock>>,
+        capture_drops: Arc<Mutex<CaptureDrops>>,
     ) -> Result<(), glib::error::Error> {
         let sample = appsink.pull_sample().map_err(|_| {
             glib::error::Error::new(gstreamer::CoreError::Failed, "Failed to pull sample")
@@ -91,6 +151,7 @@ impl GStreamerCamera {
         let buffer = sample.buffer().ok_or_else(|| {
             glib::error::Error::new(gstreamer::CoreError::Failed, "Failed to get buffer")
         })?;
+        capture_drops.lock().unwrap().observe_offset(buffer.offset());
         
         let caps = sample.caps().ok_or_else(|| {
             glib::error::Error::new(gstreamer::CoreError::Failed, "Failed to get caps")
@@ -111,6 +172,8 @@ impl GStreamerCamera {
         
         let data = map.as_slice().to_vec();
         
+        let timestamp = frame_clock.lock().unwrap().buffer_timestamp_ms(buffer);
+        
         // Increment sequence number
         let mut seq_num = sequence_num.lock().unwrap();
         *seq_num += 1;
@@ -118,20 +181,20 @@ impl GStreamerCamera {
         
         // Create frame
         let frame = CameraFrame {
+            camera_id: camera_id.to_string(),
             data,
             width,
             height,
             format,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
+            timestamp,
             sequence_num: current_seq,
         };
         
         // Send frame through channel (non-blocking)
-        if let Err(e) = frame_tx.try_send(frame) {
-            warn!("Failed to send frame: {}", e);
+        match frame_tx.try_send(frame) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => capture_drops.lock().unwrap().channel_full(),
+            Err(e) => warn!("Failed to send frame: {}", e),
         }
         
         Ok(())
@@ -151,21 +214,30 @@ impl Camera for GStreamerCamera {
         // Build pipeline
         let pipeline = self.build_pipeline()?;
         let appsink = self.setup_appsink(&pipeline)?;
+        Self::count_source_bytes(&pipeline, self.bytes_received.clone());
         
-        // Clone needed values for callback
-        let frame_tx = self.frame_tx.take().ok_or_else(|| anyhow!("Frame transmitter already taken"))?;
+        // Clone needed values for callback; the sender is kept so a stalled
+        // pipeline can be restarted onto the same channel
+        let frame_tx = self.frame_tx.clone().ok_or_else(|| anyhow!("Frame transmitter already taken"))?;
         let sequence_num = self.sequence_num.clone();
+        let frame_clock = self.frame_clock.clone();
+        let capture_drops = self.capture_drops.clone();
+        let capture_cores = self.config.capture_cores.clone();
+        let camera_id = self.config.id.clone();
         
-        // Connect to the new-sample signal
+        // Connect to the new-sample signal; it fires on GStreamer's streaming thread
         appsink.connect_new_sample(move |appsink| {
-            Self::on_new_sample(&Self, appsink, frame_tx.clone(), sequence_num.clone())
+            affinity::pin_current_thread_once(&capture_cores);
+            Self::on_new_sample(appsink, &camera_id, frame_tx.clone(), sequence_num.clone(), frame_clock.clone(), capture_drops.clone())
         });
         
         // Create and run main loop in a separate thread
         let main_loop = glib::MainLoop::new(None, false);
         let main_loop_clone = main_loop.clone();
         
+        let capture_cores = self.config.capture_cores.clone();
         std::thread::spawn(move || {
+            affinity::pin_current_thread(&capture_cores, 0);
             info!("Starting GStreamer main loop");
             main_loop_clone.run();
             info!("GStreamer main loop exited");
@@ -189,13 +261,13 @@ impl Camera for GStreamerCamera {
             return Ok(());
         }
         
-        if let Some(pipeline) = &self.pipeline {
+        if let Some(pipeline) = self.pipeline.take() {
             pipeline.set_state(gstreamer::State::Null).map_err(|e| {
                 anyhow!("Failed to set pipeline to null state: {}", e)
             })?;
         }
         
-        if let Some(main_loop) = &self.main_loop {
+        if let Some(main_loop) = self.main_loop.take() {
             main_loop.quit();
         }
         
@@ -212,6 +284,57 @@ impl Camera for GStreamerCamera {
     fn get_config(&self) -> &CameraConfig {
         &self.config
     }
+    
+    fn frame_count(&self) -> u64 {
+        *self.sequence_num.lock().unwrap()
+    }
+    
+    fn bytes_received(&self) -> u64 {
+        self.bytes_received.load(Ordering::Relaxed)
+    }
+    
+    // Narrows the source capsfilter, so the source renegotiates and itself
+    // delivers fewer bytes. Only pipelines with a `source_caps` filter can be
+    // limited, and only to a format the source offers. An encoded network
+    // stream's bitrate is set by the camera's encoder, not here.
+    fn set_capture_limit(&mut self, limit: Option<CaptureLimit>) -> Result<()> {
+        if !self.config.pipeline.is_empty() && !self.config.pipeline.contains(SOURCE_CAPS) {
+            bail!("Camera {} pipeline has no {} capsfilter to limit", self.config.id, SOURCE_CAPS);
+        }
+        
+        if let Some(pipeline) = &self.pipeline {
+            let filter = pipeline
+                .by_name(SOURCE_CAPS)
+                .ok_or_else(|| anyhow!("No {} element found in pipeline", SOURCE_CAPS))?;
+            let caps = Self::source_caps(limit.unwrap_or(CaptureLimit {
+                framerate: self.config.framerate,
+                width: self.config.width,
+                height: self.config.height,
+            }));
+            
+            // A format the source can't produce would fail negotiation and
+            // keep failing across watchdog restarts
+            if let Some(source_pad) = filter.static_pad("sink").and_then(|pad| pad.peer()) {
+                if !source_pad.query_caps(None).can_intersect(&caps) {
+                    bail!("Camera {} source doesn't offer {}", self.config.id, caps);
+                }
+            }
+            
+            let appsink = pipeline
+                .by_name("sink")
+                .and_then(|element| element.downcast::<AppSink>().ok())
+                .ok_or_else(|| anyhow!("No appsink element found in pipeline"))?;
+            appsink.set_caps(Some(&self.appsink_caps(limit)));
+            filter.set_property("caps", &caps);
+            if let Some(pad) = appsink.static_pad("sink") {
+                pad.push_event(gstreamer::event::Reconfigure::new());
+            }
+            info!("Camera {} capture limited to {:?}", self.config.id, limit);
+        }
+        
+        self.capture_limit = limit;
+        Ok(())
+    }
 }
 
 impl Drop for GStreamerCamera {
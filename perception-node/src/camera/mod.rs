@@ -8,15 +8,7 @@ use tokio::sync::mpsc;
 
 use crate::config::CameraConfig;
 
-#[derive(Debug, Clone)]
-pub struct CameraFrame {
-    pub data: Vec<u8>,
-    pub width: u32,
-    pub height: u32,
-    pub format: String,
-    pub timestamp: u64,
-    pub sequence_num: u64,
-}
+pub use aetherforge_common::CameraFrame;
 
 #[async_trait]
 pub trait Camera {
@@ -24,6 +16,13 @@ pub trait Camera {
     async fn stop(&mut self) -> Result<()>;
     fn get_frame_rx(&self) -> Option<tokio::sync::mpsc::Receiver<CameraFrame>>;
     fn get_config(&self) -> &CameraConfig;
+    fn frame_count(&self) -> u64; // frames delivered since creation, across restarts
+    fn bytes_received(&self) -> u64; // bytes out of the source since creation, across restarts
+    fn set_capture_limit(&mut self, limit: Option<bandwidth::CaptureLimit>) -> Result<()>;
 }
 
-pub mod gstreamer_camera;
\ No newline at end of file
+pub mod bandwidth;
+pub mod capture_drops;
+pub mod gstreamer_camera;
+pub mod timestamp;
+pub mod watchdog;
\ No newline at end of file
@@ -1,13 +1,16 @@
-use super::{Camera, CameraFrame};
+use super::{bandwidth::{CameraStreams, CaptureLimit}, watchdog::CameraPipelines, Camera, CameraFrame};
 use crate::error::Result;
 use aetherforge_common::CameraConfig;
 use dashmap::DashMap;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Mutex};
 use tracing::{error, info, warn};
 
+// Locked so one camera's pipeline can be restarted while the rest run
+type SharedCamera = Arc<Mutex<Box<dyn Camera + Send>>>;
+
 pub struct MultiCameraManager {
-    cameras: DashMap<String, Arc<dyn Camera>>,
+    cameras: DashMap<String, SharedCamera>,
     frame_receivers: DashMap<String, mpsc::Receiver<CameraFrame>>,
     metrics: Arc<crate::utils::metrics::Metrics>,
 }
@@ -25,7 +28,7 @@ impl MultiCameraManager {
             
             match Self::create_camera(config, metrics.clone()).await {
                 Ok((camera, receiver)) => {
-                    cameras.insert(camera.get_id().to_string(), Arc::new(camera));
+                    cameras.insert(camera.get_id().to_string(), Arc::new(Mutex::new(camera)));
                     frame_receivers.insert(camera.get_id().to_string(), receiver);
                     info!("Camera {} initialized successfully", camera.get_id());
                 }
@@ -43,7 +46,7 @@ impl MultiCameraManager {
     }
     
     async fn create_camera(config: CameraConfig, metrics: Arc<crate::utils::metrics::Metrics>) 
-        -> Result<(Box<dyn Camera>, mpsc::Receiver<CameraFrame>)> 
+        -> Result<(Box<dyn Camera + Send>, mpsc::Receiver<CameraFrame>)> 
     {
         use super::gstreamer::GStreamerCamera;
         
@@ -55,7 +58,7 @@ impl MultiCameraManager {
         Ok((Box::new(camera), receiver))
     }
     
-    pub fn get_camera(&self, camera_id: &str) -> Option<Arc<dyn Camera>> {
+    pub fn get_camera(&self, camera_id: &str) -> Option<SharedCamera> {
         self.cameras.get(camera_id).map(|c| c.value().clone())
     }
     
@@ -69,7 +72,7 @@ impl MultiCameraManager {
     
     pub async fn start_all(&self) -> Result<()> {
         for camera in self.cameras.iter() {
-            if let Err(e) = camera.value().start().await {
+            if let Err(e) = camera.value().lock().await.start().await {
                 error!("Failed to start camera {}: {}", camera.key(), e);
             }
         }
@@ -78,7 +81,7 @@ impl MultiCameraManager {
     
     pub async fn stop_all(&self) -> Result<()> {
         for camera in self.cameras.iter() {
-            if let Err(e) = camera.value().stop().await {
+            if let Err(e) = camera.value().lock().await.stop().await {
                 error!("Failed to stop camera {}: {}", camera.key(), e);
             }
         }
@@ -96,11 +99,52 @@ impl MultiCameraManager {
     }
 }
 
+#[async_trait::async_trait]
+impl CameraPipelines for MultiCameraManager {
+    async fn frame_counts(&self) -> Vec<(String, u64)> {
+        let cameras: Vec<(String, SharedCamera)> = self.cameras.iter().map(|c| (c.key().clone(), c.value().clone())).collect();
+        let mut counts = Vec::with_capacity(cameras.len());
+        for (camera_id, camera) in cameras {
+            counts.push((camera_id, camera.lock().await.frame_count()));
+        }
+        counts
+    }
+    
+    // Stops and starts just this camera; frames resume on the same receiver
+    async fn restart(&self, camera_id: &str) -> anyhow::Result<()> {
+        let camera = self.get_camera(camera_id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown camera {}", camera_id))?;
+        let mut camera = camera.lock().await;
+        camera.stop().await?;
+        camera.start().await?;
+        info!("Camera {} pipeline restarted", camera_id);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CameraStreams for MultiCameraManager {
+    async fn bytes_received(&self) -> Vec<(String, u64)> {
+        let cameras: Vec<(String, SharedCamera)> = self.cameras.iter().map(|c| (c.key().clone(), c.value().clone())).collect();
+        let mut bytes = Vec::with_capacity(cameras.len());
+        for (camera_id, camera) in cameras {
+            bytes.push((camera_id, camera.lock().await.bytes_received()));
+        }
+        bytes
+    }
+    
+    async fn limit_capture(&self, camera_id: &str, limit: Option<CaptureLimit>) -> anyhow::Result<()> {
+        let camera = self.get_camera(camera_id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown camera {}", camera_id))?;
+        camera.lock().await.set_capture_limit(limit)
+    }
+}
+
 #[async_trait::async_trait]
 pub trait CameraManager {
     async fn start_all(&self) -> Result<()>;
     async fn stop_all(&self) -> Result<()>;
-    fn get_camera(&self, camera_id: &str) -> Option<Arc<dyn Camera>>;
+    fn get_camera(&self, camera_id: &str) -> Option<SharedCamera>;
     fn list_cameras(&self) -> Vec<String>;
     fn get_health_status(&self) -> HashMap<String, CameraHealthStatus>;
 }
\ No newline at end of file
@@ -0,0 +1,111 @@
+use gstreamer::{BufferRef, ClockTime, ReferenceTimestampMeta};
+
+use crate::config::TimestampSource;
+use aetherforge_common::utils::current_timestamp_ms;
+
+// Milliseconds between the NTP epoch (1900) and the Unix epoch
+const NTP_UNIX_OFFSET_MS: u64 = 2_208_988_800_000;
+// PTS this far from wall clock means the stream restarted; re-anchor
+const MAX_PTS_DRIFT_MS: i64 = 5_000;
+
+// Turns buffer timestamps into epoch milliseconds for one camera. PTS only
+// measures time since the stream started, so it is anchored to wall clock
+// on the first frame; after that frame spacing comes from the camera, not
+// from when frames happened to arrive.
+pub struct FrameClock {
+    source: TimestampSource,
+    pts_origin_ms: Option<i64>,
+}
+
+impl FrameClock {
+    pub fn new(source: TimestampSource) -> Self {
+        Self {
+            source,
+            pts_origin_ms: None,
+        }
+    }
+
+    pub fn buffer_timestamp_ms(&mut self, buffer: &BufferRef) -> u64 {
+        let ntp = buffer
+            .iter_meta::<ReferenceTimestampMeta>()
+            .find(|meta| meta.reference().structure(0).is_some_and(|s| s.name() == "timestamp/x-ntp"))
+            .map(|meta| meta.timestamp());
+
+        self.timestamp_ms(buffer.pts(), ntp, current_timestamp_ms())
+    }
+
+    pub fn timestamp_ms(&mut self, pts: Option<ClockTime>, ntp: Option<ClockTime>, now_ms: u64) -> u64 {
+        match self.source {
+            TimestampSource::WallClockReceive => now_ms,
+            TimestampSource::NtpAligned => match ntp {
+                Some(ntp) if ntp.mseconds() > NTP_UNIX_OFFSET_MS => ntp.mseconds() - NTP_UNIX_OFFSET_MS,
+                _ => self.from_pts(pts, now_ms),
+            },
+            TimestampSource::BufferPts => self.from_pts(pts, now_ms),
+        }
+    }
+
+    fn from_pts(&mut self, pts: Option<ClockTime>, now_ms: u64) -> u64 {
+        let Some(pts) = pts else {
+            return now_ms;
+        };
+        let pts_ms = pts.mseconds() as i64;
+        let now_ms = now_ms as i64;
+
+        let origin = match self.pts_origin_ms {
+            Some(origin) if (origin + pts_ms - now_ms).abs() <= MAX_PTS_DRIFT_MS => origin,
+            _ => {
+                let origin = now_ms - pts_ms;
+                self.pts_origin_ms = Some(origin);
+                origin
+            }
+        };
+
+        (origin + pts_ms).max(0) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_carries_buffer_pts_not_receive_time() {
+        gstreamer::init().unwrap();
+        let mut clock = FrameClock::new(TimestampSource::BufferPts);
+
+        let mut first = gstreamer::Buffer::new();
+        first.get_mut().unwrap().set_pts(ClockTime::from_mseconds(1_000));
+        let anchored = clock.buffer_timestamp_ms(&first);
+
+        // Second frame captured 40ms later but delivered after a 300ms stall
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        let mut second = gstreamer::Buffer::new();
+        second.get_mut().unwrap().set_pts(ClockTime::from_mseconds(1_040));
+        let stamped = clock.buffer_timestamp_ms(&second);
+
+        assert_eq!(stamped, anchored + 40);
+        assert!(current_timestamp_ms() >= stamped + 250);
+    }
+
+    #[test]
+    fn test_timestamp_sources() {
+        let pts = Some(ClockTime::from_mseconds(2_000));
+        let ntp = Some(ClockTime::from_mseconds(NTP_UNIX_OFFSET_MS + 1_700_000_000_000));
+
+        let mut wall = FrameClock::new(TimestampSource::WallClockReceive);
+        assert_eq!(wall.timestamp_ms(pts, ntp, 50_000), 50_000);
+
+        let mut ntp_clock = FrameClock::new(TimestampSource::NtpAligned);
+        assert_eq!(ntp_clock.timestamp_ms(pts, ntp, 50_000), 1_700_000_000_000);
+        // Without sender reports it falls back to anchored PTS
+        assert_eq!(ntp_clock.timestamp_ms(pts, None, 50_000), 50_000);
+        assert_eq!(ntp_clock.timestamp_ms(Some(ClockTime::from_mseconds(2_100)), None, 50_400), 50_100);
+
+        // A PTS reset (stream restart) re-anchors instead of jumping back
+        let mut pts_clock = FrameClock::new(TimestampSource::BufferPts);
+        assert_eq!(pts_clock.timestamp_ms(pts, None, 50_000), 50_000);
+        assert_eq!(pts_clock.timestamp_ms(Some(ClockTime::from_mseconds(0)), None, 60_000), 60_000);
+        assert_eq!(pts_clock.timestamp_ms(None, None, 61_000), 61_000);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,263 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, warn};
+
+use crate::{
+    config::CameraConfig,
+    messaging::{AlertSeverity, MessagePublisher, SystemAlert},
+    utils::metrics::Metrics,
+};
+
+pub const CAMERA_PIPELINE_RESTARTED: &str = "camera_pipeline_restarted";
+
+const WATCHDOG_TICK: Duration = Duration::from_secs(1);
+
+// What the watchdog needs from the camera manager, so one camera's pipeline
+// can be restarted without touching the others
+#[async_trait]
+pub trait CameraPipelines: Send + Sync {
+    // Frames each running camera has delivered since it was created
+    async fn frame_counts(&self) -> Vec<(String, u64)>;
+    async fn restart(&self, camera_id: &str) -> Result<()>;
+}
+
+struct Progress {
+    stall_after_ms: u64,
+    frame_count: u64,
+    since_ms: u64, // when `frame_count` last changed, or the last restart
+}
+
+struct Sample {
+    frame_count: u64,
+    at_ms: u64,
+    advanced_ms: u64, // when the count last moved; restarts don't reset it
+}
+
+// Restarts camera pipelines that are PLAYING but have stopped delivering
+// buffers, which GStreamer doesn't report as an error. A camera is stalled
+// once its frame count hasn't moved for `health_check_interval_sec`; after
+// a restart it gets a full interval again before the next one. An interval
+// of 0 leaves the camera unwatched.
+// With metrics, each sweep also sets every camera's observed fps and
+// whether it is up: a watched camera is down once it counts as stalled, an
+// unwatched one as soon as a sweep finds no new frames.
+pub struct PipelineWatchdog {
+    cameras: HashMap<String, Progress>,
+    samples: HashMap<String, Sample>,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl PipelineWatchdog {
+    pub fn new(cameras: &[CameraConfig], now_ms: u64) -> Self {
+        let cameras = cameras
+            .iter()
+            .filter(|camera| camera.enabled && camera.health_check_interval_sec > 0)
+            .map(|camera| {
+                let progress = Progress {
+                    stall_after_ms: camera.health_check_interval_sec * 1000,
+                    frame_count: 0,
+                    since_ms: now_ms,
+                };
+                (camera.id.clone(), progress)
+            })
+            .collect();
+
+        Self { cameras, samples: HashMap::new(), metrics: None }
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    // Restarts every stalled camera, returning a health alert for each
+    pub async fn sweep<C: CameraPipelines + ?Sized>(&mut self, pipelines: &C, now_ms: u64) -> Vec<SystemAlert> {
+        let mut alerts = Vec::new();
+
+        for (camera_id, frame_count) in pipelines.frame_counts().await {
+            self.record_metrics(&camera_id, frame_count, now_ms);
+            let Some(progress) = self.cameras.get_mut(&camera_id) else {
+                continue;
+            };
+            if frame_count != progress.frame_count {
+                progress.frame_count = frame_count;
+                progress.since_ms = now_ms;
+                continue;
+            }
+
+            let stalled_ms = now_ms.saturating_sub(progress.since_ms);
+            if stalled_ms < progress.stall_after_ms {
+                continue;
+            }
+            progress.since_ms = now_ms;
+
+            warn!("Camera {} delivered no frames for {}ms, restarting its pipeline", camera_id, stalled_ms);
+            let restarted = pipelines.restart(&camera_id).await;
+            if let Err(e) = &restarted {
+                error!("Failed to restart camera {} pipeline: {}", camera_id, e);
+            }
+
+            alerts.push(SystemAlert {
+                severity: if restarted.is_ok() { AlertSeverity::Warning } else { AlertSeverity::Error },
+                source: camera_id.clone(),
+                message: match &restarted {
+                    Ok(()) => format!("Camera {} stalled for {}s, pipeline restarted", camera_id, stalled_ms / 1000),
+                    Err(e) => format!("Camera {} stalled for {}s, pipeline restart failed: {}", camera_id, stalled_ms / 1000, e),
+                },
+                timestamp: now_ms,
+                details: Some(json!({
+                    "alert_type": CAMERA_PIPELINE_RESTARTED,
+                    "stalled_ms": stalled_ms,
+                    "frame_count": frame_count,
+                    "restarted": restarted.is_ok(),
+                })),
+            });
+        }
+
+        alerts
+    }
+
+    fn record_metrics(&mut self, camera_id: &str, frame_count: u64, now_ms: u64) {
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+        let sample = self.samples.entry(camera_id.to_string()).or_insert(Sample {
+            frame_count,
+            at_ms: now_ms,
+            advanced_ms: now_ms,
+        });
+
+        let elapsed_ms = now_ms.saturating_sub(sample.at_ms);
+        if elapsed_ms > 0 {
+            let frames = frame_count.saturating_sub(sample.frame_count);
+            metrics.set_camera_fps(camera_id, frames as f64 * 1000.0 / elapsed_ms as f64);
+        }
+        if frame_count != sample.frame_count {
+            sample.advanced_ms = now_ms;
+        }
+        sample.frame_count = frame_count;
+        sample.at_ms = now_ms;
+
+        let window_ms = self.cameras.get(camera_id).map_or(0, |progress| progress.stall_after_ms);
+        metrics.set_camera_health(camera_id, now_ms.saturating_sub(sample.advanced_ms) <= window_ms);
+    }
+}
+
+pub async fn run<C, P>(mut watchdog: PipelineWatchdog, pipelines: Arc<C>, publisher: Arc<P>)
+where
+    C: CameraPipelines + ?Sized,
+    P: MessagePublisher + ?Sized,
+{
+    let mut ticker = tokio::time::interval(WATCHDOG_TICK);
+    loop {
+        ticker.tick().await;
+        let now_ms = aetherforge_common::utils::current_timestamp_ms();
+        for alert in watchdog.sweep(pipelines.as_ref(), now_ms).await {
+            if let Err(e) = publisher.publish_alert(&alert).await {
+                warn!("Failed to publish pipeline restart for {}: {}", alert.source, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // Two cameras on one node: the dock camera's source froze after 40 frames
+    struct FakePipelines {
+        counts: Mutex<HashMap<String, u64>>,
+        restarts: Mutex<Vec<String>>,
+    }
+
+    impl FakePipelines {
+        fn advance(&self, camera_id: &str, frames: u64) {
+            *self.counts.lock().unwrap().get_mut(camera_id).unwrap() += frames;
+        }
+    }
+
+    #[async_trait]
+    impl CameraPipelines for FakePipelines {
+        async fn frame_counts(&self) -> Vec<(String, u64)> {
+            let mut counts: Vec<(String, u64)> = self.counts.lock().unwrap().iter().map(|(id, n)| (id.clone(), *n)).collect();
+            counts.sort();
+            counts
+        }
+
+        async fn restart(&self, camera_id: &str) -> Result<()> {
+            self.restarts.lock().unwrap().push(camera_id.to_string());
+            Ok(())
+        }
+    }
+
+    fn camera(id: &str) -> CameraConfig {
+        CameraConfig { id: id.to_string(), health_check_interval_sec: 5, ..CameraConfig::default() }
+    }
+
+    #[tokio::test]
+    async fn test_stalled_camera_is_restarted_and_healthy_one_left_alone() {
+        let pipelines = FakePipelines {
+            counts: Mutex::new(HashMap::from([("aisle-3".to_string(), 0), ("dock-1".to_string(), 40)])),
+            restarts: Mutex::new(Vec::new()),
+        };
+        let mut watchdog = PipelineWatchdog::new(&[camera("aisle-3"), camera("dock-1")], 0);
+
+        // One sweep a second; aisle-3 keeps delivering 30fps, dock-1 is stuck
+        let mut alerts = Vec::new();
+        for second in 0..=10u64 {
+            pipelines.advance("aisle-3", 30);
+            alerts.extend(watchdog.sweep(&pipelines, second * 1000).await);
+        }
+
+        // Stuck from t=0, restarted at 5s, then again after another full interval
+        assert_eq!(*pipelines.restarts.lock().unwrap(), vec!["dock-1", "dock-1"]);
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].source, "dock-1");
+        assert!(matches!(alerts[0].severity, AlertSeverity::Warning));
+        let details = alerts[0].details.as_ref().unwrap();
+        assert_eq!(details["alert_type"], CAMERA_PIPELINE_RESTARTED);
+        assert_eq!(details["stalled_ms"], 5000);
+
+        // Frames flowing again after the restart keep it healthy
+        for second in 11..=20u64 {
+            pipelines.advance("aisle-3", 30);
+            pipelines.advance("dock-1", 30);
+            assert!(watchdog.sweep(&pipelines, second * 1000).await.is_empty());
+        }
+        assert_eq!(pipelines.restarts.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sweeps_report_camera_fps_and_health() {
+        let pipelines = FakePipelines {
+            counts: Mutex::new(HashMap::from([("aisle-3".to_string(), 0), ("dock-1".to_string(), 40)])),
+            restarts: Mutex::new(Vec::new()),
+        };
+        let metrics = Arc::new(Metrics::new());
+        let mut watchdog = PipelineWatchdog::new(&[camera("aisle-3"), camera("dock-1")], 0).with_metrics(metrics.clone());
+
+        for second in 0..=3u64 {
+            pipelines.advance("aisle-3", 30);
+            watchdog.sweep(&pipelines, second * 1000).await;
+        }
+        let encoded = metrics.encode();
+        assert!(encoded.contains("aetherforge_camera_fps{camera_id=\"aisle-3\"} 30"), "{}", encoded);
+        assert!(encoded.contains("aetherforge_camera_fps{camera_id=\"dock-1\"} 0"), "{}", encoded);
+        // Not stalled for a full interval yet
+        assert!(encoded.contains("aetherforge_camera_up{camera_id=\"dock-1\"} 1"), "{}", encoded);
+
+        for second in 4..=6u64 {
+            pipelines.advance("aisle-3", 30);
+            watchdog.sweep(&pipelines, second * 1000).await;
+        }
+        let encoded = metrics.encode();
+        assert!(encoded.contains("aetherforge_camera_up{camera_id=\"aisle-3\"} 1"), "{}", encoded);
+        // Restarting it didn't bring frames back
+        assert!(encoded.contains("aetherforge_camera_up{camera_id=\"dock-1\"} 0"), "{}", encoded);
+    }
+}
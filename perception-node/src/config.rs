@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, time::Duration};
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+use crate::error::{PerceptionError, Result};
+use crate::messaging::AlertSeverity;
+use aetherforge_common::secrets::resolve_secret;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PerceptionConfig {
@@ -10,6 +14,73 @@ pub struct PerceptionConfig {
     pub processing: ProcessingConfig,
     pub monitoring: MonitoringConfig,
     pub logging: LoggingConfig,
+    pub aggregator: AggregatorConfig, // only used with --aggregate
+    pub startup: StartupConfig,
+    pub config_sync: ConfigSyncConfig,
+}
+
+// Talking to the operator platform: pushed config and heartbeats
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfigSyncConfig {
+    pub enabled: bool,
+    pub operator_url: String, // e.g. http://operator:8080/api/v1
+    pub poll_interval_ms: u64,
+    pub heartbeat_interval_ms: u64, // 0 disables heartbeats to the node registry
+    pub max_samples_per_hour: u32, // cap on annotation samples across all cameras; 0 sends none
+    pub sample_jpeg_quality: u8,
+}
+
+// How long startup waits on external dependencies before giving up
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StartupConfig {
+    pub dependency_timeout_ms: u64,
+    pub retry_interval_ms: u64,
+    pub degraded_start: bool, // start without messaging and buffer frames until it recovers
+    pub buffer_capacity: usize, // frames held while degraded; the oldest are dropped past this
+}
+
+// Facility-wide fusion across perception nodes
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AggregatorConfig {
+    pub zone_id: String,
+    pub nodes: Vec<AggregatorNode>,
+    pub publish_endpoint: String, // world models go out here as JSON, like the simulator's
+    pub publish_interval_ms: u64,
+    pub node_timeout_ms: u64, // a silent node's objects are dropped after this
+    pub association_radius_m: f32, // objects of one class from different nodes this close are one object
+    pub persist_url: Option<String>, // operator platform API base; world models are posted to its /worldmodel for playback
+    pub persist_interval_ms: u64, // how often one is posted
+    pub kafka: Option<KafkaConsumerConfig>, // read nodes from Kafka rather than ZeroMQ; needs the kafka feature
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AggregatorNode {
+    pub node_id: String,
+    pub endpoint: String, // the node's ZeroMQ publisher, or its topic when reading from Kafka
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KafkaConsumerConfig {
+    pub brokers: String, // bootstrap servers, e.g. kafka-1:9092,kafka-2:9092
+    pub group_id: String, // consumers in one group split the partitions and share committed offsets
+    pub offset_commit: OffsetCommit,
+    pub auto_commit_interval_ms: u64, // how often processed offsets are committed with OffsetCommit::Auto
+    pub start_from: StartOffset, // where a group with no committed offset begins
+    pub session_timeout_ms: u64, // a consumer silent this long is dropped from the group and its partitions reassigned
+    pub reconnect_backoff_ms: u64,
+    pub reconnect_backoff_max_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum OffsetCommit {
+    Auto,   // committed in the background every auto_commit_interval_ms
+    Manual, // committed when the consumer says so, on rebalance and on close
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum StartOffset {
+    Earliest,
+    Latest,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -26,6 +97,38 @@ pub struct CameraConfig {
     pub rtsp_url: Option<String>,
     pub zone: Option<String>,
     pub health_check_interval_sec: u64,
+    pub timestamp_source: TimestampSource,
+    pub capture_cores: Vec<usize>, // cores the capture thread may run on; empty leaves it unpinned
+    pub max_publish_fps: Option<f32>, // cap on published perception frames; tracking still sees every frame
+    pub annotation_sampling: Option<SamplingPolicy>, // periodic captures sent for labeling, regardless of confidence
+    pub warmup: CameraWarmupConfig,
+    pub priority: u8, // frames of higher-priority cameras are batched first when inference can't keep up
+}
+
+// How long a camera settles before its frames reach inference. The first
+// `discard_frames` are always dropped; after that frames are held back until
+// `stable_frames` in a row pass the frame quality limits, or `max_wait_ms`
+// after the first frame, whichever comes first.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CameraWarmupConfig {
+    pub discard_frames: u32,
+    pub stable_frames: u32, // 0 skips the stability check
+    pub max_wait_ms: u64,
+}
+
+// When a camera's frames are captured for the annotation pipeline
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum SamplingPolicy {
+    Interval { seconds: f32 },     // one frame every `seconds`
+    RandomPerHour { frames: u32 }, // on average `frames` an hour, at random times
+}
+
+// Where a frame's timestamp comes from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum TimestampSource {
+    WallClockReceive, // when the appsink received the frame
+    BufferPts,        // buffer PTS, anchored to wall clock at the first frame
+    NtpAligned,       // RTCP sender-report NTP time from rtspsrc, else BufferPts
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -70,8 +173,18 @@ pub struct RegionOfInterest {
 pub struct InferenceConfig {
     pub model_path: PathBuf,
     pub model_version: String,
-    pub confidence_threshold: f32,
+    pub confidence_threshold: f32, // compared against calibrated confidences
+    pub confidence_calibration: HashMap<String, ConfidenceCalibration>, // keyed by model_version
+    pub objectness_threshold: Option<f32>, // with class_score_threshold, gates raw scores separately instead of their product
+    pub class_score_threshold: Option<f32>, // an unset half of the pair falls back to confidence_threshold
     pub nms_threshold: f32,
+    pub class_nms_thresholds: HashMap<String, f32>, // keyed by class name, overrides nms_threshold
+    pub nms_strategy: NmsStrategy,
+    pub soft_nms_sigma: f32, // Gaussian decay width for SoftGaussian
+    pub max_detections_per_frame: usize, // only the most confident K go into NMS; 0 keeps all
+    pub min_box_size: MinBoxSize, // detections smaller than this are dropped after NMS
+    pub class_min_box_sizes: HashMap<String, MinBoxSize>, // keyed by class name, overrides min_box_size
+    pub clamp_to_frame: bool, // clamp boxes to the frame, dropping any left without area; off keeps decoded extents
     pub input_width: u32,
     pub input_height: u32,
     pub use_gpu: bool,
@@ -82,15 +195,23 @@ pub struct InferenceConfig {
     pub segmentation_model_path: Option<PathBuf>,
     pub robot_identification_model_path: Option<PathBuf>,
     pub pose_estimation_model_path: Option<PathBuf>,
+    pub shadow_model_path: Option<PathBuf>, // candidate detection model compared against the primary; same input and output format
+    pub shadow_sample_rate: f32, // fraction of frames the shadow model also runs on
     pub max_batch_size: usize,
     pub batch_timeout_ms: u64,
+    pub priority_aging_ms: u64, // a queued frame gains one camera priority level per this wait; 0 is strict priority
     pub enable_dynamic_batching: bool,
     pub model_warmup: bool,
     pub model_cache_size: usize,
     pub gpu_memory_limit_mb: Option<u32>,
     pub enable_fp16: bool,
-    pub enable_int8: bool,
+    pub enable_int8: bool, // needs TensorRT and the calibration cache --calibrate-int8 builds
+    pub int8_calibration: Int8CalibrationConfig,
     pub optimization_level: OptimizationLevel,
+    pub preprocessing: PreprocessingConfig, // must match the transforms the model was trained with
+    pub output_format: OutputFormat,
+    pub intra_op_threads: usize, // ORT threads per session; 0 splits the inference cores between workers
+    pub preprocess_cache_size: usize, // tensors kept per camera frame for models sharing an ROI; 0, the default, disables the cache
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -102,6 +223,74 @@ pub enum InferenceBackend {
     DirectML,
 }
 
+// Post-hoc recalibration of a model's confidences, fitted on held-out data.
+// Both work on the logit, so the ranking of detections is unchanged.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ConfidenceCalibration {
+    Temperature { temperature: f32 }, // sigmoid(logit / temperature); above 1 softens overconfidence
+    Platt { a: f32, b: f32 },         // sigmoid(a * logit + b); `a` must be positive
+}
+
+// Size floor for a detection box; zero disables each check
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct MinBoxSize {
+    pub min_width: f32,
+    pub min_height: f32,
+    pub min_area: f32,
+    pub relative: bool, // values are fractions of the frame size (area: of the frame area)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum NmsStrategy {
+    Standard,
+    SoftLinear,
+    SoftGaussian,
+}
+
+// Layout of the detection model's outputs
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum OutputFormat {
+    YoloV5, // [batch, N, 5 + classes] with objectness
+    YoloV8, // [batch, 4 + classes, N] without objectness
+    YoloV8Obb, // [batch, 4 + classes + 1, N] with a trailing rotation angle in radians
+    SeparateHeads {
+        boxes: String,   // [batch, N, 4] x1, y1, x2, y2
+        scores: String,  // [batch, N]
+        classes: String, // [batch, N]
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ResizeMode {
+    Stretch,   // scale each axis independently to the input size
+    Letterbox, // keep aspect ratio, pad the remainder with `letterbox_fill`
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ChannelOrder {
+    Rgb,
+    Bgr,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PreprocessingConfig {
+    pub resize_mode: ResizeMode,
+    pub mean: [f32; 3], // per channel in model channel order, on the 0..1 scale
+    pub std: [f32; 3],
+    pub channel_order: ChannelOrder,
+    pub letterbox_fill: u8,
+}
+
+// Representative frames for TensorRT's INT8 calibration, see
+// inference::int8_calibration
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Int8CalibrationConfig {
+    pub frames: usize, // captured across all cameras
+    pub capture_interval_ms: u64, // per camera, so the set spans changing scenes rather than one moment
+    pub frames_dir: PathBuf,
+    pub cache_path: PathBuf, // read by the TensorRT session when enable_int8 is set
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum OptimizationLevel {
     Disabled,
@@ -133,6 +322,13 @@ pub struct MessagingConfig {
     pub send_timeout_ms: i32,
     pub reconnect_interval_ms: i32,
     pub security: MessagingSecurity,
+    pub dead_letter: DeadLetterConfig,
+    pub max_payload_bytes: usize, // larger payloads are sent in chunks of this size; 0 never chunks
+    pub chunk_timeout_ms: u64, // subscribers discard a chunked message not complete within this
+    pub max_chunks_per_message: u32, // subscribers drop chunks claiming a larger set, before allocating for it
+    pub delta_encoding: bool, // send perception frames as deltas between periodic keyframes
+    pub keyframe_interval: u32, // frames per camera between keyframes when delta encoding
+    pub message_ttl_ms: u64, // frames older than this, live, held or dead-lettered, are dropped rather than published late; 0 keeps them all
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -162,16 +358,28 @@ pub struct MessagingSecurity {
     pub password: Option<String>,
     pub ssl_cert_path: Option<PathBuf>,
     pub ssl_key_path: Option<PathBuf>,
+    pub signing_key: Option<String>, // shared HMAC key, or an env:/file:/vault: reference; when set, published messages are signed
+    pub require_signatures: bool, // subscribers reject unsigned or invalid messages
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeadLetterConfig {
+    pub enabled: bool,
+    pub path: PathBuf,
+    pub max_messages: usize,
+    pub retry_interval_ms: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProcessingConfig {
-    pub max_queue_size: usize,
-    pub num_worker_threads: usize,
+    pub max_queue_size: usize, // frames waiting per camera; the oldest is dropped when full
+    pub num_worker_threads: usize, // inference pool size, i.e. max concurrent inferences
+    pub inference_cores: Vec<usize>, // cores inference workers are pinned to; empty leaves them unpinned
     pub enable_batch_processing: bool,
     pub batch_timeout_ms: u64,
     pub enable_data_fusion: bool,
     pub fusion_algorithm: FusionAlgorithm,
+    pub fusion_interval_ms: u64, // how often calibrated cameras' detections are fused and published
     
     // New additions
     pub enable_tracking: bool,
@@ -182,6 +390,96 @@ pub struct ProcessingConfig {
     pub frame_skip_interval: u32,
     pub enable_roi_processing: bool,
     pub enable_multi_scale_processing: bool,
+    pub association_radius_m: f32,
+    pub global_track_timeout_ms: u64,
+    pub coast_frames: u32, // frames an unseen global track is still reported at its predicted position; 0 retires it at once
+    pub confidence_ema_alpha: f32, // weight of the newest frame in smoothed confidence; 1.0 disables
+    pub class_smoothing: ClassSmoothing, // how a global track's published class follows its per-frame labels
+    pub facility_map_path: Option<PathBuf>, // facility map whose cells fused objects are tagged with; none leaves them untagged
+    pub proximity: ProximityConfig,
+    pub frame_quality: FrameQualityConfig,
+    pub detection_anomaly: DetectionAnomalyConfig,
+    pub alert_rules: Vec<AlertRule>, // site safety policies over fused objects; hot-reloadable
+    pub bandwidth_budget: BandwidthBudgetConfig,
+}
+
+// Keeps a track's class from flickering when the detector wavers between
+// labels frame to frame; the per-frame label is still reported alongside
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ClassSmoothing {
+    Off,
+    MajorityVote { window: usize }, // most frequent label over the last `window` frames
+    Ema { alpha: f32 },             // highest exponentially decayed confidence per label
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProximityConfig {
+    pub enabled: bool,
+    pub human_class: String,
+    pub robot_class: String,
+    pub warning_radius_m: f32,
+    pub critical_radius_m: f32,
+    pub min_robot_speed_mps: f32, // slower robots are treated as stationary
+}
+
+// Total camera ingest the node's uplink can carry. Over budget, the
+// lowest-priority cameras' sources are renegotiated to a lower framerate,
+// then resolution, one step at a time. Only pipelines with a `source_caps`
+// capsfilter after the source can be limited.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BandwidthBudgetConfig {
+    pub enabled: bool,
+    pub max_total_kbps: u32,
+    pub check_interval_ms: u64,
+    pub step_factor: f32, // each step scales framerate, or width and height, by this
+    pub min_framerate: u32, // resolution is only reduced once framerate is down to this
+    pub min_width: u32, // a camera this narrow is not stepped down further
+    pub restore_ratio: f32, // throttled cameras step back up once the total is below this share of the budget
+}
+
+// A site-defined alert over fused objects. An object matches when it meets
+// every condition that is set; the rule fires once more than `count_above`
+// objects match, and again only after it has stopped matching.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AlertRule {
+    pub name: String, // unique; also the alert's source
+    pub class: Option<String>,
+    pub zone: Option<String>, // facility map cell the object is in
+    pub min_speed_mps: Option<f32>,
+    pub near: Option<ProximityCondition>,
+    pub count_above: usize,
+    pub severity: AlertSeverity,
+    pub message: String, // {rule}, {count}, {class}, {zone} and {track_ids} are filled in
+}
+
+// Within `within_m` of any object of `class`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProximityCondition {
+    pub class: String,
+    pub within_m: f32,
+}
+
+// Per-frame exposure and blur limits; luminance is on a 0-255 scale
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FrameQualityConfig {
+    pub enabled: bool,
+    pub min_mean_luma: f32,
+    pub max_mean_luma: f32,
+    pub min_luma_variance: f32, // uniform frames: lens cap, obstruction
+    pub min_sharpness: f32,     // variance of the Laplacian; low means blurred
+    pub sustain_sec: u64,       // how long a fault must last before alerting
+    pub sample_stride: u32,     // check every Nth pixel in each direction
+}
+
+// When a camera's detections per frame count as leaving its usual range.
+// The baseline is an exponential moving average over about `baseline_frames`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DetectionAnomalyConfig {
+    pub enabled: bool,
+    pub baseline_frames: u32, // also how many frames are seen before alerting
+    pub window_frames: u32,   // recent frames averaged and compared with the baseline
+    pub sensitivity: f32,     // deviation, in standard errors, that alerts; lower is more sensitive
+    pub min_std: f32,         // floor on the baseline spread, so a very steady scene isn't hair-trigger
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -209,6 +507,16 @@ pub enum TrackerType {
 pub struct MonitoringConfig {
     pub enable_metrics: bool,
     pub metrics_port: u16,
+    pub metrics_mode: MetricsMode,
+    pub pushgateway_url: Option<String>, // e.g. http://pushgateway.central:9091, used in Push mode
+    pub pushgateway_job: String,
+    pub push_interval_sec: u64,
+    pub enable_control_api: bool,
+    pub control_bind_address: String, // interface the control API listens on
+    pub control_port: u16,
+    pub enable_scoring_api: bool, // POST /score on the control API, loading requested models on demand
+    pub scoring_models_dir: PathBuf, // models POST /score may load; requests for anything outside are rejected
+    pub scoring_token: Option<String>, // bearer token POST /score requires; may be a secret reference
     pub health_check_interval_sec: u64,
     pub performance_metrics_interval_sec: u64,
     pub enable_alerting: bool,
@@ -223,6 +531,23 @@ pub struct MonitoringConfig {
     pub enable_latency_tracking: bool,
     pub enable_throughput_monitoring: bool,
     pub alert_thresholds: AlertThresholds,
+    pub debug_overlay: DebugOverlayConfig,
+}
+
+// Annotated frames for debugging a model on-node; costs a render and a JPEG
+// encode per sampled frame, so it's off by default
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DebugOverlayConfig {
+    pub enabled: bool,
+    pub every_n_frames: u64,        // render one frame in N per camera
+    pub jpeg_quality: u8,
+    pub font_path: Option<PathBuf>, // TTF for labels; boxes and confidence bars only without one
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum MetricsMode {
+    Scrape, // serve /metrics on metrics_port
+    Push,   // push to pushgateway_url, for nodes behind NAT
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -268,6 +593,93 @@ impl Default for PerceptionConfig {
             processing: ProcessingConfig::default(),
             monitoring: MonitoringConfig::default(),
             logging: LoggingConfig::default(),
+            aggregator: AggregatorConfig::default(),
+            startup: StartupConfig::default(),
+            config_sync: ConfigSyncConfig::default(),
+        }
+    }
+}
+
+impl PerceptionConfig {
+    // Swaps `env:`/`file:`/`vault:` references in secret fields for their values
+    pub fn resolve_secrets(&mut self) -> Result<()> {
+        if let Some(token) = &mut self.monitoring.scoring_token {
+            *token = resolve_secret(token).map_err(|e| PerceptionError::ConfigError(format!("monitoring.scoring_token: {}", e)))?;
+        }
+        self.messaging.resolve_secrets()
+    }
+
+    // Derives the ORT intra-op thread count from the worker pool unless set
+    pub fn reconcile_threads(&mut self) {
+        if self.inference.intra_op_threads == 0 {
+            self.inference.intra_op_threads = crate::inference::worker_pool::intra_op_threads(&self.processing);
+        }
+    }
+}
+
+impl MessagingConfig {
+    fn resolve_secrets(&mut self) -> Result<()> {
+        for secret in [&mut self.security.username, &mut self.security.password, &mut self.security.signing_key].into_iter().flatten() {
+            *secret = resolve_secret(secret).map_err(|e| PerceptionError::ConfigError(format!("messaging.security: {}", e)))?;
+        }
+        match &mut self.fallback_config {
+            Some(fallback) => fallback.resolve_secrets(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for ConfigSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            operator_url: "http://localhost:8080/api/v1".to_string(),
+            poll_interval_ms: 30000,
+            heartbeat_interval_ms: 10000,
+            max_samples_per_hour: 600,
+            sample_jpeg_quality: 90,
+        }
+    }
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            dependency_timeout_ms: 30000,
+            retry_interval_ms: 1000,
+            degraded_start: false,
+            buffer_capacity: 1000,
+        }
+    }
+}
+
+impl Default for AggregatorConfig {
+    fn default() -> Self {
+        Self {
+            zone_id: "FACILITY".to_string(),
+            nodes: Vec::new(),
+            publish_endpoint: "tcp://*:5570".to_string(),
+            publish_interval_ms: 100,
+            node_timeout_ms: 5000,
+            association_radius_m: 1.0,
+            persist_url: None,
+            persist_interval_ms: 1000,
+            kafka: None,
+        }
+    }
+}
+
+impl Default for KafkaConsumerConfig {
+    fn default() -> Self {
+        Self {
+            brokers: "localhost:9092".to_string(),
+            group_id: "aetherforge-aggregator".to_string(),
+            offset_commit: OffsetCommit::Manual,
+            auto_commit_interval_ms: 1000,
+            start_from: StartOffset::Latest,
+            session_timeout_ms: 10_000,
+            reconnect_backoff_ms: 100,
+            reconnect_backoff_max_ms: 10_000,
         }
     }
 }
@@ -287,6 +699,22 @@ impl Default for CameraConfig {
             rtsp_url: None,
             zone: Some("production-line-1".to_string()),
             health_check_interval_sec: 30,
+            timestamp_source: TimestampSource::BufferPts,
+            capture_cores: Vec::new(),
+            max_publish_fps: None,
+            annotation_sampling: None,
+            warmup: CameraWarmupConfig::default(),
+            priority: 0,
+        }
+    }
+}
+
+impl Default for CameraWarmupConfig {
+    fn default() -> Self {
+        Self {
+            discard_frames: 15, // about half a second at 30fps, past RTSP keyframe garbage
+            stable_frames: 3,
+            max_wait_ms: 5000,
         }
     }
 }
@@ -297,7 +725,17 @@ impl Default for InferenceConfig {
             model_path: PathBuf::from("models/yolov5s.onnx"),
             model_version: "1.0".to_string(),
             confidence_threshold: 0.5,
+            confidence_calibration: HashMap::new(),
+            objectness_threshold: None,
+            class_score_threshold: None,
             nms_threshold: 0.5,
+            class_nms_thresholds: HashMap::new(),
+            nms_strategy: NmsStrategy::Standard,
+            soft_nms_sigma: 0.5,
+            max_detections_per_frame: 300,
+            min_box_size: MinBoxSize::default(),
+            class_min_box_sizes: HashMap::new(),
+            clamp_to_frame: true,
             input_width: 640,
             input_height: 480,
             use_gpu: true,
@@ -312,15 +750,49 @@ impl Default for InferenceConfig {
             segmentation_model_path: None,
             robot_identification_model_path: None,
             pose_estimation_model_path: None,
+            shadow_model_path: None,
+            shadow_sample_rate: 0.1,
             max_batch_size: 8,
             batch_timeout_ms: 100,
+            priority_aging_ms: 50,
             enable_dynamic_batching: true,
             model_warmup: true,
             model_cache_size: 2,
             gpu_memory_limit_mb: Some(2048),
             enable_fp16: true,
             enable_int8: false,
+            int8_calibration: Int8CalibrationConfig::default(),
             optimization_level: OptimizationLevel::Level3,
+            preprocessing: PreprocessingConfig::default(),
+            output_format: OutputFormat::YoloV5,
+            intra_op_threads: 0,
+            // Only pays off when several models run on the same frame and ROI
+            preprocess_cache_size: 0,
+        }
+    }
+}
+
+impl Default for Int8CalibrationConfig {
+    fn default() -> Self {
+        Self {
+            // TensorRT's guidance is around 500 images for ImageNet-sized
+            // models; detection ranges settle well before that
+            frames: 500,
+            capture_interval_ms: 2000,
+            frames_dir: PathBuf::from("calibration/int8"),
+            cache_path: PathBuf::from("models/calibration.cache"),
+        }
+    }
+}
+
+impl Default for PreprocessingConfig {
+    fn default() -> Self {
+        Self {
+            resize_mode: ResizeMode::Stretch,
+            mean: [0.0, 0.0, 0.0],
+            std: [1.0, 1.0, 1.0],
+            channel_order: ChannelOrder::Rgb,
+            letterbox_fill: 114,
         }
     }
 }
@@ -346,6 +818,19 @@ impl Default for MessagingConfig {
             send_timeout_ms: 1000,
             reconnect_interval_ms: 100,
             security: MessagingSecurity::default(),
+            dead_letter: DeadLetterConfig::default(),
+            // Under Kafka's default 1MB message.max.bytes, with room for the envelope
+            max_payload_bytes: 900 * 1024,
+            chunk_timeout_ms: 5000,
+            // Over 100MB at the default chunk size
+            max_chunks_per_message: 128,
+            delta_encoding: false,
+            // About a second at 30fps, which bounds how long a subscriber
+            // that missed a frame goes without detections
+            keyframe_interval: 30,
+            // Past a couple of seconds, tracks and alerts downstream have
+            // moved on and a late frame only misleads them
+            message_ttl_ms: 2000,
         }
     }
 }
@@ -359,6 +844,19 @@ impl Default for MessagingSecurity {
             password: None,
             ssl_cert_path: None,
             ssl_key_path: None,
+            signing_key: None,
+            require_signatures: false,
+        }
+    }
+}
+
+impl Default for DeadLetterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: PathBuf::from("/var/lib/aetherforge/dead_letters"),
+            max_messages: 10_000,
+            retry_interval_ms: 5000,
         }
     }
 }
@@ -366,12 +864,14 @@ impl Default for MessagingSecurity {
 impl Default for ProcessingConfig {
     fn default() -> Self {
         Self {
-            max_queue_size: 100,
+            max_queue_size: 8,
             num_worker_threads: 4,
+            inference_cores: Vec::new(),
             enable_batch_processing: true,
             batch_timeout_ms: 100,
             enable_data_fusion: false,
             fusion_algorithm: FusionAlgorithm::LateFusion,
+            fusion_interval_ms: 100,
             enable_tracking: true,
             tracker_type: TrackerType::DeepSort,
             max_track_age: 30,
@@ -380,6 +880,70 @@ impl Default for ProcessingConfig {
             frame_skip_interval: 0,
             enable_roi_processing: true,
             enable_multi_scale_processing: false,
+            association_radius_m: 1.0,
+            global_track_timeout_ms: 2000,
+            coast_frames: 5,
+            confidence_ema_alpha: 0.3,
+            class_smoothing: ClassSmoothing::MajorityVote { window: 5 },
+            facility_map_path: None,
+            proximity: ProximityConfig::default(),
+            frame_quality: FrameQualityConfig::default(),
+            detection_anomaly: DetectionAnomalyConfig::default(),
+            alert_rules: Vec::new(),
+            bandwidth_budget: BandwidthBudgetConfig::default(),
+        }
+    }
+}
+
+impl Default for BandwidthBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_total_kbps: 100_000,
+            check_interval_ms: 2000,
+            step_factor: 0.5,
+            min_framerate: 5,
+            min_width: 320,
+            restore_ratio: 0.7,
+        }
+    }
+}
+
+impl Default for ProximityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            human_class: "person".to_string(),
+            robot_class: "robot".to_string(),
+            warning_radius_m: 2.0,
+            critical_radius_m: 1.0,
+            min_robot_speed_mps: 0.1,
+        }
+    }
+}
+
+impl Default for FrameQualityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_mean_luma: 20.0,
+            max_mean_luma: 235.0,
+            min_luma_variance: 25.0,
+            min_sharpness: 10.0,
+            sustain_sec: 10,
+            sample_stride: 4,
+        }
+    }
+}
+
+impl Default for DetectionAnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            baseline_frames: 9000, // five minutes at 30fps
+            window_frames: 30,
+            sensitivity: 6.0,
+            min_std: 0.5,
         }
     }
 }
@@ -389,6 +953,16 @@ impl Default for MonitoringConfig {
         Self {
             enable_metrics: true,
             metrics_port: 9090,
+            metrics_mode: MetricsMode::Scrape,
+            pushgateway_url: None,
+            pushgateway_job: "aetherforge-perception".to_string(),
+            push_interval_sec: 15,
+            enable_control_api: true,
+            control_bind_address: "0.0.0.0".to_string(),
+            control_port: 9091,
+            enable_scoring_api: false,
+            scoring_models_dir: PathBuf::from("/var/lib/aetherforge/models"),
+            scoring_token: None,
             health_check_interval_sec: 30,
             performance_metrics_interval_sec: 5,
             enable_alerting: false,
@@ -401,6 +975,18 @@ impl Default for MonitoringConfig {
             enable_latency_tracking: true,
             enable_throughput_monitoring: true,
             alert_thresholds: AlertThresholds::default(),
+            debug_overlay: DebugOverlayConfig::default(),
+        }
+    }
+}
+
+impl Default for DebugOverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            every_n_frames: 15,
+            jpeg_quality: 80,
+            font_path: None,
         }
     }
 }
@@ -0,0 +1,490 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::{
+    config::{AlertRule, CameraCalibration, CameraConfig, DistortionCoefficients, Extrinsics, InferenceConfig, Intrinsics, PerceptionConfig},
+    error::{PerceptionError, Result},
+    processing::alert_rules,
+};
+
+// Fields a running node picks up without a restart. A pushed config that
+// changes anything else is rejected as a whole.
+const HOT_RELOADABLE: [&str; 8] = [
+    "inference.confidence_threshold",
+    "inference.nms_threshold",
+    "inference.class_nms_thresholds",
+    "inference.nms_strategy",
+    "inference.soft_nms_sigma",
+    "inference.min_box_size",
+    "inference.class_min_box_sizes",
+    "processing.alert_rules",
+];
+
+// The operator platform's /nodes/{id}/config response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigAssignment {
+    pub version: i64,
+    pub config: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApplyStatus {
+    Applied,
+    Rejected, // changes fields that need a restart
+    Invalid,  // doesn't parse or validate
+}
+
+// Posted back to /nodes/{id}/config/status
+#[derive(Debug, Serialize)]
+pub struct ConfigReport {
+    pub version: i64,
+    pub status: ApplyStatus,
+    pub message: Option<String>,
+    pub restart_required: Vec<String>,
+}
+
+// Polls the operator platform for this node's assigned config and applies
+// the hot-reloadable part of it to the running engine
+pub struct ConfigSync {
+    client: reqwest::Client,
+    operator_url: String,
+    current: Mutex<PerceptionConfig>,
+    inference: Arc<RwLock<InferenceConfig>>, // the engine's live config
+    alert_rules: Option<Arc<RwLock<Vec<AlertRule>>>>, // the rule engine's live rules, when one runs
+    cameras_assigned: bool, // cameras come from /nodes/{id}/cameras, not the pushed config
+    last_version: Mutex<Option<i64>>, // last assignment reported on, applied or not
+}
+
+impl ConfigSync {
+    pub fn new(config: PerceptionConfig, inference: Arc<RwLock<InferenceConfig>>) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| PerceptionError::ConfigError(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            operator_url: config.config_sync.operator_url.trim_end_matches('/').to_string(),
+            current: Mutex::new(config),
+            inference,
+            alert_rules: None,
+            cameras_assigned: false,
+            last_version: Mutex::new(None),
+        })
+    }
+
+    pub fn with_alert_rules(mut self, rules: Arc<RwLock<Vec<AlertRule>>>) -> Self {
+        self.alert_rules = Some(rules);
+        self
+    }
+
+    // The running cameras were assigned on the operator platform, so the
+    // pushed config's camera list is ignored rather than diffed
+    pub fn with_assigned_cameras(mut self) -> Self {
+        self.cameras_assigned = true;
+        self
+    }
+
+    pub async fn run(self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.poll_once().await {
+                warn!("Config sync failed: {}", e);
+            }
+        }
+    }
+
+    // Fetches the assignment and, if it's new, applies it and reports the
+    // outcome. Returns None when nothing is assigned or nothing changed.
+    pub async fn poll_once(&self) -> Result<Option<ConfigReport>> {
+        let node_id = self.current.lock().unwrap().node_id.clone();
+        let url = format!("{}/nodes/{}/config", self.operator_url, node_id);
+
+        let response = self.client.get(&url).send().await
+            .map_err(|e| PerceptionError::ConfigError(format!("GET {}: {}", url, e)))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let assignment: ConfigAssignment = response.error_for_status()
+            .map_err(|e| PerceptionError::ConfigError(format!("GET {}: {}", url, e)))?
+            .json()
+            .await
+            .map_err(|e| PerceptionError::ConfigError(format!("GET {}: {}", url, e)))?;
+
+        if *self.last_version.lock().unwrap() == Some(assignment.version) {
+            return Ok(None);
+        }
+
+        let report = self.apply(&assignment);
+        match report.status {
+            ApplyStatus::Applied => info!("Applied config version {}", report.version),
+            _ => warn!("Config version {} not applied: {}", report.version, report.message.as_deref().unwrap_or("")),
+        }
+
+        let status_url = format!("{}/status", url);
+        self.client.post(&status_url).json(&report).send().await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| PerceptionError::ConfigError(format!("POST {}: {}", status_url, e)))?;
+
+        *self.last_version.lock().unwrap() = Some(assignment.version);
+        Ok(Some(report))
+    }
+
+    pub fn apply(&self, assignment: &ConfigAssignment) -> ConfigReport {
+        let report = |status, message: String, restart_required| ConfigReport {
+            version: assignment.version,
+            status,
+            message: Some(message),
+            restart_required,
+        };
+
+        let desired = match self.parse(&assignment.config) {
+            Ok(desired) => desired,
+            Err(e) => return report(ApplyStatus::Invalid, e.to_string(), Vec::new()),
+        };
+
+        let mut current = self.current.lock().unwrap();
+        let changed = match (serde_json::to_value(&*current), serde_json::to_value(&desired)) {
+            (Ok(from), Ok(to)) => changed_fields(&from, &to),
+            (Err(e), _) | (_, Err(e)) => return report(ApplyStatus::Invalid, e.to_string(), Vec::new()),
+        };
+
+        let restart_required: Vec<String> = changed.iter().filter(|field| !is_hot_reloadable(field)).cloned().collect();
+        if !restart_required.is_empty() {
+            let message = format!("Restart required to change {}", restart_required.join(", "));
+            return report(ApplyStatus::Rejected, message, restart_required);
+        }
+
+        {
+            let mut live = self.inference.write().unwrap();
+            live.confidence_threshold = desired.inference.confidence_threshold;
+            live.nms_threshold = desired.inference.nms_threshold;
+            live.class_nms_thresholds = desired.inference.class_nms_thresholds.clone();
+            live.nms_strategy = desired.inference.nms_strategy;
+            live.soft_nms_sigma = desired.inference.soft_nms_sigma;
+            live.min_box_size = desired.inference.min_box_size;
+            live.class_min_box_sizes = desired.inference.class_min_box_sizes.clone();
+        }
+        if let Some(rules) = &self.alert_rules {
+            *rules.write().unwrap() = desired.processing.alert_rules.clone();
+        }
+        *current = desired;
+
+        let message = match changed.is_empty() {
+            true => "No changes".to_string(),
+            false => format!("Applied {}", changed.join(", ")),
+        };
+        report(ApplyStatus::Applied, message, Vec::new())
+    }
+
+    // A push may be partial; it's merged over the running config, then
+    // resolved the same way load_config resolves the file, so only real
+    // changes show up in the diff
+    fn parse(&self, value: &serde_json::Value) -> Result<PerceptionConfig> {
+        let (node_id, cameras, mut merged) = {
+            let current = self.current.lock().unwrap();
+            let merged = serde_json::to_value(&*current).map_err(|e| PerceptionError::ConfigError(e.to_string()))?;
+            (current.node_id.clone(), current.cameras.clone(), merged)
+        };
+        merge(&mut merged, value);
+
+        let mut desired: PerceptionConfig = serde_json::from_value(merged)
+            .map_err(|e| PerceptionError::ConfigError(format!("Invalid config: {}", e)))?;
+        desired.resolve_secrets()?;
+        desired.reconcile_threads();
+        if self.cameras_assigned {
+            desired.cameras = cameras;
+        }
+
+        if desired.node_id != node_id {
+            return Err(PerceptionError::ConfigError(format!("Config is for node '{}', not '{}'", desired.node_id, node_id)));
+        }
+        validate_thresholds(&desired.inference)?;
+        alert_rules::validate(&desired.processing.alert_rules)?;
+        Ok(desired)
+    }
+}
+
+// The fields of an operator platform camera the node runs on
+#[derive(Debug, Deserialize)]
+struct AssignedCamera {
+    device_id: String,
+    name: String,
+    stream_url: String,
+    rtsp_url: Option<String>,
+    zone: Option<String>,
+    fps: Option<f32>,
+    resolution_width: Option<i32>,
+    resolution_height: Option<i32>,
+    intrinsics: Option<serde_json::Value>,
+    extrinsics: Option<serde_json::Value>,
+}
+
+// Intrinsics as the platform stores them, distortion alongside
+#[derive(Debug, Deserialize)]
+struct StoredIntrinsics {
+    #[serde(flatten)]
+    intrinsics: Intrinsics,
+    distortion: Option<DistortionCoefficients>,
+}
+
+// Cameras the operator platform assigned to this node. Empty if none are
+// assigned, in which case the node keeps the cameras from its own config.
+pub async fn fetch_assigned_cameras(config: &PerceptionConfig) -> Result<Vec<CameraConfig>> {
+    let url = format!("{}/nodes/{}/cameras", config.config_sync.operator_url.trim_end_matches('/'), config.node_id);
+    let cameras: Vec<AssignedCamera> = reqwest::Client::new()
+        .get(&url)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| PerceptionError::ConfigError(format!("GET {}: {}", url, e)))?
+        .json()
+        .await
+        .map_err(|e| PerceptionError::ConfigError(format!("GET {}: {}", url, e)))?;
+
+    Ok(cameras.into_iter().map(camera_config).collect())
+}
+
+fn camera_config(camera: AssignedCamera) -> CameraConfig {
+    let defaults = CameraConfig::default();
+    CameraConfig {
+        calibration: calibration(&camera),
+        id: camera.device_id,
+        name: camera.name,
+        source: camera.stream_url,
+        width: camera.resolution_width.map_or(defaults.width, |w| w as u32),
+        height: camera.resolution_height.map_or(defaults.height, |h| h as u32),
+        framerate: camera.fps.map_or(defaults.framerate, |fps| fps.round() as u32),
+        rtsp_url: camera.rtsp_url,
+        zone: camera.zone,
+        ..defaults
+    }
+}
+
+// None unless both halves are present and parse; an uncalibrated camera
+// still captures, it just can't be projected into the world
+fn calibration(camera: &AssignedCamera) -> Option<CameraCalibration> {
+    let (intrinsics, extrinsics) = (camera.intrinsics.clone()?, camera.extrinsics.clone()?);
+    match (serde_json::from_value::<StoredIntrinsics>(intrinsics), serde_json::from_value::<Extrinsics>(extrinsics)) {
+        (Ok(stored), Ok(extrinsics)) => Some(CameraCalibration {
+            intrinsics: stored.intrinsics,
+            extrinsics,
+            distortion: stored.distortion.unwrap_or(DistortionCoefficients { k1: 0.0, k2: 0.0, p1: 0.0, p2: 0.0, k3: 0.0 }),
+        }),
+        (Err(e), _) | (_, Err(e)) => {
+            warn!("Ignoring unreadable calibration for camera {}: {}", camera.device_id, e);
+            None
+        }
+    }
+}
+
+fn validate_thresholds(inference: &InferenceConfig) -> Result<()> {
+    let thresholds = [("confidence_threshold", inference.confidence_threshold), ("nms_threshold", inference.nms_threshold)]
+        .into_iter()
+        .chain(inference.class_nms_thresholds.values().map(|t| ("class_nms_thresholds", *t)));
+
+    for (name, value) in thresholds {
+        if !(0.0..=1.0).contains(&value) {
+            return Err(PerceptionError::ConfigError(format!("inference.{} must be within 0..=1, got {}", name, value)));
+        }
+    }
+    if inference.soft_nms_sigma <= 0.0 {
+        return Err(PerceptionError::ConfigError("inference.soft_nms_sigma must be positive".to_string()));
+    }
+    Ok(())
+}
+
+// Objects merge key by key; anything else in `patch` replaces what's there
+fn merge(base: &mut serde_json::Value, patch: &serde_json::Value) {
+    match (base.as_object_mut(), patch.as_object()) {
+        (Some(base), Some(patch)) => {
+            for (key, value) in patch {
+                merge(base.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        _ => *base = patch.clone(),
+    }
+}
+
+fn is_hot_reloadable(field: &str) -> bool {
+    HOT_RELOADABLE.iter().any(|hot| field == *hot || field.starts_with(&format!("{}.", hot)))
+}
+
+// Dotted paths of every leaf that differs between two configs
+fn changed_fields(from: &serde_json::Value, to: &serde_json::Value) -> Vec<String> {
+    fn walk(path: &str, from: &serde_json::Value, to: &serde_json::Value, out: &mut Vec<String>) {
+        match (from.as_object(), to.as_object()) {
+            (Some(from), Some(to)) => {
+                let mut keys: Vec<&String> = from.keys().chain(to.keys()).collect();
+                keys.sort();
+                keys.dedup();
+                for key in keys {
+                    let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    let null = serde_json::Value::Null;
+                    walk(&child, from.get(key).unwrap_or(&null), to.get(key).unwrap_or(&null), out);
+                }
+            }
+            _ if from != to => out.push(path.to_string()),
+            _ => {}
+        }
+    }
+
+    let mut out = Vec::new();
+    walk("", from, to, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::get, Json, Router};
+
+    fn sync_for(config: &PerceptionConfig) -> (ConfigSync, Arc<RwLock<InferenceConfig>>) {
+        let live = Arc::new(RwLock::new(config.inference.clone()));
+        (ConfigSync::new(config.clone(), live.clone()).unwrap(), live)
+    }
+
+    fn node_config() -> PerceptionConfig {
+        let mut config = PerceptionConfig::default();
+        config.reconcile_threads();
+        config
+    }
+
+    // Stand-in for the operator platform's node endpoints, serving
+    // `assignment` and collecting the reports posted back
+    fn serve_assignment(listener: tokio::net::TcpListener, assignment: serde_json::Value) -> Arc<Mutex<Vec<serde_json::Value>>> {
+        let reports: Arc<Mutex<Vec<serde_json::Value>>> = Arc::default();
+
+        let app = Router::new()
+            .route("/nodes/:id/config", get(move || async move { Json(assignment) }))
+            .route(
+                "/nodes/:id/config/status",
+                axum::routing::post(|State(reports): State<Arc<Mutex<Vec<serde_json::Value>>>>, Json(report): Json<serde_json::Value>| async move {
+                    reports.lock().unwrap().push(report);
+                }),
+            )
+            .with_state(reports.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        reports
+    }
+
+    #[tokio::test]
+    async fn test_node_fetches_updated_threshold_and_reports_applied() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut current = node_config();
+        current.config_sync.operator_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let mut desired = current.clone();
+        desired.inference.confidence_threshold = 0.7;
+        let reports = serve_assignment(listener, serde_json::json!({ "version": 3, "config": desired }));
+
+        let (sync, live) = sync_for(&current);
+        let report = sync.poll_once().await.unwrap().unwrap();
+
+        assert_eq!(report.status, ApplyStatus::Applied);
+        assert_eq!(live.read().unwrap().confidence_threshold, 0.7);
+        let reports = reports.lock().unwrap().clone();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0]["version"], 3);
+        assert_eq!(reports[0]["status"], "applied");
+
+        // Same version again is not re-applied or re-reported
+        assert!(sync.poll_once().await.unwrap().is_none());
+    }
+
+    // What the operator platform stores for a push that only sets a threshold
+    #[tokio::test]
+    async fn test_threshold_only_push_is_applied() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut current = node_config();
+        current.config_sync.operator_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let partial = serde_json::json!({ "inference": { "confidence_threshold": 0.65 } });
+        let reports = serve_assignment(listener, serde_json::json!({ "version": 4, "config": partial }));
+
+        let (sync, live) = sync_for(&current);
+        let report = sync.poll_once().await.unwrap().unwrap();
+
+        assert_eq!(report.status, ApplyStatus::Applied, "{:?}", report.message);
+        assert_eq!(report.message.as_deref(), Some("Applied inference.confidence_threshold"));
+        assert_eq!(live.read().unwrap().confidence_threshold, 0.65);
+        assert_eq!(live.read().unwrap().nms_threshold, current.inference.nms_threshold);
+        assert_eq!(reports.lock().unwrap()[0]["status"], "applied");
+    }
+
+    #[test]
+    fn test_pushed_cameras_are_ignored_when_assigned() {
+        let mut running = node_config();
+        running.cameras = vec![CameraConfig { id: "dock-1".to_string(), ..CameraConfig::default() }];
+        let (sync, live) = sync_for(&running);
+        let sync = sync.with_assigned_cameras();
+
+        // A full config from the node's YAML, camera list and all
+        let mut desired = node_config();
+        desired.inference.confidence_threshold = 0.8;
+        let report = sync.apply(&ConfigAssignment { version: 1, config: serde_json::to_value(&desired).unwrap() });
+
+        assert_eq!(report.status, ApplyStatus::Applied, "{:?}", report.message);
+        assert_eq!(live.read().unwrap().confidence_threshold, 0.8);
+        assert_eq!(sync.current.lock().unwrap().cameras[0].id, "dock-1");
+    }
+
+    #[test]
+    fn test_restart_required_fields_are_rejected() {
+        let (sync, live) = sync_for(&node_config());
+        let mut desired = node_config();
+        desired.inference.confidence_threshold = 0.9;
+        desired.inference.input_width = 1280;
+
+        let report = sync.apply(&ConfigAssignment { version: 1, config: serde_json::to_value(&desired).unwrap() });
+
+        assert_eq!(report.status, ApplyStatus::Rejected);
+        assert_eq!(report.restart_required, vec!["inference.input_width".to_string()]);
+        assert_eq!(live.read().unwrap().confidence_threshold, node_config().inference.confidence_threshold);
+    }
+
+    #[test]
+    fn test_assigned_camera_becomes_camera_config_with_calibration() {
+        let camera: AssignedCamera = serde_json::from_value(serde_json::json!({
+            "id": "5b0f6c8e-8d2a-4b3e-9c1a-2f7d3e4a5b6c",
+            "device_id": "dock-1",
+            "name": "Dock 1",
+            "location": "North wall",
+            "stream_url": "rtspsrc location=rtsp://dock-1/stream ! decodebin",
+            "rtsp_url": "rtsp://dock-1/stream",
+            "zone": "loading",
+            "fps": 15.0,
+            "resolution_width": 1920,
+            "resolution_height": 1080,
+            "intrinsics": { "fx": 1000.0, "fy": 1001.0, "cx": 960.0, "cy": 540.0 },
+            "extrinsics": { "rotation": [0.0, 0.1, 0.0], "translation": [1.0, 2.0, 3.0] },
+        }))
+        .unwrap();
+
+        let config = camera_config(camera);
+
+        assert_eq!(config.id, "dock-1");
+        assert_eq!((config.width, config.height, config.framerate), (1920, 1080, 15));
+        assert_eq!(config.zone.as_deref(), Some("loading"));
+        let calibration = config.calibration.unwrap();
+        assert_eq!(calibration.intrinsics.fy, 1001.0);
+        assert_eq!(calibration.extrinsics.translation, [1.0, 2.0, 3.0]);
+        assert_eq!(calibration.distortion.k1, 0.0);
+    }
+
+    #[test]
+    fn test_out_of_range_threshold_is_invalid() {
+        let (sync, _) = sync_for(&node_config());
+        let mut desired = node_config();
+        desired.inference.nms_threshold = 1.5;
+
+        let report = sync.apply(&ConfigAssignment { version: 1, config: serde_json::to_value(&desired).unwrap() });
+
+        assert_eq!(report.status, ApplyStatus::Invalid);
+    }
+}
@@ -0,0 +1,189 @@
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::{
+    error::{PerceptionError, Result},
+    inference::{InferenceMetricsReport, InferenceStats},
+    messaging::{DeferredPublisher, MultiProtocolPublisher},
+    processing::camera_warmup::CameraWarmup,
+    scoring::{ImageScorer, ScoreRequest},
+    utils::overlay::DebugOverlay,
+};
+
+// Largest image POST /score accepts
+const MAX_SCORE_IMAGE_BYTES: usize = 16 * 1024 * 1024;
+
+// State shared by the node's control endpoints
+#[derive(Clone)]
+pub struct ControlState {
+    pub inference_stats: Arc<InferenceStats>,
+    pub debug_overlay: Option<Arc<DebugOverlay>>, // set when the debug overlay is enabled
+    pub messaging: Option<Arc<DeferredPublisher<MultiProtocolPublisher>>>,
+    pub scorer: Option<Arc<ImageScorer>>, // set when on-demand scoring is enabled
+    pub scoring_token: Option<String>, // bearer token /score requires; without one it refuses everyone
+    pub camera_warmup: Option<Arc<CameraWarmup>>,
+}
+
+pub fn router(state: ControlState) -> Router {
+    Router::new()
+        .route("/metrics/inference", get(inference_metrics))
+        .route("/debug/frames/:camera_id", get(latest_debug_frame))
+        .route("/health/messaging", get(messaging_health))
+        .route("/health/cameras", get(camera_health))
+        .route("/score", post(score_image).layer(DefaultBodyLimit::max(MAX_SCORE_IMAGE_BYTES)))
+        .with_state(state)
+}
+
+pub async fn start_control_server(addr: String, state: ControlState) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    info!("Control API listening on {}", addr);
+
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+async fn inference_metrics(State(state): State<ControlState>) -> Json<InferenceMetricsReport> {
+    Json(state.inference_stats.report())
+}
+
+// Publisher connection status, last successful publish and re-send backlog
+async fn messaging_health(State(state): State<ControlState>) -> impl IntoResponse {
+    match &state.messaging {
+        Some(messaging) => Json(messaging.health().await).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+// Per-camera warm-up progress; inference sees no frames from a camera
+// until it reports complete
+async fn camera_health(State(state): State<ControlState>) -> impl IntoResponse {
+    match &state.camera_warmup {
+        Some(warmup) => Json(warmup.status()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+// Latest annotated frame for a camera, as a JPEG
+async fn latest_debug_frame(State(state): State<ControlState>, Path(camera_id): Path<String>) -> impl IntoResponse {
+    match state.debug_overlay.as_ref().and_then(|overlay| overlay.latest_jpeg(&camera_id)) {
+        Some(jpeg) => ([(header::CONTENT_TYPE, "image/jpeg")], jpeg).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+// Detections for an uploaded image, from the requested model or the node's
+// own; the body is the encoded image
+async fn score_image(
+    State(state): State<ControlState>,
+    headers: HeaderMap,
+    Query(request): Query<ScoreRequest>,
+    image: Bytes,
+) -> impl IntoResponse {
+    let presented = headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match (presented, &state.scoring_token) {
+        (Some(presented), Some(token)) if tokens_match(presented, token) => {}
+        _ => return StatusCode::UNAUTHORIZED.into_response(),
+    }
+    let Some(scorer) = &state.scorer else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match scorer.score(&image, request).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e @ (PerceptionError::ProcessingError(_) | PerceptionError::ConfigError(_))) => {
+            (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+        }
+        Err(e) => {
+            warn!("On-demand scoring failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+// Compares every byte, so the time taken doesn't reveal how much matched
+fn tokens_match(presented: &str, token: &str) -> bool {
+    presented.len() == token.len() && presented.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_inference_metrics_report_latency_after_frames() {
+        let stats = Arc::new(InferenceStats::new(4));
+
+        // Two batches as the engine would record them
+        stats.record_batch("detection", 2, 12.5);
+        stats.record_frame("cam-1", 2, 14.0);
+        stats.record_frame("cam-2", 2, 15.0);
+        stats.record_batch("detection", 1, 8.0);
+        stats.record_frame("cam-1", 1, 9.0);
+        stats.set_queue_depth(3);
+        stats.set_model_memory("detection", 12_000_000);
+
+        let app = router(ControlState { inference_stats: stats, debug_overlay: None, messaging: None, scorer: None, scoring_token: None, camera_warmup: None });
+        let response = app
+            .oneshot(Request::builder().uri("/metrics/inference").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(report["queue_depth"], 3);
+        assert_eq!(report["max_batch_size"], 4);
+
+        assert_eq!(report["model_memory_usage"]["detection"], 12_000_000);
+
+        let model = &report["models"]["detection"];
+        assert_eq!(model["frames_processed"], 3);
+        assert!(model["avg_latency_ms"].as_f64().unwrap() > 0.0);
+        assert!((model["avg_batch_size"].as_f64().unwrap() - 1.5).abs() < 1e-6);
+
+        let camera = &report["cameras"]["cam-1"];
+        assert_eq!(camera["frames_processed"], 2);
+        assert!(camera["last_latency_ms"].as_f64().unwrap() > 0.0);
+        assert!(report["cameras"]["cam-2"]["avg_latency_ms"].as_f64().unwrap() > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_score_requires_the_token_and_caps_the_upload() {
+        let app = router(ControlState {
+            inference_stats: Arc::new(InferenceStats::new(1)),
+            debug_overlay: None,
+            messaging: None,
+            scorer: None,
+            scoring_token: Some("node-secret".to_string()),
+            camera_warmup: None,
+        });
+        let score = |token: Option<&str>, body: Vec<u8>| {
+            let mut request = Request::builder().method("POST").uri("/score");
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            app.clone().oneshot(request.body(Body::from(body)).unwrap())
+        };
+
+        assert_eq!(score(None, vec![0; 16]).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(score(Some("node-secreT"), vec![0; 16]).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        // Past the token, this node just has no scorer
+        assert_eq!(score(Some("node-secret"), vec![0; 16]).await.unwrap().status(), StatusCode::NOT_FOUND);
+        let oversized = vec![0; MAX_SCORE_IMAGE_BYTES + 1];
+        assert_eq!(score(Some("node-secret"), oversized).await.unwrap().status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
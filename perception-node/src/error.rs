@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -45,6 +46,13 @@ impl From<zmq::Error> for PerceptionError {
     }
 }
 
+#[cfg(feature = "kafka")]
+impl From<rdkafka::error::KafkaError> for PerceptionError {
+    fn from(error: rdkafka::error::KafkaError) -> Self {
+        PerceptionError::MessagingError(format!("Kafka: {}", error))
+    }
+}
+
 impl From<gstreamer::Error> for PerceptionError {
     fn from(error: gstreamer::Error) -> Self {
         PerceptionError::CameraError(error.to_string())
@@ -63,4 +71,98 @@ impl From<tokio::time::error::Elapsed> for PerceptionError {
     }
 }
 
-pub type Result<T> = std::result::Result<T, PerceptionError>;
\ No newline at end of file
+// Stable machine-readable error codes; the messages behind them are not
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    Camera,
+    Inference,
+    Messaging,
+    Config,
+    Processing,
+    Io,
+    Serialization,
+    ResourceExhausted,
+    Timeout,
+    Unknown,
+}
+
+impl PerceptionError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PerceptionError::CameraError(_) => ErrorCode::Camera,
+            PerceptionError::InferenceError(_) => ErrorCode::Inference,
+            PerceptionError::MessagingError(_) => ErrorCode::Messaging,
+            PerceptionError::ConfigError(_) => ErrorCode::Config,
+            PerceptionError::ProcessingError(_) => ErrorCode::Processing,
+            PerceptionError::IoError(_) => ErrorCode::Io,
+            PerceptionError::SerializationError(_) => ErrorCode::Serialization,
+            PerceptionError::ResourceExhausted(_) => ErrorCode::ResourceExhausted,
+            PerceptionError::Timeout(_) => ErrorCode::Timeout,
+            PerceptionError::Unknown(_) => ErrorCode::Unknown,
+        }
+    }
+
+    // Whether the same operation may succeed if tried again later. Bad config,
+    // unserializable data and model failures fail the same way every time.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            PerceptionError::CameraError(_)
+            | PerceptionError::MessagingError(_)
+            | PerceptionError::ResourceExhausted(_)
+            | PerceptionError::Timeout(_) => true,
+            PerceptionError::IoError(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::NotConnected
+                    | std::io::ErrorKind::BrokenPipe
+            ),
+            PerceptionError::InferenceError(_)
+            | PerceptionError::ConfigError(_)
+            | PerceptionError::ProcessingError(_)
+            | PerceptionError::SerializationError(_)
+            | PerceptionError::Unknown(_) => false,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, PerceptionError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_variants_map_to_codes_and_retryability() {
+        let cases = [
+            (PerceptionError::CameraError("no signal".into()), ErrorCode::Camera, true),
+            (PerceptionError::InferenceError("bad shape".into()), ErrorCode::Inference, false),
+            (PerceptionError::MessagingError("broker down".into()), ErrorCode::Messaging, true),
+            (PerceptionError::ConfigError("missing field".into()), ErrorCode::Config, false),
+            (PerceptionError::ProcessingError("bad frame".into()), ErrorCode::Processing, false),
+            (PerceptionError::IoError(io::Error::from(io::ErrorKind::TimedOut)), ErrorCode::Io, true),
+            (PerceptionError::IoError(io::Error::from(io::ErrorKind::PermissionDenied)), ErrorCode::Io, false),
+            (PerceptionError::SerializationError("NaN".into()), ErrorCode::Serialization, false),
+            (PerceptionError::ResourceExhausted("queue full".into()), ErrorCode::ResourceExhausted, true),
+            (PerceptionError::Timeout("publish".into()), ErrorCode::Timeout, true),
+            (PerceptionError::Unknown("?".into()), ErrorCode::Unknown, false),
+        ];
+
+        for (error, code, retryable) in cases {
+            assert_eq!(error.code(), code, "{}", error);
+            assert_eq!(error.is_retryable(), retryable, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_codes_serialize_stably() {
+        assert_eq!(serde_json::to_value(ErrorCode::ResourceExhausted).unwrap(), "RESOURCE_EXHAUSTED");
+        assert_eq!(serde_json::to_value(ErrorCode::Io).unwrap(), "IO");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,84 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use sysinfo::{CpuExt, System, SystemExt};
+use tracing::warn;
+
+use crate::{
+    error::{PerceptionError, Result},
+    messaging::{ConnectionStatus, DeferredPublisher, MultiProtocolPublisher, NodeStatus},
+};
+
+// Body of POST /nodes/{id}/heartbeat; the operator platform reads `status`
+// and keeps the rest as the node's last known health
+#[derive(Debug, Serialize)]
+pub struct Heartbeat {
+    pub node_id: String,
+    pub status: NodeStatus,
+    pub cpu_usage: f32,
+    pub memory_usage: f32,
+    pub messaging_status: ConnectionStatus,
+    pub buffered_frames: usize,
+    pub timestamp: u64,
+}
+
+pub async fn run(
+    operator_url: String,
+    node_id: String,
+    interval: Duration,
+    messaging: Arc<DeferredPublisher<MultiProtocolPublisher>>,
+) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| PerceptionError::ConfigError(e.to_string()))?;
+    let url = format!("{}/nodes/{}/heartbeat", operator_url.trim_end_matches('/'), node_id);
+    let mut system = System::new();
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        system.refresh_cpu();
+        system.refresh_memory();
+        let health = messaging.health().await;
+        let heartbeat = Heartbeat {
+            node_id: node_id.clone(),
+            status: health.node_status,
+            cpu_usage: system.global_cpu_info().cpu_usage(),
+            memory_usage: system.used_memory() as f32 / system.total_memory().max(1) as f32 * 100.0,
+            messaging_status: health.status,
+            buffered_frames: health.buffered_frames,
+            timestamp: aetherforge_common::utils::current_timestamp_ms(),
+        };
+
+        // A missed heartbeat only matters if they keep failing, which the
+        // platform notices on its own
+        if let Err(e) = client.post(&url).json(&heartbeat).send().await.and_then(|r| r.error_for_status()) {
+            warn!("Heartbeat to {} failed: {}", url, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_carries_status_the_platform_reads() {
+        let heartbeat = Heartbeat {
+            node_id: "node-1".to_string(),
+            status: ConnectionStatus::Degraded.node_status(),
+            cpu_usage: 12.5,
+            memory_usage: 40.0,
+            messaging_status: ConnectionStatus::Degraded,
+            buffered_frames: 7,
+            timestamp: 1,
+        };
+
+        let json = serde_json::to_value(&heartbeat).unwrap();
+
+        assert_eq!(json["status"], "Degraded");
+        assert_eq!(json["buffered_frames"], 7);
+    }
+}
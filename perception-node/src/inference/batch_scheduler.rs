@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::config::{CameraConfig, InferenceConfig};
+use aetherforge_common::CameraFrame;
+
+struct PendingFrame {
+    frame: CameraFrame,
+    priority: u8,
+    enqueued_at: Instant,
+}
+
+// Frames waiting for inference. Batches are taken highest camera priority
+// first, oldest first within a priority, so a safety-critical camera isn't
+// stuck behind a backlog of others when the GPU is saturated. Waiting raises
+// a frame's priority by one level per `priority_aging_ms`, which bounds how
+// long low-priority cameras can be starved.
+pub struct BatchScheduler {
+    max_batch_size: usize,
+    aging: Option<Duration>,
+    priorities: HashMap<String, u8>,
+    pending: Vec<PendingFrame>,
+}
+
+impl BatchScheduler {
+    pub fn new(config: &InferenceConfig) -> Self {
+        Self {
+            max_batch_size: config.max_batch_size.max(1),
+            aging: (config.priority_aging_ms > 0).then(|| Duration::from_millis(config.priority_aging_ms)),
+            priorities: HashMap::new(),
+            pending: Vec::with_capacity(config.max_batch_size),
+        }
+    }
+
+    // Cameras not listed keep priority 0
+    pub fn set_camera_priorities(&mut self, cameras: &[CameraConfig]) {
+        self.priorities = cameras.iter().map(|camera| (camera.id.clone(), camera.priority)).collect();
+    }
+
+    pub fn push(&mut self, frame: CameraFrame, enqueued_at: Instant) {
+        let priority = self.priorities.get(&frame.camera_id).copied().unwrap_or(0);
+        self.pending.push(PendingFrame { frame, priority, enqueued_at });
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.pending.len() >= self.max_batch_size
+    }
+
+    pub fn oldest(&self) -> Option<Instant> {
+        self.pending.iter().map(|pending| pending.enqueued_at).min()
+    }
+
+    // Removes and returns up to `max_batch_size` frames in processing order,
+    // each with when it was queued
+    pub fn next_batch(&mut self, now: Instant) -> Vec<(CameraFrame, Instant)> {
+        // Stable, so equal priorities keep arrival order
+        let aging = self.aging;
+        self.pending.sort_by_key(|pending| std::cmp::Reverse(effective_priority(pending, aging, now)));
+        let taken = self.pending.len().min(self.max_batch_size);
+        self.pending
+            .drain(..taken)
+            .map(|pending| (pending.frame, pending.enqueued_at))
+            .collect()
+    }
+}
+
+fn effective_priority(pending: &PendingFrame, aging: Option<Duration>, now: Instant) -> u64 {
+    let aged = aging.map_or(0, |aging| {
+        (now.saturating_duration_since(pending.enqueued_at).as_millis() / aging.as_millis()) as u64
+    });
+    pending.priority as u64 + aged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(camera_id: &str, sequence_num: u64) -> CameraFrame {
+        CameraFrame {
+            camera_id: camera_id.to_string(),
+            data: vec![0; 4 * 4 * 3],
+            width: 4,
+            height: 4,
+            format: "RGB".to_string(),
+            timestamp: 1_700_000_000_000 + sequence_num * 33,
+            sequence_num,
+        }
+    }
+
+    fn scheduler(priority_aging_ms: u64) -> BatchScheduler {
+        let config = InferenceConfig { max_batch_size: 2, priority_aging_ms, ..InferenceConfig::default() };
+        let mut scheduler = BatchScheduler::new(&config);
+        let crossing = CameraConfig { id: "crossing".to_string(), priority: 5, ..CameraConfig::default() };
+        let storage = CameraConfig { id: "storage".to_string(), priority: 0, ..CameraConfig::default() };
+        scheduler.set_camera_priorities(&[crossing, storage]);
+        scheduler
+    }
+
+    fn cameras(batch: &[(CameraFrame, Instant)]) -> Vec<(&str, u64)> {
+        batch.iter().map(|(frame, _)| (frame.camera_id.as_str(), frame.sequence_num)).collect()
+    }
+
+    #[test]
+    fn test_high_priority_frame_jumps_queued_low_priority_frames() {
+        let mut scheduler = scheduler(0);
+        let start = Instant::now();
+
+        // A backlog of storage frames, then a pedestrian crossing frame
+        for seq in 0..4 {
+            scheduler.push(frame("storage", seq), start + Duration::from_millis(seq));
+        }
+        scheduler.push(frame("crossing", 10), start + Duration::from_millis(10));
+        assert!(scheduler.is_full());
+
+        let now = start + Duration::from_millis(20);
+        assert_eq!(cameras(&scheduler.next_batch(now)), vec![("crossing", 10), ("storage", 0)]);
+        assert_eq!(cameras(&scheduler.next_batch(now)), vec![("storage", 1), ("storage", 2)]);
+        assert_eq!(cameras(&scheduler.next_batch(now)), vec![("storage", 3)]);
+        assert!(scheduler.is_empty() && scheduler.next_batch(now).is_empty());
+    }
+
+    #[test]
+    fn test_waiting_frames_age_past_fresh_high_priority_ones() {
+        let mut scheduler = scheduler(50);
+        let start = Instant::now();
+
+        // The storage frame has waited 300ms, six levels' worth, by the time
+        // the crossing camera floods the queue
+        scheduler.push(frame("storage", 0), start);
+        let now = start + Duration::from_millis(300);
+        for seq in 1..=4 {
+            scheduler.push(frame("crossing", seq), now);
+        }
+
+        assert_eq!(scheduler.oldest(), Some(start));
+        assert_eq!(cameras(&scheduler.next_batch(now)), vec![("storage", 0), ("crossing", 1)]);
+        assert_eq!(scheduler.len(), 3);
+    }
+}
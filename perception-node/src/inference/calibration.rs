@@ -0,0 +1,74 @@
+use crate::config::{ConfidenceCalibration, InferenceConfig};
+
+// Keeps the logit finite for confidences of exactly 0 or 1
+const EPSILON: f32 = 1e-7;
+
+impl ConfidenceCalibration {
+    pub fn apply(&self, confidence: f32) -> f32 {
+        let p = confidence.clamp(EPSILON, 1.0 - EPSILON);
+        let logit = (p / (1.0 - p)).ln();
+        let scaled = match *self {
+            ConfidenceCalibration::Temperature { temperature } => logit / temperature.max(EPSILON),
+            ConfidenceCalibration::Platt { a, b } => a * logit + b,
+        };
+        1.0 / (1.0 + (-scaled).exp())
+    }
+}
+
+// The calibration fitted for the configured model, if any. Keyed by model
+// version so a newly deployed model never inherits its predecessor's.
+pub fn calibrator(config: &InferenceConfig) -> impl Fn(f32) -> f32 {
+    let calibration = config.confidence_calibration.get(&config.model_version).copied();
+    move |confidence| calibration.map_or(confidence, |c| c.apply(confidence))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputFormat;
+    use crate::inference::decode::{decode, ModelOutputs};
+    use ndarray::Array3;
+
+    #[test]
+    fn test_temperature_is_monotonic_and_shifts_threshold() {
+        let softened = ConfidenceCalibration::Temperature { temperature: 2.0 };
+        let raw: Vec<f32> = (0..=100).map(|i| i as f32 / 100.0).collect();
+        let calibrated: Vec<f32> = raw.iter().map(|p| softened.apply(*p)).collect();
+
+        assert!(calibrated.windows(2).all(|w| w[0] < w[1]));
+        assert!((softened.apply(0.5) - 0.5).abs() < 1e-6);
+        // Overconfident scores come down, underconfident ones up
+        assert!(softened.apply(0.9) < 0.9 && softened.apply(0.2) > 0.2);
+        assert!((softened.apply(0.9) - 0.75).abs() < 1e-3); // logit ln 9 halved is ln 3
+
+        // Three anchors scoring 0.95, 0.8 and 0.6 for class 0
+        let mut output = Array3::<f32>::zeros((1, 4 + 1, 3));
+        for (j, score) in [0.95, 0.8, 0.6].into_iter().enumerate() {
+            for channel in 0..4 {
+                output[[0, channel, j]] = 0.25;
+            }
+            output[[0, 4, j]] = score;
+        }
+        let outputs = ModelOutputs::new(vec![("output0".to_string(), output.into_dyn())]);
+        let mut config = InferenceConfig {
+            model_version: "2.1.0".to_string(),
+            confidence_threshold: 0.7,
+            output_format: OutputFormat::YoloV8,
+            class_names: vec!["person".to_string()],
+            ..InferenceConfig::default()
+        };
+
+        let uncalibrated = decode(&outputs, 0, &config).unwrap();
+        assert_eq!(uncalibrated.len(), 2);
+
+        // The same threshold now only passes the first: 0.8 calibrates to about 0.67
+        config.confidence_calibration.insert("2.1.0".to_string(), softened);
+        let confidences: Vec<f32> = decode(&outputs, 0, &config).unwrap().iter().map(|c| c.confidence).collect();
+        assert_eq!(confidences.len(), 1);
+        assert!((confidences[0] - softened.apply(0.95)).abs() < 1e-6);
+
+        // Parameters fitted for another version are ignored
+        config.model_version = "2.2.0".to_string();
+        assert_eq!(decode(&outputs, 0, &config).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,465 @@
+use ndarray::{ArrayD, ArrayViewD, Axis};
+use std::ops::RangeInclusive;
+
+use super::calibration;
+use crate::{
+    config::{InferenceConfig, OutputFormat},
+    error::{PerceptionError, Result},
+};
+use aetherforge_common::{BBox, OrientedBBox};
+
+// A model's output tensors for one batch, keyed by output name in session order
+pub struct ModelOutputs {
+    tensors: Vec<(String, ArrayD<f32>)>,
+}
+
+impl ModelOutputs {
+    pub fn new(tensors: Vec<(String, ArrayD<f32>)>) -> Self {
+        Self { tensors }
+    }
+
+    pub fn shapes(&self) -> Vec<(String, Vec<Option<usize>>)> {
+        self.tensors
+            .iter()
+            .map(|(name, tensor)| (name.clone(), tensor.shape().iter().map(|d| Some(*d)).collect()))
+            .collect()
+    }
+
+    fn first(&self) -> Result<&ArrayD<f32>> {
+        self.tensors
+            .first()
+            .map(|(_, tensor)| tensor)
+            .ok_or_else(|| PerceptionError::InferenceError("Model produced no outputs".to_string()))
+    }
+
+    fn named(&self, name: &str) -> Result<&ArrayD<f32>> {
+        self.tensors
+            .iter()
+            .find(|(output, _)| output == name)
+            .map(|(_, tensor)| tensor)
+            .ok_or_else(|| PerceptionError::InferenceError(format!("Model has no output named {}", name)))
+    }
+}
+
+// One detection above the confidence threshold, boxed in input-tensor pixels
+#[derive(Debug, Clone, Copy)]
+pub struct Candidate {
+    pub bbox: BBox, // the envelope of `oriented` when the model predicts angles
+    pub class_id: usize,
+    pub confidence: f32,
+    pub oriented: Option<OrientedBBox>,
+}
+
+// Which candidates are kept. By default the calibrated product of
+// objectness and class score must reach `confidence_threshold`. With either
+// split threshold configured, each raw score is held to its own instead, so
+// a clear object of uncertain class (a half-hidden person) isn't dropped.
+#[derive(Debug, Clone, Copy)]
+enum ScoreGate {
+    Combined(f32),
+    Split { objectness: f32, class_score: f32 },
+}
+
+impl ScoreGate {
+    fn new(config: &InferenceConfig) -> Self {
+        match (config.objectness_threshold, config.class_score_threshold) {
+            (None, None) => ScoreGate::Combined(config.confidence_threshold),
+            (objectness, class_score) => ScoreGate::Split {
+                objectness: objectness.unwrap_or(config.confidence_threshold),
+                class_score: class_score.unwrap_or(config.confidence_threshold),
+            },
+        }
+    }
+
+    // Early rejection before class scores are looked at; a product never
+    // exceeds its objectness, and calibration keeps order
+    fn objectness_passes(self, objectness: f32, calibrate: impl Fn(f32) -> f32) -> bool {
+        match self {
+            ScoreGate::Combined(threshold) => calibrate(objectness) >= threshold,
+            ScoreGate::Split { objectness: threshold, .. } => objectness >= threshold,
+        }
+    }
+
+    // `confidence` is the calibrated product
+    fn passes(self, objectness: f32, class_score: f32, confidence: f32) -> bool {
+        match self {
+            ScoreGate::Combined(threshold) => confidence >= threshold,
+            ScoreGate::Split { objectness: min_objectness, class_score: min_class_score } => {
+                objectness >= min_objectness && class_score >= min_class_score
+            }
+        }
+    }
+}
+
+// What a format needs of one output: its rank and how many values each
+// anchor has along `values_axis`
+struct ExpectedOutput<'a> {
+    name: Option<&'a str>, // the first output when unnamed
+    layout: &'static str,
+    rank: usize,
+    values_axis: usize,
+    values: RangeInclusive<usize>,
+}
+
+fn expected_outputs(format: &OutputFormat) -> Vec<ExpectedOutput<'_>> {
+    let first = |layout, values_axis, min_values| ExpectedOutput {
+        name: None,
+        layout,
+        rank: 3,
+        values_axis,
+        values: min_values..=usize::MAX,
+    };
+    match format {
+        OutputFormat::YoloV5 => vec![first("[batch, anchors, 5 + classes]", 2, 6)],
+        OutputFormat::YoloV8 => vec![first("[batch, 4 + classes, anchors]", 1, 5)],
+        OutputFormat::YoloV8Obb => vec![first("[batch, 4 + classes + 1, anchors]", 1, 6)],
+        OutputFormat::SeparateHeads { boxes, scores, classes } => vec![
+            ExpectedOutput { name: Some(boxes), layout: "[batch, anchors, 4]", rank: 3, values_axis: 2, values: 4..=4 },
+            ExpectedOutput { name: Some(scores), layout: "[batch, anchors]", rank: 2, values_axis: 1, values: 1..=usize::MAX },
+            ExpectedOutput { name: Some(classes), layout: "[batch, anchors]", rank: 2, values_axis: 1, values: 1..=usize::MAX },
+        ],
+    }
+}
+
+// Checks output shapes against `format` before anything is indexed, with
+// None for dimensions only known at run time. Used both on the shapes a
+// model declares when it's loaded and on each inference's actual outputs.
+pub fn check_output_shapes(shapes: &[(String, Vec<Option<usize>>)], format: &OutputFormat) -> Result<()> {
+    for expected in expected_outputs(format) {
+        let (name, shape) = match expected.name {
+            Some(name) => shapes.iter().find(|(output, _)| output == name),
+            None => shapes.first(),
+        }
+        .ok_or_else(|| match expected.name {
+            Some(name) => PerceptionError::InferenceError(format!("Model has no output named {}", name)),
+            None => PerceptionError::InferenceError("Model produced no outputs".to_string()),
+        })?;
+
+        let values_fit = shape.len() == expected.rank
+            && shape[expected.values_axis].is_none_or(|values| expected.values.contains(&values));
+        if !values_fit {
+            let shape: Vec<String> = shape.iter().map(|d| d.map_or("?".to_string(), |d| d.to_string())).collect();
+            return Err(PerceptionError::InferenceError(format!(
+                "Model output {} has shape [{}], expected {} for output format {:?}",
+                name,
+                shape.join(", "),
+                expected.layout,
+                format
+            )));
+        }
+    }
+    Ok(())
+}
+
+// Decodes batch item `batch_index` according to `config.output_format`.
+// Box coordinates are normalized to the model input in every format.
+// Confidences are calibrated before the threshold is applied.
+pub fn decode(outputs: &ModelOutputs, batch_index: usize, config: &InferenceConfig) -> Result<Vec<Candidate>> {
+    check_output_shapes(&outputs.shapes(), &config.output_format)?;
+
+    let candidates = match &config.output_format {
+        OutputFormat::YoloV5 => {
+            // [batch, N, 5 + classes]: cx, cy, w, h, objectness, class scores
+            let rows = item(outputs.first()?, batch_index, 3)?;
+            decode_rows(rows, config, true, false)
+        }
+        OutputFormat::YoloV8 => {
+            // [batch, 4 + classes, N]: no objectness, anchors along the last axis
+            let rows = item(outputs.first()?, batch_index, 3)?.reversed_axes();
+            decode_rows(rows, config, false, false)
+        }
+        OutputFormat::YoloV8Obb => {
+            // [batch, 4 + classes + 1, N]: as YoloV8 with the angle after the class scores
+            let rows = item(outputs.first()?, batch_index, 3)?.reversed_axes();
+            decode_rows(rows, config, false, true)
+        }
+        OutputFormat::SeparateHeads { boxes, scores, classes } => {
+            // boxes [batch, N, 4] as x1, y1, x2, y2; scores and classes [batch, N]
+            let boxes = item(outputs.named(boxes)?, batch_index, 3)?;
+            let scores = item(outputs.named(scores)?, batch_index, 2)?;
+            let classes = item(outputs.named(classes)?, batch_index, 2)?;
+            let calibrate = calibration::calibrator(config);
+            let gate = ScoreGate::new(config);
+
+            // The single score stands in for the class score
+            boxes
+                .outer_iter()
+                .zip(scores.iter().zip(classes.iter()))
+                .filter_map(|(b, (score, class))| {
+                    let confidence = calibrate(*score);
+                    gate.passes(1.0, *score, confidence).then(|| Candidate {
+                        bbox: scale_to_input(b[0], b[1], b[2], b[3], config),
+                        class_id: *class as usize,
+                        confidence,
+                        oriented: None,
+                    })
+                })
+                .collect()
+        }
+    };
+
+    Ok(candidates)
+}
+
+fn item<'a>(tensor: &'a ArrayD<f32>, batch_index: usize, ndim: usize) -> Result<ArrayViewD<'a, f32>> {
+    if tensor.ndim() != ndim || tensor.shape()[0] <= batch_index {
+        return Err(PerceptionError::InferenceError(format!(
+            "Unexpected output shape {:?} for batch item {}",
+            tensor.shape(),
+            batch_index
+        )));
+    }
+    Ok(tensor.index_axis(Axis(0), batch_index))
+}
+
+// Rows of cx, cy, w, h, [objectness,] class scores..., [angle]
+fn decode_rows(rows: ArrayViewD<f32>, config: &InferenceConfig, has_objectness: bool, has_angle: bool) -> Vec<Candidate> {
+    let class_offset = if has_objectness { 5 } else { 4 };
+    let trailing = if has_angle { 1 } else { 0 };
+    let calibrate = calibration::calibrator(config);
+    let gate = ScoreGate::new(config);
+
+    rows.outer_iter()
+        .filter_map(|row| {
+            let objectness = if has_objectness { row[4] } else { 1.0 };
+            if has_objectness && !gate.objectness_passes(objectness, &calibrate) {
+                return None;
+            }
+
+            let (class_id, class_score) = row
+                .iter()
+                .skip(class_offset)
+                .take(row.len().saturating_sub(class_offset + trailing))
+                .copied()
+                .enumerate()
+                .fold((0, 0.0), |best, (c, score)| if score > best.1 { (c, score) } else { best });
+
+            let confidence = calibrate(objectness * class_score);
+            if !gate.passes(objectness, class_score, confidence) {
+                return None;
+            }
+
+            let (cx, cy, w, h) = (row[0], row[1], row[2], row[3]);
+            if has_angle {
+                let oriented = scale_oriented_to_input(cx, cy, w, h, row[row.len() - 1], config);
+                return Some(Candidate { bbox: oriented.envelope(), class_id, confidence, oriented: Some(oriented) });
+            }
+            Some(Candidate {
+                bbox: scale_to_input(cx - w / 2.0, cy - h / 2.0, cx + w / 2.0, cy + h / 2.0, config),
+                class_id,
+                confidence,
+                oriented: None,
+            })
+        })
+        .collect()
+}
+
+fn scale_to_input(x1: f32, y1: f32, x2: f32, y2: f32, config: &InferenceConfig) -> BBox {
+    let (width, height) = (config.input_width as f32, config.input_height as f32);
+    BBox::new(x1 * width, y1 * height, x2 * width, y2 * height)
+}
+
+// Box sides are normalized like the axis they lie along when unrotated, so
+// the result is only a true rectangle for square inputs, which is how
+// oriented models are exported in practice
+fn scale_oriented_to_input(cx: f32, cy: f32, w: f32, h: f32, angle: f32, config: &InferenceConfig) -> OrientedBBox {
+    let (width, height) = (config.input_width as f32, config.input_height as f32);
+    OrientedBBox::new(cx * width, cy * height, w * width, h * height, angle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{Array2, Array3, IxDyn};
+
+    // (cx, cy, w, h, class, confidence), normalized
+    const OBJECTS: [(f32, f32, f32, f32, usize, f32); 2] = [
+        (0.25, 0.5, 0.1, 0.2, 1, 0.9),
+        (0.75, 0.25, 0.2, 0.1, 3, 0.6),
+    ];
+    const NUM_CLASSES: usize = 5;
+    const NUM_ANCHORS: usize = 6; // two real objects, the rest below threshold
+
+    fn config(output_format: OutputFormat) -> InferenceConfig {
+        InferenceConfig {
+            input_width: 640,
+            input_height: 480,
+            confidence_threshold: 0.5,
+            output_format,
+            ..InferenceConfig::default()
+        }
+    }
+
+    fn yolov5() -> ModelOutputs {
+        let mut output = Array3::<f32>::zeros((1, NUM_ANCHORS, 5 + NUM_CLASSES));
+        for (j, (cx, cy, w, h, class, confidence)) in OBJECTS.iter().enumerate() {
+            output[[0, j, 0]] = *cx;
+            output[[0, j, 1]] = *cy;
+            output[[0, j, 2]] = *w;
+            output[[0, j, 3]] = *h;
+            output[[0, j, 4]] = 1.0;
+            output[[0, j, 5 + class]] = *confidence;
+        }
+        output[[0, 4, 4]] = 0.3; // weak anchor
+        ModelOutputs::new(vec![("output0".to_string(), output.into_dyn())])
+    }
+
+    fn yolov8() -> ModelOutputs {
+        let mut output = Array3::<f32>::zeros((1, 4 + NUM_CLASSES, NUM_ANCHORS));
+        for (j, (cx, cy, w, h, class, confidence)) in OBJECTS.iter().enumerate() {
+            output[[0, 0, j]] = *cx;
+            output[[0, 1, j]] = *cy;
+            output[[0, 2, j]] = *w;
+            output[[0, 3, j]] = *h;
+            output[[0, 4 + class, j]] = *confidence;
+        }
+        output[[0, 4, 4]] = 0.3;
+        ModelOutputs::new(vec![("output0".to_string(), output.into_dyn())])
+    }
+
+    fn separate_heads() -> ModelOutputs {
+        let mut boxes = Array3::<f32>::zeros((1, NUM_ANCHORS, 4));
+        let mut scores = Array2::<f32>::zeros((1, NUM_ANCHORS));
+        let mut classes = Array2::<f32>::zeros((1, NUM_ANCHORS));
+        for (j, (cx, cy, w, h, class, confidence)) in OBJECTS.iter().enumerate() {
+            boxes[[0, j, 0]] = cx - w / 2.0;
+            boxes[[0, j, 1]] = cy - h / 2.0;
+            boxes[[0, j, 2]] = cx + w / 2.0;
+            boxes[[0, j, 3]] = cy + h / 2.0;
+            scores[[0, j]] = *confidence;
+            classes[[0, j]] = *class as f32;
+        }
+        scores[[0, 4]] = 0.3;
+
+        // Heads in an order unrelated to their roles, found by name
+        ModelOutputs::new(vec![
+            ("scores".to_string(), scores.into_dyn()),
+            ("labels".to_string(), classes.into_dyn()),
+            ("boxes".to_string(), boxes.into_dyn()),
+        ])
+    }
+
+    fn assert_matches_objects(candidates: &[Candidate]) {
+        assert_eq!(candidates.len(), OBJECTS.len());
+        for (candidate, (cx, cy, w, h, class, confidence)) in candidates.iter().zip(OBJECTS) {
+            assert_eq!(candidate.class_id, class);
+            assert!((candidate.confidence - confidence).abs() < 1e-6);
+            assert!((candidate.bbox.xmin - (cx - w / 2.0) * 640.0).abs() < 1e-3);
+            assert!((candidate.bbox.ymin - (cy - h / 2.0) * 480.0).abs() < 1e-3);
+            assert!((candidate.bbox.xmax - (cx + w / 2.0) * 640.0).abs() < 1e-3);
+            assert!((candidate.bbox.ymax - (cy + h / 2.0) * 480.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_all_formats_decode_identical_detections() {
+        let v5 = decode(&yolov5(), 0, &config(OutputFormat::YoloV5)).unwrap();
+        let v8 = decode(&yolov8(), 0, &config(OutputFormat::YoloV8)).unwrap();
+        let heads = decode(
+            &separate_heads(),
+            0,
+            &config(OutputFormat::SeparateHeads {
+                boxes: "boxes".to_string(),
+                scores: "scores".to_string(),
+                classes: "labels".to_string(),
+            }),
+        )
+        .unwrap();
+
+        assert_matches_objects(&v5);
+        assert_matches_objects(&v8);
+        assert_matches_objects(&heads);
+    }
+
+    #[test]
+    fn test_split_thresholds_keep_clear_object_of_uncertain_class() {
+        // A half-hidden person: surely an object, only 0.45 sure of the class
+        let mut output = Array3::<f32>::zeros((1, NUM_ANCHORS, 5 + NUM_CLASSES));
+        for (j, (objectness, class_score)) in [(0.95, 0.45), (0.3, 0.9), (0.9, 0.2)].into_iter().enumerate() {
+            output[[0, j, 0]] = 0.5;
+            output[[0, j, 1]] = 0.5;
+            output[[0, j, 2]] = 0.1;
+            output[[0, j, 3]] = 0.2;
+            output[[0, j, 4]] = objectness;
+            output[[0, j, 5]] = class_score;
+        }
+        let outputs = ModelOutputs::new(vec![("output0".to_string(), output.into_dyn())]);
+
+        // 0.95 * 0.45 is under the combined threshold
+        let combined = config(OutputFormat::YoloV5);
+        assert!(decode(&outputs, 0, &combined).unwrap().is_empty());
+
+        let split = InferenceConfig {
+            objectness_threshold: Some(0.8),
+            class_score_threshold: Some(0.4),
+            ..config(OutputFormat::YoloV5)
+        };
+        let kept = decode(&outputs, 0, &split).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert!((kept[0].confidence - 0.95 * 0.45).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_yolov8_obb_decodes_angle() {
+        // One pallet lying at 30 degrees, one weak anchor
+        let angle = std::f32::consts::FRAC_PI_6;
+        let mut output = Array3::<f32>::zeros((1, 4 + NUM_CLASSES + 1, NUM_ANCHORS));
+        for (channel, value) in [0.5, 0.5, 0.25, 0.125].into_iter().enumerate() {
+            output[[0, channel, 2]] = value;
+        }
+        output[[0, 4 + 2, 2]] = 0.8;
+        output[[0, 4 + NUM_CLASSES, 2]] = angle;
+        output[[0, 4, 5]] = 0.3;
+        let outputs = ModelOutputs::new(vec![("output0".to_string(), output.into_dyn())]);
+
+        let square = InferenceConfig { input_height: 640, ..config(OutputFormat::YoloV8Obb) };
+        let candidates = decode(&outputs, 0, &square).unwrap();
+
+        assert_eq!(candidates.len(), 1);
+        let candidate = candidates[0];
+        assert_eq!(candidate.class_id, 2);
+        assert!((candidate.confidence - 0.8).abs() < 1e-6);
+
+        // The angle channel is not mistaken for a class score
+        let oriented = candidate.oriented.unwrap();
+        assert_eq!((oriented.cx, oriented.cy, oriented.width, oriented.height), (320.0, 320.0, 160.0, 80.0));
+        assert!((oriented.angle - angle).abs() < 1e-6);
+
+        // The axis-aligned box is the rotated box's envelope
+        let half_width = 80.0 * angle.cos() + 40.0 * angle.sin();
+        assert!((candidate.bbox.xmin - (320.0 - half_width)).abs() < 1e-3);
+        assert!((candidate.bbox.xmax - (320.0 + half_width)).abs() < 1e-3);
+
+        // Plain YoloV8 models leave detections unrotated
+        assert!(decode(&yolov8(), 0, &config(OutputFormat::YoloV8)).unwrap().iter().all(|c| c.oriented.is_none()));
+    }
+
+    #[test]
+    fn test_missing_head_and_bad_shape_are_errors() {
+        let format = OutputFormat::SeparateHeads {
+            boxes: "boxes".to_string(),
+            scores: "confidences".to_string(),
+            classes: "labels".to_string(),
+        };
+        assert!(decode(&separate_heads(), 0, &config(format)).is_err());
+
+        let flat = ModelOutputs::new(vec![("output0".to_string(), ArrayD::zeros(IxDyn(&[1, 10])))]);
+        assert!(decode(&flat, 0, &config(OutputFormat::YoloV5)).is_err());
+        assert!(decode(&yolov5(), 1, &config(OutputFormat::YoloV5)).is_err());
+    }
+
+    #[test]
+    fn test_output_shape_mismatch_is_described() {
+        // Four values per anchor would index past the end looking for objectness
+        let narrow = ModelOutputs::new(vec![("output0".to_string(), ArrayD::zeros(IxDyn(&[1, NUM_ANCHORS, 4])))]);
+        let error = decode(&narrow, 0, &config(OutputFormat::YoloV5)).unwrap_err().to_string();
+        assert!(error.contains("output0 has shape [1, 6, 4], expected [batch, anchors, 5 + classes]"), "{}", error);
+
+        // As declared at load time, with dynamic batch and anchors
+        let declared = [("output0".to_string(), vec![None, Some(4 + NUM_CLASSES), None])];
+        assert!(check_output_shapes(&declared, &OutputFormat::YoloV8).is_ok());
+        let flat = [("output0".to_string(), vec![None, Some(4 + NUM_CLASSES)])];
+        let error = check_output_shapes(&flat, &OutputFormat::YoloV8).unwrap_err().to_string();
+        assert!(error.contains("has shape [?, 9], expected [batch, 4 + classes, anchors]"), "{}", error);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,502 @@
+use async_trait::async_trait;
+use image::{codecs::png::PngEncoder, ImageEncoder};
+use ndarray::ArrayD;
+use ort::Session;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+
+use super::{InferencePool, OrtEngine};
+use crate::{
+    camera::multi_camera::MultiCameraManager,
+    config::{InferenceBackend, InferenceConfig, Int8CalibrationConfig, PerceptionConfig},
+    error::{PerceptionError, Result},
+    scoring,
+    utils::metrics::Metrics,
+};
+use aetherforge_common::CameraFrame;
+
+// TensorRT's header for a max-abs table; onnxruntime's TensorRT provider
+// reads the per-tensor scales below it as the native calibration table
+const CACHE_HEADER: &str = "TRT-8601-MinMaxCalibration";
+
+// ONNX protobuf field numbers: ModelProto.graph, GraphProto.node and
+// .output, NodeProto.output, ValueInfoProto.name
+const MODEL_GRAPH: u64 = 7;
+const GRAPH_NODE: u64 = 1;
+const GRAPH_OUTPUT: u64 = 12;
+const NODE_OUTPUT: u64 = 2;
+const VALUE_INFO_NAME: u64 = 1;
+
+// Captured frames and cache building report progress in steps of this
+const PROGRESS_STEPS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationProgress {
+    pub captured: usize,
+    pub target: usize,
+}
+
+impl CalibrationProgress {
+    fn is_step(&self) -> bool {
+        self.captured == self.target || self.captured.is_multiple_of((self.target / PROGRESS_STEPS).max(1))
+    }
+}
+
+// Stores live frames for INT8 calibration as lossless PNGs, at most one per
+// camera every `capture_interval_ms` of frame time. Frames already in the
+// directory count, so an interrupted capture picks up where it stopped.
+pub struct CalibrationCapture {
+    dir: PathBuf,
+    target: usize,
+    interval_ms: u64,
+    next_due: HashMap<String, u64>,
+    captured: usize,
+}
+
+impl CalibrationCapture {
+    pub fn new(config: &Int8CalibrationConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.frames_dir)?;
+        Ok(Self {
+            dir: config.frames_dir.clone(),
+            target: config.frames.max(1),
+            interval_ms: config.capture_interval_ms,
+            next_due: HashMap::new(),
+            captured: scoring::list_images(&config.frames_dir)?.len(),
+        })
+    }
+
+    pub fn progress(&self) -> CalibrationProgress {
+        CalibrationProgress { captured: self.captured.min(self.target), target: self.target }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.captured >= self.target
+    }
+
+    // Returns whether the frame was stored
+    pub fn offer(&mut self, frame: &CameraFrame) -> Result<bool> {
+        if self.is_complete() || self.next_due.get(&frame.camera_id).is_some_and(|due| frame.timestamp < *due) {
+            return Ok(false);
+        }
+        if frame.data.len() != (frame.width * frame.height * 3) as usize {
+            return Err(PerceptionError::ProcessingError(format!(
+                "Frame from {} is not {}x{} RGB",
+                frame.camera_id, frame.width, frame.height
+            )));
+        }
+
+        let mut png = Vec::new();
+        PngEncoder::new(&mut png)
+            .write_image(&frame.data, frame.width, frame.height, image::ColorType::Rgb8)
+            .map_err(|e| PerceptionError::ProcessingError(format!("PNG encoding failed: {}", e)))?;
+        std::fs::write(self.dir.join(format!("{}-{}.png", frame.camera_id, frame.timestamp)), png)?;
+
+        self.next_due.insert(frame.camera_id.clone(), frame.timestamp + self.interval_ms);
+        self.captured += 1;
+        Ok(true)
+    }
+}
+
+// The tensors of one frame calibration needs ranges for; the real node
+// uses the ORT engine, tests use a stub
+#[async_trait]
+pub trait TensorProbe {
+    async fn probe(&self, frame: &CameraFrame) -> Result<Vec<(String, ArrayD<f32>)>>;
+}
+
+// Runs frames through a copy of the detection model with every
+// intermediate activation exposed as an output, as onnxruntime's own
+// calibrator does, so TensorRT gets a range for each layer and not just the
+// model's inputs and outputs
+pub struct ActivationProbe {
+    engine: OrtEngine,
+    session: Session,
+}
+
+impl ActivationProbe {
+    pub fn new(engine: OrtEngine, config: &InferenceConfig) -> Result<Self> {
+        let (model, exposed) = expose_activations(&std::fs::read(&config.model_path)?)?;
+        let session = OrtEngine::session_builder(config)?
+            .with_model_from_memory(&model)
+            .map_err(|e| PerceptionError::InferenceError(format!("Failed to load model with exposed activations: {}", e)))?;
+        info!("INT8 calibration: exposed {} activations of {}", exposed, config.model_path.display());
+        Ok(Self { engine, session })
+    }
+}
+
+#[async_trait]
+impl TensorProbe for ActivationProbe {
+    async fn probe(&self, frame: &CameraFrame) -> Result<Vec<(String, ArrayD<f32>)>> {
+        self.engine.calibration_tensors(&self.session, frame).await
+    }
+}
+
+// Adds every node output that isn't already a graph output to the graph's
+// outputs, editing the serialized model in place so everything else in it
+// is kept byte for byte. The added outputs carry only a name; onnxruntime
+// infers their types. Returns the model and how many outputs were added.
+pub fn expose_activations(model: &[u8]) -> Result<(Vec<u8>, usize)> {
+    let mut out = Vec::with_capacity(model.len());
+    let mut exposed = None;
+    for field in wire_fields(model)? {
+        match field.payload {
+            Some(graph) if field.number == MODEL_GRAPH => {
+                let (graph, count) = expose_graph_activations(graph)?;
+                write_len_field(&mut out, MODEL_GRAPH, &graph);
+                exposed = Some(count);
+            }
+            _ => out.extend_from_slice(field.raw),
+        }
+    }
+
+    let exposed = exposed.ok_or_else(|| PerceptionError::InferenceError("Model has no graph".to_string()))?;
+    Ok((out, exposed))
+}
+
+fn expose_graph_activations(graph: &[u8]) -> Result<(Vec<u8>, usize)> {
+    let mut outputs = HashSet::new();
+    let mut activations = Vec::new();
+    for field in wire_fields(graph)? {
+        let (number, Some(message)) = (field.number, field.payload) else {
+            continue;
+        };
+        let wanted = match number {
+            GRAPH_NODE => NODE_OUTPUT,
+            GRAPH_OUTPUT => VALUE_INFO_NAME,
+            _ => continue,
+        };
+        let names = wire_fields(message)?
+            .into_iter()
+            .filter(|f| f.number == wanted)
+            .filter_map(|f| f.payload)
+            .map(|name| String::from_utf8_lossy(name).into_owned());
+        if number == GRAPH_NODE {
+            activations.extend(names);
+        } else {
+            outputs.extend(names);
+        }
+    }
+
+    // Repeated fields may be appended; outputs keep their order, the added
+    // ones after the model's own
+    let mut graph = graph.to_vec();
+    let mut exposed = 0;
+    for name in activations {
+        // Unused optional outputs have no name
+        if !name.is_empty() && outputs.insert(name.clone()) {
+            let mut value_info = Vec::new();
+            write_len_field(&mut value_info, VALUE_INFO_NAME, name.as_bytes());
+            write_len_field(&mut graph, GRAPH_OUTPUT, &value_info);
+            exposed += 1;
+        }
+    }
+    Ok((graph, exposed))
+}
+
+struct WireField<'a> {
+    number: u64,
+    payload: Option<&'a [u8]>, // length-delimited fields only
+    raw: &'a [u8],             // key included
+}
+
+fn wire_fields(bytes: &[u8]) -> Result<Vec<WireField<'_>>> {
+    let malformed = || PerceptionError::InferenceError("Malformed ONNX model".to_string());
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let start = pos;
+        let key = read_varint(bytes, &mut pos).ok_or_else(malformed)?;
+        let payload = match key & 7 {
+            0 => {
+                read_varint(bytes, &mut pos).ok_or_else(malformed)?;
+                None
+            }
+            1 | 5 => {
+                pos += if key & 7 == 1 { 8 } else { 4 };
+                None
+            }
+            2 => {
+                let len = read_varint(bytes, &mut pos).ok_or_else(malformed)? as usize;
+                let payload = bytes.get(pos..pos.checked_add(len).ok_or_else(malformed)?).ok_or_else(malformed)?;
+                pos += len;
+                Some(payload)
+            }
+            _ => return Err(malformed()),
+        };
+        let raw = bytes.get(start..pos).ok_or_else(malformed)?;
+        fields.push(WireField { number: key >> 3, payload, raw });
+    }
+    Ok(fields)
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_len_field(out: &mut Vec<u8>, number: u64, payload: &[u8]) {
+    write_varint(out, number << 3 | 2);
+    write_varint(out, payload.len() as u64);
+    out.extend_from_slice(payload);
+}
+
+// Largest magnitude seen per tensor, which with symmetric INT8 gives the
+// scale: max / 127
+#[derive(Debug, Default)]
+pub struct TensorRanges {
+    max_abs: BTreeMap<String, f32>,
+}
+
+impl TensorRanges {
+    pub fn observe(&mut self, name: &str, tensor: &ArrayD<f32>) {
+        let max = tensor.iter().filter(|v| v.is_finite()).fold(0.0f32, |max, v| max.max(v.abs()));
+        let entry = self.max_abs.entry(name.to_string()).or_insert(0.0);
+        *entry = entry.max(max);
+    }
+
+    pub fn scale(&self, name: &str) -> Option<f32> {
+        self.max_abs.get(name).map(|max| max / 127.0)
+    }
+}
+
+// One line per tensor: its name and its scale as the bits of an f32 in
+// hex. Tensors that never left zero have no usable scale and are left out;
+// TensorRT runs the layers around any tensor without one at higher
+// precision.
+pub fn write_cache(path: &Path, ranges: &TensorRanges) -> Result<()> {
+    let mut cache = format!("{}\n", CACHE_HEADER);
+    for (name, max) in &ranges.max_abs {
+        if *max > 0.0 {
+            cache.push_str(&format!("{}: {:08x}\n", name, (max / 127.0).to_bits()));
+        }
+    }
+
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, cache)?;
+    Ok(())
+}
+
+// Runs every captured frame through `probe` and writes the cache. Returns
+// the number of frames it was built from.
+pub async fn build_cache(probe: &impl TensorProbe, frames_dir: &Path, cache_path: &Path) -> Result<usize> {
+    let paths = scoring::list_images(frames_dir)?;
+    if paths.is_empty() {
+        return Err(PerceptionError::ConfigError(format!("No calibration frames in {}", frames_dir.display())));
+    }
+
+    let mut ranges = TensorRanges::default();
+    for (i, path) in paths.iter().enumerate() {
+        let frame = scoring::load_frame(path, i as u64, None)?;
+        for (name, tensor) in probe.probe(&frame).await? {
+            ranges.observe(&name, &tensor);
+        }
+
+        let progress = CalibrationProgress { captured: i + 1, target: paths.len() };
+        if progress.is_step() {
+            info!("INT8 calibration: ran {}/{} frames through the model", progress.captured, progress.target);
+        }
+    }
+
+    write_cache(cache_path, &ranges)?;
+    Ok(paths.len())
+}
+
+// Captures frames from the configured cameras until there are enough, then
+// builds the calibration cache on the CPU. Returns the cache path.
+pub async fn run(config: &PerceptionConfig) -> Result<PathBuf> {
+    let calibration = &config.inference.int8_calibration;
+    let mut capture = CalibrationCapture::new(calibration)?;
+    let metrics = Arc::new(Metrics::new());
+
+    if !capture.is_complete() {
+        let cameras = MultiCameraManager::new(config.cameras.clone(), metrics.clone()).await?;
+        let (tx, mut frames) = mpsc::channel(16);
+        for camera_id in cameras.list_cameras() {
+            let Some(mut receiver) = cameras.get_frame_receiver(&camera_id) else {
+                continue;
+            };
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                while let Some(frame) = receiver.recv().await {
+                    if tx.send(frame).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        cameras.start_all().await?;
+        info!("INT8 calibration: capturing {} frames into {}", capture.progress().target, calibration.frames_dir.display());
+        while let Some(frame) = frames.recv().await {
+            if !capture.offer(&frame)? {
+                continue;
+            }
+            let progress = capture.progress();
+            debug!("INT8 calibration frame {} from {}", progress.captured, frame.camera_id);
+            if progress.is_step() {
+                info!("INT8 calibration: captured {}/{} frames", progress.captured, progress.target);
+            }
+            if capture.is_complete() {
+                break;
+            }
+        }
+        cameras.stop_all().await?;
+
+        if !capture.is_complete() {
+            let progress = capture.progress();
+            return Err(PerceptionError::CameraError(format!(
+                "Cameras stopped after {}/{} calibration frames",
+                progress.captured, progress.target
+            )));
+        }
+    }
+
+    // INT8 can't be used before the cache exists, so the ranges come from
+    // the full-precision model
+    let mut inference = config.inference.clone();
+    inference.inference_backend = InferenceBackend::Cpu;
+    inference.enable_int8 = false;
+    inference.shadow_model_path = None;
+    let pool = Arc::new(InferencePool::new(&config.processing)?);
+    let engine = OrtEngine::new(&inference, pool, metrics).await?;
+    let probe = ActivationProbe::new(engine, &inference)?;
+
+    let frames = build_cache(&probe, &calibration.frames_dir, &calibration.cache_path).await?;
+    info!("INT8 calibration cache built from {} frames: {}", frames, calibration.cache_path.display());
+    Ok(calibration.cache_path.clone())
+}
+
+// Only TensorRT builds use the cache
+#[cfg(all(test, feature = "tensorrt"))]
+mod tests {
+    use super::*;
+    use ndarray::{ArrayD, IxDyn};
+
+    // The input as raw pixel values and one output whose largest magnitude
+    // is 2.54 on every frame
+    struct StubProbe;
+
+    #[async_trait]
+    impl TensorProbe for StubProbe {
+        async fn probe(&self, frame: &CameraFrame) -> Result<Vec<(String, ArrayD<f32>)>> {
+            let input = ArrayD::from_shape_vec(IxDyn(&[frame.data.len()]), frame.data.iter().map(|v| *v as f32 / 255.0).collect()).unwrap();
+            let output = ArrayD::from_shape_vec(IxDyn(&[3]), vec![0.5, -2.54, 1.0]).unwrap();
+            Ok(vec![("images".to_string(), input), ("output0".to_string(), output)])
+        }
+    }
+
+    fn frame(camera_id: &str, timestamp: u64, value: u8) -> CameraFrame {
+        CameraFrame {
+            camera_id: camera_id.to_string(),
+            data: vec![value; 4 * 4 * 3],
+            width: 4,
+            height: 4,
+            format: "RGB".to_string(),
+            timestamp,
+            sequence_num: timestamp,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_captures_frames_and_builds_calibration_cache() {
+        let dir = std::env::temp_dir().join(format!("aetherforge-int8-{}", aetherforge_common::utils::current_timestamp_ms()));
+        let config = Int8CalibrationConfig {
+            frames: 4,
+            capture_interval_ms: 1000,
+            frames_dir: dir.join("frames"),
+            cache_path: dir.join("calibration.cache"),
+        };
+        let mut capture = CalibrationCapture::new(&config).unwrap();
+
+        // Two cameras at 10fps for 3s; each is captured once a second until four are in
+        let mut captured = 0;
+        for t in (0..3000).step_by(100) {
+            captured += capture.offer(&frame("dock", t, 255)).unwrap() as usize;
+            captured += capture.offer(&frame("aisle", t, 64)).unwrap() as usize;
+        }
+        assert_eq!(captured, 4);
+        assert_eq!(capture.progress(), CalibrationProgress { captured: 4, target: 4 });
+        assert_eq!(scoring::list_images(&config.frames_dir).unwrap().len(), 4);
+
+        // A restarted capture counts what's already stored
+        assert!(CalibrationCapture::new(&config).unwrap().is_complete());
+
+        assert_eq!(build_cache(&StubProbe, &config.frames_dir, &config.cache_path).await.unwrap(), 4);
+        let cache = std::fs::read_to_string(&config.cache_path).unwrap();
+        let lines: Vec<&str> = cache.lines().collect();
+        assert_eq!(lines[0], CACHE_HEADER);
+        assert_eq!(lines[1], format!("images: {:08x}", (1.0f32 / 127.0).to_bits()));
+        assert_eq!(lines[2], format!("output0: {:08x}", (2.54f32 / 127.0).to_bits()));
+        assert_eq!(lines.len(), 3);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn graph_outputs(model: &[u8]) -> Vec<String> {
+        let graph = wire_fields(model).unwrap().into_iter().find(|f| f.number == MODEL_GRAPH).unwrap().payload.unwrap();
+        wire_fields(graph)
+            .unwrap()
+            .into_iter()
+            .filter(|f| f.number == GRAPH_OUTPUT)
+            .map(|f| {
+                let name = wire_fields(f.payload.unwrap()).unwrap()[0].payload.unwrap();
+                String::from_utf8(name.to_vec()).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_expose_activations_adds_every_node_output() {
+        // conv -> relu -> head, where only the head's output is a graph output
+        let node = |outputs: &[&str]| {
+            let mut node = Vec::new();
+            write_len_field(&mut node, 1, b"x"); // input
+            for output in outputs {
+                write_len_field(&mut node, NODE_OUTPUT, output.as_bytes());
+            }
+            node
+        };
+        let mut output0 = Vec::new();
+        write_len_field(&mut output0, VALUE_INFO_NAME, b"output0");
+        let mut graph = Vec::new();
+        for outputs in [&["conv1"][..], &["relu1", ""], &["output0"]] {
+            write_len_field(&mut graph, GRAPH_NODE, &node(outputs));
+        }
+        write_len_field(&mut graph, 2, b"detector");
+        write_len_field(&mut graph, GRAPH_OUTPUT, &output0);
+        let mut model = vec![0x08, 0x08]; // ir_version: 8
+        write_len_field(&mut model, MODEL_GRAPH, &graph);
+        model.extend([0x42, 0x02, 0x08, 0x11]); // opset_import { version: 17 }
+
+        let (augmented, exposed) = expose_activations(&model).unwrap();
+        assert_eq!(exposed, 2);
+        assert_eq!(graph_outputs(&augmented), vec!["output0", "conv1", "relu1"]);
+        // Everything else survives as it was
+        assert!(augmented.starts_with(&[0x08, 0x08]) && augmented.ends_with(&[0x42, 0x02, 0x08, 0x11]));
+        assert_eq!(expose_activations(&augmented).unwrap().1, 0);
+
+        assert!(expose_activations(&model[..model.len() - 3]).is_err());
+    }
+}
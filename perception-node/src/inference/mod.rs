@@ -1,3 +1,18 @@
+mod batch_scheduler;
+mod calibration;
+mod decode;
+pub mod int8_calibration;
+mod nms;
+mod normalization;
 mod ort_engine;
+pub mod preprocess;
+pub mod shadow;
+mod size_filter;
+pub mod stats;
+mod tensor_cache;
+pub mod worker_pool;
 
-pub use ort_engine::OrtEngine;
\ No newline at end of file
+pub use batch_scheduler::BatchScheduler;
+pub use ort_engine::{InferenceMetrics, OrtEngine};
+pub use stats::{InferenceMetricsReport, InferenceStats};
+pub use worker_pool::InferencePool;
\ No newline at end of file
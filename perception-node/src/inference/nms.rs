@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use crate::config::{InferenceConfig, NmsStrategy};
+use crate::utils::metrics::Metrics;
+use aetherforge_common::Detection;
+
+// Keeps the `max_detections_per_frame` most confident detections so a
+// pathological frame can't make NMS, which is quadratic per class, or the
+// published message arbitrarily large. Hitting the cap is counted per camera.
+pub fn cap_detections(mut detections: Vec<Detection>, config: &InferenceConfig, metrics: &Metrics, camera_id: &str) -> Vec<Detection> {
+    let cap = config.max_detections_per_frame;
+    if cap == 0 || detections.len() <= cap {
+        return detections;
+    }
+    
+    detections.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    detections.truncate(cap);
+    metrics.increment_detection_cap_hits(camera_id);
+    detections
+}
+
+// Non-maximum suppression applied independently per class. Each class uses
+// its entry in `class_nms_thresholds` if present, otherwise `nms_threshold`.
+// Soft strategies decay overlapping confidences instead of dropping boxes,
+// then discard anything that fell below `confidence_threshold`.
+pub fn apply_nms(detections: Vec<Detection>, config: &InferenceConfig) -> Vec<Detection> {
+    let mut by_class: HashMap<u32, Vec<Detection>> = HashMap::new();
+    for detection in detections {
+        by_class.entry(detection.class_id).or_default().push(detection);
+    }
+    
+    let mut kept = Vec::new();
+    for (_, mut class_detections) in by_class {
+        let threshold = class_threshold(&class_detections[0].class_label, config);
+        
+        match config.nms_strategy {
+            NmsStrategy::Standard => {
+                class_detections.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+                kept.extend(suppress(class_detections, threshold));
+            }
+            strategy => {
+                kept.extend(soft_suppress(class_detections, threshold, strategy, config));
+            }
+        }
+    }
+    
+    kept.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    kept
+}
+
+fn class_threshold(class_label: &str, config: &InferenceConfig) -> f32 {
+    config
+        .class_nms_thresholds
+        .get(class_label)
+        .copied()
+        .unwrap_or(config.nms_threshold)
+}
+
+// Expects detections sorted by descending confidence
+fn suppress(sorted: Vec<Detection>, iou_threshold: f32) -> Vec<Detection> {
+    let mut kept: Vec<Detection> = Vec::with_capacity(sorted.len());
+    
+    for candidate in sorted {
+        let overlaps = kept
+            .iter()
+            .any(|k| k.intersection_over_union(&candidate) > iou_threshold);
+        
+        if !overlaps {
+            kept.push(candidate);
+        }
+    }
+    
+    kept
+}
+
+fn soft_suppress(
+    mut remaining: Vec<Detection>,
+    iou_threshold: f32,
+    strategy: NmsStrategy,
+    config: &InferenceConfig,
+) -> Vec<Detection> {
+    let sigma = config.soft_nms_sigma.max(f32::EPSILON);
+    let mut kept = Vec::with_capacity(remaining.len());
+    
+    while !remaining.is_empty() {
+        // Scores change every round, so pick the current best each time
+        let best_index = remaining
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.confidence.total_cmp(&b.confidence))
+            .map(|(i, _)| i)
+            .unwrap();
+        let best = remaining.swap_remove(best_index);
+        
+        for detection in remaining.iter_mut() {
+            let iou = best.intersection_over_union(detection);
+            let decay = match strategy {
+                NmsStrategy::SoftLinear if iou > iou_threshold => 1.0 - iou,
+                NmsStrategy::SoftGaussian => (-(iou * iou) / sigma).exp(),
+                _ => 1.0,
+            };
+            detection.confidence *= decay;
+        }
+        
+        remaining.retain(|d| d.confidence >= config.confidence_threshold);
+        kept.push(best);
+    }
+    
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aetherforge_common::BBox;
+    
+    fn detection(class_id: u32, class_label: &str, xmin: f32, confidence: f32) -> Detection {
+        Detection {
+            bbox: BBox::new(xmin, 0.0, xmin + 100.0, 100.0),
+            confidence,
+            class_id,
+            class_label: class_label.to_string(),
+            tracker_id: None,
+            oriented: None,
+        }
+    }
+    
+    #[test]
+    fn test_pathological_frame_is_capped_before_nms() {
+        let config = InferenceConfig { max_detections_per_frame: 300, ..InferenceConfig::default() };
+        let metrics = Metrics::new();
+        
+        // 5000 boxes, as glare on a wet floor might produce
+        let flood: Vec<Detection> = (0..5000)
+            .map(|i| detection(0, "person", (i % 100) as f32 * 3.0, ((i * 7919) % 5000) as f32 / 5000.0))
+            .collect();
+        
+        let capped = cap_detections(flood, &config, &metrics, "cam-1");
+        
+        assert_eq!(capped.len(), 300);
+        assert!(capped.iter().all(|d| d.confidence >= 4700.0 / 5000.0));
+        assert!(metrics.encode().contains(r#"aetherforge_detection_cap_hits_total{camera_id="cam-1"} 1"#));
+        
+        // A normal frame passes through without counting
+        let normal = cap_detections(vec![detection(0, "person", 0.0, 0.9)], &config, &metrics, "cam-1");
+        assert_eq!(normal.len(), 1);
+        assert!(metrics.encode().contains(r#"aetherforge_detection_cap_hits_total{camera_id="cam-1"} 1"#));
+        assert!(apply_nms(capped, &config).len() <= 300);
+    }
+    
+    #[test]
+    fn test_per_class_nms_thresholds() {
+        let mut config = InferenceConfig::default();
+        config.nms_threshold = 0.5;
+        config.class_nms_thresholds.insert("person".to_string(), 0.8);
+        
+        // Each pair is shifted by 25px, giving IoU = 7500 / 12500 = 0.6
+        let detections = vec![
+            detection(0, "person", 0.0, 0.9),
+            detection(0, "person", 25.0, 0.8),
+            detection(3, "forklift", 500.0, 0.9),
+            detection(3, "forklift", 525.0, 0.8),
+        ];
+        
+        let kept = apply_nms(detections, &config);
+        
+        // IoU 0.6 is under the person threshold (0.8) but over the global one (0.5)
+        assert_eq!(kept.iter().filter(|d| d.class_label == "person").count(), 2);
+        assert_eq!(kept.iter().filter(|d| d.class_label == "forklift").count(), 1);
+    }
+    
+    #[test]
+    fn test_soft_nms_retains_more_overlapping_boxes() {
+        let mut config = InferenceConfig::default();
+        config.confidence_threshold = 0.1;
+        config.nms_threshold = 0.5;
+        
+        // Three people standing close together, IoU 0.6 between neighbours
+        let crowd = || vec![
+            detection(0, "person", 0.0, 0.95),
+            detection(0, "person", 25.0, 0.9),
+            detection(0, "person", 50.0, 0.85),
+        ];
+        
+        config.nms_strategy = NmsStrategy::Standard;
+        let standard = apply_nms(crowd(), &config);
+        
+        config.nms_strategy = NmsStrategy::SoftLinear;
+        let soft_linear = apply_nms(crowd(), &config);
+        
+        config.nms_strategy = NmsStrategy::SoftGaussian;
+        let soft_gaussian = apply_nms(crowd(), &config);
+        
+        assert_eq!(standard.len(), 2);
+        assert_eq!(soft_linear.len(), 3);
+        assert_eq!(soft_gaussian.len(), 3);
+        assert!(soft_linear.iter().all(|d| d.confidence >= config.confidence_threshold));
+        assert!(soft_gaussian.iter().all(|d| d.confidence >= config.confidence_threshold));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,183 @@
+use ndarray::Array4;
+use std::fmt;
+
+use super::preprocess;
+use crate::config::PreprocessingConfig;
+
+// The best-scoring normalization must reach this confidence before a
+// mismatch is reported; a synthetic probe image often excites nothing
+const MIN_PROBE_SCORE: f32 = 0.25;
+
+// and must beat the configured normalization by this factor
+const MISMATCH_RATIO: f32 = 2.0;
+
+// How much slack mean/std get when recognizing a convention
+const TOLERANCE: f32 = 0.02;
+
+const IMAGENET_MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+const IMAGENET_STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+// The input conventions detection models are commonly exported with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputRange {
+    ZeroToOne,
+    MinusOneToOne,
+    ZeroTo255,
+    ImageNet, // per-channel mean/std standardization
+    Other,
+}
+
+impl InputRange {
+    pub const CANDIDATES: [InputRange; 4] = [
+        InputRange::ZeroToOne,
+        InputRange::MinusOneToOne,
+        InputRange::ZeroTo255,
+        InputRange::ImageNet,
+    ];
+
+    // Recognizes the convention a configured mean/std produces
+    pub fn of(config: &PreprocessingConfig) -> InputRange {
+        let uniform = |mean: f32, std: f32| {
+            config.mean.iter().all(|m| close(*m, mean)) && config.std.iter().all(|s| close(*s, std))
+        };
+        let per_channel = |mean: [f32; 3], std: [f32; 3]| {
+            config.mean.iter().zip(mean).all(|(a, b)| close(*a, b))
+                && config.std.iter().zip(std).all(|(a, b)| close(*a, b))
+        };
+        let reversed = |values: [f32; 3]| [values[2], values[1], values[0]];
+
+        if uniform(0.0, 1.0) {
+            InputRange::ZeroToOne
+        } else if uniform(0.5, 0.5) {
+            InputRange::MinusOneToOne
+        } else if config.mean.iter().all(|m| close(*m, 0.0)) && config.std.iter().all(|s| close(*s * 255.0, 1.0)) {
+            InputRange::ZeroTo255
+        } else if per_channel(IMAGENET_MEAN, IMAGENET_STD)
+            || per_channel(reversed(IMAGENET_MEAN), reversed(IMAGENET_STD))
+        {
+            InputRange::ImageNet
+        } else {
+            InputRange::Other
+        }
+    }
+
+    // `base` with mean/std swapped for this convention's
+    pub fn preprocessing(self, base: &PreprocessingConfig) -> PreprocessingConfig {
+        let (mean, std) = match self {
+            InputRange::ZeroToOne | InputRange::Other => ([0.0; 3], [1.0; 3]),
+            InputRange::MinusOneToOne => ([0.5; 3], [0.5; 3]),
+            InputRange::ZeroTo255 => ([0.0; 3], [1.0 / 255.0; 3]),
+            InputRange::ImageNet => (IMAGENET_MEAN, IMAGENET_STD),
+        };
+        PreprocessingConfig { mean, std, ..base.clone() }
+    }
+}
+
+impl fmt::Display for InputRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            InputRange::ZeroToOne => "0..1",
+            InputRange::MinusOneToOne => "-1..1",
+            InputRange::ZeroTo255 => "0..255",
+            InputRange::ImageNet => "ImageNet mean/std",
+            InputRange::Other => "custom mean/std",
+        };
+        f.write_str(name)
+    }
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() <= TOLERANCE
+}
+
+// A synthetic scene for warmup: a grey floor with a gradient, a dark and a
+// bright block. Not a real object, but enough texture for a detector
+// to respond far more to the input range it was trained on.
+pub fn probe_tensor(width: u32, height: u32, config: &PreprocessingConfig) -> Array4<f32> {
+    let (width, height) = (width as usize, height as usize);
+    let mut tensor = Array4::<f32>::zeros((1, 3, height, width));
+
+    for y in 0..height {
+        for x in 0..width {
+            let in_block = |x0: usize, y0: usize| {
+                (x0 * width / 8..(x0 + 2) * width / 8).contains(&x) && (y0 * height / 8..(y0 + 4) * height / 8).contains(&y)
+            };
+            let value = if in_block(2, 2) {
+                30
+            } else if in_block(5, 3) {
+                220
+            } else {
+                (96 + 64 * y / height.max(1)) as u8
+            };
+            for c in 0..3 {
+                tensor[[0, c, y, x]] = preprocess::normalize(value, c, config);
+            }
+        }
+    }
+
+    tensor
+}
+
+// `scores` pairs each probed convention with the model's strongest
+// confidence on the probe image. Returns the warning to log when another
+// convention gets a clearly stronger response than the configured one.
+pub fn mismatch_warning(configured: InputRange, scores: &[(InputRange, f32)]) -> Option<String> {
+    let configured_score = scores.iter().find(|(range, _)| *range == configured).map(|(_, score)| *score)?;
+    let (best, best_score) = scores
+        .iter()
+        .filter(|(range, _)| *range != configured)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .copied()?;
+
+    if best_score < MIN_PROBE_SCORE || best_score < configured_score * MISMATCH_RATIO {
+        return None;
+    }
+
+    Some(format!(
+        "LIKELY INPUT NORMALIZATION MISMATCH: the model responds to {} input (max confidence {:.2}) \
+         far more than to the configured {} normalization ({:.2}). Check inference.preprocessing.mean/std \
+         against how the model was trained; a mismatch silently ruins accuracy.",
+        best, best_score, configured, configured_score
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::InferenceConfig;
+
+    #[test]
+    fn test_recognizes_common_conventions() {
+        let base = InferenceConfig::default().preprocessing;
+
+        for range in InputRange::CANDIDATES {
+            assert_eq!(InputRange::of(&range.preprocessing(&base)), range);
+        }
+        let custom = PreprocessingConfig { mean: [0.3, 0.3, 0.3], ..base.clone() };
+        assert_eq!(InputRange::of(&custom), InputRange::Other);
+
+        let tensor = probe_tensor(64, 48, &InputRange::ZeroTo255.preprocessing(&base));
+        assert_eq!(tensor.shape(), &[1, 3, 48, 64]);
+        assert!(tensor.iter().any(|v| *v > 200.0));
+    }
+
+    #[test]
+    fn test_mismatched_normalization_is_warned_about() {
+        // Configured for 0..1 but the model was trained on raw 0..255 pixels
+        let scores = [
+            (InputRange::ZeroToOne, 0.04),
+            (InputRange::MinusOneToOne, 0.06),
+            (InputRange::ZeroTo255, 0.81),
+            (InputRange::ImageNet, 0.10),
+        ];
+
+        let warning = mismatch_warning(InputRange::ZeroToOne, &scores).unwrap();
+        assert!(warning.contains("MISMATCH"));
+        assert!(warning.contains("0..255"));
+
+        // The configured convention winning, or nothing responding, is quiet
+        assert!(mismatch_warning(InputRange::ZeroTo255, &scores).is_none());
+        let flat = [(InputRange::ZeroToOne, 0.05), (InputRange::ZeroTo255, 0.12)];
+        assert!(mismatch_warning(InputRange::ZeroToOne, &flat).is_none());
+    }
+}
@@ -1,49 +1,51 @@
-use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 use dashmap::DashMap;
 use async_trait::async_trait;
 use ort::{Session, SessionBuilder, ExecutionProvider};
-use ndarray::{Array4, Axis};
-use tracing::{debug, error, info, instrument, warn};
+use ndarray::{Array4, ArrayD, Axis};
+use tracing::{debug, error, info, warn};
 
+use super::{decode::{self, ModelOutputs}, nms, normalization::{self, InputRange}, preprocess::{self, InputTransform, Roi}, shadow::{self, ShadowSampler}, size_filter, stats::InferenceStats, tensor_cache::TensorCache, worker_pool::InferencePool};
 use crate::{
     config::{InferenceConfig, InferenceBackend},
     error::{Result, PerceptionError},
     utils::metrics::Metrics,
     processing::fusion_engine::FusionResult,
 };
-use aetherforge_common::{CameraFrame, Detection, BBox, PerceptionFrame};
+use aetherforge_common::{CameraFrame, Detection, CoordinateSpace, PerceptionFrame};
 
 #[derive(Clone)]
 pub struct OrtEngine {
     sessions: Arc<DashMap<String, Session>>, // Multiple models by name
-    config: InferenceConfig,
+    config: Arc<RwLock<InferenceConfig>>, // thresholds can change at runtime, see config_sync
     metrics: Arc<Metrics>,
     current_model: String,
-    batch_processor: BatchProcessor,
-}
-
-pub struct BatchProcessor {
-    max_batch_size: usize,
-    batch_timeout: Duration,
-    pending_frames: Vec<(CameraFrame, Instant)>,
+    stats: Arc<InferenceStats>,
+    pool: Arc<InferencePool>,
+    tensor_cache: Arc<TensorCache>, // shared by the models run on one frame
+    shadow: Option<Arc<ShadowSampler>>, // set when a shadow model is loaded
 }
 
 impl OrtEngine {
-    pub async fn new(config: &InferenceConfig, metrics: Arc<Metrics>) -> Result<Self> {
+    pub async fn new(config: &InferenceConfig, pool: Arc<InferencePool>, metrics: Arc<Metrics>) -> Result<Self> {
         info!("Initializing ORT inference engine with config: {:?}", config);
         
         let mut sessions = DashMap::new();
+        let stats = Arc::new(InferenceStats::new(config.max_batch_size));
         
         // Load primary detection model
         let detection_session = Self::create_session(&config.model_path, config).await?;
+        decode::check_output_shapes(&Self::declared_output_shapes(&detection_session), &config.output_format)?;
         sessions.insert("detection".to_string(), detection_session);
+        stats.set_model_memory("detection", model_size(&config.model_path));
         
         // Load segmentation model if configured
         if let Some(seg_model_path) = &config.segmentation_model_path {
             let seg_config = config.clone(); // Would have different config in reality
             let seg_session = Self::create_session(seg_model_path, &seg_config).await?;
             sessions.insert("segmentation".to_string(), seg_session);
+            stats.set_model_memory("segmentation", model_size(seg_model_path));
         }
         
         // Load robot identification model if configured
@@ -51,24 +53,88 @@ impl OrtEngine {
             let robot_config = config.clone();
             let robot_session = Self::create_session(robot_model_path, &robot_config).await?;
             sessions.insert("robot_identification".to_string(), robot_session);
+            stats.set_model_memory("robot_identification", model_size(robot_model_path));
         }
         
-        let batch_processor = BatchProcessor {
-            max_batch_size: config.max_batch_size,
-            batch_timeout: Duration::from_millis(config.batch_timeout_ms),
-            pending_frames: Vec::with_capacity(config.max_batch_size),
+        // Candidate detection model evaluated alongside the primary
+        let shadow = match &config.shadow_model_path {
+            Some(shadow_model_path) => {
+                let shadow_session = Self::create_session(shadow_model_path, config).await?;
+                decode::check_output_shapes(&Self::declared_output_shapes(&shadow_session), &config.output_format)?;
+                sessions.insert("shadow".to_string(), shadow_session);
+                stats.set_model_memory("shadow", model_size(shadow_model_path));
+                info!("Shadow model {:?} runs on {:.0}% of frames", shadow_model_path, config.shadow_sample_rate * 100.0);
+                Some(Arc::new(ShadowSampler::new(config.shadow_sample_rate)))
+            }
+            None => None,
         };
         
-        Ok(Self {
+        let engine = Self {
             sessions: Arc::new(sessions),
-            config: config.clone(),
+            config: Arc::new(RwLock::new(config.clone())),
             metrics,
             current_model: "detection".to_string(),
-            batch_processor,
-        })
+            stats,
+            pool,
+            tensor_cache: Arc::new(TensorCache::new(config.preprocess_cache_size)),
+            shadow,
+        };
+        
+        if config.model_warmup {
+            engine.warm_up().await?;
+        }
+        
+        Ok(engine)
     }
     
-    async fn create_session(model_path: &std::path::Path, config: &InferenceConfig) -> Result<Session> {
+    // Runs the detection model on a synthetic frame so the first real frame
+    // doesn't pay for lazy initialization. The frame is also run under each
+    // common input convention; if the model clearly prefers another one than
+    // the configured mean/std, a mismatch is likely and we say so loudly.
+    async fn warm_up(&self) -> Result<()> {
+        let session = self.sessions.get("detection")
+            .ok_or_else(|| PerceptionError::InferenceError("Model not found".to_string()))?;
+        let mut config = self.config.read().unwrap().clone();
+        // Score the raw response, not what survives the threshold
+        config.confidence_threshold = 0.0;
+        
+        let configured = InputRange::of(&config.preprocessing);
+        let probes = std::iter::once((configured, config.preprocessing.clone()))
+            .chain(InputRange::CANDIDATES.into_iter()
+                .filter(|range| *range != configured)
+                .map(|range| (range, range.preprocessing(&config.preprocessing))));
+        
+        let warmup_start = Instant::now();
+        let mut scores = Vec::new();
+        for (range, preprocessing) in probes {
+            let input = normalization::probe_tensor(config.input_width, config.input_height, &preprocessing);
+            let outputs = self.run_inference(session.value(), &input).await?;
+            let outputs = Self::named_outputs(session.value(), outputs)?;
+            let score = decode::decode(&outputs, 0, &config)?
+                .iter()
+                .map(|candidate| candidate.confidence)
+                .fold(0.0, f32::max);
+            scores.push((range, score));
+        }
+        
+        info!("Model warmup finished in {:?}; probe confidences by input range: {:?}", warmup_start.elapsed(), scores);
+        if let Some(warning) = normalization::mismatch_warning(configured, &scores) {
+            warn!("{}", warning);
+        }
+        
+        Ok(())
+    }
+    
+    pub(crate) async fn create_session(model_path: &std::path::Path, config: &InferenceConfig) -> Result<Session> {
+        let session = Self::session_builder(config)?
+            .with_model_from_file(model_path)
+            .map_err(|e| PerceptionError::InferenceError(format!("Failed to load model: {}", e)))?;
+            
+        info!("Model loaded successfully: {}", model_path.display());
+        Ok(session)
+    }
+    
+    pub(crate) fn session_builder(config: &InferenceConfig) -> Result<SessionBuilder> {
         let mut session_builder = SessionBuilder::new()?;
         
         // Configure hardware acceleration based on backend
@@ -77,7 +143,7 @@ impl OrtEngine {
                 // Use CPU provider with optimizations
                 session_builder = session_builder
                     .with_optimization_level(ort::GraphOptimizationLevel::Level3)?
-                    .with_intra_threads(num_cpus::get() as i16)?;
+                    .with_intra_threads(Self::intra_op_threads(config) as i16)?;
             }
             InferenceBackend::Cuda => {
                 #[cfg(feature = "cuda")]
@@ -93,8 +159,17 @@ impl OrtEngine {
             InferenceBackend::TensorRT => {
                 #[cfg(feature = "tensorrt")]
                 {
+                    let mut tensorrt = ort::TensorRTExecutionProviderOptions::default();
+                    let cache_path = &config.int8_calibration.cache_path;
+                    if config.enable_int8 && !cache_path.exists() {
+                        warn!("INT8 requested but there is no calibration cache at {}; run --calibrate-int8 first", cache_path.display());
+                    } else if config.enable_int8 {
+                        tensorrt.int8_enable = true;
+                        tensorrt.int8_calibration_table_name = Some(cache_path.display().to_string());
+                        tensorrt.int8_use_native_calibration_table = true;
+                    }
                     session_builder = session_builder
-                        .with_execution_providers([ExecutionProvider::TensorRT(Default::default())])?;
+                        .with_execution_providers([ExecutionProvider::TensorRT(tensorrt)])?;
                 }
                 #[cfg(not(feature = "tensorrt"))]
                 {
@@ -114,165 +189,199 @@ impl OrtEngine {
             }
         }
         
-        let session = session_builder
-            .with_model_from_file(model_path)
-            .map_err(|e| PerceptionError::InferenceError(format!("Failed to load model: {}", e)))?;
-            
-        info!("Model loaded successfully: {}", model_path.display());
-        Ok(session)
+        Ok(session_builder)
     }
     
-    #[instrument(skip(self, frame), level = "debug")]
-    pub async fn process_frame(&mut self, frame: CameraFrame) -> Result<PerceptionFrame> {
-        let start_time = Instant::now();
-        
-        // Add to batch processor
-        self.batch_processor.pending_frames.push((frame, start_time));
-        
-        // Check if we should process the batch
-        if self.batch_processor.pending_frames.len() >= self.batch_processor.max_batch_size ||
-           start_time.duration_since(self.batch_processor.pending_frames.first().unwrap().1) 
-           >= self.batch_processor.batch_timeout 
-        {
-            return self.process_batch().await;
-        }
-        
-        // For single frame processing, we'll still process immediately
-        // In a real implementation, we might use a background task for batching
-        self.process_batch().await
+    // Output shapes as the model file declares them; dynamic dimensions
+    // (batch, usually anchors) are None
+    fn declared_output_shapes(session: &Session) -> Vec<(String, Vec<Option<usize>>)> {
+        session.outputs.iter()
+            .map(|output| {
+                let shape = output.output_type.tensor_dimensions()
+                    .map(|dimensions| dimensions.iter().map(|d| usize::try_from(*d).ok()).collect())
+                    .unwrap_or_default();
+                (output.name.clone(), shape)
+            })
+            .collect()
     }
     
-    async fn process_batch(&mut self) -> Result<PerceptionFrame> {
-        if self.batch_processor.pending_frames.is_empty() {
-            return Err(PerceptionError::InferenceError("No frames to process".to_string()));
-        }
-        
-        let batch_size = self.batch_processor.pending_frames.len();
-        let mut batch_tensors = Vec::with_capacity(batch_size);
-        let mut frames = Vec::with_capacity(batch_size);
+    // Preprocesses, runs and decodes one batch of frames of any sizes
+    pub async fn detect_batch(&self, frames: &[CameraFrame]) -> Result<Vec<PerceptionFrame>> {
+        let mut batch_tensors = Vec::with_capacity(frames.len());
+        let mut transforms = Vec::with_capacity(frames.len());
         
-        // Preprocess all frames in the batch
-        for (frame, _) in self.batch_processor.pending_frames.drain(..) {
-            let input_tensor = self.preprocess(&frame)?;
+        for frame in frames {
+            let (input_tensor, transform) = self.preprocess(frame)?;
             batch_tensors.push(input_tensor);
-            frames.push(frame);
+            transforms.push(transform);
         }
         
         // Stack batch tensors
-        let batch_input = self.create_batch_input(batch_tensors)?;
+        let batch_input = preprocess::stack_batch(&batch_tensors)?;
         
         // Run inference
         let session = self.sessions.get(&self.current_model)
             .ok_or_else(|| PerceptionError::InferenceError("Model not found".to_string()))?;
         
-        let outputs = self.run_inference(session.value(), batch_input).await?;
+        let inference_start = Instant::now();
+        let outputs = self.run_inference(session.value(), &batch_input).await?;
+        let outputs = Self::named_outputs(session.value(), outputs)?;
+        self.metrics.record_inference(inference_start.elapsed());
+        self.stats.record_batch(
+            &self.current_model,
+            frames.len(),
+            inference_start.elapsed().as_secs_f32() * 1000.0,
+        );
         
         // Postprocess results
-        let results = self.postprocess_batch(outputs, &frames)?;
+        let results = self.postprocess_batch(outputs, frames, &transforms)?;
+        self.spawn_shadow(frames, &results);
         
-        // For now, return the first result
-        // In a real implementation, we'd return all results
-        Ok(results.into_iter().next()
-            .ok_or_else(|| PerceptionError::InferenceError("No results from batch".to_string()))?)
+        Ok(results)
     }
     
-    fn create_batch_input(&self, tensors: Vec<Array4<f32>>) -> Result<Array4<f32>> {
-        let batch_size = tensors.len();
-        if batch_size == 0 {
-            return Err(PerceptionError::InferenceError("Empty batch".to_string()));
+    // Samples frames for the shadow model and runs it on them in the
+    // background, so the primary's results aren't held up. Its detections are
+    // only compared with the primary's in metrics, never published, and a
+    // failing shadow model is only logged.
+    fn spawn_shadow(&self, frames: &[CameraFrame], results: &[PerceptionFrame]) {
+        let Some(sampler) = &self.shadow else {
+            return;
+        };
+        let sampled: Vec<usize> = (0..frames.len()).filter(|_| sampler.sample()).collect();
+        if sampled.is_empty() {
+            return;
         }
+        let Some(run) = sampler.try_start() else {
+            debug!("Shadow model still busy, skipping {} sampled frames", sampled.len());
+            return;
+        };
         
-        let shape = tensors[0].shape();
-        let mut batch_array = Array4::zeros((batch_size, shape[1], shape[2], shape[3]));
+        let sampled: Vec<(CameraFrame, Vec<Detection>)> = sampled
+            .into_iter()
+            .map(|i| (frames[i].clone(), results[i].detections.clone()))
+            .collect();
+        let engine = self.clone();
+        tokio::spawn(async move {
+            engine.run_shadow(&sampled).await;
+            drop(run);
+        });
+    }
+    
+    async fn run_shadow(&self, sampled: &[(CameraFrame, Vec<Detection>)]) {
+        let Some(session) = self.sessions.get("shadow") else {
+            return;
+        };
         
-        for (i, tensor) in tensors.into_iter().enumerate() {
-            batch_array.slice_mut(s![i, .., .., ..]).assign(&tensor);
+        for (frame, primary) in sampled {
+            match self.shadow_detections(session.value(), frame).await {
+                Ok(detections) => {
+                    let divergence = shadow::compare(primary, &detections, shadow::SHADOW_MATCH_IOU);
+                    debug!("Shadow model on camera {} frame {}: {:?}", frame.camera_id, frame.sequence_num, divergence);
+                    self.metrics.record_shadow(&divergence);
+                }
+                Err(e) => warn!("Shadow model failed on camera {}: {}", frame.camera_id, e),
+            }
         }
+    }
+    
+    async fn shadow_detections(&self, session: &Session, frame: &CameraFrame) -> Result<Vec<Detection>> {
+        // The primary's input, so usually a tensor cache hit
+        let (input, transform) = self.preprocess(frame)?;
+        let outputs = self.run_inference(session, &input).await?;
+        let outputs = Self::named_outputs(session, outputs)?;
         
-        Ok(batch_array)
+        let config = self.config.read().unwrap();
+        let detections = Self::frame_detections(&outputs, 0, &transform, &config)?;
+        let detections = size_filter::clamp_to_frame(detections, frame.width, frame.height, &config);
+        let detections = nms::apply_nms(detections, &config);
+        Ok(size_filter::filter_small(detections, frame.width, frame.height, &config))
+    }
+    
+    // The config postprocessing reads on every batch; config_sync writes
+    // hot-reloaded thresholds into it
+    pub fn live_config(&self) -> Arc<RwLock<InferenceConfig>> {
+        self.config.clone()
+    }
+    
+    fn preprocess(&self, frame: &CameraFrame) -> Result<(Arc<Array4<f32>>, InputTransform)> {
+        self.preprocess_roi(frame, Roi::full(frame))
+    }
+    
+    // Reuses the tensor when another model already ran on this frame and ROI
+    fn preprocess_roi(&self, frame: &CameraFrame, roi: Roi) -> Result<(Arc<Array4<f32>>, InputTransform)> {
+        let config = self.config.read().unwrap();
+        self.tensor_cache.get_or_preprocess(frame, roi, &config, || preprocess::preprocess_roi(frame, roi, &config))
+    }
+    
+    fn intra_op_threads(config: &InferenceConfig) -> usize {
+        match config.intra_op_threads {
+            0 => num_cpus::get(),
+            threads => threads,
+        }
     }
     
-    async fn run_inference(&self, session: &Session, input: Array4<f32>) -> Result<Vec<ort::Value>> {
-        let input_tensor = ort::Value::from_array(session.allocator(), &input)
-            .map_err(|e| PerceptionError::InferenceError(format!("Failed to create input tensor: {}", e)))?;
+    // One frame's preprocessed input and every float output of `session`,
+    // the detection model with its activations exposed, by tensor name for
+    // INT8 calibration
+    pub async fn calibration_tensors(&self, session: &Session, frame: &CameraFrame) -> Result<Vec<(String, ArrayD<f32>)>> {
+        let (input, _) = self.preprocess(frame)?;
+        let input_name = session.inputs.first()
+            .map(|input| input.name.clone())
+            .ok_or_else(|| PerceptionError::InferenceError("Model has no inputs".to_string()))?;
         
-        let outputs = session.run(vec![input_tensor])
-            .map_err(|e| PerceptionError::InferenceError(format!("Inference failed: {}", e)))?;
+        let mut tensors = vec![(input_name, input.as_ref().clone().into_dyn())];
+        let outputs = self.run_inference(session, &input).await?;
+        // Shape and index tensors aren't quantized
+        tensors.extend(session.outputs.iter().zip(outputs.iter()).filter_map(|(output, value)| {
+            value.try_extract_tensor::<f32>().ok().map(|tensor| (output.name.clone(), tensor.into_owned()))
+        }));
+        Ok(tensors)
+    }
+    
+    // Runs on the worker pool so concurrent inferences never exceed its size
+    async fn run_inference(&self, session: &Session, input: &Array4<f32>) -> Result<Vec<ort::Value>> {
+        self.pool.install(|| {
+            let input_tensor = ort::Value::from_array(session.allocator(), input)
+                .map_err(|e| PerceptionError::InferenceError(format!("Failed to create input tensor: {}", e)))?;
+            
+            session.run(vec![input_tensor])
+                .map_err(|e| PerceptionError::InferenceError(format!("Inference failed: {}", e)))
+        })
+    }
+    
+    // Pairs each output with its name so formats with several heads can find them
+    fn named_outputs(session: &Session, outputs: Vec<ort::Value>) -> Result<ModelOutputs> {
+        let tensors = session.outputs.iter()
+            .zip(outputs.iter())
+            .map(|(output, value)| {
+                let tensor = match value.try_extract_tensor::<f32>() {
+                    Ok(tensor) => tensor.into_owned(),
+                    // Class heads are often exported as integers
+                    Err(_) => value.try_extract_tensor::<i64>()
+                        .map_err(|e| PerceptionError::InferenceError(format!("Failed to extract {}: {}", output.name, e)))?
+                        .mapv(|v| v as f32),
+                };
+                Ok((output.name.clone(), tensor))
+            })
+            .collect::<Result<Vec<_>>>()?;
         
-        Ok(outputs)
+        Ok(ModelOutputs::new(tensors))
     }
     
-    fn postprocess_batch(&self, outputs: Vec<ort::Value>, frames: &[CameraFrame]) -> Result<Vec<PerceptionFrame>> {
+    fn postprocess_batch(&self, outputs: ModelOutputs, frames: &[CameraFrame], transforms: &[InputTransform]) -> Result<Vec<PerceptionFrame>> {
         let mut results = Vec::with_capacity(frames.len());
+        let config = self.config.read().unwrap();
         
         for (i, frame) in frames.iter().enumerate() {
-            // Extract results for this batch item
-            let mut detections = Vec::new();
+            let detections = Self::frame_detections(&outputs, i, &transforms[i], &config)?;
+            let detections = size_filter::clamp_to_frame(detections, frame.width, frame.height, &config);
             
-            // This is a simplified postprocessing - actual implementation depends on model output format
-            let output = &outputs[0];
-            let output_array = output.try_extract_tensor::<f32>()
-                .map_err(|e| PerceptionError::InferenceError(format!("Failed to extract tensor: {}", e)))?;
-            
-            let num_detections = output_array.shape()[1];
-            
-            for j in 0..num_detections {
-                let confidence = output_array[[i, j, 4]];
-                
-                if confidence < self.config.confidence_threshold {
-                    continue;
-                }
-                
-                // Extract detection details (simplified)
-                let x = output_array[[i, j, 0]];
-                let y = output_array[[i, j, 1]];
-                let w = output_array[[i, j, 2]];
-                let h = output_array[[i, j, 3]];
-                
-                // Convert to pixel coordinates
-                let xmin = (x - w / 2.0) * frame.width as f32;
-                let ymin = (y - h / 2.0) * frame.height as f32;
-                let xmax = (x + w / 2.0) * frame.width as f32;
-                let ymax = (y + h / 2.0) * frame.height as f32;
-                
-                // Find class with highest score
-                let mut max_class = 0;
-                let mut max_score = 0.0;
-                let num_classes = output_array.shape()[2] - 5;
-                
-                for c in 0..num_classes {
-                    let score = output_array[[i, j, 5 + c]];
-                    if score > max_score {
-                        max_score = score;
-                        max_class = c;
-                    }
-                }
-                
-                let final_confidence = confidence * max_score;
-                
-                if final_confidence < self.config.confidence_threshold {
-                    continue;
-                }
-                
-                let class_label = if max_class < self.config.class_names.len() {
-                    self.config.class_names[max_class].clone()
-                } else {
-                    format!("class_{}", max_class)
-                };
-                
-                let detection = Detection {
-                    bbox: BBox::new(xmin, ymin, xmax, ymax),
-                    confidence: final_confidence,
-                    class_id: max_class as u32,
-                    class_label,
-                    tracker_id: None,
-                };
-                
-                detections.push(detection);
-            }
-            
-            // Apply NMS
-            let detections = self.apply_nms(detections);
+            // Bound NMS time and message size on pathological frames, then apply NMS
+            let detections = nms::cap_detections(detections, &config, &self.metrics, &frame.camera_id);
+            let detections = nms::apply_nms(detections, &config);
+            let detections = size_filter::filter_small(detections, frame.width, frame.height, &config);
+            self.metrics.record_detections(&frame.camera_id, detections.iter().map(|d| d.class_label.as_str()));
             
             // Create perception frame
             let mut perception_frame = PerceptionFrame::new(
@@ -282,10 +391,11 @@ impl OrtEngine {
                 frame.camera_id.clone(),
                 frame.width,
                 frame.height,
-                self.config.model_version.clone(),
+                config.model_version.clone(),
             );
             
             perception_frame.detections = detections;
+            perception_frame.coordinate_space = CoordinateSpace::Pixels;
             perception_frame.inference_time_ms = start_time.elapsed().as_secs_f32() * 1000.0;
             
             results.push(perception_frame);
@@ -294,14 +404,40 @@ impl OrtEngine {
         Ok(results)
     }
     
+    // Decodes batch item `i` per the configured output format, back in frame pixels
+    fn frame_detections(outputs: &ModelOutputs, i: usize, transform: &InputTransform, config: &InferenceConfig) -> Result<Vec<Detection>> {
+        let detections = decode::decode(outputs, i, config)?
+            .into_iter()
+            .map(|candidate| {
+                let class_label = if candidate.class_id < config.class_names.len() {
+                    config.class_names[candidate.class_id].clone()
+                } else {
+                    format!("class_{}", candidate.class_id)
+                };
+                
+                Detection {
+                    // Undo the resize/letterbox
+                    bbox: transform.to_frame(&candidate.bbox),
+                    confidence: candidate.confidence,
+                    class_id: candidate.class_id as u32,
+                    class_label,
+                    tracker_id: None,
+                    oriented: candidate.oriented.map(|oriented| transform.oriented_to_frame(&oriented)),
+                }
+            })
+            .collect();
+        
+        Ok(detections)
+    }
+    
     // Additional methods for multi-model processing
     pub async fn process_segmentation(&self, frame: &CameraFrame) -> Result<SegmentationResult> {
         let session = self.sessions.get("segmentation")
             .ok_or_else(|| PerceptionError::InferenceError("Segmentation model not loaded".to_string()))?;
         
         // Similar processing pipeline but for segmentation
-        let input_tensor = self.preprocess(frame)?;
-        let outputs = self.run_inference(session.value(), input_tensor).await?;
+        let (input_tensor, _) = self.preprocess(frame)?;
+        let outputs = self.run_inference(session.value(), &input_tensor).await?;
         let segmentation = self.postprocess_segmentation(outputs, frame)?;
         
         Ok(segmentation)
@@ -312,9 +448,9 @@ impl OrtEngine {
             .ok_or_else(|| PerceptionError::InferenceError("Robot identification model not loaded".to_string()))?;
         
         // Extract ROI based on detection
-        let roi = self.extract_roi(frame, detection);
-        let input_tensor = self.preprocess_roi(&roi)?;
-        let outputs = self.run_inference(session.value(), input_tensor).await?;
+        let roi = Roi::around(&detection.bbox, frame);
+        let (input_tensor, _) = self.preprocess_roi(frame, roi)?;
+        let outputs = self.run_inference(session.value(), &input_tensor).await?;
         let robot_id = self.postprocess_robot_identification(outputs)?;
         
         Ok(robot_id)
@@ -333,10 +469,20 @@ impl OrtEngine {
         self.sessions.iter().map(|s| s.key().clone()).collect()
     }
     
+    // Shared handle for the control API's /metrics/inference report
+    pub fn stats(&self) -> Arc<InferenceStats> {
+        self.stats.clone()
+    }
+    
+    // Weights of every loaded model, in bytes
+    pub fn get_model_memory_usage(&self) -> u64 {
+        self.stats.report().model_memory_usage.values().sum()
+    }
+    
     // Health monitoring
     pub fn get_inference_metrics(&self) -> InferenceMetrics {
         InferenceMetrics {
-            batch_size: self.batch_processor.pending_frames.len(),
+            batch_size: self.stats.report().queue_depth,
             model_memory_usage: self.get_model_memory_usage(),
             inference_latency: self.metrics.get_average_latency(),
             throughput: self.metrics.get_throughput(),
@@ -344,6 +490,12 @@ impl OrtEngine {
     }
 }
 
+// A session holds its model's weights in memory, so the file size is a
+// floor on what loading it cost
+fn model_size(path: &std::path::Path) -> u64 {
+    std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
 // Support for different model types
 pub enum ModelType {
     ObjectDetection,
@@ -378,9 +530,39 @@ pub struct Keypoint {
     pub id: usize,
 }
 
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct InferenceMetrics {
     pub batch_size: usize,
     pub model_memory_usage: u64,
     pub inference_latency: f32,
     pub throughput: f32,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{OutputFormat, ProcessingConfig};
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn test_model_with_wrong_output_rank_fails_to_load() {
+        // The identity model's output is its [N, 3, 4, 4] input
+        let config = InferenceConfig {
+            model_path: Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/identity_nx3x4x4.onnx"),
+            input_width: 4,
+            input_height: 4,
+            inference_backend: InferenceBackend::Cpu,
+            output_format: OutputFormat::YoloV5,
+            model_warmup: false,
+            ..InferenceConfig::default()
+        };
+        let pool = Arc::new(InferencePool::new(&ProcessingConfig::default()).unwrap());
+
+        let error = OrtEngine::new(&config, pool, Arc::new(Metrics::new())).await.err().unwrap();
+
+        assert!(matches!(error, PerceptionError::InferenceError(_)));
+        let message = error.to_string();
+        assert!(message.contains("3, 4, 4]"), "{}", message);
+        assert!(message.contains("expected [batch, anchors, 5 + classes]"), "{}", message);
+    }
+}
@@ -0,0 +1,318 @@
+use image::{imageops, imageops::FilterType, RgbImage};
+use ndarray::{s, Array4};
+use std::borrow::Borrow;
+
+use crate::{
+    config::{ChannelOrder, InferenceConfig, PreprocessingConfig, ResizeMode},
+    error::{PerceptionError, Result},
+};
+use aetherforge_common::{BBox, CameraFrame, OrientedBBox};
+
+// Where a frame landed inside the model input, so boxes predicted in input
+// coordinates can be mapped back onto the original frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputTransform {
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub pad_x: f32,
+    pub pad_y: f32,
+    pub frame_width: u32,
+    pub frame_height: u32,
+}
+
+impl InputTransform {
+    pub fn new(frame_width: u32, frame_height: u32, input_width: u32, input_height: u32, mode: ResizeMode) -> Self {
+        let (resized_width, resized_height) = resized_size(frame_width, frame_height, input_width, input_height, mode);
+
+        Self {
+            scale_x: resized_width as f32 / frame_width as f32,
+            scale_y: resized_height as f32 / frame_height as f32,
+            pad_x: ((input_width - resized_width) / 2) as f32,
+            pad_y: ((input_height - resized_height) / 2) as f32,
+            frame_width,
+            frame_height,
+        }
+    }
+
+    // Input-pixel box to frame-pixel box. Boxes decoded near the edges can
+    // reach past the frame; see size_filter::clamp_to_frame.
+    pub fn to_frame(&self, bbox: &BBox) -> BBox {
+        let x = |v: f32| (v - self.pad_x) / self.scale_x;
+        let y = |v: f32| (v - self.pad_y) / self.scale_y;
+
+        BBox::new(x(bbox.xmin), y(bbox.ymin), x(bbox.xmax), y(bbox.ymax))
+    }
+    
+    // Input-pixel oriented box to frame pixels. Exact for letterboxing; a
+    // stretch scales the axes unequally, which skews a rotated box, so the
+    // sides and angle are the nearest rectangle's. The center is clamped to
+    // the frame, the corners are not.
+    pub fn oriented_to_frame(&self, bbox: &OrientedBBox) -> OrientedBBox {
+        let (sin, cos) = bbox.angle.sin_cos();
+        let (width_x, width_y) = (bbox.width * cos / self.scale_x, bbox.width * sin / self.scale_y);
+        let (height_x, height_y) = (-bbox.height * sin / self.scale_x, bbox.height * cos / self.scale_y);
+        
+        OrientedBBox::new(
+            ((bbox.cx - self.pad_x) / self.scale_x).clamp(0.0, self.frame_width as f32),
+            ((bbox.cy - self.pad_y) / self.scale_y).clamp(0.0, self.frame_height as f32),
+            width_x.hypot(width_y),
+            height_x.hypot(height_y),
+            width_y.atan2(width_x),
+        )
+    }
+}
+
+// Pixel rectangle of a frame that a model runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Roi {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Roi {
+    pub fn full(frame: &CameraFrame) -> Self {
+        Self { x: 0, y: 0, width: frame.width, height: frame.height }
+    }
+
+    // A detection's box, clamped to the frame and at least a pixel wide
+    pub fn around(bbox: &BBox, frame: &CameraFrame) -> Self {
+        let x = (bbox.xmin.max(0.0) as u32).min(frame.width.saturating_sub(1));
+        let y = (bbox.ymin.max(0.0) as u32).min(frame.height.saturating_sub(1));
+        let xmax = (bbox.xmax.ceil().max(0.0) as u32).clamp(x + 1, frame.width.max(x + 1));
+        let ymax = (bbox.ymax.ceil().max(0.0) as u32).clamp(y + 1, frame.height.max(y + 1));
+
+        Self { x, y, width: xmax - x, height: ymax - y }
+    }
+}
+
+fn resized_size(frame_width: u32, frame_height: u32, input_width: u32, input_height: u32, mode: ResizeMode) -> (u32, u32) {
+    match mode {
+        ResizeMode::Stretch => (input_width, input_height),
+        ResizeMode::Letterbox => {
+            let scale = (input_width as f32 / frame_width as f32).min(input_height as f32 / frame_height as f32);
+            (
+                ((frame_width as f32 * scale).round() as u32).clamp(1, input_width),
+                ((frame_height as f32 * scale).round() as u32).clamp(1, input_height),
+            )
+        }
+    }
+}
+
+fn check_size(frame: &CameraFrame) -> Result<()> {
+    let expected = frame.width as usize * frame.height as usize * 3;
+    if frame.data.len() != expected {
+        return Err(PerceptionError::ProcessingError(format!(
+            "Frame from {} has {} bytes, expected {} for {}x{} RGB",
+            frame.camera_id, frame.data.len(), expected, frame.width, frame.height
+        )));
+    }
+    Ok(())
+}
+
+// Resizes an RGB frame and packs it into a normalized [1, 3, H, W] tensor
+pub fn preprocess(frame: &CameraFrame, config: &InferenceConfig) -> Result<(Array4<f32>, InputTransform)> {
+    check_size(frame)?;
+
+    let image = RgbImage::from_raw(frame.width, frame.height, frame.data.clone())
+        .ok_or_else(|| PerceptionError::ProcessingError("Invalid frame buffer".to_string()))?;
+
+    let pre = &config.preprocessing;
+    let transform = InputTransform::new(frame.width, frame.height, config.input_width, config.input_height, pre.resize_mode);
+    let (resized_width, resized_height) =
+        resized_size(frame.width, frame.height, config.input_width, config.input_height, pre.resize_mode);
+    let resized = imageops::resize(&image, resized_width, resized_height, FilterType::Triangle);
+
+    let (height, width) = (config.input_height as usize, config.input_width as usize);
+    let mut tensor = Array4::<f32>::zeros((1, 3, height, width));
+    for c in 0..3 {
+        let fill = normalize(pre.letterbox_fill, c, pre);
+        tensor.slice_mut(s![0, c, .., ..]).fill(fill);
+    }
+
+    let (pad_x, pad_y) = (transform.pad_x as usize, transform.pad_y as usize);
+    for (x, y, pixel) in resized.enumerate_pixels() {
+        for c in 0..3 {
+            let source = match pre.channel_order {
+                ChannelOrder::Rgb => c,
+                ChannelOrder::Bgr => 2 - c,
+            };
+            tensor[[0, c, pad_y + y as usize, pad_x + x as usize]] = normalize(pixel[source], c, pre);
+        }
+    }
+
+    Ok((tensor, transform))
+}
+
+// `preprocess` on just the ROI; the transform maps boxes back into ROI
+// coordinates
+pub fn preprocess_roi(frame: &CameraFrame, roi: Roi, config: &InferenceConfig) -> Result<(Array4<f32>, InputTransform)> {
+    if roi == Roi::full(frame) {
+        return preprocess(frame, config);
+    }
+    check_size(frame)?;
+    if roi.width == 0 || roi.height == 0 || roi.x + roi.width > frame.width || roi.y + roi.height > frame.height {
+        return Err(PerceptionError::ProcessingError(format!(
+            "ROI {:?} is outside the {}x{} frame from {}",
+            roi, frame.width, frame.height, frame.camera_id
+        )));
+    }
+
+    let (stride, row) = (frame.width as usize * 3, roi.width as usize * 3);
+    let mut data = Vec::with_capacity(row * roi.height as usize);
+    for y in roi.y as usize..(roi.y + roi.height) as usize {
+        let start = y * stride + roi.x as usize * 3;
+        data.extend_from_slice(&frame.data[start..start + row]);
+    }
+
+    let cropped = CameraFrame {
+        camera_id: frame.camera_id.clone(),
+        data,
+        width: roi.width,
+        height: roi.height,
+        format: frame.format.clone(),
+        timestamp: frame.timestamp,
+        sequence_num: frame.sequence_num,
+    };
+    preprocess(&cropped, config)
+}
+
+// Stacks [1, 3, H, W] tensors into one [N, 3, H, W] batch. preprocess
+// resizes every frame to the model input, so frames of any resolution can
+// share a batch; a tensor of another shape is an error, not a panic. Takes
+// the tensors by reference, as they may be shared through the TensorCache.
+pub fn stack_batch<T: Borrow<Array4<f32>>>(tensors: &[T]) -> Result<Array4<f32>> {
+    let shape = match tensors.first() {
+        Some(first) => first.borrow().shape().to_vec(),
+        None => return Err(PerceptionError::InferenceError("Empty batch".to_string())),
+    };
+
+    if let Some((i, tensor)) = tensors.iter().map(Borrow::borrow).enumerate().find(|(_, t)| t.shape() != shape.as_slice() || t.shape()[0] != 1) {
+        return Err(PerceptionError::InferenceError(format!(
+            "Batch item {} has shape {:?}, expected [1, {}, {}, {}]",
+            i,
+            tensor.shape(),
+            shape[1],
+            shape[2],
+            shape[3]
+        )));
+    }
+
+    let mut batch = Array4::zeros((tensors.len(), shape[1], shape[2], shape[3]));
+    for (i, tensor) in tensors.iter().enumerate() {
+        batch.slice_mut(s![i..i + 1, .., .., ..]).assign(tensor.borrow());
+    }
+
+    Ok(batch)
+}
+
+pub(crate) fn normalize(value: u8, channel: usize, config: &PreprocessingConfig) -> f32 {
+    (value as f32 / 255.0 - config.mean[channel]) / config.std[channel]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_with_square(width: u32, height: u32, square: (u32, u32, u32, u32)) -> CameraFrame {
+        let mut data = vec![0u8; (width * height * 3) as usize];
+        for y in square.1..square.3 {
+            for x in square.0..square.2 {
+                let i = ((y * width + x) * 3) as usize;
+                data[i..i + 3].copy_from_slice(&[255, 0, 0]);
+            }
+        }
+
+        CameraFrame {
+            camera_id: "cam-test".to_string(),
+            data,
+            width,
+            height,
+            format: "RGB".to_string(),
+            timestamp: 0,
+            sequence_num: 0,
+        }
+    }
+
+    #[test]
+    fn test_letterbox_box_maps_back_to_original_frame() {
+        let mut config = InferenceConfig::default();
+        config.input_width = 320;
+        config.input_height = 320;
+        config.preprocessing.resize_mode = ResizeMode::Letterbox;
+
+        // 640x360 frame with a centered 100x100 object
+        let frame = frame_with_square(640, 360, (270, 130, 370, 230));
+        let (tensor, transform) = preprocess(&frame, &config).unwrap();
+
+        // Scaled by 0.5 to 320x180, padded by 70 rows top and bottom
+        assert_eq!(transform.pad_x, 0.0);
+        assert_eq!(transform.pad_y, 70.0);
+        assert_eq!(tensor[[0, 0, 10, 160]], 114.0 / 255.0);
+        assert_eq!(tensor[[0, 0, 160, 160]], 1.0);
+
+        // The model sees the object at 135..185 on both axes
+        let predicted = BBox::new(135.0, 135.0, 185.0, 185.0);
+        let mapped = transform.to_frame(&predicted);
+
+        assert!((mapped.xmin - 270.0).abs() < 1e-3);
+        assert!((mapped.ymin - 130.0).abs() < 1e-3);
+        assert!((mapped.xmax - 370.0).abs() < 1e-3);
+        assert!((mapped.ymax - 230.0).abs() < 1e-3);
+
+        // Letterboxing keeps the aspect ratio, so rotated boxes map exactly
+        let oriented = transform.oriented_to_frame(&OrientedBBox::new(160.0, 160.0, 60.0, 20.0, 0.5));
+        assert!((oriented.cx - 320.0).abs() < 1e-3);
+        assert!((oriented.cy - 180.0).abs() < 1e-3);
+        assert!((oriented.width - 120.0).abs() < 1e-3);
+        assert!((oriented.height - 40.0).abs() < 1e-3);
+        assert!((oriented.angle - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_mean_std_and_channel_order() {
+        let mut config = InferenceConfig::default();
+        config.input_width = 4;
+        config.input_height = 4;
+        config.preprocessing.mean = [0.5, 0.5, 0.5];
+        config.preprocessing.std = [0.25, 0.25, 0.25];
+        config.preprocessing.channel_order = ChannelOrder::Bgr;
+
+        let frame = frame_with_square(4, 4, (0, 0, 4, 4));
+        let (tensor, transform) = preprocess(&frame, &config).unwrap();
+
+        // Red lands in the last channel and is normalized to (1 - 0.5) / 0.25
+        assert_eq!(tensor[[0, 2, 1, 1]], 2.0);
+        assert_eq!(tensor[[0, 0, 1, 1]], -2.0);
+
+        let mapped = transform.to_frame(&BBox::new(1.0, 1.0, 3.0, 3.0));
+        assert_eq!((mapped.xmin, mapped.ymin, mapped.xmax, mapped.ymax), (1.0, 1.0, 3.0, 3.0));
+    }
+
+    #[test]
+    fn test_mixed_resolution_frames_share_a_batch() {
+        let mut config = InferenceConfig::default();
+        config.input_width = 64;
+        config.input_height = 48;
+
+        // A 1080p RTSP camera and a 480p USB camera in one batch
+        let frames = [frame_with_square(1920, 1080, (0, 0, 10, 10)), frame_with_square(640, 480, (0, 0, 10, 10))];
+        let (tensors, transforms): (Vec<_>, Vec<_>) = frames.iter().map(|f| preprocess(f, &config).unwrap()).unzip();
+
+        let batch = stack_batch(&tensors).unwrap();
+
+        assert_eq!(batch.shape(), &[2, 3, 48, 64]);
+        assert_eq!((transforms[0].frame_width, transforms[1].frame_width), (1920, 640));
+    }
+
+    #[test]
+    fn test_mismatched_tensor_shapes_are_an_error() {
+        let tensors = vec![Array4::<f32>::zeros((1, 3, 48, 64)), Array4::<f32>::zeros((1, 3, 1080, 1920))];
+
+        let err = stack_batch(&tensors).unwrap_err();
+
+        assert!(err.to_string().contains("Batch item 1 has shape [1, 3, 1080, 1920]"), "{}", err);
+        assert!(stack_batch::<Array4<f32>>(&[]).is_err());
+    }
+}
\ No newline at end of file
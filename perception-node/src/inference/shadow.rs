@@ -0,0 +1,156 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use aetherforge_common::Detection;
+
+// A primary and a shadow detection are the same object at this overlap
+pub const SHADOW_MATCH_IOU: f32 = 0.5;
+
+// Batches the shadow model may be working through at once. It runs off the
+// primary's path, so when it falls behind new samples are skipped rather
+// than queued.
+pub const MAX_SHADOW_RUNS: usize = 2;
+
+// Picks the frames the shadow model also runs on, spread evenly: frame n is
+// sampled when the running total of `rate` passes a whole number, so a rate
+// of 0.1 runs exactly every tenth frame
+pub struct ShadowSampler {
+    rate: f64,
+    seen: AtomicU64,
+    runs: Arc<Semaphore>,
+}
+
+impl ShadowSampler {
+    pub fn new(rate: f32) -> Self {
+        Self {
+            rate: f64::from(rate).clamp(0.0, 1.0),
+            seen: AtomicU64::new(0),
+            runs: Arc::new(Semaphore::new(MAX_SHADOW_RUNS)),
+        }
+    }
+
+    pub fn sample(&self) -> bool {
+        let n = self.seen.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * self.rate).floor() > (n * self.rate).floor()
+    }
+
+    // A slot for one background shadow run, held until the run finishes;
+    // None while MAX_SHADOW_RUNS are already going
+    pub fn try_start(&self) -> Option<OwnedSemaphorePermit> {
+        self.runs.clone().try_acquire_owned().ok()
+    }
+}
+
+// How far the shadow model's detections on one frame are from the
+// primary's. The means are over matched pairs and are 0 without any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub matched: usize,
+    pub primary_only: usize, // missed by the shadow model
+    pub shadow_only: usize,  // found only by the shadow model
+    pub mean_iou: f32,
+    pub class_agreement: f32, // fraction of matched pairs with the same class
+    pub mean_confidence_delta: f32, // shadow minus primary
+}
+
+// Pairs detections greedily by highest IoU, regardless of class, so a class
+// disagreement shows up as such rather than as a miss on both sides
+pub fn compare(primary: &[Detection], shadow: &[Detection], match_iou: f32) -> Divergence {
+    let mut candidates: Vec<(f32, usize, usize)> = primary
+        .iter()
+        .enumerate()
+        .flat_map(|(p, a)| shadow.iter().enumerate().map(move |(s, b)| (a.intersection_over_union(b), p, s)))
+        .filter(|(iou, _, _)| *iou >= match_iou)
+        .collect();
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut primary_used = vec![false; primary.len()];
+    let mut shadow_used = vec![false; shadow.len()];
+    let (mut matched, mut iou_sum, mut same_class, mut delta_sum) = (0usize, 0.0, 0usize, 0.0);
+    for (iou, p, s) in candidates {
+        if primary_used[p] || shadow_used[s] {
+            continue;
+        }
+        primary_used[p] = true;
+        shadow_used[s] = true;
+
+        matched += 1;
+        iou_sum += iou;
+        if primary[p].class_id == shadow[s].class_id {
+            same_class += 1;
+        }
+        delta_sum += shadow[s].confidence - primary[p].confidence;
+    }
+
+    let mean = |sum: f32| if matched == 0 { 0.0 } else { sum / matched as f32 };
+    Divergence {
+        matched,
+        primary_only: primary.len() - matched,
+        shadow_only: shadow.len() - matched,
+        mean_iou: mean(iou_sum),
+        class_agreement: mean(same_class as f32),
+        mean_confidence_delta: mean(delta_sum),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::metrics::Metrics;
+    use aetherforge_common::BBox;
+
+    fn detection(class_id: u32, confidence: f32, x: f32) -> Detection {
+        Detection {
+            bbox: BBox::new(x, 100.0, x + 100.0, 300.0),
+            confidence,
+            class_id,
+            class_label: format!("class_{}", class_id),
+            tracker_id: None,
+            oriented: None,
+        }
+    }
+
+    #[test]
+    fn test_shadow_runs_on_sampled_fraction_and_records_divergence() {
+        let sampler = ShadowSampler::new(0.1);
+        let metrics = Metrics::new();
+
+        // The primary sees a person and a forklift; the candidate model places
+        // the person slightly off, calls the forklift a robot, and adds a pallet
+        let primary = vec![detection(0, 0.9, 0.0), detection(3, 0.8, 400.0)];
+        let shadow = vec![detection(0, 0.7, 10.0), detection(1, 0.9, 400.0), detection(2, 0.6, 800.0)];
+
+        let mut runs = 0;
+        for _ in 0..200 {
+            if !sampler.sample() {
+                continue;
+            }
+            runs += 1;
+            let divergence = compare(&primary, &shadow, SHADOW_MATCH_IOU);
+            metrics.record_shadow(&divergence);
+        }
+        assert_eq!(runs, 20);
+
+        let divergence = compare(&primary, &shadow, SHADOW_MATCH_IOU);
+        assert_eq!((divergence.matched, divergence.primary_only, divergence.shadow_only), (2, 0, 1));
+        assert!((divergence.mean_iou - (18000.0 / 22000.0 + 1.0) / 2.0).abs() < 1e-3);
+        assert_eq!(divergence.class_agreement, 0.5);
+        assert!((divergence.mean_confidence_delta - (-0.2 + 0.1) / 2.0).abs() < 1e-5);
+
+        let exposed = metrics.encode();
+        assert!(exposed.contains("aetherforge_shadow_frames_total 20"));
+        assert!(exposed.contains(r#"aetherforge_shadow_unmatched_total{side="shadow"} 20"#));
+        assert!(exposed.contains("aetherforge_shadow_class_agreement_count 20"));
+    }
+
+    #[test]
+    fn test_background_runs_are_capped() {
+        let sampler = ShadowSampler::new(1.0);
+        let runs: Vec<_> = (0..MAX_SHADOW_RUNS).map(|_| sampler.try_start().unwrap()).collect();
+        assert!(sampler.try_start().is_none());
+
+        drop(runs);
+        assert!(sampler.try_start().is_some());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::config::{InferenceConfig, MinBoxSize};
+use aetherforge_common::Detection;
+
+// Clamps boxes to the frame and drops those left with no area, i.e. that
+// were entirely off it. Runs before NMS so overlaps are judged on what's
+// actually in view.
+pub fn clamp_to_frame(detections: Vec<Detection>, frame_width: u32, frame_height: u32, config: &InferenceConfig) -> Vec<Detection> {
+    if !config.clamp_to_frame {
+        return detections;
+    }
+    
+    detections
+        .into_iter()
+        .filter_map(|mut detection| {
+            detection.bbox = detection.bbox.clamp(frame_width as f32, frame_height as f32);
+            (detection.bbox.width() > 0.0 && detection.bbox.height() > 0.0).then_some(detection)
+        })
+        .collect()
+}
+
+// Drops detections smaller than the size floor, after NMS. Each class uses
+// its entry in `class_min_box_sizes` if present, otherwise `min_box_size`,
+// so a class seen small at a distance can get a lower floor.
+pub fn filter_small(detections: Vec<Detection>, frame_width: u32, frame_height: u32, config: &InferenceConfig) -> Vec<Detection> {
+    detections
+        .into_iter()
+        .filter(|detection| {
+            let floor = config.class_min_box_sizes.get(&detection.class_label).unwrap_or(&config.min_box_size);
+            meets(floor, detection, frame_width, frame_height)
+        })
+        .collect()
+}
+
+fn meets(floor: &MinBoxSize, detection: &Detection, frame_width: u32, frame_height: u32) -> bool {
+    // Boxes are in pixels here; relative floors are fractions of the frame
+    let (scale_x, scale_y) = match floor.relative {
+        true => (frame_width as f32, frame_height as f32),
+        false => (1.0, 1.0),
+    };
+    
+    detection.bbox.width() >= floor.min_width * scale_x
+        && detection.bbox.height() >= floor.min_height * scale_y
+        && detection.bbox.area() >= floor.min_area * scale_x * scale_y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ResizeMode;
+    use crate::inference::preprocess::InputTransform;
+    use aetherforge_common::BBox;
+    
+    fn detection(class_label: &str, size: f32) -> Detection {
+        Detection {
+            bbox: BBox::new(100.0, 100.0, 100.0 + size, 100.0 + size),
+            confidence: 0.9,
+            class_id: 0,
+            class_label: class_label.to_string(),
+            tracker_id: None,
+            oriented: None,
+        }
+    }
+    
+    #[test]
+    fn test_tiny_box_dropped_and_real_one_kept() {
+        let mut config = InferenceConfig::default();
+        config.min_box_size = MinBoxSize { min_width: 8.0, min_height: 8.0, min_area: 100.0, relative: false };
+        
+        let kept = filter_small(vec![detection("forklift", 3.0), detection("forklift", 40.0)], 640, 480, &config);
+        
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].bbox.width(), 40.0);
+    }
+    
+    #[test]
+    fn test_edge_box_clamped_and_off_frame_box_dropped() {
+        let config = InferenceConfig::default();
+        // 1280x960 frame stretched to the 640x480 input
+        let transform = InputTransform::new(1280, 960, 640, 480, ResizeMode::Stretch);
+        let decoded = |xmin, ymin, xmax, ymax| Detection {
+            bbox: transform.to_frame(&BBox::new(xmin, ymin, xmax, ymax)),
+            ..detection("person", 0.0)
+        };
+        
+        // A center/size decode at the corner spills past it; another box is past the right edge
+        let kept = clamp_to_frame(vec![decoded(-10.0, 450.0, 40.0, 500.0), decoded(650.0, 100.0, 700.0, 200.0)], 1280, 960, &config);
+        
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].bbox, BBox::new(0.0, 900.0, 80.0, 960.0));
+        
+        let unclamped = InferenceConfig { clamp_to_frame: false, ..InferenceConfig::default() };
+        assert_eq!(clamp_to_frame(vec![decoded(650.0, 100.0, 700.0, 200.0)], 1280, 960, &unclamped).len(), 1);
+    }
+    
+    #[test]
+    fn test_per_class_and_relative_floors() {
+        let mut config = InferenceConfig::default();
+        // 5% of a 640x480 frame: 32x24 pixels
+        config.min_box_size = MinBoxSize { min_width: 0.05, min_height: 0.05, min_area: 0.0, relative: true };
+        config.class_min_box_sizes.insert(
+            "person".to_string(),
+            MinBoxSize { min_width: 4.0, min_height: 4.0, min_area: 0.0, relative: false },
+        );
+        
+        let kept = filter_small(vec![detection("person", 10.0), detection("pallet", 10.0), detection("pallet", 40.0)], 640, 480, &config);
+        
+        let labels: Vec<_> = kept.iter().map(|d| (d.class_label.as_str(), d.bbox.width())).collect();
+        assert_eq!(labels, vec![("person", 10.0), ("pallet", 40.0)]);
+    }
+}
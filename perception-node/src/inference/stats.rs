@@ -0,0 +1,129 @@
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+// Running latency/batch totals for one model or camera
+#[derive(Debug, Clone)]
+struct LatencyStats {
+    frames: u64,
+    batches: u64,
+    total_batch_size: u64,
+    total_latency_ms: f64,
+    last_latency_ms: f32,
+    max_latency_ms: f32,
+    first_seen: Instant,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LatencySnapshot {
+    pub frames_processed: u64,
+    pub avg_latency_ms: f32,
+    pub last_latency_ms: f32,
+    pub max_latency_ms: f32,
+    pub avg_batch_size: f32,
+    pub throughput_fps: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InferenceMetricsReport {
+    pub queue_depth: usize,
+    pub max_batch_size: usize,
+    pub models: HashMap<String, LatencySnapshot>,
+    pub cameras: HashMap<String, LatencySnapshot>,
+    pub model_memory_usage: HashMap<String, u64>, // bytes of weights per loaded model, run or not
+}
+
+// Shared between the inference engine, which records every batch, and the
+// control API, which reports it. Cheap to update from the hot path.
+pub struct InferenceStats {
+    max_batch_size: usize,
+    queue_depth: AtomicUsize,
+    models: DashMap<String, LatencyStats>,
+    cameras: DashMap<String, LatencyStats>,
+    model_memory: DashMap<String, u64>,
+}
+
+impl LatencyStats {
+    fn new() -> Self {
+        Self {
+            frames: 0,
+            batches: 0,
+            total_batch_size: 0,
+            total_latency_ms: 0.0,
+            last_latency_ms: 0.0,
+            max_latency_ms: 0.0,
+            first_seen: Instant::now(),
+        }
+    }
+
+    fn record(&mut self, frames: u64, batch_size: usize, latency_ms: f32) {
+        self.frames += frames;
+        self.batches += 1;
+        self.total_batch_size += batch_size as u64;
+        self.total_latency_ms += latency_ms as f64;
+        self.last_latency_ms = latency_ms;
+        self.max_latency_ms = self.max_latency_ms.max(latency_ms);
+    }
+
+    fn snapshot(&self) -> LatencySnapshot {
+        let batches = self.batches.max(1) as f64;
+        let elapsed = self.first_seen.elapsed().as_secs_f64();
+
+        LatencySnapshot {
+            frames_processed: self.frames,
+            avg_latency_ms: (self.total_latency_ms / batches) as f32,
+            last_latency_ms: self.last_latency_ms,
+            max_latency_ms: self.max_latency_ms,
+            avg_batch_size: (self.total_batch_size as f64 / batches) as f32,
+            throughput_fps: if elapsed > 0.0 { (self.frames as f64 / elapsed) as f32 } else { 0.0 },
+        }
+    }
+}
+
+impl InferenceStats {
+    pub fn new(max_batch_size: usize) -> Self {
+        Self {
+            max_batch_size,
+            queue_depth: AtomicUsize::new(0),
+            models: DashMap::new(),
+            cameras: DashMap::new(),
+            model_memory: DashMap::new(),
+        }
+    }
+
+    pub fn set_queue_depth(&self, depth: usize) {
+        self.queue_depth.store(depth, Ordering::Relaxed);
+    }
+
+    pub fn set_model_memory(&self, model: &str, bytes: u64) {
+        self.model_memory.insert(model.to_string(), bytes);
+    }
+
+    // One inference call over `batch_size` frames
+    pub fn record_batch(&self, model: &str, batch_size: usize, latency_ms: f32) {
+        self.models
+            .entry(model.to_string())
+            .or_insert_with(LatencyStats::new)
+            .record(batch_size as u64, batch_size, latency_ms);
+    }
+
+    // End-to-end latency of a single frame, from enqueue to results
+    pub fn record_frame(&self, camera_id: &str, batch_size: usize, latency_ms: f32) {
+        self.cameras
+            .entry(camera_id.to_string())
+            .or_insert_with(LatencyStats::new)
+            .record(1, batch_size, latency_ms);
+    }
+
+    pub fn report(&self) -> InferenceMetricsReport {
+        InferenceMetricsReport {
+            queue_depth: self.queue_depth.load(Ordering::Relaxed),
+            max_batch_size: self.max_batch_size,
+            models: self.models.iter().map(|e| (e.key().clone(), e.value().snapshot())).collect(),
+            cameras: self.cameras.iter().map(|e| (e.key().clone(), e.value().snapshot())).collect(),
+            model_memory_usage: self.model_memory.iter().map(|e| (e.key().clone(), *e.value())).collect(),
+        }
+    }
+}
\ No newline at end of file
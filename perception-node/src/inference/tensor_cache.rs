@@ -0,0 +1,167 @@
+use ndarray::Array4;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::preprocess::{InputTransform, Roi};
+use crate::{
+    config::{InferenceConfig, PreprocessingConfig},
+    error::Result,
+};
+use aetherforge_common::CameraFrame;
+
+// What a model's input depends on besides the pixels; models that agree on
+// it can share a tensor
+#[derive(Debug, Clone, PartialEq)]
+struct InputSpec {
+    width: u32,
+    height: u32,
+    preprocessing: PreprocessingConfig,
+}
+
+impl InputSpec {
+    fn of(config: &InferenceConfig) -> Self {
+        Self {
+            width: config.input_width,
+            height: config.input_height,
+            preprocessing: config.preprocessing.clone(),
+        }
+    }
+}
+
+struct CachedTensor {
+    roi: Roi,
+    spec: InputSpec,
+    tensor: Arc<Array4<f32>>,
+    transform: InputTransform,
+}
+
+struct FrameTensors {
+    frame: (u64, u64), // sequence number and timestamp
+    tensors: Vec<CachedTensor>,
+}
+
+// Preprocessed tensors of each camera's current frame, so several models
+// run on the same frame and ROI (detection, robot identification,
+// segmentation) resize and normalize it once. A camera's tensors are
+// dropped as soon as a different frame of it is preprocessed. At most
+// `capacity` are kept per frame; 0 disables the cache. Tensors are handed
+// out shared, so a hit costs no copy.
+pub struct TensorCache {
+    capacity: usize,
+    cameras: Mutex<HashMap<String, FrameTensors>>,
+}
+
+impl TensorCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            cameras: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // The cached tensor for this frame, ROI and input, or `preprocess`'s
+    // result, which is cached for the next model
+    pub fn get_or_preprocess<F>(
+        &self,
+        frame: &CameraFrame,
+        roi: Roi,
+        config: &InferenceConfig,
+        preprocess: F,
+    ) -> Result<(Arc<Array4<f32>>, InputTransform)>
+    where
+        F: FnOnce() -> Result<(Array4<f32>, InputTransform)>,
+    {
+        if self.capacity == 0 {
+            return preprocess().map(|(tensor, transform)| (Arc::new(tensor), transform));
+        }
+
+        let key = (frame.sequence_num, frame.timestamp);
+        let spec = InputSpec::of(config);
+        {
+            let cameras = self.cameras.lock().unwrap();
+            let hit = cameras
+                .get(&frame.camera_id)
+                .filter(|current| current.frame == key)
+                .and_then(|current| current.tensors.iter().find(|t| t.roi == roi && t.spec == spec));
+            if let Some(hit) = hit {
+                return Ok((hit.tensor.clone(), hit.transform));
+            }
+        }
+
+        // Outside the lock, so cameras don't wait on each other's preprocessing
+        let (tensor, transform) = preprocess()?;
+        let tensor = Arc::new(tensor);
+
+        let mut cameras = self.cameras.lock().unwrap();
+        let current = cameras
+            .entry(frame.camera_id.clone())
+            .or_insert_with(|| FrameTensors { frame: key, tensors: Vec::new() });
+        if current.frame != key {
+            *current = FrameTensors { frame: key, tensors: Vec::new() };
+        }
+        if current.tensors.len() < self.capacity {
+            current.tensors.push(CachedTensor { roi, spec, tensor: tensor.clone(), transform });
+        }
+
+        Ok((tensor, transform))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inference::preprocess::preprocess_roi;
+    use std::cell::Cell;
+
+    fn frame(sequence_num: u64) -> CameraFrame {
+        CameraFrame {
+            camera_id: "dock-1".to_string(),
+            data: (0..64 * 48 * 3).map(|i| (i % 251) as u8).collect(),
+            width: 64,
+            height: 48,
+            format: "RGB".to_string(),
+            timestamp: 1_700_000_000_000 + sequence_num * 33,
+            sequence_num,
+        }
+    }
+
+    #[test]
+    fn test_detection_then_robot_id_preprocess_roi_once() {
+        let cache = TensorCache::new(8);
+        let detection_config = InferenceConfig { input_width: 32, input_height: 32, ..InferenceConfig::default() };
+        // OrtEngine loads the robot identification model with the same input config
+        let robot_config = detection_config.clone();
+        let forklift = Roi { x: 8, y: 4, width: 24, height: 20 };
+
+        let preprocessed = Cell::new(0);
+        let run = |frame: &CameraFrame, roi: Roi, config: &InferenceConfig| {
+            cache
+                .get_or_preprocess(frame, roi, config, || {
+                    preprocessed.set(preprocessed.get() + 1);
+                    preprocess_roi(frame, roi, config)
+                })
+                .unwrap()
+        };
+
+        let current = frame(7);
+        let (detection_input, detection_transform) = run(&current, forklift, &detection_config);
+        let (robot_input, robot_transform) = run(&current, forklift, &robot_config);
+        assert_eq!(preprocessed.get(), 1);
+        assert!(Arc::ptr_eq(&detection_input, &robot_input));
+        assert_eq!(detection_transform, robot_transform);
+
+        // Another ROI, or a model normalized differently, needs its own tensor
+        run(&current, Roi::full(&current), &detection_config);
+        let mut bgr_config = robot_config.clone();
+        bgr_config.preprocessing.channel_order = crate::config::ChannelOrder::Bgr;
+        run(&current, forklift, &bgr_config);
+        assert_eq!(preprocessed.get(), 3);
+
+        // The next frame replaces everything cached for the camera
+        run(&frame(8), forklift, &detection_config);
+        run(&frame(8), forklift, &robot_config);
+        assert_eq!(preprocessed.get(), 4);
+        run(&current, forklift, &detection_config);
+        assert_eq!(preprocessed.get(), 5);
+    }
+}
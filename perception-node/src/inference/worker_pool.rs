@@ -0,0 +1,123 @@
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use tokio::sync::oneshot;
+
+use crate::{
+    config::ProcessingConfig,
+    error::{PerceptionError, Result},
+    utils::affinity,
+};
+
+// Fixed-size pool that runs model inference. At most `num_worker_threads`
+// inferences run at once, each on a thread optionally pinned to one of
+// `inference_cores` so capture and inference don't fight over cores.
+pub struct InferencePool {
+    pool: ThreadPool,
+    workers: usize,
+}
+
+impl InferencePool {
+    pub fn new(config: &ProcessingConfig) -> Result<Self> {
+        let workers = config.num_worker_threads.max(1);
+        let cores = config.inference_cores.clone();
+
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(workers)
+            .thread_name(|i| format!("inference-{}", i))
+            .start_handler(move |i| {
+                affinity::pin_current_thread(&cores, i);
+            })
+            .build()
+            .map_err(|e| PerceptionError::ConfigError(format!("Failed to build inference pool: {}", e)))?;
+
+        Ok(Self { pool, workers })
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    // Runs `f` on a pool thread, blocking the caller until it finishes
+    pub fn install<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T + Send,
+        T: Send,
+    {
+        self.pool.install(f)
+    }
+
+    // Queues `f` on the pool without blocking the async runtime
+    pub async fn run<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        self.pool.spawn(move || {
+            let _ = tx.send(f());
+        });
+        rx.await
+            .map_err(|_| PerceptionError::InferenceError("Inference worker dropped the task".to_string()))
+    }
+}
+
+// ORT intra-op threads per session so that all workers together fill the
+// cores they may run on rather than each claiming every core
+pub fn intra_op_threads(config: &ProcessingConfig) -> usize {
+    (affinity::available_cores(&config.inference_cores) / config.num_worker_threads.max(1)).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_worker_count_bounds_concurrent_inference() {
+        let config = ProcessingConfig {
+            num_worker_threads: 3,
+            ..ProcessingConfig::default()
+        };
+        let pool = Arc::new(InferencePool::new(&config).unwrap());
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..16)
+            .map(|_| {
+                let (pool, running, peak) = (pool.clone(), running.clone(), peak.clone());
+                tokio::spawn(async move {
+                    pool.run(move || {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(20));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                    .await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(pool.workers(), 3);
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_intra_op_threads_split_cores_between_workers() {
+        let config = ProcessingConfig {
+            num_worker_threads: 2,
+            inference_cores: vec![2, 3, 4, 5, 6],
+            ..ProcessingConfig::default()
+        };
+        assert_eq!(intra_op_threads(&config), 2);
+
+        let oversubscribed = ProcessingConfig {
+            num_worker_threads: 8,
+            ..config
+        };
+        assert_eq!(intra_op_threads(&oversubscribed), 1);
+    }
+}
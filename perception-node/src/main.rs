@@ -1,17 +1,25 @@
+mod aggregator;
 mod camera;
+mod control;
 mod inference;
 mod messaging;
-// mod processing;
-// mod utils;
+mod processing;
+mod utils;
 mod config;
+mod config_sync;
 mod error;
+mod heartbeat;
+mod self_test;
+mod startup;
+mod scoring;
 
 use clap::Parser;
 use config::PerceptionConfig;
 use error::Result;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::signal;
-use tracing::{error, info, Level};
+use tracing::{error, info, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
 #[derive(Parser, Debug)]
@@ -24,6 +32,38 @@ struct Args {
     /// Log level
     #[arg(short, long, default_value = "info")]
     log_level: String,
+    
+    /// Check config, cameras, model and messaging, then exit
+    #[arg(long)]
+    self_test: bool,
+    
+    /// Score every image in this directory offline, then exit
+    #[arg(long)]
+    score_dir: Option<PathBuf>,
+    
+    /// Size of raw RGB frames (.rgb, .raw) under --score-dir, e.g. 1280x720
+    #[arg(long, value_parser = scoring::parse_frame_size)]
+    raw_frame_size: Option<(u32, u32)>,
+    
+    /// Replay a detections export from the operator platform offline, then exit
+    #[arg(long)]
+    replay: Option<PathBuf>,
+    
+    /// Where --score-dir and --replay write detections
+    #[arg(long, default_value = "detections.json")]
+    score_output: PathBuf,
+    
+    /// Output layout for --score-dir and --replay
+    #[arg(long, value_enum, default_value = "json")]
+    score_format: scoring::ScoreFormat,
+    
+    /// Capture frames from the cameras and build the TensorRT INT8 calibration cache, then exit
+    #[arg(long)]
+    calibrate_int8: bool,
+    
+    /// Run as the facility-wide fusion aggregator instead of a camera node
+    #[arg(long)]
+    aggregate: bool,
 }
 
 #[tokio::main]
@@ -34,8 +74,51 @@ async fn main() -> Result<()> {
     // Initialize logging
     init_logging(&args.log_level)?;
     
+    if args.self_test {
+        let report = self_test::run(&args.config).await;
+        report.print();
+        std::process::exit(if report.passed() { 0 } else { 1 });
+    }
+    
     // Load configuration
-    let config = load_config(&args.config).await?;
+    let mut config = load_config(&args.config).await?;
+    
+    if let Some(dir) = &args.score_dir {
+        scoring::run(&config, dir, args.raw_frame_size, &args.score_output, args.score_format).await?;
+        return Ok(());
+    }
+    
+    if let Some(input) = &args.replay {
+        scoring::replay(input, &config.inference.class_names, &args.score_output, args.score_format)?;
+        return Ok(());
+    }
+    
+    if args.calibrate_int8 {
+        inference::int8_calibration::run(&config).await?;
+        return Ok(());
+    }
+    
+    if args.aggregate {
+        tokio::select! {
+            result = aggregator::run(&config.aggregator, &config.messaging) => result?,
+            _ = wait_for_shutdown() => {}
+        }
+        return Ok(());
+    }
+    
+    // Cameras assigned on the operator platform replace the configured list
+    let mut cameras_assigned = false;
+    if config.config_sync.enabled {
+        match config_sync::fetch_assigned_cameras(&config).await {
+            Ok(cameras) if !cameras.is_empty() => {
+                info!("Running {} cameras assigned by the operator platform", cameras.len());
+                config.cameras = cameras;
+                cameras_assigned = true;
+            }
+            Ok(_) => info!("No cameras assigned by the operator platform, using configured cameras"),
+            Err(e) => warn!("Failed to fetch assigned cameras, using configured cameras: {}", e),
+        }
+    }
     
     info!("Starting AetherForge Perception Node {}", config.node_id);
     
@@ -50,16 +133,121 @@ async fn main() -> Result<()> {
         }
     });
     
-    // Start metrics server if enabled
+    // Restart camera pipelines that stop delivering frames without erroring
+    let watchdog = camera::watchdog::PipelineWatchdog::new(&app_state.config.cameras, aetherforge_common::utils::current_timestamp_ms())
+        .with_metrics(app_state.metrics.clone());
+    tokio::spawn(camera::watchdog::run(watchdog, app_state.camera_manager.clone(), app_state.message_publisher.clone()));
+
+    // Step lower-priority cameras down while ingest is over the uplink budget
+    let bandwidth = &app_state.config.processing.bandwidth_budget;
+    if bandwidth.enabled {
+        let budget = camera::bandwidth::BandwidthBudget::new(&app_state.config.cameras, bandwidth);
+        let interval = std::time::Duration::from_millis(bandwidth.check_interval_ms.max(1));
+        tokio::spawn(camera::bandwidth::run(budget, app_state.camera_manager.clone(), app_state.message_publisher.clone(), interval));
+    }
+    
+    // Fuse the cameras' detections into facility-wide tracks and publish
+    // them, with the alerts the site's rules raise on them
+    let mut live_alert_rules = None;
+    if let Some(fusion) = &app_state.fusion {
+        if fusion.is_empty() {
+            warn!("Data fusion is enabled but no camera is calibrated; fusion results will be empty");
+        }
+        let mut engine = processing::fusion_engine::FusionEngine::new(&app_state.config.processing);
+        if let Some(zones) = processing::zone_map::ZoneMap::from_config(&app_state.config.processing)? {
+            info!("Tagging fused objects with {} facility map cells", zones.len());
+            engine = engine.with_zone_map(zones);
+        }
+        let rules = processing::alert_rules::AlertRuleEngine::new(&app_state.config.processing.alert_rules);
+        live_alert_rules = Some(rules.live_rules());
+        
+        // Handoffs go to the operator platform's analytics while config sync is on
+        let handoffs = app_state.config.config_sync.enabled.then(|| {
+            let (tx, rx) = tokio::sync::mpsc::channel(processing::fusion_stage::HANDOFF_QUEUE_CAPACITY);
+            let operator_url = app_state.config.config_sync.operator_url.clone();
+            let node_id = app_state.config.node_id.clone();
+            tokio::spawn(async move {
+                if let Err(e) = processing::fusion_stage::run_handoff_reporter(operator_url, node_id, rx).await {
+                    error!("Track handoff reporting failed: {}", e);
+                }
+            });
+            tx
+        });
+        
+        let interval = std::time::Duration::from_millis(app_state.config.processing.fusion_interval_ms.max(1));
+        let publisher = app_state.message_publisher.clone();
+        tokio::spawn(processing::fusion_stage::run(fusion.clone(), engine, rules, handoffs, publisher, interval));
+    } else if !app_state.config.processing.alert_rules.is_empty() {
+        warn!("Alert rules are configured but data fusion is disabled; they will not be evaluated");
+    }
+    
+    // Expose metrics if enabled, by scrape server or pushgateway
     if app_state.config.monitoring.enable_metrics {
-        let metrics_addr = format!("0.0.0.0:{}", app_state.config.monitoring.metrics_port);
+        let metrics = app_state.metrics.clone();
+        match app_state.config.monitoring.metrics_mode {
+            config::MetricsMode::Scrape => {
+                let metrics_addr = format!("0.0.0.0:{}", app_state.config.monitoring.metrics_port);
+                tokio::spawn(async move {
+                    if let Err(e) = utils::metrics::start_metrics_server(metrics_addr, metrics).await {
+                        error!("Metrics server failed: {}", e);
+                    }
+                });
+            }
+            config::MetricsMode::Push => {
+                let monitoring = app_state.config.monitoring.clone();
+                let node_id = app_state.config.node_id.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = utils::metrics::run_pushgateway(metrics, &monitoring, &node_id).await {
+                        error!("Metrics push failed: {}", e);
+                    }
+                });
+            }
+        }
+    }
+    
+    // Start control API if enabled
+    if app_state.config.monitoring.enable_control_api {
+        let control_addr = format!("{}:{}", app_state.config.monitoring.control_bind_address, app_state.config.monitoring.control_port);
+        let control_state = control::ControlState {
+            inference_stats: app_state.inference_engine.stats(),
+            debug_overlay: app_state.debug_overlay.clone(),
+            messaging: Some(app_state.message_publisher.clone()),
+            scorer: app_state.scorer.clone(),
+            scoring_token: app_state.config.monitoring.scoring_token.clone(),
+            camera_warmup: Some(app_state.camera_warmup.clone()),
+        };
         tokio::spawn(async move {
-            if let Err(e) = utils::metrics::start_metrics_server(metrics_addr).await {
-                error!("Metrics server failed: {}", e);
+            if let Err(e) = control::start_control_server(control_addr, control_state).await {
+                error!("Control API failed: {}", e);
             }
         });
     }
     
+    // Pick up config pushed from the operator platform
+    if app_state.config.config_sync.enabled {
+        let mut sync = config_sync::ConfigSync::new(app_state.config.clone(), app_state.inference_engine.live_config())?;
+        if let Some(rules) = live_alert_rules {
+            sync = sync.with_alert_rules(rules);
+        }
+        if cameras_assigned {
+            sync = sync.with_assigned_cameras();
+        }
+        let interval = std::time::Duration::from_millis(app_state.config.config_sync.poll_interval_ms.max(1));
+        tokio::spawn(sync.run(interval));
+        
+        if app_state.config.config_sync.heartbeat_interval_ms > 0 {
+            let operator_url = app_state.config.config_sync.operator_url.clone();
+            let node_id = app_state.config.node_id.clone();
+            let interval = std::time::Duration::from_millis(app_state.config.config_sync.heartbeat_interval_ms);
+            let messaging = app_state.message_publisher.clone();
+            tokio::spawn(async move {
+                if let Err(e) = heartbeat::run(operator_url, node_id, interval, messaging).await {
+                    error!("Heartbeat failed: {}", e);
+                }
+            });
+        }
+    }
+    
     // Start processing pipeline
     let processor = processing::frame_processor::FrameProcessor::new(app_state.clone());
     processor.start().await?;
@@ -100,8 +288,14 @@ async fn load_config(path: &str) -> Result<PerceptionConfig> {
         .build()
         .map_err(|e| error::PerceptionError::ConfigError(e.to_string()))?;
     
-    settings.try_deserialize()
-        .map_err(|e| error::PerceptionError::ConfigError(e.to_string()))
+    let mut config: PerceptionConfig = settings.try_deserialize()
+        .map_err(|e| error::PerceptionError::ConfigError(e.to_string()))?;
+    
+    config.resolve_secrets()?;
+    config.reconcile_threads();
+    processing::alert_rules::validate(&config.processing.alert_rules)?;
+    
+    Ok(config)
 }
 
 async fn wait_for_shutdown() {
@@ -136,8 +330,14 @@ pub struct AppState {
     pub config: PerceptionConfig,
     pub camera_manager: Arc<camera::multi_camera::MultiCameraManager>,
     pub inference_engine: Arc<inference::ort_engine::OrtEngine>,
-    pub message_publisher: Arc<messaging::zmq_pub::ZmqPublisher>,
+    pub message_publisher: Arc<messaging::DeferredPublisher<messaging::MultiProtocolPublisher>>,
     pub metrics: Arc<utils::metrics::Metrics>,
+    pub debug_overlay: Option<Arc<utils::overlay::DebugOverlay>>,
+    pub annotation_sampler: Option<Arc<processing::annotation_sampler::AnnotationSampler>>,
+    pub scorer: Option<Arc<scoring::ImageScorer>>,
+    pub publish_throttle: Arc<processing::publish_throttle::PublishThrottle>,
+    pub camera_warmup: Arc<processing::camera_warmup::CameraWarmup>,
+    pub fusion: Option<Arc<processing::fusion_stage::FloorObservations>>,
 }
 
 impl AppState {
@@ -150,15 +350,71 @@ impl AppState {
             camera::multi_camera::MultiCameraManager::new(config.cameras.clone(), metrics.clone()).await?
         );
         
-        // Initialize inference engine
+        // The model may live on a mount that isn't ready yet
+        startup::wait_for("model file", &config.startup, || startup::model_file_present(&config.inference.model_path)).await?;
+        
+        // Initialize inference engine on a bounded, optionally pinned pool
+        let inference_pool = Arc::new(inference::InferencePool::new(&config.processing)?);
         let inference_engine = Arc::new(
-            inference::ort_engine::OrtEngine::new(&config.inference, metrics.clone()).await?
+            inference::ort_engine::OrtEngine::new(&config.inference, inference_pool.clone(), metrics.clone()).await?
         );
         
-        // Initialize message publisher
-        let message_publisher = Arc::new(
-            messaging::zmq_pub::ZmqPublisher::new(&config.messaging, metrics.clone())?
-        );
+        // Scoring uploaded images shares the inference pool with the cameras
+        if config.monitoring.enable_scoring_api && config.monitoring.scoring_token.is_none() {
+            return Err(error::PerceptionError::ConfigError("monitoring.enable_scoring_api requires monitoring.scoring_token".to_string()));
+        }
+        let scorer = config.monitoring.enable_scoring_api
+            .then(|| Arc::new(scoring::ImageScorer::new(&config.inference, &config.monitoring.scoring_models_dir, inference_pool, metrics.clone())));
+        
+        // Initialize message publisher, with fallback if configured. With
+        // degraded_start the node comes up without a broker and holds
+        // frames until the publisher reconnects.
+        let message_publisher = Arc::new(messaging::DeferredPublisher::new(
+            messaging::MultiProtocolPublisher::new(config.messaging.clone(), metrics.clone())?,
+            config.startup.buffer_capacity,
+            config.messaging.message_ttl_ms,
+            metrics.clone(),
+        ));
+        match startup::wait_for("messaging broker", &config.startup, || message_publisher.try_connect()).await {
+            Ok(()) => {}
+            Err(e) if config.startup.degraded_start => {
+                warn!("Starting in degraded mode, buffering frames until messaging recovers: {}", e);
+            }
+            Err(e) => return Err(e),
+        }
+        message_publisher.spawn_recovery(std::time::Duration::from_millis(config.startup.retry_interval_ms.max(1)));
+        
+        // Annotated debug frames, only when asked for
+        let debug_overlay = config.monitoring.debug_overlay.enabled
+            .then(|| Arc::new(utils::overlay::DebugOverlay::new(&config.monitoring.debug_overlay)));
+        
+        // Periodic captures for labeling, uploaded while config sync is on
+        let (annotation_sampler, samples) = if config.config_sync.enabled && config.config_sync.max_samples_per_hour > 0 {
+            let (tx, rx) = tokio::sync::mpsc::channel(processing::annotation_sampler::SAMPLE_QUEUE_CAPACITY);
+            let sampler = processing::annotation_sampler::AnnotationSampler::new(&config.cameras, &config.config_sync, tx);
+            (Some(Arc::new(sampler)), Some(rx))
+        } else {
+            (None, None)
+        };
+        if let Some(samples) = samples {
+            let operator_url = config.config_sync.operator_url.clone();
+            let node_id = config.node_id.clone();
+            tokio::spawn(async move {
+                if let Err(e) = processing::annotation_sampler::run_uploader(operator_url, node_id, samples).await {
+                    error!("Annotation sample upload failed: {}", e);
+                }
+            });
+        }
+        
+        // Per-camera cap on published frames
+        let publish_throttle = Arc::new(processing::publish_throttle::PublishThrottle::new(&config.cameras));
+        
+        // Frames held back from inference while each camera settles
+        let camera_warmup = Arc::new(processing::camera_warmup::CameraWarmup::new(&config.cameras, &config.processing.frame_quality));
+        
+        // Detections of calibrated cameras, on the floor plane for fusion
+        let fusion = config.processing.enable_data_fusion
+            .then(|| Arc::new(processing::fusion_stage::FloorObservations::new(&config.cameras)));
         
         Ok(Self {
             config,
@@ -166,6 +422,12 @@ impl AppState {
             inference_engine,
             message_publisher,
             metrics,
+            debug_overlay,
+            annotation_sampler,
+            scorer,
+            publish_throttle,
+            camera_warmup,
+            fusion,
         })
     }
 }
\ No newline at end of file
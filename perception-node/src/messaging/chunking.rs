@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use super::{MessageEnvelope, MessageType};
+
+// Position of one part of a payload that was too big to send whole. Every
+// chunk carries the full message's envelope, so sizes and sequence number
+// describe the reassembled payload.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChunkInfo {
+    pub index: u32,
+    pub count: u32,
+}
+
+// Splits a payload larger than `max_payload_bytes` into numbered chunks;
+// smaller payloads, or any payload when the limit is 0, go out whole
+pub fn split(envelope: &MessageEnvelope, payload: &[u8], max_payload_bytes: usize) -> Vec<(MessageEnvelope, Vec<u8>)> {
+    if max_payload_bytes == 0 || payload.len() <= max_payload_bytes {
+        return vec![(MessageEnvelope { chunk: None, ..envelope.clone() }, payload.to_vec())];
+    }
+
+    let count = payload.len().div_ceil(max_payload_bytes) as u32;
+    payload
+        .chunks(max_payload_bytes)
+        .enumerate()
+        .map(|(index, part)| {
+            let chunk = ChunkInfo { index: index as u32, count };
+            (MessageEnvelope { chunk: Some(chunk), ..envelope.clone() }, part.to_vec())
+        })
+        .collect()
+}
+
+struct PartialMessage {
+    envelope: MessageEnvelope,
+    parts: Vec<Option<Vec<u8>>>,
+    received: u32,
+    started: Instant,
+}
+
+// Collects chunks back into whole payloads, in whatever order they arrive.
+// A set not completed within `timeout` of its first chunk is discarded, and
+// a chunk claiming a set of more than `max_chunks` is dropped outright.
+pub struct ChunkAssembler {
+    timeout: Duration,
+    max_chunks: u32,
+    pending: HashMap<(String, MessageType, u64), PartialMessage>,
+}
+
+impl ChunkAssembler {
+    pub fn new(timeout: Duration, max_chunks: u32) -> Self {
+        Self {
+            timeout,
+            max_chunks,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    // The whole message once its last chunk is in; unchunked messages pass
+    // straight through
+    pub fn push(&mut self, envelope: MessageEnvelope, payload: Vec<u8>, now: Instant) -> Option<(MessageEnvelope, Vec<u8>)> {
+        self.expire(now);
+
+        let Some(chunk) = envelope.chunk else {
+            return Some((envelope, payload));
+        };
+        // The set's parts are allocated from `count`, so it's checked first
+        if chunk.count == 0 || chunk.count > self.max_chunks || chunk.index >= chunk.count {
+            warn!("Dropping chunk {}/{} of message {} from {}", chunk.index, chunk.count, envelope.sequence_number, envelope.camera_id);
+            return None;
+        }
+
+        let key = (envelope.camera_id.clone(), envelope.message_type, envelope.sequence_number);
+        let partial = self.pending.entry(key.clone()).or_insert_with(|| PartialMessage {
+            envelope: MessageEnvelope { chunk: None, ..envelope.clone() },
+            parts: vec![None; chunk.count as usize],
+            received: 0,
+            started: now,
+        });
+        // A count that disagrees with the set's means a different message
+        // reused the sequence number; its chunks can't be trusted either
+        if partial.parts.len() != chunk.count as usize {
+            warn!("Chunk count changed mid-message {} from {}, discarding it", envelope.sequence_number, envelope.camera_id);
+            self.pending.remove(&key);
+            return None;
+        }
+        let slot = &mut partial.parts[chunk.index as usize];
+        if slot.is_none() {
+            partial.received += 1;
+        }
+        *slot = Some(payload);
+
+        if partial.received < chunk.count {
+            return None;
+        }
+        let partial = self.pending.remove(&key)?;
+        let payload: Vec<u8> = partial.parts.into_iter().flatten().flatten().collect();
+        if payload.len() != partial.envelope.compressed_size {
+            warn!(
+                "Reassembled message {} from {} is {} bytes, expected {}",
+                partial.envelope.sequence_number, partial.envelope.camera_id, payload.len(), partial.envelope.compressed_size
+            );
+            return None;
+        }
+        Some((partial.envelope, payload))
+    }
+
+    // Drops incomplete sets that have waited longer than the timeout
+    pub fn expire(&mut self, now: Instant) -> usize {
+        let timeout = self.timeout;
+        let before = self.pending.len();
+        self.pending.retain(|(camera_id, _, sequence_number), partial| {
+            let live = now.saturating_duration_since(partial.started) < timeout;
+            if !live {
+                warn!(
+                    "Discarding message {} from {}: {} of {} chunks arrived within {:?}",
+                    sequence_number, camera_id, partial.received, partial.parts.len(), timeout
+                );
+            }
+            live
+        });
+        before - self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(sequence_number: u64, payload: &[u8]) -> MessageEnvelope {
+        MessageEnvelope {
+            message_type: MessageType::PerceptionFrame,
+            camera_id: "cam-1".to_string(),
+            sequence_number,
+            timestamp: 1_700_000_000_000,
+            compression: "none".to_string(),
+            original_size: payload.len(),
+            compressed_size: payload.len(),
+            signature: None,
+            chunk: None,
+        }
+    }
+
+    #[test]
+    fn test_oversized_payload_is_chunked_and_reassembled() {
+        // A segmentation mask well over the broker limit
+        let payload: Vec<u8> = (0..10_000u32).map(|i| (i * 31 % 251) as u8).collect();
+        let mut chunks = split(&envelope(7, &payload), &payload, 4096);
+
+        let shape: Vec<(Option<ChunkInfo>, usize)> = chunks.iter().map(|(e, p)| (e.chunk, p.len())).collect();
+        assert_eq!(shape, vec![
+            (Some(ChunkInfo { index: 0, count: 3 }), 4096),
+            (Some(ChunkInfo { index: 1, count: 3 }), 4096),
+            (Some(ChunkInfo { index: 2, count: 3 }), 1808),
+        ]);
+
+        // Arrival order doesn't matter; a duplicate doesn't complete the set
+        chunks.swap(0, 2);
+        let now = Instant::now();
+        let mut assembler = ChunkAssembler::new(Duration::from_secs(5), 16);
+        let (first, second, third) = (chunks[0].clone(), chunks[1].clone(), chunks[2].clone());
+        assert!(assembler.push(first.0.clone(), first.1.clone(), now).is_none());
+        assert!(assembler.push(first.0, first.1, now).is_none());
+        assert!(assembler.push(second.0, second.1, now).is_none());
+        let (whole, reassembled) = assembler.push(third.0, third.1, now).unwrap();
+
+        assert_eq!(reassembled, payload);
+        assert_eq!(whole.sequence_number, 7);
+        assert_eq!(whole.chunk, None);
+        assert_eq!(assembler.pending(), 0);
+
+        // Small payloads go out whole
+        let small = split(&envelope(8, b"ok"), b"ok", 4096);
+        assert_eq!(small.len(), 1);
+        assert_eq!(assembler.push(small[0].0.clone(), small[0].1.clone(), now).unwrap().1, b"ok");
+    }
+
+    #[test]
+    fn test_incomplete_set_is_discarded_after_timeout() {
+        let payload = vec![1u8; 300];
+        let chunks = split(&envelope(3, &payload), &payload, 100);
+        let start = Instant::now();
+        let mut assembler = ChunkAssembler::new(Duration::from_millis(500), 16);
+
+        assert!(assembler.push(chunks[0].0.clone(), chunks[0].1.clone(), start).is_none());
+        assert!(assembler.push(chunks[1].0.clone(), chunks[1].1.clone(), start).is_none());
+        assert_eq!(assembler.expire(start + Duration::from_millis(499)), 0);
+        assert_eq!(assembler.expire(start + Duration::from_millis(500)), 1);
+
+        // The straggler alone starts a set that can never complete
+        let late = start + Duration::from_millis(600);
+        assert!(assembler.push(chunks[2].0.clone(), chunks[2].1.clone(), late).is_none());
+        assert_eq!(assembler.pending(), 1);
+    }
+
+    #[test]
+    fn test_chunk_claiming_an_oversized_or_impossible_set_is_dropped() {
+        let now = Instant::now();
+        let mut assembler = ChunkAssembler::new(Duration::from_secs(5), 16);
+        let chunk = |index, count| MessageEnvelope { chunk: Some(ChunkInfo { index, count }), ..envelope(9, &[0; 64]) };
+
+        assert!(assembler.push(chunk(0, u32::MAX), vec![0; 64], now).is_none());
+        assert!(assembler.push(chunk(0, 17), vec![0; 64], now).is_none());
+        assert!(assembler.push(chunk(3, 3), vec![0; 64], now).is_none());
+        assert_eq!(assembler.pending(), 0);
+
+        assert!(assembler.push(chunk(0, 16), vec![0; 4], now).is_none());
+        assert_eq!(assembler.pending(), 1);
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::{MessageEnvelope, MessagePublisher, MessageType};
+use aetherforge_common::utils::current_timestamp_ms;
+use crate::{
+    config::DeadLetterConfig,
+    error::{Result, PerceptionError},
+};
+
+const DEAD_LETTER_EXTENSION: &str = "dlq";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub envelope: MessageEnvelope,
+    pub payload: Vec<u8>,
+}
+
+// Disk-backed ring buffer of messages that failed every publisher.
+// Each letter is its own file named by an increasing sequence number,
+// so ordering survives restarts and the oldest entry is dropped first.
+// Perception frames older than `ttl_ms` are dropped rather than re-sent.
+pub struct DeadLetterQueue {
+    dir: PathBuf,
+    max_messages: usize,
+    ttl_ms: u64, // 0 never expires
+    state: Mutex<QueueState>,
+}
+
+struct QueueState {
+    entries: VecDeque<u64>,
+    next_seq: u64,
+}
+
+impl DeadLetterQueue {
+    pub fn open(config: &DeadLetterConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.path)?;
+
+        let mut entries: Vec<u64> = std::fs::read_dir(&config.path)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| Self::parse_seq(&entry.path()))
+            .collect();
+        entries.sort_unstable();
+
+        if !entries.is_empty() {
+            info!("Recovered {} dead-lettered messages from {}", entries.len(), config.path.display());
+        }
+
+        let next_seq = entries.last().map(|seq| seq + 1).unwrap_or(0);
+        let queue = Self {
+            dir: config.path.clone(),
+            max_messages: config.max_messages.max(1),
+            ttl_ms: 0,
+            state: Mutex::new(QueueState {
+                entries: entries.into(),
+                next_seq,
+            }),
+        };
+
+        // The configured bound may have shrunk since the letters were written
+        {
+            let mut state = queue.state.lock().unwrap();
+            queue.evict_overflow(&mut state);
+        }
+
+        Ok(queue)
+    }
+
+    pub fn with_ttl(mut self, ttl_ms: u64) -> Self {
+        self.ttl_ms = ttl_ms;
+        self
+    }
+
+    pub fn push(&self, letter: &DeadLetter) -> Result<()> {
+        let bytes = bincode::serialize(letter)
+            .map_err(|e| PerceptionError::SerializationError(format!("Dead letter serialization failed: {}", e)))?;
+
+        let mut state = self.state.lock().unwrap();
+        let seq = state.next_seq;
+        std::fs::write(self.entry_path(seq), bytes)?;
+
+        state.next_seq += 1;
+        state.entries.push_back(seq);
+        self.evict_overflow(&mut state);
+
+        Ok(())
+    }
+
+    pub fn peek_oldest(&self) -> Result<Option<(u64, DeadLetter)>> {
+        loop {
+            let seq = match self.state.lock().unwrap().entries.front() {
+                Some(seq) => *seq,
+                None => return Ok(None),
+            };
+
+            let bytes = std::fs::read(self.entry_path(seq))?;
+            match bincode::deserialize::<DeadLetter>(&bytes) {
+                Ok(letter) => return Ok(Some((seq, letter))),
+                Err(e) => {
+                    // A torn write from a crash must not block the whole queue
+                    warn!("Discarding corrupt dead letter {}: {}", seq, e);
+                    self.remove(seq)?;
+                }
+            }
+        }
+    }
+
+    pub fn remove(&self, seq: u64) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|entry| *entry != seq);
+
+        match std::fs::remove_file(self.entry_path(seq)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Re-sends letters oldest-first, stopping at the first failure so
+    // ordering is preserved for the next attempt.
+    pub async fn resend<P: MessagePublisher + ?Sized>(&self, publisher: &P) -> Result<usize> {
+        let mut resent = 0;
+
+        while let Some((seq, letter)) = self.peek_oldest()? {
+            if self.is_stale(&letter.envelope, current_timestamp_ms()) {
+                debug!("Dropping dead letter {}, older than the {}ms message TTL", seq, self.ttl_ms);
+                self.remove(seq)?;
+                continue;
+            }
+            match publisher.publish_raw(&letter.envelope, &letter.payload).await {
+                Ok(()) => resent += 1,
+                // Would never go through; don't let it block the queue
+                Err(e) if !e.is_retryable() => warn!("Dropping dead letter {} ({:?}): {}", seq, e.code(), e),
+                Err(e) => return Err(e),
+            }
+            self.remove(seq)?;
+        }
+
+        Ok(resent)
+    }
+
+    pub fn spawn_resender<P: MessagePublisher + 'static>(
+        self: Arc<Self>,
+        publisher: Arc<P>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                if self.is_empty() {
+                    continue;
+                }
+
+                match self.resend(publisher.as_ref()).await {
+                    Ok(count) if count > 0 => info!("Re-sent {} dead-lettered messages", count),
+                    Ok(_) => {}
+                    Err(e) => debug!("Dead-letter re-send deferred: {}", e),
+                }
+            }
+        })
+    }
+
+    // Alerts and health are still worth delivering late; frames are not
+    fn is_stale(&self, envelope: &MessageEnvelope, now_ms: u64) -> bool {
+        let is_frame = matches!(envelope.message_type, MessageType::PerceptionFrame | MessageType::PerceptionFrameDelta);
+        is_frame && self.ttl_ms > 0 && now_ms.saturating_sub(envelope.timestamp) > self.ttl_ms
+    }
+
+    fn evict_overflow(&self, state: &mut QueueState) {
+        while state.entries.len() > self.max_messages {
+            if let Some(oldest) = state.entries.pop_front() {
+                warn!("Dead-letter queue full, dropping oldest message {}", oldest);
+                let _ = std::fs::remove_file(self.entry_path(oldest));
+            }
+        }
+    }
+
+    fn entry_path(&self, seq: u64) -> PathBuf {
+        self.dir.join(format!("{:020}.{}", seq, DEAD_LETTER_EXTENSION))
+    }
+
+    fn parse_seq(path: &Path) -> Option<u64> {
+        if path.extension()?.to_str()? != DEAD_LETTER_EXTENSION {
+            return None;
+        }
+        path.file_stem()?.to_str()?.parse().ok()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,328 @@
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use super::{MessageEnvelope, MessagePublisher, MessagingHealthReport, MultiProtocolPublisher, SystemAlert, SystemHealth};
+use crate::error::Result;
+use crate::utils::metrics::Metrics;
+use aetherforge_common::{utils::current_timestamp_ms, FusionResult, PerceptionFrame};
+
+// Lets a node started without its broker keep capturing and inferring.
+// Perception frames are held in memory while the publisher is down and
+// flushed in order once it reconnects; health and alerts are point-in-time
+// and go straight through. A frame older than `ttl_ms` by the time it would
+// be published, live or flushed, is dropped instead, as it's no use to
+// anyone that late.
+pub struct DeferredPublisher<P> {
+    inner: RwLock<P>,
+    buffer: Mutex<VecDeque<PerceptionFrame>>,
+    capacity: usize,
+    ttl_ms: u64, // 0 never expires
+    dropped: AtomicU64,
+    metrics: Arc<Metrics>,
+}
+
+impl<P: MessagePublisher> DeferredPublisher<P> {
+    pub fn new(inner: P, capacity: usize, ttl_ms: u64, metrics: Arc<Metrics>) -> Self {
+        Self {
+            inner: RwLock::new(inner),
+            buffer: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            ttl_ms,
+            dropped: AtomicU64::new(0),
+            metrics,
+        }
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.buffer.lock().unwrap().len()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub async fn is_connected(&self) -> bool {
+        self.inner.read().await.is_connected()
+    }
+
+    pub async fn try_connect(&self) -> Result<()> {
+        self.inner.write().await.connect().await
+    }
+
+    fn hold(&self, frame: PerceptionFrame) {
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.len() >= self.capacity {
+            buffer.pop_front();
+            self.metrics.increment_publisher_overflow();
+            if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                warn!("Messaging buffer full ({} frames), dropping the oldest", self.capacity);
+            }
+        }
+        buffer.push_back(frame);
+    }
+
+    fn is_stale(&self, frame: &PerceptionFrame, now_ms: u64) -> bool {
+        self.ttl_ms > 0 && now_ms.saturating_sub(frame.timestamp) > self.ttl_ms
+    }
+
+    // Reconnects if needed, then publishes everything held that's still
+    // within the TTL. Returns the number of frames flushed; stops early if
+    // the publisher fails again.
+    pub async fn recover(&self) -> Result<usize> {
+        if !self.is_connected().await {
+            self.try_connect().await?;
+            info!("Messaging reconnected, flushing {} buffered frames", self.buffered());
+        }
+
+        let inner = self.inner.read().await;
+        let mut flushed = 0;
+        loop {
+            let frame = match self.buffer.lock().unwrap().pop_front() {
+                Some(frame) => frame,
+                None => return Ok(flushed),
+            };
+            if self.is_stale(&frame, current_timestamp_ms()) {
+                self.metrics.increment_stale_messages();
+                continue;
+            }
+            if let Err(e) = inner.publish_perception_frame(&frame).await {
+                self.buffer.lock().unwrap().push_front(frame);
+                return Err(e);
+            }
+            flushed += 1;
+        }
+    }
+
+    pub fn spawn_recovery(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()>
+    where
+        P: 'static,
+    {
+        let publisher = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if publisher.buffered() == 0 && publisher.is_connected().await {
+                    continue;
+                }
+                if let Err(e) = publisher.recover().await {
+                    warn!("Messaging still unavailable ({} frames buffered): {}", publisher.buffered(), e);
+                }
+            }
+        })
+    }
+}
+
+impl DeferredPublisher<MultiProtocolPublisher> {
+    pub async fn health(&self) -> MessagingHealthReport {
+        let mut report = self.inner.read().await.health();
+        report.buffered_frames = self.buffered();
+        report
+    }
+}
+
+#[async_trait]
+impl<P: MessagePublisher> MessagePublisher for DeferredPublisher<P> {
+    async fn publish_perception_frame(&self, frame: &PerceptionFrame) -> Result<()> {
+        if self.is_stale(frame, current_timestamp_ms()) {
+            self.metrics.increment_stale_messages();
+            return Ok(());
+        }
+
+        // Queue behind anything already held so frames stay in order
+        if self.buffered() > 0 || !self.is_connected().await {
+            self.hold(frame.clone());
+            return Ok(());
+        }
+
+        match self.inner.read().await.publish_perception_frame(frame).await {
+            Err(e) if e.is_retryable() => {
+                self.hold(frame.clone());
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    async fn publish_fusion_result(&self, result: &FusionResult) -> Result<()> {
+        self.inner.read().await.publish_fusion_result(result).await
+    }
+
+    async fn publish_system_health(&self, health: &SystemHealth) -> Result<()> {
+        self.inner.read().await.publish_system_health(health).await
+    }
+
+    async fn publish_alert(&self, alert: &SystemAlert) -> Result<()> {
+        self.inner.read().await.publish_alert(alert).await
+    }
+
+    async fn publish_raw(&self, envelope: &MessageEnvelope, payload: &[u8]) -> Result<()> {
+        self.inner.read().await.publish_raw(envelope, payload).await
+    }
+
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.get_mut().connect().await
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.inner.get_mut().disconnect().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.try_read().map(|inner| inner.is_connected()).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PerceptionError;
+    use aetherforge_common::CoordinateSpace;
+    use std::sync::atomic::AtomicBool;
+
+    // A broker that refuses connections until `up` is set
+    struct FlakyBroker {
+        up: Arc<AtomicBool>,
+        connected: AtomicBool,
+        received: Arc<Mutex<Vec<u64>>>,
+    }
+
+    #[async_trait]
+    impl MessagePublisher for FlakyBroker {
+        async fn publish_perception_frame(&self, frame: &PerceptionFrame) -> Result<()> {
+            if !self.connected.load(Ordering::SeqCst) {
+                return Err(PerceptionError::MessagingError("not connected".into()));
+            }
+            self.received.lock().unwrap().push(frame.frame_id);
+            Ok(())
+        }
+
+        async fn publish_fusion_result(&self, _result: &FusionResult) -> Result<()> {
+            Ok(())
+        }
+
+        async fn publish_system_health(&self, _health: &SystemHealth) -> Result<()> {
+            Ok(())
+        }
+
+        async fn publish_alert(&self, _alert: &SystemAlert) -> Result<()> {
+            Ok(())
+        }
+
+        async fn publish_raw(&self, _envelope: &MessageEnvelope, _payload: &[u8]) -> Result<()> {
+            Ok(())
+        }
+
+        async fn connect(&mut self) -> Result<()> {
+            if !self.up.load(Ordering::SeqCst) {
+                return Err(PerceptionError::MessagingError("connection refused".into()));
+            }
+            self.connected.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn disconnect(&mut self) -> Result<()> {
+            self.connected.store(false, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            self.connected.load(Ordering::SeqCst)
+        }
+    }
+
+    fn frame(frame_id: u64) -> PerceptionFrame {
+        PerceptionFrame {
+            frame_id,
+            timestamp: frame_id * 33,
+            source_camera_id: "cam-1".to_string(),
+            image_width: 640,
+            image_height: 480,
+            model_version: "test".to_string(),
+            inference_time_ms: 1.0,
+            detections: Vec::new(),
+            camera_intrinsics: None,
+            camera_extrinsics: None,
+            coordinate_space: CoordinateSpace::Pixels,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_buffers_while_broker_down_then_flushes_in_order() {
+        let up = Arc::new(AtomicBool::new(false));
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let broker = FlakyBroker { up: up.clone(), connected: AtomicBool::new(false), received: received.clone() };
+        let publisher = DeferredPublisher::new(broker, 100, 0, Arc::new(Metrics::new()));
+
+        // Degraded start: the broker never came up within the startup wait
+        let startup = crate::config::StartupConfig { dependency_timeout_ms: 30, retry_interval_ms: 10, ..Default::default() };
+        assert!(crate::startup::wait_for("messaging broker", &startup, || publisher.try_connect()).await.is_err());
+
+        for id in 0..3 {
+            publisher.publish_perception_frame(&frame(id)).await.unwrap();
+        }
+        assert_eq!(publisher.buffered(), 3);
+        assert!(received.lock().unwrap().is_empty());
+        assert!(publisher.recover().await.is_err());
+
+        up.store(true, Ordering::SeqCst);
+        assert_eq!(publisher.recover().await.unwrap(), 3);
+        publisher.publish_perception_frame(&frame(3)).await.unwrap();
+
+        assert_eq!(publisher.buffered(), 0);
+        assert_eq!(*received.lock().unwrap(), vec![0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_drops_oldest_when_full() {
+        let broker = FlakyBroker {
+            up: Arc::new(AtomicBool::new(false)),
+            connected: AtomicBool::new(false),
+            received: Arc::new(Mutex::new(Vec::new())),
+        };
+        let metrics = Arc::new(Metrics::new());
+        let publisher = DeferredPublisher::new(broker, 2, 0, metrics.clone());
+
+        for id in 0..5 {
+            publisher.publish_perception_frame(&frame(id)).await.unwrap();
+        }
+
+        assert_eq!(publisher.buffered(), 2);
+        assert_eq!(publisher.dropped(), 3);
+        assert_eq!(publisher.buffer.lock().unwrap().front().unwrap().frame_id, 3);
+        assert!(metrics.encode().contains("aetherforge_publisher_dropped_messages_total{reason=\"queue_full\"} 3"));
+    }
+
+    #[tokio::test]
+    async fn test_stale_frame_dropped_instead_of_sent() {
+        let up = Arc::new(AtomicBool::new(false));
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let broker = FlakyBroker { up: up.clone(), connected: AtomicBool::new(false), received: received.clone() };
+        let metrics = Arc::new(Metrics::new());
+        let publisher = DeferredPublisher::new(broker, 100, 200, metrics.clone());
+
+        // One held past the TTL during the outage, then one captured just before recovery
+        publisher.publish_perception_frame(&PerceptionFrame { timestamp: current_timestamp_ms(), ..frame(0) }).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let now = current_timestamp_ms();
+        publisher.publish_perception_frame(&PerceptionFrame { timestamp: now, ..frame(1) }).await.unwrap();
+        assert_eq!(publisher.buffered(), 2);
+
+        up.store(true, Ordering::SeqCst);
+        assert_eq!(publisher.recover().await.unwrap(), 1);
+
+        assert_eq!(publisher.buffered(), 0);
+        assert_eq!(*received.lock().unwrap(), vec![1]);
+        assert!(metrics.encode().contains("aetherforge_publisher_dropped_messages_total{reason=\"stale\"} 1"));
+
+        // A frame that was already stale when it reached the publisher
+        publisher.publish_perception_frame(&PerceptionFrame { timestamp: now - 5000, ..frame(2) }).await.unwrap();
+        assert_eq!(*received.lock().unwrap(), vec![1]);
+        assert!(metrics.encode().contains("aetherforge_publisher_dropped_messages_total{reason=\"stale\"} 2"));
+    }
+}
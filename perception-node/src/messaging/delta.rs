@@ -0,0 +1,320 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+
+use aetherforge_common::{Detection, PerceptionFrame};
+
+// Changes to a camera's detections since the frame `base_frame_id`, keyed by
+// tracker ID. Untracked detections can't be matched across frames, so the
+// current ones are always sent whole.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameDelta {
+    pub source_camera_id: String,
+    pub base_frame_id: u64,
+    pub frame_id: u64,
+    pub timestamp: u64,
+    pub inference_time_ms: f32,
+    pub removed: Vec<u64>,
+    pub changed: Vec<Detection>, // new or moved since the base frame
+    pub untracked: Vec<Detection>,
+    // Tracker ID of each detection in the frame's order (None for the next
+    // untracked one); only sent when the default order is wrong
+    pub order: Option<Vec<Option<u64>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FrameUpdate {
+    Keyframe(PerceptionFrame),
+    Delta(FrameDelta),
+}
+
+impl FrameDelta {
+    // The delta from `base` to `frame`, or None when the two differ in more
+    // than detections (resolution, model, calibration) or trackers collide
+    pub fn between(base: &PerceptionFrame, frame: &PerceptionFrame) -> Option<Self> {
+        let same_stream = PerceptionFrame {
+            frame_id: base.frame_id,
+            timestamp: base.timestamp,
+            inference_time_ms: base.inference_time_ms,
+            detections: Vec::new(),
+            ..frame.clone()
+        } == PerceptionFrame { detections: Vec::new(), ..base.clone() };
+        if !same_stream {
+            return None;
+        }
+
+        let previous = tracked(base)?;
+        let current = tracked(frame)?;
+        let mut delta = FrameDelta {
+            source_camera_id: frame.source_camera_id.clone(),
+            base_frame_id: base.frame_id,
+            frame_id: frame.frame_id,
+            timestamp: frame.timestamp,
+            inference_time_ms: frame.inference_time_ms,
+            removed: previous.keys().filter(|id| !current.contains_key(id)).copied().collect(),
+            changed: frame
+                .detections
+                .iter()
+                .filter(|d| d.tracker_id.is_some_and(|id| previous.get(&id) != Some(d)))
+                .cloned()
+                .collect(),
+            untracked: frame.detections.iter().filter(|d| d.tracker_id.is_none()).cloned().collect(),
+            order: None,
+        };
+        delta.removed.sort_unstable();
+
+        if delta.apply(base)?.detections != frame.detections {
+            delta.order = Some(frame.detections.iter().map(|d| d.tracker_id).collect());
+        }
+        Some(delta)
+    }
+
+    // Rebuilds the frame from its base. Without an explicit order, surviving
+    // detections keep the base's order, new ones follow, untracked ones last.
+    pub fn apply(&self, base: &PerceptionFrame) -> Option<PerceptionFrame> {
+        if base.frame_id != self.base_frame_id || base.source_camera_id != self.source_camera_id {
+            return None;
+        }
+
+        let changed: HashMap<u64, &Detection> = self.changed.iter().filter_map(|d| Some((d.tracker_id?, d))).collect();
+        let mut detections: Vec<Detection> = base
+            .detections
+            .iter()
+            .filter_map(|d| {
+                let id = d.tracker_id?;
+                if self.removed.contains(&id) {
+                    return None;
+                }
+                Some(changed.get(&id).map_or_else(|| d.clone(), |d| (*d).clone()))
+            })
+            .collect();
+        let kept: HashSet<u64> = detections.iter().filter_map(|d| d.tracker_id).collect();
+        detections.extend(self.changed.iter().filter(|d| d.tracker_id.is_some_and(|id| !kept.contains(&id))).cloned());
+        detections.extend(self.untracked.iter().cloned());
+
+        if let Some(order) = &self.order {
+            let mut by_id: HashMap<u64, Detection> = detections.into_iter().filter_map(|d| Some((d.tracker_id?, d))).collect();
+            let mut untracked = self.untracked.iter();
+            detections = order
+                .iter()
+                .map(|id| match id {
+                    Some(id) => by_id.remove(id),
+                    None => untracked.next().cloned(),
+                })
+                .collect::<Option<_>>()?;
+        }
+
+        Some(PerceptionFrame {
+            frame_id: self.frame_id,
+            timestamp: self.timestamp,
+            inference_time_ms: self.inference_time_ms,
+            detections,
+            ..base.clone()
+        })
+    }
+}
+
+fn tracked(frame: &PerceptionFrame) -> Option<HashMap<u64, &Detection>> {
+    let mut by_id = HashMap::new();
+    for detection in &frame.detections {
+        if let Some(id) = detection.tracker_id {
+            if by_id.insert(id, detection).is_some() {
+                return None;
+            }
+        }
+    }
+    Some(by_id)
+}
+
+// Publisher side: a keyframe every `keyframe_interval` frames per camera and
+// deltas against the previous frame in between. After a failed send the
+// next frame is a keyframe, since subscribers never saw the one it would
+// be based on.
+pub struct DeltaEncoder {
+    keyframe_interval: u32,
+    cameras: HashMap<String, (PerceptionFrame, u32)>,
+}
+
+impl DeltaEncoder {
+    pub fn new(keyframe_interval: u32) -> Self {
+        Self {
+            keyframe_interval: keyframe_interval.max(1),
+            cameras: HashMap::new(),
+        }
+    }
+
+    pub fn encode(&mut self, frame: &PerceptionFrame) -> FrameUpdate {
+        let delta = self
+            .cameras
+            .get(&frame.source_camera_id)
+            .filter(|(_, since_keyframe)| *since_keyframe < self.keyframe_interval)
+            .and_then(|(base, _)| FrameDelta::between(base, frame));
+
+        let since_keyframe = match &delta {
+            Some(_) => self.cameras.get(&frame.source_camera_id).map_or(0, |(_, n)| n + 1),
+            None => 1,
+        };
+        self.cameras.insert(frame.source_camera_id.clone(), (frame.clone(), since_keyframe));
+
+        match delta {
+            Some(delta) => FrameUpdate::Delta(delta),
+            None => FrameUpdate::Keyframe(frame.clone()),
+        }
+    }
+
+    pub fn force_keyframe(&mut self, camera_id: &str) {
+        self.cameras.remove(camera_id);
+    }
+}
+
+// Subscriber side. A delta whose base isn't the last frame seen from its
+// camera means something was lost; that camera's deltas are dropped until
+// its next keyframe.
+#[derive(Default)]
+pub struct DeltaDecoder {
+    frames: HashMap<String, PerceptionFrame>,
+}
+
+impl DeltaDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apply(&mut self, update: FrameUpdate) -> Option<PerceptionFrame> {
+        let frame = match update {
+            FrameUpdate::Keyframe(frame) => frame,
+            FrameUpdate::Delta(delta) => {
+                let rebuilt = self.frames.get(&delta.source_camera_id).and_then(|base| delta.apply(base));
+                match rebuilt {
+                    Some(frame) => frame,
+                    None => {
+                        warn!(
+                            "Dropping delta for frame {} from {}: base frame {} missing, waiting for a keyframe",
+                            delta.frame_id, delta.source_camera_id, delta.base_frame_id
+                        );
+                        self.frames.remove(&delta.source_camera_id);
+                        return None;
+                    }
+                }
+            }
+        };
+        self.frames.insert(frame.source_camera_id.clone(), frame.clone());
+        Some(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aetherforge_common::{BBox, CoordinateSpace};
+
+    fn detection(tracker_id: Option<u64>, x: f32) -> Detection {
+        Detection {
+            bbox: BBox::new(x, 100.0, x + 40.0, 180.0),
+            confidence: 0.9,
+            class_id: 0,
+            class_label: "person".to_string(),
+            tracker_id,
+            oriented: None,
+        }
+    }
+
+    fn frame(frame_id: u64, detections: Vec<Detection>) -> PerceptionFrame {
+        PerceptionFrame {
+            frame_id,
+            timestamp: 1_700_000_000_000 + frame_id * 33,
+            source_camera_id: "cam-1".to_string(),
+            image_width: 1920,
+            image_height: 1080,
+            model_version: "yolov8n".to_string(),
+            inference_time_ms: 8.5,
+            detections,
+            camera_intrinsics: None,
+            camera_extrinsics: None,
+            coordinate_space: CoordinateSpace::Pixels,
+        }
+    }
+
+    // Loading bay: parked pallets and a forklift that moves, one worker who
+    // leaves, one who arrives, and an untracked detection
+    fn scene() -> Vec<PerceptionFrame> {
+        let parked: Vec<Detection> = (1..=20).map(|id| detection(Some(id), id as f32 * 50.0)).collect();
+        (0..10u64)
+            .map(|i| {
+                let mut detections = parked.clone();
+                detections.push(detection(Some(100), 10.0 * i as f32));
+                if i < 5 {
+                    detections.insert(3, detection(Some(200), 700.0));
+                } else {
+                    detections.insert(0, detection(Some(300), 900.0));
+                }
+                if i % 2 == 0 {
+                    detections.push(detection(None, 1200.0));
+                }
+                frame(i, detections)
+            })
+            .collect()
+    }
+
+    fn size(update: &FrameUpdate) -> usize {
+        bincode::serialize(update).unwrap().len()
+    }
+
+    #[test]
+    fn test_stationary_scene_produces_small_deltas() {
+        let parked: Vec<Detection> = (1..=20).map(|id| detection(Some(id), id as f32 * 50.0)).collect();
+        let mut encoder = DeltaEncoder::new(30);
+
+        let keyframe = encoder.encode(&frame(0, parked.clone()));
+        assert!(matches!(keyframe, FrameUpdate::Keyframe(_)));
+        let delta = encoder.encode(&frame(1, parked));
+        let FrameUpdate::Delta(changes) = &delta else { panic!("expected a delta") };
+
+        assert!(changes.removed.is_empty() && changes.changed.is_empty() && changes.order.is_none());
+        assert!(size(&delta) * 10 < size(&keyframe), "delta {} vs keyframe {} bytes", size(&delta), size(&keyframe));
+    }
+
+    #[test]
+    fn test_reconstruction_matches_full_frames() {
+        let frames = scene();
+        let mut encoder = DeltaEncoder::new(4);
+        let mut decoder = DeltaDecoder::new();
+
+        let updates: Vec<FrameUpdate> = frames.iter().map(|f| encoder.encode(f)).collect();
+        let keyframes: Vec<u64> = updates
+            .iter()
+            .filter_map(|u| match u {
+                FrameUpdate::Keyframe(f) => Some(f.frame_id),
+                FrameUpdate::Delta(_) => None,
+            })
+            .collect();
+        assert_eq!(keyframes, vec![0, 4, 8]);
+
+        for (update, expected) in updates.into_iter().zip(&frames) {
+            assert_eq!(decoder.apply(update).as_ref(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_lost_frame_waits_for_next_keyframe() {
+        let frames = scene();
+        let mut encoder = DeltaEncoder::new(4);
+        let mut decoder = DeltaDecoder::new();
+        let updates: Vec<FrameUpdate> = frames.iter().map(|f| encoder.encode(f)).collect();
+
+        // Frame 1 never arrives
+        let received: Vec<Option<u64>> = updates
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| *i != 1)
+            .map(|(_, update)| decoder.apply(update).map(|f| f.frame_id))
+            .collect();
+        assert_eq!(received, vec![Some(0), None, None, Some(4), Some(5), Some(6), Some(7), Some(8), Some(9)]);
+
+        // A failed send on the publisher makes the next frame a keyframe
+        let mut encoder = DeltaEncoder::new(30);
+        encoder.encode(&frames[0]);
+        encoder.force_keyframe("cam-1");
+        assert!(matches!(encoder.encode(&frames[1]), FrameUpdate::Keyframe(_)));
+    }
+}
@@ -0,0 +1,145 @@
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{BaseProducer, BaseRecord, Producer};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use super::kafka_subscriber::ENVELOPE_HEADER;
+use super::{
+    chunking, CompressionStrategy, MessageEnvelope, MessagePublisher, MessageSigner, MessageType, SystemAlert,
+    SystemHealth,
+};
+use crate::config::MessagingConfig;
+use crate::error::{PerceptionError, Result};
+use crate::utils::metrics::Metrics;
+use aetherforge_common::{FusionResult, PerceptionFrame};
+
+// Publishes to `topic` on the brokers listed in `endpoint`, framed the way
+// `KafkaSubscriber` reads it: the bincode envelope in the ENVELOPE_HEADER
+// header, the (possibly compressed) payload as the record value. Records
+// are keyed by camera id, so a camera's messages, and the chunks of each,
+// stay in order on one partition.
+pub struct KafkaPublisher {
+    producer: Option<BaseProducer>,
+    config: MessagingConfig,
+    metrics: Arc<Metrics>,
+    sequence_number: AtomicU64,
+    compression: CompressionStrategy,
+    signer: Option<MessageSigner>,
+}
+
+impl KafkaPublisher {
+    pub fn new(config: &MessagingConfig, metrics: Arc<Metrics>) -> Result<Self> {
+        Ok(Self {
+            producer: None,
+            config: config.clone(),
+            metrics,
+            sequence_number: AtomicU64::new(0),
+            compression: CompressionStrategy::from_config(&config.compression),
+            signer: MessageSigner::from_config(&config.security)?,
+        })
+    }
+
+    // Queues one record per chunk; delivery failures past
+    // message_timeout_ms are logged by the client
+    fn send(&self, envelope: &MessageEnvelope, payload: &[u8]) -> Result<()> {
+        let producer = self.producer.as_ref()
+            .ok_or_else(|| PerceptionError::MessagingError("Not connected".to_string()))?;
+
+        for (mut envelope, part) in chunking::split(envelope, payload, self.config.max_payload_bytes) {
+            if let Some(signer) = &self.signer {
+                signer.sign(&mut envelope, &part)?;
+            }
+
+            let serialized_envelope = bincode::serialize(&envelope)
+                .map_err(|e| PerceptionError::MessagingError(format!("Envelope serialization failed: {}", e)))?;
+            let headers = OwnedHeaders::new().insert(Header { key: ENVELOPE_HEADER, value: Some(&serialized_envelope) });
+
+            producer
+                .send(BaseRecord::to(&self.config.topic).key(&envelope.camera_id).payload(&part).headers(headers))
+                .map_err(|(e, _)| PerceptionError::MessagingError(format!("Failed to queue record: {}", e)))?;
+        }
+
+        // Serves delivery reports without blocking
+        producer.poll(Duration::ZERO);
+        Ok(())
+    }
+
+    fn publish<T: Serialize>(&self, message_type: MessageType, camera_id: &str, timestamp: u64, data: &T) -> Result<()> {
+        let start_time = Instant::now();
+        let serialized = bincode::serialize(data)
+            .map_err(|e| PerceptionError::MessagingError(format!("Serialization failed: {}", e)))?;
+        let compressed = self.compression.compress(&serialized)?;
+
+        let envelope = MessageEnvelope {
+            message_type,
+            camera_id: camera_id.to_string(),
+            sequence_number: self.sequence_number.fetch_add(1, Ordering::Relaxed),
+            timestamp,
+            compression: self.compression.to_string(),
+            original_size: serialized.len(),
+            compressed_size: compressed.len(),
+            signature: None,
+            chunk: None,
+        };
+
+        self.send(&envelope, &compressed)?;
+        self.metrics.record_message_sent(compressed.len(), start_time.elapsed());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MessagePublisher for KafkaPublisher {
+    async fn publish_perception_frame(&self, frame: &PerceptionFrame) -> Result<()> {
+        self.publish(MessageType::PerceptionFrame, &frame.source_camera_id, frame.timestamp, frame)
+    }
+
+    async fn publish_fusion_result(&self, result: &FusionResult) -> Result<()> {
+        self.publish(MessageType::FusionResult, "", result.timestamp, result)
+    }
+
+    async fn publish_system_health(&self, health: &SystemHealth) -> Result<()> {
+        self.publish(MessageType::SystemHealth, "", health.timestamp, health)
+    }
+
+    async fn publish_alert(&self, alert: &SystemAlert) -> Result<()> {
+        self.publish(MessageType::Alert, "", alert.timestamp, alert)
+    }
+
+    // Dead letters are stored whole and unsigned; they're chunked and
+    // signed as they go out
+    async fn publish_raw(&self, envelope: &MessageEnvelope, payload: &[u8]) -> Result<()> {
+        self.send(envelope, payload)
+    }
+
+    async fn connect(&mut self) -> Result<()> {
+        let producer: BaseProducer = ClientConfig::new()
+            .set("bootstrap.servers", &self.config.endpoint)
+            .set("message.timeout.ms", self.config.message_timeout_ms.to_string())
+            .set("queue.buffering.max.messages", self.config.max_queue_size.to_string())
+            .create()?;
+
+        self.producer = Some(producer);
+        info!("Kafka publisher producing {} to {}", self.config.topic, self.config.endpoint);
+
+        Ok(())
+    }
+
+    // Waits up to message_timeout_ms for queued records to be delivered
+    async fn disconnect(&mut self) -> Result<()> {
+        if let Some(producer) = self.producer.take() {
+            producer.flush(Duration::from_millis(self.config.message_timeout_ms))?;
+            info!("Kafka publisher disconnected");
+        }
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.producer.is_some()
+    }
+}
@@ -0,0 +1,237 @@
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{BaseConsumer, CommitMode, Consumer, ConsumerContext, Rebalance};
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::message::{Headers, Message};
+use rdkafka::{ClientContext, Offset, TopicPartitionList};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use super::subscriber::{MessageReader, MessageSubscriber};
+use super::{MessageEnvelope, MessageSigner};
+use crate::config::{KafkaConsumerConfig, OffsetCommit, StartOffset};
+use crate::error::{PerceptionError, Result};
+
+// Kafka record header carrying the bincode `MessageEnvelope`, as written by
+// `KafkaPublisher`; the record value is the payload, exactly as the second
+// ZeroMQ frame would be
+pub const ENVELOPE_HEADER: &str = "aetherforge-envelope";
+
+// Logs partition moves and, before partitions are taken away, commits what
+// has been processed from them so their next owner starts right after it
+struct GroupContext;
+
+impl ClientContext for GroupContext {}
+
+impl ConsumerContext for GroupContext {
+    fn pre_rebalance(&self, consumer: &BaseConsumer<Self>, rebalance: &Rebalance<'_>) {
+        if let Rebalance::Revoke(partitions) = rebalance {
+            info!("Kafka partitions revoked: {}", describe(partitions));
+            if let Err(e) = commit_stored(consumer) {
+                warn!("Committing offsets before rebalance failed: {}", e);
+            }
+        }
+    }
+
+    fn post_rebalance(&self, _consumer: &BaseConsumer<Self>, rebalance: &Rebalance<'_>) {
+        match rebalance {
+            Rebalance::Assign(partitions) => info!("Kafka partitions assigned: {}", describe(partitions)),
+            Rebalance::Error(e) => warn!("Kafka rebalance failed: {}", e),
+            Rebalance::Revoke(_) => {}
+        }
+    }
+}
+
+fn describe(partitions: &TopicPartitionList) -> String {
+    partitions
+        .elements()
+        .iter()
+        .map(|p| format!("{}[{}]", p.topic(), p.partition()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Nothing stored since the last commit is not an error
+fn commit_stored(consumer: &BaseConsumer<GroupContext>) -> Result<()> {
+    match consumer.commit_consumer_state(CommitMode::Sync) {
+        Ok(()) | Err(KafkaError::ConsumerCommit(RDKafkaErrorCode::NoOffset)) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Reads one node's topic as a member of a consumer group. A message's
+// offset is stored only once the caller asks for the next one, i.e. after
+// it was processed, and only stored offsets are ever committed; a restarted
+// consumer in the same group resumes right after the last committed one,
+// so nothing processed is read twice and nothing unprocessed is skipped.
+// Broker connections are re-established by the client with the configured
+// backoff; polling meanwhile just comes back empty.
+pub struct KafkaSubscriber {
+    consumer: BaseConsumer<GroupContext>,
+    topic: String,
+    receive_timeout: Duration,
+    reader: MessageReader,
+    delivered: Option<(i32, i64)>, // partition and offset of the last message read
+}
+
+impl KafkaSubscriber {
+    pub fn connect(
+        config: &KafkaConsumerConfig,
+        topic: &str,
+        receive_timeout: Duration,
+        verifier: Option<MessageSigner>,
+        chunk_timeout: Duration,
+        max_chunks: u32,
+    ) -> Result<Self> {
+        let consumer: BaseConsumer<GroupContext> = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("group.id", &config.group_id)
+            .set("enable.auto.commit", (config.offset_commit == OffsetCommit::Auto).to_string())
+            .set("auto.commit.interval.ms", config.auto_commit_interval_ms.to_string())
+            .set("enable.auto.offset.store", "false")
+            .set(
+                "auto.offset.reset",
+                match config.start_from {
+                    StartOffset::Earliest => "earliest",
+                    StartOffset::Latest => "latest",
+                },
+            )
+            .set("session.timeout.ms", config.session_timeout_ms.to_string())
+            .set("reconnect.backoff.ms", config.reconnect_backoff_ms.to_string())
+            .set("reconnect.backoff.max.ms", config.reconnect_backoff_max_ms.to_string())
+            .create_with_context(GroupContext)?;
+        consumer.subscribe(&[topic])?;
+        info!("Consuming {} from {} as group {}", topic, config.brokers, config.group_id);
+
+        Ok(Self {
+            consumer,
+            topic: topic.to_string(),
+            receive_timeout,
+            reader: MessageReader::new(verifier, chunk_timeout, max_chunks),
+            delivered: None,
+        })
+    }
+
+    fn store_delivered(&mut self) -> Result<()> {
+        if let Some((partition, offset)) = self.delivered.take() {
+            // The committed offset is the next one to read
+            let mut partitions = TopicPartitionList::new();
+            partitions.add_partition_offset(&self.topic, partition, Offset::Offset(offset + 1))?;
+            self.consumer.store_offsets(&partitions)?;
+        }
+        Ok(())
+    }
+}
+
+impl MessageSubscriber for KafkaSubscriber {
+    fn recv(&mut self) -> Result<Option<(MessageEnvelope, Vec<u8>)>> {
+        loop {
+            // Records that were only part of a chunked message count as
+            // read; a restart mid-message loses that message
+            self.store_delivered()?;
+
+            let message = match self.consumer.poll(self.receive_timeout) {
+                Some(message) => message?,
+                None => {
+                    self.reader.idle();
+                    return Ok(None);
+                }
+            };
+            self.delivered = Some((message.partition(), message.offset()));
+
+            let envelope = message
+                .headers()
+                .and_then(|headers| headers.iter().find(|header| header.key == ENVELOPE_HEADER))
+                .and_then(|header| header.value)
+                .ok_or_else(|| {
+                    PerceptionError::MessagingError(format!(
+                        "Record {} of {}[{}] has no {} header",
+                        message.offset(),
+                        self.topic,
+                        message.partition(),
+                        ENVELOPE_HEADER
+                    ))
+                })?
+                .to_vec();
+            let payload = message.payload().unwrap_or_default().to_vec();
+
+            if let Some(message) = self.reader.read(&self.topic, &envelope, payload)? {
+                return Ok(Some(message));
+            }
+        }
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.topic
+    }
+
+    // With OffsetCommit::Auto this happens in the background anyway
+    fn commit(&mut self) -> Result<()> {
+        self.store_delivered()?;
+        commit_stored(&self.consumer)
+    }
+}
+
+// The last message read was handled by the time the subscriber goes away
+impl Drop for KafkaSubscriber {
+    fn drop(&mut self) {
+        if let Err(e) = self.commit() {
+            warn!("Committing {} offsets on shutdown failed: {}", self.topic, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{MessagingConfig, MessagingProtocol};
+    use crate::messaging::{KafkaPublisher, MessagePublisher};
+    use crate::utils::metrics::Metrics;
+    use aetherforge_common::FusionResult;
+    use std::sync::Arc;
+
+    // Needs a Kafka broker that creates topics on first use, e.g.
+    // AETHERFORGE_TEST_KAFKA_BROKERS=localhost:9092
+    #[tokio::test]
+    #[ignore]
+    async fn test_restarted_consumer_resumes_from_committed_offset() {
+        let brokers = std::env::var("AETHERFORGE_TEST_KAFKA_BROKERS").expect("AETHERFORGE_TEST_KAFKA_BROKERS");
+        let topic = format!("aetherforge-test-{}-{}", std::process::id(), aetherforge_common::utils::current_timestamp_ms());
+
+        let messaging = MessagingConfig {
+            protocol: MessagingProtocol::Kafka,
+            endpoint: brokers.clone(),
+            topic: topic.clone(),
+            message_timeout_ms: 10_000,
+            ..MessagingConfig::default()
+        };
+        let mut publisher = KafkaPublisher::new(&messaging, Arc::new(Metrics::new())).unwrap();
+        publisher.connect().await.unwrap();
+        for timestamp in 0..5u64 {
+            let result = FusionResult { timestamp, objects: Vec::new(), fusion_confidence: 1.0, raw_fusion_confidence: 1.0 };
+            publisher.publish_fusion_result(&result).await.unwrap();
+        }
+        publisher.disconnect().await.unwrap();
+
+        let config = KafkaConsumerConfig {
+            brokers,
+            group_id: format!("{}-group", topic),
+            offset_commit: OffsetCommit::Manual,
+            start_from: StartOffset::Earliest,
+            ..KafkaConsumerConfig::default()
+        };
+        let connect = || KafkaSubscriber::connect(&config, &topic, Duration::from_secs(10), None, Duration::from_secs(5), 16).unwrap();
+
+        // Processes three results, then the aggregator restarts
+        let mut first = connect();
+        for expected in 0..3 {
+            assert_eq!(first.recv_fusion_result().unwrap().unwrap().timestamp, expected);
+        }
+        drop(first);
+
+        let mut second = connect();
+        assert_eq!(second.recv_fusion_result().unwrap().unwrap().timestamp, 3);
+        assert_eq!(second.recv_fusion_result().unwrap().unwrap().timestamp, 4);
+        second.receive_timeout = Duration::from_secs(1);
+        assert!(second.recv_fusion_result().unwrap().is_none());
+    }
+}
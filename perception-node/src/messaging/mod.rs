@@ -1,14 +1,37 @@
+pub mod chunking;
+pub mod dead_letter;
+pub mod deferred;
+pub mod delta;
+#[cfg(feature = "kafka")]
+pub mod kafka_publisher;
+#[cfg(feature = "kafka")]
+pub mod kafka_subscriber;
+pub mod signing;
+pub mod subscriber;
+
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tracing::{error, info, warn};
 
+use chunking::ChunkInfo;
+use dead_letter::{DeadLetter, DeadLetterQueue};
+use delta::{DeltaEncoder, FrameUpdate};
+pub use deferred::DeferredPublisher;
+#[cfg(feature = "kafka")]
+pub use kafka_publisher::KafkaPublisher;
+pub use signing::MessageSigner;
+
 use crate::{
     config::{MessagingConfig, MessagingProtocol, CompressionType},
     error::{Result, PerceptionError},
     utils::metrics::Metrics,
 };
-use aetherforge_common::{PerceptionFrame, FusionResult, SystemHealth};
+use crate::inference::InferenceMetrics;
+use aetherforge_common::{CameraStatus, PerceptionFrame, FusionResult};
 
 #[async_trait]
 pub trait MessagePublisher: Send + Sync {
@@ -16,6 +39,7 @@ pub trait MessagePublisher: Send + Sync {
     async fn publish_fusion_result(&self, result: &FusionResult) -> Result<()>;
     async fn publish_system_health(&self, health: &SystemHealth) -> Result<()>;
     async fn publish_alert(&self, alert: &SystemAlert) -> Result<()>;
+    async fn publish_raw(&self, envelope: &MessageEnvelope, payload: &[u8]) -> Result<()>;
     async fn connect(&mut self) -> Result<()>;
     async fn disconnect(&mut self) -> Result<()>;
     fn is_connected(&self) -> bool;
@@ -26,15 +50,85 @@ pub struct MultiProtocolPublisher {
     fallback: Option<Box<dyn MessagePublisher>>,
     config: MessagingConfig,
     metrics: Arc<Metrics>,
-    connection_status: ConnectionStatus,
+    status: Arc<MessagingStatus>,
+    dead_letters: Option<Arc<DeadLetterQueue>>,
+    dead_letter_sequence: AtomicU64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ConnectionStatus {
     Connected,
     Disconnected,
     Degraded, // Using fallback
 }
 
+impl ConnectionStatus {
+    // A node publishing only through its fallback is degraded, not down
+    pub fn node_status(&self) -> NodeStatus {
+        match self {
+            Self::Connected => NodeStatus::Healthy,
+            Self::Degraded => NodeStatus::Degraded,
+            Self::Disconnected => NodeStatus::Unhealthy,
+        }
+    }
+}
+
+// Publisher state shared with the health endpoint; updated on every publish
+#[derive(Debug)]
+pub struct MessagingStatus {
+    connection: AtomicU8,
+    last_success_ms: AtomicU64, // 0 until the first successful publish
+}
+
+impl MessagingStatus {
+    pub fn new() -> Self {
+        Self {
+            connection: AtomicU8::new(ConnectionStatus::Disconnected as u8),
+            last_success_ms: AtomicU64::new(0),
+        }
+    }
+
+    pub fn connection(&self) -> ConnectionStatus {
+        match self.connection.load(Ordering::Relaxed) {
+            x if x == ConnectionStatus::Connected as u8 => ConnectionStatus::Connected,
+            x if x == ConnectionStatus::Degraded as u8 => ConnectionStatus::Degraded,
+            _ => ConnectionStatus::Disconnected,
+        }
+    }
+
+    pub fn set_connection(&self, status: ConnectionStatus) {
+        self.connection.store(status as u8, Ordering::Relaxed);
+    }
+
+    // A publish went out, through the primary or the fallback
+    pub fn record_success(&self, status: ConnectionStatus) {
+        self.set_connection(status);
+        self.last_success_ms.store(aetherforge_common::utils::current_timestamp_ms(), Ordering::Relaxed);
+    }
+
+    pub fn last_success_ms(&self) -> Option<u64> {
+        match self.last_success_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(ms),
+        }
+    }
+}
+
+impl Default for MessagingStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessagingHealthReport {
+    pub status: ConnectionStatus,
+    pub node_status: NodeStatus,
+    pub last_successful_publish_ms: Option<u64>,
+    pub queue_depth: usize, // dead-lettered messages waiting to be re-sent
+    pub buffered_frames: usize, // held in memory while degraded, see DeferredPublisher
+}
+
 impl MultiProtocolPublisher {
     pub fn new(config: MessagingConfig, metrics: Arc<Metrics>) -> Result<Self> {
         let primary = Self::create_publisher(&config, &metrics)?;
@@ -44,76 +138,194 @@ impl MultiProtocolPublisher {
             None
         };
         
+        let dead_letters = if config.dead_letter.enabled {
+            Some(Arc::new(DeadLetterQueue::open(&config.dead_letter)?.with_ttl(config.message_ttl_ms)))
+        } else {
+            None
+        };
+        
         Ok(Self {
             primary,
             fallback,
             config,
             metrics,
-            connection_status: ConnectionStatus::Disconnected,
+            status: Arc::new(MessagingStatus::new()),
+            dead_letters,
+            dead_letter_sequence: AtomicU64::new(0),
         })
     }
     
+    pub fn start_dead_letter_resender(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let dead_letters = self.dead_letters.clone()?;
+        let interval = Duration::from_millis(self.config.dead_letter.retry_interval_ms);
+        
+        Some(dead_letters.spawn_resender(self.clone(), interval))
+    }
+    
+    pub fn dead_letter_count(&self) -> usize {
+        self.dead_letters.as_ref().map(|dlq| dlq.len()).unwrap_or(0)
+    }
+    
+    pub fn connection_status(&self) -> ConnectionStatus {
+        self.status.connection()
+    }
+    
+    pub fn health(&self) -> MessagingHealthReport {
+        let status = self.status.connection();
+        MessagingHealthReport {
+            status,
+            node_status: status.node_status(),
+            last_successful_publish_ms: self.status.last_success_ms(),
+            queue_depth: self.dead_letter_count(),
+            buffered_frames: 0,
+        }
+    }
+    
+    // Persists a message that failed every publisher. Returns false if no
+    // dead-letter queue is configured or the write itself failed.
+    fn dead_letter<T: Serialize>(&self, message_type: MessageType, camera_id: &str, timestamp: u64, data: &T) -> bool {
+        let dead_letters = match &self.dead_letters {
+            Some(dead_letters) => dead_letters,
+            None => return false,
+        };
+        
+        let payload = match bincode::serialize(data) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to serialize message for dead-lettering: {}", e);
+                return false;
+            }
+        };
+        
+        let envelope = MessageEnvelope {
+            message_type,
+            camera_id: camera_id.to_string(),
+            sequence_number: self.dead_letter_sequence.fetch_add(1, Ordering::Relaxed),
+            timestamp,
+            compression: CompressionStrategy::None.to_string(),
+            original_size: payload.len(),
+            compressed_size: payload.len(),
+            signature: None,
+            chunk: None,
+        };
+        
+        match dead_letters.push(&DeadLetter { envelope, payload }) {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Failed to persist dead letter: {}", e);
+                false
+            }
+        }
+    }
+    
     fn create_publisher(config: &MessagingConfig, metrics: &Arc<Metrics>) -> Result<Box<dyn MessagePublisher>> {
         match config.protocol {
             MessagingProtocol::ZeroMQ => Ok(Box::new(ZmqPublisher::new(config, metrics.clone())?)),
             MessagingProtocol::Redis => Ok(Box::new(RedisPublisher::new(config, metrics.clone())?)),
+            #[cfg(feature = "kafka")]
             MessagingProtocol::Kafka => Ok(Box::new(KafkaPublisher::new(config, metrics.clone())?)),
+            #[cfg(not(feature = "kafka"))]
+            MessagingProtocol::Kafka => Err(PerceptionError::ConfigError(
+                "Kafka messaging needs the node built with the kafka feature".to_string(),
+            )),
             MessagingProtocol::MQTT => Ok(Box::new(MqttPublisher::new(config, metrics.clone())?)),
         }
     }
     
-    async fn try_publish<T, F>(&mut self, data: &T, publish_fn: F) -> Result<()>
+    async fn try_publish<T, F>(&mut self, data: &T, route: (MessageType, &str, u64), publish_fn: F) -> Result<()>
     where
+        T: Serialize,
         F: Fn(&mut Box<dyn MessagePublisher>, &T) -> Result<()>,
     {
+        let (message_type, camera_id, timestamp) = route;
+        
         // Try primary publisher
         match publish_fn(&mut self.primary, data) {
             Ok(()) => {
-                self.connection_status = ConnectionStatus::Connected;
+                self.status.record_success(ConnectionStatus::Connected);
                 Ok(())
             }
             Err(e) => {
                 warn!("Primary publisher failed: {}", e);
                 self.metrics.increment_message_failures();
                 
+                // The fallback and a re-send would fail the same way
+                if !e.is_retryable() {
+                    return Err(e);
+                }
+                
                 // Try fallback if available
                 if let Some(ref mut fallback) = self.fallback {
                     match publish_fn(fallback, data) {
                         Ok(()) => {
-                            self.connection_status = ConnectionStatus::Degraded;
+                            self.status.record_success(ConnectionStatus::Degraded);
                             Ok(())
                         }
                         Err(e) => {
                             error!("Fallback publisher also failed: {}", e);
-                            self.connection_status = ConnectionStatus::Disconnected;
-                            Err(e)
+                            self.status.set_connection(ConnectionStatus::Disconnected);
+                            self.dead_letter_or(e, message_type, camera_id, timestamp, data)
                         }
                     }
                 } else {
-                    self.connection_status = ConnectionStatus::Disconnected;
-                    Err(e)
+                    self.status.set_connection(ConnectionStatus::Disconnected);
+                    self.dead_letter_or(e, message_type, camera_id, timestamp, data)
                 }
             }
         }
     }
+
+    // A dead-lettered message is durably queued for re-send, so the caller
+    // sees success; otherwise the original publish error is surfaced.
+    fn dead_letter_or<T: Serialize>(
+        &self,
+        error: PerceptionError,
+        message_type: MessageType,
+        camera_id: &str,
+        timestamp: u64,
+        data: &T,
+    ) -> Result<()> {
+        if self.dead_letter(message_type, camera_id, timestamp, data) {
+            warn!("All publishers failed, message dead-lettered: {}", error);
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
 }
 
 #[async_trait]
 impl MessagePublisher for MultiProtocolPublisher {
     async fn publish_perception_frame(&self, frame: &PerceptionFrame) -> Result<()> {
-        self.try_publish(frame, |publisher, data| publisher.publish_perception_frame(data)).await
+        let route = (MessageType::PerceptionFrame, frame.source_camera_id.as_str(), frame.timestamp);
+        self.try_publish(frame, route, |publisher, data| publisher.publish_perception_frame(data)).await
     }
     
     async fn publish_fusion_result(&self, result: &FusionResult) -> Result<()> {
-        self.try_publish(result, |publisher, data| publisher.publish_fusion_result(data)).await
+        let route = (MessageType::FusionResult, "", aetherforge_common::utils::current_timestamp_ms());
+        self.try_publish(result, route, |publisher, data| publisher.publish_fusion_result(data)).await
     }
     
     async fn publish_system_health(&self, health: &SystemHealth) -> Result<()> {
-        self.try_publish(health, |publisher, data| publisher.publish_system_health(data)).await
+        let route = (MessageType::SystemHealth, "", health.timestamp);
+        self.try_publish(health, route, |publisher, data| publisher.publish_system_health(data)).await
     }
     
     async fn publish_alert(&self, alert: &SystemAlert) -> Result<()> {
-        self.try_publish(alert, |publisher, data| publisher.publish_alert(data)).await
+        let route = (MessageType::Alert, "", alert.timestamp);
+        self.try_publish(alert, route, |publisher, data| publisher.publish_alert(data)).await
+    }
+    
+    // Raw publishes come from the dead-letter re-sender, so a failure here
+    // is returned rather than dead-lettered again.
+    async fn publish_raw(&self, envelope: &MessageEnvelope, payload: &[u8]) -> Result<()> {
+        match self.primary.publish_raw(envelope, payload).await {
+            Ok(()) => Ok(()),
+            Err(e) => match &self.fallback {
+                Some(fallback) => fallback.publish_raw(envelope, payload).await,
+                None => Err(e),
+            },
+        }
     }
     
     async fn connect(&mut self) -> Result<()> {
@@ -126,12 +338,12 @@ impl MessagePublisher for MultiProtocolPublisher {
             // Try fallback
             if let Some(ref mut fallback) = self.fallback {
                 fallback.connect().await?;
-                self.connection_status = ConnectionStatus::Degraded;
+                self.status.set_connection(ConnectionStatus::Degraded);
             } else {
                 return Err(e);
             }
         } else {
-            self.connection_status = ConnectionStatus::Connected;
+            self.status.set_connection(ConnectionStatus::Connected);
         }
         
         Ok(())
@@ -152,7 +364,7 @@ impl MessagePublisher for MultiProtocolPublisher {
             }
         }
         
-        self.connection_status = ConnectionStatus::Disconnected;
+        self.status.set_connection(ConnectionStatus::Disconnected);
         
         if errors.is_empty() {
             Ok(())
@@ -162,67 +374,111 @@ impl MessagePublisher for MultiProtocolPublisher {
     }
     
     fn is_connected(&self) -> bool {
-        matches!(self.connection_status, ConnectionStatus::Connected | ConnectionStatus::Degraded)
+        matches!(self.status.connection(), ConnectionStatus::Connected | ConnectionStatus::Degraded)
     }
 }
 
-// Enhanced ZeroMQ implementation with compression
+// Enhanced ZeroMQ implementation with compression. zmq sockets aren't Sync,
+// so the socket is only used under its lock.
 pub struct ZmqPublisher {
     context: zmq::Context,
-    socket: Option<zmq::Socket>,
+    socket: std::sync::Mutex<Option<zmq::Socket>>,
     config: MessagingConfig,
     metrics: Arc<Metrics>,
-    sequence_number: u64,
+    sequence_number: AtomicU64,
     compression: CompressionStrategy,
+    signer: Option<MessageSigner>,
+    deltas: Option<std::sync::Mutex<DeltaEncoder>>,
 }
 
 impl ZmqPublisher {
     pub fn new(config: &MessagingConfig, metrics: Arc<Metrics>) -> Result<Self> {
         let context = zmq::Context::new();
         let compression = CompressionStrategy::from_config(&config.compression);
+        let signer = MessageSigner::from_config(&config.security)?;
+        let deltas = config.delta_encoding.then(|| std::sync::Mutex::new(DeltaEncoder::new(config.keyframe_interval)));
         
         Ok(Self {
             context,
-            socket: None,
+            socket: std::sync::Mutex::new(None),
             config: config.clone(),
             metrics,
-            sequence_number: 0,
+            sequence_number: AtomicU64::new(0),
             compression,
+            signer,
+            deltas,
         })
     }
     
-    fn compress_data(&self, data: &[u8]) -> Result<Vec<u8>> {
-        match self.compression {
-            CompressionStrategy::None => Ok(data.to_vec()),
-            CompressionStrategy::Zstd => {
-                zstd::encode_all(data, 3)
-                    .map_err(|e| PerceptionError::MessagingError(format!("Zstd compression failed: {}", e)))
-            }
-            CompressionStrategy::Lz4 => {
-                lz4_flex::compress_prepend_size(data)
-                    .map_err(|e| PerceptionError::MessagingError(format!("LZ4 compression failed: {}", e)))
-            }
-            CompressionStrategy::Gzip => {
-                use flate2::{Compression, write::GzEncoder};
-                use std::io::Write;
-                
-                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
-                encoder.write_all(data)
-                    .map_err(|e| PerceptionError::MessagingError(format!("Gzip compression failed: {}", e)))?;
-                encoder.finish()
-                    .map_err(|e| PerceptionError::MessagingError(format!("Gzip compression failed: {}", e)))
+    // Sends the envelope and payload as one two-part message, or as several
+    // when the payload is over `max_payload_bytes`; each part is signed on
+    // its own so subscribers can check chunks before reassembling them
+    fn send(&self, envelope: &MessageEnvelope, payload: &[u8]) -> Result<()> {
+        let socket = self.socket.lock().unwrap();
+        let socket = socket.as_ref()
+            .ok_or_else(|| PerceptionError::MessagingError("Not connected".to_string()))?;
+        
+        for (mut envelope, part) in chunking::split(envelope, payload, self.config.max_payload_bytes) {
+            if let Some(signer) = &self.signer {
+                signer.sign(&mut envelope, &part)?;
             }
+            
+            let serialized_envelope = bincode::serialize(&envelope)
+                .map_err(|e| PerceptionError::MessagingError(format!("Envelope serialization failed: {}", e)))?;
+            
+            socket.send(&serialized_envelope, zmq::SNDMORE)
+                .map_err(|e| PerceptionError::MessagingError(format!("Failed to send envelope: {}", e)))?;
+            
+            socket.send(&part, 0)
+                .map_err(|e| PerceptionError::MessagingError(format!("Failed to send message: {}", e)))?;
         }
+        
+        Ok(())
+    }
+    
+    fn compress_data(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.compression.compress(data)
+    }
+    
+    // Fusion results, health and alerts: serialized, compressed and sent
+    // whole, with no delta encoding
+    fn publish_message<T: Serialize>(&self, message_type: MessageType, camera_id: &str, timestamp: u64, data: &T) -> Result<()> {
+        let start_time = std::time::Instant::now();
+        let serialized = bincode::serialize(data)
+            .map_err(|e| PerceptionError::MessagingError(format!("Serialization failed: {}", e)))?;
+        let compressed = self.compress_data(&serialized)?;
+        
+        let envelope = MessageEnvelope {
+            message_type,
+            camera_id: camera_id.to_string(),
+            sequence_number: self.sequence_number.fetch_add(1, Ordering::Relaxed),
+            timestamp,
+            compression: self.compression.to_string(),
+            original_size: serialized.len(),
+            compressed_size: compressed.len(),
+            signature: None,
+            chunk: None,
+        };
+        
+        self.send(&envelope, &compressed)?;
+        self.metrics.record_message_sent(compressed.len(), start_time.elapsed());
+        Ok(())
     }
 }
 
 #[async_trait]
 impl MessagePublisher for ZmqPublisher {
-    async fn publish_perception_frame(&mut self, frame: &PerceptionFrame) -> Result<()> {
+    async fn publish_perception_frame(&self, frame: &PerceptionFrame) -> Result<()> {
         let start_time = std::time::Instant::now();
         
-        // Serialize frame
-        let serialized = bincode::serialize(frame)
+        // Serialize frame, or its changes since the last one when delta encoding.
+        // Keyframes go out as plain PerceptionFrame messages.
+        let update = self.deltas.as_ref().map(|deltas| deltas.lock().unwrap().encode(frame));
+        let (message_type, serialized) = match &update {
+            Some(FrameUpdate::Delta(delta)) => (MessageType::PerceptionFrameDelta, bincode::serialize(delta)),
+            _ => (MessageType::PerceptionFrame, bincode::serialize(frame)),
+        };
+        let serialized = serialized
             .map_err(|e| PerceptionError::MessagingError(format!("Serialization failed: {}", e)))?;
         
         // Compress data
@@ -230,36 +486,46 @@ impl MessagePublisher for ZmqPublisher {
         
         // Create message envelope
         let envelope = MessageEnvelope {
-            message_type: MessageType::PerceptionFrame,
+            message_type,
             camera_id: frame.source_camera_id.clone(),
-            sequence_number: self.sequence_number,
+            sequence_number: self.sequence_number.fetch_add(1, Ordering::Relaxed),
             timestamp: frame.timestamp,
             compression: self.compression.to_string(),
             original_size: serialized.len(),
             compressed_size: compressed.len(),
+            signature: None,
+            chunk: None,
         };
         
-        let serialized_envelope = bincode::serialize(&envelope)
-            .map_err(|e| PerceptionError::MessagingError(format!("Envelope serialization failed: {}", e)))?;
-        
-        // Send message
-        if let Some(socket) = &self.socket {
-            socket.send(&serialized_envelope, zmq::SNDMORE)
-                .map_err(|e| PerceptionError::MessagingError(format!("Failed to send envelope: {}", e)))?;
-            
-            socket.send(&compressed, 0)
-                .map_err(|e| PerceptionError::MessagingError(format!("Failed to send message: {}", e)))?;
-            
-            self.sequence_number += 1;
-            self.metrics.record_message_sent(compressed.len(), start_time.elapsed());
-            
-            Ok(())
-        } else {
-            Err(PerceptionError::MessagingError("Not connected".to_string()))
+        // Send message; subscribers won't have this frame to apply the next delta to
+        if let Err(e) = self.send(&envelope, &compressed) {
+            if let Some(deltas) = &self.deltas {
+                deltas.lock().unwrap().force_keyframe(&frame.source_camera_id);
+            }
+            return Err(e);
         }
+        self.metrics.record_message_sent(compressed.len(), start_time.elapsed());
+        
+        Ok(())
     }
     
-    // Other publish methods implemented similarly
+    async fn publish_fusion_result(&self, result: &FusionResult) -> Result<()> {
+        self.publish_message(MessageType::FusionResult, "", result.timestamp, result)
+    }
+    
+    async fn publish_system_health(&self, health: &SystemHealth) -> Result<()> {
+        self.publish_message(MessageType::SystemHealth, "", health.timestamp, health)
+    }
+    
+    async fn publish_alert(&self, alert: &SystemAlert) -> Result<()> {
+        self.publish_message(MessageType::Alert, "", alert.timestamp, alert)
+    }
+    
+    // Dead letters are stored whole and unsigned; they're chunked and
+    // signed as they go out
+    async fn publish_raw(&self, envelope: &MessageEnvelope, payload: &[u8]) -> Result<()> {
+        self.send(envelope, payload)
+    }
     
     async fn connect(&mut self) -> Result<()> {
         let socket = self.context.socket(zmq::PUB)
@@ -281,39 +547,28 @@ impl MessagePublisher for ZmqPublisher {
                 .map_err(|e| PerceptionError::MessagingError(format!("Failed to connect: {}", e)))?;
         }
         
-        self.socket = Some(socket);
+        *self.socket.lock().unwrap() = Some(socket);
         info!("ZeroMQ publisher connected to {}", self.config.endpoint);
         
         Ok(())
     }
     
     async fn disconnect(&mut self) -> Result<()> {
-        if let Some(socket) = self.socket.take() {
-            // ZeroMQ sockets are automatically closed when dropped
+        // ZeroMQ sockets are automatically closed when dropped
+        if self.socket.lock().unwrap().take().is_some() {
             info!("ZeroMQ publisher disconnected");
         }
         Ok(())
     }
     
     fn is_connected(&self) -> bool {
-        self.socket.is_some()
-    }
-    
-    async fn publish_fusion_result(&self, result: &FusionResult) -> Result<()> {
-        todo!()
-    }
-    
-    async fn publish_system_health(&self, health: &SystemHealth) -> Result<()> {
-        todo!()
-    }
-    
-    async fn publish_alert(&self, alert: &SystemAlert) -> Result<()> {
-        todo!()
+        self.socket.lock().unwrap().is_some()
     }
 }
 
 // Support for other protocols (Redis, Kafka, MQTT) would be implemented similarly
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageEnvelope {
     pub message_type: MessageType,
     pub camera_id: String,
@@ -322,10 +577,14 @@ pub struct MessageEnvelope {
     pub compression: String,
     pub original_size: usize,
     pub compressed_size: usize,
+    pub signature: Option<Vec<u8>>, // HMAC-SHA256, see MessageSigner
+    pub chunk: Option<ChunkInfo>, // set on each part of a payload split by `chunking::split`
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MessageType {
     PerceptionFrame,
+    PerceptionFrameDelta, // a delta::FrameDelta against the camera's previous frame
     FusionResult,
     SystemHealth,
     Alert,
@@ -348,6 +607,27 @@ impl CompressionStrategy {
         }
     }
     
+    fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(data.to_vec()),
+            Self::Zstd => {
+                zstd::encode_all(data, 3)
+                    .map_err(|e| PerceptionError::MessagingError(format!("Zstd compression failed: {}", e)))
+            }
+            Self::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+            Self::Gzip => {
+                use flate2::{Compression, write::GzEncoder};
+                use std::io::Write;
+                
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)
+                    .map_err(|e| PerceptionError::MessagingError(format!("Gzip compression failed: {}", e)))?;
+                encoder.finish()
+                    .map_err(|e| PerceptionError::MessagingError(format!("Gzip compression failed: {}", e)))
+            }
+        }
+    }
+    
     fn to_string(&self) -> String {
         match self {
             Self::None => "none".to_string(),
@@ -359,6 +639,7 @@ impl CompressionStrategy {
 }
 
 // System health and alert structures
+#[derive(Serialize)]
 pub struct SystemHealth {
     pub node_id: String,
     pub status: NodeStatus,
@@ -367,15 +648,18 @@ pub struct SystemHealth {
     pub gpu_usage: Option<f32>,
     pub camera_status: Vec<CameraHealth>,
     pub inference_metrics: InferenceMetrics,
+    pub messaging_status: ConnectionStatus, // Degraded here makes the node Degraded
     pub timestamp: u64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum NodeStatus {
     Healthy,
     Degraded,
     Unhealthy,
 }
 
+#[derive(Serialize)]
 pub struct CameraHealth {
     pub camera_id: String,
     pub status: CameraStatus,
@@ -383,6 +667,7 @@ pub struct CameraHealth {
     pub latency_ms: f32,
 }
 
+#[derive(Debug, Clone, Serialize)]
 pub struct SystemAlert {
     pub severity: AlertSeverity,
     pub source: String,
@@ -391,9 +676,248 @@ pub struct SystemAlert {
     pub details: Option<serde_json::Value>,
 }
 
+impl SystemAlert {
+    // Alert for a failure, tagged with its code so consumers needn't parse the message
+    pub fn from_error(source: &str, error: &PerceptionError, timestamp: u64) -> Self {
+        Self {
+            severity: if error.is_retryable() { AlertSeverity::Warning } else { AlertSeverity::Error },
+            source: source.to_string(),
+            message: error.to_string(),
+            timestamp,
+            details: Some(serde_json::json!({
+                "error_code": error.code(),
+                "retryable": error.is_retryable(),
+            })),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum AlertSeverity {
     Info,
     Warning,
     Error,
     Critical,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DeadLetterConfig;
+    use std::sync::atomic::AtomicBool;
+    
+    // Publisher whose availability can be toggled to simulate an outage
+    struct FlakyPublisher {
+        available: Arc<AtomicBool>,
+        raw_sent: Arc<std::sync::Mutex<Vec<MessageEnvelope>>>,
+    }
+    
+    impl FlakyPublisher {
+        fn check(&self) -> Result<()> {
+            if self.available.load(Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err(PerceptionError::MessagingError("Broker unreachable".to_string()))
+            }
+        }
+    }
+    
+    #[async_trait]
+    impl MessagePublisher for FlakyPublisher {
+        async fn publish_perception_frame(&self, _frame: &PerceptionFrame) -> Result<()> { self.check() }
+        async fn publish_fusion_result(&self, _result: &FusionResult) -> Result<()> { self.check() }
+        async fn publish_system_health(&self, _health: &SystemHealth) -> Result<()> { self.check() }
+        async fn publish_alert(&self, _alert: &SystemAlert) -> Result<()> { self.check() }
+        
+        async fn publish_raw(&self, envelope: &MessageEnvelope, _payload: &[u8]) -> Result<()> {
+            self.check()?;
+            self.raw_sent.lock().unwrap().push(envelope.clone());
+            Ok(())
+        }
+        
+        async fn connect(&mut self) -> Result<()> { self.check() }
+        async fn disconnect(&mut self) -> Result<()> { Ok(()) }
+        fn is_connected(&self) -> bool { self.available.load(Ordering::SeqCst) }
+    }
+    
+    #[tokio::test]
+    async fn test_failed_publish_is_dead_lettered_and_resent() {
+        let dir = std::env::temp_dir().join(format!("aetherforge-dlq-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        
+        let mut config = MessagingConfig::default();
+        config.dead_letter = DeadLetterConfig {
+            enabled: true,
+            path: dir.clone(),
+            max_messages: 10,
+            retry_interval_ms: 10,
+        };
+        
+        let primary_up = Arc::new(AtomicBool::new(false));
+        let primary_sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let fallback_up = Arc::new(AtomicBool::new(false));
+        
+        let publisher = MultiProtocolPublisher {
+            primary: Box::new(FlakyPublisher { available: primary_up.clone(), raw_sent: primary_sent.clone() }),
+            fallback: Some(Box::new(FlakyPublisher {
+                available: fallback_up.clone(),
+                raw_sent: Arc::new(std::sync::Mutex::new(Vec::new())),
+            })),
+            config: config.clone(),
+            metrics: Arc::new(Metrics::new()),
+            status: Arc::new(MessagingStatus::new()),
+            dead_letters: Some(Arc::new(DeadLetterQueue::open(&config.dead_letter).unwrap())),
+            dead_letter_sequence: AtomicU64::new(0),
+        };
+        
+        let frame = PerceptionFrame::new(1, 7, 1_700_000_000_000, "cam-1".to_string(), 640, 480, "1.0".to_string());
+        publisher.publish_perception_frame(&frame).await.unwrap();
+        assert_eq!(publisher.dead_letter_count(), 1);
+        
+        // Nothing is re-sent while the outage lasts
+        let dead_letters = publisher.dead_letters.clone().unwrap();
+        assert!(dead_letters.resend(&publisher).await.is_err());
+        assert_eq!(publisher.dead_letter_count(), 1);
+        
+        primary_up.store(true, Ordering::SeqCst);
+        assert_eq!(dead_letters.resend(&publisher).await.unwrap(), 1);
+        assert_eq!(publisher.dead_letter_count(), 0);
+        
+        let sent = primary_sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].message_type, MessageType::PerceptionFrame);
+        assert_eq!(sent[0].camera_id, "cam-1");
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_stale_dead_lettered_frames_are_dropped_not_resent() {
+        let dir = std::env::temp_dir().join(format!("aetherforge-dlq-ttl-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        
+        let config = DeadLetterConfig { enabled: true, path: dir.clone(), max_messages: 10, retry_interval_ms: 10 };
+        let dead_letters = DeadLetterQueue::open(&config).unwrap().with_ttl(2000);
+        let now = aetherforge_common::utils::current_timestamp_ms();
+        let letter = |message_type, timestamp| DeadLetter {
+            envelope: MessageEnvelope {
+                message_type,
+                camera_id: "cam-1".to_string(),
+                sequence_number: 0,
+                timestamp,
+                compression: CompressionStrategy::None.to_string(),
+                original_size: 0,
+                compressed_size: 0,
+                signature: None,
+                chunk: None,
+            },
+            payload: Vec::new(),
+        };
+        dead_letters.push(&letter(MessageType::PerceptionFrame, now - 5000)).unwrap();
+        dead_letters.push(&letter(MessageType::Alert, now - 5000)).unwrap();
+        dead_letters.push(&letter(MessageType::PerceptionFrame, now)).unwrap();
+        
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let publisher = FlakyPublisher { available: Arc::new(AtomicBool::new(true)), raw_sent: sent.clone() };
+        assert_eq!(dead_letters.resend(&publisher).await.unwrap(), 2);
+        assert!(dead_letters.is_empty());
+        
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.iter().map(|envelope| envelope.message_type).collect::<Vec<_>>(), vec![MessageType::Alert, MessageType::PerceptionFrame]);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_health_endpoint_reports_degraded_on_fallback() {
+        use axum::body::Body;
+        use axum::http::{Request, StatusCode};
+        use tower::ServiceExt;
+        
+        let publisher = Arc::new(MultiProtocolPublisher {
+            primary: Box::new(FlakyPublisher {
+                available: Arc::new(AtomicBool::new(false)),
+                raw_sent: Arc::new(std::sync::Mutex::new(Vec::new())),
+            }),
+            fallback: Some(Box::new(FlakyPublisher {
+                available: Arc::new(AtomicBool::new(true)),
+                raw_sent: Arc::new(std::sync::Mutex::new(Vec::new())),
+            })),
+            config: MessagingConfig::default(),
+            metrics: Arc::new(Metrics::new()),
+            status: Arc::new(MessagingStatus::new()),
+            dead_letters: None,
+            dead_letter_sequence: AtomicU64::new(0),
+        });
+        assert_eq!(publisher.health().last_successful_publish_ms, None);
+        
+        let frame = PerceptionFrame::new(1, 7, 1_700_000_000_000, "cam-1".to_string(), 640, 480, "1.0".to_string());
+        publisher.publish_perception_frame(&frame).await.unwrap();
+        
+        let app = crate::control::router(crate::control::ControlState {
+            inference_stats: Arc::new(crate::inference::InferenceStats::new(1)),
+            debug_overlay: None,
+            messaging: Some(publisher.clone()),
+            scorer: None,
+            scoring_token: None,
+            camera_warmup: None,
+        });
+        let response = app
+            .oneshot(Request::builder().uri("/health/messaging").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["status"], "Degraded");
+        assert_eq!(report["node_status"], "Degraded");
+        assert!(report["last_successful_publish_ms"].as_u64().unwrap() > 0);
+        assert_eq!(report["queue_depth"], 0);
+    }
+    
+    #[tokio::test]
+    async fn test_alert_is_published_over_zmq() {
+        let config = MessagingConfig {
+            endpoint: "inproc://alerts".to_string(),
+            compression: CompressionType::None,
+            ..MessagingConfig::default()
+        };
+        let mut publisher = ZmqPublisher::new(&config, Arc::new(Metrics::new())).unwrap();
+        
+        // inproc only reaches sockets of the same context; the subscriber
+        // binds, as the publisher connects to anything but tcp://*:
+        let subscriber = publisher.context.socket(zmq::SUB).unwrap();
+        subscriber.bind("inproc://alerts").unwrap();
+        subscriber.set_subscribe(b"").unwrap();
+        subscriber.set_rcvtimeo(100).unwrap();
+        publisher.connect().await.unwrap();
+        
+        let alert = SystemAlert {
+            severity: AlertSeverity::Warning,
+            source: "camera:dock-1".to_string(),
+            message: "Camera stalled".to_string(),
+            timestamp: 42,
+            details: Some(serde_json::json!({ "stalled_ms": 5000 })),
+        };
+        
+        // The subscription reaches the publisher asynchronously; sends
+        // before then are dropped
+        let mut parts = None;
+        for _ in 0..50 {
+            publisher.publish_alert(&alert).await.unwrap();
+            if let Ok(received) = subscriber.recv_multipart(0) {
+                parts = Some(received);
+                break;
+            }
+        }
+        let parts = parts.expect("alert never arrived");
+        
+        let envelope: MessageEnvelope = bincode::deserialize(&parts[0]).unwrap();
+        assert_eq!(envelope.message_type, MessageType::Alert);
+        assert_eq!(envelope.timestamp, 42);
+        assert_eq!(envelope.compression, "none");
+        assert_eq!(parts[1], bincode::serialize(&alert).unwrap());
+    }
+}
@@ -0,0 +1,169 @@
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+use super::chunking::ChunkAssembler;
+use super::delta::{DeltaDecoder, FrameUpdate};
+use super::{MessageEnvelope, MessageSigner, MessageType};
+use crate::error::{PerceptionError, Result};
+use aetherforge_common::{FusionResult, PerceptionFrame};
+
+// Whole messages from one source, whatever transport carries them
+pub trait MessageSubscriber: Send {
+    // Next whole message, or None if nothing completed one within the
+    // receive timeout
+    fn recv(&mut self) -> Result<Option<(MessageEnvelope, Vec<u8>)>>;
+
+    // Where messages come from, for logs
+    fn endpoint(&self) -> &str;
+
+    // Marks everything received so far as processed, on transports that
+    // remember a reader's position
+    fn commit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    // Next fusion result, skipping other message types
+    fn recv_fusion_result(&mut self) -> Result<Option<FusionResult>> {
+        while let Some((envelope, payload)) = self.recv()? {
+            if envelope.message_type == MessageType::FusionResult {
+                let result = bincode::deserialize(&payload)
+                    .map_err(|e| PerceptionError::SerializationError(format!("Bad fusion result from {}: {}", self.endpoint(), e)))?;
+                return Ok(Some(result));
+            }
+        }
+        Ok(None)
+    }
+}
+
+// Turns received (envelope, payload) pairs into whole messages: with a
+// verifier, messages failing signature checks are rejected before they're
+// decoded, and chunked payloads are reassembled before decompression
+pub(super) struct MessageReader {
+    verifier: Option<MessageSigner>,
+    chunks: ChunkAssembler,
+}
+
+impl MessageReader {
+    pub(super) fn new(verifier: Option<MessageSigner>, chunk_timeout: Duration, max_chunks: u32) -> Self {
+        Self {
+            verifier,
+            chunks: ChunkAssembler::new(chunk_timeout, max_chunks),
+        }
+    }
+
+    pub(super) fn read(&mut self, source: &str, envelope: &[u8], payload: Vec<u8>) -> Result<Option<(MessageEnvelope, Vec<u8>)>> {
+        let envelope: MessageEnvelope = bincode::deserialize(envelope)
+            .map_err(|e| PerceptionError::SerializationError(format!("Bad envelope from {}: {}", source, e)))?;
+        if let Some(verifier) = &self.verifier {
+            verifier.verify(&envelope, &payload)?;
+        }
+
+        match self.chunks.push(envelope, payload, Instant::now()) {
+            Some((envelope, payload)) => {
+                let payload = decompress(&envelope.compression, payload)?;
+                Ok(Some((envelope, payload)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Called when nothing arrived, so stale partial messages are let go
+    pub(super) fn idle(&mut self) {
+        self.chunks.expire(Instant::now());
+    }
+}
+
+// Reads what `ZmqPublisher` sends: a bincode envelope, then the
+// (possibly compressed) bincode payload. Delta-encoded perception frames
+// are rebuilt into full ones.
+pub struct ZmqSubscriber {
+    socket: zmq::Socket,
+    endpoint: String,
+    reader: MessageReader,
+    deltas: DeltaDecoder,
+}
+
+impl ZmqSubscriber {
+    pub fn connect(
+        context: &zmq::Context,
+        endpoint: &str,
+        receive_timeout_ms: i32,
+        verifier: Option<MessageSigner>,
+        chunk_timeout: Duration,
+        max_chunks: u32,
+    ) -> Result<Self> {
+        let socket = context.socket(zmq::SUB)?;
+        socket.set_rcvtimeo(receive_timeout_ms)?;
+        // Envelopes are bincode, so there is no text topic prefix to filter on
+        socket.set_subscribe(b"")?;
+        socket.connect(endpoint)?;
+
+        Ok(Self {
+            socket,
+            endpoint: endpoint.to_string(),
+            reader: MessageReader::new(verifier, chunk_timeout, max_chunks),
+            deltas: DeltaDecoder::new(),
+        })
+    }
+
+    // Next perception frame, whether it was sent whole or as a delta; deltas
+    // that can't be applied are skipped until the camera's next keyframe
+    pub fn recv_perception_frame(&mut self) -> Result<Option<PerceptionFrame>> {
+        while let Some((envelope, payload)) = self.recv()? {
+            let update = match envelope.message_type {
+                MessageType::PerceptionFrame => FrameUpdate::Keyframe(bincode::deserialize(&payload)
+                    .map_err(|e| PerceptionError::SerializationError(format!("Bad perception frame from {}: {}", self.endpoint, e)))?),
+                MessageType::PerceptionFrameDelta => FrameUpdate::Delta(bincode::deserialize(&payload)
+                    .map_err(|e| PerceptionError::SerializationError(format!("Bad frame delta from {}: {}", self.endpoint, e)))?),
+                _ => continue,
+            };
+            if let Some(frame) = self.deltas.apply(update) {
+                return Ok(Some(frame));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl MessageSubscriber for ZmqSubscriber {
+    fn recv(&mut self) -> Result<Option<(MessageEnvelope, Vec<u8>)>> {
+        loop {
+            let parts = match self.socket.recv_multipart(0) {
+                Ok(parts) => parts,
+                Err(zmq::Error::EAGAIN) => {
+                    self.reader.idle();
+                    return Ok(None);
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let [envelope, payload] = <[Vec<u8>; 2]>::try_from(parts)
+                .map_err(|parts| PerceptionError::MessagingError(format!("Expected 2 message parts, got {}", parts.len())))?;
+
+            if let Some(message) = self.reader.read(&self.endpoint, &envelope, payload)? {
+                return Ok(Some(message));
+            }
+        }
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+}
+
+fn decompress(compression: &str, payload: Vec<u8>) -> Result<Vec<u8>> {
+    let failed = |e: String| PerceptionError::SerializationError(format!("{} decompression failed: {}", compression, e));
+
+    match compression {
+        "none" => Ok(payload),
+        "zstd" => zstd::decode_all(payload.as_slice()).map_err(|e| failed(e.to_string())),
+        "lz4" => lz4_flex::decompress_size_prepended(&payload).map_err(|e| failed(e.to_string())),
+        "gzip" => {
+            let mut data = Vec::new();
+            flate2::read::GzDecoder::new(payload.as_slice())
+                .read_to_end(&mut data)
+                .map_err(|e| failed(e.to_string()))?;
+            Ok(data)
+        }
+        other => Err(PerceptionError::SerializationError(format!("Unknown compression {}", other))),
+    }
+}
@@ -156,6 +156,7 @@ mod tests {
             class_id: 1,
             class_label: "robot".to_string(),
             tracker_id: Some(123),
+            oriented: None,
         });
         
         let serialized = publisher.serialize_message(&frame).unwrap();
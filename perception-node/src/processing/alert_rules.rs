@@ -0,0 +1,219 @@
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    config::AlertRule,
+    error::{PerceptionError, Result},
+    messaging::SystemAlert,
+};
+use aetherforge_common::{FusedObject, FusionResult, WorldPosition};
+
+pub const ALERT_RULE: &str = "alert_rule";
+
+struct TrackMotion {
+    position: WorldPosition,
+    timestamp: u64,
+    speed_mps: f32,
+}
+
+// Evaluates the configured alert rules against each fused result. Rules are
+// read through a shared handle, so config sync can swap them while the
+// engine runs; a rule that is removed or stops matching re-arms.
+pub struct AlertRuleEngine {
+    rules: Arc<RwLock<Vec<AlertRule>>>,
+    tracks: HashMap<u64, TrackMotion>,
+    firing: HashSet<String>,
+}
+
+impl AlertRuleEngine {
+    pub fn new(rules: &[AlertRule]) -> Self {
+        Self {
+            rules: Arc::new(RwLock::new(rules.to_vec())),
+            tracks: HashMap::new(),
+            firing: HashSet::new(),
+        }
+    }
+
+    // Handle config sync writes reloaded rules through
+    pub fn live_rules(&self) -> Arc<RwLock<Vec<AlertRule>>> {
+        self.rules.clone()
+    }
+
+    pub fn evaluate(&mut self, result: &FusionResult) -> Vec<SystemAlert> {
+        self.update_motion(result);
+
+        let rules = self.rules.read().unwrap();
+        let mut alerts = Vec::new();
+        let mut firing = HashSet::new();
+
+        for rule in rules.iter() {
+            let matched: Vec<&FusedObject> = result.objects.iter().filter(|o| self.matches(rule, o, result)).collect();
+            if matched.len() <= rule.count_above {
+                continue;
+            }
+
+            firing.insert(rule.name.clone());
+            if !self.firing.contains(&rule.name) {
+                alerts.push(alert(rule, &matched, result.timestamp));
+            }
+        }
+
+        self.firing = firing;
+        alerts
+    }
+
+    fn matches(&self, rule: &AlertRule, object: &FusedObject, result: &FusionResult) -> bool {
+        if rule.class.as_ref().is_some_and(|class| *class != object.class_label) {
+            return false;
+        }
+        if rule.zone.is_some() && rule.zone != object.zone_id {
+            return false;
+        }
+        if let Some(min_speed) = rule.min_speed_mps {
+            let speed = self.tracks.get(&object.global_track_id).map_or(0.0, |t| t.speed_mps);
+            if speed < min_speed {
+                return false;
+            }
+        }
+        if let Some(near) = &rule.near {
+            let close = result.objects.iter().any(|other| {
+                other.global_track_id != object.global_track_id
+                    && other.class_label == near.class
+                    && other.position.distance(&object.position) <= near.within_m
+            });
+            if !close {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn update_motion(&mut self, result: &FusionResult) {
+        let mut seen = HashMap::new();
+
+        for object in &result.objects {
+            let speed_mps = match self.tracks.get(&object.global_track_id) {
+                Some(previous) if result.timestamp > previous.timestamp => {
+                    let elapsed_s = (result.timestamp - previous.timestamp) as f32 / 1000.0;
+                    object.position.distance(&previous.position) / elapsed_s
+                }
+                Some(previous) => previous.speed_mps,
+                None => 0.0,
+            };
+
+            seen.insert(object.global_track_id, TrackMotion {
+                position: object.position,
+                timestamp: result.timestamp,
+                speed_mps,
+            });
+        }
+
+        self.tracks = seen;
+    }
+}
+
+fn alert(rule: &AlertRule, matched: &[&FusedObject], timestamp: u64) -> SystemAlert {
+    let track_ids: Vec<u64> = matched.iter().map(|o| o.global_track_id).collect();
+    let message = rule
+        .message
+        .replace("{rule}", &rule.name)
+        .replace("{count}", &matched.len().to_string())
+        .replace("{class}", rule.class.as_deref().unwrap_or("object"))
+        .replace("{zone}", rule.zone.as_deref().unwrap_or("facility"))
+        .replace("{track_ids}", &track_ids.iter().map(u64::to_string).collect::<Vec<_>>().join(", "));
+
+    SystemAlert {
+        severity: rule.severity,
+        source: rule.name.clone(),
+        message,
+        timestamp,
+        details: Some(json!({
+            "alert_type": ALERT_RULE,
+            "rule": rule.name,
+            "count": matched.len(),
+            "track_ids": track_ids,
+        })),
+    }
+}
+
+// Firing state is kept per rule name, so names must be unique
+pub fn validate(rules: &[AlertRule]) -> Result<()> {
+    let mut names = HashSet::new();
+    for rule in rules {
+        if rule.name.is_empty() {
+            return Err(PerceptionError::ConfigError("processing.alert_rules: a rule has no name".to_string()));
+        }
+        if !names.insert(rule.name.as_str()) {
+            return Err(PerceptionError::ConfigError(format!("processing.alert_rules: duplicate rule '{}'", rule.name)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging::AlertSeverity;
+
+    fn object(track: u64, class: &str, x: f32, zone: Option<&str>) -> FusedObject {
+        FusedObject {
+            global_track_id: track,
+            class_label: class.to_string(),
+            raw_class_label: Some(class.to_string()),
+            position: WorldPosition { x, y: 0.0 },
+            confidence: 0.9,
+            raw_confidence: 0.9,
+            sources: Vec::new(),
+            zone_id: zone.map(str::to_string),
+            coasting: false,
+        }
+    }
+
+    fn result(timestamp: u64, objects: Vec<FusedObject>) -> FusionResult {
+        FusionResult {
+            timestamp,
+            objects,
+            fusion_confidence: 0.9,
+            raw_fusion_confidence: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_crowded_forbidden_zone_fires_critical_once() {
+        let rule: AlertRule = serde_json::from_value(json!({
+            "name": "forbidden_zone_crowding",
+            "class": "person",
+            "zone": "forbidden_zone",
+            "count_above": 3,
+            "severity": "critical",
+            "message": "{count} {class}s in {zone} (tracks {track_ids})",
+        }))
+        .unwrap();
+        let mut engine = AlertRuleEngine::new(&[rule]);
+        let crowd = |n: u64| (1..=n).map(|track| object(track, "person", track as f32, Some("forbidden_zone"))).collect::<Vec<_>>();
+
+        // Three people, plus a fourth elsewhere and a robot inside: not yet
+        let mut objects = crowd(3);
+        objects.push(object(4, "person", 20.0, Some("aisle_3")));
+        objects.push(object(5, "robot", 1.5, Some("forbidden_zone")));
+        assert!(engine.evaluate(&result(0, objects)).is_empty());
+
+        let alerts = engine.evaluate(&result(100, crowd(4)));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, AlertSeverity::Critical);
+        assert_eq!(alerts[0].source, "forbidden_zone_crowding");
+        assert_eq!(alerts[0].message, "4 persons in forbidden_zone (tracks 1, 2, 3, 4)");
+
+        // Still crowded: no repeat; clears, then crowds again: fires again
+        assert!(engine.evaluate(&result(200, crowd(5))).is_empty());
+        assert!(engine.evaluate(&result(300, crowd(2))).is_empty());
+        assert_eq!(engine.evaluate(&result(400, crowd(4))).len(), 1);
+
+        // A reloaded threshold takes effect on the next frame
+        engine.live_rules().write().unwrap()[0].count_above = 5;
+        assert!(engine.evaluate(&result(500, crowd(2))).is_empty());
+        assert!(engine.evaluate(&result(600, crowd(5))).is_empty());
+        assert_eq!(engine.evaluate(&result(700, crowd(6))).len(), 1);
+    }
+}
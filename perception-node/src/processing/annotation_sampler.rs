@@ -0,0 +1,265 @@
+use dashmap::DashMap;
+use image::{codecs::jpeg::JpegEncoder, RgbImage};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::{
+    config::{CameraConfig, ConfigSyncConfig, SamplingPolicy},
+    error::{PerceptionError, Result},
+};
+use aetherforge_common::{CameraFrame, Detection};
+
+const HOUR_MS: u64 = 3_600_000;
+
+// Samples waiting for upload; past this a slow operator platform costs
+// samples, not memory
+pub const SAMPLE_QUEUE_CAPACITY: usize = 32;
+
+struct CameraSampling {
+    policy: SamplingPolicy,
+    next_due_ms: Option<u64>,
+}
+
+// A captured frame with what the model saw in it, bound for labeling
+#[derive(Debug, Clone)]
+pub struct SampledFrame {
+    pub metadata: SampleMetadata,
+    pub jpeg: Vec<u8>,
+}
+
+// The `metadata` part of POST /nodes/{id}/samples
+#[derive(Debug, Clone, Serialize)]
+pub struct SampleMetadata {
+    pub camera_id: String,
+    pub timestamp: u64,
+    pub width: u32,
+    pub height: u32,
+    pub detections: Vec<Detection>, // pre-labels for the annotator, in frame pixels
+}
+
+// Picks frames for the annotation pipeline on each camera's own policy,
+// independent of detection confidence, under one node-wide hourly cap.
+// Cameras without `annotation_sampling` are never sampled.
+pub struct AnnotationSampler {
+    cameras: DashMap<String, CameraSampling>,
+    recent: Mutex<VecDeque<u64>>, // sample times in the last hour
+    max_per_hour: u32,
+    jpeg_quality: u8,
+    rng: Mutex<u64>,
+    samples: mpsc::Sender<SampledFrame>,
+}
+
+impl AnnotationSampler {
+    pub fn new(cameras: &[CameraConfig], sync: &ConfigSyncConfig, samples: mpsc::Sender<SampledFrame>) -> Self {
+        let cameras = cameras
+            .iter()
+            .filter_map(|camera| {
+                let policy = camera.annotation_sampling?;
+                Some((camera.id.clone(), CameraSampling { policy, next_due_ms: None }))
+            })
+            .collect();
+        let seed = aetherforge_common::utils::current_timestamp_ms() | 1;
+
+        Self {
+            cameras,
+            recent: Mutex::new(VecDeque::new()),
+            max_per_hour: sync.max_samples_per_hour,
+            jpeg_quality: sync.sample_jpeg_quality,
+            rng: Mutex::new(seed),
+            samples,
+        }
+    }
+
+    pub fn should_sample(&self, camera_id: &str, timestamp: u64) -> bool {
+        let Some(mut camera) = self.cameras.get_mut(camera_id) else {
+            return false;
+        };
+
+        match camera.next_due_ms {
+            Some(due) if timestamp < due => return false,
+            Some(due) => camera.next_due_ms = Some(self.next_due(camera.policy, due, timestamp)),
+            None => {
+                // Random policies start at a random point too
+                camera.next_due_ms = Some(self.next_due(camera.policy, timestamp, timestamp));
+                if matches!(camera.policy, SamplingPolicy::RandomPerHour { .. }) {
+                    return false;
+                }
+            }
+        }
+
+        // A slot over the cap is skipped rather than deferred, so cameras
+        // don't burst once the window frees up
+        let mut recent = self.recent.lock().unwrap();
+        while recent.front().is_some_and(|t| timestamp.saturating_sub(*t) >= HOUR_MS) {
+            recent.pop_front();
+        }
+        if recent.len() >= self.max_per_hour as usize {
+            return false;
+        }
+        recent.push_back(timestamp);
+        true
+    }
+
+    fn next_due(&self, policy: SamplingPolicy, due: u64, now: u64) -> u64 {
+        match policy {
+            SamplingPolicy::Interval { seconds } => {
+                let interval = ((seconds * 1000.0).round() as u64).max(1);
+                // Keep to the slot grid, restarting it after a gap
+                if now - due < interval {
+                    due + interval
+                } else {
+                    now + interval
+                }
+            }
+            SamplingPolicy::RandomPerHour { frames } => {
+                // Uniform gaps averaging an hour / frames
+                let mean = HOUR_MS / frames.max(1) as u64;
+                now + (self.random_unit() * 2.0 * mean as f64) as u64
+            }
+        }
+    }
+
+    fn random_unit(&self) -> f64 {
+        let mut state = self.rng.lock().unwrap();
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        (*state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Queues `frame` for upload if it's due. Returns whether it was queued.
+    pub fn offer(&self, frame: &CameraFrame, detections: &[Detection]) -> Result<bool> {
+        if !self.should_sample(&frame.camera_id, frame.timestamp) {
+            return Ok(false);
+        }
+
+        let image = RgbImage::from_raw(frame.width, frame.height, frame.data.clone())
+            .ok_or_else(|| PerceptionError::ProcessingError(format!("Frame from {} is not {}x{} RGB", frame.camera_id, frame.width, frame.height)))?;
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg, self.jpeg_quality.clamp(1, 100))
+            .encode_image(&image)
+            .map_err(|e| PerceptionError::ProcessingError(format!("JPEG encoding failed: {}", e)))?;
+
+        let sample = SampledFrame {
+            metadata: SampleMetadata {
+                camera_id: frame.camera_id.clone(),
+                timestamp: frame.timestamp,
+                width: frame.width,
+                height: frame.height,
+                detections: detections.to_vec(),
+            },
+            jpeg,
+        };
+
+        match self.samples.try_send(sample) {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                debug!("Dropping annotation sample from {}: {}", frame.camera_id, e);
+                Ok(false)
+            }
+        }
+    }
+}
+
+// Posts queued samples to the operator platform's annotation ingestion.
+// A failed upload is logged and the sample dropped; the next one is never
+// far off.
+pub async fn run_uploader(operator_url: String, node_id: String, mut samples: mpsc::Receiver<SampledFrame>) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| PerceptionError::ConfigError(e.to_string()))?;
+    let url = format!("{}/nodes/{}/samples", operator_url.trim_end_matches('/'), node_id);
+
+    while let Some(sample) = samples.recv().await {
+        let camera_id = sample.metadata.camera_id.clone();
+        let metadata = serde_json::to_string(&sample.metadata)
+            .map_err(|e| PerceptionError::ProcessingError(format!("Sample metadata: {}", e)))?;
+        let image = reqwest::multipart::Part::bytes(sample.jpeg)
+            .file_name(format!("{}-{}.jpg", camera_id, sample.metadata.timestamp))
+            .mime_str("image/jpeg")
+            .map_err(|e| PerceptionError::ProcessingError(e.to_string()))?;
+        let form = reqwest::multipart::Form::new().text("metadata", metadata).part("image", image);
+
+        if let Err(e) = client.post(&url).multipart(form).send().await.and_then(|r| r.error_for_status()) {
+            warn!("Annotation sample from {} to {} failed: {}", camera_id, url, e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera(id: &str, policy: SamplingPolicy) -> CameraConfig {
+        CameraConfig {
+            id: id.to_string(),
+            annotation_sampling: Some(policy),
+            ..CameraConfig::default()
+        }
+    }
+
+    fn frame(camera_id: &str, timestamp: u64) -> CameraFrame {
+        CameraFrame {
+            camera_id: camera_id.to_string(),
+            data: vec![128; 8 * 8 * 3],
+            width: 8,
+            height: 8,
+            format: "RGB".to_string(),
+            timestamp,
+            sequence_num: 0,
+        }
+    }
+
+    // 30fps frame times over `seconds`
+    fn timestamps(seconds: u64) -> impl Iterator<Item = u64> {
+        (0..seconds * 30).map(|i| 1_700_000_000_000 + i * 1000 / 30)
+    }
+
+    #[tokio::test]
+    async fn test_one_per_second_policy_queues_one_sample_per_second() {
+        let cameras = [camera("dock", SamplingPolicy::Interval { seconds: 1.0 }), CameraConfig::default()];
+        let (tx, mut rx) = mpsc::channel(SAMPLE_QUEUE_CAPACITY);
+        let sampler = AnnotationSampler::new(&cameras, &ConfigSyncConfig::default(), tx);
+
+        let mut queued = 0;
+        for t in timestamps(20) {
+            queued += sampler.offer(&frame("dock", t), &[]).unwrap() as usize;
+            assert!(!sampler.offer(&frame("camera-1", t), &[]).unwrap());
+            // Drain like the uploader would
+            while let Ok(sample) = rx.try_recv() {
+                assert_eq!(sample.metadata.camera_id, "dock");
+                assert_eq!(&sample.jpeg[..2], &[0xFF, 0xD8]);
+            }
+        }
+
+        assert!((19..=21).contains(&queued), "queued {} samples in 20s", queued);
+    }
+
+    #[test]
+    fn test_global_cap_and_random_policy() {
+        let cameras = [
+            camera("a", SamplingPolicy::Interval { seconds: 1.0 }),
+            camera("b", SamplingPolicy::Interval { seconds: 1.0 }),
+        ];
+        let sync = ConfigSyncConfig { max_samples_per_hour: 15, ..ConfigSyncConfig::default() };
+        let (tx, _rx) = mpsc::channel(1);
+        let sampler = AnnotationSampler::new(&cameras, &sync, tx);
+
+        let sampled = timestamps(20)
+            .map(|t| sampler.should_sample("a", t) as usize + sampler.should_sample("b", t) as usize)
+            .sum::<usize>();
+        assert_eq!(sampled, 15);
+
+        // 60 an hour over ten hours of one frame a second
+        let random = AnnotationSampler::new(&[camera("r", SamplingPolicy::RandomPerHour { frames: 60 })], &ConfigSyncConfig::default(), mpsc::channel(1).0);
+        let sampled = (0..36_000u64).filter(|s| random.should_sample("r", s * 1000)).count();
+        assert!((480..=720).contains(&sampled), "sampled {} in 10h", sampled);
+    }
+}
@@ -0,0 +1,185 @@
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::info;
+
+use crate::{
+    config::{CameraConfig, CameraWarmupConfig, FrameQualityConfig},
+    processing::frame_quality::FrameStats,
+};
+use aetherforge_common::CameraFrame;
+
+struct WarmupState {
+    config: CameraWarmupConfig,
+    frames_seen: u32,
+    stable_run: u32,
+    first_frame_ms: Option<u64>,
+    completed_at_ms: Option<u64>,
+    timed_out: bool,
+}
+
+// A camera's warm-up progress as reported on /health/cameras
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmupStatus {
+    pub complete: bool,
+    pub frames_discarded: u32,
+    pub completed_at_ms: Option<u64>,
+    pub timed_out: bool, // forwarded on `max_wait_ms` without seeing stable frames
+}
+
+// Holds back each camera's first frames until it has settled, so RTSP
+// startup garbage and auto-exposure ramps never reach inference. Once a
+// camera completes warm-up every later frame passes; cameras not in the
+// config pass from the start.
+pub struct CameraWarmup {
+    cameras: DashMap<String, WarmupState>,
+    quality: FrameQualityConfig,
+}
+
+impl CameraWarmup {
+    pub fn new(cameras: &[CameraConfig], quality: &FrameQualityConfig) -> Self {
+        let cameras = cameras
+            .iter()
+            .map(|camera| {
+                let state = WarmupState {
+                    config: camera.warmup.clone(),
+                    frames_seen: 0,
+                    stable_run: 0,
+                    first_frame_ms: None,
+                    completed_at_ms: None,
+                    timed_out: false,
+                };
+                (camera.id.clone(), state)
+            })
+            .collect();
+
+        Self { cameras, quality: quality.clone() }
+    }
+
+    // Whether `frame` may go on to inference
+    pub fn admit(&self, frame: &CameraFrame) -> bool {
+        let Some(mut state) = self.cameras.get_mut(&frame.camera_id) else {
+            return true;
+        };
+        if state.completed_at_ms.is_some() {
+            return true;
+        }
+
+        state.frames_seen += 1;
+        let first_frame_ms = *state.first_frame_ms.get_or_insert(frame.timestamp);
+        if state.frames_seen <= state.config.discard_frames {
+            return false;
+        }
+
+        if self.is_stable(frame) {
+            state.stable_run += 1;
+        } else {
+            state.stable_run = 0;
+        }
+
+        let waited_ms = frame.timestamp.saturating_sub(first_frame_ms);
+        if state.stable_run < state.config.stable_frames && waited_ms < state.config.max_wait_ms {
+            return false;
+        }
+
+        state.timed_out = state.stable_run < state.config.stable_frames;
+        state.completed_at_ms = Some(frame.timestamp);
+        info!(
+            "Camera {} warmed up after discarding {} frames{}",
+            frame.camera_id,
+            state.frames_seen - 1,
+            if state.timed_out { " (timed out waiting for stable frames)" } else { "" }
+        );
+        true
+    }
+
+    // A frame is stable once it decodes to sane luminance statistics
+    // within the frame quality limits
+    fn is_stable(&self, frame: &CameraFrame) -> bool {
+        FrameStats::compute(frame, self.quality.sample_stride)
+            .is_some_and(|stats| stats.fault(&self.quality).is_none())
+    }
+
+    pub fn is_complete(&self, camera_id: &str) -> bool {
+        self.cameras.get(camera_id).is_none_or(|state| state.completed_at_ms.is_some())
+    }
+
+    pub fn status(&self) -> HashMap<String, WarmupStatus> {
+        self.cameras
+            .iter()
+            .map(|entry| {
+                let state = entry.value();
+                let status = WarmupStatus {
+                    complete: state.completed_at_ms.is_some(),
+                    frames_discarded: state.frames_seen.saturating_sub(state.completed_at_ms.is_some() as u32),
+                    completed_at_ms: state.completed_at_ms,
+                    timed_out: state.timed_out,
+                };
+                (entry.key().clone(), status)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera(id: &str, warmup: CameraWarmupConfig) -> CameraConfig {
+        CameraConfig { id: id.to_string(), warmup, ..CameraConfig::default() }
+    }
+
+    // A textured 16x16 RGB frame, or a black one as a camera still opening
+    fn frame(camera_id: &str, sequence_num: u64, black: bool) -> CameraFrame {
+        let data = (0..16 * 16)
+            .flat_map(|i| {
+                let value = if black { 0 } else if (i / 64 + i % 16 / 4) % 2 == 0 { 40 } else { 200 };
+                [value; 3]
+            })
+            .collect();
+        CameraFrame {
+            camera_id: camera_id.to_string(),
+            data,
+            width: 16,
+            height: 16,
+            format: "RGB".to_string(),
+            timestamp: 1_700_000_000_000 + sequence_num * 33,
+            sequence_num,
+        }
+    }
+
+    #[test]
+    fn test_initial_frames_are_discarded_before_inference() {
+        let config = CameraWarmupConfig { discard_frames: 10, stable_frames: 0, max_wait_ms: 5000 };
+        let warmup = CameraWarmup::new(&[camera("dock", config)], &FrameQualityConfig::default());
+
+        let inference: Vec<u64> = (1..=30)
+            .map(|seq| frame("dock", seq, false))
+            .filter(|f| warmup.admit(f))
+            .map(|f| f.sequence_num)
+            .collect();
+
+        assert_eq!(inference, (11..=30).collect::<Vec<_>>());
+        let status = &warmup.status()["dock"];
+        assert!(status.complete && !status.timed_out);
+        assert_eq!(status.frames_discarded, 10);
+
+        // Unconfigured cameras are never held back
+        assert!(warmup.admit(&frame("yard", 1, true)));
+    }
+
+    #[test]
+    fn test_waits_for_stable_frames_then_times_out() {
+        let config = CameraWarmupConfig { discard_frames: 2, stable_frames: 3, max_wait_ms: 1000 };
+        let warmup = CameraWarmup::new(&[camera("a", config.clone()), camera("b", config)], &FrameQualityConfig::default());
+
+        // Black until frame 6, then three good frames in a row complete warm-up
+        let admitted: Vec<u64> = (1..=12).filter(|seq| warmup.admit(&frame("a", *seq, *seq < 6))).collect();
+        assert_eq!(admitted, (8..=12).collect::<Vec<_>>());
+
+        // A camera that never settles is forwarded after max_wait_ms
+        let first = (1..=60).find(|seq| warmup.admit(&frame("b", *seq, true))).unwrap();
+        assert_eq!(first, 32); // 31 * 33ms >= 1000ms
+        assert!(warmup.is_complete("b") && warmup.status()["b"].timed_out);
+    }
+}
@@ -0,0 +1,154 @@
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    config::DetectionAnomalyConfig,
+    messaging::{AlertSeverity, SystemAlert},
+};
+
+pub const DETECTION_RATE_ANOMALY: &str = "detection_rate_anomaly";
+
+struct CameraBaseline {
+    mean: f64,
+    variance: f64,
+    frames: u64,
+    recent: VecDeque<usize>,
+    alerted: bool,
+}
+
+// Watches each camera's detections per frame against its own rolling
+// baseline. A camera that suddenly sees nothing (occluded, crashed
+// pipeline) or far too much (glare) alerts once, and re-arms when its
+// rate is back in range. The baseline keeps adapting during an anomaly,
+// so a lasting change in the scene becomes the new normal.
+pub struct DetectionAnomalyMonitor {
+    config: DetectionAnomalyConfig,
+    cameras: HashMap<String, CameraBaseline>,
+}
+
+impl DetectionAnomalyMonitor {
+    pub fn new(config: &DetectionAnomalyConfig) -> Self {
+        Self {
+            config: config.clone(),
+            cameras: HashMap::new(),
+        }
+    }
+
+    // `detections` is the frame's count after NMS and filtering
+    pub fn check(&mut self, camera_id: &str, timestamp: u64, detections: usize) -> Option<SystemAlert> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let window = self.config.window_frames.max(1) as usize;
+        let baseline = self.cameras.entry(camera_id.to_string()).or_insert(CameraBaseline {
+            mean: detections as f64,
+            variance: 0.0,
+            frames: 0,
+            recent: VecDeque::with_capacity(window),
+            alerted: false,
+        });
+
+        if baseline.recent.len() == window {
+            baseline.recent.pop_front();
+        }
+        baseline.recent.push_back(detections);
+
+        let alert = if baseline.frames >= self.config.baseline_frames as u64 && baseline.recent.len() == window {
+            let recent_mean = baseline.recent.iter().sum::<usize>() as f64 / window as f64;
+            let std = baseline.variance.sqrt().max(self.config.min_std as f64);
+            let z_score = (recent_mean - baseline.mean) / (std / (window as f64).sqrt());
+
+            if z_score.abs() < self.config.sensitivity as f64 {
+                baseline.alerted = false;
+                None
+            } else if baseline.alerted {
+                None
+            } else {
+                baseline.alerted = true;
+                let direction = if z_score < 0.0 { "drop" } else { "spike" };
+                Some(SystemAlert {
+                    severity: AlertSeverity::Warning,
+                    source: camera_id.to_string(),
+                    message: format!(
+                        "Camera {} detection rate {}: {:.1} per frame against a baseline of {:.1}",
+                        camera_id, direction, recent_mean, baseline.mean
+                    ),
+                    timestamp,
+                    details: Some(json!({
+                        "alert_type": DETECTION_RATE_ANOMALY,
+                        "direction": direction,
+                        "recent_mean": recent_mean,
+                        "baseline_mean": baseline.mean,
+                        "baseline_std": baseline.variance.sqrt(),
+                        "z_score": z_score,
+                    })),
+                })
+            }
+        } else {
+            None
+        };
+
+        // Exponentially weighted mean and variance
+        let alpha = 2.0 / (self.config.baseline_frames.max(1) as f64 + 1.0);
+        let diff = detections as f64 - baseline.mean;
+        baseline.mean += alpha * diff;
+        baseline.variance = (1.0 - alpha) * (baseline.variance + alpha * diff * diff);
+        baseline.frames += 1;
+
+        alert
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DetectionAnomalyConfig {
+        DetectionAnomalyConfig { baseline_frames: 300, ..DetectionAnomalyConfig::default() }
+    }
+
+    // 3 to 5 people in view, as on a busy aisle
+    fn steady(frame: u64) -> usize {
+        [3, 4, 5, 4][frame as usize % 4]
+    }
+
+    #[test]
+    fn test_sudden_drop_to_zero_alerts_once() {
+        let mut monitor = DetectionAnomalyMonitor::new(&config());
+
+        for frame in 0..900 {
+            assert!(monitor.check("cam-1", frame * 33, steady(frame)).is_none(), "alerted on steady frame {}", frame);
+        }
+
+        // Camera occluded: nothing detected from here on
+        let alerts: Vec<(u64, SystemAlert)> = (900..1200)
+            .filter_map(|frame| monitor.check("cam-1", frame * 33, 0).map(|alert| (frame, alert)))
+            .collect();
+
+        assert_eq!(alerts.len(), 1);
+        let (frame, alert) = &alerts[0];
+        assert!(*frame < 900 + 30, "alerted only at frame {}", frame);
+        assert_eq!(alert.source, "cam-1");
+        let details = alert.details.as_ref().unwrap();
+        assert_eq!(details["alert_type"], DETECTION_RATE_ANOMALY);
+        assert_eq!(details["direction"], "drop");
+    }
+
+    #[test]
+    fn test_spike_alerts_and_cameras_are_independent() {
+        let mut monitor = DetectionAnomalyMonitor::new(&config());
+
+        for frame in 0..600 {
+            assert!(monitor.check("dock", frame * 33, steady(frame)).is_none());
+            assert!(monitor.check("yard", frame * 33, 0).is_none());
+        }
+
+        // Glare on the dock camera
+        let spike = (600..660).find_map(|frame| monitor.check("dock", frame * 33, 40)).unwrap();
+        assert_eq!(spike.details.unwrap()["direction"], "spike");
+
+        // An always-empty scene stays quiet
+        assert!((600..660).all(|frame| monitor.check("yard", frame * 33, 0).is_none()));
+    }
+}
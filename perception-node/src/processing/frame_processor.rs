@@ -0,0 +1,270 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+use tracing::{debug, error, info, warn};
+
+use super::{detection_anomaly::DetectionAnomalyMonitor, frame_quality::FrameQualityMonitor, inference_batcher::InferenceBatcher};
+use crate::{
+    error::Result,
+    messaging::{MessagePublisher, SystemAlert},
+    utils::metrics::Metrics,
+    AppState,
+};
+use aetherforge_common::CameraFrame;
+
+// Frames from one camera waiting to be processed. Bounded so a camera whose
+// frames are slow to process falls behind on its own: when full, the oldest
+// frame is dropped, as a fresher one is worth more than a late one.
+pub struct FrameQueue {
+    camera_id: String,
+    capacity: usize,
+    frames: Mutex<VecDeque<CameraFrame>>,
+    available: Notify,
+    metrics: Arc<Metrics>,
+}
+
+impl FrameQueue {
+    pub fn new(camera_id: &str, capacity: usize, metrics: Arc<Metrics>) -> Self {
+        Self {
+            camera_id: camera_id.to_string(),
+            capacity: capacity.max(1),
+            frames: Mutex::new(VecDeque::new()),
+            available: Notify::new(),
+            metrics,
+        }
+    }
+
+    pub fn push(&self, frame: CameraFrame) {
+        let depth = {
+            let mut frames = self.frames.lock().unwrap();
+            if frames.len() >= self.capacity {
+                frames.pop_front();
+                self.metrics.increment_queue_overflow(&self.camera_id);
+            }
+            frames.push_back(frame);
+            frames.len()
+        };
+        self.metrics.set_camera_queue_depth(&self.camera_id, depth);
+        self.available.notify_one();
+    }
+
+    pub async fn pop(&self) -> CameraFrame {
+        loop {
+            if let Some((frame, depth)) = self.try_pop() {
+                self.metrics.set_camera_queue_depth(&self.camera_id, depth);
+                return frame;
+            }
+            self.available.notified().await;
+        }
+    }
+
+    fn try_pop(&self) -> Option<(CameraFrame, usize)> {
+        let mut frames = self.frames.lock().unwrap();
+        frames.pop_front().map(|frame| (frame, frames.len()))
+    }
+
+    pub fn depth(&self) -> usize {
+        self.frames.lock().unwrap().len()
+    }
+}
+
+// Processes one camera's frames in order on its own task, so a stalled or
+// slow camera never holds up the others. Inference concurrency is still
+// bounded by the inference pool.
+pub fn spawn_worker<F, Fut>(queue: Arc<FrameQueue>, process: F) -> tokio::task::JoinHandle<()>
+where
+    F: Fn(CameraFrame) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send,
+{
+    tokio::spawn(async move {
+        loop {
+            let frame = queue.pop().await;
+            let (camera_id, sequence_num) = (frame.camera_id.clone(), frame.sequence_num);
+            if let Err(e) = process(frame).await {
+                error!("Processing frame {} from {} failed: {}", sequence_num, camera_id, e);
+            }
+        }
+    })
+}
+
+// What is watched on each of one camera's frames
+struct CameraMonitors {
+    quality: FrameQualityMonitor,
+    anomaly: DetectionAnomalyMonitor,
+    inference_failing: bool, // alerted on this run of failures already
+}
+
+// Feeds every camera's frames through inference and publishing, one queue
+// and worker per camera; the workers' frames are batched together for
+// inference
+pub struct FrameProcessor {
+    state: AppState,
+}
+
+impl FrameProcessor {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        let capacity = self.state.config.processing.max_queue_size;
+        let batcher = Arc::new(InferenceBatcher::new(
+            &self.state.config.inference,
+            &self.state.config.cameras,
+            self.state.inference_engine.stats(),
+        ));
+        tokio::spawn(batcher.clone().run(self.state.inference_engine.clone()));
+
+        for camera_id in self.state.camera_manager.list_cameras() {
+            let Some(mut receiver) = self.state.camera_manager.get_frame_receiver(&camera_id) else {
+                continue;
+            };
+            let queue = Arc::new(FrameQueue::new(&camera_id, capacity, self.state.metrics.clone()));
+
+            let incoming = queue.clone();
+            tokio::spawn(async move {
+                while let Some(frame) = receiver.recv().await {
+                    incoming.push(frame);
+                }
+            });
+
+            let state = self.state.clone();
+            let batcher = batcher.clone();
+            let monitors = Arc::new(Mutex::new(CameraMonitors {
+                quality: FrameQualityMonitor::new(&self.state.config.processing.frame_quality),
+                anomaly: DetectionAnomalyMonitor::new(&self.state.config.processing.detection_anomaly),
+                inference_failing: false,
+            }));
+            spawn_worker(queue, move |frame| {
+                let (state, batcher, monitors) = (state.clone(), batcher.clone(), monitors.clone());
+                async move { process_frame(&state, &batcher, &monitors, frame).await }
+            });
+            debug!("Frame queue for camera {} holds up to {} frames", camera_id, capacity);
+        }
+
+        self.state.camera_manager.start_all().await?;
+        info!("Frame processing started");
+        Ok(())
+    }
+}
+
+async fn process_frame(state: &AppState, batcher: &InferenceBatcher, monitors: &Mutex<CameraMonitors>, frame: CameraFrame) -> Result<()> {
+    if !state.camera_warmup.admit(&frame) {
+        return Ok(());
+    }
+    let degraded = monitors.lock().unwrap().quality.check(&frame);
+    if let Some(alert) = degraded {
+        publish_alert(state, &alert).await;
+    }
+
+    let (camera_id, timestamp) = (frame.camera_id.clone(), frame.timestamp);
+    let (frame, result) = match batcher.detect(frame).await {
+        Ok(detected) => {
+            monitors.lock().unwrap().inference_failing = false;
+            detected
+        }
+        Err(e) => {
+            // One alert per run of failures; each one is still logged
+            let failing = std::mem::replace(&mut monitors.lock().unwrap().inference_failing, true);
+            if !failing {
+                publish_alert(state, &SystemAlert::from_error(&camera_id, &e, timestamp)).await;
+            }
+            return Err(e);
+        }
+    };
+    state.metrics.record_frame(&frame.camera_id);
+    let anomaly = monitors.lock().unwrap().anomaly.check(&frame.camera_id, result.timestamp, result.detections.len());
+    if let Some(alert) = anomaly {
+        publish_alert(state, &alert).await;
+    }
+    if let Some(fusion) = &state.fusion {
+        fusion.observe(&result);
+    }
+    if let Some(overlay) = &state.debug_overlay {
+        overlay.observe(&frame, &result.detections)?;
+    }
+    if let Some(sampler) = &state.annotation_sampler {
+        sampler.offer(&frame, &result.detections)?;
+    }
+    if state.publish_throttle.should_publish(&result) {
+        state.message_publisher.publish_perception_frame(&result).await?;
+    }
+    Ok(())
+}
+
+async fn publish_alert(state: &AppState, alert: &SystemAlert) {
+    if let Err(e) = state.message_publisher.publish_alert(alert).await {
+        warn!("Failed to publish alert from {}: {}", alert.source, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use tokio::sync::mpsc;
+
+    fn frame(camera_id: &str, sequence_num: u64) -> CameraFrame {
+        CameraFrame {
+            camera_id: camera_id.to_string(),
+            data: vec![0; 12],
+            width: 2,
+            height: 2,
+            format: "RGB".to_string(),
+            timestamp: sequence_num * 40,
+            sequence_num,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stalled_camera_does_not_delay_others() {
+        let metrics = Arc::new(Metrics::new());
+        let (done, mut processed) = mpsc::unbounded_channel();
+
+        // The dock camera's first frame never finishes processing
+        let mut queues = Vec::new();
+        for camera_id in ["dock", "aisle"] {
+            let queue = Arc::new(FrameQueue::new(camera_id, 4, metrics.clone()));
+            let done = done.clone();
+            spawn_worker(queue.clone(), move |frame| {
+                let done = done.clone();
+                async move {
+                    if frame.camera_id == "dock" {
+                        std::future::pending::<()>().await;
+                    }
+                    done.send((frame.camera_id, frame.sequence_num, Instant::now())).unwrap();
+                    Ok(())
+                }
+            });
+            queues.push(queue);
+        }
+
+        // 25fps from both for 400ms
+        let mut pushed_at = Vec::new();
+        for sequence_num in 0..10 {
+            pushed_at.push(Instant::now());
+            queues[0].push(frame("dock", sequence_num));
+            queues[1].push(frame("aisle", sequence_num));
+            tokio::time::sleep(Duration::from_millis(40)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let mut aisle = Vec::new();
+        while let Ok((camera_id, sequence_num, at)) = processed.try_recv() {
+            assert_eq!(camera_id, "aisle");
+            // Each within a few ms of arriving, not behind the stalled camera
+            assert!(at.duration_since(pushed_at[sequence_num as usize]) < Duration::from_millis(20));
+            aisle.push(sequence_num);
+        }
+        assert_eq!(aisle, (0..10).collect::<Vec<_>>());
+
+        // The dock backlog is capped: its worker holds frame 0, the queue
+        // keeps the newest four and the rest were dropped
+        assert_eq!(queues[0].depth(), 4);
+        assert_eq!(queues[1].depth(), 0);
+        let encoded = metrics.encode();
+        assert!(encoded.contains("aetherforge_camera_queue_depth{camera_id=\"dock\"} 4"), "{}", encoded);
+        assert!(encoded.contains("aetherforge_queue_overflow_frames_total{camera_id=\"dock\"} 5"), "{}", encoded);
+    }
+}
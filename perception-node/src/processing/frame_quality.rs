@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+use crate::{
+    config::FrameQualityConfig,
+    messaging::{AlertSeverity, SystemAlert},
+};
+use aetherforge_common::CameraFrame;
+
+pub const CAMERA_QUALITY_DEGRADED: &str = "camera_quality_degraded";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityFault {
+    TooDark,
+    Overexposed,
+    LowVariance,
+    Blurred,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameStats {
+    pub mean_luma: f32,
+    pub luma_variance: f32,
+    pub sharpness: f32,
+}
+
+impl FrameStats {
+    // Luminance statistics over a grid sampled every `stride` pixels. Frames
+    // with 1 byte per pixel are treated as grayscale, 3 or 4 as RGB(x) or BGR(x).
+    pub fn compute(frame: &CameraFrame, stride: u32) -> Option<Self> {
+        let pixels = frame.width as usize * frame.height as usize;
+        if pixels == 0 || frame.data.len() % pixels != 0 {
+            return None;
+        }
+        let bytes_per_pixel = frame.data.len() / pixels;
+        if bytes_per_pixel == 2 || bytes_per_pixel > 4 {
+            return None;
+        }
+        let bgr = frame.format.to_ascii_uppercase().starts_with('B');
+
+        let luma = |x: u32, y: u32| -> f32 {
+            let i = (y as usize * frame.width as usize + x as usize) * bytes_per_pixel;
+            match bytes_per_pixel {
+                1 => frame.data[i] as f32,
+                _ => {
+                    let (r, g, b) = (frame.data[i], frame.data[i + 1], frame.data[i + 2]);
+                    let (r, b) = if bgr { (b, r) } else { (r, b) };
+                    0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32
+                }
+            }
+        };
+
+        let stride = stride.max(1);
+        let grid: Vec<Vec<f32>> = (0..frame.height)
+            .step_by(stride as usize)
+            .map(|y| (0..frame.width).step_by(stride as usize).map(|x| luma(x, y)).collect())
+            .collect();
+
+        let values: Vec<f32> = grid.iter().flatten().copied().collect();
+        let (mean_luma, luma_variance) = mean_and_variance(&values);
+
+        // 4-neighbour Laplacian on the sampled grid
+        let mut laplacian = Vec::new();
+        for y in 1..grid.len().saturating_sub(1) {
+            for x in 1..grid[y].len().saturating_sub(1) {
+                laplacian.push(4.0 * grid[y][x] - grid[y - 1][x] - grid[y + 1][x] - grid[y][x - 1] - grid[y][x + 1]);
+            }
+        }
+        let (_, sharpness) = mean_and_variance(&laplacian);
+
+        Some(Self {
+            mean_luma,
+            luma_variance,
+            sharpness,
+        })
+    }
+
+    pub fn fault(&self, config: &FrameQualityConfig) -> Option<QualityFault> {
+        if self.mean_luma < config.min_mean_luma {
+            Some(QualityFault::TooDark)
+        } else if self.mean_luma > config.max_mean_luma {
+            Some(QualityFault::Overexposed)
+        } else if self.luma_variance < config.min_luma_variance {
+            Some(QualityFault::LowVariance)
+        } else if self.sharpness < config.min_sharpness {
+            Some(QualityFault::Blurred)
+        } else {
+            None
+        }
+    }
+}
+
+fn mean_and_variance(values: &[f32]) -> (f32, f32) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n;
+    (mean, variance)
+}
+
+struct FaultState {
+    since: u64,
+    alerted: bool,
+}
+
+// Watches each camera's frames for exposure and blur faults. A camera alerts
+// once its frames have been faulty for `sustain_sec`, and re-arms after a
+// good frame, so a dead illuminator doesn't read as an empty scene.
+pub struct FrameQualityMonitor {
+    config: FrameQualityConfig,
+    cameras: HashMap<String, FaultState>,
+}
+
+impl FrameQualityMonitor {
+    pub fn new(config: &FrameQualityConfig) -> Self {
+        Self {
+            config: config.clone(),
+            cameras: HashMap::new(),
+        }
+    }
+
+    pub fn check(&mut self, frame: &CameraFrame) -> Option<SystemAlert> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let stats = FrameStats::compute(frame, self.config.sample_stride)?;
+        let Some(fault) = stats.fault(&self.config) else {
+            self.cameras.remove(&frame.camera_id);
+            return None;
+        };
+
+        // Flickering between fault kinds is still one degraded period
+        let state = self.cameras.entry(frame.camera_id.clone()).or_insert(FaultState {
+            since: frame.timestamp,
+            alerted: false,
+        });
+
+        let duration_ms = frame.timestamp.saturating_sub(state.since);
+        if state.alerted || duration_ms < self.config.sustain_sec * 1000 {
+            return None;
+        }
+        state.alerted = true;
+
+        Some(SystemAlert {
+            severity: AlertSeverity::Warning,
+            source: frame.camera_id.clone(),
+            message: format!("Camera {} frames degraded: {:?} for {}s", frame.camera_id, fault, duration_ms / 1000),
+            timestamp: frame.timestamp,
+            details: Some(json!({
+                "alert_type": CAMERA_QUALITY_DEGRADED,
+                "fault": fault,
+                "mean_luma": stats.mean_luma,
+                "luma_variance": stats.luma_variance,
+                "sharpness": stats.sharpness,
+                "duration_ms": duration_ms,
+            })),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(timestamp: u64, pixel: impl Fn(u32, u32) -> u8) -> CameraFrame {
+        let (width, height) = (64, 48);
+        let mut data = Vec::with_capacity((width * height * 3) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let v = pixel(x, y);
+                data.extend_from_slice(&[v, v, v]);
+            }
+        }
+        CameraFrame {
+            camera_id: "cam-1".to_string(),
+            data,
+            width,
+            height,
+            format: "RGB".to_string(),
+            timestamp,
+            sequence_num: timestamp / 100,
+        }
+    }
+
+    fn checkerboard(timestamp: u64) -> CameraFrame {
+        frame(timestamp, |x, y| if (x / 4 + y / 4) % 2 == 0 { 40 } else { 200 })
+    }
+
+    #[test]
+    fn test_black_frames_alert_after_sustain_window() {
+        let config = FrameQualityConfig::default();
+        let mut monitor = FrameQualityMonitor::new(&config);
+
+        // 10 fps of black frames; nothing until sustain_sec has passed
+        let sustain_ms = config.sustain_sec * 1000;
+        for t in (0..sustain_ms).step_by(100) {
+            assert!(monitor.check(&frame(t, |_, _| 0)).is_none(), "alerted early at {}ms", t);
+        }
+
+        let alert = monitor.check(&frame(sustain_ms, |_, _| 0)).unwrap();
+        assert!(matches!(alert.severity, AlertSeverity::Warning));
+        assert_eq!(alert.source, "cam-1");
+        let details = alert.details.unwrap();
+        assert_eq!(details["alert_type"], CAMERA_QUALITY_DEGRADED);
+        assert_eq!(details["fault"], "too_dark");
+
+        // One alert per degraded period, re-armed by a good frame
+        assert!(monitor.check(&frame(sustain_ms + 100, |_, _| 0)).is_none());
+        assert!(monitor.check(&checkerboard(sustain_ms + 200)).is_none());
+        assert!(monitor.check(&frame(sustain_ms + 300, |_, _| 0)).is_none());
+    }
+
+    #[test]
+    fn test_frame_faults() {
+        let config = FrameQualityConfig::default();
+        let fault = |f: &CameraFrame| FrameStats::compute(f, config.sample_stride).unwrap().fault(&config);
+
+        assert_eq!(fault(&checkerboard(0)), None);
+        assert_eq!(fault(&frame(0, |_, _| 0)), Some(QualityFault::TooDark));
+        assert_eq!(fault(&frame(0, |_, _| 255)), Some(QualityFault::Overexposed));
+        assert_eq!(fault(&frame(0, |_, _| 128)), Some(QualityFault::LowVariance));
+        // Smooth gradient: plenty of variance, no edges
+        assert_eq!(fault(&frame(0, |x, _| 60 + x as u8 * 2)), Some(QualityFault::Blurred));
+    }
+}
\ No newline at end of file
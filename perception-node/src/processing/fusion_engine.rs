@@ -0,0 +1,631 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use tracing::info;
+
+use super::zone_map::ZoneMap;
+use crate::config::{ClassSmoothing, ProcessingConfig};
+use aetherforge_common::{FusedObject, TrackHandoff, TrackSource, WorldPosition};
+
+pub use aetherforge_common::FusionResult;
+
+// Handoffs kept until taken; past this the oldest are dropped, so a caller
+// that falls behind costs handoff history, not memory
+pub const MAX_PENDING_HANDOFFS: usize = 1024;
+
+// A tracked detection from one camera, already projected onto the floor plane
+#[derive(Debug, Clone)]
+pub struct CameraObservation {
+    pub camera_id: String,
+    pub tracker_id: u64,
+    pub class_label: String,
+    pub confidence: f32,
+    pub position: WorldPosition,
+}
+
+struct GlobalTrack {
+    class_label: String, // smoothed
+    class_history: ClassHistory,
+    position: WorldPosition, // predicted while coasting
+    observed: WorldPosition, // where a camera last saw it
+    velocity: (f32, f32),    // meters per ms, from the last two sightings
+    last_seen: u64,
+    missed_frames: u32,
+    last_camera: String,
+    smoothed_confidence: Option<f32>,
+}
+
+impl GlobalTrack {
+    fn observe(&mut self, position: WorldPosition, timestamp: u64) {
+        let elapsed = timestamp.saturating_sub(self.last_seen);
+        if elapsed > 0 {
+            self.velocity = (
+                (position.x - self.observed.x) / elapsed as f32,
+                (position.y - self.observed.y) / elapsed as f32,
+            );
+        }
+        self.position = position;
+        self.observed = position;
+        self.last_seen = timestamp;
+        self.missed_frames = 0;
+    }
+
+    // Dead reckoning from the last sighting
+    fn predict(&mut self, timestamp: u64) {
+        let elapsed = timestamp.saturating_sub(self.last_seen) as f32;
+        self.position = WorldPosition {
+            x: self.observed.x + self.velocity.0 * elapsed,
+            y: self.observed.y + self.velocity.1 * elapsed,
+        };
+    }
+}
+
+#[derive(Default)]
+struct ClassHistory {
+    recent: VecDeque<String>,     // majority vote window
+    scores: HashMap<String, f32>, // decayed confidence per label
+}
+
+impl ClassHistory {
+    // Folds in this frame's label and returns the one to publish. The
+    // current label wins ties, so a track only switches once another label
+    // clearly leads.
+    fn update(&mut self, smoothing: ClassSmoothing, label: &str, confidence: f32, current: &str) -> String {
+        let scores = match smoothing {
+            ClassSmoothing::Off => return label.to_string(),
+            ClassSmoothing::MajorityVote { window } => {
+                self.recent.push_back(label.to_string());
+                while self.recent.len() > window.max(1) {
+                    self.recent.pop_front();
+                }
+                let mut counts: HashMap<String, f32> = HashMap::new();
+                for label in &self.recent {
+                    *counts.entry(label.clone()).or_default() += 1.0;
+                }
+                counts
+            }
+            ClassSmoothing::Ema { alpha } => {
+                let alpha = alpha.clamp(f32::EPSILON, 1.0);
+                for score in self.scores.values_mut() {
+                    *score *= 1.0 - alpha;
+                }
+                *self.scores.entry(label.to_string()).or_default() += alpha * confidence.max(f32::EPSILON);
+                self.scores.clone()
+            }
+        };
+
+        let mut best = (current, scores.get(current).copied().unwrap_or(f32::MIN));
+        for (label, score) in &scores {
+            if *score > best.1 {
+                best = (label, *score);
+            }
+        }
+        best.0.to_string()
+    }
+}
+
+// Exponential moving average; the first value passes through unchanged
+fn ema(previous: Option<f32>, value: f32, alpha: f32) -> f32 {
+    match previous {
+        Some(previous) => previous + alpha * (value - previous),
+        None => value,
+    }
+}
+
+// Reconciles per-camera tracker ids into facility-wide global track ids.
+// A camera track keeps its global id for as long as it lives; a camera
+// track seen for the first time joins the nearest global track of the same
+// class, so an object walking from one camera's view into another's keeps
+// its identity across the handoff. Per-track and scene confidence are
+// EMA-smoothed so single-frame dips don't flap downstream alerts, and the
+// class label is smoothed per `class_smoothing` so a detector wavering
+// between labels doesn't flip the published class; the per-frame label is
+// kept as `raw_class_label`.
+// Every cross-camera join is logged and kept as a `TrackHandoff` until the
+// caller takes it, up to `MAX_PENDING_HANDOFFS`, so association thresholds
+// can be tuned from real data.
+// With a zone map, each fused object is tagged with the cell it's in.
+// A global track no camera sees this frame (a robot behind a rack) keeps
+// being reported, marked coasting, at a position extrapolated from its last
+// motion for up to `coast_frames` frames; a camera track reappearing near
+// the prediction rejoins it. After that it is no longer reported, but is
+// held at its last prediction for a camera track to rejoin until
+// `global_track_timeout_ms` after it was last seen.
+pub struct FusionEngine {
+    association_radius_m: f32,
+    track_timeout_ms: u64,
+    coast_frames: u32,
+    confidence_alpha: f32,
+    class_smoothing: ClassSmoothing,
+    smoothed_fusion_confidence: Option<f32>,
+    next_global_id: u64,
+    tracks: HashMap<u64, GlobalTrack>,
+    bindings: HashMap<(String, u64), u64>,
+    handoffs: VecDeque<TrackHandoff>,
+    zones: Option<ZoneMap>,
+}
+
+impl FusionEngine {
+    pub fn new(config: &ProcessingConfig) -> Self {
+        Self {
+            association_radius_m: config.association_radius_m,
+            track_timeout_ms: config.global_track_timeout_ms,
+            coast_frames: config.coast_frames,
+            confidence_alpha: config.confidence_ema_alpha.clamp(f32::EPSILON, 1.0),
+            class_smoothing: config.class_smoothing,
+            smoothed_fusion_confidence: None,
+            next_global_id: 1,
+            tracks: HashMap::new(),
+            bindings: HashMap::new(),
+            handoffs: VecDeque::new(),
+            zones: None,
+        }
+    }
+
+    pub fn with_zone_map(mut self, zones: ZoneMap) -> Self {
+        self.zones = Some(zones);
+        self
+    }
+
+    pub fn fuse(&mut self, observations: &[CameraObservation], timestamp: u64) -> FusionResult {
+        self.expire(timestamp);
+        // Match against where tracks should be by now, as long as they coast
+        for track in self.tracks.values_mut() {
+            if track.missed_frames <= self.coast_frames {
+                track.predict(timestamp);
+            }
+        }
+
+        let mut assigned: Vec<Option<u64>> = vec![None; observations.len()];
+        // A camera never sees the same object twice in one frame
+        let mut claimed: HashSet<(&str, u64)> = HashSet::new();
+
+        // Camera tracks already bound keep their global id
+        for (i, observation) in observations.iter().enumerate() {
+            let key = (observation.camera_id.clone(), observation.tracker_id);
+            if let Some(&global_id) = self.bindings.get(&key) {
+                if let Some(track) = self.tracks.get_mut(&global_id) {
+                    track.position = observation.position;
+                    track.last_camera = observation.camera_id.clone();
+                    assigned[i] = Some(global_id);
+                    claimed.insert((observation.camera_id.as_str(), global_id));
+                }
+            }
+        }
+
+        // New camera tracks join the nearest global track or start a new one
+        for (i, observation) in observations.iter().enumerate() {
+            if assigned[i].is_some() {
+                continue;
+            }
+
+            let global_id = match self.nearest_track(observation, &claimed) {
+                Some((global_id, distance)) => {
+                    self.record_join(global_id, observation, distance, timestamp);
+                    global_id
+                }
+                None => self.start_track(observation, timestamp),
+            };
+
+            self.bindings.insert((observation.camera_id.clone(), observation.tracker_id), global_id);
+            claimed.insert((observation.camera_id.as_str(), global_id));
+            assigned[i] = Some(global_id);
+        }
+
+        let mut grouped: BTreeMap<u64, Vec<&CameraObservation>> = BTreeMap::new();
+        for (observation, global_id) in observations.iter().zip(assigned) {
+            if let Some(global_id) = global_id {
+                grouped.entry(global_id).or_default().push(observation);
+            }
+        }
+
+        let seen: HashSet<u64> = grouped.keys().copied().collect();
+        let alpha = self.confidence_alpha;
+        let class_smoothing = self.class_smoothing;
+        let zones = self.zones.as_ref();
+        let mut objects: Vec<FusedObject> = grouped
+            .into_iter()
+            .map(|(global_id, members)| {
+                let mut object = Self::merge(global_id, &members);
+                object.zone_id = zones.and_then(|zones| zones.zone_at(&object.position)).map(String::from);
+                if let Some(track) = self.tracks.get_mut(&global_id) {
+                    track.observe(object.position, timestamp);
+                    let smoothed = ema(track.smoothed_confidence, object.raw_confidence, alpha);
+                    track.smoothed_confidence = Some(smoothed);
+                    object.confidence = smoothed;
+
+                    let raw_label = object.raw_class_label.as_deref().unwrap_or(&object.class_label);
+                    track.class_label = track.class_history.update(class_smoothing, raw_label, object.raw_confidence, &track.class_label);
+                    object.class_label = track.class_label.clone();
+                }
+                object
+            })
+            .collect();
+
+        // Mean confidence of what the cameras saw; an empty scene has
+        // nothing uncertain in it
+        let raw_fusion_confidence = if objects.is_empty() {
+            1.0
+        } else {
+            objects.iter().map(|o| o.raw_confidence).sum::<f32>() / objects.len() as f32
+        };
+        let fusion_confidence = ema(self.smoothed_fusion_confidence, raw_fusion_confidence, alpha);
+        self.smoothed_fusion_confidence = Some(fusion_confidence);
+
+        objects.extend(self.coast(&seen));
+        objects.sort_by_key(|o| o.global_track_id);
+
+        FusionResult {
+            timestamp,
+            objects,
+            fusion_confidence,
+            raw_fusion_confidence,
+        }
+    }
+
+    pub fn active_tracks(&self) -> usize {
+        self.tracks.len()
+    }
+
+    // Handoffs recorded since the last call
+    pub fn take_handoffs(&mut self) -> Vec<TrackHandoff> {
+        self.handoffs.drain(..).collect()
+    }
+
+    // A camera track joining a global track another camera was following
+    // is a handoff; a camera re-acquiring its own track is not
+    fn record_join(&mut self, global_id: u64, observation: &CameraObservation, distance: f32, timestamp: u64) {
+        let Some(track) = self.tracks.get_mut(&global_id) else {
+            return;
+        };
+        if track.last_camera == observation.camera_id {
+            return;
+        }
+
+        let handoff = TrackHandoff {
+            global_track_id: global_id,
+            from_camera: std::mem::replace(&mut track.last_camera, observation.camera_id.clone()),
+            to_camera: observation.camera_id.clone(),
+            timestamp,
+            overlap_confidence: (1.0 - distance / self.association_radius_m.max(f32::EPSILON)).clamp(0.0, 1.0),
+        };
+        info!(
+            global_track_id = handoff.global_track_id,
+            from_camera = %handoff.from_camera,
+            to_camera = %handoff.to_camera,
+            overlap_confidence = handoff.overlap_confidence,
+            "Track handoff"
+        );
+        if self.handoffs.len() >= MAX_PENDING_HANDOFFS {
+            self.handoffs.pop_front();
+        }
+        self.handoffs.push_back(handoff);
+    }
+
+    fn nearest_track(&self, observation: &CameraObservation, claimed: &HashSet<(&str, u64)>) -> Option<(u64, f32)> {
+        self.tracks
+            .iter()
+            .filter(|(global_id, track)| {
+                track.class_label == observation.class_label
+                    && !claimed.contains(&(observation.camera_id.as_str(), **global_id))
+            })
+            .map(|(global_id, track)| (*global_id, track.position.distance(&observation.position)))
+            .filter(|(_, distance)| *distance <= self.association_radius_m)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+    }
+
+    fn start_track(&mut self, observation: &CameraObservation, timestamp: u64) -> u64 {
+        let global_id = self.next_global_id;
+        self.next_global_id += 1;
+
+        self.tracks.insert(global_id, GlobalTrack {
+            class_label: observation.class_label.clone(),
+            class_history: ClassHistory::default(),
+            position: observation.position,
+            observed: observation.position,
+            velocity: (0.0, 0.0),
+            last_seen: timestamp,
+            missed_frames: 0,
+            last_camera: observation.camera_id.clone(),
+            smoothed_confidence: None,
+        });
+
+        global_id
+    }
+
+    // Confidence-weighted position across every camera seeing the object;
+    // the label is the most confident camera's
+    fn merge(global_id: u64, members: &[&CameraObservation]) -> FusedObject {
+        let total_weight: f32 = members.iter().map(|m| m.confidence.max(f32::EPSILON)).sum();
+        let (x, y) = members.iter().fold((0.0, 0.0), |(x, y), m| {
+            let weight = m.confidence.max(f32::EPSILON) / total_weight;
+            (x + m.position.x * weight, y + m.position.y * weight)
+        });
+        let best = members.iter().max_by(|a, b| a.confidence.total_cmp(&b.confidence)).unwrap();
+        let confidence = best.confidence.max(0.0);
+
+        FusedObject {
+            global_track_id: global_id,
+            class_label: best.class_label.clone(),
+            raw_class_label: Some(best.class_label.clone()),
+            position: WorldPosition { x, y },
+            confidence,
+            raw_confidence: confidence,
+            sources: members
+                .iter()
+                .map(|m| TrackSource {
+                    camera_id: m.camera_id.clone(),
+                    tracker_id: m.tracker_id,
+                })
+                .collect(),
+            zone_id: None,
+            coasting: false,
+        }
+    }
+
+    // Tracks missed this frame: report the ones still within their coasting
+    // budget at their predicted position. The rest stay unreported until
+    // they expire.
+    fn coast(&mut self, seen: &HashSet<u64>) -> Vec<FusedObject> {
+        let zones = self.zones.as_ref();
+        let mut coasting = Vec::new();
+
+        for (&global_id, track) in self.tracks.iter_mut() {
+            if seen.contains(&global_id) {
+                continue;
+            }
+            track.missed_frames += 1;
+            if track.missed_frames > self.coast_frames {
+                continue;
+            }
+
+            coasting.push(FusedObject {
+                global_track_id: global_id,
+                class_label: track.class_label.clone(),
+                raw_class_label: None,
+                position: track.position,
+                confidence: track.smoothed_confidence.unwrap_or(0.0),
+                raw_confidence: 0.0,
+                sources: Vec::new(),
+                zone_id: zones.and_then(|zones| zones.zone_at(&track.position)).map(String::from),
+                coasting: true,
+            });
+        }
+
+        coasting
+    }
+
+    fn expire(&mut self, now: u64) {
+        let timeout = self.track_timeout_ms;
+        self.tracks.retain(|_, track| now.saturating_sub(track.last_seen) <= timeout);
+
+        let tracks = &self.tracks;
+        self.bindings.retain(|_, global_id| tracks.contains_key(global_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(camera_id: &str, tracker_id: u64, label: &str, x: f32, y: f32) -> CameraObservation {
+        CameraObservation {
+            camera_id: camera_id.to_string(),
+            tracker_id,
+            class_label: label.to_string(),
+            confidence: 0.9,
+            position: WorldPosition { x, y },
+        }
+    }
+
+    #[test]
+    fn test_global_track_id_survives_camera_handoff() {
+        let mut engine = FusionEngine::new(&ProcessingConfig::default());
+        let mut global_ids = HashSet::new();
+
+        // A worker walks from x=0 to x=10; cam-a covers x<=6, cam-b covers x>=4
+        for step in 0..=20u64 {
+            let x = step as f32 * 0.5;
+            let mut observations = Vec::new();
+            if x <= 6.0 {
+                observations.push(observation("cam-a", 3, "person", x, 2.0));
+            }
+            if x >= 4.0 {
+                observations.push(observation("cam-b", 11, "person", x + 0.2, 2.1));
+            }
+
+            let result = engine.fuse(&observations, step * 100);
+            assert_eq!(result.objects.len(), 1, "step {} split the worker", step);
+            global_ids.insert(result.objects[0].global_track_id);
+        }
+
+        assert_eq!(global_ids.len(), 1);
+        assert_eq!(engine.active_tracks(), 1);
+    }
+
+    #[test]
+    fn test_handoff_between_cameras_is_recorded_once() {
+        let mut engine = FusionEngine::new(&ProcessingConfig::default());
+
+        // Same walk as above: cam-b picks the worker up at x=4
+        for step in 0..=20u64 {
+            let x = step as f32 * 0.5;
+            let mut observations = Vec::new();
+            if x <= 6.0 {
+                observations.push(observation("cam-a", 3, "person", x, 2.0));
+            }
+            if x >= 4.0 {
+                observations.push(observation("cam-b", 11, "person", x + 0.2, 2.1));
+            }
+            engine.fuse(&observations, step * 100);
+        }
+
+        let handoffs = engine.take_handoffs();
+        assert_eq!(handoffs.len(), 1);
+        assert_eq!(handoffs[0].from_camera, "cam-a");
+        assert_eq!(handoffs[0].to_camera, "cam-b");
+        assert_eq!(handoffs[0].timestamp, 800);
+        assert!(handoffs[0].overlap_confidence > 0.0 && handoffs[0].overlap_confidence <= 1.0);
+        assert!(engine.take_handoffs().is_empty());
+    }
+
+    #[test]
+    fn test_untaken_handoffs_are_capped_keeping_the_newest() {
+        let mut engine = FusionEngine::new(&ProcessingConfig::default());
+
+        // A worker standing where two cameras overlap, picked up under a new
+        // tracker id by alternating cameras every frame
+        let frames = MAX_PENDING_HANDOFFS as u64 + 100;
+        for step in 0..=frames {
+            let camera = if step % 2 == 0 { "cam-a" } else { "cam-b" };
+            engine.fuse(&[observation(camera, step, "person", 1.0, 1.0)], step * 100);
+        }
+
+        let handoffs = engine.take_handoffs();
+        assert_eq!(handoffs.len(), MAX_PENDING_HANDOFFS);
+        assert_eq!(handoffs.last().unwrap().timestamp, frames * 100);
+        assert!(engine.take_handoffs().is_empty());
+    }
+
+    #[test]
+    fn test_reacquired_track_on_same_camera_is_not_a_handoff() {
+        let mut engine = FusionEngine::new(&ProcessingConfig::default());
+
+        engine.fuse(&[observation("cam-a", 1, "person", 1.0, 1.0)], 0);
+        // The camera's tracker lost the worker and assigned a new id
+        engine.fuse(&[observation("cam-a", 2, "person", 1.1, 1.0)], 100);
+
+        assert!(engine.take_handoffs().is_empty());
+    }
+
+    #[test]
+    fn test_distinct_objects_get_distinct_global_ids() {
+        let mut engine = FusionEngine::new(&ProcessingConfig::default());
+
+        let result = engine.fuse(
+            &[
+                observation("cam-a", 1, "person", 1.0, 1.0),
+                observation("cam-a", 2, "person", 1.5, 1.0),
+                observation("cam-b", 5, "robot", 1.2, 1.0),
+                observation("cam-b", 6, "person", 8.0, 8.0),
+            ],
+            0,
+        );
+
+        let ids: HashSet<u64> = result.objects.iter().map(|o| o.global_track_id).collect();
+        assert_eq!(ids.len(), 4);
+    }
+
+    #[test]
+    fn test_stale_global_tracks_expire() {
+        let mut engine = FusionEngine::new(&ProcessingConfig::default());
+
+        let first = engine.fuse(&[observation("cam-a", 1, "person", 1.0, 1.0)], 0);
+        let later = engine.fuse(&[observation("cam-a", 1, "person", 1.0, 1.0)], 10_000);
+
+        assert_ne!(first.objects[0].global_track_id, later.objects[0].global_track_id);
+        assert_eq!(engine.active_tracks(), 1);
+    }
+
+    #[test]
+    fn test_occluded_track_coasts_and_is_reacquired() {
+        let mut engine = FusionEngine::new(&ProcessingConfig { coast_frames: 5, ..ProcessingConfig::default() });
+
+        // A robot drives along y=2 at 1 m/s, seen every 100ms
+        let mut global_id = None;
+        for step in 0..5u64 {
+            let result = engine.fuse(&[observation("cam-a", 1, "robot", step as f32 * 0.1, 2.0)], step * 100);
+            global_id = Some(result.objects[0].global_track_id);
+            assert!(!result.objects[0].coasting);
+        }
+
+        // Behind a rack for three frames: still reported, moving on
+        for step in 5..8u64 {
+            let result = engine.fuse(&[], step * 100);
+            assert_eq!(result.objects.len(), 1, "step {} lost the robot", step);
+            let object = &result.objects[0];
+            assert!(object.coasting);
+            assert_eq!(Some(object.global_track_id), global_id);
+            assert!(object.sources.is_empty());
+            assert!((object.position.x - step as f32 * 0.1).abs() < 1e-3, "predicted {:?}", object.position);
+        }
+
+        // The camera's tracker picks it up again under a new id
+        let result = engine.fuse(&[observation("cam-a", 9, "robot", 0.8, 2.0)], 800);
+        assert_eq!(result.objects.len(), 1);
+        assert!(!result.objects[0].coasting);
+        assert_eq!(Some(result.objects[0].global_track_id), global_id);
+        assert_eq!(engine.active_tracks(), 1);
+
+        // Gone for longer than the coasting budget: no longer reported, but
+        // kept, held where it was last predicted at x=1.4
+        for step in 9..=15u64 {
+            let result = engine.fuse(&[], step * 100);
+            assert_eq!(result.objects.len(), usize::from(step < 14), "step {}", step);
+        }
+        assert_eq!(engine.active_tracks(), 1);
+
+        // Picked up again near there, it is still the same robot
+        let result = engine.fuse(&[observation("cam-a", 12, "robot", 1.5, 2.0)], 1600);
+        assert_eq!(result.objects.len(), 1);
+        assert_eq!(Some(result.objects[0].global_track_id), global_id);
+
+        // Unseen for longer than the track timeout: retired
+        let result = engine.fuse(&[], 1600 + ProcessingConfig::default().global_track_timeout_ms + 1);
+        assert!(result.objects.is_empty());
+        assert_eq!(engine.active_tracks(), 0);
+    }
+
+    #[test]
+    fn test_smoothed_confidence_damps_oscillation_without_flapping() {
+        let mut engine = FusionEngine::new(&ProcessingConfig::default());
+        let threshold = 0.7;
+
+        let (mut raw, mut smoothed, mut scene) = (Vec::new(), Vec::new(), Vec::new());
+        for step in 0..40u64 {
+            let mut worker = observation("cam-a", 1, "person", 1.0, 1.0);
+            worker.confidence = if step % 2 == 0 { 0.9 } else { 0.6 };
+
+            let result = engine.fuse(&[worker], step * 100);
+            raw.push(result.objects[0].raw_confidence);
+            smoothed.push(result.objects[0].confidence);
+            scene.push(result.fusion_confidence);
+            assert_eq!(result.raw_fusion_confidence, result.objects[0].raw_confidence);
+        }
+
+        let variance = |values: &[f32]| {
+            let mean = values.iter().sum::<f32>() / values.len() as f32;
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32
+        };
+        let crossings = |values: &[f32]| values.windows(2).filter(|w| (w[0] < threshold) != (w[1] < threshold)).count();
+
+        assert!(variance(&smoothed) < variance(&raw) / 4.0);
+        assert_eq!(crossings(&raw), 39);
+        assert_eq!(crossings(&smoothed), 0);
+        assert_eq!(crossings(&scene), 0);
+    }
+
+    #[test]
+    fn test_flickering_label_keeps_a_stable_smoothed_class() {
+        let strategies = [ClassSmoothing::MajorityVote { window: 5 }, ClassSmoothing::Ema { alpha: 0.3 }];
+        for class_smoothing in strategies {
+            let mut engine = FusionEngine::new(&ProcessingConfig { class_smoothing, ..ProcessingConfig::default() });
+
+            // A worker the detector calls a robot every third frame
+            let mut raw = Vec::new();
+            for step in 0..30u64 {
+                let label = if step % 3 == 2 { "robot" } else { "person" };
+                let result = engine.fuse(&[observation("cam-a", 1, label, 1.0, 1.0)], step * 100);
+                assert_eq!(result.objects.len(), 1);
+                assert_eq!(result.objects[0].class_label, "person", "{:?} flipped at step {}", class_smoothing, step);
+                raw.push(result.objects[0].raw_class_label.clone().unwrap());
+            }
+            assert_eq!(raw.iter().filter(|label| *label == "robot").count(), 10);
+        }
+
+        // Unsmoothed, the published class follows every frame
+        let mut engine = FusionEngine::new(&ProcessingConfig { class_smoothing: ClassSmoothing::Off, ..ProcessingConfig::default() });
+        engine.fuse(&[observation("cam-a", 1, "person", 1.0, 1.0)], 0);
+        let result = engine.fuse(&[observation("cam-a", 1, "robot", 1.0, 1.0)], 100);
+        assert_eq!(result.objects[0].class_label, "robot");
+    }
+}
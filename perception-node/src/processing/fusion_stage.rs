@@ -0,0 +1,351 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, error, warn};
+
+use super::{
+    alert_rules::AlertRuleEngine,
+    fusion_engine::{CameraObservation, FusionEngine},
+};
+use crate::{
+    config::{CameraCalibration, CameraConfig, Intrinsics},
+    error::{PerceptionError, Result},
+    messaging::MessagePublisher,
+};
+use aetherforge_common::{utils::current_timestamp_ms, CoordinateSpace, Detection, PerceptionFrame, TrackHandoff, WorldPosition};
+
+// Handoff batches waiting for upload; past this a slow operator platform
+// costs handoffs, not memory
+pub const HANDOFF_QUEUE_CAPACITY: usize = 64;
+
+pub(crate) type Vec3 = [f64; 3];
+pub(crate) type Mat3 = [[f64; 3]; 3];
+
+// A same-class detection overlapping one in the previous frame at least this
+// much is taken to be the same object
+const MIN_TRACK_IOU: f32 = 0.3;
+
+#[derive(Default)]
+struct CameraTracks {
+    previous: Vec<Detection>,       // last frame's detections, tracker ids filled in
+    latest: Vec<CameraObservation>, // newest frame since the last fuse
+    next_tracker_id: u64,
+}
+
+impl CameraTracks {
+    fn assign_tracker_ids(&mut self, detections: &[Detection]) -> Vec<Detection> {
+        let mut taken = HashSet::new();
+        detections
+            .iter()
+            .map(|detection| {
+                let mut detection = detection.clone();
+                if detection.tracker_id.is_none() {
+                    let matched = self
+                        .previous
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, previous)| !taken.contains(i) && previous.class_label == detection.class_label)
+                        .map(|(i, previous)| (i, previous.intersection_over_union(&detection)))
+                        .filter(|(_, iou)| *iou >= MIN_TRACK_IOU)
+                        .max_by(|a, b| a.1.total_cmp(&b.1))
+                        .map(|(i, _)| i);
+                    if let Some(i) = matched {
+                        taken.insert(i);
+                    }
+                    detection.tracker_id = matched.and_then(|i| self.previous[i].tracker_id).or_else(|| {
+                        self.next_tracker_id += 1;
+                        Some(self.next_tracker_id)
+                    });
+                }
+                detection
+            })
+            .collect()
+    }
+}
+
+// Puts each calibrated camera's detections on the floor plane for the
+// fusion engine: the bottom centre of a box is where the object stands.
+// Lens distortion is not corrected. Only a camera's newest frame since the
+// last fuse counts, and uncalibrated cameras are left out.
+// Until the node runs a tracker, a detection without a tracker id takes the
+// id of the same-class detection it overlaps most in the camera's previous
+// frame, so the engine's per-camera bindings hold from frame to frame.
+pub struct FloorObservations {
+    calibrations: HashMap<String, CameraCalibration>,
+    cameras: Mutex<HashMap<String, CameraTracks>>,
+}
+
+impl FloorObservations {
+    pub fn new(cameras: &[CameraConfig]) -> Self {
+        let calibrations = cameras
+            .iter()
+            .filter_map(|camera| Some((camera.id.clone(), camera.calibration.clone()?)))
+            .collect();
+
+        Self { calibrations, cameras: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calibrations.is_empty()
+    }
+
+    pub fn observe(&self, frame: &PerceptionFrame) {
+        let Some(calibration) = self.calibrations.get(&frame.source_camera_id) else {
+            return;
+        };
+        let (scale_x, scale_y) = match frame.coordinate_space {
+            CoordinateSpace::Pixels => (1.0, 1.0),
+            CoordinateSpace::Normalized => (frame.image_width as f64, frame.image_height as f64),
+        };
+
+        let mut cameras = self.cameras.lock().unwrap();
+        let tracks = cameras.entry(frame.source_camera_id.clone()).or_default();
+        let detections = tracks.assign_tracker_ids(&frame.detections);
+        tracks.latest = detections
+            .iter()
+            .filter_map(|detection| {
+                let u = (detection.bbox.xmin + detection.bbox.xmax) as f64 / 2.0 * scale_x;
+                let v = detection.bbox.ymax as f64 * scale_y;
+                Some(CameraObservation {
+                    camera_id: frame.source_camera_id.clone(),
+                    tracker_id: detection.tracker_id?,
+                    class_label: detection.class_label.clone(),
+                    confidence: detection.confidence,
+                    position: floor_position(calibration, u, v)?,
+                })
+            })
+            .collect();
+        tracks.previous = detections;
+    }
+
+    // Every camera's newest observations since the last call
+    pub fn take(&self) -> Vec<CameraObservation> {
+        self.cameras
+            .lock()
+            .unwrap()
+            .values_mut()
+            .flat_map(|tracks| std::mem::take(&mut tracks.latest))
+            .collect()
+    }
+}
+
+// Fuses what the cameras saw every `interval`, publishes the result and
+// any alerts the site's rules raise on it, and queues the engine's
+// handoffs for the reporter when one runs
+pub async fn run<P>(
+    observations: Arc<FloorObservations>,
+    mut engine: FusionEngine,
+    mut rules: AlertRuleEngine,
+    handoffs: Option<mpsc::Sender<Vec<TrackHandoff>>>,
+    publisher: Arc<P>,
+    interval: Duration,
+) where
+    P: MessagePublisher + ?Sized,
+{
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let result = engine.fuse(&observations.take(), current_timestamp_ms());
+        if let Err(e) = publisher.publish_fusion_result(&result).await {
+            error!("Failed to publish fusion result: {}", e);
+        }
+        for alert in rules.evaluate(&result) {
+            if let Err(e) = publisher.publish_alert(&alert).await {
+                warn!("Failed to publish alert rule {}: {}", alert.source, e);
+            }
+        }
+
+        let taken = engine.take_handoffs();
+        if let (Some(handoffs), false) = (&handoffs, taken.is_empty()) {
+            if let Err(e) = handoffs.try_send(taken) {
+                debug!("Dropping track handoffs: {}", e);
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HandoffReport<'a> {
+    node_id: &'a str,
+    handoffs: &'a [TrackHandoff],
+}
+
+// Posts handoffs to the operator platform's /analytics/handoffs, everything
+// queued by then in one request
+pub async fn run_handoff_reporter(operator_url: String, node_id: String, mut handoffs: mpsc::Receiver<Vec<TrackHandoff>>) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| PerceptionError::ConfigError(e.to_string()))?;
+    let url = format!("{}/analytics/handoffs", operator_url.trim_end_matches('/'));
+
+    while let Some(mut batch) = handoffs.recv().await {
+        while let Ok(more) = handoffs.try_recv() {
+            batch.extend(more);
+        }
+        let report = HandoffReport { node_id: &node_id, handoffs: &batch };
+        if let Err(e) = client.post(&url).json(&report).send().await.and_then(|r| r.error_for_status()) {
+            warn!("Reporting {} track handoffs to {} failed: {}", batch.len(), url, e);
+        }
+    }
+
+    Ok(())
+}
+
+// Where the ray through pixel (u, v) meets the floor, if it points down at it
+pub fn floor_position(calibration: &CameraCalibration, u: f64, v: f64) -> Option<WorldPosition> {
+    let Intrinsics { fx, fy, cx, cy } = calibration.intrinsics;
+    let rotation = matrix_from_rodrigues(calibration.extrinsics.rotation);
+    let transposed = transpose(&rotation);
+    let center = neg(mul(&transposed, calibration.extrinsics.translation));
+    let ray = mul(&transposed, [(u - cx) / fx, (v - cy) / fy, 1.0]);
+    if ray[2] >= 0.0 {
+        return None;
+    }
+
+    let s = -center[2] / ray[2];
+    Some(WorldPosition { x: (center[0] + s * ray[0]) as f32, y: (center[1] + s * ray[1]) as f32 })
+}
+
+pub(crate) fn neg(a: Vec3) -> Vec3 {
+    [-a[0], -a[1], -a[2]]
+}
+
+pub(crate) fn mul(m: &Mat3, v: Vec3) -> Vec3 {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+fn transpose(m: &Mat3) -> Mat3 {
+    [[m[0][0], m[1][0], m[2][0]], [m[0][1], m[1][1], m[2][1]], [m[0][2], m[1][2], m[2][2]]]
+}
+
+// Rotation vector (axis * angle), as stored in `Extrinsics::rotation`
+pub(crate) fn matrix_from_rodrigues(r: Vec3) -> Mat3 {
+    let theta = (r[0] * r[0] + r[1] * r[1] + r[2] * r[2]).sqrt();
+    if theta < 1e-12 {
+        return [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    }
+    let [x, y, z] = [r[0] / theta, r[1] / theta, r[2] / theta];
+    let (s, c) = theta.sin_cos();
+    let t = 1.0 - c;
+    [
+        [c + x * x * t, x * y * t - z * s, x * z * t + y * s],
+        [y * x * t + z * s, c + y * y * t, y * z * t - x * s],
+        [z * x * t - y * s, z * y * t + x * s, c + z * z * t],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProcessingConfig;
+    use crate::messaging::{MessageEnvelope, SystemAlert, SystemHealth};
+    use crate::processing::fusion_scene::SceneCamera;
+    use aetherforge_common::{BBox, FusionResult};
+
+    // One person standing at `position`, as `camera` would detect them
+    fn frame(camera: &SceneCamera, step: u64, position: WorldPosition) -> PerceptionFrame {
+        let (u, v) = camera.project(position).unwrap();
+        let (u, v) = (u as f32, v as f32);
+        PerceptionFrame {
+            frame_id: step,
+            timestamp: step * 100,
+            source_camera_id: camera.id.clone(),
+            image_width: camera.width,
+            image_height: camera.height,
+            model_version: "stub".to_string(),
+            inference_time_ms: 1.0,
+            detections: vec![Detection {
+                bbox: BBox::new(u - 20.0, v - 120.0, u + 20.0, v),
+                confidence: 0.9,
+                class_id: 0,
+                class_label: "person".to_string(),
+                tracker_id: None,
+                oriented: None,
+            }],
+            camera_intrinsics: None,
+            camera_extrinsics: None,
+            coordinate_space: CoordinateSpace::Pixels,
+        }
+    }
+
+    #[test]
+    fn test_two_cameras_detecting_one_worker_fuse_into_one_track() {
+        let scene_cameras = [
+            SceneCamera::looking_at("cam-a", [0.0, 3.0, 5.0], [4.0, 3.0], 1280, 720, 700.0),
+            SceneCamera::looking_at("cam-b", [12.0, 3.0, 5.0], [8.0, 3.0], 1280, 720, 700.0),
+        ];
+        let mut cameras: Vec<CameraConfig> = scene_cameras
+            .iter()
+            .map(|camera| CameraConfig { id: camera.id.clone(), calibration: Some(camera.calibration.clone()), ..CameraConfig::default() })
+            .collect();
+        cameras.push(CameraConfig { id: "uncalibrated".to_string(), calibration: None, ..CameraConfig::default() });
+
+        let observations = FloorObservations::new(&cameras);
+        let mut engine = FusionEngine::new(&ProcessingConfig::default());
+        let mut global_ids = HashSet::new();
+
+        for step in 0..10u64 {
+            let worker = WorldPosition { x: 5.5 + step as f32 * 0.1, y: 3.0 };
+            for camera in &scene_cameras {
+                observations.observe(&frame(camera, step, worker));
+            }
+
+            let result = engine.fuse(&observations.take(), step * 100);
+            assert_eq!(result.objects.len(), 1, "step {}", step);
+            let object = &result.objects[0];
+            assert_eq!(object.sources.len(), 2);
+            // Untracked detections keep one id per camera while they overlap
+            assert!(object.sources.iter().all(|source| source.tracker_id == 1));
+            assert!(object.position.distance(&worker) < 0.05, "{:?} for {:?}", object.position, worker);
+            global_ids.insert(object.global_track_id);
+        }
+
+        assert_eq!(global_ids.len(), 1);
+        // Nothing new arrived since the last fuse
+        assert!(observations.take().is_empty());
+    }
+
+    // Records the timestamp of every fusion result published
+    #[derive(Default)]
+    struct RecordingPublisher {
+        fusion_results: Mutex<Vec<u64>>,
+    }
+
+    #[async_trait::async_trait]
+    impl MessagePublisher for RecordingPublisher {
+        async fn publish_perception_frame(&self, _frame: &PerceptionFrame) -> Result<()> { Ok(()) }
+        async fn publish_fusion_result(&self, result: &FusionResult) -> Result<()> {
+            self.fusion_results.lock().unwrap().push(result.timestamp);
+            Ok(())
+        }
+        async fn publish_system_health(&self, _health: &SystemHealth) -> Result<()> { Ok(()) }
+        async fn publish_alert(&self, _alert: &SystemAlert) -> Result<()> { Ok(()) }
+        async fn publish_raw(&self, _envelope: &MessageEnvelope, _payload: &[u8]) -> Result<()> { Ok(()) }
+        async fn connect(&mut self) -> Result<()> { Ok(()) }
+        async fn disconnect(&mut self) -> Result<()> { Ok(()) }
+        fn is_connected(&self) -> bool { true }
+    }
+
+    #[tokio::test]
+    async fn test_fusion_loop_publishes_a_result_every_interval() {
+        let observations = Arc::new(FloorObservations::new(&[]));
+        let engine = FusionEngine::new(&ProcessingConfig::default());
+        let publisher = Arc::new(RecordingPublisher::default());
+
+        let task = tokio::spawn(run(observations, engine, AlertRuleEngine::new(&[]), None, publisher.clone(), Duration::from_millis(20)));
+        tokio::time::sleep(Duration::from_millis(110)).await;
+        task.abort();
+
+        // An empty floor is still published, every tick
+        let published = publisher.fusion_results.lock().unwrap();
+        assert!(published.len() >= 3, "{} results", published.len());
+        assert!(published.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
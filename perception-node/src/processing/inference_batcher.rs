@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Notify};
+use tracing::error;
+
+use crate::{
+    config::{CameraConfig, InferenceConfig},
+    error::{PerceptionError, Result},
+    inference::{BatchScheduler, InferenceStats},
+    scoring::BatchDetector,
+};
+use aetherforge_common::{CameraFrame, PerceptionFrame};
+
+type Reply = oneshot::Sender<Result<(CameraFrame, PerceptionFrame)>>;
+
+struct Pending {
+    scheduler: BatchScheduler,
+    replies: HashMap<(String, u64), Reply>, // by camera and sequence number
+}
+
+// Gathers frames from every camera's worker into batches for the detector.
+// Each worker waits for its frame's results, so there is at most one frame
+// per camera waiting: a batch goes once it is full, once every camera has a
+// frame in it, or once its oldest frame has waited `batch_timeout_ms`. When
+// more frames wait than fit, the scheduler picks by camera priority.
+pub struct InferenceBatcher {
+    pending: Mutex<Pending>,
+    arrived: Notify,
+    cameras: usize,
+    batch_timeout: Duration,
+    stats: Arc<InferenceStats>,
+}
+
+impl InferenceBatcher {
+    pub fn new(config: &InferenceConfig, cameras: &[CameraConfig], stats: Arc<InferenceStats>) -> Self {
+        let mut scheduler = BatchScheduler::new(config);
+        scheduler.set_camera_priorities(cameras);
+
+        Self {
+            pending: Mutex::new(Pending { scheduler, replies: HashMap::new() }),
+            arrived: Notify::new(),
+            cameras: cameras.iter().filter(|camera| camera.enabled).count().max(1),
+            batch_timeout: Duration::from_millis(config.batch_timeout_ms),
+            stats,
+        }
+    }
+
+    // The frame back with its detections, from whichever batch it went in
+    pub async fn detect(&self, frame: CameraFrame) -> Result<(CameraFrame, PerceptionFrame)> {
+        let (reply, result) = oneshot::channel();
+        {
+            let mut pending = self.pending.lock().unwrap();
+            pending.replies.insert((frame.camera_id.clone(), frame.sequence_num), reply);
+            pending.scheduler.push(frame, Instant::now());
+            self.stats.set_queue_depth(pending.scheduler.len());
+        }
+        self.arrived.notify_one();
+
+        result
+            .await
+            .map_err(|_| PerceptionError::InferenceError("Inference batcher stopped".to_string()))?
+    }
+
+    pub async fn run<D: BatchDetector + ?Sized>(self: Arc<Self>, detector: Arc<D>) {
+        loop {
+            let batch = self.next_batch().await;
+            let batch_size = batch.len();
+            let (frames, waiting): (Vec<_>, Vec<_>) = batch.into_iter().map(|(frame, enqueued, reply)| (frame, (enqueued, reply))).unzip();
+
+            let results = match detector.detect_batch(&frames).await {
+                Ok(results) if results.len() == frames.len() => results.into_iter().map(Ok).collect(),
+                Ok(results) => {
+                    let message = format!("Detector returned {} results for {} frames", results.len(), frames.len());
+                    error!("{}", message);
+                    frames.iter().map(|_| Err(message.clone())).collect::<Vec<_>>()
+                }
+                Err(e) => frames.iter().map(|_| Err(e.to_string())).collect(),
+            };
+
+            for ((frame, (enqueued, reply)), result) in frames.into_iter().zip(waiting).zip(results) {
+                let result = match result {
+                    Ok(result) => {
+                        self.stats.record_frame(&frame.camera_id, batch_size, enqueued.elapsed().as_secs_f32() * 1000.0);
+                        Ok((frame, result))
+                    }
+                    Err(message) => Err(PerceptionError::InferenceError(message)),
+                };
+                // The worker may have gone away; nothing to tell it then
+                let _ = reply.send(result);
+            }
+        }
+    }
+
+    async fn next_batch(&self) -> Vec<(CameraFrame, Instant, Reply)> {
+        loop {
+            let wait = {
+                let mut pending = self.pending.lock().unwrap();
+                match pending.scheduler.oldest() {
+                    Some(oldest) => {
+                        let waited = oldest.elapsed();
+                        if pending.scheduler.is_full() || pending.scheduler.len() >= self.cameras || waited >= self.batch_timeout {
+                            let batch = pending.scheduler.next_batch(Instant::now());
+                            self.stats.set_queue_depth(pending.scheduler.len());
+                            return batch
+                                .into_iter()
+                                .filter_map(|(frame, enqueued)| {
+                                    let reply = pending.replies.remove(&(frame.camera_id.clone(), frame.sequence_num))?;
+                                    Some((frame, enqueued, reply))
+                                })
+                                .collect();
+                        }
+                        Some(self.batch_timeout - waited)
+                    }
+                    None => None,
+                }
+            };
+
+            match wait {
+                Some(wait) => {
+                    let _ = tokio::time::timeout(wait, self.arrived.notified()).await;
+                }
+                None => self.arrived.notified().await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aetherforge_common::CoordinateSpace;
+    use async_trait::async_trait;
+
+    // Records the cameras in each batch
+    struct RecordingDetector {
+        batches: Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl BatchDetector for RecordingDetector {
+        async fn detect_batch(&self, frames: &[CameraFrame]) -> Result<Vec<PerceptionFrame>> {
+            self.batches.lock().unwrap().push(frames.iter().map(|frame| frame.camera_id.clone()).collect());
+            Ok(frames
+                .iter()
+                .map(|frame| PerceptionFrame {
+                    frame_id: frame.sequence_num,
+                    timestamp: frame.timestamp,
+                    source_camera_id: frame.camera_id.clone(),
+                    image_width: frame.width,
+                    image_height: frame.height,
+                    model_version: "stub".to_string(),
+                    inference_time_ms: 1.0,
+                    detections: Vec::new(),
+                    camera_intrinsics: None,
+                    camera_extrinsics: None,
+                    coordinate_space: CoordinateSpace::Pixels,
+                })
+                .collect())
+        }
+    }
+
+    fn frame(camera_id: &str, sequence_num: u64) -> CameraFrame {
+        CameraFrame {
+            camera_id: camera_id.to_string(),
+            data: vec![0; 12],
+            width: 2,
+            height: 2,
+            format: "RGB".to_string(),
+            timestamp: sequence_num * 40,
+            sequence_num,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_frames_from_several_cameras_share_a_batch_by_priority() {
+        let cameras: Vec<CameraConfig> = [("crossing", 5), ("dock", 1), ("storage", 0)]
+            .into_iter()
+            .map(|(id, priority)| CameraConfig { id: id.to_string(), priority, ..CameraConfig::default() })
+            .collect();
+        let config = InferenceConfig { max_batch_size: 2, batch_timeout_ms: 50, ..InferenceConfig::default() };
+        let stats = Arc::new(InferenceStats::new(config.max_batch_size));
+        let batcher = Arc::new(InferenceBatcher::new(&config, &cameras, stats.clone()));
+        let detector = Arc::new(RecordingDetector { batches: Mutex::new(Vec::new()) });
+
+        // All three cameras have a frame waiting before the batcher runs
+        let workers: Vec<_> = ["storage", "dock", "crossing"]
+            .into_iter()
+            .map(|camera_id| {
+                let batcher = batcher.clone();
+                tokio::spawn(async move { batcher.detect(frame(camera_id, 1)).await })
+            })
+            .collect();
+        tokio::task::yield_now().await;
+        tokio::spawn(batcher.clone().run(detector.clone()));
+
+        for worker in workers {
+            let (frame, result) = worker.await.unwrap().unwrap();
+            assert_eq!(result.source_camera_id, frame.camera_id);
+        }
+
+        // Not one inference per frame: the two highest-priority cameras
+        // together, then the one left over once the timeout passes
+        let batches = detector.batches.lock().unwrap().clone();
+        assert_eq!(batches[0], vec!["crossing", "dock"]);
+        assert_eq!(batches[1], vec!["storage"]);
+
+        let report = stats.report();
+        assert_eq!(report.cameras.len(), 3);
+        assert_eq!(report.cameras["crossing"].frames_processed, 1);
+        assert_eq!(report.cameras["crossing"].avg_batch_size, 2.0);
+        assert_eq!(report.queue_depth, 0);
+    }
+}
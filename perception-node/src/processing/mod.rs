@@ -0,0 +1,14 @@
+pub mod alert_rules;
+pub mod annotation_sampler;
+pub mod camera_warmup;
+pub mod detection_anomaly;
+pub mod frame_processor;
+pub mod fusion_engine;
+pub mod inference_batcher;
+#[cfg(test)]
+pub mod fusion_scene;
+pub mod fusion_stage;
+pub mod proximity;
+pub mod frame_quality;
+pub mod publish_throttle;
+pub mod zone_map;
\ No newline at end of file
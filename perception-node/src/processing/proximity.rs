@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::ProximityConfig;
+use aetherforge_common::{FusionResult, WorldPosition};
+
+pub const HUMAN_ROBOT_PROXIMITY: &str = "human_robot_proximity";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ProximitySeverity {
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProximityAlert {
+    pub alert_type: String,
+    pub severity: ProximitySeverity,
+    pub human_track_id: u64,
+    pub robot_track_id: u64,
+    pub distance_m: f32,
+    pub robot_speed_mps: f32,
+    pub timestamp: u64,
+}
+
+struct RobotMotion {
+    position: WorldPosition,
+    timestamp: u64,
+    speed_mps: f32,
+}
+
+// Flags humans near moving robots in fused results. A (human, robot) pair
+// alerts once when it enters the warning radius and again only if it
+// escalates to critical; it re-arms after leaving the radius.
+pub struct ProximityDetector {
+    config: ProximityConfig,
+    robots: HashMap<u64, RobotMotion>,
+    active_pairs: HashMap<(u64, u64), ProximitySeverity>,
+}
+
+impl ProximityDetector {
+    pub fn new(config: &ProximityConfig) -> Self {
+        Self {
+            config: config.clone(),
+            robots: HashMap::new(),
+            active_pairs: HashMap::new(),
+        }
+    }
+
+    pub fn update(&mut self, result: &FusionResult) -> Vec<ProximityAlert> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        self.update_robot_motion(result);
+
+        let humans: Vec<_> = result
+            .objects
+            .iter()
+            .filter(|o| o.class_label == self.config.human_class)
+            .collect();
+
+        let mut alerts = Vec::new();
+        let mut in_range = HashMap::new();
+
+        for (&robot_id, motion) in &self.robots {
+            if motion.timestamp != result.timestamp || motion.speed_mps < self.config.min_robot_speed_mps {
+                continue;
+            }
+
+            for human in &humans {
+                let distance = human.position.distance(&motion.position);
+                let severity = if distance <= self.config.critical_radius_m {
+                    ProximitySeverity::Critical
+                } else if distance <= self.config.warning_radius_m {
+                    ProximitySeverity::Warning
+                } else {
+                    continue;
+                };
+
+                let pair = (human.global_track_id, robot_id);
+                let previous = self.active_pairs.get(&pair).copied();
+                // Keep the highest severity raised so de-escalation doesn't re-alert
+                in_range.insert(pair, previous.map_or(severity, |p| p.max(severity)));
+
+                if !matches!(previous, Some(p) if p >= severity) {
+                    alerts.push(ProximityAlert {
+                        alert_type: HUMAN_ROBOT_PROXIMITY.to_string(),
+                        severity,
+                        human_track_id: human.global_track_id,
+                        robot_track_id: robot_id,
+                        distance_m: distance,
+                        robot_speed_mps: motion.speed_mps,
+                        timestamp: result.timestamp,
+                    });
+                }
+            }
+        }
+
+        self.active_pairs = in_range;
+        alerts
+    }
+
+    fn update_robot_motion(&mut self, result: &FusionResult) {
+        let mut seen = HashMap::new();
+
+        for robot in result.objects.iter().filter(|o| o.class_label == self.config.robot_class) {
+            let speed_mps = match self.robots.get(&robot.global_track_id) {
+                Some(previous) if result.timestamp > previous.timestamp => {
+                    let elapsed_s = (result.timestamp - previous.timestamp) as f32 / 1000.0;
+                    robot.position.distance(&previous.position) / elapsed_s
+                }
+                Some(previous) => previous.speed_mps,
+                None => 0.0,
+            };
+
+            seen.insert(robot.global_track_id, RobotMotion {
+                position: robot.position,
+                timestamp: result.timestamp,
+                speed_mps,
+            });
+        }
+
+        self.robots = seen;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aetherforge_common::FusedObject;
+
+    fn object(global_track_id: u64, label: &str, x: f32, y: f32) -> FusedObject {
+        FusedObject {
+            global_track_id,
+            class_label: label.to_string(),
+            raw_class_label: Some(label.to_string()),
+            position: WorldPosition { x, y },
+            confidence: 0.9,
+            raw_confidence: 0.9,
+            sources: Vec::new(),
+            zone_id: None,
+            coasting: false,
+        }
+    }
+
+    fn frame(timestamp: u64, objects: Vec<FusedObject>) -> FusionResult {
+        FusionResult {
+            timestamp,
+            objects,
+            fusion_confidence: 0.9,
+            raw_fusion_confidence: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_human_near_moving_robot_raises_critical_alert() {
+        let mut detector = ProximityDetector::new(&ProximityConfig::default());
+
+        // Robot drives at 1 m/s along x; the worker stands still
+        assert!(detector.update(&frame(0, vec![object(1, "robot", 0.0, 0.0), object(2, "person", 5.8, 0.0)])).is_empty());
+        let alerts = detector.update(&frame(1000, vec![object(1, "robot", 1.0, 0.0), object(2, "person", 1.8, 0.0)]));
+
+        assert_eq!(alerts.len(), 1);
+        let alert = &alerts[0];
+        assert_eq!(alert.alert_type, HUMAN_ROBOT_PROXIMITY);
+        assert_eq!(alert.severity, ProximitySeverity::Critical);
+        assert_eq!((alert.human_track_id, alert.robot_track_id), (2, 1));
+        assert!((alert.distance_m - 0.8).abs() < 1e-4);
+        assert!((alert.robot_speed_mps - 1.0).abs() < 1e-4);
+
+        // Same pair, still close: no repeat
+        let alerts = detector.update(&frame(1100, vec![object(1, "robot", 1.1, 0.0), object(2, "person", 1.8, 0.0)]));
+        assert!(alerts.is_empty());
+    }
+
+    #[test]
+    fn test_stationary_robot_and_far_humans_do_not_alert() {
+        let mut detector = ProximityDetector::new(&ProximityConfig::default());
+
+        detector.update(&frame(0, vec![object(1, "robot", 0.0, 0.0), object(2, "person", 0.5, 0.0)]));
+        let alerts = detector.update(&frame(1000, vec![object(1, "robot", 0.0, 0.0), object(2, "person", 0.5, 0.0)]));
+        assert!(alerts.is_empty());
+
+        detector.update(&frame(2000, vec![object(3, "robot", 0.0, 0.0), object(4, "person", 9.0, 0.0)]));
+        let alerts = detector.update(&frame(3000, vec![object(3, "robot", 1.0, 0.0), object(4, "person", 9.0, 0.0)]));
+        assert!(alerts.is_empty());
+    }
+
+    #[test]
+    fn test_pair_escalates_from_warning_to_critical() {
+        let mut detector = ProximityDetector::new(&ProximityConfig::default());
+
+        detector.update(&frame(0, vec![object(1, "robot", 0.0, 0.0), object(2, "person", 3.0, 0.0)]));
+        let warning = detector.update(&frame(1000, vec![object(1, "robot", 1.0, 0.0), object(2, "person", 2.5, 0.0)]));
+        let critical = detector.update(&frame(2000, vec![object(1, "robot", 2.0, 0.0), object(2, "person", 2.5, 0.0)]));
+
+        assert_eq!(warning[0].severity, ProximitySeverity::Warning);
+        assert_eq!(critical[0].severity, ProximitySeverity::Critical);
+    }
+}
\ No newline at end of file
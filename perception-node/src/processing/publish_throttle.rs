@@ -0,0 +1,100 @@
+use dashmap::DashMap;
+
+use crate::config::CameraConfig;
+use aetherforge_common::PerceptionFrame;
+
+struct CameraSchedule {
+    interval_ms: u64,
+    next_due_ms: Option<u64>,
+}
+
+// Caps how often each camera's perception frames are published. Tracking
+// runs on every frame; only the publish is skipped, and whichever frame is
+// current when a slot comes due goes out, so consumers always get the most
+// recent state. Cameras without `max_publish_fps` publish every frame.
+pub struct PublishThrottle {
+    cameras: DashMap<String, CameraSchedule>,
+}
+
+impl PublishThrottle {
+    pub fn new(cameras: &[CameraConfig]) -> Self {
+        let cameras = cameras
+            .iter()
+            .filter_map(|camera| {
+                let fps = camera.max_publish_fps.filter(|fps| *fps > 0.0)?;
+                let schedule = CameraSchedule {
+                    interval_ms: (1000.0 / fps).round().max(1.0) as u64,
+                    next_due_ms: None,
+                };
+                Some((camera.id.clone(), schedule))
+            })
+            .collect();
+
+        Self { cameras }
+    }
+
+    pub fn should_publish(&self, frame: &PerceptionFrame) -> bool {
+        let Some(mut schedule) = self.cameras.get_mut(&frame.source_camera_id) else {
+            return true;
+        };
+        let now = frame.timestamp;
+
+        match schedule.next_due_ms {
+            Some(due) if now < due => false,
+            // Keep to the slot grid so frame jitter doesn't lower the rate,
+            // but restart it after a gap rather than bursting to catch up
+            Some(due) if now - due < schedule.interval_ms => {
+                schedule.next_due_ms = Some(due + schedule.interval_ms);
+                true
+            }
+            _ => {
+                schedule.next_due_ms = Some(now + schedule.interval_ms);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aetherforge_common::CoordinateSpace;
+
+    fn frame(camera_id: &str, timestamp: u64) -> PerceptionFrame {
+        PerceptionFrame {
+            frame_id: 0,
+            timestamp,
+            source_camera_id: camera_id.to_string(),
+            image_width: 640,
+            image_height: 480,
+            model_version: "test".to_string(),
+            inference_time_ms: 1.0,
+            detections: Vec::new(),
+            camera_intrinsics: None,
+            camera_extrinsics: None,
+            coordinate_space: CoordinateSpace::Pixels,
+        }
+    }
+
+    #[test]
+    fn test_30fps_source_capped_at_5fps() {
+        let throttled = CameraConfig {
+            id: "busy".to_string(),
+            max_publish_fps: Some(5.0),
+            ..CameraConfig::default()
+        };
+        let unthrottled = CameraConfig {
+            id: "quiet".to_string(),
+            ..CameraConfig::default()
+        };
+        let throttle = PublishThrottle::new(&[throttled, unthrottled]);
+
+        // 10s at 30fps, with the usual integer-millisecond timestamps
+        let timestamps: Vec<u64> = (0..300).map(|i| 1_700_000_000_000 + i * 1000 / 30).collect();
+        let published = timestamps.iter().filter(|&&t| throttle.should_publish(&frame("busy", t))).count();
+        assert!((49..=51).contains(&published), "published {} frames in 10s", published);
+
+        let all = timestamps.iter().filter(|&&t| throttle.should_publish(&frame("quiet", t))).count();
+        assert_eq!(all, 300);
+    }
+}
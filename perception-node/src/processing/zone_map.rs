@@ -0,0 +1,101 @@
+use std::path::Path;
+
+use crate::config::ProcessingConfig;
+use crate::error::{PerceptionError, Result};
+use aetherforge_common::facility_map::FacilityMap;
+use aetherforge_common::world_model::SemanticCell;
+use aetherforge_common::WorldPosition;
+
+// The facility's semantic cells, for tagging fused objects with the cell
+// they're in so analytics can query per zone without redoing the geometry.
+// Where cells overlap the riskiest wins, then the smallest.
+#[derive(Debug, Clone)]
+pub struct ZoneMap {
+    cells: Vec<SemanticCell>,
+}
+
+impl ZoneMap {
+    pub fn new(mut cells: Vec<SemanticCell>) -> Self {
+        cells.sort_by(|a, b| {
+            b.risk_level
+                .cmp(&a.risk_level)
+                .then((a.size.width * a.size.height).total_cmp(&(b.size.width * b.size.height)))
+        });
+        Self { cells }
+    }
+
+    // A facility map JSON file, as exported by the operator platform
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        let map = FacilityMap::from_json(&json)
+            .map_err(|e| PerceptionError::ConfigError(format!("Bad facility map {}: {}", path.display(), e)))?;
+        Ok(Self::new(map.cells))
+    }
+
+    // None when no facility map is configured
+    pub fn from_config(config: &ProcessingConfig) -> Result<Option<Self>> {
+        config.facility_map_path.as_deref().map(Self::load).transpose()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn zone_at(&self, position: &WorldPosition) -> Option<&str> {
+        self.cells
+            .iter()
+            .find(|cell| cell.contains(position.x as f64, position.y as f64))
+            .map(|cell| cell.cell_id.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::fusion_engine::{CameraObservation, FusionEngine};
+    use aetherforge_common::world_model::{Position, Size};
+
+    fn cell(cell_id: &str, x: f64, y: f64, size: f64, risk_level: u8) -> SemanticCell {
+        SemanticCell {
+            cell_id: cell_id.to_string(),
+            position: Position { x, y, z: 0.0 },
+            size: Size { width: size, height: size },
+            r#type: "pathway".to_string(),
+            risk_level,
+        }
+    }
+
+    fn observation(tracker_id: u64, x: f32, y: f32) -> CameraObservation {
+        CameraObservation {
+            camera_id: "cam-1".to_string(),
+            tracker_id,
+            class_label: "person".to_string(),
+            confidence: 0.9,
+            position: WorldPosition { x, y },
+        }
+    }
+
+    #[test]
+    fn test_fused_detection_is_tagged_with_its_cell() {
+        // A 2x2 grid of 5m cells, plus a loading dock inside the first
+        let zones = ZoneMap::new(vec![
+            cell("CELL-0-0", 0.0, 0.0, 5.0, 1),
+            cell("CELL-0-1", 0.0, 5.0, 5.0, 1),
+            cell("CELL-1-0", 5.0, 0.0, 5.0, 1),
+            cell("CELL-1-1", 5.0, 5.0, 5.0, 1),
+            cell("DOCK-A", 1.0, 1.0, 2.0, 3),
+        ]);
+        let mut engine = FusionEngine::new(&ProcessingConfig::default()).with_zone_map(zones);
+
+        let result = engine.fuse(
+            &[observation(1, 7.5, 2.0), observation(2, 2.0, 2.0), observation(3, 5.0, 5.0), observation(4, 12.0, 1.0)],
+            1_700_000_000_000,
+        );
+        let zones: Vec<Option<&str>> = result.objects.iter().map(|o| o.zone_id.as_deref()).collect();
+        assert_eq!(zones, vec![Some("CELL-1-0"), Some("DOCK-A"), Some("CELL-1-1"), None]);
+    }
+}
@@ -0,0 +1,657 @@
+use async_trait::async_trait;
+use image::{ImageFormat, RgbImage};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::info;
+
+use crate::{
+    config::{InferenceConfig, PerceptionConfig},
+    error::{PerceptionError, Result},
+    inference::{InferencePool, OrtEngine},
+    utils::metrics::Metrics,
+};
+use aetherforge_common::{CameraFrame, Detection, PerceptionFrame};
+use aetherforge_common::replay::ReplayReader;
+
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "bmp", "rgb", "raw"];
+const OFFLINE_CAMERA_ID: &str = "offline";
+
+// Models an `ImageScorer` keeps loaded at once
+const MAX_LOADED_MODELS: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ScoreFormat {
+    Json, // per-image detections
+    Coco, // COCO images/annotations/categories
+}
+
+// Anything that can run detection on a batch of frames; the real node uses
+// the ORT engine, tests use a stub
+#[async_trait]
+pub trait BatchDetector {
+    async fn detect_batch(&self, frames: &[CameraFrame]) -> Result<Vec<PerceptionFrame>>;
+}
+
+#[async_trait]
+impl BatchDetector for OrtEngine {
+    async fn detect_batch(&self, frames: &[CameraFrame]) -> Result<Vec<PerceptionFrame>> {
+        OrtEngine::detect_batch(self, frames).await
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoredImage {
+    pub file: String,
+    pub width: u32,
+    pub height: u32,
+    pub detections: Vec<Detection>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoreReport {
+    pub model_version: String,
+    pub images_scored: usize,
+    pub elapsed_sec: f64,
+    pub images_per_sec: f64,
+    pub images: Vec<ScoredImage>,
+}
+
+// Scores every image in `dir` with the configured model and writes the
+// detections to `output`; the live camera pipeline is not started. Raw RGB
+// frames carry no size, so they can only be scored given `raw_frame_size`.
+pub async fn run(
+    config: &PerceptionConfig,
+    dir: &Path,
+    raw_frame_size: Option<(u32, u32)>,
+    output: &Path,
+    format: ScoreFormat,
+) -> Result<ScoreReport> {
+    let pool = Arc::new(InferencePool::new(&config.processing)?);
+    let engine = OrtEngine::new(&config.inference, pool, Arc::new(Metrics::new())).await?;
+    let report =
+        score_dir(&engine, dir, raw_frame_size, config.inference.max_batch_size, &config.inference.model_version).await?;
+
+    write_report(&report, &config.inference.class_names, output, format)?;
+    info!(
+        "Scored {} images in {:.1}s ({:.1} images/s), wrote {}",
+        report.images_scored,
+        report.elapsed_sec,
+        report.images_per_sec,
+        output.display()
+    );
+    Ok(report)
+}
+
+// Turns a replay file exported by the operator platform back into a score
+// report, so persisted history can go through the same outputs as --score-dir
+pub fn replay(input: &Path, class_names: &[String], output: &Path, format: ScoreFormat) -> Result<ScoreReport> {
+    let file = std::io::BufReader::new(std::fs::File::open(input)?);
+    let report = replay_frames(ReplayReader::new(file)?)?;
+
+    write_report(&report, class_names, output, format)?;
+    info!("Replayed {} frames from {}, wrote {}", report.images_scored, input.display(), output.display());
+    Ok(report)
+}
+
+fn replay_frames<R: std::io::Read>(reader: ReplayReader<R>) -> Result<ScoreReport> {
+    let camera_id = reader.header().camera_id.clone();
+    let started = Instant::now();
+    let mut model_version = String::new();
+    let mut images = Vec::new();
+
+    for frame in reader {
+        let frame = frame?;
+        if model_version.is_empty() {
+            model_version = frame.model_version.clone();
+        }
+        images.push(ScoredImage {
+            file: format!("{}/{}", camera_id, frame.frame_id),
+            width: frame.image_width,
+            height: frame.image_height,
+            detections: frame.detections,
+        });
+    }
+
+    let elapsed_sec = started.elapsed().as_secs_f64();
+    Ok(ScoreReport {
+        model_version,
+        images_scored: images.len(),
+        elapsed_sec,
+        images_per_sec: images.len() as f64 / elapsed_sec.max(f64::EPSILON),
+        images,
+    })
+}
+
+pub async fn score_dir(
+    detector: &impl BatchDetector,
+    dir: &Path,
+    raw_frame_size: Option<(u32, u32)>,
+    batch_size: usize,
+    model_version: &str,
+) -> Result<ScoreReport> {
+    let paths = list_images(dir)?;
+    let started = Instant::now();
+    let mut images = Vec::with_capacity(paths.len());
+
+    for batch in paths.chunks(batch_size.max(1)) {
+        let frames = batch
+            .iter()
+            .enumerate()
+            .map(|(i, path)| load_frame(path, (images.len() + i) as u64, raw_frame_size))
+            .collect::<Result<Vec<_>>>()?;
+
+        let results = detector.detect_batch(&frames).await?;
+        if results.len() != frames.len() {
+            return Err(PerceptionError::InferenceError(format!(
+                "Detector returned {} results for {} images",
+                results.len(),
+                frames.len()
+            )));
+        }
+
+        for (path, result) in batch.iter().zip(results) {
+            images.push(ScoredImage {
+                file: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                width: result.image_width,
+                height: result.image_height,
+                detections: result.detections,
+            });
+        }
+    }
+
+    let elapsed_sec = started.elapsed().as_secs_f64();
+    Ok(ScoreReport {
+        model_version: model_version.to_string(),
+        images_scored: images.len(),
+        elapsed_sec,
+        images_per_sec: images.len() as f64 / elapsed_sec.max(f64::EPSILON),
+        images,
+    })
+}
+
+// Scores single uploaded images on request, for the operator platform to
+// compare a model's labels with stored annotations. Requested models must be
+// in `models_dir` and are loaded on first use; the node's own model is the
+// default.
+pub struct ImageScorer {
+    inference: InferenceConfig,
+    models_dir: PathBuf,
+    pool: Arc<InferencePool>,
+    metrics: Arc<Metrics>,
+    engines: tokio::sync::Mutex<LoadedModels<ModelKey, OrtEngine>>,
+}
+
+// A loaded engine labels with the version and classes it was loaded with,
+// so those are part of what it's cached under
+#[derive(Debug, Clone, PartialEq)]
+struct ModelKey {
+    path: PathBuf,
+    version: String,
+    class_names: Vec<String>,
+}
+
+// Which model to score with; anything unset comes from the node's config
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+pub struct ScoreRequest {
+    pub model_path: Option<PathBuf>,
+    pub model_version: Option<String>,
+    pub classes: Option<String>, // comma-separated class names, in model output order
+}
+
+impl ImageScorer {
+    pub fn new(inference: &InferenceConfig, models_dir: &Path, pool: Arc<InferencePool>, metrics: Arc<Metrics>) -> Self {
+        Self {
+            inference: inference.clone(),
+            models_dir: models_dir.to_path_buf(),
+            pool,
+            metrics,
+            engines: tokio::sync::Mutex::new(LoadedModels::new(MAX_LOADED_MODELS)),
+        }
+    }
+
+    pub async fn score(&self, image: &[u8], request: ScoreRequest) -> Result<ScoreReport> {
+        let model_version = request.model_version.clone().unwrap_or_else(|| self.inference.model_version.clone());
+        let engine = self.engine(request).await?;
+        score_image(engine.as_ref(), image, &model_version).await
+    }
+
+    async fn engine(&self, request: ScoreRequest) -> Result<Arc<OrtEngine>> {
+        let key = self.model_key(request)?;
+        let mut engines = self.engines.lock().await;
+        if let Some(engine) = engines.get(&key) {
+            return Ok(engine);
+        }
+        if !key.path.is_file() {
+            return Err(PerceptionError::ConfigError(format!("No model at {}", key.path.display())));
+        }
+
+        let config = InferenceConfig {
+            model_path: key.path.clone(),
+            model_version: key.version.clone(),
+            class_names: key.class_names.clone(),
+            ..self.inference.clone()
+        };
+        let engine = Arc::new(OrtEngine::new(&config, self.pool.clone(), self.metrics.clone()).await?);
+        info!("Loaded {} ({}) for on-demand scoring", key.path.display(), key.version);
+
+        engines.insert(key, engine.clone());
+        Ok(engine)
+    }
+
+    fn model_key(&self, request: ScoreRequest) -> Result<ModelKey> {
+        Ok(ModelKey {
+            path: match &request.model_path {
+                Some(requested) => resolve_model_path(&self.models_dir, requested)?,
+                None => self.inference.model_path.clone(),
+            },
+            version: request.model_version.unwrap_or_else(|| self.inference.model_version.clone()),
+            class_names: request
+                .classes
+                .map(|classes| classes.split(',').map(|c| c.trim().to_string()).collect())
+                .unwrap_or_else(|| self.inference.class_names.clone()),
+        })
+    }
+}
+
+// Requested models must resolve, symlinks and `..` followed, to a file
+// inside `models_dir`; relative paths are taken from there
+fn resolve_model_path(models_dir: &Path, requested: &Path) -> Result<PathBuf> {
+    let rejected = || PerceptionError::ConfigError(format!("No model {} in {}", requested.display(), models_dir.display()));
+    let models_dir = models_dir.canonicalize().map_err(|_| rejected())?;
+    let path = models_dir.join(requested).canonicalize().map_err(|_| rejected())?;
+    if !path.starts_with(&models_dir) || !path.is_file() {
+        return Err(rejected());
+    }
+    Ok(path)
+}
+
+// Loaded engines by key, least recently used first. Inserting past
+// `capacity` drops the least recently used.
+struct LoadedModels<K, E> {
+    engines: VecDeque<(K, Arc<E>)>,
+    capacity: usize,
+}
+
+impl<K: PartialEq, E> LoadedModels<K, E> {
+    fn new(capacity: usize) -> Self {
+        Self { engines: VecDeque::new(), capacity: capacity.max(1) }
+    }
+
+    fn get(&mut self, key: &K) -> Option<Arc<E>> {
+        let position = self.engines.iter().position(|(loaded, _)| loaded == key)?;
+        let entry = self.engines.remove(position)?;
+        let engine = entry.1.clone();
+        self.engines.push_back(entry);
+        Some(engine)
+    }
+
+    fn insert(&mut self, key: K, engine: Arc<E>) {
+        while self.engines.len() >= self.capacity {
+            self.engines.pop_front();
+        }
+        self.engines.push_back((key, engine));
+    }
+}
+
+// Detections for one encoded image, in its own pixel coordinates
+pub async fn score_image(detector: &impl BatchDetector, image: &[u8], model_version: &str) -> Result<ScoreReport> {
+    let started = Instant::now();
+    let frame = decode_frame(image, 0, None)
+        .map_err(|e| PerceptionError::ProcessingError(format!("Failed to decode image: {}", e)))?;
+
+    let result = detector
+        .detect_batch(&[frame])
+        .await?
+        .pop()
+        .ok_or_else(|| PerceptionError::InferenceError("Detector returned no result".to_string()))?;
+
+    let elapsed_sec = started.elapsed().as_secs_f64();
+    Ok(ScoreReport {
+        model_version: model_version.to_string(),
+        images_scored: 1,
+        elapsed_sec,
+        images_per_sec: 1.0 / elapsed_sec.max(f64::EPSILON),
+        images: vec![ScoredImage {
+            file: "upload".to_string(),
+            width: result.image_width,
+            height: result.image_height,
+            detections: result.detections,
+        }],
+    })
+}
+
+pub(crate) fn list_images(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+pub(crate) fn load_frame(path: &Path, sequence_num: u64, raw_frame_size: Option<(u32, u32)>) -> Result<CameraFrame> {
+    let bytes = std::fs::read(path)?;
+    decode_frame(&bytes, sequence_num, raw_frame_size)
+        .map_err(|e| PerceptionError::ProcessingError(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+// Encoded images are recognized by their contents, not their file name, and
+// decoded to the RGB frames the live pipeline gets from GStreamer. Anything
+// that isn't a supported encoding must be a raw RGB frame of the given size.
+pub fn decode_frame(bytes: &[u8], sequence_num: u64, raw_frame_size: Option<(u32, u32)>) -> Result<CameraFrame> {
+    let image = match image::guess_format(bytes) {
+        Ok(format @ (ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::Bmp)) => image::load_from_memory_with_format(bytes, format)
+            .map_err(|e| PerceptionError::ProcessingError(format!("Corrupt {:?} image: {}", format, e)))?
+            .to_rgb8(),
+        Ok(format) => {
+            return Err(PerceptionError::ProcessingError(format!(
+                "Unsupported image format {:?}; expected JPEG, PNG, BMP or raw RGB",
+                format
+            )))
+        }
+        Err(_) => raw_frame(bytes, raw_frame_size)?,
+    };
+
+    Ok(camera_frame_from(image, sequence_num))
+}
+
+fn raw_frame(bytes: &[u8], raw_frame_size: Option<(u32, u32)>) -> Result<RgbImage> {
+    let Some((width, height)) = raw_frame_size else {
+        return Err(PerceptionError::ProcessingError(
+            "Unrecognized image format; raw RGB frames need a frame size".to_string(),
+        ));
+    };
+    if bytes.len() != width as usize * height as usize * 3 {
+        return Err(PerceptionError::ProcessingError(format!(
+            "Unrecognized image format, and {} bytes is not a {}x{} raw RGB frame",
+            bytes.len(),
+            width,
+            height
+        )));
+    }
+    Ok(RgbImage::from_raw(width, height, bytes.to_vec()).expect("length checked above"))
+}
+
+// `WIDTHxHEIGHT`, e.g. 1280x720
+pub fn parse_frame_size(size: &str) -> std::result::Result<(u32, u32), String> {
+    size.split_once('x')
+        .and_then(|(width, height)| Some((width.trim().parse().ok()?, height.trim().parse().ok()?)))
+        .filter(|&(width, height)| width > 0 && height > 0)
+        .ok_or_else(|| format!("Expected a frame size like 1280x720, got {:?}", size))
+}
+
+fn camera_frame_from(image: RgbImage, sequence_num: u64) -> CameraFrame {
+    CameraFrame {
+        camera_id: OFFLINE_CAMERA_ID.to_string(),
+        width: image.width(),
+        height: image.height(),
+        data: image.into_raw(),
+        format: "RGB".to_string(),
+        timestamp: 0,
+        sequence_num,
+    }
+}
+
+fn write_report(report: &ScoreReport, class_names: &[String], output: &Path, format: ScoreFormat) -> Result<()> {
+    let json = match format {
+        ScoreFormat::Json => serde_json::to_vec_pretty(report)?,
+        ScoreFormat::Coco => serde_json::to_vec_pretty(&to_coco(report, class_names))?,
+    };
+
+    std::fs::write(output, json)?;
+    Ok(())
+}
+
+// COCO detection layout: boxes are [x, y, width, height] in pixels
+pub fn to_coco(report: &ScoreReport, class_names: &[String]) -> serde_json::Value {
+    let images: Vec<_> = report
+        .images
+        .iter()
+        .enumerate()
+        .map(|(id, image)| serde_json::json!({ "id": id, "file_name": image.file, "width": image.width, "height": image.height }))
+        .collect();
+
+    let annotations: Vec<_> = report
+        .images
+        .iter()
+        .enumerate()
+        .flat_map(|(image_id, image)| image.detections.iter().map(move |d| (image_id, d)))
+        .enumerate()
+        .map(|(id, (image_id, d))| {
+            let b = &d.bbox;
+            serde_json::json!({
+                "id": id,
+                "image_id": image_id,
+                "category_id": d.class_id,
+                "bbox": [b.xmin, b.ymin, b.xmax - b.xmin, b.ymax - b.ymin],
+                "area": (b.xmax - b.xmin) * (b.ymax - b.ymin),
+                "score": d.confidence,
+            })
+        })
+        .collect();
+
+    let categories: Vec<_> = class_names
+        .iter()
+        .enumerate()
+        .map(|(id, name)| serde_json::json!({ "id": id, "name": name }))
+        .collect();
+
+    serde_json::json!({ "images": images, "annotations": annotations, "categories": categories })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aetherforge_common::{BBox, CoordinateSpace};
+    use image::{Rgb, RgbImage};
+
+    // Reports one "person" covering the middle of each frame
+    struct StubDetector;
+
+    #[async_trait]
+    impl BatchDetector for StubDetector {
+        async fn detect_batch(&self, frames: &[CameraFrame]) -> Result<Vec<PerceptionFrame>> {
+            Ok(frames
+                .iter()
+                .map(|frame| {
+                    let (w, h) = (frame.width as f32, frame.height as f32);
+                    PerceptionFrame {
+                        frame_id: frame.sequence_num,
+                        timestamp: frame.timestamp,
+                        source_camera_id: frame.camera_id.clone(),
+                        image_width: frame.width,
+                        image_height: frame.height,
+                        model_version: "stub".to_string(),
+                        inference_time_ms: 1.0,
+                        detections: vec![Detection {
+                            bbox: BBox::new(w / 4.0, h / 4.0, w * 3.0 / 4.0, h * 3.0 / 4.0),
+                            confidence: 0.9,
+                            class_id: 0,
+                            class_label: "person".to_string(),
+                            tracker_id: None,
+                            oriented: None,
+                        }],
+                        camera_intrinsics: None,
+                        camera_extrinsics: None,
+                        coordinate_space: CoordinateSpace::Pixels,
+                    }
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scores_fixture_images_to_json_and_coco() {
+        let dir = std::env::temp_dir().join(format!("aetherforge-score-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        RgbImage::from_pixel(64, 48, Rgb([200, 10, 10])).save(dir.join("a.png")).unwrap();
+        RgbImage::from_pixel(32, 32, Rgb([10, 200, 10])).save(dir.join("b.jpg")).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not an image").unwrap();
+
+        let report = score_dir(&StubDetector, &dir, None, 2, "stub").await.unwrap();
+
+        assert_eq!(report.images_scored, 2);
+        assert!(report.images_per_sec > 0.0);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["images"][0]["file"], "a.png");
+        assert_eq!(json["images"][0]["width"], 64);
+        assert_eq!(json["images"][0]["detections"][0]["class_label"], "person");
+        assert_eq!(json["images"][1]["file"], "b.jpg");
+
+        let output = dir.join("detections.json");
+        write_report(&report, &["person".to_string()], &output, ScoreFormat::Coco).unwrap();
+        let coco: serde_json::Value = serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+        assert_eq!(coco["images"].as_array().unwrap().len(), 2);
+        assert_eq!(coco["annotations"][0]["bbox"], serde_json::json!([16.0, 12.0, 32.0, 24.0]));
+        assert_eq!(coco["annotations"][1]["image_id"], 1);
+        assert_eq!(coco["categories"][0]["name"], "person");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_inputs_are_decoded_by_content_not_extension() {
+        let dir = std::env::temp_dir().join(format!("aetherforge-score-mixed-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // A PNG and a JPEG saved under each other's extension, and a raw frame
+        let mut png = std::io::Cursor::new(Vec::new());
+        RgbImage::from_pixel(64, 48, Rgb([200, 10, 10])).write_to(&mut png, image::ImageOutputFormat::Png).unwrap();
+        std::fs::write(dir.join("a.jpg"), png.get_ref()).unwrap();
+        let mut jpeg = std::io::Cursor::new(Vec::new());
+        RgbImage::from_pixel(40, 30, Rgb([10, 200, 10])).write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(90)).unwrap();
+        std::fs::write(dir.join("b.png"), jpeg.get_ref()).unwrap();
+        std::fs::write(dir.join("c.rgb"), vec![128; 16 * 8 * 3]).unwrap();
+
+        let report = score_dir(&StubDetector, &dir, Some((16, 8)), 4, "stub").await.unwrap();
+
+        let sizes: Vec<_> = report.images.iter().map(|i| (i.file.as_str(), i.width, i.height)).collect();
+        assert_eq!(sizes, vec![("a.jpg", 64, 48), ("b.png", 40, 30), ("c.rgb", 16, 8)]);
+        assert!(report.images.iter().all(|i| i.detections.len() == 1));
+
+        // Without a frame size the raw frame can't be read
+        let error = score_dir(&StubDetector, &dir, None, 4, "stub").await.unwrap_err().to_string();
+        assert!(error.contains("c.rgb") && error.contains("raw RGB frames need a frame size"), "{}", error);
+
+        // Neither an encoding we read nor a raw frame of the right size
+        std::fs::remove_file(dir.join("c.rgb")).unwrap();
+        let mut gif = std::io::Cursor::new(Vec::new());
+        RgbImage::from_pixel(8, 8, Rgb([0, 0, 0])).write_to(&mut gif, image::ImageOutputFormat::Gif).unwrap();
+        std::fs::write(dir.join("d.bmp"), gif.get_ref()).unwrap();
+        let error = score_dir(&StubDetector, &dir, Some((16, 8)), 4, "stub").await.unwrap_err().to_string();
+        assert!(error.contains("d.bmp") && error.contains("Unsupported image format Gif"), "{}", error);
+
+        assert_eq!(parse_frame_size("1280x720"), Ok((1280, 720)));
+        assert!(parse_frame_size("1280").is_err() && parse_frame_size("0x720").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_uploaded_image_is_scored_in_its_own_pixels() {
+        let mut jpeg = std::io::Cursor::new(Vec::new());
+        RgbImage::from_pixel(80, 60, Rgb([90, 90, 90])).write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(90)).unwrap();
+
+        let report = score_image(&StubDetector, jpeg.get_ref(), "candidate-2").await.unwrap();
+
+        assert_eq!(report.model_version, "candidate-2");
+        assert_eq!((report.images[0].width, report.images[0].height), (80, 60));
+        assert_eq!(report.images[0].detections[0].bbox, BBox::new(20.0, 15.0, 60.0, 45.0));
+        assert!(score_image(&StubDetector, b"not an image", "candidate-2").await.is_err());
+    }
+
+    #[test]
+    fn test_requested_models_must_be_inside_models_dir() {
+        let root = std::env::temp_dir().join(format!("aetherforge-score-models-{}", std::process::id()));
+        let models_dir = root.join("models");
+        std::fs::create_dir_all(&models_dir).unwrap();
+        std::fs::write(models_dir.join("candidate.onnx"), b"onnx").unwrap();
+        std::fs::write(root.join("outside.onnx"), b"onnx").unwrap();
+
+        let candidate = models_dir.join("candidate.onnx").canonicalize().unwrap();
+        assert_eq!(resolve_model_path(&models_dir, Path::new("candidate.onnx")).unwrap(), candidate);
+        assert_eq!(resolve_model_path(&models_dir, &models_dir.join("candidate.onnx")).unwrap(), candidate);
+
+        for requested in [root.join("outside.onnx"), PathBuf::from("../outside.onnx"), PathBuf::from("missing.onnx"), PathBuf::from(".")] {
+            let error = resolve_model_path(&models_dir, &requested).unwrap_err();
+            assert!(matches!(error, PerceptionError::ConfigError(_)), "{}: {}", requested.display(), error);
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_least_recently_used_model_is_evicted() {
+        let mut models = LoadedModels::new(2);
+        models.insert(PathBuf::from("a.onnx"), Arc::new('a'));
+        models.insert(PathBuf::from("b.onnx"), Arc::new('b'));
+
+        // Using a makes b the least recently used
+        assert_eq!(models.get(&PathBuf::from("a.onnx")).as_deref(), Some(&'a'));
+        models.insert(PathBuf::from("c.onnx"), Arc::new('c'));
+
+        assert!(models.get(&PathBuf::from("b.onnx")).is_none());
+        assert_eq!(models.get(&PathBuf::from("a.onnx")).as_deref(), Some(&'a'));
+        assert_eq!(models.get(&PathBuf::from("c.onnx")).as_deref(), Some(&'c'));
+    }
+
+    #[test]
+    fn test_same_model_with_other_classes_is_loaded_separately() {
+        let key = |version: &str, classes: &[&str]| ModelKey {
+            path: PathBuf::from("detector.onnx"),
+            version: version.to_string(),
+            class_names: classes.iter().map(|c| c.to_string()).collect(),
+        };
+        let mut models = LoadedModels::new(4);
+        models.insert(key("1.0", &["person"]), Arc::new('a'));
+
+        assert!(models.get(&key("1.0", &["person", "forklift"])).is_none());
+        assert!(models.get(&key("1.1", &["person"])).is_none());
+        assert_eq!(models.get(&key("1.0", &["person"])).as_deref(), Some(&'a'));
+    }
+
+    #[tokio::test]
+    async fn test_replay_file_becomes_score_report() {
+        use aetherforge_common::replay::{encode_frame, encode_header, ReplayHeader, REPLAY_SCHEMA_VERSION};
+
+        let frames = StubDetector
+            .detect_batch(&[camera_frame(0), camera_frame(1)])
+            .await
+            .unwrap();
+        let mut bytes = encode_header(&ReplayHeader {
+            schema_version: REPLAY_SCHEMA_VERSION,
+            camera_id: "dock-1".to_string(),
+            from_ms: 0,
+            to_ms: 1000,
+        })
+        .unwrap();
+        for frame in &frames {
+            bytes.extend(encode_frame(frame).unwrap());
+        }
+
+        let report = replay_frames(ReplayReader::new(bytes.as_slice()).unwrap()).unwrap();
+
+        assert_eq!(report.images_scored, 2);
+        assert_eq!(report.model_version, "stub");
+        assert_eq!(report.images[1].file, "dock-1/1");
+        assert_eq!(report.images[1].detections, frames[1].detections);
+    }
+
+    fn camera_frame(sequence_num: u64) -> CameraFrame {
+        CameraFrame {
+            camera_id: "dock-1".to_string(),
+            width: 64,
+            height: 48,
+            data: vec![0; 64 * 48 * 3],
+            format: "RGB".to_string(),
+            timestamp: sequence_num * 33,
+            sequence_num,
+        }
+    }
+}
\ No newline at end of file
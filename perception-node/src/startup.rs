@@ -0,0 +1,104 @@
+use std::future::Future;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::{
+    config::StartupConfig,
+    error::{PerceptionError, Result},
+};
+
+// Retries `check` until it succeeds or the configured timeout runs out.
+// The timeout error names the dependency and carries the last failure.
+pub async fn wait_for<F, Fut>(name: &str, config: &StartupConfig, mut check: F) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let timeout = Duration::from_millis(config.dependency_timeout_ms);
+    let interval = Duration::from_millis(config.retry_interval_ms.max(1));
+    let started = Instant::now();
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        let error = match check().await {
+            Ok(()) => {
+                if attempts > 1 {
+                    info!("{} available after {} attempts", name, attempts);
+                }
+                return Ok(());
+            }
+            Err(e) => e,
+        };
+
+        if started.elapsed() + interval > timeout {
+            return Err(PerceptionError::Timeout(format!(
+                "{} not available after {:.1}s ({} attempts), last error: {}",
+                name,
+                started.elapsed().as_secs_f64(),
+                attempts,
+                error
+            )));
+        }
+
+        warn!("Waiting for {} (attempt {}): {}", name, attempts, error);
+        tokio::time::sleep(interval).await;
+    }
+}
+
+// A model on a network mount may show up after the node starts
+pub async fn model_file_present(path: &Path) -> Result<()> {
+    match tokio::fs::metadata(path).await {
+        Ok(meta) if meta.is_file() => Ok(()),
+        Ok(_) => Err(PerceptionError::ConfigError(format!("{} is not a file", path.display()))),
+        Err(e) => Err(PerceptionError::IoError(std::io::Error::new(
+            e.kind(),
+            format!("model {}: {}", path.display(), e),
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn config(timeout_ms: u64) -> StartupConfig {
+        StartupConfig {
+            dependency_timeout_ms: timeout_ms,
+            retry_interval_ms: 10,
+            ..StartupConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_retries_until_available() {
+        let calls = AtomicU32::new(0);
+        let result = wait_for("broker", &config(1000), || async {
+            if calls.fetch_add(1, Ordering::SeqCst) < 3 {
+                Err(PerceptionError::MessagingError("connection refused".into()))
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_times_out_with_last_error() {
+        let err = wait_for("model file", &config(50), || async {
+            model_file_present(Path::new("does/not/exist.onnx")).await
+        })
+        .await
+        .unwrap_err();
+
+        let message = err.to_string();
+        assert!(matches!(err, PerceptionError::Timeout(_)));
+        assert!(message.contains("model file not available"), "{}", message);
+        assert!(message.contains("does/not/exist.onnx"), "{}", message);
+    }
+}
@@ -0,0 +1,43 @@
+use std::cell::Cell;
+use tracing::{debug, warn};
+
+thread_local! {
+    static PINNED: Cell<bool> = const { Cell::new(false) };
+}
+
+// Pins the calling thread to `cores[index % cores.len()]`. An empty list
+// means no pinning; returns whether the thread was pinned.
+pub fn pin_current_thread(cores: &[usize], index: usize) -> bool {
+    if cores.is_empty() {
+        return false;
+    }
+    let core = cores[index % cores.len()];
+
+    let pinned = core_affinity::get_core_ids()
+        .and_then(|ids| ids.into_iter().find(|id| id.id == core))
+        .is_some_and(core_affinity::set_for_current);
+    if pinned {
+        debug!("Pinned {:?} to core {}", std::thread::current().name(), core);
+    } else {
+        warn!("Could not pin {:?} to core {}", std::thread::current().name(), core);
+    }
+    pinned
+}
+
+// For callbacks on threads we don't create, such as GStreamer streaming
+// threads: pins on the first call from each thread only
+pub fn pin_current_thread_once(cores: &[usize]) {
+    if !cores.is_empty() && !PINNED.with(|pinned| pinned.replace(true)) {
+        pin_current_thread(cores, 0);
+    }
+}
+
+// Cores available to a set of threads: the pinned set if there is one,
+// otherwise every core
+pub fn available_cores(cores: &[usize]) -> usize {
+    if cores.is_empty() {
+        num_cpus::get()
+    } else {
+        cores.len()
+    }
+}
@@ -0,0 +1,453 @@
+use axum::{extract::State, routing::get, Router};
+use prometheus::{
+    Encoder, GaugeVec, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::MonitoringConfig,
+    error::{PerceptionError, Result},
+    inference::shadow::Divergence,
+};
+
+const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Per-class detection rates are recomputed over windows this long
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+// Detections per class since `started`; rolled into the rate gauge
+struct RateWindow {
+    started: Instant,
+    counts: HashMap<String, u64>,
+}
+
+// Node metrics in a private registry, exposed either by the scrape server or
+// by pushing to a Prometheus pushgateway
+pub struct Metrics {
+    registry: Registry,
+    started_at: Instant,
+    total_frames: AtomicU64,
+    inference_latency_ms: Histogram,
+    frames_processed: IntCounterVec,
+    camera_fps: GaugeVec,
+    camera_up: IntGaugeVec,
+    capture_dropped_frames: IntCounterVec,
+    queue_overflow_frames: IntCounterVec,
+    camera_queue_depth: IntGaugeVec,
+    detections: IntCounterVec,
+    detection_rate: GaugeVec,
+    detection_cap_hits: IntCounterVec,
+    rate_window: Mutex<RateWindow>,
+    messages_sent: IntCounter,
+    message_bytes: IntCounter,
+    message_failures: IntCounter,
+    publisher_dropped_messages: IntCounterVec,
+    shadow_frames: IntCounter,
+    shadow_unmatched: IntCounterVec,
+    shadow_box_iou: Histogram,
+    shadow_class_agreement: Histogram,
+    shadow_confidence_delta: Histogram,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let inference_latency_ms = Histogram::with_opts(
+            HistogramOpts::new("aetherforge_inference_latency_ms", "Model inference latency per batch")
+                .buckets(vec![1.0, 2.5, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0]),
+        )
+        .unwrap();
+        let frames_processed = IntCounterVec::new(
+            Opts::new("aetherforge_frames_processed_total", "Frames run through inference"),
+            &["camera_id"],
+        )
+        .unwrap();
+        let camera_fps = GaugeVec::new(Opts::new("aetherforge_camera_fps", "Observed camera frame rate"), &["camera_id"]).unwrap();
+        let camera_up = IntGaugeVec::new(Opts::new("aetherforge_camera_up", "1 if the camera is streaming"), &["camera_id"]).unwrap();
+        // Frame loss, one counter per stage it can happen at
+        let capture_dropped_frames = IntCounterVec::new(
+            Opts::new("aetherforge_capture_dropped_frames_total", "Frames lost between the camera and the processing queue"),
+            &["camera_id"],
+        )
+        .unwrap();
+        let queue_overflow_frames = IntCounterVec::new(
+            Opts::new("aetherforge_queue_overflow_frames_total", "Frames dropped from a full processing queue"),
+            &["camera_id"],
+        )
+        .unwrap();
+        let camera_queue_depth = IntGaugeVec::new(
+            Opts::new("aetherforge_camera_queue_depth", "Frames waiting in the camera's processing queue"),
+            &["camera_id"],
+        )
+        .unwrap();
+        let detections = IntCounterVec::new(
+            Opts::new("aetherforge_detections_total", "Detections kept after postprocessing"),
+            &["camera_id", "class"],
+        )
+        .unwrap();
+        let detection_rate = GaugeVec::new(
+            Opts::new("aetherforge_detections_per_minute", "Detections per minute over the last full minute"),
+            &["class"],
+        )
+        .unwrap();
+        let detection_cap_hits = IntCounterVec::new(
+            Opts::new("aetherforge_detection_cap_hits_total", "Frames with more candidates than max_detections_per_frame"),
+            &["camera_id"],
+        )
+        .unwrap();
+        let messages_sent = IntCounter::new("aetherforge_messages_sent_total", "Perception messages published").unwrap();
+        let message_bytes = IntCounter::new("aetherforge_message_bytes_total", "Bytes of perception messages published").unwrap();
+        let message_failures = IntCounter::new("aetherforge_message_failures_total", "Failed publish attempts").unwrap();
+        let publisher_dropped_messages = IntCounterVec::new(
+            Opts::new(
+                "aetherforge_publisher_dropped_messages_total",
+                "Perception frames dropped while held for publishing: stale (older than the message TTL) or queue_full",
+            ),
+            &["reason"],
+        )
+        .unwrap();
+
+        // Shadow model evaluation, see inference::shadow
+        let shadow_frames = IntCounter::new("aetherforge_shadow_frames_total", "Frames also run through the shadow model").unwrap();
+        let shadow_unmatched = IntCounterVec::new(
+            Opts::new("aetherforge_shadow_unmatched_total", "Detections without a counterpart from the other model"),
+            &["side"],
+        )
+        .unwrap();
+        let ratio_buckets = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 0.95, 1.0];
+        let shadow_box_iou = Histogram::with_opts(
+            HistogramOpts::new("aetherforge_shadow_box_iou", "Mean IoU of matched primary and shadow boxes per frame")
+                .buckets(ratio_buckets.clone()),
+        )
+        .unwrap();
+        let shadow_class_agreement = Histogram::with_opts(
+            HistogramOpts::new("aetherforge_shadow_class_agreement", "Fraction of matched boxes given the same class per frame")
+                .buckets(ratio_buckets),
+        )
+        .unwrap();
+        let shadow_confidence_delta = Histogram::with_opts(
+            HistogramOpts::new("aetherforge_shadow_confidence_delta", "Mean shadow minus primary confidence of matched boxes per frame")
+                .buckets(vec![-0.5, -0.2, -0.1, -0.05, 0.0, 0.05, 0.1, 0.2, 0.5]),
+        )
+        .unwrap();
+
+        let registry = Registry::new();
+        registry.register(Box::new(inference_latency_ms.clone())).unwrap();
+        registry.register(Box::new(frames_processed.clone())).unwrap();
+        registry.register(Box::new(camera_fps.clone())).unwrap();
+        registry.register(Box::new(camera_up.clone())).unwrap();
+        registry.register(Box::new(capture_dropped_frames.clone())).unwrap();
+        registry.register(Box::new(queue_overflow_frames.clone())).unwrap();
+        registry.register(Box::new(camera_queue_depth.clone())).unwrap();
+        registry.register(Box::new(detections.clone())).unwrap();
+        registry.register(Box::new(detection_rate.clone())).unwrap();
+        registry.register(Box::new(detection_cap_hits.clone())).unwrap();
+        registry.register(Box::new(messages_sent.clone())).unwrap();
+        registry.register(Box::new(message_bytes.clone())).unwrap();
+        registry.register(Box::new(message_failures.clone())).unwrap();
+        registry.register(Box::new(publisher_dropped_messages.clone())).unwrap();
+        registry.register(Box::new(shadow_frames.clone())).unwrap();
+        registry.register(Box::new(shadow_unmatched.clone())).unwrap();
+        registry.register(Box::new(shadow_box_iou.clone())).unwrap();
+        registry.register(Box::new(shadow_class_agreement.clone())).unwrap();
+        registry.register(Box::new(shadow_confidence_delta.clone())).unwrap();
+
+        Self {
+            registry,
+            started_at: Instant::now(),
+            total_frames: AtomicU64::new(0),
+            inference_latency_ms,
+            frames_processed,
+            camera_fps,
+            camera_up,
+            capture_dropped_frames,
+            queue_overflow_frames,
+            camera_queue_depth,
+            detections,
+            detection_rate,
+            detection_cap_hits,
+            rate_window: Mutex::new(RateWindow { started: Instant::now(), counts: HashMap::new() }),
+            messages_sent,
+            message_bytes,
+            message_failures,
+            publisher_dropped_messages,
+            shadow_frames,
+            shadow_unmatched,
+            shadow_box_iou,
+            shadow_class_agreement,
+            shadow_confidence_delta,
+        }
+    }
+
+    pub fn record_inference(&self, latency: Duration) {
+        self.inference_latency_ms.observe(latency.as_secs_f64() * 1000.0);
+    }
+
+    pub fn record_frame(&self, camera_id: &str) {
+        self.frames_processed.with_label_values(&[camera_id]).inc();
+        self.total_frames.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn set_camera_fps(&self, camera_id: &str, fps: f64) {
+        self.camera_fps.with_label_values(&[camera_id]).set(fps);
+    }
+
+    pub fn set_camera_health(&self, camera_id: &str, streaming: bool) {
+        self.camera_up.with_label_values(&[camera_id]).set(streaming as i64);
+    }
+
+    pub fn increment_capture_dropped_frames(&self, camera_id: &str, count: u64) {
+        self.capture_dropped_frames.with_label_values(&[camera_id]).inc_by(count);
+    }
+
+    pub fn increment_queue_overflow(&self, camera_id: &str) {
+        self.queue_overflow_frames.with_label_values(&[camera_id]).inc();
+    }
+
+    pub fn set_camera_queue_depth(&self, camera_id: &str, depth: usize) {
+        self.camera_queue_depth.with_label_values(&[camera_id]).set(depth as i64);
+    }
+
+    pub fn increment_detection_cap_hits(&self, camera_id: &str) {
+        self.detection_cap_hits.with_label_values(&[camera_id]).inc();
+    }
+
+    // One frame's detections after NMS and size filtering
+    pub fn record_detections<'a>(&self, camera_id: &str, classes: impl IntoIterator<Item = &'a str>) {
+        let mut window = self.rate_window.lock().unwrap();
+        for class in classes {
+            self.detections.with_label_values(&[camera_id, class]).inc();
+            *window.counts.entry(class.to_string()).or_insert(0) += 1;
+        }
+        drop(window);
+        self.roll_detection_rates(Instant::now());
+    }
+
+    // Publishes the finished window's rates. Classes seen before but not in
+    // this window drop to zero, so a camera going quiet shows up as a drop
+    // rather than a stale value.
+    fn roll_detection_rates(&self, now: Instant) {
+        let mut window = self.rate_window.lock().unwrap();
+        let elapsed = now.saturating_duration_since(window.started);
+        if elapsed < RATE_WINDOW {
+            return;
+        }
+
+        let per_minute = 60.0 / elapsed.as_secs_f64();
+        for (class, count) in window.counts.iter_mut() {
+            self.detection_rate.with_label_values(&[class]).set(*count as f64 * per_minute);
+            *count = 0;
+        }
+        window.started = now;
+    }
+
+    pub fn record_message_sent(&self, bytes: usize, _elapsed: Duration) {
+        self.messages_sent.inc();
+        self.message_bytes.inc_by(bytes as u64);
+    }
+
+    pub fn increment_message_failures(&self) {
+        self.message_failures.inc();
+    }
+
+    pub fn increment_stale_messages(&self) {
+        self.publisher_dropped_messages.with_label_values(&["stale"]).inc();
+    }
+
+    pub fn increment_publisher_overflow(&self) {
+        self.publisher_dropped_messages.with_label_values(&["queue_full"]).inc();
+    }
+
+    // The means only exist when some boxes matched
+    pub fn record_shadow(&self, divergence: &Divergence) {
+        self.shadow_frames.inc();
+        self.shadow_unmatched.with_label_values(&["primary"]).inc_by(divergence.primary_only as u64);
+        self.shadow_unmatched.with_label_values(&["shadow"]).inc_by(divergence.shadow_only as u64);
+        if divergence.matched > 0 {
+            self.shadow_box_iou.observe(f64::from(divergence.mean_iou));
+            self.shadow_class_agreement.observe(f64::from(divergence.class_agreement));
+            self.shadow_confidence_delta.observe(f64::from(divergence.mean_confidence_delta));
+        }
+    }
+
+    pub fn get_average_latency(&self) -> f32 {
+        let count = self.inference_latency_ms.get_sample_count();
+        if count == 0 {
+            return 0.0;
+        }
+        (self.inference_latency_ms.get_sample_sum() / count as f64) as f32
+    }
+
+    // Frames per second across all cameras since startup
+    pub fn get_throughput(&self) -> f32 {
+        let frames = self.total_frames.load(Ordering::Relaxed);
+        frames as f32 / self.started_at.elapsed().as_secs_f32().max(f32::EPSILON)
+    }
+
+    // Prometheus text exposition format
+    pub fn encode(&self) -> String {
+        self.roll_detection_rates(Instant::now());
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding into a Vec cannot fail");
+        String::from_utf8(buffer).expect("text encoding is always UTF-8")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub async fn start_metrics_server(addr: String, metrics: Arc<Metrics>) -> Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(|State(metrics): State<Arc<Metrics>>| async move { metrics.encode() }))
+        .with_state(metrics);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    info!("Metrics server listening on {}", addr);
+
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+// Pushes to `{url}/metrics/job/{job}/instance/{instance}` every interval, for
+// edge nodes a central Prometheus can't reach to scrape. A failed push is
+// logged and retried on the next tick.
+pub async fn run_pushgateway(metrics: Arc<Metrics>, config: &MonitoringConfig, instance: &str) -> Result<()> {
+    let url = config
+        .pushgateway_url
+        .as_deref()
+        .ok_or_else(|| PerceptionError::ConfigError("pushgateway_url is required in Push mode".to_string()))?;
+    let push_url = format!(
+        "{}/metrics/job/{}/instance/{}",
+        url.trim_end_matches('/'),
+        config.pushgateway_job,
+        instance
+    );
+    let client = reqwest::Client::builder()
+        .timeout(PUSH_TIMEOUT)
+        .build()
+        .map_err(|e| PerceptionError::ConfigError(e.to_string()))?;
+
+    info!("Pushing metrics to {} every {}s", push_url, config.push_interval_sec);
+    let mut interval = tokio::time::interval(Duration::from_secs(config.push_interval_sec.max(1)));
+
+    loop {
+        interval.tick().await;
+        match push_metrics(&client, &push_url, &metrics).await {
+            Ok(()) => debug!("Pushed metrics to {}", push_url),
+            Err(e) => warn!("Metrics push failed: {}", e),
+        }
+    }
+}
+
+async fn push_metrics(client: &reqwest::Client, push_url: &str, metrics: &Metrics) -> Result<()> {
+    let response = client
+        .put(push_url)
+        .header(reqwest::header::CONTENT_TYPE, TextEncoder::new().format_type())
+        .body(metrics.encode())
+        .send()
+        .await
+        .map_err(|e| PerceptionError::MessagingError(e.to_string()))?;
+
+    if !response.status().is_success() {
+        return Err(PerceptionError::MessagingError(format!("pushgateway returned {}", response.status())));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MetricsMode;
+    use axum::{extract::Path, http::StatusCode, routing::put};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_pushgateway_receives_node_metrics_on_interval() {
+        // Mock pushgateway recording the path and body of each push
+        let (tx, mut rx) = mpsc::unbounded_channel::<(String, String)>();
+        let app = Router::new().route(
+            "/metrics/job/:job/instance/:instance",
+            put(move |Path((job, instance)): Path<(String, String)>, body: String| {
+                let tx = tx.clone();
+                async move {
+                    tx.send((format!("{}/{}", job, instance), body)).unwrap();
+                    StatusCode::OK
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let metrics = Arc::new(Metrics::new());
+        metrics.record_inference(Duration::from_millis(12));
+        metrics.record_frame("cam-1");
+        metrics.set_camera_fps("cam-1", 29.5);
+        metrics.set_camera_health("cam-1", true);
+        metrics.increment_queue_overflow("cam-1");
+
+        let mut config = MonitoringConfig::default();
+        config.metrics_mode = MetricsMode::Push;
+        config.pushgateway_url = Some(format!("http://{}/", addr));
+        config.push_interval_sec = 1;
+
+        let pusher = tokio::spawn({
+            let metrics = metrics.clone();
+            async move { run_pushgateway(metrics, &config, "edge-7").await }
+        });
+
+        let started = Instant::now();
+        for _ in 0..2 {
+            let (path, body) = tokio::time::timeout(Duration::from_secs(3), rx.recv()).await.unwrap().unwrap();
+            assert_eq!(path, "aetherforge-perception/edge-7");
+            for name in [
+                "aetherforge_inference_latency_ms",
+                "aetherforge_camera_fps",
+                "aetherforge_camera_up",
+                "aetherforge_queue_overflow_frames_total",
+            ] {
+                assert!(body.contains(name), "push is missing {}", name);
+            }
+        }
+        // First tick fires immediately, the second one interval later
+        assert!(started.elapsed() >= Duration::from_millis(900));
+
+        pusher.abort();
+    }
+
+    #[test]
+    fn test_detections_are_counted_per_class() {
+        let metrics = Metrics::new();
+
+        // Two frames from one camera and one from another
+        metrics.record_detections("cam-1", ["person", "person", "forklift"]);
+        metrics.record_detections("cam-1", ["person"]);
+        metrics.record_detections("cam-2", ["person", "pallet"]);
+
+        let count = |camera: &str, class: &str| metrics.detections.with_label_values(&[camera, class]).get();
+        assert_eq!(count("cam-1", "person"), 3);
+        assert_eq!(count("cam-1", "forklift"), 1);
+        assert_eq!(count("cam-2", "person"), 1);
+        assert_eq!(count("cam-2", "pallet"), 1);
+        assert!(metrics.encode().contains(r#"aetherforge_detections_total{camera_id="cam-1",class="forklift"} 1"#));
+
+        // A full minute later: 4 people and one each of the others per minute
+        let started = metrics.rate_window.lock().unwrap().started;
+        metrics.roll_detection_rates(started + RATE_WINDOW);
+        let rate = |class: &str| metrics.detection_rate.with_label_values(&[class]).get();
+        assert_eq!(rate("person"), 4.0);
+        assert_eq!(rate("forklift"), 1.0);
+
+        // A quiet minute drops every class to zero
+        metrics.roll_detection_rates(started + RATE_WINDOW * 2);
+        assert_eq!(rate("person"), 0.0);
+    }
+}
\ No newline at end of file
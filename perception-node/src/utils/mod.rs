@@ -0,0 +1,3 @@
+pub mod affinity;
+pub mod metrics;
+pub mod overlay;
@@ -0,0 +1,187 @@
+use dashmap::DashMap;
+use image::{codecs::jpeg::JpegEncoder, Rgb, RgbImage};
+use imageproc::{
+    drawing::{draw_filled_rect_mut, draw_hollow_rect_mut, draw_line_segment_mut, draw_text_mut},
+    rect::Rect,
+};
+use rusttype::{Font, Scale};
+use tracing::warn;
+
+use crate::{
+    config::DebugOverlayConfig,
+    error::{PerceptionError, Result},
+};
+use aetherforge_common::{CameraFrame, Detection, OrientedBBox};
+
+const BOX_THICKNESS: u32 = 2;
+const BAR_HEIGHT: u32 = 4;
+const LABEL_SCALE: f32 = 14.0;
+
+// Confidence on a blue (low) to red (high) heat ramp
+fn heat_color(confidence: f32) -> Rgb<u8> {
+    let c = confidence.clamp(0.0, 1.0);
+    Rgb([(255.0 * c) as u8, (255.0 * (1.0 - (2.0 * c - 1.0).abs())) as u8, (255.0 * (1.0 - c)) as u8])
+}
+
+// Draws each detection's box in its confidence color, a bar above it whose
+// length is the confidence, and "label 0.87" when a font is available.
+// Oriented detections are outlined along their rotated box. Boxes are
+// expected in frame pixels.
+pub fn render_overlay(frame: &CameraFrame, detections: &[Detection], font: Option<&Font<'_>>) -> Result<RgbImage> {
+    let mut image = RgbImage::from_raw(frame.width, frame.height, frame.data.clone())
+        .ok_or_else(|| PerceptionError::ProcessingError(format!("Frame from {} is not {}x{} RGB", frame.camera_id, frame.width, frame.height)))?;
+
+    for detection in detections {
+        let b = &detection.bbox;
+        let (x, y) = (b.xmin.max(0.0) as i32, b.ymin.max(0.0) as i32);
+        let (width, height) = ((b.xmax - b.xmin).max(1.0) as u32, (b.ymax - b.ymin).max(1.0) as u32);
+        let color = heat_color(detection.confidence);
+
+        for t in 0..BOX_THICKNESS {
+            match &detection.oriented {
+                Some(oriented) => {
+                    let inset = OrientedBBox {
+                        width: (oriented.width - 2.0 * t as f32).max(1.0),
+                        height: (oriented.height - 2.0 * t as f32).max(1.0),
+                        ..*oriented
+                    };
+                    let corners = inset.corners();
+                    for (i, start) in corners.iter().enumerate() {
+                        draw_line_segment_mut(&mut image, *start, corners[(i + 1) % corners.len()], color);
+                    }
+                }
+                None => {
+                    let (w, h) = (width.saturating_sub(2 * t).max(1), height.saturating_sub(2 * t).max(1));
+                    draw_hollow_rect_mut(&mut image, Rect::at(x + t as i32, y + t as i32).of_size(w, h), color);
+                }
+            }
+        }
+
+        let bar_width = ((width as f32 * detection.confidence.clamp(0.0, 1.0)) as u32).max(1);
+        let bar_y = (y - BAR_HEIGHT as i32).max(0);
+        draw_filled_rect_mut(&mut image, Rect::at(x, bar_y).of_size(bar_width, BAR_HEIGHT), color);
+
+        if let Some(font) = font {
+            let label = format!("{} {:.2}", detection.class_label, detection.confidence);
+            let label_y = (bar_y - LABEL_SCALE as i32).max(0);
+            draw_text_mut(&mut image, color, x, label_y, Scale::uniform(LABEL_SCALE), font, &label);
+        }
+    }
+
+    Ok(image)
+}
+
+// Keeps the latest annotated JPEG per camera for the control API
+pub struct DebugOverlay {
+    config: DebugOverlayConfig,
+    font: Option<Font<'static>>,
+    frame_counts: DashMap<String, u64>,
+    latest: DashMap<String, Vec<u8>>,
+}
+
+impl DebugOverlay {
+    pub fn new(config: &DebugOverlayConfig) -> Self {
+        let font = config.font_path.as_ref().and_then(|path| {
+            let font = std::fs::read(path).ok().and_then(Font::try_from_vec);
+            if font.is_none() {
+                warn!("Could not load overlay font {}, drawing without labels", path.display());
+            }
+            font
+        });
+
+        Self {
+            config: config.clone(),
+            font,
+            frame_counts: DashMap::new(),
+            latest: DashMap::new(),
+        }
+    }
+
+    // Renders every `every_n_frames`th frame of each camera
+    pub fn observe(&self, frame: &CameraFrame, detections: &[Detection]) -> Result<()> {
+        let count = {
+            let mut count = self.frame_counts.entry(frame.camera_id.clone()).or_insert(0);
+            *count += 1;
+            *count
+        };
+        if (count - 1) % self.config.every_n_frames.max(1) != 0 {
+            return Ok(());
+        }
+
+        let image = render_overlay(frame, detections, self.font.as_ref())?;
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg, self.config.jpeg_quality.clamp(1, 100))
+            .encode_image(&image)
+            .map_err(|e| PerceptionError::ProcessingError(format!("JPEG encoding failed: {}", e)))?;
+
+        self.latest.insert(frame.camera_id.clone(), jpeg);
+        Ok(())
+    }
+
+    pub fn latest_jpeg(&self, camera_id: &str) -> Option<Vec<u8>> {
+        self.latest.get(camera_id).map(|jpeg| jpeg.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aetherforge_common::BBox;
+
+    fn gray_frame(width: u32, height: u32) -> CameraFrame {
+        CameraFrame {
+            camera_id: "cam-1".to_string(),
+            data: vec![128; (width * height * 3) as usize],
+            width,
+            height,
+            format: "RGB".to_string(),
+            timestamp: 0,
+            sequence_num: 0,
+        }
+    }
+
+    fn detection(xmin: f32, ymin: f32, xmax: f32, ymax: f32) -> Detection {
+        Detection {
+            bbox: BBox::new(xmin, ymin, xmax, ymax),
+            confidence: 0.9,
+            class_id: 0,
+            class_label: "person".to_string(),
+            tracker_id: None,
+            oriented: None,
+        }
+    }
+
+    #[test]
+    fn test_rendered_box_changes_only_the_box_region() {
+        let frame = gray_frame(100, 80);
+        let input = RgbImage::from_raw(100, 80, frame.data.clone()).unwrap();
+
+        let output = render_overlay(&frame, &[detection(20.0, 30.0, 60.0, 70.0)], None).unwrap();
+
+        // Box edges are drawn
+        for (x, y) in [(20, 30), (59, 30), (20, 69), (40, 30), (20, 50), (59, 50), (40, 69)] {
+            assert_ne!(output.get_pixel(x, y), input.get_pixel(x, y), "edge pixel ({}, {}) unchanged", x, y);
+        }
+        // Confidence bar sits just above the box
+        assert_ne!(output.get_pixel(25, 27), input.get_pixel(25, 27));
+        // Interior and far-away pixels are untouched
+        assert_eq!(output.get_pixel(40, 50), input.get_pixel(40, 50));
+        assert_eq!(output.get_pixel(90, 5), input.get_pixel(90, 5));
+    }
+
+    #[test]
+    fn test_overlay_keeps_latest_jpeg_for_sampled_frames() {
+        let overlay = DebugOverlay::new(&DebugOverlayConfig {
+            enabled: true,
+            every_n_frames: 2,
+            ..DebugOverlayConfig::default()
+        });
+
+        assert!(overlay.latest_jpeg("cam-1").is_none());
+        overlay.observe(&gray_frame(64, 48), &[detection(8.0, 8.0, 32.0, 32.0)]).unwrap();
+
+        let jpeg = overlay.latest_jpeg("cam-1").unwrap();
+        assert_eq!(&jpeg[..2], &[0xFF, 0xD8]);
+        assert!(overlay.latest_jpeg("cam-2").is_none());
+    }
+}
\ No newline at end of file
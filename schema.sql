@@ -272,4 +272,194 @@ CREATE INDEX idx_camera_calibrations_camera_id ON camera_calibrations(camera_id)
 CREATE INDEX idx_camera_health_metrics_camera_id ON camera_health_metrics(camera_id);
 CREATE INDEX idx_camera_health_metrics_timestamp ON camera_health_metrics(timestamp);
 CREATE INDEX idx_camera_status_history_camera_id ON camera_status_history(camera_id);
-CREATE INDEX idx_camera_status_history_timestamp ON camera_status_history(timestamp);
\ No newline at end of file
+CREATE INDEX idx_camera_status_history_timestamp ON camera_status_history(timestamp);
+
+-- Create detections table
+CREATE TABLE detections (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    camera_id UUID NOT NULL REFERENCES cameras(id) ON DELETE CASCADE,
+    frame_id BIGINT NOT NULL,
+    tracker_id BIGINT,
+    class_id INTEGER NOT NULL,
+    class_label TEXT NOT NULL,
+    confidence FLOAT NOT NULL,
+    xmin FLOAT NOT NULL,
+    ymin FLOAT NOT NULL,
+    xmax FLOAT NOT NULL,
+    ymax FLOAT NOT NULL,
+    model_version TEXT NOT NULL,
+    detected_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes
+CREATE INDEX idx_detections_camera_id ON detections(camera_id);
+CREATE INDEX idx_detections_detected_at ON detections(detected_at);
+CREATE INDEX idx_detections_class_label ON detections(class_label);
+
+
+-- Create user zone access table
+CREATE TABLE user_zone_access (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    zone TEXT NOT NULL,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, zone)
+);
+
+
+-- Create datasets table
+CREATE TABLE datasets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create dataset membership table
+CREATE TABLE dataset_annotations (
+    dataset_id UUID NOT NULL REFERENCES datasets(id) ON DELETE CASCADE,
+    annotation_id UUID NOT NULL REFERENCES annotations(id) ON DELETE CASCADE,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (dataset_id, annotation_id)
+);
+
+-- Create dataset versions table (immutable snapshots)
+CREATE TABLE dataset_versions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    dataset_id UUID NOT NULL REFERENCES datasets(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    content_hash TEXT NOT NULL,
+    annotation_count INTEGER NOT NULL,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (dataset_id, version)
+);
+
+-- Frozen annotation content; no FK so snapshots outlive deleted annotations
+CREATE TABLE dataset_version_annotations (
+    version_id UUID NOT NULL REFERENCES dataset_versions(id) ON DELETE CASCADE,
+    annotation_id UUID NOT NULL,
+    image_path TEXT NOT NULL,
+    annotations JSONB NOT NULL,
+    content_hash TEXT NOT NULL,
+    PRIMARY KEY (version_id, annotation_id)
+);
+
+ALTER TABLE training_jobs ADD COLUMN dataset_version_id UUID REFERENCES dataset_versions(id);
+
+-- Create indexes
+CREATE INDEX idx_dataset_annotations_annotation_id ON dataset_annotations(annotation_id);
+CREATE INDEX idx_dataset_versions_dataset_id ON dataset_versions(dataset_id);
+
+-- Create dataset split assignment table
+CREATE TYPE dataset_split AS ENUM ('train', 'val', 'test');
+
+CREATE TABLE dataset_splits (
+    dataset_id UUID NOT NULL REFERENCES datasets(id) ON DELETE CASCADE,
+    image_path TEXT NOT NULL,
+    split dataset_split NOT NULL,
+    seed BIGINT NOT NULL,
+    assigned_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (dataset_id, image_path)
+);
+-- Create perception node registry with the config operators push to each node
+CREATE TYPE node_config_status AS ENUM ('pending', 'applied', 'rejected', 'invalid');
+
+CREATE TABLE perception_nodes (
+    node_id TEXT PRIMARY KEY,
+    desired_config JSONB,
+    config_version BIGINT NOT NULL DEFAULT 0,
+    applied_version BIGINT,
+    config_status node_config_status NOT NULL DEFAULT 'pending',
+    status_message TEXT,
+    restart_required TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Track perception node liveness from their heartbeats
+CREATE TYPE node_status AS ENUM ('unknown', 'healthy', 'degraded', 'unhealthy', 'stale');
+
+ALTER TABLE perception_nodes ADD COLUMN status node_status NOT NULL DEFAULT 'unknown';
+ALTER TABLE perception_nodes ADD COLUMN health JSONB;
+ALTER TABLE perception_nodes ADD COLUMN last_seen_at TIMESTAMPTZ;
+
+-- Create camera-to-node assignments; the primary key keeps each camera on at most one node
+CREATE TABLE camera_assignments (
+    camera_id UUID PRIMARY KEY REFERENCES cameras(id) ON DELETE CASCADE,
+    node_id TEXT NOT NULL REFERENCES perception_nodes(node_id) ON DELETE CASCADE,
+    assigned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_camera_assignments_node_id ON camera_assignments(node_id);
+
+-- Record cross-camera track handoffs reported by perception nodes, for tuning fusion association
+CREATE TABLE track_handoffs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    node_id TEXT NOT NULL,
+    global_track_id BIGINT NOT NULL,
+    from_camera TEXT NOT NULL,
+    to_camera TEXT NOT NULL,
+    overlap_confidence FLOAT NOT NULL,
+    handed_off_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_track_handoffs_handed_off_at ON track_handoffs(handed_off_at);
+
+-- Let perception nodes submit sampled frames for labeling; those annotations have no human author
+ALTER TABLE annotations ALTER COLUMN created_by DROP NOT NULL;
+ALTER TABLE annotations ADD COLUMN source_node_id TEXT;
+
+-- Persist the fused facility world model periodically, for DVR-style playback of the scene
+CREATE TABLE world_model_snapshots (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    zone_id TEXT NOT NULL,
+    captured_at TIMESTAMPTZ NOT NULL,
+    object_count INTEGER NOT NULL,
+    fusion_confidence FLOAT NOT NULL,
+    snapshot JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_world_model_snapshots_captured_at ON world_model_snapshots(captured_at);
+
+-- ONVIF device service of cameras that support PTZ/exposure control from the dashboard
+ALTER TABLE cameras ADD COLUMN onvif_url TEXT;
+
+-- Reprojection error the running calibration must reach, set when it's started
+ALTER TABLE cameras ADD COLUMN calibration_target_accuracy REAL;
+
+-- Route raised alerts to per-zone webhook endpoints; routes without a zone are the fallback for zones with none of their own
+CREATE TABLE alert_routes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    zone TEXT,
+    alert_types TEXT[] NOT NULL DEFAULT '{}',
+    endpoint_url TEXT NOT NULL,
+    recipients TEXT[] NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_alert_routes_zone ON alert_routes(zone);
+
+-- Uploaded facility maps (cells, zones, camera placements); the newest per facility is current
+CREATE TABLE facility_maps (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    facility_id TEXT NOT NULL,
+    map JSONB NOT NULL,
+    uploaded_by UUID REFERENCES users(id),
+    uploaded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_facility_maps_facility_uploaded ON facility_maps(facility_id, uploaded_at DESC);
+
+-- Per-user dashboard preferences (default zone, saved event filters, notification settings) as one JSON object
+CREATE TABLE user_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    preferences JSONB NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use crate::{WarehouseSimulator, WorldModel};
+
+// Topic frame sent ahead of every serialized world model
+pub const WORLD_MODEL_TOPIC: &str = "world_model";
+
+// Publishes world models as two-part ZMQ messages: the topic, then the
+// model as JSON, the same encoding the file output uses.
+pub struct ZmqStreamer {
+    socket: zmq::Socket,
+}
+
+impl ZmqStreamer {
+    pub fn bind(context: &zmq::Context, endpoint: &str) -> zmq::Result<Self> {
+        let socket = context.socket(zmq::PUB)?;
+        socket.bind(endpoint)?;
+        Ok(Self { socket })
+    }
+
+    pub fn publish(&self, world_model: &WorldModel) -> zmq::Result<()> {
+        let payload = serde_json::to_vec(world_model).expect("world model is always serializable");
+        self.socket.send(WORLD_MODEL_TOPIC, zmq::SNDMORE)?;
+        self.socket.send(payload, 0)
+    }
+}
+
+impl WarehouseSimulator {
+    pub async fn run_zmq_stream(&mut self, streamer: &ZmqStreamer, steps: u64, interval: Duration) -> zmq::Result<()> {
+        println!("Streaming {} world models over ZMQ...", steps);
+
+        for _ in 0..steps {
+            let world_model = self.step();
+            streamer.publish(&world_model)?;
+
+            tokio::time::sleep(interval).await;
+        }
+
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,16 @@
+use aetherforge_common::facility_map::FacilityMap;
+use aetherforge_simulator::WarehouseSimulator;
+
+#[test]
+fn test_exported_facility_map_is_valid_and_loads_back() {
+    let exported = WarehouseSimulator::new((120, 100)).facility_map();
+    let map = FacilityMap::from_json(&exported.to_json()).unwrap();
+    assert_eq!(map.cells.len(), 24 * 20);
+
+    let mut simulator = WarehouseSimulator::new((120, 100)).with_facility_map(map.clone());
+    let world_model = simulator.step();
+
+    assert_eq!(simulator.facility_map(), map);
+    assert_eq!(world_model.semantic_map.len(), map.cells.len());
+    assert_eq!(world_model.semantic_map[7].cell_id, map.cells[7].cell_id);
+}
@@ -0,0 +1,32 @@
+use aetherforge_common::types::{FusedObject, FusionResult, TrackSource, WorldPosition};
+use aetherforge_common::world_model::WorldModelAdapter;
+use aetherforge_simulator::WorldModel;
+
+#[test]
+fn test_perception_world_model_parses_as_simulator_world_model() {
+    let result = FusionResult {
+        timestamp: 1_700_000_000_000,
+        objects: vec![FusedObject {
+            global_track_id: 42,
+            class_label: "robot".to_string(),
+            raw_class_label: Some("robot".to_string()),
+            position: WorldPosition { x: 10.0, y: 20.0 },
+            confidence: 0.95,
+            raw_confidence: 0.95,
+            sources: vec![TrackSource { camera_id: "CAM-01".to_string(), tracker_id: 1 }],
+            zone_id: None,
+            coasting: false,
+        }],
+        fusion_confidence: 0.95,
+        raw_fusion_confidence: 0.95,
+    };
+
+    let json = serde_json::to_string(&WorldModelAdapter::new("MAIN_WAREHOUSE").convert(&result)).unwrap();
+    let world_model: WorldModel = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(world_model.zone_id, "MAIN_WAREHOUSE");
+    assert_eq!(world_model.detections.len(), 1);
+    assert_eq!(world_model.detections[0].r#type, "robot");
+    assert_eq!(world_model.detections[0].source_cameras, vec!["CAM-01".to_string()]);
+    assert_eq!(world_model.active_cameras, vec!["CAM-01".to_string()]);
+}
\ No newline at end of file
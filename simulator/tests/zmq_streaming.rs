@@ -0,0 +1,49 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use aetherforge_simulator::streaming::{ZmqStreamer, WORLD_MODEL_TOPIC};
+use aetherforge_simulator::{WarehouseSimulator, WorldModel};
+
+const STEPS: u64 = 10;
+
+#[tokio::test]
+async fn test_simulator_world_models_reach_zmq_subscriber() {
+    let context = zmq::Context::new();
+    let streamer = ZmqStreamer::bind(&context, "inproc://simulator-world-models").unwrap();
+
+    let subscriber = context.socket(zmq::SUB).unwrap();
+    subscriber.connect("inproc://simulator-world-models").unwrap();
+    subscriber.set_subscribe(WORLD_MODEL_TOPIC.as_bytes()).unwrap();
+    subscriber.set_rcvtimeo(2000).unwrap();
+
+    // Let the subscription reach the publisher before anything is sent
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut simulator = WarehouseSimulator::new((120, 100));
+    let robot_ids: HashSet<String> = simulator.robot_ids().into_iter().collect();
+
+    simulator.run_zmq_stream(&streamer, STEPS, Duration::ZERO).await.unwrap();
+
+    let mut seen_robots = HashSet::new();
+    for _ in 0..STEPS {
+        let parts = subscriber.recv_multipart(0).expect("world model not received");
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0], WORLD_MODEL_TOPIC.as_bytes());
+
+        let world_model: WorldModel = serde_json::from_slice(&parts[1]).unwrap();
+        assert_eq!(world_model.zone_id, "MAIN_WAREHOUSE");
+        assert!(!world_model.detections.is_empty());
+
+        for detection in world_model.detections.iter().filter(|d| d.r#type == "robot") {
+            assert!(robot_ids.contains(&detection.id), "unknown robot id {}", detection.id);
+            seen_robots.insert(detection.id.clone());
+        }
+    }
+
+    // Each robot is detected with p=0.9 per step, so all show up over ten steps
+    assert_eq!(seen_robots, robot_ids);
+
+    // Nothing beyond the requested number of steps
+    subscriber.set_rcvtimeo(100).unwrap();
+    assert!(subscriber.recv_multipart(0).is_err());
+}
\ No newline at end of file